@@ -0,0 +1,194 @@
+//! the synthetic documents benchmarked, generated the same way every time so results can be compared across runs and machines
+
+#![allow(dead_code, reason = "each benchmark uses some of these")]
+
+use markdoll::{ext, MarkDoll};
+
+/// a xorshift generator, seeded the same every time
+struct Rng(u64);
+
+impl Rng {
+	fn new() -> Self {
+		Self(0x9e37_79b9_7f4a_7c15)
+	}
+
+	/// a number below `below`
+	fn below(&mut self, below: usize) -> usize {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		(self.0 % below as u64) as usize
+	}
+
+	fn pick<'a>(&mut self, from: &[&'a str]) -> &'a str {
+		from[self.below(from.len())]
+	}
+}
+
+const WORDS: &[&str] = &[
+	"the",
+	"a",
+	"doll",
+	"markup",
+	"language",
+	"is",
+	"structured",
+	"and",
+	"extensible",
+	"with",
+	"tags",
+	"sections",
+	"lists",
+	"text",
+	"of",
+	"every",
+	"kind",
+	"written",
+	"by",
+	"hand",
+	"parsed",
+	"into",
+	"trees",
+	"emitted",
+	"as",
+	"html",
+	"quickly",
+	"without",
+	"surprises",
+	"über",
+	"naïve",
+	"café",
+	"日本語",
+];
+
+/// a sentence of 6 to 20 words, capitalized and ending in a period
+fn sentence(rng: &mut Rng, out: &mut String) {
+	let len = 6 + rng.below(15);
+
+	for i in 0..len {
+		let word = rng.pick(WORDS);
+
+		if i == 0 {
+			let mut chars = word.chars();
+			out.extend(chars.next().map(char::to_uppercase).into_iter().flatten());
+			out.push_str(chars.as_str());
+		} else {
+			out.push(' ');
+			out.push_str(word);
+		}
+	}
+
+	out.push('.');
+}
+
+/// prose of at least `bytes` bytes, sections of paragraphs and lists with the occasional tag
+pub fn prose(bytes: usize) -> String {
+	let mut rng = Rng::new();
+	let mut out = String::with_capacity(bytes + 1024);
+
+	let mut section = 0;
+	while out.len() < bytes {
+		section += 1;
+		out.push_str(&format!("&section {section}\n"));
+
+		for _ in 0..3 + rng.below(6) {
+			out.push('\t');
+
+			if rng.below(4) == 0 {
+				// a list
+				for i in 0..2 + rng.below(4) {
+					if i > 0 {
+						out.push_str("\n\t");
+					}
+					out.push_str("-\t");
+					sentence(&mut rng, &mut out);
+				}
+			} else {
+				for i in 0..2 + rng.below(5) {
+					if i > 0 {
+						out.push(' ');
+					}
+					sentence(&mut rng, &mut out);
+
+					if rng.below(5) == 0 {
+						out.push_str(" see [link(https://example.com/");
+						out.push_str(&section.to_string());
+						out.push_str("):[em:");
+						out.push_str(rng.pick(WORDS));
+						out.push_str("]].");
+					}
+				}
+			}
+
+			out.push_str("\n\n");
+		}
+	}
+
+	out
+}
+
+/// `count` inline tags, nested and with arguments, in paragraphs of a few lines
+pub fn tags(count: usize) -> String {
+	let mut rng = Rng::new();
+	let mut out = String::with_capacity(count * 32);
+
+	for i in 0..count {
+		match rng.below(5) {
+			0 => out.push_str("[em:word]"),
+			1 => out.push_str("[em(b):bold [code:code]]"),
+			2 => out.push_str("[link(https://example.com):a [em:link]]"),
+			3 => out.push_str("[code:let x = 1;]"),
+			_ => out.push_str("[em(i):[em(b):nested]]"),
+		}
+
+		out.push_str(match i % 40 {
+			39 => "\n\n",
+			i if i % 8 == 7 => "\n",
+			_ => " ",
+		});
+	}
+
+	out
+}
+
+/// `count` warnings, five to a section, nested inside block tags so each is resolved through them
+pub fn warnings(count: usize) -> String {
+	let mut src = String::new();
+
+	for i in 0..count.div_ceil(5) {
+		src.push_str(&format!("&{i}\n\t[em::\n\t\t[em::\n"));
+
+		for _ in 0..5 {
+			src.push_str("\t\t\t  warn\n\n");
+		}
+
+		src.push_str("\t\t]\n\t]\n\n");
+	}
+
+	src
+}
+
+/// an instance with the standard tags
+pub fn doll() -> MarkDoll {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::citation::tags());
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::conditional::tags());
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.ext_system.add_tags(ext::code::tags());
+	doll.ext_system.add_tags(ext::datetime::tags());
+	doll.ext_system.add_tags(ext::diagram::tags());
+	doll.ext_system.add_tags(ext::emoji::tags());
+	doll.ext_system.add_tags(ext::glossary::tags());
+	doll.ext_system.add_tags(ext::index::tags());
+	doll.ext_system.add_tags(ext::layout::tags());
+	doll.ext_system.add_tags(ext::links::tags());
+	doll.ext_system.add_tags(ext::math::tags());
+	doll.ext_system.add_tags(ext::media::tags());
+	doll.ext_system.add_tags(ext::page::tags());
+	doll.ext_system.add_tags(ext::table::tags());
+	doll.ext_system.add_tags(ext::tabs::tags());
+	doll.ext_system.add_tags(ext::todo::tags());
+	doll.ext_system.add_tags(ext::var::tags());
+	doll
+}
@@ -0,0 +1,41 @@
+mod corpus;
+
+use {
+	criterion::{criterion_group, criterion_main, Criterion},
+	markdoll::{diagnostics::LineIndex, ext, MarkDoll},
+	std::hint::black_box,
+};
+
+fn diagnostics(c: &mut Criterion) {
+	// resolving each through the block tags it is nested in
+	let src = corpus::warnings(10_000);
+
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::formatting::tags());
+
+	c.bench_function("parse with warnings", |b| {
+		b.iter(|| {
+			let _ = black_box(doll.parse_document(&*src));
+			black_box(doll.finish())
+		});
+	});
+
+	let _ = doll.parse_document(&*src);
+	let diagnostics = doll.finish();
+
+	c.bench_function("structure warnings", |b| {
+		b.iter(|| {
+			let lines = LineIndex::new(&src);
+
+			black_box(
+				diagnostics
+					.iter()
+					.map(|diag| diag.to_structured(&lines))
+					.collect::<Vec<_>>(),
+			)
+		});
+	});
+}
+
+criterion_group!(benches, diagnostics);
+criterion_main!(benches);
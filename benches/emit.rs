@@ -0,0 +1,40 @@
+mod corpus;
+
+use {
+	criterion::{criterion_group, criterion_main, Criterion},
+	markdoll::emit::{BuiltInEmitters, HtmlEmit},
+	std::hint::black_box,
+};
+
+/// emit each document to html, parsed once, as emitting the same tree again gives the same output
+fn emit(c: &mut Criterion) {
+	let mut doll = corpus::doll();
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	for (name, src) in [
+		("emit spec", include_str!("../spec.doll").to_string()),
+		("emit 5 MB of prose", corpus::prose(5_000_000)),
+		("emit 10k inline tags", corpus::tags(10_000)),
+	] {
+		let (_, mut ast) = doll.parse_document(src).unwrap();
+
+		let mut to = HtmlEmit {
+			safe_urls: false,
+			max_output_bytes: usize::MAX,
+			..HtmlEmit::untrusted_profile()
+		};
+		assert!(doll.emit(&mut ast, &mut to));
+
+		c.bench_function(name, |b| {
+			b.iter(|| {
+				to.write.clear();
+				black_box(doll.emit(&mut ast, &mut to))
+			});
+		});
+
+		doll.finish();
+	}
+}
+
+criterion_group!(benches, emit);
+criterion_main!(benches);
@@ -0,0 +1,128 @@
+mod corpus;
+
+use {
+	criterion::{criterion_group, criterion_main, Criterion},
+	markdoll::{ext, MarkDoll},
+	std::{hint::black_box, sync::Arc},
+};
+
+fn parse(c: &mut Criterion) {
+	let src = Arc::<str>::from(include_str!("../spec.doll"));
+
+	let mut doll = corpus::doll();
+
+	// every reference beyond this one and the doll's is a tag whose content didn't need a buffer of its own
+	let ast = doll.parse_document(Arc::clone(&src));
+	println!(
+		"spec.doll: {} tags share the source buffer",
+		Arc::strong_count(&src) - 2
+	);
+	drop(ast);
+	doll.finish();
+
+	c.bench_function("parse spec", |b| {
+		b.iter(|| {
+			let _ = black_box(doll.parse_document(Arc::clone(&src)));
+			black_box(doll.finish())
+		});
+	});
+}
+
+/// a single 500 KB line of minified json in a codeblock, and as a paragraph
+fn long_line(c: &mut Criterion) {
+	let line = "{\"a\":[1,2,3],\"b\":\"x\"}".repeat(500_000 / 22);
+	let codeblock = format!("[codeblock(json)::\n\t{line}\n]");
+
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::code::tags());
+
+	c.bench_function("parse long line in codeblock", |b| {
+		b.iter(|| {
+			let _ = black_box(doll.parse_document(&*codeblock));
+			black_box(doll.finish())
+		});
+	});
+
+	c.bench_function("parse long line", |b| {
+		b.iter(|| {
+			let _ = black_box(doll.parse_document(&*line));
+			black_box(doll.finish())
+		});
+	});
+}
+
+/// a 5 MB codeblock of many short lines
+fn large_codeblock(c: &mut Criterion) {
+	let line = "\tlet value = compute(\"some input\", 42);\n";
+	let codeblock = format!("[codeblock(rs)::\n{}]", line.repeat(5_000_000 / line.len()));
+
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::code::tags());
+
+	c.bench_function("parse large codeblock", |b| {
+		b.iter(|| {
+			let _ = black_box(doll.parse_document(&*codeblock));
+			black_box(doll.finish())
+		});
+	});
+}
+
+/// the same few tags, thousands of times, with and without caching pure tags
+fn repetitive(c: &mut Criterion) {
+	let paragraph =
+		"[img(logo.svg):Site logo] see [link(https://example.com):[em(b):the [code:docs]]]\n\n";
+	let src = Arc::<str>::from(paragraph.repeat(5_000));
+
+	for cache in [false, true] {
+		let mut doll = MarkDoll::new();
+		doll.cache_pure_tags = cache;
+		doll.ext_system.add_tags(ext::formatting::tags());
+		doll.ext_system.add_tags(ext::code::tags());
+		doll.ext_system.add_tags(ext::links::tags());
+
+		c.bench_function(
+			if cache {
+				"parse repetitive tags, cached"
+			} else {
+				"parse repetitive tags"
+			},
+			|b| {
+				b.iter(|| {
+					let _ = black_box(doll.parse_document(Arc::clone(&src)));
+					black_box(doll.finish())
+				});
+			},
+		);
+	}
+}
+
+/// the generated documents of [`corpus`]
+fn generated(c: &mut Criterion) {
+	let mut doll = corpus::doll();
+
+	for (name, src) in [
+		("parse 5 MB of prose", corpus::prose(5_000_000)),
+		("parse 10k inline tags", corpus::tags(10_000)),
+	] {
+		let src = Arc::<str>::from(src);
+		assert!(doll.parse_document(Arc::clone(&src)).is_ok());
+		doll.finish();
+
+		c.bench_function(name, |b| {
+			b.iter(|| {
+				let _ = black_box(doll.parse_document(Arc::clone(&src)));
+				black_box(doll.finish())
+			});
+		});
+	}
+}
+
+criterion_group!(
+	benches,
+	parse,
+	long_line,
+	large_codeblock,
+	repetitive,
+	generated
+);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+//! a stateless inline tag, `[badge(kind):text]`, showing short text as a badge
+//!
+//! the simplest kind of tag: it parses its arguments and content into a value of its own, and each emit target it supports writes that value out
+//!
+//! run with `cargo run --example badge`
+
+use {
+	core::fmt::Write,
+	markdoll::{
+		args,
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::TagDefinition,
+		MarkDoll,
+	},
+};
+
+/// what a `badge` tag parsed to, everything its emitters need
+#[derive(Debug, Clone)]
+struct Badge {
+	kind: String,
+	text: String,
+}
+
+/// the tag, taking the kind of badge as its argument and the text shown as its content
+fn badge() -> TagDefinition {
+	TagDefinition::new(
+		"badge",
+		Some(|doll, mut args, text, _| {
+			// reports missing, invalid, and misplaced arguments at the tag, returning `None` so it is left out
+			args! {
+				doll, args;
+
+				args(kind);
+				opt_args();
+				flags();
+				props();
+			}
+
+			Some(Box::new(Badge {
+				kind: kind.to_string(),
+				text: text.to_string(),
+			}))
+		}),
+	)
+	// parsed only from its arguments and content, so identical badges may be parsed once and copied
+	.with_pure(|content| Some(Box::new(content.downcast_ref::<Badge>().unwrap().clone())))
+	// an empty badge is likely a mistake, reported by the strict profile
+	.with_expects_content()
+	.with_emitter::<HtmlEmit>(|_, to, content| {
+		let badge = content.downcast_ref::<Badge>().unwrap();
+
+		// anything from the document is escaped
+		write!(
+			to.write,
+			"<span class='badge badge-{}'>{}</span>",
+			html_escape::encode_single_quoted_attribute(&badge.kind),
+			html_escape::encode_text(&badge.text),
+		)
+		.unwrap();
+	})
+}
+
+/// convert a document using the `badge` tag to html
+fn render(src: &str) -> String {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(badge());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+
+	let mut out = HtmlEmit::untrusted_profile();
+	doll.emit(&mut ast, &mut out);
+	doll.finish();
+
+	out.write
+}
+
+fn main() {
+	println!(
+		"{}",
+		render("markdoll [badge(new):3.0] is out, [badge(warn):breaking <changes>] ahead")
+	);
+}
+
+#[test]
+fn renders() {
+	assert_eq!(
+		render("markdoll [badge(new):3.0] is out, [badge(warn):breaking <changes>] ahead"),
+		"<div>markdoll <span class='badge badge-new'>3.0</span> is out, <span class='badge badge-warn'>breaking &lt;changes&gt;</span> ahead</div>"
+	);
+
+	// without a kind, the badge is left out
+	assert_eq!(render("a [badge:b] c"), "<div>a  c</div>");
+}
@@ -0,0 +1,164 @@
+//! a block tag with markdoll content, `[callout(kind)::...]`, emitted both to html and to a plain text target defined here
+//!
+//! tags holding an [`AST`] expose it with `with_children`, so visitors and passes reach the content inside them, and emit it by emitting each block in turn. an emit target is any type, given [`BuiltInEmitters`] for the blocks markdoll itself parses
+//!
+//! run with `cargo run --example callout`
+
+use markdoll::{
+	args,
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::TagDefinition,
+	tree::{print, InlineItem, AST},
+	MarkDoll,
+};
+
+/// what a `callout` tag parsed to
+#[derive(Debug)]
+struct Callout {
+	kind: String,
+	ast: AST,
+}
+
+/// the tag, taking the kind of callout as its argument and markdoll as its content
+fn callout() -> TagDefinition {
+	TagDefinition::new(
+		"callout",
+		Some(|doll, mut args, text, _| {
+			args! {
+				doll, args;
+
+				args();
+				opt_args(kind);
+				flags();
+				props();
+			}
+
+			Some(Box::new(Callout {
+				kind: kind.unwrap_or("note").to_string(),
+				// diagnostics within the content are mapped back to where it was written
+				ast: match doll.parse(text) {
+					Ok(ast) | Err(ast) => ast,
+				},
+			}))
+		}),
+	)
+	.with_children(
+		|content| vec![&content.downcast_ref::<Callout>().unwrap().ast],
+		|content| vec![&mut content.downcast_mut::<Callout>().unwrap().ast],
+	)
+	.with_printer(|doll, content, options| {
+		print::to_doll(
+			doll,
+			&content.downcast_ref::<Callout>().unwrap().ast,
+			options,
+		)
+	})
+	.with_emitter::<HtmlEmit>(|doll, to, content| {
+		let callout = content.downcast_mut::<Callout>().unwrap();
+
+		to.write_open(
+			doll,
+			"aside",
+			&format!(
+				" class='callout callout-{}'",
+				html_escape::encode_single_quoted_attribute(&callout.kind)
+			),
+		);
+		let inline_block = callout.ast.len() > 1;
+		for block in &mut callout.ast {
+			block.emit(doll, to, inline_block);
+		}
+		to.write_close(doll, "aside");
+	})
+	.with_emitter::<Text>(|doll, to, content| {
+		let callout = content.downcast_mut::<Callout>().unwrap();
+
+		to.0.push_str(&callout.kind.to_uppercase());
+		to.0.push_str(": ");
+		for block in &mut callout.ast {
+			block.emit(doll, to, false);
+		}
+	})
+}
+
+/// a plain text emit target, writing text without any markup
+struct Text(String);
+
+/// how to write the blocks markdoll parses as plain text
+fn text_emitters() -> BuiltInEmitters<Text> {
+	BuiltInEmitters {
+		inline: |doll, to, segments, _| {
+			for (_, segment) in segments {
+				match segment {
+					InlineItem::Split => to.0.push(' '),
+					InlineItem::Break => to.0.push('\n'),
+					InlineItem::Text(text) => to.0.push_str(text),
+					// tags without an emitter for `Text` are reported and left out
+					InlineItem::Tag(tag) => tag.emit(doll, to),
+				}
+			}
+			to.0.push('\n');
+		},
+		section: |doll, to, _, name, _, children| {
+			to.0.push_str(name);
+			to.0.push('\n');
+			for block in children {
+				block.emit(doll, to, false);
+			}
+		},
+		list: |doll, to, ordered, items| {
+			for (i, item) in items.iter_mut().enumerate() {
+				if ordered {
+					to.0.push_str(&format!("{}. ", i + 1));
+				} else {
+					to.0.push_str("- ");
+				}
+				for block in &mut item.ast {
+					block.emit(doll, to, false);
+				}
+			}
+		},
+	}
+}
+
+const SRC: &str = "[callout(warning)::
+	this can't be undone
+
+	-	back up first
+]";
+
+/// convert a document using the `callout` tag to html and to text
+fn render(src: &str) -> (String, String) {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(callout());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	doll.set_emitters(text_emitters());
+
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+
+	let mut html = HtmlEmit::untrusted_profile();
+	doll.emit(&mut ast, &mut html);
+
+	let mut text = Text(String::new());
+	doll.emit(&mut ast, &mut text);
+	doll.finish();
+
+	(html.write, text.0)
+}
+
+fn main() {
+	let (html, text) = render(SRC);
+	println!("{html}\n\n{text}");
+}
+
+#[test]
+fn renders() {
+	assert_eq!(
+		render(SRC),
+		(
+			"<div><aside class='callout callout-warning'><div>this can't be undone</div><ul><li><div>back up first</div></li></ul></aside></div>"
+				.to_string(),
+			"WARNING: this can't be undone\n- back up first\n\n".to_string()
+		)
+	);
+}
@@ -0,0 +1,110 @@
+//! a tag that reports its own diagnostics, `[progress(done)(total)]`, at the arguments they are about
+//!
+//! positions a tag reports at are in its own content, and are mapped back to the document, so diagnostics point where the mistake was written, however deeply the tag is nested. codes are `&'static str`s, so tags may report their own codes alongside those markdoll has
+//!
+//! run with `cargo run --example diagnostics`
+
+use {
+	core::fmt::Write,
+	markdoll::{
+		args,
+		diagnostics::{LineCol, LineIndex},
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self, TagDefinition},
+		MarkDoll,
+	},
+};
+
+/// what a `progress` tag parsed to
+#[derive(Debug, Clone, Copy)]
+struct Progress {
+	done: u32,
+	total: u32,
+}
+
+/// the tag, taking how much is done and how much there is as numbers
+fn progress() -> TagDefinition {
+	TagDefinition::new(
+		"progress",
+		Some(|doll, mut args, text, _| {
+			// typed arguments that don't parse are reported at the argument as `arg <name> invalid`
+			args! {
+				doll, args;
+
+				args(done: u32, total: u32);
+				opt_args();
+				flags();
+				props();
+			}
+
+			// reported at the tag, and still parsed, as it's only a warning
+			if !text.is_empty() {
+				doll.diag(false, usize::MAX, "cannot have content");
+			}
+
+			// reported at the first argument as written, an error leaves the tag out
+			if done > total {
+				doll.diag_arg(true, 0, "progress past its total");
+				return None;
+			}
+
+			Some(Box::new(Progress { done, total }))
+		}),
+	)
+	.with_pure(|content| Some(Box::new(*content.downcast_ref::<Progress>().unwrap())))
+	.with_emitter::<HtmlEmit>(|_, to, content| {
+		let Progress { done, total } = *content.downcast_ref::<Progress>().unwrap();
+
+		write!(
+			to.write,
+			"<progress value='{done}' max='{total}'>{done}/{total}</progress>"
+		)
+		.unwrap();
+	})
+}
+
+/// check a document using the `progress` tag, listing its diagnostics as `line:col severity code`
+fn check(src: &str) -> Vec<String> {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.ext_system.add_tag(progress());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let _ = doll.parse_document(src);
+
+	// positions are character indices in the source given to `parse_document`
+	let lines = LineIndex::new(src);
+	doll.finish()
+		.iter()
+		.map(|diag| {
+			let LineCol { line, col } = lines.line_col(diag.at);
+			let severity = if diag.err { "error" } else { "warning" };
+			format!("{line}:{col} {severity} {}", diag.code)
+		})
+		.collect()
+}
+
+const SRC: &str = "&status
+	docs [progress(3)(5)]
+
+	[em:tests [progress(7)(5)]]
+
+	release [progress(soon)(1)] [progress(0)(1):extra]";
+
+fn main() {
+	for diagnostic in check(SRC) {
+		println!("{diagnostic}");
+	}
+}
+
+#[test]
+fn reports() {
+	assert_eq!(
+		check(SRC),
+		[
+			"4:22 error progress past its total",
+			"6:20 error arg done invalid",
+			"6:30 warning cannot have content",
+		]
+	);
+}
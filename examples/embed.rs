@@ -0,0 +1,164 @@
+//! an application embedding markdoll, rendering issue notes that link to an issue tracker it is configured with
+//!
+//! the embedder puts its configuration in [`MarkDoll::context`], which tags read while parsing, to check what the document names, and while emitting, to write what it configured. nothing else needs to be threaded through
+//!
+//! run with `cargo run --example embed`
+
+use {
+	core::fmt::Write,
+	markdoll::{
+		args,
+		diagnostics::LineIndex,
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self, TagDefinition},
+		MarkDoll,
+	},
+};
+
+/// the application's configuration, kept in [`MarkDoll::context`]
+#[derive(Debug, Clone)]
+struct Tracker {
+	/// where issues are, followed by `/{project}/issues/{number}`
+	base_url: String,
+	/// the projects issues may be in
+	projects: Vec<String>,
+}
+
+/// what an `issue` tag parsed to
+#[derive(Debug, Clone)]
+struct Issue {
+	project: String,
+	number: u32,
+}
+
+/// the tag, naming an issue by its project and number, `[issue(markdoll)(12)]`
+fn issue() -> TagDefinition {
+	TagDefinition::new(
+		"issue",
+		Some(|doll, mut args, _, _| {
+			args! {
+				doll, args;
+
+				args(project, number: u32);
+				opt_args();
+				flags();
+				props();
+			}
+
+			// checked while parsing, so mistakes are found without emitting
+			let tracked = doll
+				.context
+				.get_ref::<Tracker>()
+				.is_some_and(|tracker| tracker.projects.iter().any(|name| name == project));
+			if !tracked {
+				doll.diag_arg(true, 0, "project not tracked");
+				return None;
+			}
+
+			Some(Box::new(Issue {
+				project: project.to_string(),
+				number,
+			}))
+		}),
+	)
+	.with_emitter::<HtmlEmit>(|doll, to, content| {
+		let issue = content.downcast_ref::<Issue>().unwrap();
+		let tracker = doll.context.get_ref::<Tracker>().unwrap();
+
+		write!(
+			to.write,
+			"<a class='issue' href='{}/{}/issues/{}'>{}#{}</a>",
+			html_escape::encode_single_quoted_attribute(&tracker.base_url),
+			html_escape::encode_single_quoted_attribute(&issue.project),
+			issue.number,
+			html_escape::encode_text(&issue.project),
+			issue.number,
+		)
+		.unwrap();
+	})
+}
+
+/// the application, set up once and used for every document
+struct Notes {
+	doll: MarkDoll,
+}
+
+impl Notes {
+	fn new(tracker: Tracker) -> Self {
+		let mut doll = MarkDoll::new();
+		doll.ext_system.add_tags(ext::formatting::tags());
+		doll.ext_system.add_tag(issue());
+		doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+		doll.context.put(tracker);
+
+		Self { doll }
+	}
+
+	/// render a note to html, or list why it couldn't be as `line:col code`
+	fn render(&mut self, src: &str) -> Result<String, Vec<String>> {
+		let (ok, mut ast) = match self.doll.parse_document(src) {
+			Ok((_, ast)) => (true, ast),
+			Err((_, ast)) => (false, ast),
+		};
+
+		let mut out = HtmlEmit::untrusted_profile();
+		// emitted even if parsing failed, as a preview would be, and always before `finish`
+		let ok = self.doll.emit(&mut ast, &mut out) && ok;
+
+		// resets the state kept for this document, keeping the context for the next
+		let diagnostics = self.doll.finish();
+
+		if ok {
+			Ok(out.write)
+		} else {
+			let lines = LineIndex::new(src);
+			Err(diagnostics
+				.iter()
+				.filter(|diag| diag.err)
+				.map(|diag| {
+					let pos = lines.line_col(diag.at);
+					format!("{}:{} {}", pos.line, pos.col, diag.code)
+				})
+				.collect())
+		}
+	}
+}
+
+fn notes() -> Notes {
+	Notes::new(Tracker {
+		base_url: "https://issues.example".to_string(),
+		projects: vec!["markdoll".to_string(), "dollcli".to_string()],
+	})
+}
+
+fn main() {
+	let mut notes = notes();
+
+	for src in [
+		"fixed [issue(markdoll)(12)], [em:finally]",
+		"see [issue(dollcli)(3)]\nand [issue(elsewhere)(4)]",
+	] {
+		match notes.render(src) {
+			Ok(html) => println!("{html}"),
+			Err(errors) => println!("{}", errors.join("\n")),
+		}
+	}
+}
+
+#[test]
+fn renders() {
+	let mut notes = notes();
+
+	assert_eq!(
+		notes.render("fixed [issue(markdoll)(12)], [em:finally]"),
+		Ok("<div>fixed <a class='issue' href='https://issues.example/markdoll/issues/12'>markdoll#12</a>, <em><span>finally</span></em></div>".to_string())
+	);
+	assert_eq!(
+		notes.render("see [issue(dollcli)(3)]\nand [issue(elsewhere)(4)]"),
+		Err(vec!["2:12 project not tracked".to_string()])
+	);
+	assert_eq!(
+		notes.render("[issue(markdoll)(twelve)]"),
+		Err(vec!["1:18 arg number invalid".to_string()])
+	);
+}
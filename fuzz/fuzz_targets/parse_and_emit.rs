@@ -0,0 +1,45 @@
+#![no_main]
+
+use {
+	libfuzzer_sys::fuzz_target,
+	markdoll::{
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext, MarkDoll,
+	},
+};
+
+// any panic while parsing or emitting untrusted input is a bug
+fuzz_target!(|data: &[u8]| {
+	let Ok(src) = std::str::from_utf8(data) else {
+		return;
+	};
+
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::citation::tags());
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::conditional::tags());
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.ext_system.add_tags(ext::code::tags());
+	doll.ext_system.add_tags(ext::datetime::tags());
+	doll.ext_system.add_tags(ext::diagram::tags());
+	doll.ext_system.add_tags(ext::emoji::tags());
+	doll.ext_system.add_tags(ext::glossary::tags());
+	doll.ext_system.add_tags(ext::index::tags());
+	doll.ext_system.add_tags(ext::layout::tags());
+	doll.ext_system.add_tags(ext::links::tags());
+	doll.ext_system.add_tags(ext::math::tags());
+	doll.ext_system.add_tags(ext::media::tags());
+	doll.ext_system.add_tags(ext::page::tags());
+	doll.ext_system.add_tags(ext::table::tags());
+	doll.ext_system.add_tags(ext::tabs::tags());
+	doll.ext_system.add_tags(ext::todo::tags());
+	doll.ext_system.add_tags(ext::var::tags());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+
+	let mut out = HtmlEmit::default();
+	doll.emit(&mut ast, &mut out);
+
+	let _ = doll.finish();
+});
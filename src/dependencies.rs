@@ -0,0 +1,91 @@
+use {
+	alloc::{
+		format,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+};
+
+/// what kind of file a [`Dependency`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DependencyKind {
+	/// markdoll source included into the document
+	SourceFile,
+	/// a program run to produce part of the document
+	Executable,
+	/// a file the output refers to, such as an image
+	Asset,
+}
+
+/// a file a document depends on, recorded with [`MarkDoll::record_dependency`](crate::MarkDoll::record_dependency)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Dependency {
+	/// what kind of file it is
+	pub kind: DependencyKind,
+	/// the path, as written in the document
+	pub path: String,
+	/// the position in the source of the tag depending on it
+	pub at: usize,
+	/// when the file was last modified, in seconds since the unix epoch, if known
+	///
+	/// tags leave this unset, embedders that can read the file system may fill it in with [`MarkDoll::dependencies_mut`](crate::MarkDoll::dependencies_mut) before taking a [`content_fingerprint`](crate::MarkDoll::content_fingerprint)
+	pub modified: Option<u64>,
+}
+
+/// whether a URL refers to a local file, rather than having a scheme such as `https:` or `data:`, being protocol-relative, or only having a fragment
+#[must_use]
+pub fn is_local(url: &str) -> bool {
+	let scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+		scheme.len() > 1
+			&& scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+			&& scheme
+				.chars()
+				.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+	});
+
+	!url.is_empty() && !scheme && !url.starts_with("//") && !url.starts_with('#')
+}
+
+/// a Makefile rule making `target` depend on each unique path in `dependencies`, in order, as written by compilers with `-MF`
+///
+/// spaces, `#`, and `$` in paths are escaped, and the path part of local URLs is used, without any query or fragment
+#[must_use]
+pub fn depfile(target: &str, dependencies: &[Dependency]) -> String {
+	let escape = |path: &str| {
+		path.replace('$', "$$")
+			.replace('#', "\\#")
+			.replace(' ', "\\ ")
+	};
+
+	let mut paths = Vec::<String>::new();
+	for dependency in dependencies {
+		let path = dependency
+			.path
+			.split(['?', '#'])
+			.next()
+			.unwrap_or_default()
+			.to_string();
+
+		if !path.is_empty() && !paths.contains(&path) {
+			paths.push(path);
+		}
+	}
+
+	let mut rule = format!("{}:", escape(target));
+	for path in &paths {
+		rule.push_str(" \\\n  ");
+		rule.push_str(&escape(path));
+	}
+	rule.push('\n');
+
+	// a rule for each dependency, so removing one doesn't break the build
+	for path in &paths {
+		write!(rule, "\n{}:\n", escape(path)).unwrap();
+	}
+
+	rule
+}
@@ -1,10 +1,18 @@
 #[cfg(feature = "ariadne")]
 use ariadne::{Label, Report, ReportKind};
 use {
-	alloc::{rc::Rc, vec::Vec},
-	core::cmp::Ordering,
+	crate::{MarkDoll, Phase},
+	alloc::{boxed::Box, format, string::String, sync::Arc, vec::Vec},
+	core::{
+		cmp::Ordering,
+		ops::{Deref, DerefMut, Range},
+	},
+	hashbrown::{HashMap, HashSet},
 };
 
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
 /// an issue in the source
 #[derive(Debug)]
 pub struct Diagnostic {
@@ -12,8 +20,20 @@ pub struct Diagnostic {
 	pub err: bool,
 	/// the location of this diagnostic in the source
 	pub at: usize,
+	/// how many characters from [`at`](Self::at) the diagnostic covers, which is the whole tag, through its closing `]`, for diagnostics at a tag, and otherwise 1
+	pub len: usize,
 	/// the content of this diagnostic
 	pub code: &'static str,
+	/// a secondary location giving context, such as where a tag's content ended, and what happened there, see [`MarkDoll::diag_with_context`](crate::MarkDoll::diag_with_context)
+	pub context: Option<(usize, &'static str)>,
+	/// the locations of similar diagnostics that were collapsed into this one by [`MarkDoll::finish_collapsed`](crate::MarkDoll::finish_collapsed)
+	pub similar: Vec<usize>,
+	/// whether it was reported while parsing, in a pass run with [`MarkDoll::run_pass`](crate::MarkDoll::run_pass), or while emitting, including while parsing the content of tags being emitted
+	///
+	/// diagnostics are kept in the order they were reported, whatever their phase
+	pub phase: Phase,
+	/// details of what was found, for tooling that fixes it, if the code has any
+	pub data: Option<Data>,
 	/// the location in the source of this diagnostic
 	///
 	/// this only exists in debug mode, so use [`doll.diag`](crate::MarkDoll::diag) or annotate things with `#[cfg(debug_assertions)]`
@@ -22,30 +42,417 @@ pub struct Diagnostic {
 	pub src: &'static core::panic::Location<'static>,
 }
 
-#[derive(Debug)]
+/// details of a [`Diagnostic`], for tooling that fixes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "kebab-case"))]
+#[non_exhaustive]
+pub enum Data {
+	/// a section nested more deeply than the one it is in allows, for `section skips a level`
+	HeadingLevel {
+		/// the level of the section, starting at 1
+		level: usize,
+		/// the level it should have, one more than the level of the section it is in
+		expected: usize,
+	},
+	/// a tag that shows its content left empty, for `tag has no content`
+	EmptyContent {
+		/// the name of the tag
+		tag: String,
+	},
+	/// lines of a code block's `hl` prop that it doesn't have, for `highlighted lines are outside of the code`
+	LinesOutside {
+		/// the lines and ranges of lines outside of the code, such as `1,12-14`
+		lines: String,
+		/// the number of the first line of the code
+		first: usize,
+		/// the number of the last line of the code
+		last: usize,
+	},
+	/// blocks nested past [`MarkDoll::max_emit_depth`](crate::MarkDoll::max_emit_depth), for `nested too deeply to emit`
+	TooDeep {
+		/// the limit, how deeply blocks may nest
+		max_depth: usize,
+	},
+	/// a tag the [`MarkDoll::tag_filter`](crate::MarkDoll::tag_filter) refused, for `tag denied`
+	TagDenied {
+		/// the name of the tag, as written
+		tag: String,
+		/// why, as the filter gave it
+		reason: String,
+	},
+}
+
+impl Data {
+	/// a sentence about what should be done, as help
+	#[must_use]
+	pub fn help(&self) -> String {
+		match self {
+			Self::HeadingLevel { level, expected } => {
+				format!("this section is at level {level}, but should be at level {expected}")
+			}
+			Self::EmptyContent { tag } => {
+				format!("give the `{tag}` tag content, or remove it")
+			}
+			Self::TooDeep { max_depth } => {
+				format!("nest blocks less deeply, or raise `MarkDoll::max_emit_depth` above {max_depth}")
+			}
+			Self::TagDenied { reason, .. } => reason.clone(),
+			Self::LinesOutside { lines, first, last } => {
+				format!("lines {lines} are highlighted, but the code has lines {first} to {last}")
+			}
+		}
+	}
+}
+
+impl Diagnostic {
+	/// the help of the [`data`](Self::data), followed by how many similar diagnostics were collapsed into this one, if any
+	fn help(&self) -> Option<String> {
+		let similar = (!self.similar.is_empty()).then(|| {
+			format!(
+				"...and {} more similar {}{}",
+				self.similar.len(),
+				if self.err { "error" } else { "warning" },
+				if self.similar.len() == 1 { "" } else { "s" }
+			)
+		});
+
+		match (self.data.as_ref().map(Data::help), similar) {
+			(Some(data), Some(similar)) => Some(format!("{data}\n{similar}")),
+			(data, similar) => data.or(similar),
+		}
+	}
+
+	/// convert to a [`Structured`] diagnostic, resolving locations with a [`LineIndex`] of the root source
+	#[must_use]
+	pub fn to_structured(&self, lines: &LineIndex) -> Structured {
+		let label = |at: usize, length: usize, message: &'static str| {
+			let LineCol { line, col } = lines.line_col(at);
+
+			StructuredLabel {
+				at,
+				line,
+				col,
+				length,
+				message,
+			}
+		};
+
+		let mut labels = Vec::with_capacity(2 + self.similar.len());
+		labels.push(label(self.at, self.len, self.code));
+		labels.extend(self.context.map(|(at, message)| label(at, 1, message)));
+		labels.extend(self.similar.iter().map(|at| label(*at, 1, "similar")));
+
+		Structured {
+			code: self.code,
+			severity: if self.err {
+				Severity::Error
+			} else {
+				Severity::Warning
+			},
+			message: self.code,
+			help: self.help(),
+			explanation: explain(self.code).map(|entry| entry.explanation),
+			data: self.data.clone(),
+			phase: self.phase,
+			labels,
+		}
+	}
+}
+
+/// a line and column in a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineCol {
+	/// the line, starting from 1
+	pub line: usize,
+	/// the column in characters, starting from 1
+	pub col: usize,
+}
+
+impl LineCol {
+	/// resolve a character index in `src`, such as [`Diagnostic::at`], to a line and column
+	///
+	/// diagnostic locations are already mapped out of tag contents and arguments into the root source, so `src` should be the source passed to [`MarkDoll::parse_document`](crate::MarkDoll::parse_document)
+	#[must_use]
+	pub fn of(src: &str, at: usize) -> Self {
+		let mut pos = Self { line: 1, col: 1 };
+
+		for ch in src.chars().take(at) {
+			if ch == '\n' {
+				pos.line += 1;
+				pos.col = 1;
+			} else {
+				pos.col += 1;
+			}
+		}
+
+		pos
+	}
+}
+
+/// the positions of the lines in a source, for resolving many positions in it
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+	/// the character index of each newline
+	newlines: Vec<usize>,
+}
+
+impl LineIndex {
+	/// index the lines of `src`, which should be the source passed to [`MarkDoll::parse_document`](crate::MarkDoll::parse_document)
+	#[must_use]
+	pub fn new(src: &str) -> Self {
+		Self {
+			newlines: src
+				.chars()
+				.enumerate()
+				.filter_map(|(i, ch)| (ch == '\n').then_some(i))
+				.collect(),
+		}
+	}
+
+	/// resolve a character index, such as [`Diagnostic::at`], to a line and column
+	#[must_use]
+	pub fn line_col(&self, at: usize) -> LineCol {
+		let line = self.newlines.partition_point(|newline| *newline < at);
+
+		LineCol {
+			line: line + 1,
+			col: at
+				- line
+					.checked_sub(1)
+					.map_or(0, |prev| self.newlines[prev] + 1)
+				+ 1,
+		}
+	}
+}
+
+/// the severity of a [`Structured`] diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+	/// indicates invalid state
+	Error,
+	/// does not indicate invalid state
+	Warning,
+}
+
+/// a [`Diagnostic`] with its locations resolved, for tooling
+///
+/// see `diagnostics.schema.json` in the repository for its serialized form
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Structured {
+	/// the code, which can be used with [`MarkDoll::severity_overrides`](crate::MarkDoll::severity_overrides)
+	pub code: &'static str,
+	/// the severity
+	pub severity: Severity,
+	/// the message
+	pub message: &'static str,
+	/// extra help, if any
+	pub help: Option<String>,
+	/// what causes the diagnostic and how to address it, from [`explain`]
+	pub explanation: Option<&'static str>,
+	/// details of what was found, see [`Diagnostic::data`]
+	pub data: Option<Data>,
+	/// what was being done when it was reported, see [`Diagnostic::phase`]
+	pub phase: Phase,
+	/// the locations, starting with the primary one
+	pub labels: Vec<StructuredLabel>,
+}
+
+/// a resolved location of a [`Structured`] diagnostic
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StructuredLabel {
+	/// the character index in the source
+	pub at: usize,
+	/// the line, starting from 1
+	pub line: usize,
+	/// the column in characters, starting from 1
+	pub col: usize,
+	/// the length in characters
+	pub length: usize,
+	/// the message
+	pub message: &'static str,
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct TagDiagnosticTranslation {
-	pub src: Rc<str>,
+	/// the buffer containing the source, which is shared with the parent when the source is a verbatim slice of it
+	pub src: Arc<str>,
+	/// the byte range of the source within [`src`](Self::src)
+	pub range: Range<usize>,
 	pub indexed: Option<IndexedSrc>,
+	/// where the characters of the source are, once a child has needed to find its place in it
+	pub char_index: Option<Box<CharIndex>>,
 	pub offset_in_parent: usize,
+	/// the position in the parent of the tag, at its opening `[`, or the opening delimiter of a shorthand
 	pub tag_pos_in_parent: usize,
+	/// the position in the parent just after the tag's closing `]`, or [`usize::MAX`] if it isn't known, such as for tags built in code
+	pub tag_end_in_parent: usize,
 	pub indent: usize,
+	/// the position of each argument of the tag in the parent, where it starts after its `(`, empty if they weren't written in the parent
+	pub args: Vec<usize>,
 }
 
-#[derive(Debug)]
+/// an operation on a [`MarkDoll`], tracking whether any errors were emitted during it, see [`MarkDoll::begin_operation`]
+///
+/// dereferences to the [`MarkDoll`], and once ended or dropped, errors during it also fail the operation containing it
+#[must_use = "ending the operation immediately does nothing"]
+pub struct DiagnosticSink<'doll> {
+	doll: &'doll mut MarkDoll,
+	outer_ok: bool,
+	outer_phase: Phase,
+}
+
+impl<'doll> DiagnosticSink<'doll> {
+	pub(crate) fn new(doll: &'doll mut MarkDoll) -> Self {
+		let outer_ok = core::mem::replace(&mut doll.ok, true);
+		let outer_phase = doll.phase;
+
+		Self {
+			doll,
+			outer_ok,
+			outer_phase,
+		}
+	}
+
+	/// mark the diagnostics reported during the operation as [`Phase::Emit`], until it ends
+	pub(crate) fn emitting(self) -> Self {
+		self.doll.phase = Phase::Emit;
+		self
+	}
+
+	/// end the operation, returning whether no errors were emitted during it
+	#[allow(clippy::must_use_candidate, reason = "the operation ends either way")]
+	pub fn end(self) -> bool {
+		self.doll.ok
+	}
+}
+
+impl Deref for DiagnosticSink<'_> {
+	type Target = MarkDoll;
+
+	fn deref(&self) -> &MarkDoll {
+		self.doll
+	}
+}
+
+impl DerefMut for DiagnosticSink<'_> {
+	fn deref_mut(&mut self) -> &mut MarkDoll {
+		self.doll
+	}
+}
+
+impl Drop for DiagnosticSink<'_> {
+	fn drop(&mut self) {
+		self.doll.ok &= self.outer_ok;
+		self.doll.phase = self.outer_phase;
+	}
+}
+
+impl TagDiagnosticTranslation {
+	/// a translation for a source in a buffer of its own
+	pub fn new(
+		src: Arc<str>,
+		offset_in_parent: usize,
+		tag_pos_in_parent: usize,
+		indent: usize,
+	) -> Self {
+		Self {
+			range: 0..src.len(),
+			src,
+			indexed: None,
+			char_index: None,
+			offset_in_parent,
+			tag_pos_in_parent,
+			tag_end_in_parent: usize::MAX,
+			indent,
+			args: Vec::new(),
+		}
+	}
+
+	/// a translation for markdoll generated by a tag, such as from a tag argument, whose diagnostics are reported at `from` in the parent since the source isn't in it, see [`MarkDoll::parse_snippet`](crate::MarkDoll::parse_snippet)
+	pub fn generated(src: Arc<str>, from: usize) -> Self {
+		Self::new(src, usize::MAX, from, 0)
+	}
+
+	/// the source
+	pub fn text(&self) -> &str {
+		&self.src[self.range.clone()]
+	}
+
+	/// the text from the character `at`, see [`CharIndex::rest_from`]
+	pub fn text_from_char(&mut self, at: usize) -> &str {
+		let text = &self.src[self.range.clone()];
+
+		self.char_index
+			.get_or_insert_with(|| Box::new(CharIndex::new(text)))
+			.rest_from(text, at)
+	}
+}
+
+/// the byte position of every [`STRIDE`](Self::STRIDE)th character of a source, so finding a character doesn't count from the start each time
+#[derive(Debug, Clone)]
+pub(crate) struct CharIndex {
+	bytes: Vec<usize>,
+	chars: usize,
+}
+
+impl CharIndex {
+	const STRIDE: usize = 64;
+
+	#[must_use]
+	pub fn new(src: &str) -> Self {
+		let mut bytes = Vec::new();
+		let mut chars = 0;
+
+		for (byte, _) in src.char_indices() {
+			if chars % Self::STRIDE == 0 {
+				bytes.push(byte);
+			}
+			chars += 1;
+		}
+
+		Self { bytes, chars }
+	}
+
+	/// the source from the character `at`, or from its last character if `at` is past it
+	#[must_use]
+	pub fn rest_from<'src>(&self, src: &'src str, at: usize) -> &'src str {
+		let at = at.min(self.chars.saturating_sub(1));
+		let Some(&byte) = self.bytes.get(at / Self::STRIDE) else {
+			return src;
+		};
+
+		let rest = &src[byte..];
+		let skip = rest
+			.char_indices()
+			.nth(at % Self::STRIDE)
+			.map_or(rest.len(), |(byte, _)| byte);
+
+		&rest[skip..]
+	}
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct IndexedSrc {
 	lines: Vec<(usize, usize)>,
 	parent_lines: Vec<usize>,
 }
 
 impl IndexedSrc {
+	/// index a source taken from its parent at `offset_in_parent`, given the rest of the parent from there, see [`CharIndex::rest_from`]
 	#[must_use]
-	pub fn index(src: &str, parent: &str, offset_in_parent: usize, indent: usize) -> Self {
+	pub fn index(src: &str, parent_rest: &str, offset_in_parent: usize, indent: usize) -> Self {
 		let lines = {
 			let mut lines = Vec::new();
 
 			let mut start = 0;
 			for line in src.split('\n') {
-				let end = start + line.len() + 1;
+				let end = start + line.chars().count() + 1;
 				lines.push((start, end - 1));
 				start = end;
 			}
@@ -53,16 +460,23 @@ impl IndexedSrc {
 			lines
 		};
 
-		let parent_chars = parent.chars().collect::<Vec<char>>();
+		// only the lines this source spans are needed, not the whole parent
+		let mut newlines = 0;
+		let parent_chars = parent_rest
+			.chars()
+			.take_while(|ch| {
+				if *ch == '\n' {
+					newlines += 1;
+				}
+				newlines < lines.len()
+			})
+			.collect::<Vec<char>>();
 
 		let parent_lines = {
 			let mut parent_lines = Vec::new();
 
 			let mut start = 0;
-			for line in parent_chars[offset_in_parent.min(parent.len().saturating_sub(1))..]
-				.split(|ch| *ch == '\n')
-				.take(lines.len())
-			{
+			for line in parent_chars.split(|ch| *ch == '\n').take(lines.len()) {
 				let end = start + line.len() + 1;
 
 				let mut ind = 0;
@@ -72,7 +486,8 @@ impl IndexedSrc {
 						Some('\t') => ind += 1,
 						Some('-' | '=') => ind += 2,
 						None => {}
-						_ => unreachable!(),
+						// content that follows an error on the tag's own line isn't indented
+						_ => break,
 					}
 				}
 
@@ -91,6 +506,44 @@ impl IndexedSrc {
 		}
 	}
 
+	/// index a source of `chars` characters copied verbatim from its parent at `offset_in_parent`, so each position is the same distance from there
+	#[must_use]
+	pub fn verbatim(chars: usize, offset_in_parent: usize) -> Self {
+		Self {
+			lines: vec![(0, chars)],
+			parent_lines: vec![offset_in_parent],
+		}
+	}
+
+	/// index a source copied from a single line of its parent with some characters left out, such as escapes, given the position of each of its characters relative to `offset_in_parent`
+	///
+	/// each run of characters that were next to each other in the parent is indexed like a line
+	#[must_use]
+	pub fn runs(offsets: &[usize], offset_in_parent: usize) -> Self {
+		let mut lines = Vec::<(usize, usize)>::new();
+		let mut parent_lines = Vec::new();
+
+		for (i, &offset) in offsets.iter().enumerate() {
+			match lines.last_mut() {
+				Some((_, end)) if offsets[*end] + 1 == offset => *end = i,
+				_ => {
+					lines.push((i, i));
+					parent_lines.push(offset_in_parent + offset);
+				}
+			}
+		}
+
+		// the end of the source follows its last character
+		if let Some((_, end)) = lines.last_mut() {
+			*end += 1;
+		}
+
+		Self {
+			lines,
+			parent_lines,
+		}
+	}
+
 	#[must_use]
 	pub fn parent_offset(&self, index: usize) -> usize {
 		let mut offset_within_line = 0;
@@ -119,14 +572,384 @@ impl IndexedSrc {
 	}
 }
 
+/// collapse repeated diagnostics, keeping the first `limit` of each code, severity, and phase, and folding the rest (as well as any on the same line as a kept one) into the [`similar`](Diagnostic::similar) locations of the last kept one
+pub(crate) fn collapse(diagnostics: Vec<Diagnostic>, src: &str, limit: usize) -> Vec<Diagnostic> {
+	let index = LineIndex::new(src);
+	let line = |at: usize| index.line_col(at).line;
+
+	let mut lines = HashSet::new();
+	let mut kept = HashMap::<(&'static str, bool, Phase), (usize, usize)>::new();
+	let mut collapsed = Vec::<Diagnostic>::new();
+
+	for diag in diagnostics {
+		// the data of each is its own
+		if diag.data.is_some() {
+			collapsed.push(diag);
+			continue;
+		}
+
+		let new_line = lines.insert((diag.code, diag.err, diag.phase, line(diag.at)));
+
+		match kept.get_mut(&(diag.code, diag.err, diag.phase)) {
+			Some((count, last)) if *count >= limit || !new_line => {
+				collapsed[*last].similar.push(diag.at);
+			}
+			Some((count, last)) => {
+				*count += 1;
+				*last = collapsed.len();
+				collapsed.push(diag);
+			}
+			None => {
+				kept.insert((diag.code, diag.err, diag.phase), (1, collapsed.len()));
+				collapsed.push(diag);
+			}
+		}
+	}
+
+	collapsed
+}
+
+/// how many diagnostics of a code were reported with a severity in a phase, see [`summarize`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CodeSummary {
+	/// the code
+	pub code: &'static str,
+	/// the severity
+	pub severity: Severity,
+	/// what was being done when they were reported
+	pub phase: Phase,
+	/// how many there were, including those collapsed into others as [`similar`](Diagnostic::similar)
+	pub count: usize,
+	/// the earliest location in the source of any of them
+	pub first: usize,
+}
+
+/// group diagnostics by code, severity, and [`phase`](Diagnostic::phase), to see the shape of many at once, such as after a large change
+///
+/// errors come first, then codes reported more often, then those reported earlier in the source. diagnostics collapsed by [`MarkDoll::finish_collapsed`](crate::MarkDoll::finish_collapsed) are counted as the diagnostics they were, but those dropped by [`severity_overrides`](crate::MarkDoll::severity_overrides) are only counted by [`MarkDoll::suppressed`](crate::MarkDoll::suppressed)
+#[must_use]
+pub fn summarize(diagnostics: &[Diagnostic]) -> Vec<CodeSummary> {
+	let mut summaries = Vec::<CodeSummary>::new();
+	let mut index = HashMap::<(&'static str, bool, Phase), usize>::new();
+
+	for diag in diagnostics {
+		let i = *index
+			.entry((diag.code, diag.err, diag.phase))
+			.or_insert_with(|| {
+				summaries.push(CodeSummary {
+					code: diag.code,
+					severity: if diag.err {
+						Severity::Error
+					} else {
+						Severity::Warning
+					},
+					phase: diag.phase,
+					count: 0,
+					first: usize::MAX,
+				});
+				summaries.len() - 1
+			});
+
+		summaries[i].count += 1 + diag.similar.len();
+		summaries[i].first = diag
+			.similar
+			.iter()
+			.fold(summaries[i].first.min(diag.at), |first, at| first.min(*at));
+	}
+
+	summaries.sort_unstable_by(|a, b| {
+		(a.severity == Severity::Warning)
+			.cmp(&(b.severity == Severity::Warning))
+			.then(b.count.cmp(&a.count))
+			.then(a.first.cmp(&b.first))
+			.then(a.code.cmp(b.code))
+	});
+
+	summaries
+}
+
+/// an entry of the registry of diagnostic codes, see [`codes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Code {
+	/// the code, where `<name>` stands for the name of an argument or prop
+	pub code: &'static str,
+	/// what causes it and how to address it
+	pub explanation: &'static str,
+	/// markdoll source emitting it with the standard tags, or empty if it takes more than source, such as a bibliography or several documents
+	pub example: &'static str,
+}
+
+impl Code {
+	/// whether a code emitted by a [`Diagnostic`] is this entry's code, filling in any `<name>`
+	#[must_use]
+	pub fn matches(&self, code: &str) -> bool {
+		match self.code.split_once('<') {
+			Some((prefix, rest)) => {
+				let suffix = rest.split_once('>').map_or("", |(_, suffix)| suffix);
+
+				code.len() > prefix.len() + suffix.len()
+					&& code.starts_with(prefix)
+					&& code.ends_with(suffix)
+					&& !code[prefix.len()..code.len() - suffix.len()].contains(' ')
+			}
+			None => self.code == code,
+		}
+	}
+}
+
+macro_rules! codes {
+	($($code:literal: $explanation:literal, $example:literal;)*) => {
+		&[$(Code {
+			code: $code,
+			explanation: $explanation,
+			example: $example,
+		}),*]
+	};
+}
+
+/// every code emitted by markdoll and its standard tags
+static CODES: &[Code] = codes! {
+	// parsing
+	"markdoll does not support CRLF, fatal parsing error": "the source has a carriage return, convert its line endings to LF", "";
+	"erroneous leading spaces": "a line is indented with spaces, which markdoll doesn't count as indentation, indent with tabs", "[em::\n\t  text\n]";
+	"unusual whitespace in indentation": "a line starts with whitespace that looks like indentation but isn't, such as a no-break or zero-width space, which is kept as text, indent with tabs", "[em::\n\t\u{a0}text\n]";
+	"control character": "the source contains a control character, such as a bidirectional override that makes it read differently than it parses, which is left out of the output", "a\u{202e}b";
+	"unexpected indentation": "a line is indented more than the block it is in, or a tab is in a place that can't be indented", "text\n\tindented";
+	"section skips a level": "a section is nested more than one level deeper than the section it is in, usually because it is indented too far, so its heading skips a level, such as from `h2` to `h4`, which screen readers announce as missing structure", "&a\n\t\t&b\n\t\t\tc";
+	"unexpected list (expected indent)": "a list was started right inside a block tag, indent the tag's content first", "";
+	"cannot start list item mid-tag": "a list item was started on the line that opens a block tag, put it on the next line", "";
+	"list item value too large": "an ordered list item's marker gives it a number too large to store, such as `=99999999999999999999.`", "=99999999999999999999.\titem";
+	"cannot escape newline in this context": "a backslash before a line break inside tag arguments or inline content, which can't span lines", "[em:a\\\n]";
+	"cannot escape indentation in this context": "a backslash before a tab inside tag arguments or inline content", "[em:a\\\t]";
+	"unexpected newline": "a line break inside tag arguments or inline content, use the `::` block syntax for content spanning lines", "[em:a\n]";
+	"expected newline": "the `::` of a block tag must be followed by a line break", "[em::a]";
+	"unexpected EOI": "the source ended inside a tag, close it with `]`", "[em:a";
+	"unclosed bracket in tag content": "a `[` in the content of a line tag was never closed before the line ended, escape it as `\\[` if it is literal", "[em:a [b";
+	"unexpected EOI, expected newline": "the source ended where a line break was required", "";
+	"text after frontmatter fence": "the document starts with dashes followed by text on the same line, which is parsed as text rather than opening frontmatter, the fence must be alone on its line", "--- title: a\n---";
+	"frontmatter fence has too few dashes": "the document starts with a `--` line, which is parsed as text rather than opening frontmatter, fences have three or more dashes", "--\ntitle: a\n--";
+	"trailing whitespace after frontmatter fence": "a frontmatter fence has spaces or tabs after its dashes, which are ignored", "--- \ntitle: a\n---";
+	"unexpected EOI in frontmatter": "the frontmatter was never closed with a line of as many dashes as opened it", "---\ntitle: a\n";
+	"misaligned closing tag for this tag": "the `]` closing a block tag is indented less than the line opening it", "&section\n\t[em::\n\t\ttext\n]";
+	"unterminated root": "the source ended before the document did", "";
+	"unterminated list": "the source ended inside a list", "";
+	"unterminated section": "the source ended inside a section", "";
+	"unterminated tag block": "a block tag was never closed with `]` at the indentation of the line opening it, reported at the tag along with where its content ended", "[em::\n\ttext";
+	"tag not defined": "no tag with this name was added to the `ExtensionSystem`, check its spelling or import its module", "[nonexistent:text]";
+	"section has no name": "a line starts with `&` and nothing else, add the section's name after it or remove it", "&\ntext";
+	"document has too few words": "the document has fewer words than the minimum it was checked against, such as with `--min-words`, often because it is a stub", "";
+	"document has no headings": "the document has no sections, though it was checked for them, such as with `--require-headings`", "";
+	"section has no content": "a section has nothing in it, only reported when given a severity in `MarkDoll::severity_overrides`, as by the strict profile", "";
+	"tag has no content": "a tag that shows its content, such as `em` or `link`, is empty, usually because a mistyped `]` closed it early, so it shows nothing, only reported when given a severity in `MarkDoll::severity_overrides`, as by the strict profile", "";
+	"tag does not support content": "the tag was given content, but it only takes arguments", "";
+	"source limit exceeded": "parsing kept more source than `MarkDoll::max_source_bytes` or `MarkDoll::max_buffers` allow, reported at the tag whose content went past them, and the rest was left unparsed", "";
+	"parsing cancelled": "`MarkDoll::cancel` was set while parsing, so the rest of the source was left unparsed", "";
+	"parsing took too long": "parsing took longer than `MarkDoll::max_duration`, so the rest of the source was left unparsed", "";
+	"tags nested too deeply": "tags nest within the content of other tags deeper than `MarkDoll::max_tag_depth`, so the content of the deepest is kept as text rather than parsed", "";
+	"tag not allowed in untrusted documents": "the tag writes content into the output as is, which documents parsed with `MarkDoll::untrusted` can't do, so it was left out", "";
+	"tag denied": "the tag isn't allowed where the document is used, as decided by `MarkDoll::tag_filter`, so it was left out, see the help for why", "";
+
+	// arguments
+	"argument <name> required": "a required argument of the tag is missing, as when only flags and props are given, see its documentation for the arguments it takes", "[var]";
+	"arg <name> invalid": "the named argument couldn't be parsed, such as a word where a number was expected", "[columns(two)::\n\ta\n]";
+	"prop <name> invalid": "a prop couldn't be parsed, such as a word where a number was expected", "[codeblock(linenos=one)::\n\tcode\n]";
+	"empty argument": "a tag was given `()` with nothing between the parens, which is kept as an empty argument, usually because the argument was forgotten, a required argument left empty is reported as missing", "[link():text]";
+	"positional argument after named": "positional arguments must come before flags and props, a positional argument after one would be ignored, and a required argument given as one would take its place", "[tc(head)(rows=2)(stray):cell]";
+	"cannot have content": "the tag was given content, but takes all it needs as arguments", "[var(name):content]";
+
+	// emitting
+	"this tag cannot be emitted": "the tag has no emitters at all, it is probably meant to be handled by a pass before emitting", "";
+	"this tag does not support emitting for this emit target": "the tag has emitters, but not for the target being emitted to", "";
+	"no built-in emitters for this emit target": "no `BuiltInEmitters` were set for the target being emitted to with `MarkDoll::set_emitters`, so nothing was emitted, check `MarkDoll::has_emitters_for` first", "";
+	"deferred emit within deferred emit": "a tag deferred its output while emitting output deferred by another, which would never be emitted", "";
+	"id already used in this document": "a `def`, `term`, or reference emits an HTML id that was already emitted, so it was given a suffix such as `-2` and links to the id go to the first", "[def(setup):a]\n\n[def(setup):b]";
+	"nested too deeply to emit": "blocks nest deeper than `MarkDoll::max_emit_depth`, so the deepest were left out", "";
+	"url scheme not allowed": "a url has a scheme other than `http`, `https`, or `mailto`, such as `javascript:`, which `HtmlEmit::safe_urls` doesn't allow, so `#` was written instead", "";
+	"asset url could not be rewritten": "`HtmlEmit::url_rewriter` couldn't rewrite a url, such as a local file that couldn't be read or copied, so it was written as it is", "";
+	"output too large": "the output grew past `HtmlEmit::max_output_bytes`, so the rest of the document was left out", "";
+	"internal error": "markdoll panicked, which is a bug, please report it with the source", "";
+
+	// projects
+	"id already defined in another document": "a `def` with this id is already defined by another document of the project", "";
+	"id defined again in another document": "the `def` with this id is defined again by a later document of the project", "";
+	"id not defined in any document": "a `ref` names an id no document of the project defines", "";
+
+	// tags
+	"abbreviation not defined": "the `abbr` tag has no expansion, and no `abbrdef` tag before it defines one", "[abbr:HTML]";
+	"lang code invalid": "the code of a `lang` tag should be a BCP 47 language tag such as `de` or `pt-BR`, of letters, digits, and hyphens", "[lang(en_US):text]";
+	"citation key not found": "the bibliography has no reference with the key of the `cite` tag", "";
+	"no bibliography for citation": "citations are emitted without a bibliography to resolve them against, set `HtmlEmit::bibliography`", "[cite(doe2024)]";
+	"references has no citations": "the `references` tag was emitted without any `cite` tags collected before it", "[references]";
+	"failed to highlight code": "the highlighter failed on a line of the code, it is written unhighlighted", "";
+	"highlight theme not found": "the highlighter has no theme by the given name", "";
+	"highlighted lines are outside of the code": "the `hl` prop of a code block names lines past its end", "[codeblock(hl=4)::\n\tone line\n]";
+	"language not recognized, not highlighting": "the highlighter doesn't know the language of the code block", "";
+	"prop hl invalid, expected lines or ranges such as `1,4-6,12`": "the `hl` prop of a code block couldn't be parsed", "[codeblock(hl=one)::\n\tcode\n]";
+	"condition invalid": "a term of the `if` tag's condition is empty", "[if(,):text]";
+	"flag not defined": "the condition of an `if` tag names a flag that isn't defined, so it is treated as unset", "[if(preview):text]";
+	"diagram kind not registered": "no renderer for the diagram's kind is in `HtmlEmit::diagram_renderers`", "[diagram(mermaid)::\n\tgraph TD\n]";
+	"emoji not found": "there is no emoji with this name", "[emoji(nonexistent)]";
+	"index has no entries": "the `index` tag was emitted without any `ix` tags collected before it", "[index]";
+	"columns must be at least 2": "the `columns` tag was given a count below 2", "[columns(1)::\n\ta\n]";
+	"columns nested within columns": "a `columns` tag is inside another, which rarely lays out well", "[columns::\n\t[columns::\n\t\ta\n\t]\n]";
+	"autoplay is blocked unless muted": "browsers don't autoplay media with sound, add the `muted` flag", "[video(clip.mp4)(autoplay)]";
+	"poster is only used by video": "the `poster` prop was given to an `audio` tag", "[audio(song.mp3)(poster=cover.png)]";
+	"image has no alt text": "an `img` tag has no content to describe it to people who can't see it, add some or pass the `decorative` flag if it only decorates", "[img(chart.png)]";
+	"decorative image has alt text": "an `img` tag with the `decorative` flag has content, which is left out as assistive technology skips the image", "[img(divider.png)(decorative):divider]";
+	"page not found": "the page resolver doesn't know the page, or knows it doesn't exist", "";
+	"image not found": "the asset resolver knows the source of an `img` tag doesn't exist", "";
+	"element left unclosed": "while checking the balance of the output, a tag's emitter opened an element it never closed, or closed one enclosing it first", "";
+	"element closed without being opened": "while checking the balance of the output, a tag's emitter closed an element that isn't open", "";
+	"qr data too long for ecc": "the data doesn't fit in a QR code at the error correction level, shorten it or lower the `ecc` prop", "";
+	"qr ecc invalid": "the `ecc` prop of a `qr` tag must be one of `l`, `m`, `q`, or `h`", "[qr(hello)(ecc=x)]";
+	"`table` tags may only contain lists and `tr` tags": "a `table` tag's content has something other than rows", "[table::\n\ttext\n]";
+	"tr tags may only contain lists and `tc` tags": "a `tr` tag's content has something other than cells", "[table::\n\t[tr::\n\t\ttext\n\t]\n]";
+	"no unordered list to use as foot rows": "the `foot` flag was given to a table with no unordered list of rows", "";
+	"rows cannot be both head and foot rows": "a row of a table was marked as both a head and foot row", "";
+	"row has an inconsistent amount of columns": "a row of a table spans a different amount of columns than the rows before it", "[table::\n\t-\t-\ta\n\t\t-\tb\n\t-\t-\tc\n]";
+	"rowspan extends past the last row": "a cell of a table spans more rows than follow it", "";
+	"cell span must be at least 1": "the `rows` or `cols` prop of a `tc` tag was given 0", "[table::\n\t[tr::\n\t\t[tc(rows=0):a]\n\t]\n]";
+	"cell align invalid": "the `align` prop of a `tc` tag must be one of `left`, `center`, or `right`", "[table::\n\t[tr::\n\t\t[tc(align=justify):a]\n\t]\n]";
+	"cell valign invalid": "the `valign` prop of a `tc` tag must be one of `top`, `middle`, or `bottom`", "[table::\n\t[tr::\n\t\t[tc(valign=baseline):a]\n\t]\n]";
+	"unterminated quote in csv record": "a quoted field of a `tablecsv` tag was never closed", "[tablecsv::\n\t\"a,b\n]";
+	"`tabs` tags may only contain `tab` tags": "a `tabs` tag's content has something other than `tab` tags", "[tabs::\n\ttext\n]";
+	"no tabs in `tabs` tag": "a `tabs` tag has no `tab` tags", "[tabs]";
+	"no time reference for relative time": "a relative time was emitted without `HtmlEmit::time_reference`, so it is written as absolute", "[time(2024-01-01)(relative)]";
+	"time format invalid": "the `format` prop of a `time` tag must be one of `iso`, `date`, `datetime`, or `weekday`", "[time(2024-01-01)(format=short)]";
+	"time year invalid": "the year of a time must be 4 digits", "[time(24-01-01)]";
+	"time month invalid": "the month of a time must be 2 digits from 01 to 12", "[time(2024-13-01)]";
+	"time day invalid": "the day of a time must be 2 digits within the month", "[time(2024-02-30)]";
+	"time hour invalid": "the hour of a time must be 2 digits from 00 to 23", "[time(2024-01-01T24:00)]";
+	"time minute invalid": "the minute of a time must be 2 digits from 00 to 59", "[time(2024-01-01T12:60)]";
+	"time second invalid": "the second of a time must be 2 digits from 00 to 59", "[time(2024-01-01T12:00:60)]";
+	"time offset invalid": "the offset of a time must be `Z`, or a sign followed by hours and minutes", "[time(2024-01-01T12:00+25:00)]";
+	"time has trailing characters": "a time has more after its offset", "[time(2024-01-01T12:00Zextra)]";
+	"todo in release": "a `todo` tag was emitted with `DraftPolicy::Error`, address it or remove it", "[todo:write this]";
+	"comment in release": "a comment kept with `MarkDoll::keep_comments` was emitted with `DraftPolicy::Error`, address it or remove it", "";
+	"variable not defined": "no variable with this name is defined in `HtmlEmit::vars`, `MarkDoll::arg_vars`, or the frontmatter", "[var(nonexistent)]";
+};
+
+/// every code emitted by markdoll and its standard tags, with an explanation and example of each
+///
+/// codes emitted by the `args!` macro contain the name of the argument, written as `<name>`, use [`explain`] to find the entry of a code
+#[must_use]
+pub fn codes() -> &'static [Code] {
+	CODES
+}
+
+/// the entry of a code in [`codes`], if it has one
+#[must_use]
+pub fn explain(code: &str) -> Option<&'static Code> {
+	CODES.iter().find(|entry| entry.matches(code))
+}
+
+/// a preset of [`MarkDoll::severity_overrides`](crate::MarkDoll::severity_overrides), applied with [`MarkDoll::set_profile`](crate::MarkDoll::set_profile)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+	/// every diagnostic at its own severity
+	#[default]
+	Default,
+	/// warnings about the document itself are errors, such as suspicious whitespace, input a tag ignores, or references to things that aren't defined
+	///
+	/// warnings about the environment, such as a missing bibliography or highlighter theme, are left as they are
+	Strict,
+	/// errors about content that can be left out are warnings, and warnings about style are suppressed
+	Relaxed,
+}
+
+impl Profile {
+	/// the codes this profile overrides and their severities, as in [`MarkDoll::severity_overrides`](crate::MarkDoll::severity_overrides)
+	///
+	/// each code has an entry in [`codes`]
+	#[must_use]
+	pub fn overrides(self) -> &'static [(&'static str, Option<bool>)] {
+		match self {
+			Self::Default => &[],
+			Self::Strict => &[
+				("erroneous leading spaces", Some(true)),
+				("trailing whitespace after frontmatter fence", Some(true)),
+				("section has no name", Some(true)),
+				("section has no content", Some(false)),
+				("tag has no content", Some(false)),
+				("poster is only used by video", Some(true)),
+				("autoplay is blocked unless muted", Some(true)),
+				("image has no alt text", Some(true)),
+				("decorative image has alt text", Some(true)),
+				("columns nested within columns", Some(true)),
+				("abbreviation not defined", Some(true)),
+				("lang code invalid", Some(true)),
+				("citation key not found", Some(true)),
+				("flag not defined", Some(true)),
+				("page not found", Some(true)),
+				("image not found", Some(true)),
+				("variable not defined", Some(true)),
+				("highlighted lines are outside of the code", Some(true)),
+				("row has an inconsistent amount of columns", Some(true)),
+				("rowspan extends past the last row", Some(true)),
+				("no unordered list to use as foot rows", Some(true)),
+				("index has no entries", Some(true)),
+				("references has no citations", Some(true)),
+				("no tabs in `tabs` tag", Some(true)),
+				("id already used in this document", Some(true)),
+				("section skips a level", Some(true)),
+			],
+			Self::Relaxed => &[
+				("tag not defined", Some(false)),
+				("emoji not found", Some(false)),
+				("misaligned closing tag for this tag", Some(false)),
+				("erroneous leading spaces", None),
+				("trailing whitespace after frontmatter fence", None),
+				("columns nested within columns", None),
+			],
+		}
+	}
+}
+
+/// options for rendering [`Diagnostic`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "ariadne")]
+pub struct RenderOptions {
+	/// whether to color the output with ansi escapes
+	pub color: bool,
+	/// whether to render more compactly, with less spacing around labels
+	pub compact: bool,
+	/// how many columns a tab is rendered as
+	pub tab_width: usize,
+}
+
+#[cfg(feature = "ariadne")]
+impl Default for RenderOptions {
+	fn default() -> Self {
+		Self {
+			color: true,
+			compact: false,
+			tab_width: 4,
+		}
+	}
+}
+
 /// render [`Diagnostic`]s to ariadne [`Reports`](ariadne::Report)
 #[must_use]
+#[cfg(feature = "ariadne")]
+pub fn render(diagnostics: &[Diagnostic]) -> Vec<Report<'static>> {
+	render_with(diagnostics, RenderOptions::default())
+}
+
+/// render [`Diagnostic`]s to ariadne [`Reports`](ariadne::Report) with the given options
+#[must_use]
 #[allow(
 	clippy::range_plus_one,
 	reason = "does not account for RangeInclusive not being accepted"
 )]
 #[cfg(feature = "ariadne")]
-pub fn render(diagnostics: &[Diagnostic]) -> Vec<Report<'static>> {
+pub fn render_with(diagnostics: &[Diagnostic], options: RenderOptions) -> Vec<Report<'static>> {
+	let config = ariadne::Config::default()
+		.with_color(options.color)
+		.with_compact(options.compact)
+		.with_tab_width(options.tab_width);
+
 	diagnostics
 		.iter()
 		.map(|diag| {
@@ -140,13 +963,34 @@ pub fn render(diagnostics: &[Diagnostic]) -> Vec<Report<'static>> {
 				(),
 				diag.at,
 			)
+			.with_config(config)
 			.with_message(diag.code)
 			.with_label(
-				Label::new(diag.at..diag.at + 1)
+				Label::new(diag.at..diag.at + diag.len)
 					.with_color(ariadne::Color::Magenta)
 					.with_message(diag.code),
 			);
 
+			if let Some((at, message)) = diag.context {
+				builder.add_label(
+					Label::new(at..at + 1)
+						.with_color(ariadne::Color::Cyan)
+						.with_message(message),
+				);
+			}
+
+			for at in diag.similar.iter().take(3) {
+				builder.add_label(
+					Label::new(*at..*at + 1)
+						.with_color(ariadne::Color::Blue)
+						.with_message("similar"),
+				);
+			}
+
+			if let Some(help) = diag.help() {
+				builder.set_help(help);
+			}
+
 			#[cfg(debug_assertions)]
 			builder.set_note(alloc::format!("originated from {}", diag.src));
 
@@ -154,3 +998,36 @@ pub fn render(diagnostics: &[Diagnostic]) -> Vec<Report<'static>> {
 		})
 		.collect()
 }
+
+/// render [`Diagnostic`]s against the root source `src`, returning one string per diagnostic
+#[must_use]
+#[cfg(feature = "ariadne")]
+pub fn render_all(diagnostics: &[Diagnostic], src: &str, options: RenderOptions) -> Vec<String> {
+	let mut cache = ariadne::Source::from(src);
+
+	render_with(diagnostics, options)
+		.into_iter()
+		.map(|report| {
+			let mut out = Vec::new();
+			report.write(&mut cache, &mut out).unwrap();
+			String::from_utf8(out).unwrap()
+		})
+		.collect()
+}
+
+/// render [`Diagnostic`]s against the root source `src` into a writer
+#[cfg(feature = "ariadne")]
+pub fn render_to(
+	mut to: impl std::io::Write,
+	diagnostics: &[Diagnostic],
+	src: &str,
+	options: RenderOptions,
+) -> std::io::Result<()> {
+	let mut cache = ariadne::Source::from(src);
+
+	for report in render_with(diagnostics, options) {
+		report.write(&mut cache, &mut to)?;
+	}
+
+	Ok(())
+}
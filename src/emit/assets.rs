@@ -0,0 +1,207 @@
+use alloc::string::{String, ToString};
+
+/// what a url passed to a [`UrlRewriter`](super::UrlRewriter) is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AssetKind {
+	/// the target of a `link`
+	Link,
+	/// the source of an `img`, or the poster of a `video`
+	Image,
+	/// a source of a `video`
+	Video,
+	/// a source of an `audio`
+	Audio,
+}
+
+/// what an [`AssetResolver`](super::AssetResolver) knows of an asset, such as the source of an `img` tag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetInfo {
+	/// whether the asset exists, assets that don't are warned of
+	pub exists: bool,
+	/// the width in pixels, written unless the tag gives one
+	pub width: Option<u32>,
+	/// the height in pixels, written unless the tag gives one
+	pub height: Option<u32>,
+	/// the media type, such as `image/png`
+	pub mime: Option<String>,
+}
+
+/// the media type and dimensions of a PNG, JPEG, GIF, or WebP image from the start of its file, or `None` if it isn't one or is cut off before its dimensions
+#[must_use]
+pub fn sniff(bytes: &[u8]) -> Option<AssetInfo> {
+	let be16 = |at: usize| {
+		Some(u32::from(u16::from_be_bytes(
+			bytes.get(at..at + 2)?.try_into().ok()?,
+		)))
+	};
+	let le16 = |at: usize| {
+		Some(u32::from(u16::from_le_bytes(
+			bytes.get(at..at + 2)?.try_into().ok()?,
+		)))
+	};
+	let be32 = |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+	let le24 = |at: usize| {
+		let bytes = bytes.get(at..at + 3)?;
+		Some(u32::from(bytes[0]) | u32::from(bytes[1]) << 8 | u32::from(bytes[2]) << 16)
+	};
+
+	let (mime, width, height) = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+		// the IHDR chunk comes first
+		("image/png", be32(16)?, be32(20)?)
+	} else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+		("image/gif", le16(6)?, le16(8)?)
+	} else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+		match bytes.get(12..16)? {
+			// lossy, after the frame tag and start code
+			b"VP8 " => ("image/webp", le16(26)? & 0x3fff, le16(28)? & 0x3fff),
+			// lossless, 14 bits each less one, after the signature
+			b"VP8L" => {
+				let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+				("image/webp", (bits & 0x3fff) + 1, (bits >> 14 & 0x3fff) + 1)
+			}
+			// extended, 24 bits each less one, after the flags
+			b"VP8X" => ("image/webp", le24(24)? + 1, le24(27)? + 1),
+			_ => return None,
+		}
+	} else if bytes.starts_with(b"\xff\xd8") {
+		// walk the segments to the first start of frame
+		let mut at = 2;
+		loop {
+			if *bytes.get(at)? != 0xff {
+				return None;
+			}
+
+			match *bytes.get(at + 1)? {
+				// padding
+				0xff => at += 1,
+				// start of frame, other than the huffman, arithmetic coding, and jpeg-ls markers that share the range
+				0xc0..=0xcf if !matches!(bytes[at + 1], 0xc4 | 0xc8 | 0xcc) => {
+					break ("image/jpeg", be16(at + 7)?, be16(at + 5)?);
+				}
+				// markers without a length
+				0x01 | 0xd0..=0xd9 => at += 2,
+				_ => at += 2 + usize::try_from(be16(at + 2)?).ok()?,
+			}
+		}
+	} else {
+		return None;
+	};
+
+	Some(AssetInfo {
+		exists: true,
+		width: Some(width),
+		height: Some(height),
+		mime: Some(mime.to_string()),
+	})
+}
+
+/// the file a local url refers to relative to `root`, without any query or fragment, or `None` if it escapes `root`, such as with `../`
+///
+/// a leading `/` is relative to `root` too. this is the file [`fs_resolver`] and [`fs_copier`] read
+#[cfg(feature = "fs-assets")]
+#[must_use]
+pub fn fs_asset_path(root: &std::path::Path, url: &str) -> Option<std::path::PathBuf> {
+	use std::path::{Component, Path};
+
+	let path = url.split(['?', '#']).next().unwrap_or_default();
+
+	let mut resolved = root.to_path_buf();
+	let mut depth = 0_usize;
+	for component in Path::new(path.trim_start_matches('/')).components() {
+		match component {
+			Component::Normal(name) => {
+				resolved.push(name);
+				depth += 1;
+			}
+			Component::CurDir => {}
+			Component::ParentDir => {
+				depth = depth.checked_sub(1)?;
+				resolved.pop();
+			}
+			Component::RootDir | Component::Prefix(_) => return None,
+		}
+	}
+
+	Some(resolved)
+}
+
+/// a resolver of local assets relative to `root`, probing images with [`sniff`]
+///
+/// remote assets, such as `https://` URLs, and those escaping `root` aren't resolved, see [`fs_asset_path`]
+#[cfg(feature = "fs-assets")]
+#[must_use]
+pub fn fs_resolver(root: impl Into<std::path::PathBuf>) -> alloc::sync::Arc<super::AssetResolver> {
+	use std::io::Read;
+
+	// how much of a file to read, which covers the metadata some JPEGs have before their frame
+	const HEADER_LEN: u64 = 1 << 18;
+
+	let root = root.into();
+
+	alloc::sync::Arc::new(move |src: &str| {
+		if !crate::dependencies::is_local(src) {
+			return None;
+		}
+
+		let path = fs_asset_path(&root, src)?;
+
+		let Ok(file) = std::fs::File::open(path) else {
+			return Some(AssetInfo::default());
+		};
+
+		let mut header = alloc::vec::Vec::new();
+		Some(
+			file.take(HEADER_LEN)
+				.read_to_end(&mut header)
+				.ok()
+				.and_then(|_| sniff(&header))
+				.unwrap_or(AssetInfo {
+					exists: true,
+					..AssetInfo::default()
+				}),
+		)
+	})
+}
+
+/// a rewriter copying local assets relative to `root` into `out`, named by a hash of their content, and rewriting their urls to `out` joined with that name
+///
+/// the extension, query, and fragment are kept, and files already copied aren't written again. urls of remote assets, such as `https://` URLs, are kept as they are. assets that escape `root`, see [`fs_asset_path`], or can't be read or written aren't rewritten, so [`HtmlEmit::asset_url`](super::HtmlEmit::asset_url) warns of them
+#[cfg(feature = "fs-assets")]
+#[must_use]
+pub fn fs_copier(
+	root: impl Into<std::path::PathBuf>,
+	out: impl Into<std::path::PathBuf>,
+) -> alloc::sync::Arc<super::UrlRewriter> {
+	use core::hash::Hasher;
+
+	let root = root.into();
+	let out = out.into();
+	let prefix = out.to_string_lossy().replace('\\', "/");
+	let prefix = prefix.trim_end_matches('/').to_string();
+
+	alloc::sync::Arc::new(move |url: &str, _| {
+		if !crate::dependencies::is_local(url) {
+			return Some(url.to_string());
+		}
+
+		let end = url.find(['?', '#']).unwrap_or(url.len());
+		let (path, suffix) = url.split_at(end);
+		let bytes = std::fs::read(fs_asset_path(&root, path)?).ok()?;
+
+		let mut hash = crate::tree::fingerprint::Fnv::default();
+		hash.write(&bytes);
+		let name = match std::path::Path::new(path).extension() {
+			Some(ext) => alloc::format!("{:016x}.{}", hash.finish(), ext.to_string_lossy()),
+			None => alloc::format!("{:016x}", hash.finish()),
+		};
+
+		let dest = out.join(&name);
+		if !dest.exists() {
+			std::fs::create_dir_all(&out).ok()?;
+			std::fs::write(dest, bytes).ok()?;
+		}
+
+		Some(alloc::format!("{prefix}/{name}{suffix}"))
+	})
+}
@@ -0,0 +1,84 @@
+use {
+	crate::MarkDoll,
+	alloc::{
+		string::{String, ToString},
+		vec::Vec,
+	},
+};
+
+/// an element opened with [`HtmlEmit::write_open`](super::HtmlEmit::write_open) and not closed yet
+#[derive(Debug, Clone)]
+struct Open {
+	name: String,
+	/// the position and length of the tag being emitted when it was opened
+	tag: (usize, usize),
+}
+
+/// the elements open so far in an emit, kept in [`MarkDoll::scratch`] while [`check_balance`](super::HtmlEmit::check_balance) is set
+#[derive(Debug, Clone, Default)]
+struct OpenElements(Vec<Open>);
+
+/// the tag being emitted, as diagnostics at it are positioned, or the start of the source outside any tag
+fn current_tag(doll: &mut MarkDoll) -> (usize, usize) {
+	if doll.diagnostic_translations.len() < 2 {
+		(0, 1)
+	} else {
+		doll.resolve_tag_span()
+	}
+}
+
+/// report an imbalance at a tag already resolved with [`current_tag`]
+fn report(doll: &mut MarkDoll, (at, len): (usize, usize), code: &'static str) {
+	let count = doll.diagnostics.len();
+	doll.diag(true, 0, code);
+
+	// unless it was suppressed
+	if doll.diagnostics.len() > count {
+		let diag = doll.diagnostics.last_mut().unwrap();
+		diag.at = at;
+		diag.len = len;
+	}
+}
+
+/// track an element opened by the tag being emitted
+pub(crate) fn open(doll: &mut MarkDoll, name: &str) {
+	let tag = current_tag(doll);
+
+	doll.scratch
+		.get_or_insert_with(OpenElements::default)
+		.0
+		.push(Open {
+			name: name.to_string(),
+			tag,
+		});
+}
+
+/// track an element closed by the tag being emitted, reporting the elements it skips over as unclosed, or itself if it was never opened
+pub(crate) fn close(doll: &mut MarkDoll, name: &str) {
+	let open = &mut doll.scratch.get_or_insert_with(OpenElements::default).0;
+
+	let Some(index) = open.iter().rposition(|element| element.name == name) else {
+		let tag = current_tag(doll);
+		report(doll, tag, "element closed without being opened");
+		return;
+	};
+
+	let skipped = open.split_off(index);
+	// leaving nothing for the rest of the emit once everything is closed
+	if open.is_empty() {
+		doll.scratch.remove::<OpenElements>();
+	}
+
+	for element in &skipped[1..] {
+		report(doll, element.tag, "element left unclosed");
+	}
+}
+
+/// report the elements left open at the end of an emit, if any were tracked
+pub(crate) fn finish(doll: &mut MarkDoll) {
+	if let Some(open) = doll.scratch.remove::<OpenElements>() {
+		for element in open.0 {
+			report(doll, element.tag, "element left unclosed");
+		}
+	}
+}
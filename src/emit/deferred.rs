@@ -0,0 +1,112 @@
+use {
+	crate::{MarkDoll, TagDiagnosticTranslation},
+	alloc::{sync::Arc, vec::Vec},
+};
+
+/// the output of a tag deferred with [`DeferredEmit::defer`]
+pub type DeferredEmitter<To> = dyn Fn(&mut MarkDoll, &mut To) + Send + Sync;
+
+/// an emit target whose tags can leave part of their output until the rest of the document has been emitted, such as for a table of contents or a list of references, see [`defer`](Self::defer)
+pub trait DeferredEmit: Sized + 'static {
+	/// the position the next output will be written at, which deferred output is spliced in at
+	fn position(&self) -> usize;
+
+	/// call `emit` to emit into this target as if it were writing at `at`, an earlier [`position`](Self::position), and splice what it writes in there
+	fn splice(&mut self, doll: &mut MarkDoll, at: usize, emit: &DeferredEmitter<Self>);
+
+	/// leave the output of a tag for later, calling `emit` once the whole document has been emitted, with the [`scratch`](MarkDoll::scratch) state the rest of the document left, and splicing what it writes in here
+	///
+	/// call this from a tag's emitter. deferred output is emitted in the order it was deferred, after the rest of an [`emit`](MarkDoll::emit), [`emit_block`](MarkDoll::emit_block), or each block of an [`emit_parallel`](MarkDoll::emit_parallel). diagnostics reported while emitting it are positioned as if in the tag, and deferring again while emitting deferred output is an error
+	fn defer(
+		&mut self,
+		doll: &mut MarkDoll,
+		emit: impl Fn(&mut MarkDoll, &mut Self) + Send + Sync + 'static,
+	) {
+		if doll.scratch.get_ref::<Deferring>().is_some() {
+			doll.diag(true, usize::MAX, "deferred emit within deferred emit");
+			return;
+		}
+
+		let at = self.position();
+		let translations = doll.diagnostic_translations.clone();
+
+		doll.scratch
+			.get_or_insert_with(|| Deferred::<Self> {
+				entries: Vec::new(),
+				run: run::<Self>,
+			})
+			.entries
+			.push(Entry {
+				at,
+				translations,
+				emit: Arc::new(emit),
+			});
+	}
+}
+
+/// kept in [`MarkDoll::scratch`] while deferred output is being emitted
+#[derive(Debug, Clone)]
+struct Deferring;
+
+/// output deferred by a tag
+struct Entry<To> {
+	/// where it is spliced in
+	at: usize,
+	/// the diagnostic translations of the tag
+	translations: Vec<TagDiagnosticTranslation>,
+	/// how to emit it
+	emit: Arc<DeferredEmitter<To>>,
+}
+
+impl<To> Clone for Entry<To> {
+	fn clone(&self) -> Self {
+		Self {
+			at: self.at,
+			translations: self.translations.clone(),
+			emit: Arc::clone(&self.emit),
+		}
+	}
+}
+
+/// the output deferred so far in an emit, kept in [`MarkDoll::scratch`]
+struct Deferred<To> {
+	entries: Vec<Entry<To>>,
+	/// emits the entries, which needs to know `To` is a [`DeferredEmit`]
+	run: fn(&mut MarkDoll, &mut To, Vec<Entry<To>>),
+}
+
+impl<To> Clone for Deferred<To> {
+	fn clone(&self) -> Self {
+		Self {
+			entries: self.entries.clone(),
+			run: self.run,
+		}
+	}
+}
+
+fn run<To: DeferredEmit>(doll: &mut MarkDoll, to: &mut To, entries: Vec<Entry<To>>) {
+	doll.scratch.put(Deferring);
+
+	// each splice moves the positions after it
+	let mut shift = 0;
+
+	for entry in entries {
+		let translations =
+			core::mem::replace(&mut doll.diagnostic_translations, entry.translations);
+
+		let before = to.position();
+		to.splice(doll, entry.at + shift, &*entry.emit);
+		shift += to.position() - before;
+
+		doll.diagnostic_translations = translations;
+	}
+
+	doll.scratch.remove::<Deferring>();
+}
+
+/// emit the output deferred while emitting to `to`, if any was
+pub(crate) fn run_deferred<To: 'static>(doll: &mut MarkDoll, to: &mut To) {
+	if let Some(deferred) = doll.scratch.remove::<Deferred<To>>() {
+		(deferred.run)(doll, to, deferred.entries);
+	}
+}
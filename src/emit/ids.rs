@@ -0,0 +1,252 @@
+use {
+	alloc::{
+		format,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	hashbrown::HashMap,
+};
+
+/// text as an id or URL path segment, lowercased with each run of other characters than letters and digits replaced by `-`, and none at either end
+///
+/// letters and digits of any script are kept, so `Über Café!` becomes `über-café`
+#[must_use]
+pub fn slugify(text: &str) -> String {
+	text.split(|ch: char| !ch.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>()
+		.join("-")
+}
+
+/// text as an HTML id that is also a CSS identifier, so `#id` selects it without escaping, for ids derived from text the author wrote
+///
+/// letters and digits of any script, `-`, and `_` are kept, and anything else replaced by `-`. like those [`generate`](IdAllocator::generate)d, it is prefixed with `id-` unless it starts with a letter or `_`, and is `id` if the text is empty
+#[must_use]
+pub fn html_id(text: &str) -> String {
+	let id = text
+		.chars()
+		.map(|ch| {
+			if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+				ch
+			} else {
+				'-'
+			}
+		})
+		.collect::<String>();
+
+	match id.chars().next() {
+		Some(ch) if ch.is_alphabetic() || ch == '_' => id,
+		Some(_) => format!("id-{id}"),
+		None => "id".to_string(),
+	}
+}
+
+/// hands out ids unique within a document, for any emit target, such as the HTML ids of [`HtmlEmit`](super::HtmlEmit) or labels of another format
+///
+/// an id is taken as is the first time, and with the first free suffix of `-2`, `-3`, and so on after that
+#[derive(Debug, Clone, Default)]
+pub struct IdAllocator {
+	/// how many times each id has been asked for
+	taken: HashMap<String, usize>,
+	/// where in the source each id was claimed, for diagnostics about claiming it again
+	claimed_at: HashMap<String, usize>,
+	/// how many of each kind of thing have been [`number`](Self::number)ed
+	numbered: HashMap<String, usize>,
+	/// what was asked of this allocator since it was [`split`](Self::split) off, in order, to check it against the allocator it is [`merge`](Self::merge)d into
+	requests: Option<Vec<Request>>,
+}
+
+/// something asked of an allocator, with what it answered
+#[derive(Debug, Clone)]
+enum Request {
+	/// [`reserve`](IdAllocator::reserve), and whether the id was free
+	Reserve(String, bool),
+	/// [`claim`](IdAllocator::claim), and the id taken
+	Claim(String, String),
+	/// [`number`](IdAllocator::number), and the number given
+	Number(String, usize),
+}
+
+/// how many times each id has been asked for, which [`reserve`] and [`claim`] change, or each kind of thing numbered
+trait Taken {
+	fn count(&self, id: &str) -> usize;
+	fn set(&mut self, id: &str, count: usize);
+}
+
+impl Taken for HashMap<String, usize> {
+	fn count(&self, id: &str) -> usize {
+		self.get(id).copied().unwrap_or(0)
+	}
+
+	fn set(&mut self, id: &str, count: usize) {
+		self.insert(id.to_string(), count);
+	}
+}
+
+/// changes to the ids taken by an allocator, kept apart from it until they are known to be wanted
+struct Overlay<'a> {
+	base: &'a HashMap<String, usize>,
+	changes: HashMap<String, usize>,
+}
+
+impl Taken for Overlay<'_> {
+	fn count(&self, id: &str) -> usize {
+		self.changes
+			.get(id)
+			.or_else(|| self.base.get(id))
+			.copied()
+			.unwrap_or(0)
+	}
+
+	fn set(&mut self, id: &str, count: usize) {
+		self.changes.insert(id.to_string(), count);
+	}
+}
+
+fn reserve(taken: &mut impl Taken, id: &str) -> bool {
+	let claims = taken.count(id) + 1;
+	taken.set(id, claims);
+	claims == 1
+}
+
+fn claim(taken: &mut impl Taken, id: &str) -> (String, bool) {
+	if reserve(taken, id) {
+		return (id.to_string(), true);
+	}
+
+	let mut suffix = taken.count(id);
+	let unique = loop {
+		let candidate = format!("{id}-{suffix}");
+		if taken.count(&candidate) == 0 {
+			break candidate;
+		}
+		suffix += 1;
+	};
+	taken.set(&unique, 1);
+
+	(unique, false)
+}
+
+impl IdAllocator {
+	/// whether an id has been taken
+	#[must_use]
+	pub fn is_taken(&self, id: &str) -> bool {
+		self.taken.contains_key(id)
+	}
+
+	/// take an id exactly as given, such as one the author wrote, returning whether it was free
+	///
+	/// reserve the ids of a document before emitting it, so the ids [`claim`](Self::claim)ed and [`generate`](Self::generate)d while emitting step around them rather than taking them first
+	pub fn reserve(&mut self, id: &str) -> bool {
+		let free = reserve(&mut self.taken, id);
+
+		if let Some(requests) = &mut self.requests {
+			requests.push(Request::Reserve(id.to_string(), free));
+		}
+
+		free
+	}
+
+	/// take `id`, or the first free id of it with a suffix, returning the id taken and whether it was `id` itself
+	pub fn claim(&mut self, id: &str) -> (String, bool) {
+		let (unique, free) = claim(&mut self.taken, id);
+
+		if let Some(requests) = &mut self.requests {
+			requests.push(Request::Claim(id.to_string(), unique.clone()));
+		}
+
+		(unique, free)
+	}
+
+	/// the next number of a kind of thing, counting from 1, such as to number the ids of each group of elements a tag writes
+	///
+	/// numbers are counted apart from the ids taken, so make the ids they number unlikely to be written otherwise, such as `doll-tabs-1`
+	pub fn number(&mut self, kind: &str) -> usize {
+		let number = self.numbered.count(kind) + 1;
+		self.numbered.set(kind, number);
+
+		if let Some(requests) = &mut self.requests {
+			requests.push(Request::Number(kind.to_string(), number));
+		}
+
+		number
+	}
+
+	/// take an id derived from text, such as the name of a section, by [`slugify`], and [`claim`](Self::claim) it
+	///
+	/// the slug is made an id with [`html_id`], so it is prefixed with `id-` unless it starts with a letter, so it can be used in a CSS selector, and is `id` if the text has no letters or digits
+	pub fn generate(&mut self, text: &str) -> String {
+		self.claim(&html_id(&slugify(text))).0
+	}
+
+	/// the ids taken and how many times each was asked for, sorted, for [`HtmlEmit::canonical_options`](super::HtmlEmit::canonical_options)
+	pub(crate) fn sorted(&self) -> Vec<(&str, usize)> {
+		let mut taken = self
+			.taken
+			.iter()
+			.map(|(id, claims)| (&**id, *claims))
+			.collect::<Vec<_>>();
+		taken.sort_unstable();
+		taken
+	}
+
+	/// where in the source an id was claimed by [`HtmlEmit::unique_id_at`](super::HtmlEmit::unique_id_at), if it was
+	pub(crate) fn claimed_at(&self, id: &str) -> Option<usize> {
+		self.claimed_at.get(id).copied()
+	}
+
+	/// record where in the source an id was claimed
+	pub(crate) fn set_claimed_at(&mut self, id: &str, at: usize) {
+		self.claimed_at.insert(id.to_string(), at);
+	}
+
+	/// a copy of this allocator to take the ids of part of the same document, such as one emitted on another thread, to [`merge`](Self::merge) back in
+	pub(crate) fn split(&self) -> Self {
+		Self {
+			taken: self.taken.clone(),
+			claimed_at: self.claimed_at.clone(),
+			numbered: self.numbered.clone(),
+			requests: Some(Vec::new()),
+		}
+	}
+
+	/// take the ids taken by an allocator [`split`](Self::split) off this one, as if they were asked of this one in the same order, returning whether each got the same answer, and taking none of them if not
+	pub(crate) fn merge(&mut self, part: Self) -> bool {
+		let requests = part.requests.unwrap_or_default();
+
+		let mut taken = Overlay {
+			base: &self.taken,
+			changes: HashMap::new(),
+		};
+		let mut numbered = Overlay {
+			base: &self.numbered,
+			changes: HashMap::new(),
+		};
+		for request in &requests {
+			let same = match request {
+				Request::Reserve(id, free) => reserve(&mut taken, id) == *free,
+				Request::Claim(id, unique) => claim(&mut taken, id).0 == *unique,
+				Request::Number(kind, number) => {
+					let next = numbered.count(kind) + 1;
+					numbered.set(kind, next);
+					next == *number
+				}
+			};
+
+			if !same {
+				return false;
+			}
+		}
+
+		let (taken, numbered) = (taken.changes, numbered.changes);
+		self.taken.extend(taken);
+		self.numbered.extend(numbered);
+		self.claimed_at.extend(part.claimed_at);
+		if let Some(ours) = &mut self.requests {
+			ours.extend(requests);
+		}
+
+		true
+	}
+}
@@ -1,27 +1,667 @@
+mod assets;
+mod balance;
+mod deferred;
+mod ids;
+mod source_map;
+
+pub(crate) use {balance::finish as check_balance, deferred::run_deferred};
+#[cfg(feature = "fs-assets")]
+pub use assets::{fs_asset_path, fs_copier, fs_resolver};
+pub use {
+	assets::{sniff, AssetInfo, AssetKind},
+	deferred::{DeferredEmit, DeferredEmitter},
+	ids::{html_id, slugify, IdAllocator},
+	source_map::{SourceMap, SourceMapping},
+};
+
 use {
 	crate::{
-		tree::{InlineItem, AST},
+		dependencies::{self, DependencyKind},
+		diagnostics::Profile,
+		ext::{citation::Bibliography, datetime::Unit, page::ResolvedPage, todo::DraftPolicy},
+		tree::{parser::is_hidden_control, InlineItem, ListItem, SectionMeta, AST},
 		MarkDoll,
 	},
-	::alloc::{format, rc::Rc, string::String},
+	::alloc::{
+		borrow::Cow,
+		format,
+		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
+	},
+	core::{fmt::Write, sync::atomic::Ordering},
+	hashbrown::{HashMap, HashSet},
 };
 
+/// a stylesheet covering every class the built-in tags emit, such as `doll-quote` and `doll-tabs`, to make output presentable without writing one
+///
+/// colors come from custom properties, such as `--doll-accent`, which can be overridden to theme it
+#[cfg(feature = "default-css")]
+pub const DEFAULT_CSS: &str = include_str!("default.css");
+
 /// emit a code block with a given language
 /// 
 /// - `doll` - markdoll instance
 /// - `emit` - emit target
 /// - `lang` - language requested
 /// - `src` - content
-pub type CodeBlockFormatter = dyn Fn(&mut MarkDoll, &mut HtmlEmit, &str, &str);
+pub type CodeBlockFormatter = dyn Fn(&mut MarkDoll, &mut HtmlEmit, &str, &str) + Send + Sync;
+
+/// emit math, replacing the markup left for client-side rendering
+/// 
+/// - `doll` - markdoll instance
+/// - `emit` - emit target
+/// - `tex` - TeX source
+/// - `display` - whether the math is in display mode rather than inline
+pub type MathRenderer = dyn Fn(&mut MarkDoll, &mut HtmlEmit, &str, bool) + Send + Sync;
+
+/// render the source of a diagram to HTML, or return a diagnostic code if it can't be
+pub type DiagramRenderer = dyn Fn(&str) -> Result<String, &'static str> + Send + Sync;
+
+/// resolve the name of a page to where it is, or `None` if it can't be
+pub type PageResolver = dyn Fn(&str) -> Option<ResolvedPage> + Send + Sync;
+
+/// look up an asset, such as the source of an `img` tag, or return `None` if it can't be, such as a remote one
+pub type AssetResolver = dyn Fn(&str) -> Option<AssetInfo> + Send + Sync;
+
+/// rewrite the source of an image, such as to send it through a proxy
+pub type ImageProxy = dyn Fn(&str) -> String + Send + Sync;
+
+/// rewrite a url from the document, given what it is written for, or return `None` if it can't be, such as a local file that couldn't be copied
+pub type UrlRewriter = dyn Fn(&str, AssetKind) -> Option<String> + Send + Sync;
+
+/// the schemes [`HtmlEmit::safe_urls`] allows
+pub const SAFE_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// the text for a [`StringKey`] in some language
+pub type Localizer = dyn Fn(&StringKey) -> String + Send + Sync;
+
+/// a piece of text written by an emitter rather than the document, looked up in [`Strings`]
+///
+/// a [`Localizer`] matching every key without a wildcard stops compiling when one is added, rather than leaving it in english
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKey<'a> {
+	/// a date, `March 9, 2024`
+	Date {
+		/// the year
+		year: i32,
+		/// the month, from 1
+		month: u8,
+		/// the day of the month, from 1
+		day: u8,
+	},
+	/// a date with its day of the week, `Saturday, March 9, 2024`
+	Weekday {
+		/// the day of the week, from 0 for sunday
+		weekday: u8,
+		/// the date, from [`Date`](Self::Date)
+		date: &'a str,
+	},
+	/// a time less than a minute from [`HtmlEmit::time_reference`], `just now`
+	JustNow,
+	/// a time before [`HtmlEmit::time_reference`], `3 days ago`
+	Ago(u64, Unit),
+	/// a time after [`HtmlEmit::time_reference`], `in 3 days`
+	In(u64, Unit),
+	/// the family names of the two authors of a cited work, `Smith and Jones`
+	TwoAuthors(&'a str, &'a str),
+	/// the family name of the first of more than two authors of a cited work, `Smith et al.`
+	EtAl(&'a str),
+	/// the year of a cited work without one, `n.d.`
+	NoDate,
+}
+
+/// the english text for a [`StringKey`], the default [`Localizer`]
+#[must_use]
+pub fn english(key: &StringKey) -> String {
+	const MONTHS: [&str; 12] = [
+		"January",
+		"February",
+		"March",
+		"April",
+		"May",
+		"June",
+		"July",
+		"August",
+		"September",
+		"October",
+		"November",
+		"December",
+	];
+
+	const WEEKDAYS: [&str; 7] = [
+		"Sunday",
+		"Monday",
+		"Tuesday",
+		"Wednesday",
+		"Thursday",
+		"Friday",
+		"Saturday",
+	];
+
+	let unit = |count: u64, unit: Unit| {
+		let name = match unit {
+			Unit::Minute => "minute",
+			Unit::Hour => "hour",
+			Unit::Day => "day",
+			Unit::Month => "month",
+			Unit::Year => "year",
+		};
+		let plural = if count == 1 { "" } else { "s" };
+
+		format!("{count} {name}{plural}")
+	};
+
+	match *key {
+		StringKey::Date { year, month, day } => {
+			format!("{} {day}, {year}", MONTHS[usize::from(month) - 1])
+		}
+		StringKey::Weekday { weekday, date } => {
+			format!("{}, {date}", WEEKDAYS[usize::from(weekday)])
+		}
+		StringKey::JustNow => "just now".to_string(),
+		StringKey::Ago(count, of) => format!("{} ago", unit(count, of)),
+		StringKey::In(count, of) => format!("in {}", unit(count, of)),
+		StringKey::TwoAuthors(first, second) => format!("{first} and {second}"),
+		StringKey::EtAl(first) => format!("{first} et al."),
+		StringKey::NoDate => "n.d.".to_string(),
+	}
+}
+
+/// the language of the output, and the text emitters write in it
+#[derive(Clone)]
+pub struct Strings {
+	/// the BCP 47 language tag of the output, such as `en` or `de-AT`, for anything else that depends on it, such as [`QuoteStyle::for_locale`](crate::tree::QuoteStyle::for_locale)
+	pub locale: Cow<'static, str>,
+	/// the text for each [`StringKey`]
+	pub text: Arc<Localizer>,
+}
+
+impl Strings {
+	/// the text for a key
+	#[must_use]
+	pub fn get(&self, key: &StringKey) -> String {
+		(self.text)(key)
+	}
+}
+
+impl Default for Strings {
+	/// [`english`], as `en`
+	fn default() -> Self {
+		Self {
+			locale: Cow::Borrowed("en"),
+			text: Arc::new(english),
+		}
+	}
+}
 
 /// emit to HTML
 pub struct HtmlEmit {
 	/// HTML buffer
 	pub write: String,
-	/// heading level, initialize this to 0
+	/// how many sections the output being written is nested in, initialize this to 0
+	///
+	/// the section emitter increments it before writing a section's heading and decrements it after the section's content, so while the heading and content of a section are emitted it is the level of that heading, and outside any section it is 0, see [`current_heading_level`](Self::current_heading_level)
 	pub section_level: usize,
 	/// defines how code block languages should be emitted
-	pub code_block_format: Rc<CodeBlockFormatter>,
+	pub code_block_format: Arc<CodeBlockFormatter>,
+	/// names [`code_block_format`](Self::code_block_format) in the [`canonical_options`](Self::canonical_options), such as `syntect:base16-ocean.dark`, set it along with a formatter that writes anything so the options change with it
+	pub code_block_format_name: Option<String>,
+	/// defines how math should be rendered, or `None` to leave it to client-side KaTeX/MathJax
+	pub math_render: Option<Arc<MathRenderer>>,
+	/// the unix timestamp relative times are displayed against, fix it to keep output reproducible
+	pub time_reference: Option<i64>,
+	/// the values `var` tags are substituted with, see [`frontmatter_vars`](crate::ext::var::frontmatter_vars) to define them from a document
+	pub vars: HashMap<String, String>,
+	/// the flags `if` tags test, alongside `target:html`
+	pub flags: HashSet<String>,
+	/// the renderers `diagram` tags use for each kind, besides the built-in `preformatted`
+	pub diagram_renderers: HashMap<String, Arc<DiagramRenderer>>,
+	/// the works `cite` tags refer to
+	pub bibliography: Option<Arc<Bibliography>>,
+	/// how `todo` tags are emitted
+	pub draft_mode: DraftPolicy,
+	/// resolves the pages `page` tags link to, or `None` to use [`default_resolver`](crate::ext::page::default_resolver)
+	pub page_resolver: Option<Arc<PageResolver>>,
+	/// looks up the sources of `img` tags, to warn of missing ones and write their dimensions, or `None` to leave them unchecked
+	pub asset_resolver: Option<Arc<AssetResolver>>,
+	/// the language of the output and the text written in it, such as the names of months
+	pub strings: Strings,
+	/// where each range of [`write`](Self::write) came from in the source, recorded while `Some`, such as for an editor to find what was clicked in a preview
+	///
+	/// the built-in emitters record each inline item and list item, and the tags within them, with [`begin_span`](Self::begin_span) and [`end_span`](Self::end_span)
+	pub source_map: Option<SourceMap>,
+	/// whether void elements are closed in XML style, such as `<br />` rather than `<br>`, for downstreams parsing the output as XML, see [`write_void`](Self::write_void)
+	pub xml_style: bool,
+	/// whether the elements written with [`write_open`](Self::write_open) and [`write_close`](Self::write_close) are checked to balance, for debugging tag emitters
+	///
+	/// elements closed out of order or never closed are reported as errors at the tag that opened them, and elements closed without being opened at the tag that closed them. while unset, nothing is checked
+	pub check_balance: bool,
+	/// the ids taken in the output, which [`unique_id`](Self::unique_id) claims from, so they are unique across everything emitted into it, and the groups of tabs numbered so far
+	///
+	/// [`reserve`](IdAllocator::reserve) the ids the output has besides those the built-in tags write before emitting, so tags step around them
+	pub ids: IdAllocator,
+	/// whether urls from the document, such as those of links and images, are only written if they are relative or have one of the [`SAFE_URL_SCHEMES`], see [`url`](Self::url)
+	///
+	/// others, such as `javascript:` urls, are written as `#` with an error
+	pub safe_urls: bool,
+	/// rewrites the sources of images from the document, after [`safe_urls`](Self::safe_urls) checks them, such as to load them through a proxy, see [`asset_url`](Self::asset_url)
+	pub image_proxy: Option<Arc<ImageProxy>>,
+	/// rewrites the urls of links, images, and the sources of media, after [`safe_urls`](Self::safe_urls) checks them and before [`image_proxy`](Self::image_proxy), such as to point them at copies of local files, see [`asset_url`](Self::asset_url) and `fs_copier`
+	pub url_rewriter: Option<Arc<UrlRewriter>>,
+	/// how many bytes [`write`](Self::write) may grow to before the rest of the document is left out with an error, unlimited with [`usize::MAX`]
+	///
+	/// checked by the default inline emitter before each inline item, so the output may pass it by one item. what is left out is left out whole, so the output is still well-formed. [`MarkDoll::emit_parallel`](crate::MarkDoll::emit_parallel) limits what all of its parts write together
+	pub max_output_bytes: usize,
+}
+
+impl Default for HtmlEmit {
+	/// a target for trusted documents, with nothing written yet
+	///
+	/// code blocks with a language are left to [`code_block_format`](Self::code_block_format), which writes nothing until it is set. urls are written as they are, output is unlimited, void elements are closed in XML style, and no functions are set
+	fn default() -> Self {
+		Self {
+			write: String::new(),
+			section_level: 0,
+			code_block_format: Arc::new(|_, _, _, _| {}),
+			code_block_format_name: None,
+			math_render: None,
+			time_reference: None,
+			vars: HashMap::new(),
+			flags: HashSet::new(),
+			diagram_renderers: HashMap::new(),
+			bibliography: None,
+			draft_mode: DraftPolicy::default(),
+			page_resolver: None,
+			asset_resolver: None,
+			strings: Strings::default(),
+			source_map: None,
+			xml_style: true,
+			check_balance: false,
+			ids: IdAllocator::default(),
+			safe_urls: false,
+			image_proxy: None,
+			url_rewriter: None,
+			max_output_bytes: usize::MAX,
+		}
+	}
+}
+
+/// whether an emit has already passed [`HtmlEmit::max_output_bytes`], kept in [`MarkDoll::scratch`]
+#[derive(Debug, Clone, Copy, Default)]
+struct OutputExceeded;
+
+impl HtmlEmit {
+	/// a target for documents from people who aren't trusted, emitted with [`MarkDoll::untrusted`]
+	///
+	/// it writes only relative urls and those with [`SAFE_URL_SCHEMES`], and at most 4 MiB of output. `todo` tags are left out, and no code is highlighted, no math rendered, and no pages or assets are looked up. set [`image_proxy`](Self::image_proxy) to load images through a proxy
+	///
+	/// text and arguments from the document are always escaped, this also keeps the urls they give from running scripts and the output from growing without bound
+	#[must_use]
+	pub fn untrusted_profile() -> Self {
+		Self {
+			draft_mode: DraftPolicy::Hide,
+			safe_urls: true,
+			max_output_bytes: 4 << 20,
+			..Self::default()
+		}
+	}
+
+	/// the options of this target that change its output, written the same way whenever they are the same, one `name=value` per line with maps sorted by key
+	///
+	/// functions, such as [`math_render`](Self::math_render), are only recorded by whether they are set, [`code_block_format`](Self::code_block_format) by its [name](Self::code_block_format_name), and the text of [`strings`](Self::strings) not at all. what is emitted so far, such as [`write`](Self::write) and the groups of tabs [`number`](IdAllocator::number)ed, isn't an option, but the [`ids`](Self::ids) reserved before emitting are. see [`options_fingerprint`](Self::options_fingerprint) to hash it
+	#[must_use]
+	pub fn canonical_options(&self) -> String {
+		/// whether a function is set
+		fn set<T: ?Sized>(option: Option<&Arc<T>>) -> &'static str {
+			if option.is_some() {
+				"set"
+			} else {
+				"unset"
+			}
+		}
+
+		let mut out = String::new();
+		let mut line = |name: &str, value: &dyn core::fmt::Debug| {
+			writeln!(out, "{name}={value:?}").unwrap();
+		};
+
+		line("code_block_format", &self.code_block_format_name);
+		line("math_render", &set(self.math_render.as_ref()));
+		line("time_reference", &self.time_reference);
+
+		let mut vars = self.vars.iter().collect::<Vec<_>>();
+		vars.sort_unstable();
+		for (name, value) in vars {
+			line(&format!("vars.{name:?}"), value);
+		}
+
+		let mut flags = self.flags.iter().collect::<Vec<_>>();
+		flags.sort_unstable();
+		line("flags", &flags);
+
+		let mut renderers = self.diagram_renderers.keys().collect::<Vec<_>>();
+		renderers.sort_unstable();
+		line("diagram_renderers", &renderers);
+
+		if let Some(bibliography) = &self.bibliography {
+			line("bibliography.style", &bibliography.style);
+
+			let mut references = bibliography.references.iter().collect::<Vec<_>>();
+			references.sort_unstable_by_key(|(key, _)| *key);
+			for (key, reference) in references {
+				line(&format!("bibliography.{key:?}"), reference);
+			}
+		} else {
+			line("bibliography", &"unset");
+		}
+
+		line("draft_mode", &self.draft_mode);
+		line("page_resolver", &set(self.page_resolver.as_ref()));
+		line("asset_resolver", &set(self.asset_resolver.as_ref()));
+		line("strings.locale", &self.strings.locale);
+		line("xml_style", &self.xml_style);
+		line("check_balance", &self.check_balance);
+		line("ids", &self.ids.sorted());
+		line("safe_urls", &self.safe_urls);
+		line("image_proxy", &set(self.image_proxy.as_ref()));
+		line("url_rewriter", &set(self.url_rewriter.as_ref()));
+		line("max_output_bytes", &self.max_output_bytes);
+
+		out
+	}
+
+	/// a hash of the [`canonical_options`](Self::canonical_options), the same across runs and platforms, to key caches of output alongside [`MarkDoll::content_fingerprint`]
+	#[must_use]
+	pub fn options_fingerprint(&self) -> u64 {
+		let mut hasher = crate::tree::fingerprint::Fnv::default();
+		core::hash::Hasher::write(&mut hasher, self.canonical_options().as_bytes());
+		core::hash::Hasher::finish(&hasher)
+	}
+
+	/// a comment naming the version of markdoll and the options output was emitted with, to write before it so diffs of output can tell changes to the generator from changes to the document
+	///
+	/// such as `<!-- markdoll 0.1.0 profile=strict options-hash=0123456789abcdef -->`, with the [`options_fingerprint`](Self::options_fingerprint) as hex and the `profile` of diagnostics the document was checked with
+	#[must_use]
+	pub fn stamp(&self, profile: Profile) -> String {
+		format!(
+			"<!-- markdoll {} profile={} options-hash={:016x} -->",
+			env!("CARGO_PKG_VERSION"),
+			match profile {
+				Profile::Default => "default",
+				Profile::Strict => "strict",
+				Profile::Relaxed => "relaxed",
+			},
+			self.options_fingerprint()
+		)
+	}
+
+	/// a url from the document, as it should be written, which is `#` with an error at the tag being emitted if [`safe_urls`](Self::safe_urls) is set and it has a scheme other than the [`SAFE_URL_SCHEMES`]
+	///
+	/// the scheme is found as browsers find it, ignoring case and the whitespace and control characters they strip, so `JaVa\tScRiPt:` is caught too. the url still needs escaping for the attribute it is written in
+	pub fn url<'url>(&self, doll: &mut MarkDoll, url: &'url str) -> &'url str {
+		if !self.safe_urls {
+			return url;
+		}
+
+		let stripped = url
+			.chars()
+			.filter(|ch| !ch.is_ascii_whitespace() && !ch.is_control())
+			.collect::<String>();
+		let scheme = stripped
+			.find([':', '/', '?', '#'])
+			.filter(|end| stripped[*end..].starts_with(':'))
+			.map(|end| stripped[..end].to_ascii_lowercase());
+
+		match scheme {
+			Some(scheme) if !SAFE_URL_SCHEMES.contains(&&*scheme) => {
+				doll.diag(true, usize::MAX, "url scheme not allowed");
+				"#"
+			}
+			_ => url,
+		}
+	}
+
+	/// a url from the document written for `kind`, as it should be written, checked by [`url`](Self::url) and rewritten by [`url_rewriter`](Self::url_rewriter), then by [`image_proxy`](Self::image_proxy) if it is an image
+	///
+	/// urls the rewriter can't rewrite are written as they are, with a warning at the argument `arg` of the tag being emitted, counted as [`MarkDoll::diag_arg`] counts them. local urls it rewrites are recorded as asset dependencies unless they already were, so the files links point to are too
+	pub fn asset_url(&self, doll: &mut MarkDoll, url: &str, kind: AssetKind, arg: usize) -> String {
+		let url = self.url(doll, url);
+		if url == "#" {
+			return url.to_string();
+		}
+
+		let url = match self.url_rewriter.as_ref().map(|rewrite| rewrite(url, kind)) {
+			Some(Some(rewritten)) => {
+				if rewritten != url
+					&& dependencies::is_local(url)
+					&& !doll.dependencies().iter().any(|dep| dep.path == url)
+				{
+					doll.record_dependency(DependencyKind::Asset, url);
+				}
+
+				rewritten
+			}
+			Some(None) => {
+				doll.diag_arg(false, arg, "asset url could not be rewritten");
+				url.to_string()
+			}
+			None => url.to_string(),
+		};
+
+		match &self.image_proxy {
+			Some(proxy) if kind == AssetKind::Image => proxy(&url),
+			_ => url,
+		}
+	}
+
+	/// whether [`write`](Self::write) has grown past [`max_output_bytes`](Self::max_output_bytes), reporting it at `at`, a position in the current context like those passed to [`MarkDoll::diag`], the first time in an emit
+	pub fn output_exceeded(&mut self, doll: &mut MarkDoll, at: usize) -> bool {
+		let written = match &mut doll.parallel_output {
+			// every part emitted in parallel counts, so together they can't pass it
+			Some((total, written)) => {
+				let added = self.write.len().saturating_sub(*written);
+				*written = self.write.len();
+				total.fetch_add(added, Ordering::Relaxed) + added
+			}
+			None => self.write.len(),
+		};
+
+		if written <= self.max_output_bytes {
+			return false;
+		}
+
+		if doll.scratch.get_ref::<OutputExceeded>().is_none() {
+			doll.scratch.put(OutputExceeded);
+			doll.diag(true, at, "output too large");
+		}
+
+		true
+	}
+
+	/// write an ` id='…'` attribute, escaped for it, such as of an id from [`unique_id`](Self::unique_id)
+	pub fn write_id_attr(&mut self, id: &str) {
+		self.write.push_str(" id='");
+		self.write
+			.push_str(&html_escape::encode_single_quoted_attribute(id));
+		self.write.push('\'');
+	}
+
+	/// claim an HTML id for the tag being emitted from [`ids`](Self::ids), made valid with [`html_id`] first, returning it as is the first time, and otherwise warning that it was already used and returning it with the first free suffix of `-2`, `-3`, and so on, so the output has no duplicate ids
+	///
+	/// `def`s, `term`s, and `references` claim their ids here, so they share one namespace. ids are claimed in the order tags are emitted, so the same document always gets the same ids
+	pub fn unique_id(&mut self, doll: &mut MarkDoll, id: &str) -> String {
+		self.unique_id_at(doll, id, usize::MAX)
+	}
+
+	/// claim an HTML id like [`unique_id`](Self::unique_id), for what is at `at`, a position in the current context like those passed to [`MarkDoll::diag`], such as a section
+	pub fn unique_id_at(&mut self, doll: &mut MarkDoll, id: &str, at: usize) -> String {
+		let id = &html_id(id);
+		let resolved = doll.resolve_pos(at);
+		let (unique, free) = self.ids.claim(id);
+
+		self.ids.set_claimed_at(&unique, resolved);
+		if free {
+			return unique;
+		}
+		let first = self.ids.claimed_at(id);
+
+		let len = doll.diagnostics.len();
+		doll.diag(false, at, "id already used in this document");
+
+		// unless it was suppressed, or reserved rather than claimed by a tag
+		if let (true, Some(first)) = (doll.diagnostics.len() > len, first) {
+			doll.diagnostics.last_mut().unwrap().context = Some((first, "first used here"));
+		}
+
+		unique
+	}
+
+	/// write a void element, which has no content or closing tag, such as `br` or `img`, closed as [`xml_style`](Self::xml_style) says
+	///
+	/// `attrs` is written as is after the name, so each attribute needs a leading space and its value escaped
+	pub fn write_void(&mut self, name: &str, attrs: &str) {
+		self.write.push('<');
+		self.write.push_str(name);
+		self.write.push_str(attrs);
+		self.write
+			.push_str(if self.xml_style { " />" } else { ">" });
+	}
+
+	/// write the start of an element, closed later with [`write_close`](Self::write_close), and tracked while [`check_balance`](Self::check_balance) is set
+	///
+	/// `attrs` is written as is after the name, as for [`write_void`](Self::write_void)
+	pub fn write_open(&mut self, doll: &mut MarkDoll, name: &str, attrs: &str) {
+		if self.check_balance {
+			balance::open(doll, name);
+		}
+
+		self.write.push('<');
+		self.write.push_str(name);
+		self.write.push_str(attrs);
+		self.write.push('>');
+	}
+
+	/// write the end of an element opened with [`write_open`](Self::write_open)
+	pub fn write_close(&mut self, doll: &mut MarkDoll, name: &str) {
+		if self.check_balance {
+			balance::close(doll, name);
+		}
+
+		self.write.push_str("</");
+		self.write.push_str(name);
+		self.write.push('>');
+	}
+
+	/// the level of the heading of the section being emitted, or 0 outside any section, so a tag writing a heading of its own below it would use one more
+	///
+	/// section names are plain text, so this is only seen by tags in the content of a section, including within lists and other tags. levels past 6 have no `<hN>` element, the built-in section emitter uses `aria-level` for them
+	#[must_use]
+	pub fn current_heading_level(&self) -> usize {
+		self.section_level
+	}
+
+	/// record the output written until the matching [`end_span`](Self::end_span) as coming from `at`, a position in the current context like those passed to [`MarkDoll::diag`], if [`source_map`](Self::source_map) is recording
+	pub fn begin_span(&mut self, doll: &mut MarkDoll, at: usize) {
+		if let Some(map) = &mut self.source_map {
+			map.begin(doll, self.write.len(), at);
+		}
+	}
+
+	/// end the span begun last by [`begin_span`](Self::begin_span)
+	pub fn end_span(&mut self) {
+		if let Some(map) = &mut self.source_map {
+			map.end(self.write.len());
+		}
+	}
+}
+
+/// an emit target that can be split into independent parts and joined back together, for [`MarkDoll::emit_parallel`](crate::MarkDoll::emit_parallel)
+pub trait SplitEmit {
+	/// create an empty target to emit part of the output into, in the same state as this one
+	#[must_use]
+	fn split(&self) -> Self;
+
+	/// append a part created by [`split`](Self::split) to this target, returning whether it was, which it isn't if emitting it into this target directly would have written something else, leaving this target as it was
+	///
+	/// parts are split before any are joined, so a part is written as if nothing was emitted before it. [`MarkDoll::emit_parallel`](crate::MarkDoll::emit_parallel) emits the block of a part it can't join, and those after it, again in order
+	#[must_use]
+	fn join(&mut self, part: Self) -> bool;
+}
+
+impl SplitEmit for HtmlEmit {
+	fn split(&self) -> Self {
+		Self {
+			write: String::new(),
+			section_level: self.section_level,
+			code_block_format: Arc::clone(&self.code_block_format),
+			code_block_format_name: self.code_block_format_name.clone(),
+			math_render: self.math_render.clone(),
+			time_reference: self.time_reference,
+			vars: self.vars.clone(),
+			flags: self.flags.clone(),
+			diagram_renderers: self.diagram_renderers.clone(),
+			bibliography: self.bibliography.clone(),
+			draft_mode: self.draft_mode,
+			page_resolver: self.page_resolver.clone(),
+			asset_resolver: self.asset_resolver.clone(),
+			strings: self.strings.clone(),
+			source_map: self.source_map.as_ref().map(|_| SourceMap::default()),
+			xml_style: self.xml_style,
+			check_balance: self.check_balance,
+			ids: self.ids.split(),
+			safe_urls: self.safe_urls,
+			image_proxy: self.image_proxy.clone(),
+			url_rewriter: self.url_rewriter.clone(),
+			max_output_bytes: self.max_output_bytes,
+		}
+	}
+
+	fn join(&mut self, part: Self) -> bool {
+		// it may have been cut off sooner after what was written before it
+		if self.write.len().saturating_add(part.write.len()) > self.max_output_bytes {
+			return false;
+		}
+
+		if !self.ids.merge(part.ids) {
+			return false;
+		}
+
+		if let (Some(map), Some(part_map)) = (&mut self.source_map, part.source_map) {
+			map.join(part_map, self.write.len());
+		}
+		self.write.push_str(&part.write);
+
+		true
+	}
+}
+
+impl DeferredEmit for HtmlEmit {
+	fn position(&self) -> usize {
+		self.write.len()
+	}
+
+	fn splice(&mut self, doll: &mut MarkDoll, at: usize, emit: &DeferredEmitter<Self>) {
+		let write = core::mem::take(&mut self.write);
+		let map = self.source_map.take();
+		if map.is_some() {
+			self.source_map = Some(SourceMap::default());
+		}
+
+		// recorded as the tag, which wrote nothing itself when it deferred
+		self.begin_span(doll, usize::MAX);
+		emit(doll, self);
+		self.end_span();
+
+		let spliced = core::mem::replace(&mut self.write, write);
+		let part = core::mem::replace(&mut self.source_map, map);
+		if let (Some(map), Some(part)) = (&mut self.source_map, part) {
+			map.insert(at, spliced.len());
+			map.join(part, at);
+		}
+
+		self.write.insert_str(at, &spliced);
+	}
 }
 
 /// defines the behavior of built in [`BlockItem`](crate::tree::BlockItem)s
@@ -30,9 +670,18 @@ pub struct BuiltInEmitters<To> {
 	/// how to emit [`BlockItem::Inline`](crate::tree::BlockItem::Inline)
 	pub inline: fn(doll: &mut MarkDoll, to: &mut To, segments: &mut [(usize, InlineItem)], inline_block: bool),
 	/// how to emit [`BlockItem::Section`](crate::tree::BlockItem::Section)
-	pub section: fn(doll: &mut MarkDoll, to: &mut To, name: &str, children: &mut AST),
+	pub section: fn(doll: &mut MarkDoll, to: &mut To, pos: usize, name: &str, meta: &SectionMeta, children: &mut AST),
 	/// how to emit [`BlockItem::List`](crate::tree::BlockItem::List)
-	pub list: fn(doll: &mut MarkDoll, to: &mut To, ordered: bool, items: &mut [AST]),
+	pub list: fn(doll: &mut MarkDoll, to: &mut To, ordered: bool, items: &mut [ListItem]),
+}
+
+/// escape text for html, leaving out [control characters](is_hidden_control) that would make it read differently than it was parsed
+fn encode_text(text: &str) -> Cow<'_, str> {
+	if text.contains(is_hidden_control) {
+		Cow::Owned(html_escape::encode_text(&text.replace(is_hidden_control, "")).into_owned())
+	} else {
+		html_escape::encode_text(text)
+	}
 }
 
 impl BuiltInEmitters<HtmlEmit> {
@@ -45,42 +694,78 @@ impl BuiltInEmitters<HtmlEmit> {
 	) {
 		to.write.push_str(if inline_block { "<div>" } else { "<span>" });
 
-		for (_, segment) in segments {
+		for (pos, segment) in segments {
+			if to.output_exceeded(doll, *pos) {
+				break;
+			}
+
+			// a tag is recorded from its opening `[`, rather than its name
+			let at = match segment {
+				InlineItem::Tag(tag) => tag
+					.diagnostic_translation
+					.as_ref()
+					.map_or(*pos, |translation| translation.tag_pos_in_parent),
+				_ => *pos,
+			};
+			to.begin_span(doll, at);
+
 			match segment {
 				InlineItem::Split => to.write.push(' '),
-				InlineItem::Break => to.write.push_str("<br />"),
+				InlineItem::Break => to.write_void("br", ""),
 				InlineItem::Text(text) => {
-					to.write.push_str(&html_escape::encode_text(text));
+					to.write.push_str(&encode_text(text));
 				}
 				InlineItem::Tag(tag) => tag.emit(doll, to),
 			}
+
+			to.end_span();
 		}
 
 		to.write.push_str(if inline_block { "</div>" } else { "</span>" });
 	}
 
 	/// the default [`BlockItem::Section`](crate::tree::BlockItem::Section) emitter
+	///
+	/// the [`id`](SectionMeta::id) of the section is claimed with [`unique_id_at`](HtmlEmit::unique_id_at), and it and the [`class`](SectionMeta::class) are written on the `section` element
 	pub fn default_section(
 		doll: &mut MarkDoll,
 		to: &mut HtmlEmit,
+		pos: usize,
 		name: &str,
+		meta: &SectionMeta,
 		children: &mut AST,
 	) {
 		to.section_level += 1;
 
 		let level = to.section_level;
+		write!(to.write, "<section data-level='{level}'").unwrap();
+		if let Some(id) = &meta.id {
+			let id = to.unique_id_at(doll, id, pos);
+			to.write_id_attr(&id);
+		}
+		if let Some(class) = &meta.class {
+			write!(
+				to.write,
+				" class='{}'",
+				html_escape::encode_single_quoted_attribute(class)
+			)
+			.unwrap();
+		}
+
 		if level <= 6 {
-			to.write.push_str(&format!(
-				"<section data-level='{level}'><h{level}>{}</h{level}><div>",
-				&html_escape::encode_text(name)
-			));
+			write!(
+				to.write,
+				"><h{level}>{}</h{level}><div>",
+				&encode_text(name)
+			)
+			.unwrap();
 		} else {
-			to.write.push_str(&format!(
-				
-				"<section data-level='{level}'><div role='heading' aria-level='{level}'>{}</div><div>",
-				&html_escape::encode_text(name)
+			write!(
+				to.write,
+				"><div role='heading' aria-level='{level}'>{}</div><div>",
+				&encode_text(name)
 			)
-			);
+			.unwrap();
 		}
 
 		for child in children {
@@ -93,21 +778,26 @@ impl BuiltInEmitters<HtmlEmit> {
 	}
 
 	/// the default [`BlockItem::List`](crate::tree::BlockItem::List) emitter
-	pub fn default_list(doll: &mut MarkDoll, to: &mut HtmlEmit, ordered: bool, items: &mut [AST]) {
+	pub fn default_list(doll: &mut MarkDoll, to: &mut HtmlEmit, ordered: bool, items: &mut [ListItem]) {
 		let kind = if ordered { "ol" } else { "ul" };
-		to.write.push_str(&format!( "<{kind}>"));
+		write!(to.write, "<{kind}>").unwrap();
 
 		for item in items {
-			to.write.push_str("<li>");
+			to.begin_span(doll, item.pos);
+			match item.value {
+				Some(value) if ordered => write!(to.write, "<li value='{value}'>").unwrap(),
+				_ => to.write.push_str("<li>"),
+			}
 
-			for child in item {
+			for child in &mut item.ast {
 				child.emit(doll, &mut *to, true);
 			}
 
 			to.write.push_str("</li>");
+			to.end_span();
 		}
 
-		to.write.push_str(&format!( "</{kind}>"));
+		write!(to.write, "</{kind}>").unwrap();
 	}
 }
 
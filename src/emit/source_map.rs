@@ -0,0 +1,77 @@
+use {crate::MarkDoll, alloc::vec::Vec, core::ops::Range};
+
+/// where a range of the output came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SourceMapping {
+	/// the byte range of the output
+	pub output: Range<usize>,
+	/// the character index in the source of the item written there, as diagnostics are positioned
+	pub source: usize,
+}
+
+/// the [`SourceMapping`]s recorded while emitting, see [`HtmlEmit::source_map`](super::HtmlEmit::source_map)
+///
+/// mappings nest like the items they were written for, a tag's mapping contains the mappings of its content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SourceMap {
+	/// the mappings, in the order they ended, so each comes after the ones it contains
+	pub mappings: Vec<SourceMapping>,
+	/// the output position and source of each item being written
+	#[cfg_attr(feature = "serde", serde(skip))]
+	open: Vec<(usize, usize)>,
+}
+
+impl SourceMap {
+	/// the source of the innermost item written at a byte offset of the output, such as where an editor was clicked
+	#[must_use]
+	pub fn source_at(&self, output: usize) -> Option<usize> {
+		self.mappings
+			.iter()
+			.filter(|mapping| mapping.output.contains(&output))
+			.min_by_key(|mapping| mapping.output.len())
+			.map(|mapping| mapping.source)
+	}
+
+	/// start recording output written from `output` as coming from `at` in the current context of `doll`
+	pub(crate) fn begin(&mut self, doll: &mut MarkDoll, output: usize, at: usize) {
+		self.open.push((output, doll.resolve_pos(at)));
+	}
+
+	/// stop recording the output of the item begun last at `output`, leaving it out if nothing was written
+	pub(crate) fn end(&mut self, output: usize) {
+		let (start, source) = self
+			.open
+			.pop()
+			.expect("source map span ended without beginning");
+
+		if start < output {
+			self.mappings.push(SourceMapping {
+				output: start..output,
+				source,
+			});
+		}
+	}
+
+	/// make room for `len` bytes of output inserted at `at`, moving the mappings after it and growing the ones around it
+	pub(crate) fn insert(&mut self, at: usize, len: usize) {
+		for mapping in &mut self.mappings {
+			if mapping.output.start >= at {
+				mapping.output.start += len;
+				mapping.output.end += len;
+			} else if mapping.output.end > at {
+				mapping.output.end += len;
+			}
+		}
+	}
+
+	/// append the mappings of a map recorded into output that was appended at `offset`
+	pub(crate) fn join(&mut self, part: Self, offset: usize) {
+		self.mappings
+			.extend(part.mappings.into_iter().map(|mapping| SourceMapping {
+				output: mapping.output.start + offset..mapping.output.end + offset,
+				source: mapping.source,
+			}));
+	}
+}
@@ -0,0 +1,429 @@
+use {
+	crate::{
+		args,
+		emit::{html_id, HtmlEmit, StringKey, Strings},
+		ext::{edit_distance, TagDefinition},
+		tree::{
+			visit::{self, VisitorMut},
+			TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+	hashbrown::HashMap,
+};
+
+/// an author of a [`Reference`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Author {
+	/// the family name, or the whole name of an organization
+	pub family: String,
+	/// the given names
+	pub given: Option<String>,
+}
+
+/// a work that can be cited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+	/// the key it is cited by
+	pub id: String,
+	/// the authors, in order
+	pub authors: Vec<Author>,
+	/// the year it was issued
+	pub year: Option<i32>,
+	/// the title
+	pub title: Option<String>,
+	/// the journal, book, or site it was published in
+	pub container: Option<String>,
+	/// where it can be found
+	pub url: Option<String>,
+}
+
+impl Reference {
+	/// the authors as cited in the language of `strings`, such as `Smith`, `Smith and Jones`, or `Smith et al.`
+	#[must_use]
+	pub fn cited_authors(&self, strings: &Strings) -> String {
+		match &self.authors[..] {
+			[] => self.title.clone().unwrap_or_else(|| self.id.clone()),
+			[author] => author.family.clone(),
+			[first, second] => strings.get(&StringKey::TwoAuthors(&first.family, &second.family)),
+			[first, ..] => strings.get(&StringKey::EtAl(&first.family)),
+		}
+	}
+
+	/// the year as cited, `n.d.` in the language of `strings` if there is none
+	#[must_use]
+	pub fn cited_year(&self, strings: &Strings) -> String {
+		self.year
+			.map_or_else(|| strings.get(&StringKey::NoDate), |year| year.to_string())
+	}
+}
+
+/// how citations and reference lists are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Style {
+	/// `(Smith 2020)`, with references listed without numbers
+	#[default]
+	AuthorYear,
+	/// `[1]`, numbered by first citation, with references listed by number
+	Numeric,
+}
+
+/// the works available to cite, loaded by the embedder and attached to [`HtmlEmit::bibliography`]
+///
+/// with the `serde` feature, this deserializes from a CSL-JSON array, reading the `id`, `author`, `issued`, `title`, `container-title`, and `URL` of each item
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bibliography {
+	/// the works, by key
+	pub references: HashMap<String, Reference>,
+	/// how to write citations
+	pub style: Style,
+}
+
+impl Bibliography {
+	/// the keys within two edits of an unknown one, closest first, to suggest in its place
+	#[must_use]
+	pub fn near_matches(&self, key: &str) -> Vec<&str> {
+		let mut matches = self
+			.references
+			.keys()
+			.map(|id| (edit_distance(key, id), id.as_str()))
+			.filter(|(distance, _)| *distance <= 2)
+			.collect::<Vec<_>>();
+		matches.sort_unstable();
+
+		matches.into_iter().map(|(_, id)| id).collect()
+	}
+}
+
+impl FromIterator<Reference> for Bibliography {
+	fn from_iter<T: IntoIterator<Item = Reference>>(iter: T) -> Self {
+		Self {
+			references: iter
+				.into_iter()
+				.map(|reference| (reference.id.clone(), reference))
+				.collect(),
+			style: Style::default(),
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+mod csl {
+	use {
+		super::{Author, Bibliography, Reference},
+		alloc::{string::String, vec::Vec},
+		serde::Deserialize,
+	};
+
+	#[derive(Deserialize)]
+	struct Name {
+		family: Option<String>,
+		given: Option<String>,
+		literal: Option<String>,
+	}
+
+	#[derive(Deserialize)]
+	struct Date {
+		#[serde(rename = "date-parts", default)]
+		date_parts: Vec<Vec<i32>>,
+	}
+
+	#[derive(Deserialize)]
+	struct Item {
+		id: String,
+		#[serde(default)]
+		author: Vec<Name>,
+		issued: Option<Date>,
+		title: Option<String>,
+		#[serde(rename = "container-title")]
+		container_title: Option<String>,
+		#[serde(rename = "URL")]
+		url: Option<String>,
+	}
+
+	impl<'de> Deserialize<'de> for Bibliography {
+		fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+			let items = Vec::<Item>::deserialize(deserializer)?;
+
+			Ok(items
+				.into_iter()
+				.map(|item| Reference {
+					id: item.id,
+					authors: item
+						.author
+						.into_iter()
+						.filter_map(|name| {
+							Some(Author {
+								family: name.family.or(name.literal)?,
+								given: name.given,
+							})
+						})
+						.collect(),
+					year: item
+						.issued
+						.and_then(|date| date.date_parts.first()?.first().copied()),
+					title: item.title,
+					container: item.container_title,
+					url: item.url,
+				})
+				.collect())
+		}
+	}
+}
+
+/// the HTML id of a reference
+fn id(key: &str) -> String {
+	html_id(&format!("ref-{key}"))
+}
+
+/// `cite` tag
+///
+/// cite a work from the [`Bibliography`] attached to [`HtmlEmit::bibliography`], linking to it within the [`references`](mod@references) tag
+///
+/// citations are numbered by [`collect`], which must be run on the parsed document before emitting
+///
+/// # arguments
+///
+/// - `key`\
+///   the key of the work
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes the citation in the bibliography's [`Style`], or warns and writes the key in brackets if it isn't in the bibliography
+pub mod cite {
+	use super::*;
+
+	/// a citation of a work
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Cite {
+		/// the key of the work
+		pub key: String,
+		/// the number of the work, in order of first citation, assigned by [`collect`]
+		pub number: Option<usize>,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"cite",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(key: String);
+					opt_args();
+					flags();
+					props();
+				};
+
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				Some(Box::new(Cite { key, number: None }))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let cite = content.downcast_ref::<Cite>().unwrap();
+
+		let Some(bibliography) = &to.bibliography else {
+			doll.diag(false, usize::MAX, "no bibliography for citation");
+			write!(to.write, "[{}]", html_escape::encode_text(&cite.key)).unwrap();
+			return;
+		};
+
+		let Some(reference) = bibliography.references.get(&cite.key) else {
+			// see `Bibliography::near_matches` for suggestions
+			doll.diag_arg(false, 0, "citation key not found");
+			write!(to.write, "[{}]", html_escape::encode_text(&cite.key)).unwrap();
+			return;
+		};
+
+		let text = match bibliography.style {
+			Style::AuthorYear => {
+				format!(
+					"({} {})",
+					reference.cited_authors(&to.strings),
+					reference.cited_year(&to.strings)
+				)
+			}
+			Style::Numeric => format!("[{}]", cite.number.unwrap_or_default()),
+		};
+
+		write!(
+			to.write,
+			"<a class='doll-cite' href='#{}'>{}</a>",
+			id(&cite.key),
+			html_escape::encode_text(&text)
+		)
+		.unwrap();
+	}
+}
+
+/// `references` tag
+///
+/// list every work cited by [`cite`](mod@cite) tags, in order of first citation, filled in by [`collect`]
+pub mod references {
+	use super::*;
+
+	/// the keys of the cited works
+	#[derive(Debug, Clone, Default, PartialEq, Eq)]
+	pub struct References {
+		/// the keys, in order of first citation
+		pub keys: Vec<String>,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"references",
+			Some(|doll, _, text, _| {
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				Some(Box::new(References::default()))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// write a reference
+	fn reference(to: &mut HtmlEmit, reference: &Reference) {
+		let authors = reference
+			.authors
+			.iter()
+			.map(|author| match &author.given {
+				Some(given) => format!("{}, {given}", author.family),
+				None => author.family.clone(),
+			})
+			.collect::<Vec<_>>()
+			.join("; ");
+
+		if !authors.is_empty() {
+			to.write.push_str(&html_escape::encode_text(&authors));
+			to.write.push(' ');
+		}
+
+		let year = reference.cited_year(&to.strings);
+		write!(to.write, "({}).", html_escape::encode_text(&year)).unwrap();
+
+		if let Some(title) = &reference.title {
+			write!(to.write, " {}.", html_escape::encode_text(title)).unwrap();
+		}
+
+		if let Some(container) = &reference.container {
+			write!(
+				to.write,
+				" <em>{}</em>.",
+				html_escape::encode_text(container)
+			)
+			.unwrap();
+		}
+
+		if let Some(url) = &reference.url {
+			write!(
+				to.write,
+				" <a href='{}'>{}</a>",
+				html_escape::encode_single_quoted_attribute(url),
+				html_escape::encode_text(url)
+			)
+			.unwrap();
+		}
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let references = content.downcast_ref::<References>().unwrap();
+
+		if references.keys.is_empty() {
+			doll.diag(false, usize::MAX, "references has no citations");
+			return;
+		}
+
+		let Some(bibliography) = to.bibliography.clone() else {
+			doll.diag(false, usize::MAX, "no bibliography for citation");
+			return;
+		};
+
+		let list = match bibliography.style {
+			Style::AuthorYear => "ul",
+			Style::Numeric => "ol",
+		};
+		write!(to.write, "<{list} class='doll-references'>").unwrap();
+
+		for key in &references.keys {
+			// unknown keys were already reported where they were cited
+			if let Some(work) = bibliography.references.get(key) {
+				let id = to.unique_id(doll, &id(key));
+				to.write.push_str("<li");
+				to.write_id_attr(&id);
+				to.write.push('>');
+				reference(to, work);
+				to.write.push_str("</li>");
+			}
+		}
+
+		write!(to.write, "</{list}>").unwrap();
+	}
+}
+
+/// number the [`cite`](mod@cite) tags in an [`AST`], and fill its [`references`](mod@references) tags with the cited keys
+///
+/// works are numbered in order of first citation, and citing a work again reuses its number
+pub fn collect(doll: &MarkDoll, ast: &mut AST) {
+	struct Number(Vec<String>);
+
+	impl VisitorMut for Number {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+			if let Some(cite) = tag.content.downcast_mut::<cite::Cite>() {
+				let index = self
+					.0
+					.iter()
+					.position(|key| *key == cite.key)
+					.unwrap_or_else(|| {
+						self.0.push(cite.key.clone());
+						self.0.len() - 1
+					});
+				cite.number = Some(index + 1);
+			}
+
+			visit::walk_tag_mut(self, doll, pos, tag);
+		}
+	}
+
+	struct Fill<'a>(&'a [String]);
+
+	impl VisitorMut for Fill<'_> {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+			if let Some(references) = tag.content.downcast_mut::<references::References>() {
+				references.keys = self.0.to_vec();
+			}
+
+			visit::walk_tag_mut(self, doll, pos, tag);
+		}
+	}
+
+	let mut number = Number(Vec::new());
+	visit::walk_mut(ast, doll, &mut number);
+	visit::walk_mut(ast, doll, &mut Fill(&number.0));
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 2] {
+	[cite::tag(), references::tag()]
+}
@@ -1,12 +1,67 @@
 use {
-	crate::{args, emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	crate::{
+		args, diagnostics::Data, emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll,
+	},
 	::alloc::format,
 	alloc::{
+		borrow::Cow,
 		boxed::Box,
 		string::{String, ToString},
+		vec::Vec,
 	},
+	core::{fmt::Write, ops::Range},
 };
 
+/// strip the longest common leading whitespace from all non-blank lines
+///
+/// only a prefix of identical characters is stripped, so tabs and spaces are never mixed up, and blank lines are stripped of as much of it as they have
+#[must_use]
+pub fn dedent(text: &str) -> Cow<'_, str> {
+	let mut prefix: Option<&str> = None;
+
+	for line in text.split('\n') {
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+
+		prefix = Some(match prefix {
+			Some(prefix) => {
+				let len = prefix
+					.chars()
+					.zip(indent.chars())
+					.take_while(|(a, b)| a == b)
+					.map(|(ch, _)| ch.len_utf8())
+					.sum();
+
+				&prefix[..len]
+			}
+			None => indent,
+		});
+	}
+
+	let prefix = prefix.unwrap_or_default();
+	if prefix.is_empty() {
+		return Cow::Borrowed(text);
+	}
+
+	let mut dedented = String::with_capacity(text.len());
+
+	for (i, line) in text.split('\n').enumerate() {
+		if i > 0 {
+			dedented.push('\n');
+		}
+
+		dedented.push_str(
+			line.strip_prefix(prefix)
+				.unwrap_or_else(|| line.trim_start()),
+		);
+	}
+
+	Cow::Owned(dedented)
+}
+
 /// `code` tag
 ///
 /// generates inline code
@@ -20,16 +75,24 @@ pub mod code {
 	/// the tag
 	#[must_use]
 	pub fn tag() -> TagDefinition {
-		TagDefinition::new("code", Some(|_, _, text| Some(Box::new(text.to_string()))))
-			.with_emitter::<HtmlEmit>(html)
+		TagDefinition::new(
+			"code",
+			Some(|_, _, text, _| Some(Box::new(text.to_string()))),
+		)
+		.with_printer(|_, content, _| content.downcast_ref::<String>().unwrap().clone())
+		.with_pure(|content| Some(Box::new(content.downcast_ref::<String>().unwrap().clone())))
+		.with_expects_content()
+		.with_emitter::<HtmlEmit>(html)
 	}
 
 	/// emit to html
 	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
-		to.write.push_str(&format!(
+		write!(
+			to.write,
 			"<code>{}</code>",
-			content.downcast_ref::<String>().unwrap()
-		));
+			html_escape::encode_text(content.downcast_ref::<String>().unwrap())
+		)
+		.unwrap();
 	}
 }
 
@@ -40,7 +103,25 @@ pub mod code {
 /// # arguments
 ///
 /// - (optional) `lang`:\
-///   the language code to highlight, modify [`doll.builtin_emitters.code_block`](crate::emit::BuiltInEmitters::code_block) to define behavior
+///   the language code to highlight, modify [`HtmlEmit::code_block_format`] to define behavior, such as with `syntect_highlighter` from the `highlight-syntect` feature
+///
+/// # flags
+///
+/// - `numbered`\
+///   show line numbers next to the code
+/// - `dedent`\
+///   strip common leading whitespace from the code, see [`dedent`](super::dedent)
+/// - `diff`\
+///   render the code as a unified diff, implied when `lang` is `diff`
+///
+/// # props
+///
+/// - `title`\
+///   a title to show above the code, such as a filename
+/// - `linenos`\
+///   the line number of the first line, implies `numbered`
+/// - `hl`\
+///   comma-separated lines or inclusive ranges of lines to highlight, such as `1,4-6,12`, counted from the number of the first line
 ///
 /// # content
 ///
@@ -48,51 +129,402 @@ pub mod code {
 pub mod codeblock {
 	use super::*;
 
-	/// represents the language and content
+	/// represents the language, options, and content
+	///
+	/// # breaking
+	///
+	/// this struct gained the `title`, `start_line`, and `numbered` fields, custom `codeblock` emitters that construct or destructure it exhaustively must be updated
 	#[derive(Debug)]
 	pub struct Block {
 		/// the language
 		pub lang: Option<String>,
+		/// the title, usually a filename
+		pub title: Option<String>,
+		/// the number of the first line
+		pub start_line: usize,
+		/// whether line numbers should be shown
+		pub numbered: bool,
+		/// the line numbers to highlight
+		pub highlight: Vec<Range<usize>>,
+		/// whether the code is a unified diff, coloring added/removed lines
+		pub diff: bool,
 		/// the text
 		pub text: String,
 	}
 
+	impl Block {
+		/// whether the given line number should be highlighted
+		#[must_use]
+		pub fn is_highlighted(&self, line: usize) -> bool {
+			self.highlight.iter().any(|range| range.contains(&line))
+		}
+	}
+
+	/// parse comma-separated line numbers and inclusive ranges, such as `1,4-6,12`
+	#[must_use]
+	pub fn parse_line_ranges(ranges: &str) -> Option<Vec<Range<usize>>> {
+		ranges
+			.split(',')
+			.map(|range| {
+				if let Some((start, end)) = range.split_once('-') {
+					let start = start.trim().parse::<usize>().ok()?;
+					let end = end.trim().parse::<usize>().ok()?;
+
+					(start <= end).then_some(start..end.checked_add(1)?)
+				} else {
+					let line = range.trim().parse::<usize>().ok()?;
+
+					Some(line..line.checked_add(1)?)
+				}
+			})
+			.collect()
+	}
+
 	/// the tag
 	#[must_use]
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"codeblock",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
 					args();
 					opt_args(lang: String);
-					flags();
-					props();
+					flags(numbered, dedent, diff);
+					props(title: String, linenos: usize, hl: String);
+				};
+
+				let start_line = linenos.unwrap_or(1);
+
+				// the number after the last line, which every line number must stay below
+				let Some(end_line) = start_line.checked_add(text.split('\n').count()) else {
+					doll.diag(true, usize::MAX, "prop linenos invalid");
+					return None;
+				};
+
+				let highlight = if let Some(hl) = hl {
+					if let Some(highlight) = parse_line_ranges(&hl) {
+						highlight
+					} else {
+						doll.diag(
+							true,
+							usize::MAX,
+							"prop hl invalid, expected lines or ranges such as `1,4-6,12`",
+						);
+						return None;
+					}
+				} else {
+					Vec::new()
 				};
 
+				// the parts of each range before the first line and after the last
+				let outside = highlight
+					.iter()
+					.flat_map(|range| {
+						[
+							range.start..range.end.min(start_line),
+							range.start.max(end_line)..range.end,
+						]
+					})
+					.filter(|range| !range.is_empty())
+					.map(|range| {
+						if range.len() == 1 {
+							format!("{}", range.start)
+						} else {
+							format!("{}-{}", range.start, range.end - 1)
+						}
+					})
+					.collect::<Vec<_>>();
+
+				if !outside.is_empty() {
+					let count = doll.diagnostics.len();
+					doll.diag(
+						false,
+						usize::MAX,
+						"highlighted lines are outside of the code",
+					);
+
+					// unless it was suppressed
+					if doll.diagnostics.len() > count {
+						doll.diagnostics.last_mut().unwrap().data = Some(Data::LinesOutside {
+							lines: outside.join(","),
+							first: start_line,
+							last: end_line - 1,
+						});
+					}
+				}
+
 				Some(Box::new(Block {
+					diff: diff || lang.as_deref() == Some("diff"),
 					lang,
-					text: text.to_string(),
+					title,
+					start_line,
+					numbered: numbered || linenos.is_some(),
+					highlight,
+					text: if dedent {
+						super::dedent(text).into_owned()
+					} else {
+						text.to_string()
+					},
 				}))
 			}),
 		)
+		.with_printer(|_, content, _| content.downcast_ref::<Block>().unwrap().text.clone())
 		.with_emitter::<HtmlEmit>(html)
 	}
 
+	/// close the elements in `open`, innermost first
+	fn close_all(open: &[&str], out: &mut String) {
+		for tag in open.iter().rev() {
+			let name = tag[1..]
+				.split(|ch: char| ch.is_ascii_whitespace() || ch == '>')
+				.next()
+				.unwrap_or_default();
+			write!(out, "</{name}>").unwrap();
+		}
+	}
+
+	/// copy the html a [`CodeBlockFormatter`](crate::emit::CodeBlockFormatter) wrote into `out`, wrapping the highlighted lines within its `<pre>` in `<span class='doll-hl-line'>`
+	///
+	/// elements spanning several lines, such as those of multi-line comments, are closed before each highlighted line and opened again within it, so the output stays well-formed. formatters writing no `<pre>` are copied as they are
+	fn highlight_lines(code: &Block, html: &str, out: &mut String) {
+		let Some((start, end)) = html.find("<pre").and_then(|pre| {
+			let start = pre + html[pre..].find('>')? + 1;
+			Some((start, html.rfind("</pre>").filter(|end| *end >= start)?))
+		}) else {
+			out.push_str(html);
+			return;
+		};
+
+		out.push_str(&html[..start]);
+
+		// the opening tags of the elements open at this point
+		let mut open = Vec::new();
+
+		for (i, line) in html[start..end].split('\n').enumerate() {
+			if i > 0 {
+				out.push('\n');
+			}
+
+			let highlighted = code.is_highlighted(code.start_line + i);
+			if highlighted {
+				close_all(&open, out);
+				out.push_str("<span class='doll-hl-line'>");
+				out.extend(open.iter().copied());
+			}
+
+			out.push_str(line);
+
+			let mut rest = line;
+			while let Some(lt) = rest.find('<') {
+				let Some(len) = rest[lt..].find('>') else {
+					break;
+				};
+				let tag = &rest[lt..=lt + len];
+
+				if tag.starts_with("</") {
+					open.pop();
+				} else if !tag.ends_with("/>") {
+					open.push(tag);
+				}
+
+				rest = &rest[lt + len + 1..];
+			}
+
+			if highlighted {
+				close_all(&open, out);
+				out.push_str("</span>");
+				out.extend(open.iter().copied());
+			}
+		}
+
+		out.push_str(&html[end..]);
+	}
+
 	/// emit to html
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let code = content.downcast_ref::<Block>().unwrap();
 
+		to.write.push_str("<figure class='doll-code-block'");
+
 		if let Some(lang) = &code.lang {
+			write!(
+				to.write,
+				" data-lang='{}'",
+				&html_escape::encode_single_quoted_attribute(lang)
+			)
+			.unwrap();
+		}
+
+		to.write.push('>');
+
+		if let Some(title) = &code.title {
+			write!(
+				to.write,
+				"<figcaption>{}</figcaption>",
+				&html_escape::encode_text(title)
+			)
+			.unwrap();
+		}
+
+		if code.numbered {
+			to.write.push_str(
+				"<div class='doll-code-numbered'><pre class='doll-code-gutter' aria-hidden='true'>",
+			);
+
+			for (i, _) in code.text.split('\n').enumerate() {
+				if i > 0 {
+					to.write.push('\n');
+				}
+
+				let line = code.start_line + i;
+				if code.is_highlighted(line) {
+					write!(to.write, "<span class='doll-hl-line'>{line}</span>").unwrap();
+				} else {
+					write!(to.write, "{line}").unwrap();
+				}
+			}
+
+			to.write.push_str("</pre>");
+		}
+
+		if let Some(lang) = code.lang.as_ref().filter(|_| !code.diff) {
+			let start = to.write.len();
 			(to.code_block_format.clone())(doll, to, lang, &code.text);
+
+			if !code.highlight.is_empty() {
+				let formatted = to.write.split_off(start);
+				highlight_lines(code, &formatted, &mut to.write);
+			}
 		} else {
-			to.write.push_str(&format!(
-				"<div class='doll-code-block'><pre>{}</pre></div>",
-				&html_escape::encode_text(&code.text)
-			));
+			to.write.push_str("<pre>");
+
+			for (i, text) in code.text.split('\n').enumerate() {
+				if i > 0 {
+					to.write.push('\n');
+				}
+
+				let highlighted = code.is_highlighted(code.start_line + i);
+				if highlighted {
+					to.write.push_str("<span class='doll-hl-line'>");
+				}
+
+				let class = if !code.diff {
+					None
+				} else if text.starts_with("@@") {
+					Some("doll-diff-hunk")
+				} else if text.starts_with('+') {
+					Some("doll-diff-add")
+				} else if text.starts_with('-') {
+					Some("doll-diff-del")
+				} else {
+					None
+				};
+
+				if let Some(class) = class {
+					write!(
+						to.write,
+						"<span class='{class}'>{}</span>",
+						&html_escape::encode_text(text)
+					)
+					.unwrap();
+				} else {
+					to.write.push_str(&html_escape::encode_text(text));
+				}
+
+				if highlighted {
+					to.write.push_str("</span>");
+				}
+			}
+
+			to.write.push_str("</pre>");
+		}
+
+		if code.numbered {
+			to.write.push_str("</div>");
 		}
+
+		to.write.push_str("</figure>");
+	}
+}
+
+/// a [`CodeBlockFormatter`](crate::emit::CodeBlockFormatter) that highlights code with [syntect](https://docs.rs/syntect)
+///
+/// tokens are annotated with `doll-syn-` prefixed classes instead of inline styles, so the output can be restyled with css, and the stylesheet for `theme` is written in a `<style>` element ahead of the first highlighted block
+///
+/// the syntaxes and themes are loaded lazily on first use and shared between all highlighters, languages that aren't recognized are emitted as plain `<pre>` with a warning
+///
+/// the highlighter remembers whether it has written its stylesheet, so create a new one for each document. name it in [`HtmlEmit::code_block_format_name`], such as `syntect:<theme>`, so the options hash changes with the theme
+#[cfg(feature = "highlight-syntect")]
+pub fn syntect_highlighter(
+	theme: &str,
+) -> impl Fn(&mut MarkDoll, &mut HtmlEmit, &str, &str) + Send + Sync {
+	use {
+		core::sync::atomic::{AtomicBool, Ordering},
+		std::sync::OnceLock,
+		syntect::{
+			highlighting::ThemeSet,
+			html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+			parsing::SyntaxSet,
+			util::LinesWithEndings,
+		},
+	};
+
+	const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+		prefix: "doll-syn-",
+	};
+
+	static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+	static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+
+	let theme = theme.to_string();
+	let wrote_css = AtomicBool::new(false);
+
+	move |doll, to, lang, src| {
+		let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+
+		let Some(syntax) = syntaxes.find_syntax_by_token(lang) else {
+			doll.diag(
+				false,
+				usize::MAX,
+				"language not recognized, not highlighting",
+			);
+
+			write!(to.write, "<pre>{}</pre>", &html_escape::encode_text(src)).unwrap();
+
+			return;
+		};
+
+		if !wrote_css.swap(true, Ordering::Relaxed) {
+			let themes = THEMES.get_or_init(ThemeSet::load_defaults);
+
+			if let Some(css) = themes
+				.themes
+				.get(&theme)
+				.and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+			{
+				write!(to.write, "<style>{css}</style>").unwrap();
+			} else {
+				doll.diag(false, usize::MAX, "highlight theme not found");
+			}
+		}
+
+		let mut html = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+		for line in LinesWithEndings::from(src) {
+			if html
+				.parse_html_for_line_which_includes_newline(line)
+				.is_err()
+			{
+				doll.diag(false, usize::MAX, "failed to highlight code");
+
+				write!(to.write, "<pre>{}</pre>", &html_escape::encode_text(src)).unwrap();
+
+				return;
+			}
+		}
+
+		write!(to.write, "<pre>{}</pre>", html.finalize()).unwrap();
 	}
 }
 
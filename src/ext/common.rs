@@ -1,19 +1,75 @@
-use crate::ext::TagDefinition;
+use crate::{
+	emit::HtmlEmit,
+	ext::{todo::DraftPolicy, TagDefinition},
+	tree::TagContent,
+	MarkDoll,
+};
 
 /// `//` tag
 ///
-/// exclude content from the output
+/// exclude content from the output, unless [`MarkDoll::keep_comments`] is set, which keeps it as a [`Comment`](comment::Comment) for drafts
 ///
 /// # content
 ///
 /// anything
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], follows [`HtmlEmit::draft_mode`] as [`todo`](crate::ext::todo) tags do, writing the text in an `aside` with the `doll-comment` class when shown
 pub mod comment {
-	use super::*;
+	use {
+		super::*,
+		alloc::{
+			boxed::Box,
+			string::{String, ToString},
+		},
+		core::fmt::Write,
+	};
+
+	/// a comment kept by [`MarkDoll::keep_comments`]
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Comment {
+		/// the text, with every line of a block comment
+		pub text: String,
+	}
 
 	/// the tag
 	#[must_use]
 	pub fn tag() -> TagDefinition {
-		TagDefinition::new("//", Some(|_, _, _| None))
+		TagDefinition::new(
+			"//",
+			Some(|doll, _, text, _| {
+				doll.keep_comments.then(|| {
+					Box::new(Comment {
+						text: text.to_string(),
+					}) as Box<dyn TagContent>
+				})
+			}),
+		)
+		.with_printer(|_, content, _| content.downcast_ref::<Comment>().unwrap().text.clone())
+		.with_pure(|content| Some(Box::new(content.downcast_ref::<Comment>().unwrap().clone())))
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let comment = content.downcast_ref::<Comment>().unwrap();
+
+		match to.draft_mode {
+			DraftPolicy::Show => {}
+			DraftPolicy::Hide => return,
+			DraftPolicy::Error => {
+				doll.diag(true, usize::MAX, "comment in release");
+				return;
+			}
+		}
+
+		write!(
+			to.write,
+			"<aside class='doll-comment'>{}</aside>",
+			html_escape::encode_text(&comment.text)
+		)
+		.unwrap();
 	}
 }
 
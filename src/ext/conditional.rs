@@ -0,0 +1,159 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		tree::{print, TagContent, AST},
+		MarkDoll,
+	},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// a flag within a [`Condition`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+	/// whether the flag must be unset, written `not:<flag>`
+	pub negated: bool,
+	/// the flag
+	pub flag: String,
+}
+
+/// content that is only emitted when any of its terms hold
+#[derive(Debug)]
+pub struct Condition {
+	/// the terms, any of which must hold
+	pub terms: Vec<Term>,
+	/// the content
+	pub ast: AST,
+}
+
+impl Condition {
+	/// whether the content should be emitted, given whether each flag is set
+	///
+	/// flags for which `known` returns false produce a warning at the first argument of the tag invoked, which holds the condition, `target:` flags are always known since they only differ between targets
+	pub fn eval(
+		&self,
+		doll: &mut MarkDoll,
+		set: impl Fn(&str) -> bool,
+		known: impl Fn(&str) -> bool,
+	) -> bool {
+		let mut holds = false;
+
+		for term in &self.terms {
+			if !term.flag.starts_with("target:") && !known(&term.flag) {
+				doll.diag_arg(false, 0, "flag not defined");
+			}
+
+			holds |= set(&term.flag) != term.negated;
+		}
+
+		holds
+	}
+}
+
+/// `if` tag
+///
+/// include content only when emitting with certain flags, so the same parsed document can emit differently to each target
+///
+/// # arguments
+///
+/// - `condition`\
+///   flags separated by `,`, any of which must be set, or unset when written `not:<flag>`\
+///   emit targets set `target:<target>`, such as `target:html`, alongside their own flags
+///
+/// # content
+///
+/// markdoll, emitted only when the condition holds
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], `target:html` and [`HtmlEmit::flags`] are set, and any other flag is warned about as undefined
+///
+/// custom emit targets can support the tag the same way, by registering an emitter that calls [`Condition::eval`] with their own flags
+pub mod cond {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"if",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(condition);
+					opt_args();
+					flags();
+					props();
+				};
+
+				let mut terms = Vec::new();
+				for term in condition.split(',') {
+					let term = term.trim();
+					let (negated, flag) = match term.strip_prefix("not:") {
+						Some(flag) => (true, flag),
+						None => (false, term),
+					};
+
+					if flag.is_empty() {
+						doll.diag(true, usize::MAX, "condition invalid");
+						return None;
+					}
+
+					terms.push(Term {
+						negated,
+						flag: flag.to_string(),
+					});
+				}
+
+				Some(Box::new(Condition {
+					terms,
+					ast: match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					},
+				}))
+			}),
+		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Condition>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Condition>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Condition>().unwrap().ast,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let condition = content.downcast_mut::<Condition>().unwrap();
+
+		let set = |flag: &str| flag == "target:html" || to.flags.contains(flag);
+		if !condition.eval(doll, set, set) {
+			return;
+		}
+
+		let inline_block = condition.ast.len() > 1;
+		for item in &mut condition.ast {
+			item.emit(doll, to, inline_block);
+		}
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[cond::tag()]
+}
@@ -0,0 +1,42 @@
+use {
+	crate::{emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+	},
+};
+
+/// `html` tag
+///
+/// **dangerous**: writes its content into the output without any escaping, so a document using it can inject arbitrary markup and scripts, only register it for documents you trust. [untrusted](MarkDoll::untrusted) documents can't use it, even if it is registered
+///
+/// only emits to html, other emit targets will produce a diagnostic rather than receiving raw html
+///
+/// # content
+///
+/// html, used verbatim
+pub mod raw {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"html",
+			Some(|_, _, text, _| Some(Box::new(text.to_string()))),
+		)
+		.with_passthrough()
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		to.write.push_str(content.downcast_ref::<String>().unwrap());
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[raw::tag()]
+}
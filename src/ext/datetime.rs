@@ -0,0 +1,410 @@
+use {
+	crate::{
+		args,
+		emit::{HtmlEmit, StringKey, Strings},
+		ext::{ArgValue, TagDefinition},
+		tree::{print, TagContent, AST},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+	},
+	core::fmt::Write,
+};
+
+/// the unit of a relative time, see [`StringKey::Ago`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+	/// 60 seconds
+	Minute,
+	/// 60 minutes
+	Hour,
+	/// 24 hours
+	Day,
+	/// 30 days
+	Month,
+	/// 365 days
+	Year,
+}
+
+/// a date, with an optional time of day, parsed from ISO-8601
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+	/// the year
+	pub year: i32,
+	/// the month, from 1
+	pub month: u8,
+	/// the day of the month, from 1
+	pub day: u8,
+	/// the hour, minute, and second, if a time was given
+	pub time: Option<(u8, u8, u8)>,
+	/// the offset from UTC in minutes, if one was given
+	pub offset: Option<i32>,
+}
+
+/// the number of days in a month
+fn days_in_month(year: i32, month: u8) -> u8 {
+	match month {
+		2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+/// take a fixed number of digits from the start of `src`, if they are within `range`
+fn digits(src: &mut &str, len: usize, range: core::ops::RangeInclusive<u32>) -> Option<u32> {
+	let part = src.get(..len)?;
+	if !part.bytes().all(|byte| byte.is_ascii_digit()) {
+		return None;
+	}
+
+	let value = part.parse().ok().filter(|value| range.contains(value))?;
+	*src = &src[len..];
+	Some(value)
+}
+
+/// take a character from the start of `src`, if it is one of `chars`
+fn separator(src: &mut &str, chars: &[char]) -> Option<char> {
+	let ch = src.chars().next().filter(|ch| chars.contains(ch))?;
+	*src = &src[ch.len_utf8()..];
+	Some(ch)
+}
+
+impl DateTime {
+	/// parse `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fraction]]` and `Z` or `±HH:MM`
+	///
+	/// # errors
+	///
+	/// returns the diagnostic code naming the component that failed to parse
+	#[allow(
+		clippy::cast_possible_truncation,
+		clippy::cast_possible_wrap,
+		reason = "digits are range checked"
+	)]
+	pub fn parse(mut src: &str) -> Result<Self, &'static str> {
+		let src = &mut src;
+
+		let year = digits(src, 4, 0..=9999).ok_or("time year invalid")? as i32;
+		let month = separator(src, &['-'])
+			.and_then(|_| digits(src, 2, 1..=12))
+			.ok_or("time month invalid")? as u8;
+		let day = separator(src, &['-'])
+			.and_then(|_| digits(src, 2, 1..=u32::from(days_in_month(year, month))))
+			.ok_or("time day invalid")? as u8;
+
+		let mut datetime = Self {
+			year,
+			month,
+			day,
+			time: None,
+			offset: None,
+		};
+
+		if src.is_empty() {
+			return Ok(datetime);
+		}
+
+		let hour = separator(src, &['T', 't'])
+			.and_then(|_| digits(src, 2, 0..=23))
+			.ok_or("time hour invalid")?;
+		let minute = separator(src, &[':'])
+			.and_then(|_| digits(src, 2, 0..=59))
+			.ok_or("time minute invalid")?;
+
+		let mut second = 0;
+		if separator(src, &[':']).is_some() {
+			second = digits(src, 2, 0..=59).ok_or("time second invalid")?;
+
+			if separator(src, &['.', ',']).is_some() {
+				let len = src.bytes().take_while(u8::is_ascii_digit).count();
+				if len == 0 {
+					return Err("time second invalid");
+				}
+				*src = &src[len..];
+			}
+		}
+
+		datetime.time = Some((hour as u8, minute as u8, second as u8));
+
+		match separator(src, &['Z', 'z', '+', '-']) {
+			None => {}
+			Some('Z' | 'z') => datetime.offset = Some(0),
+			Some(sign) => {
+				let hours = digits(src, 2, 0..=23).ok_or("time offset invalid")?;
+				let minutes = separator(src, &[':'])
+					.and_then(|_| digits(src, 2, 0..=59))
+					.ok_or("time offset invalid")?;
+
+				let offset = (hours * 60 + minutes) as i32;
+				datetime.offset = Some(if sign == '-' { -offset } else { offset });
+			}
+		}
+
+		if src.is_empty() {
+			Ok(datetime)
+		} else {
+			Err("time has trailing characters")
+		}
+	}
+
+	/// the number of days since 1970-01-01
+	#[must_use]
+	pub fn days(&self) -> i64 {
+		let month = i64::from(self.month);
+		let year = i64::from(self.year) - i64::from(month <= 2);
+
+		let era = year.div_euclid(400);
+		let year_of_era = year - era * 400;
+		let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+		let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+		era * 146_097 + day_of_era - 719_468
+	}
+
+	/// the unix timestamp, treating a missing time as midnight and a missing offset as UTC
+	#[must_use]
+	pub fn timestamp(&self) -> i64 {
+		let (hour, minute, second) = self.time.unwrap_or_default();
+
+		self.days() * 86_400 + i64::from(hour) * 3600 + i64::from(minute) * 60 + i64::from(second)
+			- i64::from(self.offset.unwrap_or_default()) * 60
+	}
+
+	/// the day of the week, from 0 for sunday
+	#[must_use]
+	pub fn weekday(&self) -> u8 {
+		// 1970-01-01 was a thursday
+		#[allow(clippy::cast_possible_truncation, reason = "always within 0..7")]
+		{
+			(self.days() + 4).rem_euclid(7) as u8
+		}
+	}
+}
+
+/// `time` tag
+///
+/// insert a machine-readable date or time via `<time>`
+///
+/// # arguments
+///
+/// - `datetime`\
+///   an ISO-8601 date, optionally with a time, such as `2024-03-09` or `2024-03-09T14:30:00+01:00`
+///
+/// # flags
+///
+/// - relative\
+///   display the time relative to [`HtmlEmit::time_reference`], such as `3 days ago`
+///
+/// # props
+///
+/// - `format`\
+///   how to display the time
+///   - iso\
+///     as written\
+///     **default**
+///   - date\
+///     `March 9, 2024`
+///   - datetime\
+///     `March 9, 2024 14:30`
+///   - weekday\
+///     `Saturday, March 9, 2024`
+///
+/// # content
+///
+/// markdoll, displayed instead of the time if present
+pub mod time {
+	use super::*;
+
+	/// how to display a [`Time`]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum Format {
+		/// as written
+		Iso,
+		/// `March 9, 2024`
+		Date,
+		/// `March 9, 2024 14:30`
+		DateTime,
+		/// `Saturday, March 9, 2024`
+		Weekday,
+	}
+
+	/// a date or time and how to display it
+	#[derive(Debug)]
+	pub struct Time {
+		/// the argument, as written
+		pub src: String,
+		/// the parsed date and time
+		pub datetime: DateTime,
+		/// how to display it
+		pub format: Format,
+		/// whether to display it relative to [`HtmlEmit::time_reference`]
+		pub relative: bool,
+		/// content displayed instead, if present
+		pub content: Option<AST>,
+	}
+
+	impl Time {
+		/// the displayed text in the language of `strings`, ignoring [`relative`](Self::relative) and [`content`](Self::content)
+		#[must_use]
+		pub fn display(&self, strings: &Strings) -> String {
+			let datetime = &self.datetime;
+			let date = strings.get(&StringKey::Date {
+				year: datetime.year,
+				month: datetime.month,
+				day: datetime.day,
+			});
+
+			match self.format {
+				Format::Iso => self.src.clone(),
+				Format::Date => date,
+				Format::DateTime => match datetime.time {
+					Some((hour, minute, _)) => format!("{date} {hour:02}:{minute:02}"),
+					None => date,
+				},
+				Format::Weekday => strings.get(&StringKey::Weekday {
+					weekday: datetime.weekday(),
+					date: &date,
+				}),
+			}
+		}
+
+		/// the displayed text relative to a unix timestamp in the language of `strings`, such as `3 days ago` or `in 2 hours`
+		#[must_use]
+		pub fn display_relative(&self, reference: i64, strings: &Strings) -> String {
+			let diff = reference - self.datetime.timestamp();
+
+			let seconds = diff.unsigned_abs();
+			let (count, unit) = match seconds {
+				0..60 => return strings.get(&StringKey::JustNow),
+				60..3600 => (seconds / 60, Unit::Minute),
+				3600..86_400 => (seconds / 3600, Unit::Hour),
+				86_400..2_592_000 => (seconds / 86_400, Unit::Day),
+				2_592_000..31_104_000 => (seconds / 2_592_000, Unit::Month),
+				_ => ((seconds / 31_536_000).max(1), Unit::Year),
+			};
+
+			strings.get(&if diff > 0 {
+				StringKey::Ago(count, unit)
+			} else {
+				StringKey::In(count, unit)
+			})
+		}
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"time",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(datetime);
+					opt_args();
+					flags(relative);
+					props(format: ArgValue<String>);
+				}
+
+				let parsed = match DateTime::parse(datetime) {
+					Ok(parsed) => parsed,
+					Err(code) => {
+						doll.diag_arg(true, 0, code);
+						return None;
+					}
+				};
+
+				let format = match format.as_ref().map_or("iso", |format| format.as_str()) {
+					"iso" => Format::Iso,
+					"date" => Format::Date,
+					"datetime" => Format::DateTime,
+					"weekday" => Format::Weekday,
+					_ => {
+						format.unwrap().diag(doll, true, "time format invalid");
+						return None;
+					}
+				};
+
+				Some(Box::new(Time {
+					src: datetime.to_string(),
+					datetime: parsed,
+					format,
+					relative,
+					content: (!text.is_empty()).then(|| match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					}),
+				}))
+			}),
+		)
+		.with_children(
+			|content| {
+				content
+					.downcast_ref::<Time>()
+					.unwrap()
+					.content
+					.iter()
+					.collect()
+			},
+			|content| {
+				content
+					.downcast_mut::<Time>()
+					.unwrap()
+					.content
+					.iter_mut()
+					.collect()
+			},
+		)
+		.with_printer(|doll, content, options| {
+			content
+				.downcast_ref::<Time>()
+				.unwrap()
+				.content
+				.as_ref()
+				.map(|ast| print::to_doll(doll, ast, options))
+				.unwrap_or_default()
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let time = content.downcast_mut::<Time>().unwrap();
+
+		write!(
+			to.write,
+			"<time datetime='{}'>",
+			&html_escape::encode_single_quoted_attribute(&time.src)
+		)
+		.unwrap();
+
+		if let Some(ast) = &mut time.content {
+			let inline_block = ast.len() > 1;
+			for item in ast {
+				item.emit(doll, to, inline_block);
+			}
+		} else if time.relative {
+			if let Some(reference) = to.time_reference {
+				let text = time.display_relative(reference, &to.strings);
+				to.write.push_str(&html_escape::encode_text(&text));
+			} else {
+				doll.diag(false, usize::MAX, "no time reference for relative time");
+				to.write
+					.push_str(&html_escape::encode_text(&time.display(&to.strings)));
+			}
+		} else {
+			to.write
+				.push_str(&html_escape::encode_text(&time.display(&to.strings)));
+		}
+
+		to.write.push_str("</time>");
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[time::tag()]
+}
@@ -0,0 +1,107 @@
+use {
+	crate::{args, emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+	},
+};
+
+/// the built-in renderer for the `preformatted` kind, which shows the source as is
+///
+/// # errors
+///
+/// never
+pub fn preformatted(src: &str) -> Result<String, &'static str> {
+	Ok(format!(
+		"<pre class='doll-diagram-src'>{}</pre>",
+		html_escape::encode_text(src)
+	))
+}
+
+/// `diagram` tag
+///
+/// render a diagram or chart with the renderer registered for its kind in [`HtmlEmit::diagram_renderers`]
+///
+/// renderers are ordinary callbacks from the embedder, so a `dot` or `mermaid` kind can shell out to graphviz, call a library, or leave markup for client-side rendering, without markdoll depending on any of them
+///
+/// # arguments
+///
+/// - `kind`\
+///   the renderer to use, `preformatted` is always available and shows the source as is
+///
+/// # content
+///
+/// the diagram source, kept raw so its backslashes survive
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes the HTML returned by the renderer, and if it fails, or none is registered for the kind, reports a diagnostic and shows the source as `preformatted` does
+pub mod diagram {
+	use super::*;
+
+	/// a diagram and its kind
+	#[derive(Debug)]
+	pub struct Diagram {
+		/// the renderer to use
+		pub kind: String,
+		/// the source
+		pub src: String,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"diagram",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(kind: String);
+					opt_args();
+					flags();
+					props();
+				}
+
+				Some(Box::new(Diagram {
+					kind,
+					src: text.to_string(),
+				}))
+			}),
+		)
+		.with_raw()
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let diagram = content.downcast_ref::<Diagram>().unwrap();
+
+		let rendered = match to.diagram_renderers.get(&diagram.kind) {
+			Some(render) => render(&diagram.src),
+			None if diagram.kind == "preformatted" => preformatted(&diagram.src),
+			None => {
+				doll.diag_arg(true, 0, "diagram kind not registered");
+				to.write
+					.push_str(&preformatted(&diagram.src).unwrap_or_default());
+				return;
+			}
+		};
+
+		match rendered {
+			Ok(html) => to.write.push_str(&html),
+			Err(code) => {
+				doll.diag(true, usize::MAX, code);
+				to.write
+					.push_str(&preformatted(&diagram.src).unwrap_or_default());
+			}
+		}
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[diagram::tag()]
+}
@@ -0,0 +1,394 @@
+use {
+	crate::{
+		args,
+		diagnostics::TagDiagnosticTranslation,
+		emit::HtmlEmit,
+		ext::{edit_distance, TagDefinition},
+		tree::{
+			visit::{self, VisitorMut},
+			BlockItem, InlineItem, TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
+	},
+	core::fmt::Write,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// shortcodes and the emoji they stand for, a subset of [gemoji](https://github.com/github/gemoji) sorted by shortcode
+pub const SHORTCODES: [(&str, &str); 140] = [
+	("+1", "👍"),
+	("-1", "👎"),
+	("100", "💯"),
+	("airplane", "✈️"),
+	("alarm_clock", "⏰"),
+	("angry", "😠"),
+	("apple", "🍎"),
+	("art", "🎨"),
+	("baby", "👶"),
+	("balloon", "🎈"),
+	("bangbang", "‼️"),
+	("beer", "🍺"),
+	("bell", "🔔"),
+	("bike", "🚲"),
+	("birthday", "🎂"),
+	("blush", "😊"),
+	("book", "📖"),
+	("books", "📚"),
+	("boom", "💥"),
+	("bow", "🙇"),
+	("brain", "🧠"),
+	("broken_heart", "💔"),
+	("bug", "🐛"),
+	("bulb", "💡"),
+	("bust_in_silhouette", "👤"),
+	("cake", "🍰"),
+	("calendar", "📆"),
+	("camera", "📷"),
+	("cat", "🐱"),
+	("chart_with_upwards_trend", "📈"),
+	("check", "✔️"),
+	("clap", "👏"),
+	("clipboard", "📋"),
+	("cloud", "☁️"),
+	("coffee", "☕"),
+	("computer", "💻"),
+	("confused", "😕"),
+	("construction", "🚧"),
+	("cookie", "🍪"),
+	("cool", "🆒"),
+	("crab", "🦀"),
+	("cry", "😢"),
+	("crystal_ball", "🔮"),
+	("dog", "🐶"),
+	("dolls", "🎎"),
+	("email", "📧"),
+	("eyes", "👀"),
+	("fire", "🔥"),
+	("flushed", "😳"),
+	("frowning", "😦"),
+	("gear", "⚙️"),
+	("gem", "💎"),
+	("ghost", "👻"),
+	("gift", "🎁"),
+	("globe_with_meridians", "🌐"),
+	("grin", "😁"),
+	("grinning", "😀"),
+	("hammer", "🔨"),
+	("hand", "✋"),
+	("heart", "❤️"),
+	("heart_eyes", "😍"),
+	("heavy_check_mark", "✔️"),
+	("hourglass", "⌛"),
+	("house", "🏠"),
+	("hugs", "🤗"),
+	("hushed", "😯"),
+	("information_source", "ℹ️"),
+	("innocent", "😇"),
+	("joy", "😂"),
+	("key", "🔑"),
+	("kiss", "💋"),
+	("laughing", "😆"),
+	("link", "🔗"),
+	("lock", "🔒"),
+	("mag", "🔍"),
+	("memo", "📝"),
+	("moon", "🌙"),
+	("muscle", "💪"),
+	("musical_note", "🎵"),
+	("no_entry", "⛔"),
+	("ok_hand", "👌"),
+	("package", "📦"),
+	("paperclip", "📎"),
+	("partying_face", "🥳"),
+	("pencil2", "✏️"),
+	("pensive", "😔"),
+	("point_down", "👇"),
+	("point_left", "👈"),
+	("point_right", "👉"),
+	("point_up", "☝️"),
+	("pray", "🙏"),
+	("pushpin", "📌"),
+	("question", "❓"),
+	("rainbow", "🌈"),
+	("raised_hands", "🙌"),
+	("recycle", "♻️"),
+	("relaxed", "☺️"),
+	("rocket", "🚀"),
+	("rofl", "🤣"),
+	("rose", "🌹"),
+	("rotating_light", "🚨"),
+	("scream", "😱"),
+	("see_no_evil", "🙈"),
+	("seedling", "🌱"),
+	("shrug", "🤷"),
+	("skull", "💀"),
+	("sleeping", "😴"),
+	("slightly_smiling_face", "🙂"),
+	("smile", "😄"),
+	("smiley", "😃"),
+	("smirk", "😏"),
+	("snake", "🐍"),
+	("snowflake", "❄️"),
+	("sob", "😭"),
+	("sparkles", "✨"),
+	("sparkling_heart", "💖"),
+	("speech_balloon", "💬"),
+	("star", "⭐"),
+	("star2", "🌟"),
+	("stuck_out_tongue", "😛"),
+	("sunglasses", "😎"),
+	("sunny", "☀️"),
+	("sweat_smile", "😅"),
+	("tada", "🎉"),
+	("thinking", "🤔"),
+	("thumbsdown", "👎"),
+	("thumbsup", "👍"),
+	("trophy", "🏆"),
+	("tulip", "🌷"),
+	("unamused", "😒"),
+	("unlock", "🔓"),
+	("warning", "⚠️"),
+	("wave", "👋"),
+	("white_check_mark", "✅"),
+	("wink", "😉"),
+	("wrench", "🔧"),
+	("x", "❌"),
+	("yum", "😋"),
+	("zap", "⚡"),
+	("zzz", "💤"),
+];
+
+/// the emoji a shortcode stands for
+#[must_use]
+pub fn lookup(name: &str) -> Option<&'static str> {
+	SHORTCODES
+		.binary_search_by(|(shortcode, _)| (*shortcode).cmp(name))
+		.ok()
+		.map(|index| SHORTCODES[index].1)
+}
+
+/// the shortcodes within two edits of an unknown one, closest first, to suggest in its place
+#[must_use]
+pub fn near_matches(name: &str) -> Vec<&'static str> {
+	let mut matches = SHORTCODES
+		.iter()
+		.map(|(shortcode, _)| (edit_distance(name, shortcode), *shortcode))
+		.filter(|(distance, _)| *distance <= 2)
+		.collect::<Vec<_>>();
+	matches.sort_unstable();
+
+	matches
+		.into_iter()
+		.map(|(_, shortcode)| shortcode)
+		.collect()
+}
+
+/// an emoji and the shortcode it was written as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emoji {
+	/// the shortcode
+	pub name: String,
+	/// the emoji
+	pub emoji: &'static str,
+}
+
+/// `emoji` tag
+///
+/// insert an emoji by its shortcode, such as `sparkles`
+///
+/// # arguments
+///
+/// - `name`\
+///   the shortcode, without colons, see [`SHORTCODES`] and [`near_matches`] for unknown ones
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes the emoji as is, register [`html_labelled`](emoji::html_labelled) in place of [`html`](emoji::html) to wrap it in a labelled `<span class='doll-emoji'>` for styling and screen readers
+pub mod emoji {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"emoji",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(name);
+					opt_args();
+					flags();
+					props();
+				};
+
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				let Some(emoji) = lookup(name) else {
+					doll.diag_arg(true, 0, "emoji not found");
+					return None;
+				};
+
+				Some(Box::new(Emoji {
+					name: name.to_string(),
+					emoji,
+				}))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		to.write
+			.push_str(content.downcast_ref::<Emoji>().unwrap().emoji);
+	}
+
+	/// emit to html, wrapped in a `<span>` labelled with the shortcode
+	pub fn html_labelled(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let emoji = content.downcast_ref::<Emoji>().unwrap();
+
+		write!(
+			to.write,
+			"<span class='doll-emoji' role='img' aria-label='{}'>{}</span>",
+			html_escape::encode_single_quoted_attribute(&emoji.name),
+			emoji.emoji
+		)
+		.unwrap();
+	}
+}
+
+/// whether a character can be part of a shortcode
+fn is_shortcode(ch: char) -> bool {
+	ch.is_ascii_alphanumeric() || matches!(ch, '_' | '+' | '-')
+}
+
+/// replaces shortcodes within an [`AST`]
+struct Replace {
+	def: Arc<TagDefinition>,
+}
+
+impl Replace {
+	/// split text into text and `emoji` tags
+	fn replace(&self, doll: &MarkDoll, pos: usize, text: &str, out: &mut Vec<(usize, InlineItem)>) {
+		let mut plain_start = 0;
+		let mut i = 0;
+		while let Some(offset) = text[i..].find(':') {
+			let start = i + offset;
+			let name_start = start + 1;
+			let end = text[name_start..]
+				.find(|ch| !is_shortcode(ch))
+				.map_or(text.len(), |len| name_start + len);
+			i = name_start;
+
+			// shortcodes must stand alone, so times like `12:30:45` are left alone
+			if end == name_start
+				|| !text[end..].starts_with(':')
+				|| text[..start].ends_with(char::is_alphanumeric)
+				|| text[end + 1..].starts_with(char::is_alphanumeric)
+			{
+				continue;
+			}
+
+			let name = &text[name_start..end];
+			let Some(emoji) = lookup(name) else {
+				continue;
+			};
+
+			if plain_start < start {
+				out.push((
+					pos + plain_start,
+					InlineItem::Text(text[plain_start..start].to_string()),
+				));
+			}
+
+			out.push((
+				pos + start,
+				InlineItem::Tag(TagInvocation {
+					tag: "emoji".to_string(),
+					def: Arc::clone(&self.def),
+					args: vec![name.to_string()],
+					content: Box::new(Emoji {
+						name: name.to_string(),
+						emoji,
+					}),
+					// synthesized tags have no source, so their diagnostics are reported where they were inserted
+					diagnostic_translation: Some(TagDiagnosticTranslation::new(
+						Arc::from(&text[start..=end]),
+						usize::MAX,
+						pos + start,
+						0,
+					)),
+					generation: doll.generation,
+				}),
+			));
+
+			plain_start = end + 1;
+			i = plain_start;
+		}
+
+		if plain_start == 0 {
+			out.push((pos, InlineItem::Text(text.to_string())));
+		} else if plain_start < text.len() {
+			out.push((
+				pos + plain_start,
+				InlineItem::Text(text[plain_start..].to_string()),
+			));
+		}
+	}
+}
+
+impl VisitorMut for Replace {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &mut BlockItem) {
+		if let BlockItem::Inline(items) = block {
+			let mut out = Vec::with_capacity(items.len());
+
+			for (pos, item) in core::mem::take(items) {
+				match item {
+					InlineItem::Text(text) => self.replace(doll, pos, &text, &mut out),
+					mut item => {
+						visit::walk_inline_mut(self, doll, pos, &mut item);
+						out.push((pos, item));
+					}
+				}
+			}
+
+			*items = out;
+		} else {
+			visit::walk_block_mut(self, doll, block);
+		}
+	}
+}
+
+/// replace `:name:` shortcodes in the text of an [`AST`] with `emoji` tags
+///
+/// shortcodes must not touch letters or digits on either side, so times like `12:30:45` are left alone, unknown shortcodes are left as written, and code is never touched since its content isn't markdoll
+///
+/// the `emoji` definition registered on `doll` is used if there is one, so its emitters apply to the inserted tags
+pub fn replace_shortcodes(doll: &MarkDoll, ast: &mut AST) {
+	let mut replace = Replace {
+		def: doll
+			.ext_system
+			.get_tag("emoji")
+			.cloned()
+			.unwrap_or_else(|| Arc::new(emoji::tag())),
+	};
+
+	visit::walk_mut(ast, doll, &mut replace);
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[emoji::tag()]
+}
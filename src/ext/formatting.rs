@@ -2,14 +2,22 @@ use {
 	crate::{
 		args,
 		emit::HtmlEmit,
-		ext::TagDefinition,
-		tree::{TagContent, AST},
+		ext::{TagDefinition, TagForm},
+		tree::{self, print, BlockItem, InlineItem, Snippet, TagContent, AST},
 		MarkDoll,
 	},
-	::alloc::format,
-	alloc::{boxed::Box, string::String},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+	hashbrown::HashMap,
 };
 
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
 /// `em` tag
 ///
 /// add emphasis to content
@@ -31,6 +39,12 @@ use {
 ///   highlight via `<mark>`
 /// - q\
 ///   quote via `<q>`
+/// - sc\
+///   small caps via `<span class='doll-sc'>`
+/// - sup\
+///   superscript via `<sup>`
+/// - sub\
+///   subscript via `<sub>`
 ///
 /// # content
 ///
@@ -38,22 +52,52 @@ use {
 pub mod emphasis {
 	use super::*;
 
-	/// holds content and flags for each formatting method
+	/// a form of emphasis, in the order they are nested, outermost first
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum EmphasisKind {
+		/// italics, the `i` flag
+		Italic,
+		/// bold, the `b` flag
+		Bold,
+		/// underline, the `u` flag
+		Underline,
+		/// strikethrough, the `s` flag
+		Strikethrough,
+		/// highlight, the `h` flag
+		Highlight,
+		/// quotes, the `q` flag
+		Quote,
+		/// small caps, the `sc` flag
+		SmallCaps,
+		/// superscript, the `sup` flag
+		Superscript,
+		/// subscript, the `sub` flag
+		Subscript,
+	}
+
+	impl EmphasisKind {
+		/// the tags opening and closing this emphasis in html
+		#[must_use]
+		pub fn html(self) -> (&'static str, &'static str) {
+			match self {
+				Self::Italic => ("<em>", "</em>"),
+				Self::Bold => ("<strong>", "</strong>"),
+				Self::Underline => ("<u>", "</u>"),
+				Self::Strikethrough => ("<s>", "</s>"),
+				Self::Highlight => ("<mark>", "</mark>"),
+				Self::Quote => ("<q>", "</q>"),
+				Self::SmallCaps => ("<span class='doll-sc'>", "</span>"),
+				Self::Superscript => ("<sup>", "</sup>"),
+				Self::Subscript => ("<sub>", "</sub>"),
+			}
+		}
+	}
+
+	/// holds content and the forms of emphasis applied to it
 	#[derive(Debug)]
-	#[allow(clippy::struct_excessive_bools, reason = "required")]
 	pub struct Emphasis {
-		/// whether the content should be italicized
-		pub italic: bool,
-		/// whether the content should be bolded
-		pub bold: bool,
-		/// whether the content should be underlined
-		pub underline: bool,
-		/// whether the content should be struck out
-		pub strikethrough: bool,
-		/// whether the content should be highlighted
-		pub highlight: bool,
-		/// whether the content should be in quotes
-		pub quote: bool,
+		/// the forms of emphasis, outermost first, without repeats
+		pub kinds: Vec<EmphasisKind>,
 		/// the content
 		pub ast: AST,
 	}
@@ -63,33 +107,63 @@ pub mod emphasis {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"em",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
 					args();
 					opt_args();
-					flags(i, b, u, s, h, q);
+					flags(i, b, u, s, h, q, sc, sup, sub);
 					props();
 				};
 
+				let mut kinds = [
+					(i, EmphasisKind::Italic),
+					(b, EmphasisKind::Bold),
+					(u, EmphasisKind::Underline),
+					(s, EmphasisKind::Strikethrough),
+					(h, EmphasisKind::Highlight),
+					(q, EmphasisKind::Quote),
+					(sc, EmphasisKind::SmallCaps),
+					(sup, EmphasisKind::Superscript),
+					(sub, EmphasisKind::Subscript),
+				]
+				.into_iter()
+				.filter_map(|(flag, kind)| flag.then_some(kind))
+				.collect::<Vec<_>>();
+
+				if kinds.is_empty() {
+					kinds.push(EmphasisKind::Italic);
+				}
+
 				Some(Box::new(Emphasis {
-					italic: i || (!b && !u && !s && !h && !q),
-					bold: b,
-					underline: u,
-					strikethrough: s,
-					highlight: h,
-					quote: q,
+					kinds,
 					ast: match doll.parse(text) {
-						Ok(ast) => ast,
-						Err(ast) => {
-							doll.ok = false;
-							ast
-						}
+						Ok(ast) | Err(ast) => ast,
 					},
 				}))
 			}),
 		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Emphasis>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Emphasis>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Emphasis>().unwrap().ast,
+				options,
+			)
+		})
+		.with_pure(|content| {
+			let em = content.downcast_ref::<Emphasis>().unwrap();
+
+			Some(Box::new(Emphasis {
+				kinds: em.kinds.clone(),
+				ast: tree::try_clone_ast(&em.ast)?,
+			}))
+		})
+		.with_expects_content()
 		.with_emitter::<HtmlEmit>(html)
 	}
 
@@ -97,28 +171,8 @@ pub mod emphasis {
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let em = content.downcast_mut::<Emphasis>().unwrap();
 
-		if em.italic {
-			to.write.push_str("<em>");
-		}
-
-		if em.bold {
-			to.write.push_str("<strong>");
-		}
-
-		if em.underline {
-			to.write.push_str("<u>");
-		}
-
-		if em.strikethrough {
-			to.write.push_str("<s>");
-		}
-
-		if em.highlight {
-			to.write.push_str("<mark>");
-		}
-
-		if em.quote {
-			to.write.push_str("<q>");
+		for kind in &em.kinds {
+			to.write.push_str(kind.html().0);
 		}
 
 		let inline_block = em.ast.len() > 1;
@@ -126,51 +180,45 @@ pub mod emphasis {
 			item.emit(doll, to, inline_block);
 		}
 
-		if em.quote {
-			to.write.push_str("</q>");
-		}
-
-		if em.highlight {
-			to.write.push_str("</mark>");
-		}
-
-		if em.strikethrough {
-			to.write.push_str("</s>");
-		}
-
-		if em.underline {
-			to.write.push_str("</u>");
-		}
-
-		if em.bold {
-			to.write.push_str("</strong>");
-		}
-
-		if em.italic {
-			to.write.push_str("</em>");
+		for kind in em.kinds.iter().rev() {
+			to.write.push_str(kind.html().1);
 		}
 	}
 }
 
 /// `quote` tag
 ///
-/// insert a block quote
+/// insert a block quote, or a quotation within the text when written inline, as `[quote:...]`
 ///
 /// # arguments
 ///
 /// - `cite` (optional)\
 ///   the citation to use
 ///
+/// # flags
+///
+/// - dash\
+///   prefix the citation with an em dash
+///
+/// # props
+///
+/// - `url`\
+///   where the quote is from, given as the `cite` of the block quote, which the citation links to
+///
 /// # content
 ///
 /// markdoll
 pub mod quote {
 	use super::*;
 
-	/// the content of the quote, and optionally a citation
+	/// the content of the quote, and optionally a citation and where it is from
 	#[derive(Debug)]
 	struct Quote {
 		pub cite: Option<String>,
+		pub url: Option<String>,
+		pub dash: bool,
+		/// written inline, so emitted as a quotation within the text rather than a block
+		pub inline: bool,
 		pub ast: AST,
 	}
 
@@ -179,45 +227,94 @@ pub mod quote {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"quote",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, form| {
 				args! {
 					doll, args;
 
 					args();
 					opt_args(cite: String);
-					flags();
-					props();
+					flags(dash);
+					props(url: String);
 				}
 
 				Some(Box::new(Quote {
 					cite,
+					url,
+					dash,
+					inline: form == TagForm::Inline,
 					ast: match doll.parse(text) {
-						Ok(ast) => ast,
-						Err(ast) => {
-							doll.ok = false;
-							ast
-						}
+						Ok(ast) | Err(ast) => ast,
 					},
 				}))
 			}),
 		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Quote>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Quote>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(doll, &content.downcast_ref::<Quote>().unwrap().ast, options)
+		})
+		.with_expects_content()
 		.with_emitter::<HtmlEmit>(html)
 	}
 
+	/// the citation of a quote, linking to where it is from
+	fn write_cite(to: &mut HtmlEmit, quote: &Quote, url: Option<&str>) {
+		if quote.dash {
+			to.write.push_str("\u{2014} ");
+		}
+
+		let cite = html_escape::encode_text(quote.cite.as_deref().unwrap_or_default());
+		match url {
+			Some(url) => write!(to.write, "<a href='{url}'>{cite}</a>").unwrap(),
+			None => to.write.push_str(&cite),
+		}
+	}
+
 	/// emit to html
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let quote = content.downcast_mut::<Quote>().unwrap();
 
+		let url = quote
+			.url
+			.as_deref()
+			.map(|url| html_escape::encode_safe(to.url(doll, url)));
+
+		if quote.inline {
+			to.write.push_str("<span class='doll-quote'>");
+			match &url {
+				Some(url) => write!(to.write, "<q cite='{url}'>").unwrap(),
+				None => to.write.push_str("<q>"),
+			}
+
+			for item in &mut quote.ast {
+				item.emit(doll, to, false);
+			}
+			to.write.push_str("</q>");
+
+			if quote.cite.is_some() {
+				to.write.push_str(" <cite>");
+				write_cite(to, quote, url.as_deref());
+				to.write.push_str("</cite>");
+			}
+
+			to.write.push_str("</span>");
+			return;
+		}
+
 		to.write.push_str("<figure class='doll-quote'>");
 
-		if let Some(cite) = &quote.cite {
-			to.write.push_str(&format!(
-				"<figcaption>{}</figcaption>",
-				&html_escape::encode_text(cite)
-			));
+		if quote.cite.is_some() {
+			to.write.push_str("<figcaption>");
+			write_cite(to, quote, url.as_deref());
+			to.write.push_str("</figcaption>");
 		}
 
-		to.write.push_str("<blockquote>");
+		match &url {
+			Some(url) => write!(to.write, "<blockquote cite='{url}'>").unwrap(),
+			None => to.write.push_str("<blockquote>"),
+		}
 
 		let inline_block = quote.ast.len() > 1;
 		for item in &mut quote.ast {
@@ -228,8 +325,953 @@ pub mod quote {
 	}
 }
 
+/// `verse` tag
+///
+/// insert poetry or lyrics, where every line is kept
+///
+/// # content
+///
+/// markdoll, parsed with [`MarkDoll::hard_breaks`] so each newline within a stanza is a line break, and blank lines separate stanzas
+pub mod verse {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"verse",
+			Some(|doll, args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args();
+					flags();
+					props();
+				}
+
+				let hard_breaks = core::mem::replace(&mut doll.hard_breaks, true);
+				let ast = match doll.parse(text) {
+					Ok(ast) | Err(ast) => ast,
+				};
+				doll.hard_breaks = hard_breaks;
+
+				Some(Box::new(ast))
+			}),
+		)
+		.with_children(
+			|content| vec![content.downcast_ref::<AST>().unwrap()],
+			|content| vec![content.downcast_mut::<AST>().unwrap()],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(doll, content.downcast_ref::<AST>().unwrap(), options)
+		})
+		.with_expects_content()
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let ast = content.downcast_mut::<AST>().unwrap();
+
+		to.write.push_str("<div class='doll-verse'>");
+
+		for item in ast {
+			item.emit(doll, to, true);
+		}
+
+		to.write.push_str("</div>");
+	}
+}
+
+/// `details` tag
+///
+/// insert a disclosure widget, whose content is hidden until it is opened
+///
+/// # arguments
+///
+/// - `summary` (optional)\
+///   the always visible summary, which is parsed as markdoll
+///
+/// # flags
+///
+/// - open\
+///   show the content initially
+///
+/// # content
+///
+/// markdoll
+pub mod details {
+	use super::*;
+
+	/// the summary and content of the disclosure
+	#[derive(Debug)]
+	struct Details {
+		pub summary: Option<Snippet>,
+		pub open: bool,
+		pub ast: AST,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"details",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args(summary);
+					flags(open);
+					props();
+				}
+
+				Some(Box::new(Details {
+					summary: summary.map(|summary| match doll.parse_snippet(summary, usize::MAX) {
+						Ok(summary) | Err(summary) => summary,
+					}),
+					open,
+					ast: match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					},
+				}))
+			}),
+		)
+		.with_children(
+			|content| {
+				let details = content.downcast_ref::<Details>().unwrap();
+				details
+					.summary
+					.iter()
+					.map(|summary| &summary.ast)
+					.chain([&details.ast])
+					.collect()
+			},
+			|content| {
+				let details = content.downcast_mut::<Details>().unwrap();
+				details
+					.summary
+					.iter_mut()
+					.map(|summary| &mut summary.ast)
+					.chain([&mut details.ast])
+					.collect()
+			},
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Details>().unwrap().ast,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let details = content.downcast_mut::<Details>().unwrap();
+
+		to.write.push_str(if details.open {
+			"<details class='doll-details' open>"
+		} else {
+			"<details class='doll-details'>"
+		});
+
+		if let Some(summary) = &mut details.summary {
+			to.write.push_str("<summary>");
+
+			let inline_block = summary.ast.len() > 1;
+			summary.emit(doll, to, inline_block);
+
+			to.write.push_str("</summary>");
+		}
+
+		let inline_block = details.ast.len() > 1;
+		for item in &mut details.ast {
+			item.emit(doll, to, inline_block);
+		}
+
+		to.write.push_str("</details>");
+	}
+}
+
+/// `abbr` tag
+///
+/// insert an abbreviation, with its expansion shown on hover
+///
+/// # arguments
+///
+/// - `title` (optional)\
+///   the expansion, which is otherwise looked up from the `abbrdef` tags emitted before this tag
+///
+/// # content
+///
+/// the abbreviation, as plain text
+pub mod abbr {
+	use super::*;
+
+	/// the abbreviation and its expansion, if given
+	#[derive(Debug)]
+	struct Abbr {
+		pub title: Option<String>,
+		pub text: String,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"abbr",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args(title: String);
+					flags();
+					props();
+				}
+
+				Some(Box::new(Abbr {
+					title,
+					text: text.to_string(),
+				}))
+			}),
+		)
+		.with_printer(|_, content, _| content.downcast_ref::<Abbr>().unwrap().text.clone())
+		.with_expects_content()
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let abbr = content.downcast_ref::<Abbr>().unwrap();
+
+		let title = abbr.title.as_ref().or_else(|| {
+			doll.scratch
+				.get_ref::<abbrdef::Abbreviations>()
+				.and_then(|defs| defs.0.get(&abbr.text))
+		});
+
+		if let Some(title) = title {
+			write!(
+				to.write,
+				"<abbr title='{}'>{}</abbr>",
+				&html_escape::encode_single_quoted_attribute(title),
+				&html_escape::encode_text(&abbr.text)
+			)
+			.unwrap();
+		} else {
+			write!(
+				to.write,
+				"<abbr>{}</abbr>",
+				&html_escape::encode_text(&abbr.text)
+			)
+			.unwrap();
+
+			doll.diag(false, usize::MAX, "abbreviation not defined");
+		}
+	}
+}
+
+/// `abbrdef` tag
+///
+/// define the expansion of an abbreviation for the `abbr` tags emitted after it
+///
+/// # arguments
+///
+/// - `abbr`\
+///   the abbreviation
+/// - `title`\
+///   its expansion
+pub mod abbrdef {
+	use super::*;
+
+	/// the expansions defined so far in an emit, kept in [`MarkDoll::scratch`]
+	#[derive(Debug, Clone, Default)]
+	pub(super) struct Abbreviations(pub HashMap<String, String>);
+
+	/// an abbreviation and its expansion
+	#[derive(Debug)]
+	struct AbbrDef {
+		pub abbr: String,
+		pub title: String,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"abbrdef",
+			Some(|doll, mut args, _, _| {
+				args! {
+					doll, args;
+
+					args(abbr: String, title: String);
+					opt_args();
+					flags();
+					props();
+				}
+
+				Some(Box::new(AbbrDef { abbr, title }))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html, which only defines the abbreviation
+	pub fn html(doll: &mut MarkDoll, _: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let def = content.downcast_ref::<AbbrDef>().unwrap();
+
+		doll.scratch
+			.get_or_insert_with(Abbreviations::default)
+			.0
+			.insert(def.abbr.clone(), def.title.clone());
+	}
+}
+
+/// parse markdoll content, for tags whose content is only an [`AST`]
+fn parse_ast(doll: &mut MarkDoll, text: &str) -> AST {
+	match doll.parse(text) {
+		Ok(ast) | Err(ast) => ast,
+	}
+}
+
+/// emit markdoll content wrapped in an element
+fn html_wrapped(doll: &mut MarkDoll, to: &mut HtmlEmit, element: &str, ast: &mut AST) {
+	to.write_open(doll, element, "");
+
+	let inline_block = ast.len() > 1;
+	for item in ast {
+		item.emit(doll, to, inline_block);
+	}
+
+	to.write_close(doll, element);
+}
+
+/// define a tag whose markdoll content is wrapped in an element by `html`, see [`html_wrapped`]
+fn wrapped_tag(
+	key: &'static str,
+	html: fn(&mut MarkDoll, &mut HtmlEmit, &mut Box<dyn TagContent>),
+) -> TagDefinition {
+	TagDefinition::new(
+		key,
+		Some(|doll, _, text, _| Some(Box::new(parse_ast(doll, text)))),
+	)
+	.with_children(
+		|content| vec![content.downcast_ref::<AST>().unwrap()],
+		|content| vec![content.downcast_mut::<AST>().unwrap()],
+	)
+	.with_printer(|doll, content, options| {
+		print::to_doll(doll, content.downcast_ref::<AST>().unwrap(), options)
+	})
+	.with_expects_content()
+	.with_emitter::<HtmlEmit>(html)
+}
+
+/// content inserted or deleted by an edit, for [`ins`] and [`del`]
+#[derive(Debug)]
+pub struct Edit {
+	/// when the edit was made
+	pub datetime: Option<String>,
+	/// a URL explaining the edit
+	pub cite: Option<String>,
+	/// the content
+	pub ast: AST,
+}
+
+/// define a tag for an [`Edit`], whose content is wrapped in an element by `html`, see [`html_edit`]
+fn edit_tag(
+	key: &'static str,
+	html: fn(&mut MarkDoll, &mut HtmlEmit, &mut Box<dyn TagContent>),
+) -> TagDefinition {
+	TagDefinition::new(
+		key,
+		Some(|doll, mut args, text, _| {
+			args! {
+				doll, args;
+
+				args();
+				opt_args();
+				flags();
+				props(datetime, cite);
+			}
+
+			Some(Box::new(Edit {
+				datetime: datetime.map(ToString::to_string),
+				cite: cite.map(ToString::to_string),
+				ast: parse_ast(doll, text),
+			}))
+		}),
+	)
+	.with_children(
+		|content| vec![&content.downcast_ref::<Edit>().unwrap().ast],
+		|content| vec![&mut content.downcast_mut::<Edit>().unwrap().ast],
+	)
+	.with_printer(|doll, content, options| {
+		print::to_doll(doll, &content.downcast_ref::<Edit>().unwrap().ast, options)
+	})
+	.with_expects_content()
+	.with_emitter::<HtmlEmit>(html)
+}
+
+/// emit an [`Edit`] wrapped in an element, with its attributes
+fn html_edit(doll: &mut MarkDoll, to: &mut HtmlEmit, element: &str, edit: &mut Edit) {
+	write!(to.write, "<{element}").unwrap();
+
+	for (attribute, value) in [("datetime", &edit.datetime), ("cite", &edit.cite)] {
+		if let Some(value) = value {
+			write!(
+				to.write,
+				" {attribute}='{}'",
+				html_escape::encode_single_quoted_attribute(value)
+			)
+			.unwrap();
+		}
+	}
+
+	to.write.push('>');
+
+	let inline_block = edit.ast.len() > 1;
+	for item in &mut edit.ast {
+		item.emit(doll, to, inline_block);
+	}
+
+	write!(to.write, "</{element}>").unwrap();
+}
+
+/// `sub` tag
+///
+/// insert subscript via `<sub>`
+///
+/// # content
+///
+/// markdoll
+pub mod sub {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"sub",
+			Some(|doll, _, text, _| Some(Box::new(parse_ast(doll, text)))),
+		)
+		.with_children(
+			|content| vec![content.downcast_ref::<AST>().unwrap()],
+			|content| vec![content.downcast_mut::<AST>().unwrap()],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(doll, content.downcast_ref::<AST>().unwrap(), options)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "sub", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `sup` tag
+///
+/// insert superscript via `<sup>`
+///
+/// # content
+///
+/// markdoll
+pub mod sup {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"sup",
+			Some(|doll, _, text, _| Some(Box::new(parse_ast(doll, text)))),
+		)
+		.with_children(
+			|content| vec![content.downcast_ref::<AST>().unwrap()],
+			|content| vec![content.downcast_mut::<AST>().unwrap()],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(doll, content.downcast_ref::<AST>().unwrap(), options)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "sup", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `citetitle` tag
+///
+/// insert the title of a work via `<cite>`
+///
+/// this isn't named `cite` as that is the [`cite`](crate::ext::citation::cite) tag, which cites a work from a bibliography
+///
+/// # content
+///
+/// markdoll
+pub mod citetitle {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		wrapped_tag("citetitle", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "cite", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `dfn` tag
+///
+/// insert the defining instance of a term via `<dfn>`
+///
+/// # content
+///
+/// markdoll
+pub mod dfn {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		wrapped_tag("dfn", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "dfn", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `varname` tag
+///
+/// insert the name of a variable, such as in math or code, via `<var>`
+///
+/// this isn't named `var` as that is the [`var`](crate::ext::var::var) tag, which substitutes variables
+///
+/// # content
+///
+/// markdoll
+pub mod varname {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		wrapped_tag("varname", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "var", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `samp` tag
+///
+/// insert sample output from a program via `<samp>`
+///
+/// # content
+///
+/// markdoll
+pub mod samp {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		wrapped_tag("samp", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_wrapped(doll, to, "samp", content.downcast_mut::<AST>().unwrap());
+	}
+}
+
+/// `ins` tag
+///
+/// mark content as inserted via `<ins>`
+///
+/// # props
+///
+/// - `datetime`\
+///   when it was inserted, such as `2024-05-01`
+/// - `cite`\
+///   a URL explaining why it was inserted
+///
+/// # content
+///
+/// markdoll
+pub mod ins {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		edit_tag("ins", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_edit(doll, to, "ins", content.downcast_mut::<Edit>().unwrap());
+	}
+}
+
+/// `del` tag
+///
+/// mark content as deleted via `<del>`
+///
+/// # props
+///
+/// - `datetime`\
+///   when it was deleted, such as `2024-05-01`
+/// - `cite`\
+///   a URL explaining why it was deleted
+///
+/// # content
+///
+/// markdoll
+pub mod del {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		edit_tag("del", html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		html_edit(doll, to, "del", content.downcast_mut::<Edit>().unwrap());
+	}
+}
+
+/// `kbd` tag
+///
+/// insert keyboard input via `<kbd>`
+///
+/// # flags
+///
+/// - raw\
+///   don't split the content into keys
+///
+/// # content
+///
+/// markdoll, which is split into a `<kbd>` for each key on `+` when it is only text, such as `Ctrl+Shift+P`
+pub mod kbd {
+	use super::*;
+
+	/// the keys of a shortcut, or markdoll content
+	#[derive(Debug)]
+	enum Kbd {
+		Keys(Vec<String>),
+		Content(AST),
+	}
+
+	/// split a shortcut into its keys, where an empty key followed by another is the `+` key itself
+	fn split_keys(text: &str) -> Vec<String> {
+		let parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+		let mut keys = Vec::new();
+
+		let mut i = 0;
+		while i < parts.len() {
+			if parts[i].is_empty() && parts.get(i + 1).is_some_and(|next| next.is_empty()) {
+				keys.push("+".to_string());
+				i += 2;
+			} else {
+				keys.push(parts[i].to_string());
+				i += 1;
+			}
+		}
+
+		keys
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"kbd",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args();
+					flags(raw);
+					props();
+				}
+
+				let ast = parse_ast(doll, text);
+
+				if !raw {
+					if let [BlockItem::Inline(items)] = &ast[..] {
+						if let [(_, InlineItem::Text(text))] = &items[..] {
+							return Some(Box::new(Kbd::Keys(split_keys(text))));
+						}
+					}
+				}
+
+				Some(Box::new(Kbd::Content(ast)))
+			}),
+		)
+		.with_children(
+			|content| match content.downcast_ref::<Kbd>().unwrap() {
+				Kbd::Keys(_) => vec![],
+				Kbd::Content(ast) => vec![ast],
+			},
+			|content| match content.downcast_mut::<Kbd>().unwrap() {
+				Kbd::Keys(_) => vec![],
+				Kbd::Content(ast) => vec![ast],
+			},
+		)
+		.with_printer(
+			|doll, content, options| match content.downcast_ref::<Kbd>().unwrap() {
+				Kbd::Keys(keys) => keys.join("+"),
+				Kbd::Content(ast) => print::to_doll(doll, ast, options),
+			},
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		match content.downcast_mut::<Kbd>().unwrap() {
+			Kbd::Keys(keys) => {
+				to.write.push_str("<kbd class='doll-kbd'>");
+
+				for (i, key) in keys.iter().enumerate() {
+					if i > 0 {
+						to.write.push('+');
+					}
+
+					write!(to.write, "<kbd>{}</kbd>", &html_escape::encode_text(key)).unwrap();
+				}
+
+				to.write.push_str("</kbd>");
+			}
+			Kbd::Content(ast) => html_wrapped(doll, to, "kbd", ast),
+		}
+	}
+}
+
+/// `spoiler` tag
+///
+/// hide content until it is revealed, with the reveal left to CSS/JS
+///
+/// # arguments
+///
+/// - `label` (optional)\
+///   shown in place of the content until it is revealed
+///
+/// # content
+///
+/// markdoll
+pub mod spoiler {
+	use super::*;
+
+	/// the label and hidden content
+	#[derive(Debug)]
+	pub struct Spoiler {
+		/// shown in place of the content until it is revealed
+		pub label: Option<String>,
+		/// the content
+		pub ast: AST,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"spoiler",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args(label);
+					flags();
+					props();
+				}
+
+				Some(Box::new(Spoiler {
+					label: label.map(ToString::to_string),
+					ast: parse_ast(doll, text),
+				}))
+			}),
+		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Spoiler>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Spoiler>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Spoiler>().unwrap().ast,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let spoiler = content.downcast_mut::<Spoiler>().unwrap();
+
+		let inline_block = spoiler.ast.len() > 1;
+		let element = if inline_block { "div" } else { "span" };
+
+		write!(to.write, "<{element} class='doll-spoiler' tabindex='0'>").unwrap();
+
+		if let Some(label) = &spoiler.label {
+			write!(
+				to.write,
+				"<span class='doll-spoiler-label'>{}</span>",
+				&html_escape::encode_text(label)
+			)
+			.unwrap();
+		}
+
+		write!(to.write, "<{element} class='doll-spoiler-content'>").unwrap();
+		for item in &mut spoiler.ast {
+			item.emit(doll, to, inline_block);
+		}
+		write!(to.write, "</{element}></{element}>").unwrap();
+	}
+}
+
+/// `lang` tag
+///
+/// mark the language of content that differs from the document's, for hyphenation and screen reader pronunciation
+///
+/// # arguments
+///
+/// - `code`\
+///   the BCP 47 language tag, such as `de` or `pt-BR`, which should only contain letters, digits, and hyphens
+///
+/// # content
+///
+/// markdoll
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes a `<span lang='<code>'>`, or a `<div>` for content of multiple blocks
+pub mod lang {
+	use super::*;
+
+	/// the language and its content
+	#[derive(Debug)]
+	pub struct Lang {
+		/// the BCP 47 language tag
+		pub code: String,
+		/// the content
+		pub ast: AST,
+	}
+
+	/// whether a language tag looks like BCP 47, non-empty parts of letters and digits separated by hyphens, without checking that it names a language
+	#[must_use]
+	pub fn is_valid_code(code: &str) -> bool {
+		code.split('-')
+			.all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_alphanumeric()))
+	}
+
+	/// the language of a document from a `lang: <code>` line in its frontmatter, such as to set [`Strings::locale`](crate::emit::Strings::locale)
+	#[must_use]
+	pub fn frontmatter_lang(frontmatter: &str) -> Option<&str> {
+		frontmatter.lines().find_map(|line| {
+			line.split_once(':')
+				.filter(|(key, _)| key.trim() == "lang")
+				.map(|(_, value)| value.trim())
+				.filter(|value| !value.is_empty())
+		})
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"lang",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(code: String);
+					opt_args();
+					flags();
+					props();
+				}
+
+				if !is_valid_code(&code) {
+					doll.diag_arg(false, 0, "lang code invalid");
+				}
+
+				Some(Box::new(Lang {
+					code,
+					ast: parse_ast(doll, text),
+				}))
+			}),
+		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Lang>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Lang>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(doll, &content.downcast_ref::<Lang>().unwrap().ast, options)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let lang = content.downcast_mut::<Lang>().unwrap();
+
+		let inline_block = lang.ast.len() > 1;
+		let element = if inline_block { "div" } else { "span" };
+
+		write!(
+			to.write,
+			"<{element} lang='{}'>",
+			&html_escape::encode_single_quoted_attribute(&lang.code)
+		)
+		.unwrap();
+		for item in &mut lang.ast {
+			item.emit(doll, to, inline_block);
+		}
+		write!(to.write, "</{element}>").unwrap();
+	}
+}
+
 /// all of this module's tags
 #[must_use]
-pub fn tags() -> [TagDefinition; 2] {
-	[emphasis::tag(), quote::tag()]
+pub fn tags() -> [TagDefinition; 17] {
+	[
+		emphasis::tag(),
+		quote::tag(),
+		verse::tag(),
+		details::tag(),
+		abbr::tag(),
+		abbrdef::tag(),
+		sub::tag(),
+		sup::tag(),
+		citetitle::tag(),
+		dfn::tag(),
+		varname::tag(),
+		samp::tag(),
+		ins::tag(),
+		del::tag(),
+		kbd::tag(),
+		spoiler::tag(),
+		lang::tag(),
+	]
 }
@@ -0,0 +1,389 @@
+use {
+	crate::{
+		args,
+		diagnostics::TagDiagnosticTranslation,
+		emit::{html_id, HtmlEmit},
+		ext::{TagDefinition, TagForm},
+		tree::{
+			print::{self, PrintOptions},
+			visit::{self, Visitor, VisitorMut},
+			BlockItem, InlineItem, TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
+	},
+	core::fmt::Write,
+	hashbrown::HashSet,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// a term and the content describing or linking it
+#[derive(Debug)]
+pub struct Term {
+	/// the term
+	pub term: String,
+	/// the content
+	pub ast: AST,
+}
+
+impl Term {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		vec![&content.downcast_ref::<Self>().unwrap().ast]
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		vec![&mut content.downcast_mut::<Self>().unwrap().ast]
+	}
+
+	fn print(doll: &MarkDoll, content: &dyn TagContent, options: PrintOptions) -> String {
+		print::to_doll(doll, &content.downcast_ref::<Self>().unwrap().ast, options)
+	}
+
+	/// the HTML id of the term's definition, `term-<term>` made valid with [`html_id`], which replaces whitespace and punctuation by `-`
+	#[must_use]
+	pub fn id(&self) -> String {
+		html_id(&format!("term-{}", self.term))
+	}
+}
+
+/// parse a `term` or `termref` tag
+fn parse(
+	doll: &mut MarkDoll,
+	mut args: Vec<&str>,
+	text: &str,
+	_: TagForm,
+) -> Option<Box<dyn TagContent>> {
+	args! {
+		doll, args;
+
+		args(term: String);
+		opt_args();
+		flags();
+		props();
+	};
+
+	Some(Box::new(Term {
+		term,
+		ast: match doll.parse(text) {
+			Ok(ast) | Err(ast) => ast,
+		},
+	}))
+}
+
+/// `term` tag
+///
+/// define a glossary term, which [`link_terms`] links occurrences of
+///
+/// # arguments
+///
+/// - `term`\
+///   the term being defined
+///
+/// # content
+///
+/// markdoll, the definition
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], defines the HTML id from [`Term::id`], or warns and adds a suffix if it was already used, see [`HtmlEmit::unique_id`]
+pub mod term {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new("term", Some(parse))
+			.with_children(Term::children, Term::children_mut)
+			.with_printer(Term::print)
+			.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let term = content.downcast_mut::<Term>().unwrap();
+
+		let id = to.unique_id(doll, &term.id());
+		to.write.push_str("<div class='doll-term'");
+		to.write_id_attr(&id);
+		write!(
+			to.write,
+			"><dfn>{}</dfn>: ",
+			html_escape::encode_text(&term.term)
+		)
+		.unwrap();
+
+		let inline_block = term.ast.len() > 1;
+		for item in &mut term.ast {
+			item.emit(doll, to, inline_block);
+		}
+
+		to.write.push_str("</div>");
+	}
+}
+
+/// `termref` tag
+///
+/// link to the definition of a glossary term from a [`term`](mod@term) tag, usually inserted by [`link_terms`]
+///
+/// # arguments
+///
+/// - `term`\
+///   the term being linked
+///
+/// # content
+///
+/// markdoll, used as the content of the link
+pub mod term_ref {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new("termref", Some(parse))
+			.with_children(Term::children, Term::children_mut)
+			.with_printer(Term::print)
+			.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let term = content.downcast_mut::<Term>().unwrap();
+
+		write!(to.write, "<a class='doll-term-ref' href='#{}'>", term.id()).unwrap();
+
+		let inline_block = term.ast.len() > 1;
+		for item in &mut term.ast {
+			item.emit(doll, to, inline_block);
+		}
+
+		to.write.push_str("</a>");
+	}
+}
+
+/// options for [`link_terms`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LinkTermsOptions {
+	/// whether terms only match text with the same case
+	pub case_sensitive: bool,
+	/// whether to link every occurrence of a term, rather than the first in each section
+	pub every_occurrence: bool,
+}
+
+/// tags whose content is never linked, since it is code or already links somewhere
+const SKIPPED_TAGS: [&str; 7] = ["code", "codeblock", "link", "def", "ref", "term", "termref"];
+
+/// collects the terms defined in an [`AST`]
+struct Collect(Vec<String>);
+
+impl Visitor for Collect {
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+		if &*tag.tag == "term" {
+			if let Some(term) = tag.content.downcast_ref::<Term>() {
+				self.0.push(term.term.clone());
+			}
+		}
+
+		visit::walk_tag(self, doll, pos, tag);
+	}
+}
+
+/// links terms within an [`AST`]
+struct Link {
+	/// the terms, longest first, and their characters to match against
+	terms: Vec<(String, Vec<char>)>,
+	options: LinkTermsOptions,
+	/// the terms already linked in each enclosing section
+	linked: Vec<HashSet<String>>,
+	def: Arc<TagDefinition>,
+}
+
+impl Link {
+	fn normalize(&self, ch: char) -> char {
+		if self.options.case_sensitive {
+			ch
+		} else {
+			ch.to_lowercase().next().unwrap_or(ch)
+		}
+	}
+
+	/// find the longest term starting at a word boundary at `start`
+	fn find(&self, chars: &[char], start: usize) -> Option<usize> {
+		let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+
+		if start > 0 && is_word(chars[start - 1]) {
+			return None;
+		}
+
+		self.terms.iter().position(|(_, term)| {
+			let end = start + term.len();
+
+			end <= chars.len()
+				&& chars[start..end]
+					.iter()
+					.zip(term)
+					.all(|(&ch, &term)| self.normalize(ch) == term)
+				&& !chars.get(end).is_some_and(|&ch| is_word(ch))
+		})
+	}
+
+	/// split text into text and `termref` tags
+	fn link(
+		&mut self,
+		doll: &MarkDoll,
+		pos: usize,
+		text: &str,
+		out: &mut Vec<(usize, InlineItem)>,
+	) {
+		let chars = text.chars().collect::<Vec<_>>();
+
+		let mut plain_start = 0;
+		let mut i = 0;
+		while i < chars.len() {
+			let Some(index) = self.find(&chars, i) else {
+				i += 1;
+				continue;
+			};
+
+			let (term, term_chars) = &self.terms[index];
+			let end = i + term_chars.len();
+
+			let linked = self.linked.last_mut().unwrap();
+			if !self.options.every_occurrence && linked.contains(term) {
+				i = end;
+				continue;
+			}
+			linked.insert(term.clone());
+
+			if plain_start < i {
+				out.push((
+					pos + plain_start,
+					InlineItem::Text(chars[plain_start..i].iter().collect()),
+				));
+			}
+
+			let matched = chars[i..end].iter().collect::<String>();
+			out.push((
+				pos + i,
+				InlineItem::Tag(TagInvocation {
+					tag: "termref".to_string(),
+					def: Arc::clone(&self.def),
+					args: vec![term.clone()],
+					content: Box::new(Term {
+						term: term.clone(),
+						ast: vec![BlockItem::Inline(vec![(
+							0,
+							InlineItem::Text(matched.clone()),
+						)])],
+					}),
+					// synthesized tags have no source, so their diagnostics are reported where they were inserted
+					diagnostic_translation: Some(TagDiagnosticTranslation::new(
+						Arc::from(matched),
+						usize::MAX,
+						pos + i,
+						0,
+					)),
+					generation: doll.generation,
+				}),
+			));
+
+			plain_start = end;
+			i = end;
+		}
+
+		if plain_start == 0 {
+			out.push((pos, InlineItem::Text(text.to_string())));
+		} else if plain_start < chars.len() {
+			out.push((
+				pos + plain_start,
+				InlineItem::Text(chars[plain_start..].iter().collect()),
+			));
+		}
+	}
+}
+
+impl VisitorMut for Link {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &mut BlockItem) {
+		match block {
+			BlockItem::Inline(items) => {
+				let mut out = Vec::with_capacity(items.len());
+
+				for (pos, item) in core::mem::take(items) {
+					match item {
+						InlineItem::Text(text) => self.link(doll, pos, &text, &mut out),
+						mut item => {
+							visit::walk_inline_mut(self, doll, pos, &mut item);
+							out.push((pos, item));
+						}
+					}
+				}
+
+				*items = out;
+			}
+			BlockItem::Section { .. } => {
+				self.linked.push(HashSet::new());
+				visit::walk_block_mut(self, doll, block);
+				self.linked.pop();
+			}
+			BlockItem::List { .. } => visit::walk_block_mut(self, doll, block),
+		}
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+		if !SKIPPED_TAGS.contains(&&*tag.tag) {
+			visit::walk_tag_mut(self, doll, pos, tag);
+		}
+	}
+}
+
+/// link occurrences of the terms defined by `term` tags in an [`AST`] to their definitions, by wrapping them in `termref` tags
+///
+/// terms match whole words, preferring the longest term where they overlap, and the content of `code`/`codeblock` tags and of links is left alone
+///
+/// the `termref` definition registered on `doll` is used if there is one, so register the glossary tags before parsing
+pub fn link_terms(doll: &MarkDoll, ast: &mut AST, options: LinkTermsOptions) {
+	let mut collect = Collect(Vec::new());
+	visit::walk(ast, doll, &mut collect);
+
+	let mut link = Link {
+		terms: Vec::new(),
+		options,
+		linked: vec![HashSet::new()],
+		def: doll
+			.ext_system
+			.get_tag("termref")
+			.cloned()
+			.unwrap_or_else(|| Arc::new(term_ref::tag())),
+	};
+
+	link.terms = collect
+		.0
+		.into_iter()
+		.filter(|term| !term.is_empty())
+		.map(|term| {
+			let chars = term.chars().map(|ch| link.normalize(ch)).collect();
+			(term, chars)
+		})
+		.collect();
+	// longest first, then in a fixed order so the output doesn't depend on the order terms are defined in
+	link.terms
+		.sort_by(|(a, a_chars), (b, b_chars)| b_chars.len().cmp(&a_chars.len()).then(a.cmp(b)));
+	link.terms.dedup_by(|(a, _), (b, _)| a == b);
+
+	if !link.terms.is_empty() {
+		visit::walk_mut(ast, doll, &mut link);
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 2] {
+	[term::tag(), term_ref::tag()]
+}
@@ -0,0 +1,245 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		tree::{
+			visit::{self, VisitorMut},
+			TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec},
+	core::fmt::Write,
+};
+
+/// `ix` tag
+///
+/// mark an entry for the [`index`](mod@index) tag
+///
+/// # arguments
+///
+/// - `term`\
+///   the term the entry is listed under
+/// - `subterm` (optional)\
+///   the subterm the entry is listed under, within the term
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], inserts an invisible anchor with the `ix-<n>` HTML id, where `<n>` is assigned by [`collect`]
+pub mod entry {
+	use super::*;
+
+	/// an index entry and its anchor
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Entry {
+		/// the term
+		pub term: String,
+		/// the subterm within the term
+		pub subterm: Option<String>,
+		/// the anchor, assigned by [`collect`]
+		pub anchor: Option<usize>,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"ix",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(term: String);
+					opt_args(subterm: String);
+					flags();
+					props();
+				};
+
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				Some(Box::new(Entry {
+					term,
+					subterm,
+					anchor: None,
+				}))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		if let Some(anchor) = content.downcast_ref::<Entry>().unwrap().anchor {
+			to.write.push_str("<span class='doll-ix'");
+			to.write_id_attr(&format!("ix-{anchor}"));
+			to.write.push_str("></span>");
+		}
+	}
+}
+
+/// `index` tag
+///
+/// list the entries marked by [`ix`](entry) tags, alphabetically and grouped by their first letter, with links back to each of them
+///
+/// entries are gathered by [`collect`], which must be run on the parsed document before emitting
+pub mod index {
+	use super::*;
+
+	/// the anchors of each term, and of each subterm within them
+	#[derive(Debug, Clone, Default, PartialEq, Eq)]
+	pub struct Term {
+		/// the anchors of entries without a subterm
+		pub anchors: Vec<usize>,
+		/// the anchors of entries with each subterm, sorted case insensitively
+		pub subterms: BTreeMap<(String, String), Vec<usize>>,
+	}
+
+	/// the entries to list
+	#[derive(Debug, Clone, Default, PartialEq, Eq)]
+	pub struct Index {
+		/// the terms, sorted case insensitively
+		pub terms: BTreeMap<(String, String), Term>,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"index",
+			Some(|doll, _, text, _| {
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				Some(Box::new(Index::default()))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// write links to anchors
+	fn anchors(to: &mut HtmlEmit, anchors: &[usize]) {
+		for (i, anchor) in anchors.iter().enumerate() {
+			write!(to.write, " <a href='#ix-{anchor}'>{}</a>", i + 1).unwrap();
+		}
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let index = content.downcast_ref::<Index>().unwrap();
+
+		if index.terms.is_empty() {
+			doll.diag(false, usize::MAX, "index has no entries");
+			return;
+		}
+
+		to.write.push_str("<nav class='doll-index'>");
+
+		let mut letter = None;
+		for ((key, term), entries) in &index.terms {
+			let first = key
+				.chars()
+				.next()
+				.map(|ch| ch.to_uppercase().collect::<String>());
+
+			if letter != first {
+				if letter.is_some() {
+					to.write.push_str("</ul></div>");
+				}
+
+				write!(
+					to.write,
+					"<div class='doll-index-group'><div class='doll-index-letter'>{}</div><ul>",
+					html_escape::encode_text(first.as_deref().unwrap_or_default())
+				)
+				.unwrap();
+				letter = first;
+			}
+
+			write!(to.write, "<li>{}", html_escape::encode_text(term)).unwrap();
+			anchors(to, &entries.anchors);
+
+			if !entries.subterms.is_empty() {
+				to.write.push_str("<ul>");
+
+				for ((_, subterm), anchors_of) in &entries.subterms {
+					write!(to.write, "<li>{}", html_escape::encode_text(subterm)).unwrap();
+					anchors(to, anchors_of);
+					to.write.push_str("</li>");
+				}
+
+				to.write.push_str("</ul>");
+			}
+
+			to.write.push_str("</li>");
+		}
+
+		to.write.push_str("</ul></div></nav>");
+	}
+}
+
+/// assign anchors to the [`ix`](entry) tags in an [`AST`], and fill its [`index`](mod@index) tags with them
+///
+/// anchors are numbered in document order, and identical entries are listed once with a link to each of their anchors
+pub fn collect(doll: &MarkDoll, ast: &mut AST) {
+	struct Anchor {
+		index: index::Index,
+		count: usize,
+	}
+
+	impl VisitorMut for Anchor {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+			if let Some(entry) = tag.content.downcast_mut::<entry::Entry>() {
+				self.count += 1;
+				let anchor = self.count;
+				entry.anchor = Some(anchor);
+
+				let term = self
+					.index
+					.terms
+					.entry((entry.term.to_lowercase(), entry.term.clone()))
+					.or_default();
+
+				match &entry.subterm {
+					Some(subterm) => term
+						.subterms
+						.entry((subterm.to_lowercase(), subterm.clone()))
+						.or_default()
+						.push(anchor),
+					None => term.anchors.push(anchor),
+				}
+			}
+
+			visit::walk_tag_mut(self, doll, pos, tag);
+		}
+	}
+
+	struct Fill<'a>(&'a index::Index);
+
+	impl VisitorMut for Fill<'_> {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+			if let Some(index) = tag.content.downcast_mut::<index::Index>() {
+				index.clone_from(self.0);
+			}
+
+			visit::walk_tag_mut(self, doll, pos, tag);
+		}
+	}
+
+	let mut anchor = Anchor {
+		index: index::Index::default(),
+		count: 0,
+	};
+	visit::walk_mut(ast, doll, &mut anchor);
+	visit::walk_mut(ast, doll, &mut Fill(&anchor.index));
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 2] {
+	[entry::tag(), index::tag()]
+}
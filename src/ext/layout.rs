@@ -0,0 +1,171 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::{ArgValue, TagDefinition},
+		tree::{
+			print,
+			visit::{self, Visitor},
+			BlockItem, ListItem, TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	alloc::{boxed::Box, vec::Vec},
+	core::fmt::Write,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// `columns` tag
+///
+/// flow content into columns
+///
+/// # arguments
+///
+/// - `count` (optional)\
+///   the number of columns, at least 2\
+///   **defaults to 2, or the number of explicit columns**
+///
+/// # content
+///
+/// markdoll, where content that is only an unordered list places each item in its own column, rather than flowing it across them
+pub mod columns {
+	use super::*;
+
+	/// the columns and their content
+	#[derive(Debug)]
+	pub struct Columns {
+		/// the number of columns
+		pub count: usize,
+		/// the content, see [`explicit`](Self::explicit)
+		pub ast: AST,
+	}
+
+	impl Columns {
+		/// the explicit columns, as the items of the unordered list that is the only content, if it is
+		#[must_use]
+		pub fn explicit(&self) -> Option<&[ListItem]> {
+			match &self.ast[..] {
+				[BlockItem::List {
+					ordered: false,
+					items,
+					..
+				}] => Some(items),
+				_ => None,
+			}
+		}
+	}
+
+	/// finds `columns` tags within content, without descending into them
+	struct Nested(Vec<usize>);
+
+	impl Visitor for Nested {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+			if tag.content.is::<Columns>() {
+				self.0.push(pos);
+			} else {
+				visit::walk_tag(self, doll, pos, tag);
+			}
+		}
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"columns",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args(count: ArgValue<usize>);
+					flags();
+					props();
+				}
+
+				if let Some(count) = count.filter(|count| **count < 2) {
+					count.diag(doll, true, "columns must be at least 2");
+					return None;
+				}
+
+				let ast = match doll.parse(text) {
+					Ok(ast) | Err(ast) => ast,
+				};
+
+				let mut nested = Nested(Vec::new());
+				visit::walk(&ast, doll, &mut nested);
+				for pos in nested.0 {
+					doll.diag(false, pos, "columns nested within columns");
+				}
+
+				let mut columns = Columns { count: 2, ast };
+				if let Some(count) = count {
+					columns.count = count.value;
+				} else if let Some(explicit) = columns.explicit() {
+					columns.count = explicit.len().max(2);
+				}
+
+				Some(Box::new(columns))
+			}),
+		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Columns>().unwrap().ast],
+			|content| vec![&mut content.downcast_mut::<Columns>().unwrap().ast],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Columns>().unwrap().ast,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let columns = content.downcast_mut::<Columns>().unwrap();
+
+		write!(
+			to.write,
+			"<div class='doll-columns' style='column-count: {}'>",
+			columns.count
+		)
+		.unwrap();
+
+		match &mut columns.ast[..] {
+			[BlockItem::List {
+				ordered: false,
+				items,
+				..
+			}] => {
+				for column in items {
+					to.write.push_str("<div class='doll-column'>");
+
+					let inline_block = column.ast.len() > 1;
+					for item in &mut column.ast {
+						item.emit(doll, to, inline_block);
+					}
+
+					to.write.push_str("</div>");
+				}
+			}
+			ast => {
+				let inline_block = ast.len() > 1;
+				for item in ast {
+					item.emit(doll, to, inline_block);
+				}
+			}
+		}
+
+		to.write.push_str("</div>");
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[columns::tag()]
+}
@@ -1,18 +1,31 @@
 use {
 	crate::{
 		args,
-		emit::HtmlEmit,
+		dependencies::{self, DependencyKind},
+		diagnostics::{CharIndex, IndexedSrc},
+		emit::{html_id, AssetInfo, AssetKind, HtmlEmit},
 		ext::TagDefinition,
-		tree::{TagContent, AST},
+		tree::{
+			self,
+			print::{self, PrintOptions},
+			visit::{self, Visitor},
+			TagContent, TagInvocation, AST,
+		},
 		MarkDoll,
 	},
 	::alloc::format,
 	alloc::{
 		boxed::Box,
 		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
 	},
+	core::{fmt::Write, ops::Range},
 };
 
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
 /// the link destination and visuals
 #[derive(Debug)]
 pub struct Link {
@@ -22,6 +35,29 @@ pub struct Link {
 	pub ast: AST,
 }
 
+impl Link {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		vec![&content.downcast_ref::<Self>().unwrap().ast]
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		vec![&mut content.downcast_mut::<Self>().unwrap().ast]
+	}
+
+	fn print(doll: &MarkDoll, content: &dyn TagContent, options: PrintOptions) -> String {
+		print::to_doll(doll, &content.downcast_ref::<Self>().unwrap().ast, options)
+	}
+
+	fn clone(content: &dyn TagContent) -> Option<Box<dyn TagContent>> {
+		let link = content.downcast_ref::<Self>().unwrap();
+
+		Some(Box::new(Self {
+			href: link.href.clone(),
+			ast: tree::try_clone_ast(&link.ast)?,
+		}))
+	}
+}
+
 /// `link` tag
 ///
 /// link to something
@@ -42,7 +78,7 @@ pub mod link {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"link",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
@@ -55,15 +91,15 @@ pub mod link {
 				Some(Box::new(Link {
 					href,
 					ast: match doll.parse(text) {
-						Ok(ast) => ast,
-						Err(ast) => {
-							doll.ok = false;
-							ast
-						}
+						Ok(ast) | Err(ast) => ast,
 					},
 				}))
 			}),
 		)
+		.with_children(Link::children, Link::children_mut)
+		.with_printer(Link::print)
+		.with_pure(Link::clone)
+		.with_expects_content()
 		.with_emitter::<HtmlEmit>(html)
 	}
 
@@ -71,10 +107,8 @@ pub mod link {
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let link = content.downcast_mut::<Link>().unwrap();
 
-		to.write.push_str(&format!(
-			"<a href='{}'>",
-			&html_escape::encode_safe(&link.href)
-		));
+		let href = to.asset_url(doll, &link.href, AssetKind::Link, 0);
+		write!(to.write, "<a href='{}'>", &html_escape::encode_safe(&href)).unwrap();
 
 		let inline_block = link.ast.len() > 1;
 		for item in &mut link.ast {
@@ -94,17 +128,42 @@ pub mod link {
 /// - `src`\
 ///   the url to source the image from
 ///
+/// # flags
+///
+/// - decorative\
+///   the image only decorates, so assistive technology skips it
+///
+/// # props
+///
+/// - `width`\
+///   the width in pixels
+/// - `height`\
+///   the height in pixels
+///
 /// # content
 ///
-/// text, alt text of the image
+/// text, alt text of the image\
+/// **warns if empty, unless decorative**
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], looks up the source with [`HtmlEmit::asset_resolver`], warning if it doesn't exist and writing its dimensions where the props don't give them. given one, the other is scaled to keep the aspect ratio
 pub mod image {
 	use super::*;
 
 	/// the image source and alt text
-	#[derive(Debug)]
-	struct Image {
+	#[derive(Debug, Clone)]
+	pub struct Image {
+		/// the url to source the image from
 		pub src: String,
+		/// the alt text
 		pub alt: String,
+		/// whether the image only decorates, and has no alt text
+		pub decorative: bool,
+		/// the width in pixels, if given
+		pub width: Option<u32>,
+		/// the height in pixels, if given
+		pub height: Option<u32>,
 	}
 
 	/// the tag
@@ -112,34 +171,102 @@ pub mod image {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"img",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
 					args(src: String);
 					opt_args();
-					flags();
-					props();
+					flags(decorative);
+					props(width: u32, height: u32);
 				};
 
+				if dependencies::is_local(&src) {
+					doll.record_dependency(DependencyKind::Asset, src.clone());
+				}
+
+				if decorative && !text.is_empty() {
+					doll.diag(false, usize::MAX, "decorative image has alt text");
+				} else if !decorative && text.trim().is_empty() {
+					doll.diag(false, usize::MAX, "image has no alt text");
+				}
+
 				Some(Box::new(Image {
 					src,
-					alt: text.to_string(),
+					alt: if decorative {
+						String::new()
+					} else {
+						text.to_string()
+					},
+					decorative,
+					width,
+					height,
 				}))
 			}),
 		)
+		.with_pure(|content| Some(Box::new(content.downcast_ref::<Image>().unwrap().clone())))
 		.with_emitter::<HtmlEmit>(html)
 	}
 
 	/// emit to html
-	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let img = content.downcast_mut::<Image>().unwrap();
 
-		to.write.push_str(&format!(
-			"<img src='{}' alt='{}' />",
-			&html_escape::encode_safe(&img.src),
-			&html_escape::encode_safe(&img.alt)
-		));
+		let asset = to
+			.asset_resolver
+			.as_ref()
+			.and_then(|resolver| resolver(&img.src));
+		if asset.as_ref().is_some_and(|asset| !asset.exists) {
+			doll.diag_arg(false, 0, "image not found");
+		}
+
+		let (width, height) = match (img.width, img.height, asset) {
+			(None, None, Some(asset)) => (asset.width, asset.height),
+			// scale the other to keep the aspect ratio
+			(
+				Some(width),
+				None,
+				Some(AssetInfo {
+					width: Some(w),
+					height: Some(h),
+					..
+				}),
+			) if w > 0 => (Some(width), Some(scale(width, h, w))),
+			(
+				None,
+				Some(height),
+				Some(AssetInfo {
+					width: Some(w),
+					height: Some(h),
+					..
+				}),
+			) if h > 0 => (Some(scale(height, w, h)), Some(height)),
+			_ => (img.width, img.height),
+		};
+
+		let src = to.asset_url(doll, &img.src, AssetKind::Image, 0);
+		let mut attrs = format!(" src='{}'", &html_escape::encode_safe(&src));
+
+		if img.decorative {
+			attrs.push_str(" alt='' role='presentation'");
+		} else {
+			write!(attrs, " alt='{}'", &html_escape::encode_safe(&img.alt)).unwrap();
+		}
+
+		if let Some(width) = width {
+			write!(attrs, " width='{width}'").unwrap();
+		}
+		if let Some(height) = height {
+			write!(attrs, " height='{height}'").unwrap();
+		}
+
+		to.write_void("img", &attrs);
+	}
+
+	/// `value * num / den`, rounded
+	fn scale(value: u32, num: u32, den: u32) -> u32 {
+		let (value, num, den) = (u64::from(value), u64::from(num), u64::from(den));
+		u32::try_from((value * num + den / 2) / den).unwrap_or(u32::MAX)
 	}
 }
 
@@ -158,7 +285,7 @@ pub mod image {
 ///
 /// # implementation
 ///
-/// when emitting to [`HtmlEmit`], defines the `ref-<id>` HTML id, replacing `<id>` with the `id` argument
+/// when emitting to [`HtmlEmit`], defines the `ref-<id>` HTML id, replacing `<id>` with the `id` argument, made valid with [`html_id`], or warns and adds a suffix if it was already used, see [`HtmlEmit::unique_id`]
 pub mod definition {
 	use super::*;
 
@@ -167,7 +294,7 @@ pub mod definition {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"def",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
@@ -180,15 +307,14 @@ pub mod definition {
 				Some(Box::new(Link {
 					href,
 					ast: match doll.parse(text) {
-						Ok(ast) => ast,
-						Err(ast) => {
-							doll.ok = false;
-							ast
-						}
+						Ok(ast) | Err(ast) => ast,
 					},
 				}))
 			}),
 		)
+		.with_children(Link::children, Link::children_mut)
+		.with_printer(Link::print)
+		.with_expects_content()
 		.with_emitter::<HtmlEmit>(html)
 	}
 
@@ -196,9 +322,10 @@ pub mod definition {
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		let link = content.downcast_mut::<Link>().unwrap();
 
-		let href = &html_escape::encode_safe(&link.href);
-		to.write
-			.push_str(&format!("<div class='doll-ref' id='ref-{href}'>[{href}]: "));
+		let id = to.unique_id(doll, &format!("ref-{}", link.href));
+		to.write.push_str("<div class='doll-ref'");
+		to.write_id_attr(&id);
+		write!(to.write, ">[{}]: ", html_escape::encode_text(&link.href)).unwrap();
 
 		let inline_block = link.ast.len() > 1;
 		for item in &mut link.ast {
@@ -220,20 +347,31 @@ pub mod definition {
 ///
 /// # implementation
 ///
-/// when emitting to [`HtmlEmit`], links to the `ref-<id>` HTML id, replacing `<id>` with the `id` argument
+/// when emitting to [`HtmlEmit`], links to the `ref-<id>` HTML id, replacing `<id>` with the `id` argument, made valid with [`html_id`]
+///
+/// when the `def` is in another document of a [`Project`](crate::project::Project), links to the `ref-<id>` HTML id in `<document>.html`, replacing `<document>` with the document name
 pub mod reference {
 	use super::*;
 
+	/// the content of a `ref` tag
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Reference {
+		/// the id of the corresponding `def`
+		pub id: String,
+		/// the name of the document containing the corresponding `def`, if it is another one
+		pub document: Option<String>,
+	}
+
 	/// the tag
 	#[must_use]
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"ref",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
-					args(href: String);
+					args(id: String);
 					opt_args();
 					flags();
 					props();
@@ -243,7 +381,7 @@ pub mod reference {
 					doll.diag(true, usize::MAX, "cannot have content");
 				}
 
-				Some(Box::new(href))
+				Some(Box::new(Reference { id, document: None }))
 			}),
 		)
 		.with_emitter::<HtmlEmit>(html)
@@ -251,11 +389,132 @@ pub mod reference {
 
 	/// emit to html
 	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
-		let href = content.downcast_ref::<String>().unwrap();
+		let reference = content.downcast_ref::<Reference>().unwrap();
+
+		let document = reference
+			.document
+			.as_ref()
+			.map(|document| format!("{}.html", html_escape::encode_safe(document)))
+			.unwrap_or_default();
+
+		write!(
+			to.write,
+			"<sup><a href='{document}#{}'>[{}]</a></sup>",
+			html_id(&format!("ref-{}", reference.id)),
+			html_escape::encode_text(&reference.id)
+		)
+		.unwrap();
+	}
+}
+
+/// what an [`ExtractedLink`] was referenced by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+	/// a [`link`] href
+	Hyperlink,
+	/// an [`img`](image) src
+	Image,
+	/// a [`ref`](reference) id, referencing a [`def`](definition)
+	Reference,
+	/// a [`def`](definition) id, which [`ref`](reference)s reference
+	Definition,
+}
 
-		to.write
-			.push_str(&format!("<sup><a href='#ref-{href}'>[{href}]</a></sup>"));
+/// a url referenced by a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedLink {
+	/// the url, or id for [`LinkKind::Reference`] and [`LinkKind::Definition`]
+	pub url: String,
+	/// what referenced it
+	pub kind: LinkKind,
+	/// the position of the argument it was given in, in the root source
+	pub at: usize,
+}
+
+/// extract every url referenced by `link`, `img`, and `ref` tags, and every id defined by `def` tags in an [`AST`], including within other tags
+///
+/// `src` should be the source the [`AST`] was parsed from, and is used to map positions within tag content back to it
+///
+/// pairing up [`LinkKind::Reference`]s with [`LinkKind::Definition`]s finds references that cross between pieces of a split document, see [`split_sections`](crate::tree::split_sections)
+#[must_use]
+pub fn extract(doll: &MarkDoll, src: &str, ast: &AST) -> Vec<ExtractedLink> {
+	struct Extract {
+		/// the source of each tag currently being visited, starting with the root source, and how positions in it map to its parent, indexed once when the tag is entered
+		translations: Vec<(Arc<str>, Range<usize>, Option<IndexedSrc>)>,
+		links: Vec<ExtractedLink>,
+	}
+
+	impl Extract {
+		/// map a position in the innermost tag's content to the root source
+		fn to_root(&self, at: usize) -> usize {
+			self.translations
+				.iter()
+				.rev()
+				.filter_map(|(.., indexed)| indexed.as_ref())
+				.fold(at, |at, indexed| indexed.parent_offset(at))
+		}
+	}
+
+	impl Visitor for Extract {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+			let url = match &*tag.tag {
+				"link" => tag
+					.content
+					.downcast_ref::<Link>()
+					.map(|link| (link.href.clone(), LinkKind::Hyperlink)),
+				"img" => tag
+					.content
+					.downcast_ref::<image::Image>()
+					.map(|image| (image.src.clone(), LinkKind::Image)),
+				"ref" => tag
+					.content
+					.downcast_ref::<reference::Reference>()
+					.map(|reference| (reference.id.clone(), LinkKind::Reference)),
+				"def" => tag
+					.content
+					.downcast_ref::<Link>()
+					.map(|def| (def.href.clone(), LinkKind::Definition)),
+				_ => None,
+			};
+
+			if let Some((url, kind)) = url {
+				// the first argument directly follows the tag name and its opening parenthesis
+				let at = self.to_root(pos + tag.tag.chars().count() + 1);
+
+				self.links.push(ExtractedLink { url, kind, at });
+			}
+
+			if let Some(translation) = &tag.diagnostic_translation {
+				let (parent, parent_range, _) = self.translations.last().unwrap();
+				let indexed = translation.indexed.clone().unwrap_or_else(|| {
+					let parent = &parent[parent_range.clone()];
+					IndexedSrc::index(
+						translation.text(),
+						CharIndex::new(parent).rest_from(parent, translation.offset_in_parent),
+						translation.offset_in_parent,
+						translation.indent,
+					)
+				});
+
+				self.translations.push((
+					Arc::clone(&translation.src),
+					translation.range.clone(),
+					Some(indexed),
+				));
+				visit::walk_tag(self, doll, pos, tag);
+				self.translations.pop();
+			}
+		}
 	}
+
+	let mut extract = Extract {
+		translations: vec![(Arc::from(src), 0..src.len(), None)],
+		links: Vec::new(),
+	};
+
+	visit::walk(ast, doll, &mut extract);
+
+	extract.links
 }
 
 /// all of this module's tags
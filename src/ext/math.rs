@@ -0,0 +1,79 @@
+use {
+	crate::{args, emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	alloc::{boxed::Box, string::String},
+	core::fmt::Write,
+};
+
+/// `math` tag
+///
+/// insert TeX math, rendered client-side by KaTeX/MathJax unless [`HtmlEmit::math_render`] is set
+///
+/// # flags
+///
+/// - display\
+///   render as a block, via `<div class='doll-math-display'>\[...\]</div>` rather than `<span class='doll-math'>\(...\)</span>`
+///
+/// # content
+///
+/// TeX, kept raw so its backslashes and brackets survive
+pub mod math {
+	use super::*;
+
+	/// math and how to display it
+	#[derive(Debug)]
+	pub struct Math {
+		/// whether the math is in display mode rather than inline
+		pub display: bool,
+		/// the TeX source
+		pub tex: String,
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"math",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args();
+					flags(display);
+					props();
+				}
+
+				Some(Box::new(Math {
+					display,
+					tex: String::from(text),
+				}))
+			}),
+		)
+		.with_raw()
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let math = content.downcast_ref::<Math>().unwrap();
+
+		if let Some(render) = to.math_render.clone() {
+			render(doll, to, &math.tex, math.display);
+			return;
+		}
+
+		let tex = html_escape::encode_text(&math.tex);
+
+		if math.display {
+			write!(to.write, "<div class='doll-math-display'>\\[{tex}\\]</div>").unwrap();
+		} else {
+			write!(to.write, "<span class='doll-math'>\\({tex}\\)</span>").unwrap();
+		}
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[math::tag()]
+}
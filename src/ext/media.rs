@@ -0,0 +1,291 @@
+use {
+	crate::{
+		args,
+		dependencies::{self, DependencyKind},
+		emit::{AssetKind, HtmlEmit},
+		ext::TagDefinition,
+		tree::{print, TagContent, AST},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// an embedded video or audio element, and its sources
+#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools, reason = "required")]
+pub struct Media {
+	/// whether this is a video rather than audio
+	pub video: bool,
+	/// the sources, in order of preference
+	pub sources: Vec<String>,
+	/// whether playback controls are shown
+	pub controls: bool,
+	/// whether playback loops
+	pub looped: bool,
+	/// whether the media starts muted
+	pub muted: bool,
+	/// whether the media starts playing on its own
+	pub autoplay: bool,
+	/// the image shown before a video plays
+	pub poster: Option<String>,
+	/// the content shown where the media isn't supported
+	pub fallback: AST,
+}
+
+impl Media {
+	/// the MIME type of a source, inferred from its extension
+	#[must_use]
+	pub fn source_type(&self, src: &str) -> Option<&'static str> {
+		let path = src.split(['?', '#']).next().unwrap_or_default();
+		let (_, ext) = path.rsplit_once('.')?;
+
+		Some(match (ext.to_ascii_lowercase().as_str(), self.video) {
+			("mp4" | "m4v", true) => "video/mp4",
+			("webm", true) => "video/webm",
+			("ogg" | "ogv", true) => "video/ogg",
+			("mov", true) => "video/quicktime",
+			("mp3", false) => "audio/mpeg",
+			("m4a" | "mp4", false) => "audio/mp4",
+			("webm", false) => "audio/webm",
+			("ogg" | "oga" | "opus", false) => "audio/ogg",
+			("wav", false) => "audio/wav",
+			("flac", false) => "audio/flac",
+			_ => return None,
+		})
+	}
+}
+
+/// remove every occurrence of a flag from the arguments, returning whether there were any
+fn take_flag(args: &mut Vec<&str>, flag: &str) -> bool {
+	let len = args.len();
+	args.retain(|arg| *arg != flag);
+	args.len() != len
+}
+
+/// parse a `video` or `audio` tag
+fn parse(doll: &mut MarkDoll, mut args: Vec<&str>, text: &str, video: bool) -> Option<Media> {
+	// `src` may be repeated, and `loop` can't be named by the macro
+	let mut extra_sources = Vec::new();
+	args.retain(|arg| match arg.strip_prefix("src=") {
+		Some(src) => {
+			extra_sources.push(src.to_string());
+			false
+		}
+		None => true,
+	});
+	let looped = take_flag(&mut args, "loop");
+
+	args! {
+		doll, args;
+
+		args(src);
+		opt_args();
+		flags(nocontrols, muted, autoplay);
+		props(poster);
+	}
+
+	if poster.is_some() && !video {
+		doll.diag(false, usize::MAX, "poster is only used by video");
+	}
+
+	if autoplay && !muted {
+		doll.diag(false, usize::MAX, "autoplay is blocked unless muted");
+	}
+
+	let mut sources = vec![src.to_string()];
+	sources.append(&mut extra_sources);
+
+	let poster = poster.filter(|_| video).map(ToString::to_string);
+
+	for path in sources.iter().chain(&poster) {
+		if dependencies::is_local(path) {
+			doll.record_dependency(DependencyKind::Asset, path.clone());
+		}
+	}
+
+	Some(Media {
+		video,
+		sources,
+		controls: !nocontrols,
+		looped,
+		muted,
+		autoplay,
+		poster,
+		fallback: match doll.parse(text) {
+			Ok(ast) | Err(ast) => ast,
+		},
+	})
+}
+
+/// the tag definition shared by `video` and `audio`
+fn definition(key: &'static str, parse: crate::ext::TagParser) -> TagDefinition {
+	TagDefinition::new(key, Some(parse))
+		.with_children(
+			|content| vec![&content.downcast_ref::<Media>().unwrap().fallback],
+			|content| vec![&mut content.downcast_mut::<Media>().unwrap().fallback],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Media>().unwrap().fallback,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+}
+
+/// `video` tag
+///
+/// embed a video via `<video>`
+///
+/// # arguments
+///
+/// - `src`\
+///   the video to play
+///
+/// # flags
+///
+/// - nocontrols\
+///   hide the playback controls, which are shown by default
+/// - loop\
+///   restart playback when it ends
+/// - muted\
+///   start muted
+/// - autoplay\
+///   start playing on its own, browsers block this unless `muted` is also set
+///
+/// # props
+///
+/// - `src` (repeatable)\
+///   additional sources for browsers that can't play the first, with their type inferred from the extension
+/// - `poster`\
+///   an image to show before the video plays
+///
+/// # content
+///
+/// markdoll, shown where video isn't supported
+pub mod video {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		definition("video", |doll, args, text, _| {
+			parse(doll, args, text, true).map(|media| Box::new(media) as Box<dyn TagContent>)
+		})
+	}
+}
+
+/// `audio` tag
+///
+/// embed audio via `<audio>`
+///
+/// # arguments
+///
+/// - `src`\
+///   the audio to play
+///
+/// # flags
+///
+/// - nocontrols\
+///   hide the playback controls, which are shown by default
+/// - loop\
+///   restart playback when it ends
+/// - muted\
+///   start muted
+/// - autoplay\
+///   start playing on its own, browsers block this unless `muted` is also set
+///
+/// # props
+///
+/// - `src` (repeatable)\
+///   additional sources for browsers that can't play the first, with their type inferred from the extension
+///
+/// # content
+///
+/// markdoll, shown where audio isn't supported
+pub mod audio {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		definition("audio", |doll, args, text, _| {
+			parse(doll, args, text, false).map(|media| Box::new(media) as Box<dyn TagContent>)
+		})
+	}
+}
+
+/// emit to html
+pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+	let media = content.downcast_mut::<Media>().unwrap();
+	let element = if media.video { "video" } else { "audio" };
+
+	write!(to.write, "<{element} class='doll-{element}'").unwrap();
+
+	for (flag, set) in [
+		("controls", media.controls),
+		("loop", media.looped),
+		("muted", media.muted),
+		("autoplay", media.autoplay),
+	] {
+		if set {
+			to.write.push(' ');
+			to.write.push_str(flag);
+		}
+	}
+
+	if let Some(poster) = &media.poster {
+		let poster = to.asset_url(doll, poster, AssetKind::Image, usize::MAX);
+		write!(
+			to.write,
+			" poster='{}'",
+			&html_escape::encode_single_quoted_attribute(&poster)
+		)
+		.unwrap();
+	}
+
+	to.write.push('>');
+
+	let kind = if media.video {
+		AssetKind::Video
+	} else {
+		AssetKind::Audio
+	};
+	for (i, src) in media.sources.iter().enumerate() {
+		// only the first source is positional, and known to be the first argument
+		let arg = if i == 0 { 0 } else { usize::MAX };
+		let mut attrs = format!(
+			" src='{}'",
+			&html_escape::encode_single_quoted_attribute(&to.asset_url(doll, src, kind, arg))
+		);
+
+		if let Some(ty) = media.source_type(src) {
+			write!(attrs, " type='{ty}'").unwrap();
+		}
+
+		to.write_void("source", &attrs);
+	}
+
+	let inline_block = media.fallback.len() > 1;
+	for item in &mut media.fallback {
+		item.emit(doll, to, inline_block);
+	}
+
+	write!(to.write, "</{element}>").unwrap();
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 2] {
+	[video::tag(), audio::tag()]
+}
@@ -1,26 +1,153 @@
+/// `cite`/`references` tags, and numbering citations
+pub mod citation;
 /// `code`/`codeblock` tags
 pub mod code;
 /// `//` tag
 pub mod common;
-/// `em`/`quote` tags
+/// `if` tag
+pub mod conditional;
+/// `html` tag, which does not escape its content
+#[cfg(feature = "danger")]
+pub mod danger;
+/// `time` tag
+pub mod datetime;
+/// `diagram` tag
+pub mod diagram;
+/// `emoji` tag, and replacing shortcodes in text
+pub mod emoji;
+/// `em`/`quote`/`details`/`abbr`/`abbrdef`/`sub`/`sup`/`citetitle`/`dfn`/`varname`/`samp`/`ins`/`del`/`kbd`/`spoiler` tags
 pub mod formatting;
+/// `term`/`termref` tags, and linking terms to their definitions
+pub mod glossary;
+/// `ix`/`index` tags, and collecting index entries
+pub mod index;
+/// `columns` tag
+pub mod layout;
 /// `link`/`def`/`ref` tags
 pub mod links;
+/// `math` tag
+pub mod math;
+/// `video`/`audio` tags
+pub mod media;
+/// `page` tag, linking pages by name
+pub mod page;
+/// `qr` tag
+#[cfg(feature = "qr")]
+pub mod qr;
 /// `table`/`tr`/`tc` tags
 pub mod table;
+/// `tabs`/`tab` tags
+pub mod tabs;
+/// `todo` tag, and listing todos
+pub mod todo;
+/// `var` tag, and defining variables from frontmatter
+pub mod var;
 
 use {
-	crate::{tree::TagContent, typemap::TypeMap, MarkDoll},
-	alloc::{boxed::Box, vec::Vec},
+	crate::{
+		tree::{print::PrintOptions, TagContent, AST},
+		typemap::TypeMap,
+		MarkDoll,
+	},
+	alloc::{boxed::Box, string::String, sync::Arc, vec::Vec},
+	core::{
+		any::{type_name, TypeId},
+		ops::Deref,
+		str::FromStr,
+	},
 	hashbrown::HashMap,
 };
 
 /// the parsing signature tags use
-pub type TagParser =
-	fn(doll: &mut MarkDoll, args: Vec<&str>, text: &str) -> Option<Box<dyn TagContent>>;
+pub type TagParser = fn(
+	doll: &mut MarkDoll,
+	args: Vec<&str>,
+	text: &str,
+	form: TagForm,
+) -> Option<Box<dyn TagContent>>;
+
+/// how a tag was written, given to its [`TagParser`] so it can behave differently inline and as a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagForm {
+	/// `[tag:content]`, also used for shorthands and tags added with [`Paragraph::tag`](crate::tree::build::Paragraph::tag)
+	Inline,
+	/// `[tag::`, with the content indented on the lines after it
+	Block,
+	/// `[tag]`, with no content
+	Empty,
+}
+/// a tag about to be parsed, given to a [`TagFilter`]
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct TagFilterEvent<'a> {
+	/// where the name of the tag starts in the current context, just after its `[`, like the positions passed to [`MarkDoll::diag`]
+	pub at: usize,
+	/// the name of the tag, as written
+	pub tag: &'a str,
+	/// the arguments of the tag
+	pub args: &'a [String],
+	/// how the tag was written
+	pub form: TagForm,
+}
+
+/// what to do with a tag, decided by a [`TagFilter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagFilterAction {
+	/// parse it as it is
+	Allow,
+	/// parse it with the definition of another tag, such as to map a legacy name to the tag replacing it, keeping the name as written
+	Rename(&'static str),
+	/// leave it out with a `tag denied` error at it
+	Deny {
+		/// why, given as the help of the error
+		reason: String,
+	},
+}
+
+/// decides what to do with each tag before its content is parsed, see [`MarkDoll::tag_filter`]
+///
+/// it can be shared between [`MarkDoll`]s, so it is a [`Fn`], keep any state it changes behind a lock
+#[derive(Clone)]
+pub struct TagFilter(Arc<dyn Fn(TagFilterEvent) -> TagFilterAction + Send + Sync>);
+
+impl TagFilter {
+	/// a filter deciding with `filter`
+	pub fn new(filter: impl Fn(TagFilterEvent) -> TagFilterAction + Send + Sync + 'static) -> Self {
+		Self(Arc::new(filter))
+	}
+
+	/// decide what to do with a tag
+	#[must_use]
+	pub fn decide(&self, event: TagFilterEvent) -> TagFilterAction {
+		(self.0)(event)
+	}
+}
+
+impl core::fmt::Debug for TagFilter {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("TagFilter").finish_non_exhaustive()
+	}
+}
+
 /// the emitting signature tags use for a given `To`
+///
+/// emitting must be repeatable, so an [`AST`] can be emitted again, to the same kind of target or another, with the same result. the content is borrowed mutably only so what it contains can be emitted, such as [`AST`]s whose tags keep where they were parsed, and emitters must leave it as they found it. state kept while emitting goes on the target, or in [`MarkDoll::scratch`], which each emit starts empty
 pub type TagEmitter<To> = fn(doll: &mut MarkDoll, to: &mut To, content: &mut Box<dyn TagContent>);
 
+/// the signature tags use to expose the [`AST`]s within their content to [`Visitor`](crate::tree::visit::Visitor)s
+pub type TagChildren = fn(content: &dyn TagContent) -> Vec<&AST>;
+/// the signature tags use to expose the [`AST`]s within their content to [`VisitorMut`](crate::tree::visit::VisitorMut)s
+pub type TagChildrenMut = fn(content: &mut dyn TagContent) -> Vec<&mut AST>;
+
+/// the signature tags use to print their content back to markdoll source, for [`to_doll`](crate::tree::print::to_doll)
+pub type TagPrinter =
+	fn(doll: &MarkDoll, content: &dyn TagContent, options: PrintOptions) -> String;
+
+/// the signature pure tags use to copy their content, for [`MarkDoll::cache_pure_tags`]
+///
+/// returns `None` if the content can't be copied, such as when it contains tags that aren't pure, see [`try_clone_ast`](crate::tree::try_clone_ast)
+pub type TagCloner = fn(content: &dyn TagContent) -> Option<Box<dyn TagContent>>;
+
 /// defines a tag name, how to parse its contents, and how to emit it
 #[derive(Debug, Clone)]
 #[allow(
@@ -36,8 +163,36 @@ pub struct TagDefinition {
 	/// return None to avoid being placed into the AST and emitting
 	pub parse: Option<TagParser>,
 
+	/// expose the [`AST`]s within the tag content, so visitors can descend into them
+	///
+	/// tags without this are treated as having no children
+	pub children: Option<(TagChildren, TagChildrenMut)>,
+
+	/// print the tag content back to markdoll source, its arguments are kept from the invocation
+	///
+	/// tags without this are printed with the content they were parsed from
+	pub print: Option<TagPrinter>,
+
+	/// whether inline content keeps its escapes, so backslashes reach the parser as written
+	///
+	/// escaped brackets are still not counted when finding the end of the content
+	pub raw: bool,
+
+	/// copy the tag content, for tags that are pure, parsing only from their arguments and content and not from the document around them
+	///
+	/// with [`MarkDoll::cache_pure_tags`], identical invocations of pure tags are parsed once and copied from then on. tags without this are parsed every time
+	pub pure: Option<TagCloner>,
+
+	/// whether the tag writes content from the document into the output as is, such as raw html, which [untrusted](MarkDoll::untrusted) documents can't use
+	pub passthrough: bool,
+
+	/// whether the tag shows its content, so leaving it empty is likely a mistake, reported as `tag has no content` when that is given a severity, as by the strict profile
+	pub expects_content: bool,
+
 	/// emit the tag content
 	emitters: TypeMap,
+	/// the type and name of each emit target there is an emitter for, see [`emit_targets`](Self::emit_targets)
+	emit_targets: Vec<(TypeId, &'static str)>,
 }
 
 impl TagDefinition {
@@ -47,13 +202,67 @@ impl TagDefinition {
 		Self {
 			key,
 			parse,
+			children: None,
+			print: None,
+			raw: false,
+			pure: None,
+			passthrough: false,
+			expects_content: false,
 			emitters: TypeMap::default(),
+			emit_targets: Vec::new(),
 		}
 	}
 
+	/// set how to expose the [`AST`]s within the tag content, and return self for chaining
+	#[must_use]
+	pub fn with_children(mut self, children: TagChildren, children_mut: TagChildrenMut) -> Self {
+		self.children = Some((children, children_mut));
+		self
+	}
+
+	/// set how to print the tag content back to markdoll source, and return self for chaining
+	#[must_use]
+	pub fn with_printer(mut self, print: TagPrinter) -> Self {
+		self.print = Some(print);
+		self
+	}
+
+	/// keep escapes in inline content, and return self for chaining
+	#[must_use]
+	pub fn with_raw(mut self) -> Self {
+		self.raw = true;
+		self
+	}
+
+	/// mark the tag as pure, with how to copy its content, and return self for chaining
+	#[must_use]
+	pub fn with_pure(mut self, clone: TagCloner) -> Self {
+		self.pure = Some(clone);
+		self
+	}
+
+	/// mark the tag as writing content from the document into the output as is, and return self for chaining
+	#[must_use]
+	pub fn with_passthrough(mut self) -> Self {
+		self.passthrough = true;
+		self
+	}
+
+	/// mark the tag as showing its content, so leaving it empty is likely a mistake, and return self for chaining
+	#[must_use]
+	pub fn with_expects_content(mut self) -> Self {
+		self.expects_content = true;
+		self
+	}
+
 	/// set the emitter on this tag for an emit target
 	pub fn set_emitter<To: 'static>(&mut self, emitter: TagEmitter<To>) {
 		self.emitters.put(emitter);
+
+		let target = (TypeId::of::<To>(), type_name::<To>());
+		if !self.emit_targets.contains(&target) {
+			self.emit_targets.push(target);
+		}
 	}
 
 	/// set the emitter on this tag for an emit target, and return self for chaining
@@ -74,6 +283,73 @@ impl TagDefinition {
 	pub fn has_any_emitters(&self) -> bool {
 		!self.emitters.is_empty()
 	}
+
+	/// the type and name of each emit target this tag has an emitter for, in the order they were set
+	#[must_use]
+	pub fn emit_targets(&self) -> impl ExactSizeIterator<Item = (TypeId, &'static str)> + '_ {
+		self.emit_targets.iter().copied()
+	}
+}
+
+/// a parsed argument along with the index of the argument as written, for reporting diagnostics about the value at it, such as a number that parsed but is out of range
+///
+/// [`args!`] binds one when it is given as the type of an argument or prop, as in `props(rows: ArgValue<usize>)`. it derefs to the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArgValue<T> {
+	/// the parsed value
+	pub value: T,
+	/// the index of the argument as written, counted as [`MarkDoll::diag_arg`] counts them
+	pub index: usize,
+}
+
+impl<T> ArgValue<T> {
+	/// emit a diagnostic at the argument, like [`MarkDoll::diag_arg`]
+	#[track_caller]
+	pub fn diag(&self, doll: &mut MarkDoll, err: bool, code: &'static str) {
+		doll.diag_arg(err, self.index, code);
+	}
+}
+
+impl<T> Deref for ArgValue<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.value
+	}
+}
+
+/// parse an argument given to [`args!`] with a type, knowing its index as written
+///
+/// implemented for every [`FromStr`] type, and for [`ArgValue`]s of them
+pub trait FromArg: Sized {
+	/// the error when the argument can't be parsed
+	type Err;
+
+	/// parse the argument `arg`, the `index`th as written
+	///
+	/// # Errors
+	///
+	/// if the argument can't be parsed
+	fn from_arg(arg: &str, index: usize) -> Result<Self, Self::Err>;
+}
+
+impl<T: FromStr> FromArg for T {
+	type Err = T::Err;
+
+	fn from_arg(arg: &str, _index: usize) -> Result<Self, Self::Err> {
+		arg.parse()
+	}
+}
+
+impl<T: FromStr> FromArg for ArgValue<T> {
+	type Err = T::Err;
+
+	fn from_arg(arg: &str, index: usize) -> Result<Self, Self::Err> {
+		Ok(Self {
+			value: arg.parse()?,
+			index,
+		})
+	}
 }
 
 /// helper macro to parse arguments into variables
@@ -83,11 +359,17 @@ impl TagDefinition {
 ///     doll, args; // pass in the markdoll and args
 ///
 ///     args(arg1, arg2: usize); // parse required arguments, which may be parsed into another type, if applicable. ex: `(2)`
-///     opt_args(oarg1, oarg2: usize); // parse optional arguments, which will be `Some` when present (and parsed into another type, if applicable), or `None` if not. ex: `(2)`
+///     opt_args(oarg1, oarg2: usize); // parse optional arguments, which will be `Some` when present (and parsed into another type, if applicable), or `None` if not or if the next argument is one of the flags/props below. ex: `(2)`
 ///     flags(flag1, flag2); // parse flags, which will be `true` when present and `false` when not. ex: `(flag2)`
 ///     props(oarg1, oarg2: usize); // parse named props, which will be `Some` when present (and parsed into another type, if applicable), or `None` if not. ex: `(oarg2=2)`
 /// }
 /// ```
+///
+/// an argument or prop given the type [`ArgValue<T>`](ArgValue) is parsed as `T` and keeps its index, so a value that parsed but isn't usable can be reported at it
+///
+/// positional arguments come first, followed by flags and props in any order. an argument that isn't one of the flags or props after one that is emits a `positional argument after named` error, as it would otherwise be taken as the flag or prop or ignored, and a required argument left out before them emits `argument <name> required` at the tag
+///
+/// flags and props are matched by name wherever they are written, so an argument that is meant as data but looks like one, such as `head` for a tag with a `head` flag, is written with a leading `=`, as in `(=head)`, which is never a flag or prop and is removed from the argument. an argument that starts with `=` as data is written with another, as in `(==x)`
 #[macro_export]
 macro_rules! args {
 	{
@@ -100,25 +382,49 @@ macro_rules! args {
 	} => {
 		let _ = (&$doll, &$args);
 
+		// the index of the next argument as written, for reporting at it with `MarkDoll::diag_arg`
+		#[allow(unused, reason = "macro")]
+		let mut position: usize = 0;
+
+		// whether an argument is one of the declared flags or props, which positional arguments should not consume
+		#[allow(unused, reason = "macro")]
+		let is_named = |arg: &str| {
+			$(arg == stringify!($flag) ||)*
+			$(arg.split_once('=').is_some_and(|(name, _)| name == stringify!($prop)) ||)*
+			false
+		};
+
 		$(
 			#[allow(unused, reason = "macro")]
-			let mut $arg = if !$args.is_empty() {
+			let mut $arg = if $args.first().is_some_and(|arg| arg.is_empty()) {
+				// an empty argument, `()`, is as good as none
+				$doll.diag_arg(true, position, concat!("argument ", stringify!($arg), " required"));
+
+				return None;
+			} else if !$args.is_empty() && !is_named($args[0]) {
+				position += 1;
+
 				args! {
 					if [$($arg_ty)?] {
 						#[allow(irrefutable_let_patterns, reason = "macro")]
-						if let Ok(value) = $args.remove(0).parse::<$($arg_ty)?>() {
+						if let Ok(value) = <$($arg_ty)? as $crate::ext::FromArg>::from_arg(args!(positional $args), position - 1) {
 							value
 						} else {
-							$doll.diag(true, usize::MAX, concat!("arg ", stringify!($arg), " invalid"));
+							$doll.diag_arg(true, position - 1, concat!("arg ", stringify!($arg), " invalid"));
 
 							return None;
 						}
 					} else {
-						$args.remove(0)
+						args!(positional $args)
 					}
 				}
+			} else if let Some(misplaced) = $args.iter().position(|arg| !is_named(arg)) {
+				$doll.diag_arg(true, position + misplaced, "positional argument after named");
+
+				return None;
 			} else {
-				$doll.diag(true, usize::MAX, concat!("argument ", stringify!(person), " required"));
+				// none left, or only flags and props
+				$doll.diag(true, usize::MAX, concat!("argument ", stringify!($arg), " required"));
 
 				return None;
 			};
@@ -126,19 +432,21 @@ macro_rules! args {
 
 		$(
 			#[allow(unused, reason = "macro")]
-			let mut $opt_arg = if !$args.is_empty() {
+			let mut $opt_arg = if !$args.is_empty() && !is_named($args[0]) {
+				position += 1;
+
 				Some(args! {
 					if [$($opt_arg_ty)?] {
 						#[allow(irrefutable_let_patterns, reason = "macro")]
-						if let Ok(value) = $args.remove(0).parse::<$($opt_arg_ty)?>() {
+						if let Ok(value) = <$($opt_arg_ty)? as $crate::ext::FromArg>::from_arg(args!(positional $args), position - 1) {
 							value
 						} else {
-							$doll.diag(true, usize::MAX, concat!("arg ", stringify!($opt_arg), " invalid"));
+							$doll.diag_arg(true, position - 1, concat!("arg ", stringify!($opt_arg), " invalid"));
 
 							return None;
 						}
 					} else {
-						$args.remove(0)
+						args!(positional $args)
 					}
 				})
 			} else {
@@ -164,50 +472,82 @@ macro_rules! args {
 			if [$($flag)* $($prop)*] {
 				#[allow(unused, reason = "macro")]
 				let mut retain_ok = true;
+				// whether a flag or prop has been seen, after which every argument must be one
+				#[allow(unused, reason = "macro")]
+				let mut named = false;
+				// the first positional argument following a flag or prop
+				#[allow(unused, reason = "macro")]
+				let mut misplaced = None;
 
-				$args.retain(|arg| match *arg {
-					$(
-						stringify!($flag) => {
-							$flag = true;
-							false
-						}
-					)*
-					#[allow(unused, reason = "macro")]
-					input => args! {
-						if [$($prop)*] {
-							// parse properties
-							if let Some(index) = input.find("=") {
-								match &input[..index] {
-									$(
-										stringify!($prop) => {
-											args! {
-												if [$($prop_ty)?] {
-													if let Ok(value) = input[(index + 1)..].parse::<$($prop_ty)?>() {
-														$prop = Some(value);
-													} else {
-														$doll.diag(true, usize::MAX, concat!("prop ", stringify!(person), " invalid"));
+				$args.retain(|arg| {
+					position += 1;
+
+					match *arg {
+						$(
+							stringify!($flag) => {
+								$flag = true;
+								named = true;
+								false
+							}
+						)*
+						#[allow(unused, reason = "macro")]
+						input => args! {
+							if [$($prop)*] {
+								// parse properties
+								if let Some(index) = input.find("=") {
+									match &input[..index] {
+										$(
+											stringify!($prop) => {
+												named = true;
 
-														retain_ok = false;
+												args! {
+													if [$($prop_ty)?] {
+														#[allow(irrefutable_let_patterns, reason = "macro")]
+														if let Ok(value) = <$($prop_ty)? as $crate::ext::FromArg>::from_arg(&input[(index + 1)..], position - 1) {
+															$prop = Some(value);
+														} else {
+															$doll.diag_arg(true, position - 1, concat!("prop ", stringify!($prop), " invalid"));
+
+															retain_ok = false;
+														}
+													} else {
+														$prop = Some(&input[(index + 1)..]);
 													}
-												} else {
-													$prop = Some(&input[(index + 1)..]);
-												}
-											};
-											false
+												};
+												false
+											}
+										)*
+										_ => {
+											// `=value` is positional
+											if named && index == 0 {
+												misplaced = misplaced.or(Some(position - 1));
+											}
+											true
 										}
-									)*
-									_ => true,
+									}
+								} else {
+									if named {
+										misplaced = misplaced.or(Some(position - 1));
+									}
+									true
 								}
 							} else {
+								// no properties
+								if named {
+									misplaced = misplaced.or(Some(position - 1));
+								}
 								true
 							}
-						} else {
-							// no properties
-							true
-						}
-					},
+						},
+					}
 				});
 
+				if let Some(misplaced) = misplaced {
+					$doll.diag_arg(true, misplaced, "positional argument after named");
+
+					return None;
+				}
+
 				if !retain_ok {
 					return None;
 				}
@@ -215,22 +555,29 @@ macro_rules! args {
 		};
 	};
 
+	// take the next positional argument, without the `=` that keeps it from being taken as a flag or prop
+	(positional $args:ident) => {{
+		let arg = $args.remove(0);
+		arg.strip_prefix('=').unwrap_or(arg)
+	}};
+
 	{ if [] $true:tt else $false:tt } => { $false };
 	{ if [$($tok:ident)+] $true:tt else $false:tt } => { $true };
 	{ if [$($tok:ty)+] $true:tt else $false:tt } => { $true };
 }
 
 /// handles tag definitions
-#[derive(Debug)]
+///
+/// definitions are shared with the [`TagInvocation`](crate::tree::TagInvocation)s parsed using them, so changes to a definition only apply to tags parsed afterwards
+#[derive(Debug, Clone, Default)]
 pub struct ExtensionSystem {
-	/// the tags registered
-	pub tags: HashMap<&'static str, TagDefinition>,
+	tags: HashMap<&'static str, Arc<TagDefinition>>,
 }
 
 impl ExtensionSystem {
-	/// add a tag
+	/// add a tag, replacing any with the same key
 	pub fn add_tag(&mut self, tag: TagDefinition) {
-		self.tags.insert(tag.key, tag);
+		self.tags.insert(tag.key, Arc::new(tag));
 	}
 
 	/// add multiple tags
@@ -239,4 +586,48 @@ impl ExtensionSystem {
 			self.add_tag(tag);
 		}
 	}
+
+	/// add a tag, returning the one it replaced with the same key, if any
+	pub fn replace_tag(&mut self, tag: TagDefinition) -> Option<Arc<TagDefinition>> {
+		self.tags.insert(tag.key, Arc::new(tag))
+	}
+
+	/// remove the tag with a key, returning it if there was one
+	pub fn remove_tag(&mut self, key: &str) -> Option<Arc<TagDefinition>> {
+		self.tags.remove(key)
+	}
+
+	/// the tag with a key
+	#[must_use]
+	pub fn get_tag(&self, key: &str) -> Option<&Arc<TagDefinition>> {
+		self.tags.get(key)
+	}
+
+	/// the registered tags, sorted by key so listings of them are the same every run
+	#[must_use]
+	pub fn tags(&self) -> impl ExactSizeIterator<Item = &Arc<TagDefinition>> {
+		let mut tags = self.tags.values().collect::<Vec<_>>();
+		tags.sort_unstable_by_key(|tag| tag.key);
+
+		tags.into_iter()
+	}
+}
+
+/// the number of single character insertions, deletions, and substitutions between two strings
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+	let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+	for (i, a) in a.chars().enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+
+		for (j, &b) in b.iter().enumerate() {
+			let substituted = diagonal + usize::from(a != b);
+			diagonal = row[j + 1];
+			row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+		}
+	}
+
+	row[b.len()]
 }
@@ -0,0 +1,177 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		tree::{
+			print::{self, PrintOptions},
+			TagContent, AST,
+		},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{boxed::Box, string::String, vec::Vec},
+	core::fmt::Write,
+};
+
+/// where a page linked by a [`page`](mod@page) tag is, returned by a [`PageResolver`](crate::emit::PageResolver)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPage {
+	/// the URL to link to
+	pub url: String,
+	/// whether the page exists, pages that don't are linked as missing, such as to create them
+	pub exists: bool,
+}
+
+/// the name of a page as a URL path segment, by [`slugify`](crate::emit::slugify) as ids are
+///
+/// `Getting Started!` becomes `getting-started`
+#[must_use]
+pub fn slug(name: &str) -> String {
+	crate::emit::slugify(name)
+}
+
+/// the resolver used when [`HtmlEmit::page_resolver`] is `None`, linking to `./<slug>.html` by [`slug`], assuming every page exists
+#[must_use]
+pub fn default_resolver(name: &str) -> Option<ResolvedPage> {
+	Some(ResolvedPage {
+		url: format!("./{}.html", slug(name)),
+		exists: true,
+	})
+}
+
+/// a link to a page and its visuals
+#[derive(Debug)]
+pub struct Page {
+	/// the name of the page
+	pub name: String,
+	/// the visuals, or `None` to show the name
+	pub ast: Option<AST>,
+}
+
+impl Page {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		content.downcast_ref::<Self>().unwrap().ast.iter().collect()
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		content
+			.downcast_mut::<Self>()
+			.unwrap()
+			.ast
+			.iter_mut()
+			.collect()
+	}
+
+	fn print(doll: &MarkDoll, content: &dyn TagContent, options: PrintOptions) -> String {
+		content
+			.downcast_ref::<Self>()
+			.unwrap()
+			.ast
+			.as_ref()
+			.map_or_else(String::new, |ast| print::to_doll(doll, ast, options))
+	}
+}
+
+/// `page` tag
+///
+/// link to another page of a wiki or site by its name, leaving where it is to the embedder
+///
+/// # arguments
+///
+/// - `name`\
+///   the name of the page
+///
+/// # content
+///
+/// markdoll, used as the content of the link\
+/// **defaults to the name**
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], resolves the name with [`HtmlEmit::page_resolver`], writing a link to the page. a page that doesn't exist is linked with the `doll-page-missing` class, and one that can't be resolved at all is written as a `<span class='doll-page-missing'>`, both with a warning
+pub mod page {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"page",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(name: String);
+					opt_args();
+					flags();
+					props();
+				};
+
+				Some(Box::new(Page {
+					name,
+					ast: (!text.is_empty()).then(|| match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					}),
+				}))
+			}),
+		)
+		.with_children(Page::children, Page::children_mut)
+		.with_printer(Page::print)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let page = content.downcast_mut::<Page>().unwrap();
+
+		let resolved = match &to.page_resolver {
+			Some(resolver) => resolver(&page.name),
+			None => default_resolver(&page.name),
+		};
+
+		let element = if let Some(ResolvedPage { url, exists }) = resolved {
+			if exists {
+				write!(
+					to.write,
+					"<a href='{}'>",
+					html_escape::encode_single_quoted_attribute(&url)
+				)
+				.unwrap();
+			} else {
+				doll.diag_arg(false, 0, "page not found");
+				write!(
+					to.write,
+					"<a class='doll-page-missing' href='{}'>",
+					html_escape::encode_single_quoted_attribute(&url)
+				)
+				.unwrap();
+			}
+
+			"a"
+		} else {
+			doll.diag_arg(false, 0, "page not found");
+			to.write.push_str("<span class='doll-page-missing'>");
+
+			"span"
+		};
+
+		match &mut page.ast {
+			Some(ast) => {
+				let inline_block = ast.len() > 1;
+				for item in ast {
+					item.emit(doll, to, inline_block);
+				}
+			}
+			None => to.write.push_str(&html_escape::encode_text(&page.name)),
+		}
+
+		write!(to.write, "</{element}>").unwrap();
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[page::tag()]
+}
@@ -0,0 +1,205 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::{ArgValue, TagDefinition},
+		tree::TagContent,
+		MarkDoll,
+	},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+	qrcodegen::{QrCode, QrCodeEcc},
+};
+
+/// how much of a QR code can be damaged while remaining readable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecc {
+	/// about 7%
+	Low,
+	/// about 15%
+	Medium,
+	/// about 25%
+	Quartile,
+	/// about 30%
+	High,
+}
+
+impl Ecc {
+	/// the most bytes a QR code can hold at this level
+	#[must_use]
+	pub fn max_bytes(self) -> usize {
+		match self {
+			Self::Low => 2953,
+			Self::Medium => 2331,
+			Self::Quartile => 1663,
+			Self::High => 1273,
+		}
+	}
+}
+
+/// an encoded QR code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Qr {
+	/// the encoded data
+	pub data: String,
+	/// the number of modules along each side
+	pub width: usize,
+	/// whether each module is dark, row by row
+	pub modules: Vec<bool>,
+	/// the rendered width and height, in pixels
+	pub size: Option<usize>,
+}
+
+impl Qr {
+	/// encode data, or `None` if it is longer than [`Ecc::max_bytes`]
+	#[must_use]
+	pub fn encode(data: &str, ecc: Ecc, size: Option<usize>) -> Option<Self> {
+		let code = QrCode::encode_binary(
+			data.as_bytes(),
+			match ecc {
+				Ecc::Low => QrCodeEcc::Low,
+				Ecc::Medium => QrCodeEcc::Medium,
+				Ecc::Quartile => QrCodeEcc::Quartile,
+				Ecc::High => QrCodeEcc::High,
+			},
+		)
+		.ok()?;
+
+		let width = code.size();
+		Some(Self {
+			data: data.to_string(),
+			width: width.unsigned_abs() as usize,
+			modules: (0..width)
+				.flat_map(|y| (0..width).map(move |x| (x, y)))
+				.map(|(x, y)| code.get_module(x, y))
+				.collect(),
+			size,
+		})
+	}
+
+	/// SVG path data drawing the dark modules, offset by the quiet zone
+	#[must_use]
+	pub fn path(&self) -> String {
+		let mut path = String::new();
+
+		for (i, _) in self.modules.iter().enumerate().filter(|(_, dark)| **dark) {
+			write!(
+				path,
+				"M{},{}h1v1h-1z",
+				i % self.width + QUIET_ZONE,
+				i / self.width + QUIET_ZONE
+			)
+			.unwrap();
+		}
+
+		path
+	}
+}
+
+/// the empty modules required around a QR code for it to be read
+const QUIET_ZONE: usize = 4;
+
+/// `qr` tag
+///
+/// render data, such as a URL, as a QR code
+///
+/// # arguments
+///
+/// - `data`\
+///   the data to encode
+///
+/// # props
+///
+/// - `size`\
+///   the width and height in pixels\
+///   **defaults to scaling with its container**
+/// - `ecc`\
+///   the error correction level, higher levels survive more damage but hold less data
+///   - l\
+///     7%, up to 2953 bytes
+///   - m\
+///     15%, up to 2331 bytes\
+///     **default**
+///   - q\
+///     25%, up to 1663 bytes
+///   - h\
+///     30%, up to 1273 bytes
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes an inline `<svg class='doll-qr'>`, so no requests are made to display it
+pub mod qr {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"qr",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(data);
+					opt_args();
+					flags();
+					props(size: usize, ecc: ArgValue<String>);
+				};
+
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				let ecc = match ecc.as_ref().map_or("m", |ecc| ecc.as_str()) {
+					"l" => Ecc::Low,
+					"m" => Ecc::Medium,
+					"q" => Ecc::Quartile,
+					"h" => Ecc::High,
+					_ => {
+						ecc.unwrap().diag(doll, true, "qr ecc invalid");
+						return None;
+					}
+				};
+
+				if let Some(qr) = Qr::encode(data, ecc, size) {
+					Some(Box::new(qr))
+				} else {
+					doll.diag(true, usize::MAX, "qr data too long for ecc");
+					None
+				}
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(_: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let qr = content.downcast_ref::<Qr>().unwrap();
+		let extent = qr.width + QUIET_ZONE * 2;
+
+		write!(to.write,
+			"<svg class='doll-qr' xmlns='http://www.w3.org/2000/svg' viewBox='0 0 {extent} {extent}'"
+		)
+		.unwrap();
+
+		if let Some(size) = qr.size {
+			write!(to.write, " width='{size}' height='{size}'").unwrap();
+		}
+
+		write!(to.write,
+			" shape-rendering='crispEdges' role='img' aria-label='{}'><rect width='{extent}' height='{extent}' fill='#fff'/><path d='{}'/></svg>",
+			html_escape::encode_single_quoted_attribute(&qr.data),
+			qr.path()
+		).unwrap();
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[qr::tag()]
+}
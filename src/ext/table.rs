@@ -1,15 +1,77 @@
 use {
 	crate::{
 		args,
+		diagnostics::TagDiagnosticTranslation,
 		emit::HtmlEmit,
-		ext::TagDefinition,
+		ext::{ArgValue, TagDefinition},
 		tree::{BlockItem, InlineItem, TagContent, TagInvocation, AST},
 		MarkDoll,
 	},
 	::alloc::format,
-	alloc::{boxed::Box, vec::Vec},
+	alloc::{
+		boxed::Box,
+		collections::{BTreeMap, BinaryHeap},
+		string::String,
+		vec::Vec,
+	},
+	core::{cmp::Reverse, fmt::Write, mem},
 };
 
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// the most rows a cell of an [untrusted](MarkDoll::untrusted) document can span, as many as browsers lay out
+pub const MAX_ROWSPAN: usize = 65534;
+
+/// the most columns a cell of an [untrusted](MarkDoll::untrusted) document can span, as many as browsers lay out
+pub const MAX_COLSPAN: usize = 1000;
+
+/// how the content of a [`Cell`] is aligned horizontally
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+	/// against the start of the line
+	Left,
+	/// centered
+	Center,
+	/// against the end of the line
+	Right,
+}
+
+impl Align {
+	/// the class the cell is given when emitting to html
+	#[must_use]
+	pub fn class(self) -> &'static str {
+		match self {
+			Self::Left => "doll-align-left",
+			Self::Center => "doll-align-center",
+			Self::Right => "doll-align-right",
+		}
+	}
+}
+
+/// how the content of a [`Cell`] is aligned vertically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VAlign {
+	/// against the top of the cell
+	Top,
+	/// centered
+	Middle,
+	/// against the bottom of the cell
+	Bottom,
+}
+
+impl VAlign {
+	/// the class the cell is given when emitting to html
+	#[must_use]
+	pub fn class(self) -> &'static str {
+		match self {
+			Self::Top => "doll-valign-top",
+			Self::Middle => "doll-valign-middle",
+			Self::Bottom => "doll-valign-bottom",
+		}
+	}
+}
+
 /// a table cell
 #[derive(Debug)]
 pub struct Cell {
@@ -19,8 +81,14 @@ pub struct Cell {
 	pub rows: usize,
 	/// how many columns to span
 	pub cols: usize,
+	/// how the content is aligned horizontally, or `None` to leave it to the stylesheet
+	pub align: Option<Align>,
+	/// how the content is aligned vertically, or `None` to leave it to the stylesheet
+	pub valign: Option<VAlign>,
 	/// content
 	pub content: AST,
+	/// position of this cell within its row
+	pub pos: usize,
 }
 
 /// a table row
@@ -28,6 +96,8 @@ pub struct Cell {
 pub struct Row {
 	/// whether this row is a head row, which is placed in the `head` section of the table
 	pub is_head: bool,
+	/// whether this row is a foot row, which is placed in the `foot` section of the table
+	pub is_foot: bool,
 	/// the cells on this row
 	pub cells: Vec<Cell>,
 }
@@ -39,6 +109,81 @@ pub struct Table {
 	pub head: Vec<Row>,
 	/// `<tbody>` section
 	pub body: Vec<Row>,
+	/// `<tfoot>` section
+	pub foot: Vec<Row>,
+	/// the name screen readers give the table, as `aria-label`
+	pub label: Option<String>,
+	/// the id of the element describing the table, as `aria-describedby`
+	pub describedby: Option<String>,
+	/// whether the table is wrapped in a region that keyboard users can focus to scroll it when it is wider than the page
+	pub scrollable: bool,
+}
+
+impl Cell {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		vec![&content.downcast_ref::<Self>().unwrap().content]
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		vec![&mut content.downcast_mut::<Self>().unwrap().content]
+	}
+}
+
+impl Row {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		content
+			.downcast_ref::<Self>()
+			.unwrap()
+			.cells
+			.iter()
+			.map(|cell| &cell.content)
+			.collect()
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		content
+			.downcast_mut::<Self>()
+			.unwrap()
+			.cells
+			.iter_mut()
+			.map(|cell| &mut cell.content)
+			.collect()
+	}
+}
+
+impl Table {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		let table = content.downcast_ref::<Self>().unwrap();
+
+		[&table.head, &table.body, &table.foot]
+			.into_iter()
+			.flatten()
+			.flat_map(|row| &row.cells)
+			.map(|cell| &cell.content)
+			.collect()
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		let table = content.downcast_mut::<Self>().unwrap();
+
+		[&mut table.head, &mut table.body, &mut table.foot]
+			.into_iter()
+			.flatten()
+			.flat_map(|row| &mut row.cells)
+			.map(|cell| &mut cell.content)
+			.collect()
+	}
+
+	/// push a row into the section it belongs to
+	pub fn push(&mut self, row: Row) {
+		if row.is_head {
+			self.head.push(row);
+		} else if row.is_foot {
+			self.foot.push(row);
+		} else {
+			self.body.push(row);
+		}
+	}
 }
 
 fn parse_row(doll: &mut MarkDoll, ast: AST) -> Vec<Cell> {
@@ -49,13 +194,14 @@ fn parse_row(doll: &mut MarkDoll, ast: AST) -> Vec<Cell> {
 
 	let mut cells = Vec::new();
 
-	for child in ast {
-		match child {
+	for mut child in ast {
+		match &mut child {
 			BlockItem::Inline(items) => {
-				for (pos, item) in items {
+				for (pos, item) in mem::take(items) {
 					match item {
 						InlineItem::Tag(TagInvocation { content, .. }) => {
-							if let Ok(cell) = content.downcast::<Cell>() {
+							if let Ok(mut cell) = content.downcast::<Cell>() {
+								cell.pos = pos;
 								cells.push(*cell);
 							} else {
 								fail(doll, pos);
@@ -66,26 +212,235 @@ fn parse_row(doll: &mut MarkDoll, ast: AST) -> Vec<Cell> {
 				}
 			}
 			BlockItem::List { ordered, items, .. } => {
-				for item in items {
+				for item in mem::take(items) {
 					cells.push(Cell {
-						is_head: ordered,
+						is_head: *ordered,
 						rows: 1,
 						cols: 1,
-						content: item,
+						align: None,
+						valign: None,
+						pos: item.pos,
+						content: item.ast,
 					});
 				}
 			}
-			BlockItem::Section { pos, .. } => fail(doll, pos),
+			BlockItem::Section { pos, .. } => fail(doll, *pos),
 		}
 	}
 
 	cells
 }
 
+/// the columns of a section cells span, for [`validate_spans`]
+///
+/// they are kept as ranges rather than columns, so a huge span is as cheap as any, and the runs of columns they cover together, so placing a cell skips past any amount of them at once
+#[derive(Default)]
+struct Occupied {
+	/// disjoint ranges of columns by their first column, as (column after the last, row they are free from, row, cell)
+	ranges: BTreeMap<usize, (usize, usize, usize, usize)>,
+	/// the union of the ranges, as the first column of each run to the column after its last, with no two runs touching
+	runs: BTreeMap<usize, usize>,
+	/// the rows ranges are free from, with their first column, including those of ranges since cut or removed
+	expiry: BinaryHeap<Reverse<(usize, usize)>>,
+}
+
+impl Occupied {
+	/// the first column from `col` not occupied
+	fn free_from(&self, col: usize) -> usize {
+		match self.runs.range(..=col).next_back() {
+			Some((_, &end)) if end > col => end,
+			_ => col,
+		}
+	}
+
+	/// the column after the last occupied one
+	fn width(&self) -> usize {
+		self.runs.last_key_value().map_or(0, |(_, &end)| end)
+	}
+
+	/// occupy the columns `start..end` until the row `until`, by the cell `j` of the row `i`, taking them from any cells occupying them before
+	fn insert(&mut self, start: usize, end: usize, until: usize, (i, j): (usize, usize)) {
+		// past the last column there can be
+		if start == end {
+			return;
+		}
+
+		// a range this cuts the end off of, or the middle out of
+		if let Some((&first, &range)) = self.ranges.range(..start).next_back() {
+			if range.0 > start {
+				self.ranges
+					.insert(first, (start, range.1, range.2, range.3));
+				if range.0 > end {
+					self.ranges.insert(end, range);
+					self.expiry.push(Reverse((range.1, end)));
+				}
+			}
+		}
+
+		// ranges this covers, or cuts the start off of
+		let within = self
+			.ranges
+			.range(start..end)
+			.map(|(&first, _)| first)
+			.collect::<Vec<_>>();
+		for first in within {
+			let range = self.ranges.remove(&first).unwrap();
+			if range.0 > end {
+				self.ranges.insert(end, range);
+				self.expiry.push(Reverse((range.1, end)));
+			}
+		}
+
+		self.ranges.insert(start, (end, until, i, j));
+		self.expiry.push(Reverse((until, start)));
+
+		// join the runs it touches
+		let mut run = (start, end);
+		if let Some((&first, &last)) = self.runs.range(..start).next_back() {
+			if last >= start {
+				run = (first, run.1.max(last));
+			}
+		}
+		let touching = self
+			.runs
+			.range(start..=end)
+			.map(|(&first, _)| first)
+			.collect::<Vec<_>>();
+		for first in touching {
+			run.1 = run.1.max(self.runs.remove(&first).unwrap());
+		}
+		self.runs.insert(run.0, run.1);
+	}
+
+	/// free the ranges occupied until `row`
+	fn expire(&mut self, row: usize) {
+		while let Some(&Reverse((until, start))) = self.expiry.peek() {
+			if until > row {
+				break;
+			}
+			self.expiry.pop();
+
+			// unless it was cut or removed since
+			let Some(&(end, ..)) = self.ranges.get(&start).filter(|range| range.1 == until) else {
+				continue;
+			};
+			self.ranges.remove(&start);
+
+			let (&first, &last) = self.runs.range(..=start).next_back().unwrap();
+			self.runs.remove(&first);
+			if first < start {
+				self.runs.insert(first, start);
+			}
+			if end < last {
+				self.runs.insert(end, last);
+			}
+		}
+	}
+
+	/// the (row, cell) of each cell still occupying columns after `rows` rows
+	fn overflowing(self, rows: usize) -> Vec<(usize, usize)> {
+		let mut cells = self
+			.ranges
+			.into_values()
+			.filter(|range| range.1 > rows)
+			.map(|(.., i, j)| (i, j))
+			.collect::<Vec<_>>();
+		cells.sort_unstable();
+		cells.dedup();
+		cells
+	}
+}
+
+/// simulate the placement of cells across their spans, warning about rows with inconsistent column counts and rowspans extending past the last row
+///
+/// each row may carry the diagnostic translation of the `tr` tag it originated from, as its cells' positions are relative to that tag
+fn validate_spans(doll: &mut MarkDoll, rows: &mut [(Row, Option<TagDiagnosticTranslation>)]) {
+	#[track_caller]
+	fn warn(
+		doll: &mut MarkDoll,
+		translation: &mut Option<TagDiagnosticTranslation>,
+		pos: usize,
+		code: &'static str,
+	) {
+		if let Some(taken) = translation.take() {
+			doll.diagnostic_translations.push(taken);
+			doll.diag(false, pos, code);
+			*translation = doll.diagnostic_translations.pop();
+		} else {
+			doll.diag(false, pos, code);
+		}
+	}
+
+	for section in [
+		|row: &Row| row.is_head,
+		|row: &Row| !row.is_head && !row.is_foot,
+		|row: &Row| !row.is_head && row.is_foot,
+	] {
+		let mut occupied = Occupied::default();
+		let mut width = None;
+		// rows of this section so far
+		let mut count = 0;
+
+		for (i, (row, translation)) in rows.iter_mut().enumerate() {
+			if !section(row) {
+				continue;
+			}
+
+			occupied.expire(count);
+
+			let mut col = 0;
+			let mut row_width = occupied.width();
+
+			for (j, cell) in row.cells.iter().enumerate() {
+				col = occupied.free_from(col);
+
+				// a span too wide to count reaches as far as any could
+				let end = col.saturating_add(cell.cols);
+				occupied.insert(col, end, count.saturating_add(cell.rows), (i, j));
+
+				col = end;
+				row_width = row_width.max(col);
+			}
+
+			count += 1;
+
+			if *width.get_or_insert(row_width) != row_width {
+				let pos = row.cells.last().map_or(usize::MAX, |cell| cell.pos);
+				warn(
+					doll,
+					translation,
+					pos,
+					"row has an inconsistent amount of columns",
+				);
+			}
+		}
+
+		for (i, j) in occupied.overflowing(count) {
+			let (row, translation) = &mut rows[i];
+			let pos = row.cells[j].pos;
+			warn(doll, translation, pos, "rowspan extends past the last row");
+		}
+	}
+}
+
 /// `table` tag
 ///
 /// make tables
 ///
+/// # flags
+///
+/// - `foot`\
+///   makes the rows of the last unordered list foot rows
+/// - `scrollable`\
+///   wraps the table in a `<div class='doll-table'>` region that keyboard users can focus to scroll a wide table
+///
+/// # props
+///
+/// - `label`\
+///   the name screen readers give the table, as `aria-label`
+/// - `describedby`\
+///   the id of the element describing the table, as `aria-describedby`
+///
 /// # content
 ///
 /// multiple of the following
@@ -100,7 +455,7 @@ pub mod table {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"table",
-			Some(|doll, _, text| {
+			Some(|doll, mut args, text, _| {
 				#[track_caller]
 				fn fail(doll: &mut MarkDoll, pos: usize) {
 					doll.diag(
@@ -110,31 +465,36 @@ pub mod table {
 					);
 				}
 
+				args! {
+					doll, args;
+
+					args();
+					opt_args();
+					flags(foot, scrollable);
+					props(label: String, describedby: String);
+				}
+
 				let ast = match doll.parse(text) {
-					Ok(ast) => ast,
-					Err(ast) => {
-						doll.ok = false;
-						ast
-					}
+					Ok(ast) | Err(ast) => ast,
 				};
 
-				let mut table = Table {
-					head: Vec::new(),
-					body: Vec::new(),
-				};
+				let mut rows = Vec::new();
 
-				for child in ast {
-					match child {
+				// the rows of the last unordered list, in case they should be foot rows
+				let mut last_body_list = None;
+
+				for mut child in ast {
+					match &mut child {
 						BlockItem::Inline(items) => {
-							for (pos, item) in items {
+							for (pos, item) in mem::take(items) {
 								match item {
-									InlineItem::Tag(TagInvocation { content, .. }) => {
+									InlineItem::Tag(TagInvocation {
+										content,
+										diagnostic_translation,
+										..
+									}) => {
 										if let Ok(row) = content.downcast::<Row>() {
-											if row.is_head {
-												table.head.push(*row);
-											} else {
-												table.body.push(*row);
-											}
+											rows.push((*row, diagnostic_translation));
 										} else {
 											fail(doll, pos);
 										}
@@ -144,26 +504,54 @@ pub mod table {
 							}
 						}
 						BlockItem::List { ordered, items, .. } => {
-							for item in items {
-								let row = Row {
-									is_head: ordered,
-									cells: parse_row(doll, item),
-								};
-
-								if row.is_head {
-									table.head.push(row);
-								} else {
-									table.body.push(row);
-								}
+							if !*ordered {
+								last_body_list = Some(rows.len()..rows.len() + items.len());
+							}
+
+							for item in mem::take(items) {
+								rows.push((
+									Row {
+										is_head: *ordered,
+										is_foot: false,
+										cells: parse_row(doll, item.ast),
+									},
+									None,
+								));
 							}
 						}
-						BlockItem::Section { pos, .. } => fail(doll, pos),
+						BlockItem::Section { pos, .. } => fail(doll, *pos),
 					}
 				}
 
+				if foot {
+					if let Some(last_body_list) = last_body_list {
+						for (row, _) in &mut rows[last_body_list] {
+							row.is_foot = true;
+						}
+					} else {
+						doll.diag(false, usize::MAX, "no unordered list to use as foot rows");
+					}
+				}
+
+				validate_spans(doll, &mut rows);
+
+				let mut table = Table {
+					head: Vec::new(),
+					body: Vec::new(),
+					foot: Vec::new(),
+					label,
+					describedby,
+					scrollable,
+				};
+
+				for (row, _) in rows {
+					table.push(row);
+				}
+
 				Some(Box::new(table))
 			}),
 		)
+		.with_children(Table::children, Table::children_mut)
 		.with_emitter::<HtmlEmit>(html)
 	}
 
@@ -171,13 +559,27 @@ pub mod table {
 	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
 		fn write_cell(doll: &mut MarkDoll, to: &mut HtmlEmit, cell: &mut Cell) {
 			let kind = if cell.is_head { "th" } else { "td" };
-			to.write.push_str(&format!("<{kind}"));
+			write!(to.write, "<{kind}").unwrap();
 
 			if cell.rows != 1 {
-				to.write.push_str(&format!(" rowspan='{}'", cell.rows));
+				write!(to.write, " rowspan='{}'", cell.rows).unwrap();
 			}
 			if cell.cols != 1 {
-				to.write.push_str(&format!(" colspan='{}'", cell.cols));
+				write!(to.write, " colspan='{}'", cell.cols).unwrap();
+			}
+
+			// classes rather than styles, so a stylesheet can override them
+			match (cell.align, cell.valign) {
+				(None, None) => {}
+				(Some(align), None) => {
+					write!(to.write, " class='{}'", align.class()).unwrap();
+				}
+				(None, Some(valign)) => {
+					write!(to.write, " class='{}'", valign.class()).unwrap();
+				}
+				(Some(align), Some(valign)) => {
+					write!(to.write, " class='{} {}'", align.class(), valign.class()).unwrap();
+				}
 			}
 
 			to.write.push('>');
@@ -187,17 +589,17 @@ pub mod table {
 				content.emit(doll, to, inline_block);
 			}
 
-			to.write.push_str(&format!("</{kind}>"));
+			write!(to.write, "</{kind}>").unwrap();
 		}
 
-		let table = content.downcast_mut::<Table>().unwrap();
-
-		to.write.push_str("<table>");
+		fn write_section(doll: &mut MarkDoll, to: &mut HtmlEmit, kind: &str, rows: &mut [Row]) {
+			if rows.is_empty() {
+				return;
+			}
 
-		if !table.head.is_empty() {
-			to.write.push_str("<thead>");
+			write!(to.write, "<{kind}>").unwrap();
 
-			for row in &mut table.head {
+			for row in rows {
 				to.write.push_str("<tr>");
 
 				for cell in &mut row.cells {
@@ -207,26 +609,51 @@ pub mod table {
 				to.write.push_str("</tr>");
 			}
 
-			to.write.push_str("</thead>");
+			write!(to.write, "</{kind}>").unwrap();
 		}
 
-		if !table.body.is_empty() {
-			to.write.push_str("<tbody>");
-
-			for row in &mut table.body {
-				to.write.push_str("<tr>");
-
-				for cell in &mut row.cells {
-					write_cell(doll, to, cell);
-				}
+		let table = content.downcast_mut::<Table>().unwrap();
 
-				to.write.push_str("</tr>");
-			}
+		let label = table.label.as_deref().map(|label| {
+			format!(
+				" aria-label='{}'",
+				html_escape::encode_single_quoted_attribute(label)
+			)
+		});
+
+		if table.scrollable {
+			// the region needs a name too, so it is announced as more than "region"
+			write!(
+				to.write,
+				"<div class='doll-table' role='region' tabindex='0'{}>",
+				label.as_deref().unwrap_or_default()
+			)
+			.unwrap();
+		}
 
-			to.write.push_str("</tbody>");
+		to.write.push_str("<table");
+		if let Some(label) = &label {
+			to.write.push_str(label);
+		}
+		if let Some(describedby) = &table.describedby {
+			write!(
+				to.write,
+				" aria-describedby='{}'",
+				html_escape::encode_single_quoted_attribute(describedby)
+			)
+			.unwrap();
 		}
+		to.write.push('>');
+
+		write_section(doll, to, "thead", &mut table.head);
+		write_section(doll, to, "tbody", &mut table.body);
+		write_section(doll, to, "tfoot", &mut table.foot);
 
 		to.write.push_str("</table>");
+
+		if table.scrollable {
+			to.write.push_str("</div>");
+		}
 	}
 }
 
@@ -238,6 +665,8 @@ pub mod table {
 ///
 /// - `head`\
 ///   makes this row a header row
+/// - `foot`\
+///   makes this row a footer row
 ///
 /// # content
 ///
@@ -257,31 +686,33 @@ pub mod tr {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"tr",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
 					args();
 					opt_args();
-					flags(head);
+					flags(head, foot);
 					props();
 				}
 
+				if head && foot {
+					doll.diag(true, usize::MAX, "rows cannot be both head and foot rows");
+				}
+
 				Some(Box::new(Row {
 					is_head: head,
+					is_foot: foot,
 					cells: {
 						let ast = match doll.parse(text) {
-							Ok(ast) => ast,
-							Err(ast) => {
-								doll.ok = false;
-								ast
-							}
+							Ok(ast) | Err(ast) => ast,
 						};
 						parse_row(doll, ast)
 					},
 				}))
 			}),
 		)
+		.with_children(Row::children, Row::children_mut)
 	}
 }
 
@@ -296,10 +727,18 @@ pub mod tr {
 ///
 /// # props
 ///
-/// - `rowspan`\
+/// - `rows`\
 ///   the amount of rows this cell should span
-/// - `colspan`\
+/// - `cols`\
 ///   the amount of columns this cell should span
+/// - `align`\
+///   how to align the content horizontally, one of `left`, `center`, or `right`
+/// - `valign`\
+///   how to align the content vertically, one of `top`, `middle`, or `bottom`
+///
+/// in [untrusted](MarkDoll::untrusted) documents, spans are limited to [`MAX_ROWSPAN`] rows and [`MAX_COLSPAN`] columns
+///
+/// cells written as list items can't be given props, so they are aligned however the stylesheet aligns cells
 ///
 /// # content
 ///
@@ -316,35 +755,223 @@ pub mod tc {
 	pub fn tag() -> TagDefinition {
 		TagDefinition::new(
 			"tc",
-			Some(|doll, mut args, text| {
+			Some(|doll, mut args, text, _| {
 				args! {
 					doll, args;
 
 					args();
 					opt_args();
 					flags(head);
-					props(rows: usize, cols: usize);
+					props(rows: ArgValue<usize>, cols: ArgValue<usize>, align: ArgValue<String>, valign: ArgValue<String>);
+				}
+
+				for span in [&rows, &cols].into_iter().flatten() {
+					if **span == 0 {
+						span.diag(doll, true, "cell span must be at least 1");
+						return None;
+					}
+				}
+
+				// browsers ignore anything larger, and a document from someone untrusted shouldn't get to make the table any work to lay out
+				let mut rows = rows.map_or(1, |rows| rows.value);
+				let mut cols = cols.map_or(1, |cols| cols.value);
+				if doll.is_untrusted() {
+					rows = rows.min(MAX_ROWSPAN);
+					cols = cols.min(MAX_COLSPAN);
 				}
 
+				let align = match align.as_ref().map(|align| align.as_str()) {
+					None => None,
+					Some("left") => Some(Align::Left),
+					Some("center") => Some(Align::Center),
+					Some("right") => Some(Align::Right),
+					Some(_) => {
+						align.unwrap().diag(doll, true, "cell align invalid");
+						return None;
+					}
+				};
+
+				let valign = match valign.as_ref().map(|valign| valign.as_str()) {
+					None => None,
+					Some("top") => Some(VAlign::Top),
+					Some("middle") => Some(VAlign::Middle),
+					Some("bottom") => Some(VAlign::Bottom),
+					Some(_) => {
+						valign.unwrap().diag(doll, true, "cell valign invalid");
+						return None;
+					}
+				};
+
 				Some(Box::new(Cell {
 					is_head: head,
-					rows: rows.unwrap_or(1),
-					cols: cols.unwrap_or(1),
+					rows,
+					cols,
+					align,
+					valign,
+					pos: 0,
 					content: match doll.parse(text) {
-						Ok(ast) => ast,
-						Err(ast) => {
-							doll.ok = false;
-							ast
-						}
+						Ok(ast) | Err(ast) => ast,
 					},
 				}))
 			}),
 		)
+		.with_children(Cell::children, Cell::children_mut)
+	}
+}
+
+/// `tablecsv` tag
+///
+/// make tables from comma-separated values
+///
+/// # flags
+///
+/// - `tsv`\
+///   separate fields by tabs instead of commas
+/// - `head`\
+///   treat the first record as a header row
+///
+/// # props
+///
+/// - `delim`\
+///   separate fields by an arbitrary character, overriding `tsv`
+///
+/// # content
+///
+/// CSV text, not parsed as markdoll
+///
+/// fields may be quoted with `"`, in which case they may contain delimiters, newlines, and `""` for a literal quote
+///
+/// # emitting
+///
+/// produces a [`Table`], emitted by [`table::html`]
+pub mod tablecsv {
+	use super::*;
+
+	/// parse CSV text into records of fields, each field paired with its position
+	///
+	/// records may end with `\n` or `\r\n`, and a diagnostic is emitted at an unterminated quote
+	fn parse_records(doll: &mut MarkDoll, text: &str, delim: char) -> Vec<Vec<(usize, String)>> {
+		let mut records = Vec::new();
+		let mut record = Vec::new();
+		let mut field = String::new();
+
+		let mut quote_start = None;
+		let mut field_start = 0;
+		let mut chars = text.chars().enumerate().peekable();
+
+		while let Some((pos, ch)) = chars.next() {
+			if quote_start.is_some() {
+				match ch {
+					'"' if chars.next_if(|(_, ch)| *ch == '"').is_some() => field.push('"'),
+					'"' => quote_start = None,
+					ch => field.push(ch),
+				}
+			} else {
+				match ch {
+					'"' if field.is_empty() => quote_start = Some(pos),
+					'\r' if chars.peek().is_some_and(|(_, ch)| *ch == '\n') => {}
+					'\n' => {
+						record.push((field_start, core::mem::take(&mut field)));
+						records.push(core::mem::take(&mut record));
+						field_start = pos + 1;
+					}
+					ch if ch == delim => {
+						record.push((field_start, core::mem::take(&mut field)));
+						field_start = pos + 1;
+					}
+					ch => field.push(ch),
+				}
+			}
+		}
+
+		if let Some(quote_start) = quote_start {
+			doll.diag(true, quote_start, "unterminated quote in csv record");
+		}
+
+		if !field.is_empty() || !record.is_empty() {
+			record.push((field_start, field));
+			records.push(record);
+		}
+
+		records
+	}
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"tablecsv",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args();
+					flags(tsv, head);
+					props(delim: char);
+				}
+
+				let delim = delim.unwrap_or(if tsv { '\t' } else { ',' });
+
+				let mut rows = parse_records(doll, text, delim)
+					.into_iter()
+					.enumerate()
+					.map(|(i, record)| {
+						let is_head = head && i == 0;
+
+						let row = Row {
+							is_head,
+							is_foot: false,
+							cells: record
+								.into_iter()
+								.map(|(pos, field)| Cell {
+									is_head,
+									rows: 1,
+									cols: 1,
+									align: None,
+									valign: None,
+									content: if field.is_empty() {
+										Vec::new()
+									} else {
+										vec![BlockItem::Inline(vec![(
+											pos,
+											InlineItem::Text(field),
+										)])]
+									},
+									pos,
+								})
+								.collect(),
+						};
+
+						(row, None)
+					})
+					.collect::<Vec<_>>();
+
+				validate_spans(doll, &mut rows);
+
+				let mut table = Table {
+					head: Vec::new(),
+					body: Vec::new(),
+					foot: Vec::new(),
+					label: None,
+					describedby: None,
+					scrollable: false,
+				};
+
+				for (row, _) in rows {
+					table.push(row);
+				}
+
+				Some(Box::new(table))
+			}),
+		)
+		.with_children(Table::children, Table::children_mut)
+		.with_emitter::<HtmlEmit>(table::html)
 	}
 }
 
 /// all of this module's tags
 #[must_use]
-pub fn tags() -> [TagDefinition; 3] {
-	[table::tag(), tr::tag(), tc::tag()]
+pub fn tags() -> [TagDefinition; 4] {
+	[table::tag(), tr::tag(), tc::tag(), tablecsv::tag()]
 }
@@ -0,0 +1,226 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		tree::{BlockItem, InlineItem, TagContent, TagInvocation, AST},
+		MarkDoll,
+	},
+	::alloc::format,
+	alloc::{boxed::Box, string::String, vec::Vec},
+	core::{fmt::Write, mem},
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// a tab and its panel
+#[derive(Debug)]
+pub struct Tab {
+	/// the label
+	pub label: String,
+	/// the content of the panel
+	pub content: AST,
+}
+
+/// a group of tabs, of which one panel is shown at a time
+#[derive(Debug)]
+pub struct Tabs {
+	/// the tabs, in order
+	pub tabs: Vec<Tab>,
+}
+
+impl Tab {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		vec![&content.downcast_ref::<Self>().unwrap().content]
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		vec![&mut content.downcast_mut::<Self>().unwrap().content]
+	}
+}
+
+impl Tabs {
+	fn children(content: &dyn TagContent) -> Vec<&AST> {
+		content
+			.downcast_ref::<Self>()
+			.unwrap()
+			.tabs
+			.iter()
+			.map(|tab| &tab.content)
+			.collect()
+	}
+
+	fn children_mut(content: &mut dyn TagContent) -> Vec<&mut AST> {
+		content
+			.downcast_mut::<Self>()
+			.unwrap()
+			.tabs
+			.iter_mut()
+			.map(|tab| &mut tab.content)
+			.collect()
+	}
+}
+
+/// `tabs` tag
+///
+/// switch between panels of content, such as the same example in several languages
+///
+/// # content
+///
+/// [`tab`](mod@tab) tags
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], writes a group of radio inputs and labels that switches panels without JavaScript, given CSS such as
+///
+/// ```css
+/// .doll-tab-input { position: absolute; opacity: 0; }
+/// .doll-tab-panel { display: none; }
+/// .doll-tab-input:checked + .doll-tab + .doll-tab-panel { display: block; }
+/// ```
+///
+/// each group's ids are [`number`](crate::emit::IdAllocator::number)ed by [`HtmlEmit::ids`], as `doll-tabs-1` and so on
+pub mod tabs {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"tabs",
+			Some(|doll, _, text, _| {
+				#[track_caller]
+				fn fail(doll: &mut MarkDoll, pos: usize) {
+					doll.diag(true, pos, "`tabs` tags may only contain `tab` tags");
+				}
+
+				let ast = match doll.parse(text) {
+					Ok(ast) | Err(ast) => ast,
+				};
+
+				let mut tabs = Vec::new();
+
+				for mut child in ast {
+					match &mut child {
+						BlockItem::Inline(items) => {
+							for (pos, item) in mem::take(items) {
+								match item {
+									InlineItem::Tag(TagInvocation { content, .. }) => {
+										if let Ok(tab) = content.downcast::<Tab>() {
+											tabs.push(*tab);
+										} else {
+											fail(doll, pos);
+										}
+									}
+									InlineItem::Split => {}
+									_ => fail(doll, pos),
+								}
+							}
+						}
+						BlockItem::List { pos, .. } | BlockItem::Section { pos, .. } => {
+							fail(doll, *pos);
+						}
+					}
+				}
+
+				if tabs.is_empty() {
+					doll.diag(false, usize::MAX, "no tabs in `tabs` tag");
+				}
+
+				Some(Box::new(Tabs { tabs }))
+			}),
+		)
+		.with_children(Tabs::children, Tabs::children_mut)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let tabs = content.downcast_mut::<Tabs>().unwrap();
+
+		let group = format!("doll-tabs-{}", to.ids.number("doll-tabs"));
+
+		to.write.push_str("<div class='doll-tabs' role='tablist'>");
+
+		for (i, tab) in tabs.tabs.iter_mut().enumerate() {
+			let id = format!("{group}-{}", i + 1);
+
+			to.write_void(
+				"input",
+				&format!(
+					" type='radio' class='doll-tab-input' name='{group}' id='{id}'{}",
+					if i == 0 { " checked" } else { "" },
+				),
+			);
+			write!(
+				to.write,
+				"<label class='doll-tab' role='tab' id='{id}-tab' for='{id}'>{}</label>\
+				<div class='doll-tab-panel' role='tabpanel' aria-labelledby='{id}-tab'>",
+				html_escape::encode_text(&tab.label),
+			)
+			.unwrap();
+
+			let inline_block = tab.content.len() > 1;
+			for item in &mut tab.content {
+				item.emit(doll, to, inline_block);
+			}
+
+			to.write.push_str("</div>");
+		}
+
+		to.write.push_str("</div>");
+	}
+}
+
+/// `tab` tag
+///
+/// tabs inside of [`tabs`](mod@tabs) tags
+///
+/// # arguments
+///
+/// - `label`\
+///   the label of the tab
+///
+/// # content
+///
+/// markdoll, shown when the tab is selected
+///
+/// # emitting
+///
+/// this tag will never be emitted when used properly, do not add an emitter to it
+pub mod tab {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"tab",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(label: String);
+					opt_args();
+					flags();
+					props();
+				}
+
+				Some(Box::new(Tab {
+					label,
+					content: match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					},
+				}))
+			}),
+		)
+		.with_children(Tab::children, Tab::children_mut)
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 2] {
+	[tabs::tag(), tab::tag()]
+}
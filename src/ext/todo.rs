@@ -0,0 +1,178 @@
+use {
+	crate::{
+		args,
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		tree::{
+			plain_text, print,
+			visit::{self, Visitor},
+			PlainTextOptions, TagContent, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// how [`todo`](mod@todo) tags are emitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DraftPolicy {
+	/// highlight them, for previews
+	Show,
+	/// leave them out
+	#[default]
+	Hide,
+	/// report an error at each, so release builds fail while any remain
+	Error,
+}
+
+/// a note left for the writers
+#[derive(Debug)]
+pub struct Todo {
+	/// who is to address it
+	pub assignee: Option<String>,
+	/// the note
+	pub content: AST,
+	/// the position of the tag in the source, see [`list`]
+	pub at: usize,
+}
+
+/// a [`Todo`] found by [`list`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listed {
+	/// the position of the tag in the source, resolve it with [`LineIndex`](crate::diagnostics::LineIndex)
+	pub at: usize,
+	/// who is to address it
+	pub assignee: Option<String>,
+	/// the [`plain_text`] of the note
+	pub text: String,
+}
+
+/// `todo` tag
+///
+/// leave a note for the writers, shown in previews and kept out of releases
+///
+/// # arguments
+///
+/// - `assignee` (optional)\
+///   who is to address it
+///
+/// # content
+///
+/// markdoll
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], follows [`HtmlEmit::draft_mode`]
+pub mod todo {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"todo",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args();
+					opt_args(assignee);
+					flags();
+					props();
+				}
+
+				Some(Box::new(Todo {
+					assignee: assignee.map(ToString::to_string),
+					content: match doll.parse(text) {
+						Ok(ast) | Err(ast) => ast,
+					},
+					at: doll.resolve_pos(usize::MAX),
+				}))
+			}),
+		)
+		.with_children(
+			|content| vec![&content.downcast_ref::<Todo>().unwrap().content],
+			|content| vec![&mut content.downcast_mut::<Todo>().unwrap().content],
+		)
+		.with_printer(|doll, content, options| {
+			print::to_doll(
+				doll,
+				&content.downcast_ref::<Todo>().unwrap().content,
+				options,
+			)
+		})
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let todo = content.downcast_mut::<Todo>().unwrap();
+
+		match to.draft_mode {
+			DraftPolicy::Show => {}
+			DraftPolicy::Hide => return,
+			DraftPolicy::Error => {
+				doll.diag(true, usize::MAX, "todo in release");
+				return;
+			}
+		}
+
+		let inline_block = todo.content.len() > 1;
+		let element = if inline_block { "div" } else { "span" };
+
+		write!(to.write, "<{element} class='doll-todo' role='note'>").unwrap();
+
+		if let Some(assignee) = &todo.assignee {
+			write!(
+				to.write,
+				"<span class='doll-todo-assignee'>{}</span>",
+				html_escape::encode_text(assignee)
+			)
+			.unwrap();
+		}
+
+		for item in &mut todo.content {
+			item.emit(doll, to, inline_block);
+		}
+
+		write!(to.write, "</{element}>").unwrap();
+	}
+}
+
+/// find every [`todo`](mod@todo) tag in an [`AST`], in order
+#[must_use]
+pub fn list(doll: &MarkDoll, ast: &AST) -> Vec<Listed> {
+	struct List(Vec<Listed>);
+
+	impl Visitor for List {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+			if let Some(todo) = tag.content.downcast_ref::<Todo>() {
+				self.0.push(Listed {
+					at: todo.at,
+					assignee: todo.assignee.clone(),
+					text: plain_text(doll, &todo.content, PlainTextOptions::default()),
+				});
+			}
+
+			visit::walk_tag(self, doll, pos, tag);
+		}
+	}
+
+	let mut list = List(Vec::new());
+	visit::walk(ast, doll, &mut list);
+	list.0
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[todo::tag()]
+}
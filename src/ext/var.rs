@@ -0,0 +1,101 @@
+use {
+	crate::{args, emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+	},
+	core::fmt::Write,
+	hashbrown::HashMap,
+};
+
+/// a variable to substitute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Var {
+	/// the key looked up in the emit target's variables
+	pub key: String,
+}
+
+/// `var` tag
+///
+/// substitute a variable supplied by the emit target, such as a version number from the build
+///
+/// # arguments
+///
+/// - `key`\
+///   the variable to substitute
+///
+/// # implementation
+///
+/// when emitting to [`HtmlEmit`], looks the key up in [`HtmlEmit::vars`] and writes the escaped value, or warns and writes the key in brackets if it isn't defined
+///
+/// custom emit targets can support the tag the same way, by carrying their own map of variables and registering an emitter that looks up [`Var::key`] in it
+pub mod var {
+	use super::*;
+
+	/// the tag
+	#[must_use]
+	pub fn tag() -> TagDefinition {
+		TagDefinition::new(
+			"var",
+			Some(|doll, mut args, text, _| {
+				args! {
+					doll, args;
+
+					args(key: String);
+					opt_args();
+					flags();
+					props();
+				};
+
+				if !text.is_empty() {
+					doll.diag(true, usize::MAX, "cannot have content");
+				}
+
+				Some(Box::new(Var { key }))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(html)
+	}
+
+	/// emit to html
+	pub fn html(doll: &mut MarkDoll, to: &mut HtmlEmit, content: &mut Box<dyn TagContent>) {
+		let var = content.downcast_ref::<Var>().unwrap();
+
+		if let Some(value) = to.vars.get(&var.key) {
+			to.write.push_str(&html_escape::encode_text(value));
+		} else {
+			doll.diag_arg(false, 0, "variable not defined");
+			write!(to.write, "[{}]", html_escape::encode_text(&var.key)).unwrap();
+		}
+	}
+}
+
+/// define variables from `key: value` lines in a document's frontmatter, without replacing any already defined
+///
+/// define variables given programmatically first, so they override the document's own
+///
+/// lines without a `:`, with an empty key, or starting with `#` are ignored, and keys and values are trimmed
+pub fn frontmatter_vars(vars: &mut HashMap<String, String>, frontmatter: &str) {
+	for line in frontmatter.lines() {
+		let line = line.trim();
+		if line.starts_with('#') {
+			continue;
+		}
+
+		let Some((key, value)) = line.split_once(':') else {
+			continue;
+		};
+
+		let key = key.trim();
+		if !key.is_empty() {
+			vars.entry(key.to_string())
+				.or_insert_with(|| value.trim().to_string());
+		}
+	}
+}
+
+/// all of this module's tags
+#[must_use]
+pub fn tags() -> [TagDefinition; 1] {
+	[var::tag()]
+}
@@ -0,0 +1,211 @@
+use {
+	crate::{
+		diagnostics::{explain, LineIndex, Severity, Structured},
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self},
+		MarkDoll, Phase,
+	},
+	alloc::{
+		string::{String, ToString},
+		vec::Vec,
+	},
+	std::panic::{self, AssertUnwindSafe},
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// a flag for [`convert_html`] registering the [`danger`](crate::ext::danger) tags, only pass it for trusted documents
+///
+/// without the `danger` feature, the tags don't exist and this does nothing, so builds without it can never write unescaped html
+pub const ALLOW_DANGER: u32 = 1;
+
+/// the result of [`convert_html`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "wasm",
+	wasm_bindgen::prelude::wasm_bindgen(getter_with_clone)
+)]
+pub struct Converted {
+	/// whether the document converted without errors
+	pub ok: bool,
+	/// the html, which may be partial if there were errors
+	pub html: String,
+	/// the diagnostics as a json array of [`Structured`] diagnostics
+	pub diagnostics: String,
+}
+
+/// the tags registered by [`convert_html`]
+fn doll(flags: u32) -> MarkDoll {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags(ext::citation::tags());
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::conditional::tags());
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.ext_system.add_tags(ext::code::tags());
+	doll.ext_system.add_tags(ext::datetime::tags());
+	doll.ext_system.add_tags(ext::diagram::tags());
+	doll.ext_system.add_tags(ext::emoji::tags());
+	doll.ext_system.add_tags(ext::glossary::tags());
+	doll.ext_system.add_tags(ext::index::tags());
+	doll.ext_system.add_tags(ext::layout::tags());
+	doll.ext_system.add_tags(ext::links::tags());
+	doll.ext_system.add_tags(ext::math::tags());
+	doll.ext_system.add_tags(ext::media::tags());
+	doll.ext_system.add_tags(ext::page::tags());
+	#[cfg(feature = "qr")]
+	doll.ext_system.add_tags(ext::qr::tags());
+	doll.ext_system.add_tags(ext::table::tags());
+	doll.ext_system.add_tags(ext::tabs::tags());
+	doll.ext_system.add_tags(ext::todo::tags());
+	doll.ext_system.add_tags(ext::var::tags());
+	#[cfg(feature = "danger")]
+	if flags & ALLOW_DANGER != 0 {
+		doll.ext_system.add_tags(ext::danger::tags());
+	}
+	#[cfg(not(feature = "danger"))]
+	let _ = flags;
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	doll
+}
+
+fn convert(src: &str, flags: u32) -> Converted {
+	let mut doll = doll(flags);
+
+	let mut out = HtmlEmit::default();
+
+	// what was recovered from errors is emitted too
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+	ext::index::collect(&doll, &mut ast);
+	ext::citation::collect(&doll, &mut ast);
+
+	doll.emit(&mut ast, &mut out);
+
+	let ok = doll.is_ok();
+	let lines = LineIndex::new(src);
+	let diagnostics = doll
+		.finish()
+		.iter()
+		.map(|diag| diag.to_structured(&lines))
+		.collect::<Vec<_>>();
+
+	Converted {
+		ok,
+		html: out.write,
+		diagnostics: serde_json::to_string(&diagnostics).unwrap(),
+	}
+}
+
+/// convert a document to html with the standard tags, the same way for every binding
+///
+/// pass [`ALLOW_DANGER`] in `flags` to register the danger tags. frontmatter is ignored, and panics are caught and reported as an `internal error` diagnostic rather than unwinding into the caller
+#[must_use]
+#[cfg_attr(
+	feature = "wasm",
+	wasm_bindgen::prelude::wasm_bindgen(js_name = convertHtml)
+)]
+pub fn convert_html(src: &str, flags: u32) -> Converted {
+	panic::catch_unwind(AssertUnwindSafe(|| convert(src, flags))).unwrap_or_else(|payload| {
+		let help = payload
+			.downcast_ref::<&str>()
+			.map(ToString::to_string)
+			.or_else(|| payload.downcast_ref::<String>().cloned());
+
+		Converted {
+			ok: false,
+			html: String::new(),
+			diagnostics: serde_json::to_string(&vec![Structured {
+				code: "internal error",
+				severity: Severity::Error,
+				message: "internal error",
+				help,
+				explanation: explain("internal error").map(|entry| entry.explanation),
+				data: None,
+				// what was being done is lost with the panic
+				phase: Phase::Parse,
+				labels: Vec::new(),
+			}])
+			.unwrap(),
+		}
+	})
+}
+
+/// the C interface, for linking markdoll built as a `cdylib` or `staticlib`, such as with `cargo rustc --release --features capi --crate-type cdylib`
+#[cfg(feature = "capi")]
+pub mod capi {
+	use {
+		super::convert_html,
+		alloc::string::String,
+		core::{ffi::c_char, ptr},
+		std::ffi::CString,
+	};
+
+	/// the result of [`markdoll_convert_html`], free it with [`markdoll_free`]
+	#[repr(C)]
+	#[derive(Debug)]
+	pub struct MarkdollOutput {
+		/// whether the document converted without errors
+		pub ok: bool,
+		/// the html, nul-terminated utf-8
+		pub html: *mut c_char,
+		/// the diagnostics as a json array, nul-terminated utf-8, see `diagnostics.schema.json`
+		pub diagnostics_json: *mut c_char,
+	}
+
+	/// a nul-terminated copy of `text`, with any nul characters in it replaced
+	fn c_string(text: &str) -> *mut c_char {
+		CString::new(text.replace('\0', "\u{FFFD}"))
+			.unwrap()
+			.into_raw()
+	}
+
+	/// convert `len` bytes of utf-8 at `source` to html, see [`convert_html`](super::convert_html)
+	///
+	/// invalid utf-8 is replaced with `U+FFFD`
+	///
+	/// # Safety
+	///
+	/// `source` must be valid for reading `len` bytes, or may be null if `len` is 0
+	#[no_mangle]
+	pub unsafe extern "C" fn markdoll_convert_html(
+		source: *const u8,
+		len: usize,
+		flags: u32,
+	) -> MarkdollOutput {
+		let source = if len == 0 {
+			&[]
+		} else {
+			// SAFETY: upheld by the caller
+			unsafe { core::slice::from_raw_parts(source, len) }
+		};
+
+		let converted = convert_html(&String::from_utf8_lossy(source), flags);
+
+		MarkdollOutput {
+			ok: converted.ok,
+			html: c_string(&converted.html),
+			diagnostics_json: c_string(&converted.diagnostics),
+		}
+	}
+
+	/// free the strings of a [`MarkdollOutput`], leaving them null
+	///
+	/// # Safety
+	///
+	/// `output` must point to a [`MarkdollOutput`] returned by [`markdoll_convert_html`], freed at most once, or be null
+	#[no_mangle]
+	pub unsafe extern "C" fn markdoll_free(output: *mut MarkdollOutput) {
+		// SAFETY: upheld by the caller
+		let Some(output) = (unsafe { output.as_mut() }) else {
+			return;
+		};
+
+		for text in [&mut output.html, &mut output.diagnostics_json] {
+			let text = core::mem::replace(text, ptr::null_mut());
+			if !text.is_null() {
+				// SAFETY: created by `CString::into_raw` in `c_string`, and replaced with null so it is only freed once
+				drop(unsafe { CString::from_raw(text) });
+			}
+		}
+	}
+}
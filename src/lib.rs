@@ -1,6 +1,5 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
-#![feature(downcast_unchecked)]
 #![warn(
 	clippy::pedantic,
 	clippy::allow_attributes_without_reason,
@@ -26,30 +25,47 @@
 )]
 
 extern crate alloc;
+#[cfg(any(
+	feature = "ariadne",
+	feature = "capi",
+	feature = "fs-assets",
+	feature = "highlight-syntect",
+	feature = "testing",
+	feature = "wasm"
+))]
+extern crate std;
 
 use {
 	crate::{
-		diagnostics::{Diagnostic, IndexedSrc, TagDiagnosticTranslation},
-		emit::BuiltInEmitters,
+		dependencies::{Dependency, DependencyKind},
+		diagnostics::{Diagnostic, DiagnosticSink, IndexedSrc, TagDiagnosticTranslation},
+		emit::{BuiltInEmitters, HtmlEmit},
 		ext::ExtensionSystem,
-		tree::{parser, AST},
+		tree::{parser, BlockItem, Snippet, AST},
 		typemap::TypeMap,
 	},
-	::alloc::string::String,
-	alloc::vec::Vec,
+	::alloc::string::{String, ToString},
+	alloc::{boxed::Box, sync::Arc, vec::Vec},
+	core::{
+		sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+		time::Duration,
+	},
 	hashbrown::HashMap,
 };
 
+/// trace parsing and emitting, which is left out without the `trace` feature, as it is called in every loop of the parser
 macro_rules! t {
 	($text:expr, $expr:expr) => {
 		match $expr {
 			value => {
+				#[cfg(feature = "trace")]
 				log::trace!("{}: {:#?}", $text, &value);
 				value
 			}
 		}
 	};
 	($text:literal) => {
+		#[cfg(feature = "trace")]
 		log::trace!($text);
 	};
 	($expr:expr) => {
@@ -59,31 +75,227 @@ macro_rules! t {
 
 pub(crate) use t;
 
+/// the files documents depend on, for build systems
+pub mod dependencies;
 /// emitting/translating diagnostics
 pub mod diagnostics;
 /// emitting output and default [`BuiltInEmitters`]
 pub mod emit;
 /// the extension system and standard library
 pub mod ext;
+/// converting documents from C and JavaScript
+#[cfg(any(feature = "capi", feature = "wasm"))]
+pub mod ffi;
+/// multiple documents referencing each other
+pub mod project;
+/// golden file tests for documents and tags
+#[cfg(feature = "testing")]
+pub mod testing;
 /// syntax trees and parser
 pub mod tree;
 /// storage by [`TypeId`](core::any::TypeId)
 pub mod typemap;
 
+/// what a [`MarkDoll`] is doing, when it reports [`Progress`] and each [`Diagnostic`]
+///
+/// serialized as `parse`, `emit`, or `{ "pass": name }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Phase {
+	/// parsing a source, counting characters
+	Parse,
+	/// running a pass over a parsed [`AST`] with [`MarkDoll::run_pass`], named by it, such as `heading levels`, never reported as [`Progress`]
+	Pass(&'static str),
+	/// emitting an [`AST`], counting top-level blocks
+	Emit,
+}
+
+/// how far along parsing or emitting is, reported to [`MarkDoll::progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+	/// what is being done
+	pub phase: Phase,
+	/// how much is done
+	pub done: usize,
+	/// how much there is to do
+	pub total: usize,
+}
+
+/// how much source a [`MarkDoll`] is keeping for the current source, see [`MarkDoll::source_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceStats {
+	/// how many buffers of source are kept, the source itself and the content of every tag that couldn't share it, such as content with escapes or indentation removed
+	pub buffers: usize,
+	/// the total length of those buffers in bytes
+	pub bytes: usize,
+}
+
 /// markdoll's main context
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools, reason = "independent options")]
 pub struct MarkDoll {
 	/// the extension system, used to add tags
 	pub ext_system: ExtensionSystem,
 
+	/// whether to [`normalize`](tree::normalize) parsed [`AST`]s, enabled by default
+	pub normalize: bool,
+
+	/// whether plain newlines within a paragraph are line breaks, as if each were escaped, rather than spaces, disabled by default
+	///
+	/// blank lines still separate paragraphs. a document can enable this for itself with a `breaks: hard` line in its frontmatter, and the [`verse`](ext::formatting::verse) tag enables it for its content
+	pub hard_breaks: bool,
+
+	/// whether [`comment`](ext::common::comment) tags are kept in parsed [`AST`]s, to be shown in drafts, rather than left out, disabled by default
+	///
+	/// kept comments are emitted as [`HtmlEmit::draft_mode`](emit::HtmlEmit::draft_mode) says, so they are still left out of the output by default, but they separate the text around them and keep paragraphs of only comments
+	pub keep_comments: bool,
+
+	/// the variables `${key}` in tag arguments are replaced with, leaving arguments as written if `None`, the default
+	///
+	/// a document's frontmatter adds to these with `key: value` lines while it is parsed, without replacing those given here, see [`frontmatter_vars`](ext::var::frontmatter_vars). keys that aren't defined are warned about and left as written, and `$${` writes `${`. only arguments are replaced, never content, and arguments are printed back with their values
+	pub arg_vars: Option<HashMap<String, String>>,
+
+	/// the [`Shorthand`](tree::Shorthand)s recognized in text, none by default
+	///
+	/// for example, pushing [`Shorthand::bold`](tree::Shorthand::bold) parses `*bold*` the same as `[em(b):bold]`
+	pub shorthands: Vec<tree::Shorthand>,
+
+	/// decides what to do with each tag before its content is parsed, whether to parse it, parse it as another tag, or leave it out with an error, parsing every tag as it is if `None`, the default
+	///
+	/// such as to allow different tags for each of the sites a document is shown on without a [`MarkDoll`] for each. it is asked once for each tag, after it counts towards [`tag_usage`](Self::tag_usage) and before its definition is found, so a tag it renames is found by the new name without asking it again, and must still be allowed in [`untrusted`](Self::untrusted) documents
+	pub tag_filter: Option<ext::TagFilter>,
+
+	/// whether to parse identical invocations of [`pure`](ext::TagDefinition::pure) tags once per source, copying the content from then on, disabled by default
+	///
+	/// speeds up documents repeating the same tags, such as a logo or boilerplate note, many times
+	pub cache_pure_tags: bool,
+
+	/// called with the [`Progress`] of parsing and emitting, for showing feedback on long documents
+	///
+	/// reported at the start and end, and while parsing at the first line past each hundredth of the source, and while emitting after each top-level block. only the root source is reported, not the content of tags, and [`emit_parallel`](Self::emit_parallel) reports nothing
+	pub progress: Option<fn(Progress)>,
+
+	/// how deeply blocks may be nested when emitting, including blocks within tags, before the rest are left out with an error, 256 by default
+	///
+	/// emitting is recursive, so this keeps deeply nested [`AST`]s from overflowing the stack
+	pub max_emit_depth: usize,
+	/// how deeply nested the block currently being emitted is
+	pub(crate) emit_depth: usize,
+	/// how deeply tags may be nested within the content of other tags while parsing, before the content of deeper tags is kept as text with an error, 128 by default
+	///
+	/// parsing is recursive, so this keeps deeply nested tags, such as a line of thousands of `[em:`, from overflowing the stack
+	pub max_tag_depth: usize,
+	/// what the diagnostics reported now are marked as reported during
+	pub(crate) phase: Phase,
+
+	/// how many bytes of source may be kept while parsing a source, counted as in [`source_stats`](Self::source_stats), before parsing stops with an error, unlimited by default
+	///
+	/// along with [`max_buffers`](Self::max_buffers), this bounds what untrusted input can make a parse keep in memory
+	pub max_source_bytes: usize,
+	/// how many buffers of source may be kept while parsing a source, counted as in [`source_stats`](Self::source_stats), before parsing stops with an error, unlimited by default
+	///
+	/// many small tags whose content can't share the source, such as with escapes, each keep their own buffer
+	pub max_buffers: usize,
+	/// whether parsing stops at the first error, leaving the rest of the source unparsed, such as for a quick check in CI, off by default
+	///
+	/// warnings don't stop it, nor do errors made warnings by [`severity_overrides`](Self::severity_overrides). tags parsing their content stop along with the source containing them, and what was parsed is kept as if the source ended there, see [`stopped_early`](Self::stopped_early)
+	pub fail_fast: bool,
+	/// set to `true` from anywhere to stop parsing, such as when an editor has a newer version of the source to parse instead, reported as `parsing cancelled`
+	///
+	/// checked before each line, including those within tags, and what was parsed is kept as with [`fail_fast`](Self::fail_fast), see [`stopped_early`](Self::stopped_early). it is not cleared by markdoll, so set it back before parsing again
+	pub cancel: Option<Arc<AtomicBool>>,
+	/// how long parsing a source may take before stopping as if [`cancel`](Self::cancel)led, reported as `parsing took too long`, unlimited by default
+	///
+	/// measured with [`clock`](Self::clock), without which it does nothing
+	pub max_duration: Option<Duration>,
+	/// the time now, as how long it has been since any fixed point, such as `|| start.elapsed()` with a `std::time::Instant`, which [`max_duration`](Self::max_duration) is measured with, `None` by default as markdoll has no clock of its own
+	pub clock: Option<fn() -> Duration>,
+	/// when parsing the current source must stop by, by [`clock`](Self::clock)
+	pub(crate) parse_deadline: Option<Duration>,
+
 	pub(crate) builtin_emitters: TypeMap,
 
-	/// whether the current operation is "ok"
+	/// state tags keep while emitting, such as footnote counters or numbering, shared between every tag in the same [`emit`](Self::emit)
 	///
-	/// this shouldn't really be set to `true` by anything except the language
-	pub ok: bool,
+	/// cleared when an emit begins, values are best kept in types private to the tags using them
+	pub scratch: TypeMap,
+
+	/// values the embedder gives tags to read while parsing and emitting, such as configuration or a localization table
+	///
+	/// never cleared by markdoll, unlike [`scratch`](Self::scratch), and copied to each thread by [`emit_parallel`](Self::emit_parallel)
+	pub context: TypeMap,
+
+	/// whether the current operation is "ok", see [`is_ok`](Self::is_ok)
+	pub(crate) ok: bool,
+	/// override the severity of diagnostics by their code, `Some(true)` makes them errors, `Some(false)` makes them warnings, and `None` suppresses them
+	pub severity_overrides: HashMap<String, Option<bool>>,
+	/// how many diagnostics have been suppressed by [`severity_overrides`](Self::severity_overrides) since the last [`finish`](Self::finish)
+	pub suppressed: usize,
+	/// how many diagnostics of the same code and severity [`finish_collapsed`](Self::finish_collapsed) keeps before collapsing the rest into the last one kept
+	pub similar_diagnostics_limit: usize,
 	pub(crate) diagnostics: Vec<Diagnostic>,
 	pub(crate) diagnostic_translations: Vec<TagDiagnosticTranslation>,
+	/// identifies the current source, changed by [`finish`](Self::finish) and [`reset`](Self::reset)
+	pub(crate) generation: usize,
+	/// see [`dependencies`](Self::dependencies)
+	pub(crate) dependencies: Vec<Dependency>,
+	/// see [`source_stats`](Self::source_stats)
+	pub(crate) source_stats: SourceStats,
+	/// see [`stopped_early`](Self::stopped_early)
+	pub(crate) stopped_early: bool,
+	/// the length in characters of the source, counted the first time a position is kept within it by [`resolve_pos`](Self::resolve_pos)
+	pub(crate) source_chars: Option<usize>,
+	/// see [`tag_usage`](Self::tag_usage)
+	pub(crate) tag_usage: HashMap<String, usize>,
+	/// see [`total_tag_usage`](Self::total_tag_usage)
+	pub(crate) total_tag_usage: HashMap<String, usize>,
+	/// see [`cache_pure_tags`](Self::cache_pure_tags)
+	pub(crate) tag_cache: tree::cache::TagCache,
+	/// see [`untrusted`](Self::untrusted)
+	untrusted: bool,
+	/// while emitting part of an [`emit_parallel`](Self::emit_parallel), how much every part has written so far, and how much of that the part being emitted wrote, so output limits count all of it
+	pub(crate) parallel_output: Option<(Arc<AtomicUsize>, usize)>,
+}
+
+/// the next unused [`MarkDoll::generation`], shared so generations are never reused by nested sessions or other instances
+static NEXT_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+fn next_generation() -> usize {
+	NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// the state of a [`MarkDoll`] specific to one source, which can be set aside with [`MarkDoll::swap_source_state`]
+#[derive(Debug)]
+pub(crate) struct SourceState {
+	ok: bool,
+	suppressed: usize,
+	diagnostics: Vec<Diagnostic>,
+	diagnostic_translations: Vec<TagDiagnosticTranslation>,
+	generation: usize,
+	dependencies: Vec<Dependency>,
+	source_stats: SourceStats,
+	stopped_early: bool,
+	source_chars: Option<usize>,
+	tag_cache: tree::cache::TagCache,
+}
+
+impl SourceState {
+	/// the state of a source that hasn't been parsed yet
+	pub fn new() -> Self {
+		Self {
+			ok: true,
+			suppressed: 0,
+			diagnostics: Vec::new(),
+			diagnostic_translations: Vec::new(),
+			generation: next_generation(),
+			dependencies: Vec::new(),
+			source_stats: SourceStats::default(),
+			stopped_early: false,
+			source_chars: None,
+			tag_cache: tree::cache::TagCache::default(),
+		}
+	}
 }
 
 impl MarkDoll {
@@ -91,15 +303,96 @@ impl MarkDoll {
 	#[must_use]
 	pub fn new() -> Self {
 		Self {
-			ext_system: ExtensionSystem {
-				tags: HashMap::new(),
-			},
+			ext_system: ExtensionSystem::default(),
+
+			normalize: true,
+			hard_breaks: false,
+			keep_comments: false,
+			arg_vars: None,
+			shorthands: Vec::new(),
+			tag_filter: None,
+			cache_pure_tags: false,
+			progress: None,
+
+			max_emit_depth: 256,
+			emit_depth: 0,
+			max_tag_depth: 128,
+			phase: Phase::Parse,
+
+			max_source_bytes: usize::MAX,
+			max_buffers: usize::MAX,
+			fail_fast: false,
+			cancel: None,
+			max_duration: None,
+			clock: None,
+			parse_deadline: None,
 
 			builtin_emitters: TypeMap::default(),
+			scratch: TypeMap::default(),
+			context: TypeMap::default(),
 
 			ok: true,
+			severity_overrides: HashMap::new(),
+			suppressed: 0,
+			similar_diagnostics_limit: 10,
 			diagnostics: Vec::new(),
 			diagnostic_translations: Vec::new(),
+			generation: next_generation(),
+			dependencies: Vec::new(),
+			source_stats: SourceStats::default(),
+			stopped_early: false,
+			source_chars: None,
+			tag_usage: HashMap::new(),
+			total_tag_usage: HashMap::new(),
+			tag_cache: tree::cache::TagCache::default(),
+			untrusted: false,
+			parallel_output: None,
+		}
+	}
+
+	/// construct an instance for documents from people who aren't trusted, such as posts on a forum, with only tags that can't break out of the output, and the default [`BuiltInEmitters`]
+	///
+	/// emit with [`HtmlEmit::untrusted_profile`], which keeps the output itself safe. this instance guarantees, however it is configured afterwards:
+	///
+	/// - tags that write content into the output as is, such as the `html` tag of `ext::danger`, are refused with an error while parsing and emitting, even if they are registered, see [`TagDefinition::passthrough`](ext::TagDefinition::passthrough)
+	/// - each source is limited to 1 MiB, 10000 buffers, tags nested 32 deep, and blocks nested 64 deep when emitting. lower limits are kept, higher ones are lowered to these when parsing begins
+	/// - [`severity_overrides`](Self::severity_overrides) can't make errors warnings or suppress them, so a failed document can't be made to pass
+	/// - table cells span at most [`MAX_ROWSPAN`](ext::table::MAX_ROWSPAN) rows and [`MAX_COLSPAN`](ext::table::MAX_COLSPAN) columns, larger spans are lowered to these
+	///
+	/// the tags registered are those of [`common`](ext::common), [`formatting`](ext::formatting), [`code`](ext::code), [`emoji`](ext::emoji), [`links`](ext::links), [`layout`](ext::layout), [`table`](ext::table), [`tabs`](ext::tabs), and [`math`](ext::math)
+	#[must_use]
+	pub fn untrusted() -> Self {
+		let mut doll = Self::new();
+		doll.untrusted = true;
+		doll.lock_limits();
+
+		doll.ext_system.add_tags(ext::common::tags());
+		doll.ext_system.add_tags(ext::formatting::tags());
+		doll.ext_system.add_tags(ext::code::tags());
+		doll.ext_system.add_tags(ext::emoji::tags());
+		doll.ext_system.add_tags(ext::links::tags());
+		doll.ext_system.add_tags(ext::layout::tags());
+		doll.ext_system.add_tags(ext::table::tags());
+		doll.ext_system.add_tags(ext::tabs::tags());
+		doll.ext_system.add_tags(ext::math::tags());
+		doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+		doll
+	}
+
+	/// whether this instance was constructed with [`untrusted`](Self::untrusted)
+	#[must_use]
+	pub fn is_untrusted(&self) -> bool {
+		self.untrusted
+	}
+
+	/// lower the limits to those of [`untrusted`](Self::untrusted), if this instance is
+	fn lock_limits(&mut self) {
+		if self.untrusted {
+			self.max_source_bytes = self.max_source_bytes.min(1 << 20);
+			self.max_buffers = self.max_buffers.min(10_000);
+			self.max_tag_depth = self.max_tag_depth.min(32);
+			self.max_emit_depth = self.max_emit_depth.min(64);
 		}
 	}
 
@@ -108,30 +401,60 @@ impl MarkDoll {
 		self.builtin_emitters.put(emitters);
 	}
 
+	/// whether [`BuiltInEmitters`] are set for an emit target, without which nothing can be emitted to it
+	#[must_use]
+	pub fn has_emitters_for<To: 'static>(&self) -> bool {
+		self.builtin_emitters
+			.get_ref::<BuiltInEmitters<To>>()
+			.is_some()
+	}
+
+	/// report that no [`BuiltInEmitters`] are set for an emit target, once per emit
+	pub(crate) fn missing_emitters<To: 'static>(&mut self) {
+		/// kept in [`scratch`](MarkDoll::scratch) once reported
+		#[derive(Debug, Clone)]
+		struct Reported;
+
+		if self.scratch.get_ref::<Reported>().is_none() {
+			self.scratch.put(Reported);
+			log::error!(
+				"no BuiltInEmitters set for {}",
+				core::any::type_name::<To>()
+			);
+			self.diag(true, 0, "no built-in emitters for this emit target");
+		}
+	}
+
 	/// parse the input into an AST
 	///
 	/// # errors
 	///
-	/// if any error diagnostics are emitted, the resulting [`AST`] may be incomplete
+	/// if any error diagnostics are emitted, the resulting [`AST`] may be incomplete. it still has every block that parsed before parsing stopped, with tags that failed left out, so it can be emitted, such as for a preview
 	///
 	/// # note
 	///
 	/// ensure that the `finish` method is called to reset the state *before* parsing a new file
 	pub fn parse(&mut self, input: &str) -> Result<AST, AST> {
-		if self.diagnostic_translations.is_empty() {
-			self.diagnostic_translations.push(TagDiagnosticTranslation {
-				src: input.into(),
-				indexed: None,
-				offset_in_parent: 0,
-				tag_pos_in_parent: 0,
-				indent: 0,
-			});
+		let root = self.diagnostic_translations.is_empty();
+		if root {
+			self.diagnostic_translations
+				.push(TagDiagnosticTranslation::new(input.into(), 0, 0, 0));
+		}
+		let mut operation = self.begin_operation();
+		let deadline = operation.parse_deadline;
+		if root {
+			operation.add_source(input, 0);
+			operation.set_phase(Phase::Parse);
+			operation.start_deadline();
 		}
-		let ok = self.ok;
+		let mut res = parser::parse(parser::Ctx::new(&mut operation, input, false));
+		operation.parse_deadline = deadline;
+		operation.end();
 
-		self.ok = true;
-		let res = parser::parse(parser::Ctx::new(self, input, false));
-		self.ok = ok;
+		if self.normalize {
+			let (Ok((_, ast)) | Err((_, ast))) = &mut res;
+			tree::normalize(ast);
+		}
 
 		match res {
 			Ok((_, ast)) => Ok(ast),
@@ -139,81 +462,760 @@ impl MarkDoll {
 		}
 	}
 
+	/// parse markdoll generated by a tag, such as content built from its arguments, rather than written in the source
+	///
+	/// this is the supported way for tags to generate content. diagnostics within it are reported at `derived_from`, a position in the current context like for [`diag`](Self::diag), so pass [`usize::MAX`] to report them at the tag currently containing this context. emit the content with [`Snippet::emit`] so diagnostics while emitting are reported there too
+	///
+	/// # errors
+	///
+	/// if any error diagnostics are emitted, the resulting [`Snippet`] may be incomplete
+	pub fn parse_snippet(
+		&mut self,
+		text: impl Into<Arc<str>>,
+		derived_from: usize,
+	) -> Result<Snippet, Snippet> {
+		let src = text.into();
+		self.add_source(&src, usize::MAX);
+
+		self.diagnostic_translations
+			.push(TagDiagnosticTranslation::generated(
+				Arc::clone(&src),
+				derived_from,
+			));
+		let res = self.parse(&src);
+		let diagnostic_translation = Box::new(self.diagnostic_translations.pop().unwrap());
+
+		match res {
+			Ok(ast) => Ok(Snippet {
+				ast,
+				diagnostic_translation,
+			}),
+			Err(ast) => Err(Snippet {
+				ast,
+				diagnostic_translation,
+			}),
+		}
+	}
+
 	/// parse a complete document into an AST, including frontmatter
 	///
 	/// # errors
 	///
-	/// if any error diagnostics are emitted, the resulting [`AST`] may be incomplete
+	/// if any error diagnostics are emitted, the resulting [`AST`] may be incomplete. it still has every block that parsed before parsing stopped, with tags that failed left out, so it can be emitted, such as for a preview
 	///
 	/// # note
 	///
 	/// ensure that the `finish` method is called to reset the state *before* parsing a new file
+	///
+	/// the source is kept until then to resolve diagnostics, pass an [`Arc<str>`] to share it instead of copying it
 	pub fn parse_document(
 		&mut self,
-		input: &str,
+		input: impl Into<Arc<str>>,
 	) -> Result<(Option<String>, AST), (Option<String>, AST)> {
-		if self.diagnostic_translations.is_empty() {
-			self.diagnostic_translations.push(TagDiagnosticTranslation {
-				src: input.into(),
-				indexed: None,
-				offset_in_parent: 0,
-				tag_pos_in_parent: 0,
-				indent: 0,
+		let input = input.into();
+		self.lock_limits();
+
+		let root = self.diagnostic_translations.is_empty();
+		if root {
+			self.diagnostic_translations
+				.push(TagDiagnosticTranslation::new(Arc::clone(&input), 0, 0, 0));
+		}
+		self.tag_usage.clear();
+
+		let mut operation = self.begin_operation();
+		let deadline = operation.parse_deadline;
+		if root {
+			operation.add_source(&input, 0);
+			operation.set_phase(Phase::Parse);
+			operation.start_deadline();
+		}
+		let mut res = parser::parse(parser::Ctx::new(&mut operation, &input, true));
+		operation.parse_deadline = deadline;
+		if root {
+			let (Ok((_, ast)) | Err((_, ast))) = &res;
+			operation.run_pass("heading levels", |doll| {
+				tree::check_heading_levels(doll, ast);
 			});
 		}
-		let ok = self.ok;
+		operation.end();
 
-		self.ok = true;
-		let res = parser::parse(parser::Ctx::new(self, input, true));
-		self.ok = ok;
+		if self.normalize {
+			let (Ok((_, ast)) | Err((_, ast))) = &mut res;
+			tree::normalize(ast);
+		}
 
 		res
 	}
 
 	/// emit the given [`AST`] to an output, returning true if it was successful
 	///
+	/// nothing is emitted, with an error, if no [`BuiltInEmitters`] are set for the output, see [`has_emitters_for`](Self::has_emitters_for)
+	///
+	/// if it fails, what was emitted is still usable, the built-in html emitters close every element they open and leave out what failed
+	///
+	/// emitting leaves the [`AST`] as it was, so it can be emitted again without parsing it again, such as to html for a site and to another target for a search index. targets keep state while emitting, such as [`HtmlEmit::section_level`](emit::HtmlEmit::section_level), so emit each time to a new one. see [`TagEmitter`](ext::TagEmitter) for what tags must do to keep this
+	///
 	/// # note
 	///
 	/// ensure that the `finish` method is called to reset the state *before* parsing a new file
 	pub fn emit<To: 'static>(&mut self, ast: &mut AST, to: &mut To) -> bool {
-		let ok = self.ok;
+		self.lock_limits();
+		self.scratch.clear();
 
-		self.ok = true;
-		for node in ast {
-			node.emit(self, to, true);
+		let (progress, total) = (self.progress, ast.len());
+		let report = |done| {
+			if let Some(progress) = progress {
+				progress(Progress {
+					phase: Phase::Emit,
+					done,
+					total,
+				});
+			}
+		};
+
+		report(0);
+
+		let mut operation = self.begin_operation().emitting();
+		if !operation.has_emitters_for::<To>() {
+			operation.missing_emitters::<To>();
+			return operation.end();
 		}
-		self.ok = ok;
 
+		for (i, node) in ast.iter_mut().enumerate() {
+			node.emit(&mut operation, to, true);
+			report(i + 1);
+		}
+		emit::run_deferred(&mut operation, to);
+		emit::check_balance(&mut operation);
+		operation.end()
+	}
+
+	/// emit a single block of an [`AST`] to an output, such as to preview only the block found by [`block_at`](tree::block_at)
+	///
+	/// the block is emitted as if it were at the top level, so the output must be set up for the sections it is nested in, such as by setting [`HtmlEmit::section_level`](emit::HtmlEmit::section_level)
+	///
+	/// returns whether no errors were emitted while emitting it, like [`emit`](Self::emit)
+	pub fn emit_block<To: 'static>(&mut self, block: &mut BlockItem, to: &mut To) -> bool {
+		self.lock_limits();
+		self.scratch.clear();
+
+		let mut operation = self.begin_operation().emitting();
+		if !operation.has_emitters_for::<To>() {
+			operation.missing_emitters::<To>();
+			return operation.end();
+		}
+
+		block.emit(&mut operation, to, true);
+		emit::run_deferred(&mut operation, to);
+		emit::check_balance(&mut operation);
+		operation.end()
+	}
+
+	/// emit the given [`AST`] to an output like [`emit`](Self::emit), but emitting each top-level block into its own [`split`](emit::SplitEmit::split) of the output on a thread pool, and [`join`](emit::SplitEmit::join)ing them in order
+	///
+	/// each thread emits with a copy of this instance's tags, emitters, and settings, and the diagnostics of each block are collected in order
+	///
+	/// the output and diagnostics are the same as [`emit`](Self::emit)'s. a block that leaves state in [`scratch`](Self::scratch) for the rest of the document, such as an `abbrdef` tag or [deferred](emit::DeferredEmit::defer) output, or whose part can't be joined, is emitted again along with the blocks after it, in order on this thread
+	///
+	/// # note
+	///
+	/// emitters that keep state between tags outside of the output and [`scratch`](Self::scratch), such as a highlighter writing a stylesheet ahead of the first block it highlights, may see blocks in a different order
+	#[cfg(feature = "rayon")]
+	pub fn emit_parallel<To: emit::SplitEmit + Send + 'static>(
+		&mut self,
+		ast: &mut AST,
+		to: &mut To,
+	) -> bool {
+		use rayon::prelude::*;
+
+		self.lock_limits();
+		self.scratch.clear();
+
+		if !self.has_emitters_for::<To>() {
+			let mut operation = self.begin_operation().emitting();
+			operation.missing_emitters::<To>();
+			return operation.end();
+		}
+
+		let mut base = self.fork();
+		base.parallel_output = Some((Arc::new(AtomicUsize::new(0)), 0));
+		let mut parts = ast.iter().map(|_| to.split()).collect::<Vec<_>>();
+
+		let results = ast
+			.par_iter_mut()
+			.zip(parts.par_iter_mut())
+			.map_init(
+				|| base.fork(),
+				|doll, (block, part)| {
+					doll.scratch.clear();
+					if let Some((_, written)) = &mut doll.parallel_output {
+						*written = 0;
+					}
+
+					let mut operation = doll.begin_operation().emitting();
+					block.emit(&mut operation, part, true);
+					// nothing is left for the blocks after it, so it was emitted as it would be after them
+					let isolated = operation.scratch.is_empty();
+					emit::run_deferred(&mut operation, part);
+					emit::check_balance(&mut operation);
+					let ok = operation.end();
+
+					doll.ok = true;
+					(
+						isolated,
+						ok,
+						core::mem::take(&mut doll.diagnostics),
+						core::mem::take(&mut doll.suppressed),
+					)
+				},
+			)
+			.collect::<Vec<_>>();
+
+		let mut operation = self.begin_operation().emitting();
+		let mut joined = 0;
+		for ((isolated, ok, diagnostics, suppressed), part) in results.into_iter().zip(parts) {
+			if !isolated || !to.join(part) {
+				break;
+			}
+
+			operation.ok &= ok;
+			operation.diagnostics.extend(diagnostics);
+			operation.suppressed += suppressed;
+			joined += 1;
+		}
+
+		if joined < ast.len() {
+			for block in &mut ast[joined..] {
+				block.emit(&mut operation, to, true);
+			}
+			emit::run_deferred(&mut operation, to);
+			emit::check_balance(&mut operation);
+		}
+		operation.end()
+	}
+
+	/// a copy of this instance for emitting the current source on another thread, with its own diagnostics
+	#[cfg(feature = "rayon")]
+	fn fork(&self) -> Self {
+		Self {
+			ext_system: self.ext_system.clone(),
+			normalize: self.normalize,
+			hard_breaks: self.hard_breaks,
+			keep_comments: self.keep_comments,
+			arg_vars: self.arg_vars.clone(),
+			shorthands: self.shorthands.clone(),
+			tag_filter: self.tag_filter.clone(),
+			cache_pure_tags: self.cache_pure_tags,
+			progress: None,
+			max_emit_depth: self.max_emit_depth,
+			emit_depth: self.emit_depth,
+			max_tag_depth: self.max_tag_depth,
+			phase: self.phase,
+			max_source_bytes: self.max_source_bytes,
+			max_buffers: self.max_buffers,
+			fail_fast: self.fail_fast,
+			cancel: self.cancel.clone(),
+			max_duration: self.max_duration,
+			clock: self.clock,
+			parse_deadline: None,
+			builtin_emitters: self.builtin_emitters.clone(),
+			scratch: TypeMap::default(),
+			context: self.context.clone(),
+			ok: true,
+			severity_overrides: self.severity_overrides.clone(),
+			suppressed: 0,
+			similar_diagnostics_limit: self.similar_diagnostics_limit,
+			diagnostics: Vec::new(),
+			diagnostic_translations: self.diagnostic_translations.clone(),
+			generation: self.generation,
+			dependencies: Vec::new(),
+			source_stats: SourceStats::default(),
+			stopped_early: false,
+			source_chars: None,
+			tag_usage: HashMap::new(),
+			total_tag_usage: HashMap::new(),
+			tag_cache: tree::cache::TagCache::default(),
+			untrusted: self.untrusted,
+			parallel_output: self
+				.parallel_output
+				.as_ref()
+				.map(|(total, _)| (Arc::clone(total), 0)),
+		}
+	}
+
+	/// begin an operation, such as parsing or emitting, which tracks whether any errors are emitted during it
+	///
+	/// errors also fail any operation containing it once it ends
+	pub fn begin_operation(&mut self) -> DiagnosticSink<'_> {
+		DiagnosticSink::new(self)
+	}
+
+	/// whether no errors have been emitted during the current operation
+	#[must_use]
+	pub fn is_ok(&self) -> bool {
 		self.ok
 	}
 
+	/// how many times each tag name was invoked while parsing the last document, including names that aren't defined
+	///
+	/// reset by [`parse_document`](Self::parse_document), while tags in sources passed to [`parse`](Self::parse) are added to the current document
+	#[must_use]
+	pub fn tag_usage(&self) -> &HashMap<String, usize> {
+		&self.tag_usage
+	}
+
+	/// how many times each tag name was invoked across everything this instance has parsed, including names that aren't defined
+	#[must_use]
+	pub fn total_tag_usage(&self) -> &HashMap<String, usize> {
+		&self.total_tag_usage
+	}
+
+	/// the registered tags that haven't been invoked in anything this instance has parsed, sorted
+	#[must_use]
+	pub fn unused_tags(&self) -> Vec<&'static str> {
+		self.ext_system
+			.tags()
+			.map(|tag| tag.key)
+			.filter(|key| !self.total_tag_usage.contains_key(*key))
+			.collect()
+	}
+
 	/// ensure that this method is called after parsing a source file, otherwise diagnostics may malfunction
+	///
+	/// [`AST`]s parsed before this must not be emitted afterwards, as their diagnostics would resolve against the wrong source, which panics in debug builds
+	///
+	/// every diagnostic is returned, use [`finish_collapsed`](Self::finish_collapsed) to collapse repeated ones for showing to people
 	pub fn finish(&mut self) -> Vec<Diagnostic> {
 		self.ok = true;
+		self.suppressed = 0;
 		self.diagnostic_translations.clear();
+		self.dependencies.clear();
+		self.source_stats = SourceStats::default();
+		self.stopped_early = false;
+		self.source_chars = None;
+		self.tag_cache.clear();
+		self.generation = next_generation();
 		core::mem::take(&mut self.diagnostics)
 	}
 
+	/// like [`finish`](Self::finish), but collapsing repeated diagnostics according to [`similar_diagnostics_limit`](Self::similar_diagnostics_limit), so they don't bury the rest
+	pub fn finish_collapsed(&mut self) -> Vec<Diagnostic> {
+		let mut translations = core::mem::take(&mut self.diagnostic_translations);
+		let src = translations
+			.first()
+			.map_or("", TagDiagnosticTranslation::text);
+		let diagnostics = diagnostics::collapse(self.finish(), src, self.similar_diagnostics_limit);
+
+		// keep the allocation
+		translations.clear();
+		self.diagnostic_translations = translations;
+
+		diagnostics
+	}
+
+	/// discard the state of the current source, including its diagnostics, keeping registered tags and emitters and reusing allocations
+	///
+	/// like [`finish`](Self::finish), [`AST`]s parsed before this must not be emitted afterwards
+	pub fn reset(&mut self) {
+		self.ok = true;
+		self.suppressed = 0;
+		self.diagnostics.clear();
+		self.diagnostic_translations.clear();
+		self.dependencies.clear();
+		self.source_stats = SourceStats::default();
+		self.stopped_early = false;
+		self.source_chars = None;
+		self.tag_cache.clear();
+		self.generation = next_generation();
+	}
+
+	/// run `f` on a fresh source, returning its result and the source's [`finish`](Self::finish)ed diagnostics
+	///
+	/// the state of any source currently being parsed or emitted is set aside and restored afterwards, so this may be used within tags
+	///
+	/// [`AST`]s parsed within `f` must also be emitted within it
+	pub fn session<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> (R, Vec<Diagnostic>) {
+		let mut state = SourceState::new();
+		self.swap_source_state(&mut state);
+
+		let res = f(self);
+		let mut dependencies = core::mem::take(&mut self.dependencies);
+		let kept = self.source_stats;
+		let diagnostics = self.finish();
+
+		self.swap_source_state(&mut state);
+
+		// the source containing the session depends on everything it does, and keeps what it kept
+		self.dependencies.append(&mut dependencies);
+		self.source_stats.buffers += kept.buffers;
+		self.source_stats.bytes += kept.bytes;
+
+		(res, diagnostics)
+	}
+
+	/// swap the state of the current source with another
+	pub(crate) fn swap_source_state(&mut self, state: &mut SourceState) {
+		core::mem::swap(&mut self.ok, &mut state.ok);
+		core::mem::swap(&mut self.suppressed, &mut state.suppressed);
+		core::mem::swap(&mut self.diagnostics, &mut state.diagnostics);
+		core::mem::swap(
+			&mut self.diagnostic_translations,
+			&mut state.diagnostic_translations,
+		);
+		core::mem::swap(&mut self.generation, &mut state.generation);
+		core::mem::swap(&mut self.dependencies, &mut state.dependencies);
+		core::mem::swap(&mut self.source_stats, &mut state.source_stats);
+		core::mem::swap(&mut self.stopped_early, &mut state.stopped_early);
+		core::mem::swap(&mut self.source_chars, &mut state.source_chars);
+		core::mem::swap(&mut self.tag_cache, &mut state.tag_cache);
+	}
+
+	/// what the diagnostics reported now are marked as reported during, see [`Diagnostic::phase`]
+	#[must_use]
+	pub fn phase(&self) -> Phase {
+		self.phase
+	}
+
+	/// mark the diagnostics reported from now on as reported during `phase`
+	///
+	/// parsing a document and emitting set it for the diagnostics they report, and restore it when done, so tags parsing their content while being emitted report it as emitting. prefer [`run_pass`](Self::run_pass) for passes, which restores it too
+	pub fn set_phase(&mut self, phase: Phase) {
+		self.phase = phase;
+	}
+
+	/// run a pass over a parsed [`AST`], such as a lint, marking the diagnostics it reports as [`Phase::Pass`] with `name`
+	///
+	/// its diagnostics are kept in order with those reported before and after it, until [`finish`](Self::finish)
+	pub fn run_pass<R>(&mut self, name: &'static str, f: impl FnOnce(&mut Self) -> R) -> R {
+		let outer = core::mem::replace(&mut self.phase, Phase::Pass(name));
+		let res = f(self);
+		self.phase = outer;
+		res
+	}
+
+	/// apply the [`severity_overrides`](Self::severity_overrides) of a [`Profile`](diagnostics::Profile), replacing any already set for the codes it overrides
+	///
+	/// overrides set afterwards take precedence, so individual codes can still be allowed or denied
+	pub fn set_profile(&mut self, profile: diagnostics::Profile) {
+		for (code, severity) in profile.overrides() {
+			self.severity_overrides
+				.insert((*code).to_string(), *severity);
+		}
+	}
+
+	/// record that the current source depends on a file, at the tag currently containing this context
+	///
+	/// tags call this while parsing or emitting, see [`dependencies`](Self::dependencies)
+	pub fn record_dependency(&mut self, kind: DependencyKind, path: impl Into<String>) {
+		let at = self.resolve_pos(usize::MAX);
+		let path = path.into();
+		self.tag_cache.record_dependency(kind, &path);
+
+		self.dependencies.push(Dependency {
+			kind,
+			path,
+			at,
+			modified: None,
+		});
+	}
+
+	/// the files the current source depends on so far, in the order they were recorded, such as the images it shows
+	///
+	/// cleared along with diagnostics by [`finish`](Self::finish) and [`reset`](Self::reset), so read them before finishing. dependencies recorded within a [`session`](Self::session) are kept by the source containing it
+	#[must_use]
+	pub fn dependencies(&self) -> &[Dependency] {
+		&self.dependencies
+	}
+
+	/// the files the current source depends on so far, to fill in when they were [`modified`](Dependency::modified)
+	#[must_use]
+	pub fn dependencies_mut(&mut self) -> &mut [Dependency] {
+		&mut self.dependencies
+	}
+
+	/// how much source the current source has kept so far, such as for servers to meter what each request costs, see [`max_source_bytes`](Self::max_source_bytes)
+	///
+	/// cleared by [`finish`](Self::finish) and [`reset`](Self::reset) like [`dependencies`](Self::dependencies), and what a [`session`](Self::session) keeps is kept by the source containing it
+	#[must_use]
+	pub fn source_stats(&self) -> SourceStats {
+		self.source_stats
+	}
+
+	/// count a buffer of source kept for the current source, reporting an error at `at` if that goes past [`max_source_bytes`](Self::max_source_bytes) or [`max_buffers`](Self::max_buffers)
+	pub(crate) fn add_source(&mut self, src: &str, at: usize) {
+		let exceeded = self.source_limit_exceeded();
+
+		self.source_stats.buffers += 1;
+		self.source_stats.bytes += src.len();
+
+		if !exceeded && self.source_limit_exceeded() {
+			self.diag(true, at, "source limit exceeded");
+		}
+	}
+
+	/// whether the current source has kept more than it may, in which case parsing stops
+	pub(crate) fn source_limit_exceeded(&self) -> bool {
+		self.source_stats.buffers > self.max_buffers
+			|| self.source_stats.bytes > self.max_source_bytes
+	}
+
+	/// whether parsing of the current source should stop, leaving the rest unparsed, as it has kept more than it may or an error was reported with [`fail_fast`](Self::fail_fast) set
+	pub(crate) fn should_stop_parsing(&self) -> bool {
+		self.stopped_early || self.source_limit_exceeded()
+	}
+
+	/// start the [`max_duration`](Self::max_duration) of parsing a source
+	fn start_deadline(&mut self) {
+		self.parse_deadline = self
+			.max_duration
+			.zip(self.clock)
+			.map(|(max_duration, clock)| clock().saturating_add(max_duration));
+	}
+
+	/// stop parsing the current source with an error at `at` if it was [`cancel`](Self::cancel)led or took longer than [`max_duration`](Self::max_duration)
+	pub(crate) fn check_cancelled(&mut self, at: usize) {
+		if self.stopped_early {
+			return;
+		}
+
+		let code = if self
+			.cancel
+			.as_ref()
+			.is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+		{
+			"parsing cancelled"
+		} else if self
+			.parse_deadline
+			.zip(self.clock)
+			.is_some_and(|(deadline, clock)| clock() >= deadline)
+		{
+			"parsing took too long"
+		} else {
+			return;
+		};
+
+		self.diag(true, at, code);
+		self.stopped_early = true;
+	}
+
+	/// whether parsing of the current source stopped at an error, as [`fail_fast`](Self::fail_fast) asks, or was [`cancel`](Self::cancel)led or took too long, leaving the rest of it unparsed
+	///
+	/// cleared along with diagnostics by [`finish`](Self::finish) and [`reset`](Self::reset)
+	#[must_use]
+	pub fn stopped_early(&self) -> bool {
+		self.stopped_early
+	}
+
+	/// a hash of the content of an [`AST`] and the [`dependencies`](Self::dependencies) of the current source, for caching what is emitted from it
+	///
+	/// covers the kinds of blocks and inline items, text, section names, and the names, arguments, and content of tags as written, but not positions, so the same content parsed from different places hashes the same. covers the kinds, paths, and modification times of dependencies, but not the contents of the files
+	///
+	/// the hash is the same across runs and platforms. frontmatter and the options of the emit target aren't part of the [`AST`], so include them in cache keys separately, such as with [`HtmlEmit::options_fingerprint`](emit::HtmlEmit::options_fingerprint)
+	#[must_use]
+	pub fn content_fingerprint(&self, ast: &AST) -> u64 {
+		let mut hasher = tree::fingerprint::Fnv::default();
+		hasher.ast(ast);
+		hasher.dependencies(&self.dependencies);
+		core::hash::Hasher::finish(&hasher)
+	}
+
 	/// emit a diagnostic, mapping the position accordingly
 	///
 	/// pass [`usize::MAX`] to `at` to emit at the tag currently containing this context
+	///
+	/// the severity may be changed or the diagnostic dropped by [`severity_overrides`](Self::severity_overrides)
 	#[track_caller]
-	pub fn diag(&mut self, err: bool, mut at: usize, code: &'static str) {
+	pub fn diag(&mut self, mut err: bool, at: usize, code: &'static str) {
+		match self.severity_overrides.get(code) {
+			// a failed untrusted document can't be made to pass
+			Some(Some(false) | None) if self.untrusted && err => {}
+			Some(Some(severity)) => err = *severity,
+			Some(None) => {
+				self.suppressed += 1;
+				return;
+			}
+			None => {}
+		}
+
 		if err {
 			self.ok = false;
+			self.stopped_early |= self.fail_fast && self.phase == Phase::Parse;
+		}
+
+		let (at, len) = if at == usize::MAX {
+			self.resolve_tag_span()
+		} else {
+			(self.resolve_pos(at), 1)
+		};
+
+		self.diagnostics.push(Diagnostic {
+			err,
+			at,
+			len,
+			code,
+			context: None,
+			similar: Vec::new(),
+			phase: self.phase,
+			data: None,
+			#[cfg(debug_assertions)]
+			src: core::panic::Location::caller(),
+		});
+	}
+
+	/// emit a diagnostic like [`diag`](Self::diag), at an argument of the tag currently containing this context, such as one naming something that doesn't exist
+	///
+	/// `index` counts every argument as written, including flags and props. it is reported at the tag if the argument wasn't written in the source, such as for tags built in code
+	#[track_caller]
+	pub fn diag_arg(&mut self, err: bool, index: usize, code: &'static str) {
+		let len = self.diagnostics.len();
+		self.diag(err, usize::MAX, code);
+
+		// unless it was suppressed
+		if self.diagnostics.len() > len {
+			let at = self.resolve_arg_pos(index);
+			let diag = self.diagnostics.last_mut().unwrap();
+			diag.at = at;
+			diag.len = 1;
 		}
+	}
 
-		t!("---- begin diag ----");
+	/// map the position of an argument of the tag currently containing this context to a position in the source, like [`diag_arg`](Self::diag_arg)
+	pub fn resolve_arg_pos(&mut self, index: usize) -> usize {
+		let len = self.diagnostic_translations.len();
+
+		match self.diagnostic_translations[len - 1].args.get(index) {
+			Some(&at) if len > 1 => {
+				// resolved from the parent, which the position is in
+				let top = self.diagnostic_translations.pop().unwrap();
+				let at = self.resolve_pos(at);
+				self.diagnostic_translations.push(top);
+				at
+			}
+			_ => self.resolve_pos(usize::MAX),
+		}
+	}
+
+	/// emit a diagnostic like [`diag`](Self::diag), with a secondary location giving context, such as where a tag's content ended
+	///
+	/// both positions are mapped, and `context_at` may also be [`usize::MAX`]
+	#[track_caller]
+	pub fn diag_with_context(
+		&mut self,
+		err: bool,
+		at: usize,
+		code: &'static str,
+		context_at: usize,
+		context: &'static str,
+	) {
+		let len = self.diagnostics.len();
+		self.diag(err, at, code);
+
+		// unless it was suppressed
+		if self.diagnostics.len() > len {
+			let context_at = self.resolve_pos(context_at);
+			self.diagnostics.last_mut().unwrap().context = Some((context_at, context));
+		}
+	}
+
+	/// the source of a range of characters of the current context, as written in the document, such as to copy it verbatim
+	///
+	/// the text tags are given has the indentation of block content stripped and escapes resolved, while this is a slice of the source itself, so it has them as written. the ends are mapped like [`resolve_pos`](Self::resolve_pos) maps positions, which count characters rather than bytes
+	///
+	/// `None` outside of a document, for ranges past the end of the context, and for contexts not written in the source, such as arguments parsed as markdoll, whose positions aren't tracked
+	pub fn original_text(&mut self, range: core::ops::Range<usize>) -> Option<&str> {
+		let len = self.diagnostic_translations.len();
+		if len == 0
+			|| !self.is_written_in_source(len)
+			|| range.start > range.end
+			|| range.end > self.diagnostic_translations[len - 1].text().chars().count()
+		{
+			return None;
+		}
+
+		let start = self.resolve_pos(range.start);
+		let end = if range.is_empty() {
+			start
+		} else {
+			self.resolve_pos(range.end - 1) + 1
+		};
+		self.source_slice(start..end)
+	}
+
+	/// the source of the tag currently containing this context, as written in the document, from its opening `[` through its closing `]`, including its arguments and content
+	///
+	/// `None` outside of a tag, and for tags not written in the source, such as those built in code or within arguments parsed as markdoll
+	pub fn original_tag_text(&mut self) -> Option<&str> {
+		let len = self.diagnostic_translations.len();
+		if len < 2
+			|| self.diagnostic_translations[len - 1].tag_end_in_parent == usize::MAX
+			|| !self.is_written_in_source(len - 1)
+		{
+			return None;
+		}
+
+		let (at, len) = self.resolve_tag_span();
+		self.source_slice(at..at + len)
+	}
+
+	/// whether the contexts below `end` were all written in their parents, rather than parsed from arguments
+	fn is_written_in_source(&self, end: usize) -> bool {
+		self.diagnostic_translations[1..end]
+			.iter()
+			.all(|trans| trans.offset_in_parent != usize::MAX)
+	}
+
+	/// a range of characters of the source, by the positions diagnostics have
+	fn source_slice(&self, range: core::ops::Range<usize>) -> Option<&str> {
+		let src = self.diagnostic_translations[0].text();
+		let mut bytes = src.char_indices().map(|(at, _)| at).chain([src.len()]);
+
+		let start = bytes.nth(range.start)?;
+		let end = match range.len() {
+			0 => start,
+			len => bytes.nth(len - 1)?,
+		};
+		src.get(start..end)
+	}
+
+	/// the position in the source of the tag currently containing this context, at its opening `[`, and how many characters it covers through its closing `]`, including the content of a block tag, or 1 if its end isn't known
+	fn resolve_tag_span(&mut self) -> (usize, usize) {
+		let at = self.resolve_pos(usize::MAX);
+
+		let len = self.diagnostic_translations.len();
+		let end = self.diagnostic_translations[len - 1].tag_end_in_parent;
+		if len < 2 || end == usize::MAX || end == 0 {
+			return (at, 1);
+		}
+
+		// resolved from the parent, which the end is in
+		let top = self.diagnostic_translations.pop().unwrap();
+		let last = self.resolve_pos(end - 1);
+		self.diagnostic_translations.push(top);
+
+		(at, if last >= at { last - at + 1 } else { 1 })
+	}
+
+	/// map a position in the current context to a position in the source, as diagnostics are
+	///
+	/// pass [`usize::MAX`] to `at` to resolve the position of the tag currently containing this context, such as to record where a tag was written while parsing it
+	pub fn resolve_pos(&mut self, mut at: usize) -> usize {
+		t!("---- begin resolve ----");
 		t!(at);
 		t!(&self.diagnostic_translations);
 
+		// nothing was parsed, such as when emitting before parsing or after `finish`, so there is no source to map into
+		if self.diagnostic_translations.is_empty() {
+			return at;
+		}
+
 		let mut i = self.diagnostic_translations.len() - 1;
 		while i > 0 {
 			let [parent, trans] = &mut self.diagnostic_translations[i - 1..=i] else {
 				unreachable!()
 			};
 
-			at = if at == usize::MAX {
+			at = if at == usize::MAX || trans.offset_in_parent == usize::MAX {
+				// positions in generated sources point to where they were generated from, see `TagDiagnosticTranslation::generated`
 				trans.tag_pos_in_parent
 			} else if let Some(indexed) = &trans.indexed {
 				t!(
@@ -222,8 +1224,8 @@ impl MarkDoll {
 				)
 			} else {
 				let indexed = IndexedSrc::index(
-					&trans.src,
-					&parent.src,
+					trans.text(),
+					parent.text_from_char(trans.offset_in_parent),
 					trans.offset_in_parent,
 					trans.indent,
 				);
@@ -235,13 +1237,16 @@ impl MarkDoll {
 			i -= 1;
 		}
 
-		self.diagnostics.push(Diagnostic {
-			err,
-			at,
-			code,
-			#[cfg(debug_assertions)]
-			src: core::panic::Location::caller(),
-		});
+		// content running to the end of the source, such as that of a block tag left unterminated or frontmatter left unclosed, ends past it, so its end is kept on the last character, or the start of an empty source
+		if at != usize::MAX {
+			let chars = *self
+				.source_chars
+				.get_or_insert_with(|| self.diagnostic_translations[0].text().chars().count());
+
+			at = at.min(chars.saturating_sub(1));
+		}
+
+		at
 	}
 }
 
@@ -1,14 +1,21 @@
 #![cfg(feature = "cli")]
 
 use {
-	clap::{Parser, Subcommand},
-	hashbrown::HashMap,
+	clap::{Parser, Subcommand, ValueEnum},
 	markdoll::{
-		diagnostics,
-		emit::{BuiltInEmitters, HtmlEmit},
-		ext, MarkDoll,
+		dependencies, diagnostics,
+		emit::{self, BuiltInEmitters, CodeBlockFormatter, HtmlEmit, Strings},
+		ext,
+		tree::{self, print},
+		MarkDoll, Phase, Progress,
+	},
+	std::{
+		io::{IsTerminal, Read},
+		sync::{
+			atomic::{AtomicUsize, Ordering},
+			Arc,
+		},
 	},
-	std::{io::Read, rc::Rc},
 };
 
 #[derive(Parser, Debug)]
@@ -16,75 +23,1052 @@ use {
 struct Cli {
 	#[command(subcommand)]
 	command: Command,
+
+	/// make warnings about the document errors, see `markdoll explain` for the codes affected
+	#[arg(long, global = true, conflicts_with = "relaxed")]
+	strict: bool,
+
+	/// make errors about content that can be left out warnings, and suppress warnings about style
+	#[arg(long, global = true)]
+	relaxed: bool,
+
+	/// suppress diagnostics with this code
+	#[arg(long, global = true, value_name = "CODE")]
+	allow: Vec<String>,
+
+	/// print diagnostics as json instead of reports, see `--json-format`
+	#[arg(long, global = true)]
+	json: bool,
+
+	/// how to print json, as a line for each event, see `events.schema.json`, or as an array of the diagnostics, see `diagnostics.schema.json`
+	#[arg(long, global = true, value_enum, default_value_t = JsonFormat::Events, requires = "json")]
+	json_format: JsonFormat,
+
+	/// treat diagnostics with this code as errors
+	#[arg(long, global = true, value_name = "CODE")]
+	deny: Vec<String>,
+
+	/// don't print the progress of parsing and emitting, which is otherwise printed when stderr is a terminal
+	#[arg(long, global = true)]
+	no_status: bool,
+
+	/// refuse input larger than this, in bytes or with a suffix of `K`, `M`, or `G`
+	#[arg(long, global = true, value_name = "SIZE", default_value = "256M", value_parser = parse_size)]
+	max_input_size: u64,
+
+	/// fail documents with fewer words than this, such as stubs
+	#[arg(long, global = true, value_name = "N", default_value_t = 0)]
+	min_words: usize,
+
+	/// fail documents without any sections
+	#[arg(long, global = true)]
+	require_headings: bool,
+
+	/// stop parsing at the first error, leaving the rest of the document unparsed, for the cheapest check
+	#[arg(long, global = true)]
+	fail_fast: bool,
+}
+
+/// see [`Cli::json_format`]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum JsonFormat {
+	/// a line for each event, as it happens
+	Events,
+	/// an array of the diagnostics once done, deprecated in favor of `events`
+	Aggregate,
+}
+
+/// the version of `events.schema.json`, on every line so consumers can reject versions they don't know
+const EVENTS_SCHEMA: u32 = 7;
+
+/// a line printed to stderr for `--json-format events`
+#[derive(serde::Serialize)]
+struct EventLine<'a> {
+	schema: u32,
+	#[serde(flatten)]
+	event: Event<'a>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum Event<'a> {
+	/// a file started being processed
+	FileStart { file: &'a str },
+	/// a diagnostic of a file
+	Diagnostic {
+		file: &'a str,
+		/// whether it was reported while parsing, in a pass, or while emitting, the same as in the diagnostic
+		phase: Phase,
+		diagnostic: diagnostics::Structured,
+	},
+	/// a file is done
+	FileEnd {
+		file: &'a str,
+		ok: bool,
+		duration_ms: u128,
+		suppressed: usize,
+		/// see [`markdoll::SourceStats`]
+		buffers: usize,
+		source_bytes: usize,
+		/// see [`MarkDoll::stopped_early`]
+		stopped_early: bool,
+	},
+	/// every file is done
+	Summary {
+		files: usize,
+		failed: usize,
+		errors: usize,
+		warnings: usize,
+		documents: Vec<DocumentStats<'a>>,
+		/// see [`diagnostics::summarize`]
+		codes: Vec<CodeStats<'a>>,
+	},
+}
+
+/// the statistics of a file in [`Event::Summary`], see [`tree::DocumentMeta`]
+#[derive(serde::Serialize)]
+struct DocumentStats<'a> {
+	file: &'a str,
+	words: usize,
+	headings: usize,
+	images: usize,
+	links: usize,
+	reading_minutes: usize,
+}
+
+/// how many diagnostics of a code there were in [`Event::Summary`], see [`diagnostics::CodeSummary`]
+#[derive(serde::Serialize)]
+struct CodeStats<'a> {
+	code: &'static str,
+	severity: diagnostics::Severity,
+	phase: Phase,
+	count: usize,
+	/// where the first was
+	file: &'a str,
+	line: usize,
+	col: usize,
+}
+
+impl Event<'_> {
+	fn print(self) {
+		eprintln!(
+			"{}",
+			serde_json::to_string(&EventLine {
+				schema: EVENTS_SCHEMA,
+				event: self,
+			})
+			.unwrap()
+		);
+	}
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
 	/// check the provided stdin and print any parsing errors
-	Check,
+	Check {
+		/// print how many times each tag is used to stdout, marking undefined tags, followed by the tags that are never used
+		#[arg(long)]
+		report_tags: bool,
+
+		/// print every `todo` tag to stdout, with its line and column
+		#[arg(long)]
+		list_todos: bool,
+
+		/// emit the document too, throwing the output away, to also report the diagnostics only emitting finds, such as images without alt text
+		#[arg(long, value_name = "TARGET", num_args = 0..=1, default_missing_value = "html")]
+		emit: Option<EmitTarget>,
+
+		/// how to emit with `--emit`
+		#[command(flatten)]
+		emit_args: EmitArgs,
+	},
 	/// convert the provided stdin to html and output to stdout
-	Convert,
+	Convert {
+		#[command(flatten)]
+		emit: EmitArgs,
+
+		/// write the files the document depends on to this path as a makefile rule, for the target named by the path without its `.d` extension
+		#[arg(long, value_name = "PATH")]
+		depfile: Option<std::path::PathBuf>,
+
+		/// write markdoll's default stylesheet in a `<style>` element before the document
+		#[arg(long)]
+		default_css: bool,
+
+		/// write the output even if parsing or emitting fails, still exiting with an error, such as for a preview
+		///
+		/// the output is well-formed html of everything that parsed, up to where parsing stopped, with what failed left out
+		#[arg(long)]
+		idc: bool,
+
+		/// write a comment naming the version of markdoll, the profile, and a hash of the emit options before the document, so diffs of output can tell changes to markdoll from changes to the document
+		#[arg(long, overrides_with = "no_stamp")]
+		stamp: bool,
+
+		/// don't write the comment `--stamp` writes, the default, so output only changes when the document does
+		#[arg(long)]
+		no_stamp: bool,
+	},
+	/// print the title, frontmatter, outline, and word count of the provided stdin as json to stdout
+	Meta,
+	/// print what causes a diagnostic code and how to address it, with an example, without reading stdin
+	Explain {
+		/// the code, such as `tag not defined`
+		code: String,
+	},
+	/// print each file back to markdoll and parse it again, reporting where it parses differently, reports diagnostics the file doesn't, or changes when printed again, without reading stdin
+	///
+	/// exits with an error if any file doesn't round-trip, such as to check the printer against a tree of documents in CI
+	Roundtrip {
+		/// the files to check
+		#[arg(required = true)]
+		files: Vec<std::path::PathBuf>,
+	},
 }
 
-fn main() {
-	env_logger::init();
+/// see [`Command::Check::emit`]
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum EmitTarget {
+	/// [`HtmlEmit`], as `convert` emits
+	Html,
+}
 
-	let args = Cli::parse();
+/// how to emit, shared by `convert` and `check --emit`
+#[derive(clap::Args, Debug)]
+struct EmitArgs {
+	/// highlight code blocks with syntect, using the given theme
+	#[cfg(feature = "highlight-syntect")]
+	#[arg(long, value_name = "THEME", num_args = 0..=1, default_missing_value = "InspiredGitHub")]
+	highlight: Option<String>,
+
+	/// define a variable for `var` tags, overriding the document's frontmatter
+	#[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+	vars: Vec<(String, String)>,
+
+	/// set a flag for `if` tags
+	#[arg(long = "define", value_name = "FLAG")]
+	flags: Vec<String>,
+
+	/// resolve `cite` tags against this CSL-JSON bibliography
+	#[arg(long, value_name = "PATH")]
+	bibliography: Option<std::path::PathBuf>,
+
+	/// number citations rather than writing them as author and year
+	#[arg(long, requires = "bibliography")]
+	numeric_citations: bool,
+
+	/// number sections, such as `2.3.1`, up to this depth
+	#[arg(long, value_name = "DEPTH")]
+	number_sections: Option<usize>,
+
+	/// curl quotes in the style of `english`, `german`, or `french`, and write dashes and ellipses for `--`, `---`, and `...`, unless the frontmatter has `typography: off`
+	///
+	/// without a style, uses the style of the frontmatter's `lang`, or `english`
+	#[arg(long, value_name = "STYLE", value_parser = parse_quote_style)]
+	smart_punctuation: Option<Option<tree::QuoteStyle>>,
 
-	let mut src = String::new();
+	/// keep spaces around punctuation from breaking in the style of `french` or `canadian-french`, unless the frontmatter has `typography: off`
+	///
+	/// without a style, uses the style of the frontmatter's `lang`, if it has one
+	#[arg(long, value_name = "STYLE", value_parser = parse_spacing_style)]
+	spacing: Option<Option<tree::SpacingStyle>>,
+
+	/// highlight `todo` tags and show comments, for previews
+	#[arg(long, conflicts_with = "release")]
+	draft: bool,
+
+	/// fail on `todo` tags, rather than leaving them out
+	#[arg(long)]
+	release: bool,
+
+	/// close void elements in html style, such as `<br>`, rather than xml style, such as `<br />`
+	#[arg(long)]
+	html_style: bool,
+
+	/// look up local `img` sources in this directory, warning of missing ones and writing the dimensions of png, jpeg, gif, and webp images
+	#[arg(long, value_name = "DIR")]
+	asset_root: Option<std::path::PathBuf>,
+
+	/// copy the local files links, images, and media refer to into `--asset-out`, named by a hash of their content, and point their urls at the copies
+	///
+	/// files are found relative to `--asset-root`, or the current directory. urls with a scheme are left as they are, and files that can't be copied are warned of
+	#[arg(long, requires = "asset_out")]
+	rewrite_assets: bool,
+
+	/// the directory `--rewrite-assets` copies files into, which urls are written relative to
+	#[arg(long, value_name = "DIR", requires = "rewrite_assets")]
+	asset_out: Option<std::path::PathBuf>,
+}
 
+fn parse_var(arg: &str) -> Result<(String, String), String> {
+	arg.split_once('=')
+		.map(|(key, value)| (key.to_string(), value.to_string()))
+		.ok_or_else(|| format!("expected KEY=VALUE, found `{arg}`"))
+}
+
+fn parse_size(arg: &str) -> Result<u64, String> {
+	let (digits, unit) = match arg.char_indices().last() {
+		Some((at, 'K' | 'k')) => (&arg[..at], 1 << 10),
+		Some((at, 'M' | 'm')) => (&arg[..at], 1 << 20),
+		Some((at, 'G' | 'g')) => (&arg[..at], 1 << 30),
+		_ => (arg, 1),
+	};
+
+	digits
+		.parse::<u64>()
+		.ok()
+		.and_then(|size| size.checked_mul(unit))
+		.ok_or_else(|| format!("expected a size such as 4096, 512K, or 1G, found `{arg}`"))
+}
+
+/// read all of stdin into one buffer, refusing more than `max` bytes or invalid UTF-8
+fn read_stdin(max: u64) -> Result<String, String> {
+	let mut bytes = Vec::new();
 	std::io::stdin()
-		.read_to_string(&mut src)
-		.expect("failed to read stdin");
+		.lock()
+		.take(max.saturating_add(1))
+		.read_to_end(&mut bytes)
+		.map_err(|err| format!("failed to read stdin: {err}"))?;
 
-	let mut doll = MarkDoll::new();
+	if bytes.len() as u64 > max {
+		return Err(format!(
+			"input is larger than {max} bytes, raise the limit with `--max-input-size`"
+		));
+	}
+
+	String::from_utf8(bytes).map_err(|err| {
+		let at = err.utf8_error().valid_up_to();
+		// everything before it is valid
+		let before = std::str::from_utf8(&err.as_bytes()[..at]).unwrap();
+		let line = before.matches('\n').count() + 1;
+		let col = before[before.rfind('\n').map_or(0, |at| at + 1)..]
+			.chars()
+			.count() + 1;
+
+		format!("input is not valid UTF-8 at byte {at}, line {line}, column {col}")
+	})
+}
+
+fn parse_quote_style(arg: &str) -> Result<tree::QuoteStyle, String> {
+	match arg {
+		"english" => Ok(tree::QuoteStyle::English),
+		"german" => Ok(tree::QuoteStyle::German),
+		"french" => Ok(tree::QuoteStyle::French),
+		_ => Err(format!(
+			"expected english, german, or french, found `{arg}`"
+		)),
+	}
+}
+
+fn parse_spacing_style(arg: &str) -> Result<tree::SpacingStyle, String> {
+	match arg {
+		"french" => Ok(tree::SpacingStyle::French),
+		"canadian-french" => Ok(tree::SpacingStyle::CanadianFrench),
+		_ => Err(format!("expected french or canadian-french, found `{arg}`")),
+	}
+}
+
+/// print the percentage done to stderr whenever it changes, clearing it once a phase is done
+fn print_progress(progress: Progress) {
+	static LAST: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+	let (phase, index) = match progress.phase {
+		Phase::Parse => ("parsing", 0),
+		Phase::Pass(name) => (name, 1),
+		Phase::Emit => ("emitting", 2),
+	};
+	let percent = progress.done * 100 / progress.total.max(1);
+	let key = index * 101 + percent;
+
+	if LAST.swap(key, Ordering::Relaxed) != key {
+		if progress.done >= progress.total {
+			eprint!("\r\x1b[2K");
+		} else {
+			eprint!("\r{phase} {percent}%");
+		}
+	}
+}
+
+/// prepare a document to be emitted as the options ask, returning the target to emit it into
+fn html_emit(
+	doll: &MarkDoll,
+	frontmatter: Option<&str>,
+	ast: &mut tree::AST,
+	args: EmitArgs,
+) -> HtmlEmit {
+	let EmitArgs {
+		#[cfg(feature = "highlight-syntect")]
+		highlight,
+		vars,
+		flags,
+		bibliography,
+		numeric_citations,
+		number_sections,
+		smart_punctuation,
+		spacing,
+		draft,
+		release,
+		html_style,
+		asset_root,
+		rewrite_assets,
+		asset_out,
+	} = args;
+
+	ext::index::collect(doll, ast);
+	ext::citation::collect(doll, ast);
+
+	let lang = frontmatter.and_then(ext::formatting::lang::frontmatter_lang);
+
+	if !frontmatter.is_some_and(tree::skip_typography) {
+		if let Some(style) = smart_punctuation {
+			let style = style
+				.or_else(|| lang.and_then(tree::QuoteStyle::for_locale))
+				.unwrap_or(tree::QuoteStyle::English);
+			tree::smart_punctuation(doll, ast, style);
+		}
+
+		if let Some(style) = spacing
+			.and_then(|style| style.or_else(|| lang.and_then(tree::SpacingStyle::for_locale)))
+		{
+			tree::typographic_spacing(doll, ast, style);
+		}
+	}
+
+	if let Some(max_depth) = number_sections {
+		tree::number_sections(
+			ast,
+			&tree::NumberingOptions {
+				max_depth,
+				..Default::default()
+			},
+		);
+	}
+
+	let bibliography = bibliography.map(|path| {
+		let mut bibliography: ext::citation::Bibliography = match std::fs::read_to_string(&path)
+			.map_err(|err| format!("failed to read bibliography {}: {err}", path.display()))
+			.and_then(|src| {
+				serde_json::from_str(&src).map_err(|err| {
+					format!("failed to parse bibliography {}: {err}", path.display())
+				})
+			}) {
+			Ok(bibliography) => bibliography,
+			Err(err) => {
+				eprintln!("{err}");
+				std::process::exit(1);
+			}
+		};
+
+		if numeric_citations {
+			bibliography.style = ext::citation::Style::Numeric;
+		}
+
+		Arc::new(bibliography)
+	});
+
+	#[allow(unused_mut, reason = "only reassigned with highlight-syntect")]
+	let mut code_block_format: Arc<CodeBlockFormatter> = Arc::new(|_, _, _, _| {});
+	#[allow(unused_mut, reason = "only reassigned with highlight-syntect")]
+	let mut code_block_format_name = None;
+
+	#[cfg(feature = "highlight-syntect")]
+	if let Some(theme) = highlight {
+		code_block_format = Arc::new(ext::code::syntect_highlighter(&theme));
+		code_block_format_name = Some(format!("syntect:{theme}"));
+	}
+
+	let mut vars = vars.into_iter().collect();
+	if let Some(frontmatter) = frontmatter {
+		ext::var::frontmatter_vars(&mut vars, frontmatter);
+	}
+
+	HtmlEmit {
+		code_block_format,
+		code_block_format_name,
+		// honor SOURCE_DATE_EPOCH so relative times are reproducible
+		time_reference: source_date_epoch().or_else(|| {
+			std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.ok()
+				.and_then(|now| i64::try_from(now.as_secs()).ok())
+		}),
+		vars,
+		flags: flags.into_iter().collect(),
+		bibliography,
+		draft_mode: if draft {
+			ext::todo::DraftPolicy::Show
+		} else if release {
+			ext::todo::DraftPolicy::Error
+		} else {
+			ext::todo::DraftPolicy::Hide
+		},
+		asset_resolver: asset_root.clone().map(emit::fs_resolver),
+		strings: lang.map_or_else(Strings::default, |lang| Strings {
+			locale: lang.to_string().into(),
+			..Default::default()
+		}),
+		xml_style: !html_style,
+		url_rewriter: asset_out
+			.filter(|_| rewrite_assets)
+			.map(|out| emit::fs_copier(asset_root.unwrap_or_else(|| ".".into()), out)),
+		..Default::default()
+	}
+}
+
+/// the time SOURCE_DATE_EPOCH pins output to, if it is set
+fn source_date_epoch() -> Option<i64> {
+	std::env::var("SOURCE_DATE_EPOCH")
+		.ok()
+		.and_then(|epoch| epoch.parse().ok())
+}
+
+/// add every standard tag
+fn add_tags(doll: &mut MarkDoll) {
+	doll.ext_system.add_tags(ext::citation::tags());
 	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::conditional::tags());
 	doll.ext_system.add_tags(ext::formatting::tags());
 	doll.ext_system.add_tags(ext::code::tags());
+	doll.ext_system.add_tags(ext::datetime::tags());
+	doll.ext_system.add_tags(ext::diagram::tags());
+	doll.ext_system.add_tags(ext::emoji::tags());
+	doll.ext_system.add_tags(ext::glossary::tags());
+	doll.ext_system.add_tags(ext::index::tags());
+	doll.ext_system.add_tags(ext::layout::tags());
 	doll.ext_system.add_tags(ext::links::tags());
+	doll.ext_system.add_tags(ext::math::tags());
+	doll.ext_system.add_tags(ext::media::tags());
+	doll.ext_system.add_tags(ext::page::tags());
+	#[cfg(feature = "qr")]
+	doll.ext_system.add_tags(ext::qr::tags());
 	doll.ext_system.add_tags(ext::table::tags());
+	doll.ext_system.add_tags(ext::tabs::tags());
+	doll.ext_system.add_tags(ext::todo::tags());
+	doll.ext_system.add_tags(ext::var::tags());
+}
+
+/// the line `at` is on in `src`, with its line and column
+fn line_at(src: &str, at: usize) -> (diagnostics::LineCol, &str) {
+	let pos = diagnostics::LineIndex::new(src).line_col(at);
+	(pos, src.lines().nth(pos.line - 1).unwrap_or_default())
+}
+
+/// check that a file round-trips through the printer, as `roundtrip` asks, printing what doesn't to stderr and returning whether it does
+fn roundtrip(doll: &mut MarkDoll, file: &str, src: &str) -> bool {
+	let options = print::PrintOptions::default();
+
+	let (frontmatter, ast) = match doll.parse_document(src) {
+		Ok(res) => res,
+		Err(_) => {
+			doll.finish();
+			eprintln!("{file}: doesn't parse, run `markdoll check` on it");
+			return false;
+		}
+	};
+	let printed = print::to_doll_document(doll, frontmatter.as_deref(), &ast, options);
+	let mut codes = doll
+		.finish()
+		.into_iter()
+		.map(|diag| diag.code)
+		.collect::<Vec<_>>();
+
+	let (reparsed_frontmatter, reparsed) = match doll.parse_document(&*printed) {
+		Ok(res) | Err(res) => res,
+	};
+	let difference = print::first_difference(doll, &ast, &reparsed, options);
+	let reprinted =
+		print::to_doll_document(doll, reparsed_frontmatter.as_deref(), &reparsed, options);
+	// those the original reports as many times are expected
+	let introduced = doll
+		.finish()
+		.into_iter()
+		.filter(
+			|diag| match codes.iter().position(|code| *code == diag.code) {
+				Some(index) => {
+					codes.swap_remove(index);
+					false
+				}
+				None => true,
+			},
+		)
+		.collect::<Vec<_>>();
+
+	let mut ok = true;
+
+	if reparsed_frontmatter != frontmatter {
+		eprintln!("{file}: the printed frontmatter parses differently");
+		ok = false;
+	}
+
+	if let Some(difference) = difference {
+		eprintln!(
+			"{file}: the printed form parses differently at {}",
+			difference.path
+		);
+		for (name, node) in ["original", "printed"].into_iter().zip(&difference.nodes) {
+			eprintln!("\t{name}: {}", node.as_deref().unwrap_or("nothing"));
+		}
+		for (name, (src, at)) in ["original", "printed"]
+			.into_iter()
+			.zip([(src, difference.at[0]), (&*printed, difference.at[1])])
+		{
+			let (pos, line) = line_at(src, at);
+			eprintln!("\t{name}, line {}: {line}", pos.line);
+		}
+		ok = false;
+	}
+
+	for diag in introduced {
+		let (pos, line) = line_at(&printed, diag.at);
+		eprintln!(
+			"{file}: the printed form reports `{}` at {}:{}, which the original doesn't",
+			diag.code, pos.line, pos.col
+		);
+		eprintln!("\tprinted, line {}: {line}", pos.line);
+		ok = false;
+	}
+
+	if reprinted != printed {
+		let (line, (once, twice)) = printed
+			.lines()
+			.chain(core::iter::repeat(""))
+			.zip(reprinted.lines().chain(core::iter::repeat("")))
+			.enumerate()
+			.find(|(_, (once, twice))| once != twice)
+			.unwrap_or_default();
+		eprintln!(
+			"{file}: printing the printed form changes it at line {}",
+			line + 1
+		);
+		eprintln!("\tprinted once: {once}");
+		eprintln!("\tprinted twice: {twice}");
+		ok = false;
+	}
+
+	ok
+}
+
+fn main() {
+	let args = Cli::parse();
+
+	// log lines would break up the json printed to stderr, unless asked for
+	if !args.json || std::env::var_os("RUST_LOG").is_some() {
+		env_logger::init();
+	}
+
+	if let Command::Explain { code } = &args.command {
+		let Some(entry) = diagnostics::explain(code) else {
+			eprintln!("no diagnostic has the code `{code}`");
+			std::process::exit(1);
+		};
+
+		if args.json {
+			println!("{}", serde_json::to_string(entry).unwrap());
+		} else {
+			println!("{}\n\n{}", entry.code, entry.explanation);
+
+			if !entry.example.is_empty() {
+				println!("\nexample:\n\n{}", entry.example);
+			}
+
+			for (name, profile) in [
+				("strict", diagnostics::Profile::Strict),
+				("relaxed", diagnostics::Profile::Relaxed),
+			] {
+				if let Some((_, severity)) = profile
+					.overrides()
+					.iter()
+					.find(|(code, _)| entry.matches(code))
+				{
+					let severity = match severity {
+						Some(true) => "an error",
+						Some(false) => "a warning",
+						None => "suppressed",
+					};
+
+					println!("\nwith --{name}, this is {severity}");
+				}
+			}
+		}
+
+		return;
+	}
+
+	if let Command::Roundtrip { files } = &args.command {
+		let mut doll = MarkDoll::new();
+		add_tags(&mut doll);
+		// comments are part of the source the printer must keep
+		doll.keep_comments = true;
+
+		let mut failed = 0;
+		for path in files {
+			let file = path.display().to_string();
+
+			let src = match std::fs::read_to_string(path) {
+				Ok(src) if src.len() as u64 > args.max_input_size => Err(format!(
+					"input is larger than {} bytes, raise the limit with `--max-input-size`",
+					args.max_input_size
+				)),
+				Ok(src) => Ok(src),
+				Err(err) => Err(err.to_string()),
+			};
+
+			let ok = match src {
+				Ok(src) => roundtrip(&mut doll, &file, &src),
+				Err(err) => {
+					eprintln!("{file}: {err}");
+					false
+				}
+			};
+
+			if !ok {
+				failed += 1;
+			}
+		}
+
+		if failed > 0 {
+			eprintln!("{failed} of {} files don't round-trip", files.len());
+			std::process::exit(1);
+		}
+
+		return;
+	}
+
+	// stdin is the only file
+	let file = "-";
+	let events = args.json && args.json_format == JsonFormat::Events;
+	let start = std::time::Instant::now();
+
+	let src = match read_stdin(args.max_input_size) {
+		Ok(src) => Arc::<str>::from(src),
+		Err(err) => {
+			eprintln!("{err}");
+			std::process::exit(1);
+		}
+	};
+
+	if events {
+		Event::FileStart { file }.print();
+	}
+
+	let mut doll = MarkDoll::new();
+	let profile = if args.strict {
+		diagnostics::Profile::Strict
+	} else if args.relaxed {
+		diagnostics::Profile::Relaxed
+	} else {
+		diagnostics::Profile::Default
+	};
+	doll.set_profile(profile);
+	for code in args.allow {
+		doll.severity_overrides.insert(code, None);
+	}
+	for code in args.deny {
+		doll.severity_overrides.insert(code, Some(true));
+	}
+	doll.fail_fast = args.fail_fast;
+	// events are printed to stderr too, so progress would break their lines
+	if !args.no_status && !args.json && std::io::stderr().is_terminal() {
+		doll.progress = Some(print_progress);
+	}
+	add_tags(&mut doll);
 	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	// comments are only kept to be shown, so other output stays as it was
+	doll.keep_comments = matches!(
+		&args.command,
+		Command::Convert {
+			emit: EmitArgs { draft: true, .. },
+			..
+		} | Command::Check {
+			emit_args: EmitArgs { draft: true, .. },
+			..
+		}
+	);
 
 	log::info!("parse");
 
 	let mut ok = true;
+	let report_tags = matches!(
+		args.command,
+		Command::Check {
+			report_tags: true,
+			..
+		}
+	);
+
+	let idc = matches!(args.command, Command::Convert { idc: true, .. });
 
-	match doll.parse_document(&src) {
-		Ok((_, mut ast)) => match args.command {
-			Command::Check => {
-				log::info!("parse succeeded")
+	let (parsed, (frontmatter, mut ast)) = match doll.parse_document(Arc::clone(&src)) {
+		Ok(res) => (true, res),
+		Err(res) => {
+			log::error!("parse failed");
+			ok = false;
+			(false, res)
+		}
+	};
+
+	let thresholds = tree::Thresholds {
+		min_words: args.min_words,
+		require_headings: args.require_headings,
+	};
+	let meta = (events || thresholds != tree::Thresholds::default())
+		.then(|| tree::analyze(&doll, frontmatter.as_deref(), &ast));
+	if let Some(meta) = &meta {
+		if !tree::check_thresholds(&mut doll, meta, thresholds) {
+			ok = false;
+		}
+	}
+
+	if parsed || idc {
+		match args.command {
+			Command::Check {
+				list_todos,
+				emit,
+				emit_args,
+				..
+			} => {
+				log::info!("parse succeeded");
+
+				if list_todos {
+					let lines = diagnostics::LineIndex::new(&src);
+
+					for todo in ext::todo::list(&doll, &ast) {
+						let pos = lines.line_col(todo.at);
+
+						match todo.assignee {
+							Some(assignee) => {
+								println!("{}:{}\t{assignee}\t{}", pos.line, pos.col, todo.text);
+							}
+							None => println!("{}:{}\t\t{}", pos.line, pos.col, todo.text),
+						}
+					}
+				}
+
+				if let Some(EmitTarget::Html) = emit {
+					log::info!("emitting");
+
+					let mut out = html_emit(&doll, frontmatter.as_deref(), &mut ast, emit_args);
+					// the output is thrown away, so nothing it refers to is copied
+					out.url_rewriter = None;
+					if !doll.emit(&mut ast, &mut out) {
+						log::error!("emit failed");
+						ok = false;
+					}
+				}
 			}
-			Command::Convert => {
+			Command::Meta => {
+				let meta = tree::analyze(&doll, frontmatter.as_deref(), &ast);
+
+				println!("{}", serde_json::to_string(&meta).unwrap());
+			}
+			Command::Explain { .. } => unreachable!("explained before reading stdin"),
+			Command::Roundtrip { .. } => unreachable!("checked before reading stdin"),
+			Command::Convert {
+				emit,
+				depfile,
+				default_css,
+				stamp,
+				..
+			} => {
 				log::info!("emitting");
 
-				let mut out = HtmlEmit {
-					write: String::new(),
-					section_level: 0,
-					code_block_format: Rc::new(|_, _, _, _| {}),
-				};
+				let asset_root = emit.asset_root.clone().unwrap_or_default();
+				let mut out = html_emit(&doll, frontmatter.as_deref(), &mut ast, emit);
+
+				let emitted = doll.emit(&mut ast, &mut out);
+				if !emitted {
+					log::error!("emit failed");
+					ok = false;
+				}
 
-				if doll.emit(&mut ast, &mut out) {
+				// after emitting, which records the assets copied by `--rewrite-assets`
+				if let Some(path) = depfile {
+					// the files assets are read from, rather than the urls the document wrote
+					for dependency in doll.dependencies_mut() {
+						if dependency.kind == dependencies::DependencyKind::Asset
+							&& dependencies::is_local(&dependency.path)
+						{
+							if let Some(file) = emit::fs_asset_path(&asset_root, &dependency.path) {
+								dependency.path = file.to_string_lossy().into_owned();
+							}
+						}
+					}
+
+					let target = if path.extension().is_some_and(|ext| ext == "d") {
+						path.with_extension("")
+					} else {
+						path.clone()
+					};
+
+					if let Err(err) = std::fs::write(
+						&path,
+						dependencies::depfile(&target.to_string_lossy(), doll.dependencies()),
+					) {
+						eprintln!("failed to write depfile {}: {err}", path.display());
+						std::process::exit(1);
+					}
+				}
+
+				if emitted || idc {
 					log::info!("output written to stdout");
 
+					if stamp {
+						// the wall clock would change the hash on every run, so only a reference pinned by SOURCE_DATE_EPOCH counts
+						if source_date_epoch().is_none() {
+							out.time_reference = None;
+						}
+						println!("{}", out.stamp(profile));
+					}
+					if default_css {
+						print!("<style>{}</style>", emit::DEFAULT_CSS);
+					}
 					print!("{}", out.write);
-				} else {
-					log::error!("emit failed");
-					ok = false;
 				}
 			}
-		},
-		Err(_) => {
-			log::error!("parse failed");
-			ok = false;
 		}
 	}
 
+	if report_tags {
+		let mut usage = doll.total_tag_usage().iter().collect::<Vec<_>>();
+		usage.sort_unstable_by(|(a_tag, a), (b_tag, b)| b.cmp(a).then(a_tag.cmp(b_tag)));
+
+		for (tag, count) in usage {
+			if doll.ext_system.get_tag(tag).is_some() {
+				println!("{count}\t{tag}");
+			} else {
+				println!("{count}\t{tag} (not defined)");
+			}
+		}
+
+		println!("unused: {}", doll.unused_tags().join(", "));
+	}
+
 	log::info!("diagnostics");
 
-	let mut cache = ariadne::Source::from(&src);
+	let suppressed = doll.suppressed;
+	let stats = doll.source_stats();
+	log::info!(
+		"kept {} buffers of source, {} bytes",
+		stats.buffers,
+		stats.bytes
+	);
+	let stopped_early = doll.stopped_early();
+	if stopped_early {
+		log::warn!(
+			"stopped parsing at the first error after {}ms, the rest of the document wasn't checked",
+			start.elapsed().as_millis()
+		);
+	}
+	let diagnostics = doll.finish_collapsed();
+	let summary = diagnostics::summarize(&diagnostics);
+
+	if events {
+		let lines = diagnostics::LineIndex::new(&src);
+
+		for diag in &diagnostics {
+			Event::Diagnostic {
+				file,
+				phase: diag.phase,
+				diagnostic: diag.to_structured(&lines),
+			}
+			.print();
+		}
+
+		Event::FileEnd {
+			file,
+			ok,
+			duration_ms: start.elapsed().as_millis(),
+			suppressed,
+			buffers: stats.buffers,
+			source_bytes: stats.bytes,
+			stopped_early,
+		}
+		.print();
+
+		// counting those collapsed into others
+		let count = |severity| {
+			summary
+				.iter()
+				.filter(|entry| entry.severity == severity)
+				.map(|entry| entry.count)
+				.sum()
+		};
+		Event::Summary {
+			files: 1,
+			failed: usize::from(!ok),
+			errors: count(diagnostics::Severity::Error),
+			warnings: count(diagnostics::Severity::Warning),
+			documents: meta
+				.iter()
+				.map(|meta| DocumentStats {
+					file,
+					words: meta.words,
+					headings: meta.outline.len(),
+					images: meta.images,
+					links: meta.links,
+					reading_minutes: meta.reading_minutes(),
+				})
+				.collect(),
+			codes: summary
+				.iter()
+				.map(|entry| {
+					let pos = lines.line_col(entry.first);
+
+					CodeStats {
+						code: entry.code,
+						severity: entry.severity,
+						phase: entry.phase,
+						count: entry.count,
+						file,
+						line: pos.line,
+						col: pos.col,
+					}
+				})
+				.collect(),
+		}
+		.print();
+	} else if args.json {
+		let lines = diagnostics::LineIndex::new(&src);
+		let structured = diagnostics
+			.iter()
+			.map(|diag| diag.to_structured(&lines))
+			.collect::<Vec<_>>();
+
+		eprintln!("{}", serde_json::to_string(&structured).unwrap());
+	} else {
+		diagnostics::render_to(
+			std::io::stderr(),
+			&diagnostics,
+			&src,
+			diagnostics::RenderOptions {
+				color: std::io::stderr().is_terminal(),
+				..Default::default()
+			},
+		)
+		.unwrap();
+
+		if !summary.is_empty() {
+			let lines = diagnostics::LineIndex::new(&src);
+
+			for entry in &summary {
+				let pos = lines.line_col(entry.first);
+				let phase = match entry.phase {
+					Phase::Parse => String::new(),
+					Phase::Pass(name) => format!(" in the {name} pass"),
+					Phase::Emit => " while emitting".to_string(),
+				};
+				eprintln!(
+					"{} \u{d7} {}{phase}, first at {}:{}",
+					entry.count, entry.code, pos.line, pos.col
+				);
+			}
 
-	for report in diagnostics::render(&doll.finish()) {
-		report.eprint(&mut cache).unwrap();
+			eprintln!("run `markdoll explain CODE` for details");
+		}
+
+		if suppressed > 0 {
+			eprintln!("{suppressed} suppressed");
+		}
 	}
 
 	if ok {
@@ -0,0 +1,221 @@
+use {
+	crate::{
+		diagnostics::Diagnostic,
+		ext::links::{self, reference::Reference, LinkKind},
+		tree::{
+			visit::{self, VisitorMut},
+			TagInvocation, AST,
+		},
+		MarkDoll, SourceState,
+	},
+	alloc::{string::String, sync::Arc, vec::Vec},
+	hashbrown::HashMap,
+};
+
+/// a document in a [`Project`]
+#[derive(Debug)]
+pub struct Document {
+	/// the name of the document, which HTML links to other documents use as `<name>.html`
+	pub name: String,
+	/// the source of the document
+	pub src: Arc<str>,
+	/// the frontmatter of the document
+	pub frontmatter: Option<String>,
+	/// the document
+	pub ast: AST,
+	/// the `ref` ids used in the document, and the positions of their arguments
+	refs: Vec<(String, usize)>,
+	/// the state of the document's source, while it isn't being used
+	state: SourceState,
+}
+
+/// where a `def` id is defined within a [`Project`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Definition {
+	/// the index of the defining document
+	pub document: usize,
+	/// the position of the `def`'s argument in the document's source
+	pub at: usize,
+}
+
+/// a set of named documents, whose `ref` tags can reference `def` tags in any of them
+///
+/// 1. [`add`](Self::add) every document
+/// 2. [`link`](Self::link) them, resolving references between them
+/// 3. [`emit`](Self::emit) each document
+/// 4. [`finish`](Self::finish), collecting the diagnostics of each document
+///
+/// each document keeps its own diagnostics, positioned within its own source
+#[derive(Debug, Default)]
+pub struct Project {
+	documents: Vec<Document>,
+	definitions: HashMap<String, Definition>,
+}
+
+impl Project {
+	/// create an empty project
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// the documents added
+	#[must_use]
+	pub fn documents(&self) -> &[Document] {
+		&self.documents
+	}
+
+	/// every `def` id, and where it is first defined
+	#[must_use]
+	pub fn definitions(&self) -> &HashMap<String, Definition> {
+		&self.definitions
+	}
+
+	/// parse a document and add it to the project, returning its index and whether it parsed successfully
+	///
+	/// a `def` id already defined by another document emits an error in both
+	pub fn add(
+		&mut self,
+		doll: &mut MarkDoll,
+		name: impl Into<String>,
+		src: impl Into<Arc<str>>,
+	) -> (usize, bool) {
+		let index = self.documents.len();
+		let src = src.into();
+
+		let mut state = SourceState::new();
+		doll.swap_source_state(&mut state);
+
+		let (mut ok, (frontmatter, ast)) = match doll.parse_document(Arc::clone(&src)) {
+			Ok(res) => (true, res),
+			Err(res) => (false, res),
+		};
+
+		let mut refs = Vec::new();
+		for link in links::extract(doll, &src, &ast) {
+			match link.kind {
+				LinkKind::Reference => refs.push((link.url, link.at)),
+				LinkKind::Definition => match self.definitions.get(&link.url) {
+					Some(existing) if existing.document != index => {
+						doll.diag(true, link.at, "id already defined in another document");
+						ok = false;
+
+						let existing = *existing;
+						doll.swap_source_state(&mut self.documents[existing.document].state);
+						doll.diag(true, existing.at, "id defined again in another document");
+						doll.swap_source_state(&mut self.documents[existing.document].state);
+					}
+					Some(_) => {}
+					None => {
+						self.definitions.insert(
+							link.url,
+							Definition {
+								document: index,
+								at: link.at,
+							},
+						);
+					}
+				},
+				_ => {}
+			}
+		}
+
+		doll.swap_source_state(&mut state);
+
+		self.documents.push(Document {
+			name: name.into(),
+			src,
+			frontmatter,
+			ast,
+			refs,
+			state,
+		});
+
+		(index, ok)
+	}
+
+	/// resolve `ref` tags to the documents defining their ids, returning whether every one was resolved
+	///
+	/// unresolved `ref`s emit an error
+	pub fn link(&mut self, doll: &mut MarkDoll) -> bool {
+		struct Link<'a> {
+			document: usize,
+			documents: &'a [String],
+			definitions: &'a HashMap<String, Definition>,
+		}
+
+		impl VisitorMut for Link<'_> {
+			fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+				if let Some(reference) = tag.content.downcast_mut::<Reference>() {
+					reference.document = self
+						.definitions
+						.get(&reference.id)
+						.filter(|def| def.document != self.document)
+						.map(|def| self.documents[def.document].clone());
+				}
+
+				visit::walk_tag_mut(self, doll, pos, tag);
+			}
+		}
+
+		let names = self
+			.documents
+			.iter()
+			.map(|document| document.name.clone())
+			.collect::<Vec<_>>();
+
+		let mut ok = true;
+
+		for (index, document) in self.documents.iter_mut().enumerate() {
+			doll.swap_source_state(&mut document.state);
+
+			for (id, at) in &document.refs {
+				if !self.definitions.contains_key(id) {
+					doll.diag(true, *at, "id not defined in any document");
+					ok = false;
+				}
+			}
+
+			visit::walk_mut(
+				&mut document.ast,
+				doll,
+				&mut Link {
+					document: index,
+					documents: &names,
+					definitions: &self.definitions,
+				},
+			);
+
+			doll.swap_source_state(&mut document.state);
+		}
+
+		ok
+	}
+
+	/// emit a document to an output, returning true if it was successful
+	pub fn emit<To: 'static>(&mut self, doll: &mut MarkDoll, document: usize, to: &mut To) -> bool {
+		let document = &mut self.documents[document];
+
+		doll.swap_source_state(&mut document.state);
+		let ok = doll.emit(&mut document.ast, to);
+		doll.swap_source_state(&mut document.state);
+
+		ok
+	}
+
+	/// collect the [`finish`](MarkDoll::finish)ed diagnostics of each document, in the order they were added
+	///
+	/// documents must not be emitted afterwards
+	pub fn finish(&mut self, doll: &mut MarkDoll) -> Vec<Vec<Diagnostic>> {
+		self.documents
+			.iter_mut()
+			.map(|document| {
+				doll.swap_source_state(&mut document.state);
+				let diagnostics = doll.finish();
+				doll.swap_source_state(&mut document.state);
+
+				diagnostics
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,231 @@
+use {
+	crate::{
+		diagnostics::{Diagnostic, LineIndex},
+		emit::HtmlEmit,
+		ext::TagDefinition,
+		MarkDoll,
+	},
+	alloc::{
+		format,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::{
+		any::{type_name, TypeId},
+		fmt::Write,
+	},
+	std::{
+		env, fs,
+		path::{Path, PathBuf},
+	},
+};
+
+/// the environment variable that makes the assertions in this module write their golden files instead of comparing against them
+pub const BLESS_VAR: &str = "MARKDOLL_BLESS";
+
+/// parse and emit a fixture to html, returning the output and the diagnostics
+///
+/// the output is emitted even if parsing fails, so golden files show what is recovered from errors
+fn run(doll: &mut MarkDoll, src: &str) -> (String, Vec<Diagnostic>) {
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+
+	let mut out = HtmlEmit::default();
+	doll.emit(&mut ast, &mut out);
+
+	(out.write, doll.finish())
+}
+
+/// the diagnostics of a fixture, one per line, positioned by line and column
+fn format_diagnostics(src: &str, diagnostics: &[Diagnostic]) -> String {
+	let lines = LineIndex::new(src);
+	let mut out = String::new();
+
+	for diag in diagnostics {
+		let pos = lines.line_col(diag.at);
+		write!(
+			out,
+			"{}:{} {}: {}",
+			pos.line,
+			pos.col,
+			if diag.err { "error" } else { "warning" },
+			diag.code
+		)
+		.unwrap();
+
+		if !diag.similar.is_empty() {
+			write!(out, " (and {} similar)", diag.similar.len()).unwrap();
+		}
+
+		out.push('\n');
+	}
+
+	out
+}
+
+/// the lines that differ between two texts, after their common start and end, marked with `-` and `+`
+fn diff(expected: &str, actual: &str) -> String {
+	let expected = expected.lines().collect::<Vec<_>>();
+	let actual = actual.lines().collect::<Vec<_>>();
+
+	let start = expected
+		.iter()
+		.zip(&actual)
+		.take_while(|(a, b)| a == b)
+		.count();
+	let end = expected[start..]
+		.iter()
+		.rev()
+		.zip(actual[start..].iter().rev())
+		.take_while(|(a, b)| a == b)
+		.count();
+
+	let mut out = format!("@@ line {} @@\n", start + 1);
+	for line in &expected[start..expected.len() - end] {
+		writeln!(out, "-{line}").unwrap();
+	}
+	for line in &actual[start..actual.len() - end] {
+		writeln!(out, "+{line}").unwrap();
+	}
+
+	out
+}
+
+/// the golden file for a fixture, named after it in a `snapshots` directory next to it
+fn golden_path(fixture: &Path, extension: &str) -> PathBuf {
+	let mut name = fixture.file_name().unwrap().to_os_string();
+	name.push(".");
+	name.push(extension);
+
+	fixture.with_file_name("snapshots").join(name)
+}
+
+/// compare `actual` to the golden file at `path`, or write it there if [`BLESS_VAR`] is set
+///
+/// `lines` splits both texts into lines for the diff shown on mismatch
+fn assert_golden(path: &Path, actual: &str, lines: fn(&str) -> String) {
+	if env::var_os(BLESS_VAR).is_some() {
+		fs::create_dir_all(path.parent().unwrap())
+			.and_then(|()| fs::write(path, actual))
+			.unwrap_or_else(|err| panic!("failed to write {}: {err}", path.display()));
+		return;
+	}
+
+	let expected = fs::read_to_string(path).unwrap_or_else(|err| {
+		panic!(
+			"failed to read {}: {err}, set {BLESS_VAR} to create it",
+			path.display()
+		)
+	});
+
+	assert!(
+		expected == actual,
+		"output differs from {}, set {BLESS_VAR} to update it\n{}",
+		path.display(),
+		diff(&lines(&expected), &lines(actual))
+	);
+}
+
+/// parse a `.doll` fixture and emit it to html with `doll`, and compare the output to the golden file `snapshots/<fixture>.html` next to it, such as `snapshots/table.doll.html` for `table.doll`
+///
+/// `doll` should have the tags and [`BuiltInEmitters`](crate::emit::BuiltInEmitters) the fixture uses, and its diagnostics are [`finish`](MarkDoll::finish)ed
+///
+/// # panics
+///
+/// if the fixture can't be read, or the output differs from the golden file
+pub fn assert_doll_html(fixture: impl AsRef<Path>, doll: &mut MarkDoll) {
+	let fixture = fixture.as_ref();
+	let src = fs::read_to_string(fixture)
+		.unwrap_or_else(|err| panic!("failed to read {}: {err}", fixture.display()));
+
+	let (html, _) = run(doll, &src);
+
+	// html is usually on one line, so tags are put on their own lines for the diff
+	assert_golden(&golden_path(fixture, "html"), &html, |html| {
+		html.replace("><", ">\n<")
+	});
+}
+
+/// parse a `.doll` fixture and emit it to html with `doll`, and compare its diagnostics to the golden file `snapshots/<fixture>.diagnostics` next to it
+///
+/// each diagnostic is written on its own line as `line:col severity: code`
+///
+/// # panics
+///
+/// if the fixture can't be read, or the diagnostics differ from the golden file
+pub fn assert_doll_diagnostics(fixture: impl AsRef<Path>, doll: &mut MarkDoll) {
+	let fixture = fixture.as_ref();
+	let src = fs::read_to_string(fixture)
+		.unwrap_or_else(|err| panic!("failed to read {}: {err}", fixture.display()));
+
+	let (_, diagnostics) = run(doll, &src);
+
+	assert_golden(
+		&golden_path(fixture, "diagnostics"),
+		&format_diagnostics(&src, &diagnostics),
+		ToString::to_string,
+	);
+}
+
+/// an emit target checked by [`assert_tag_coverage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+	/// the type of the target
+	pub id: TypeId,
+	/// the name of the target, shown when it is missing
+	pub name: &'static str,
+}
+
+impl Target {
+	/// the target `To`
+	#[must_use]
+	pub fn of<To: 'static>() -> Self {
+		Self {
+			id: TypeId::of::<To>(),
+			name: type_name::<To>(),
+		}
+	}
+}
+
+/// check every tag in `tags` has an emitter for every target in `targets`, besides the `(tag, target)` pairs in `allowed`, which are meant to be missing, such as for tags that only make sense for one target
+///
+/// ```
+/// # use markdoll::{emit::HtmlEmit, ext, testing::{assert_tag_coverage, Target}};
+/// assert_tag_coverage(&ext::formatting::tags(), &[Target::of::<HtmlEmit>()], &[]);
+/// ```
+///
+/// # panics
+///
+/// listing every missing pair, and every pair in `allowed` that isn't missing, so the allowlist doesn't outlive the reason for it
+pub fn assert_tag_coverage<'tag>(
+	tags: impl IntoIterator<Item = &'tag TagDefinition>,
+	targets: &[Target],
+	allowed: &[(&str, Target)],
+) {
+	let mut missing = String::new();
+	let mut unneeded = String::new();
+
+	for tag in tags {
+		for target in targets {
+			let emits = tag.emit_targets().any(|(id, _)| id == target.id);
+			let allow = allowed.contains(&(tag.key, *target));
+
+			if !emits && !allow {
+				write!(missing, "\n\t{} to {}", tag.key, target.name).unwrap();
+			} else if emits && allow {
+				write!(unneeded, "\n\t{} to {}", tag.key, target.name).unwrap();
+			}
+		}
+	}
+
+	if !missing.is_empty() {
+		missing.insert_str(0, "\nmissing emitters:");
+	}
+	if !unneeded.is_empty() {
+		unneeded.insert_str(0, "\nallowed but not missing:");
+	}
+
+	assert!(
+		missing.is_empty() && unneeded.is_empty(),
+		"tags can't emit to every target{missing}{unneeded}"
+	);
+}
@@ -0,0 +1,252 @@
+use {
+	crate::{
+		diagnostics::{DiagnosticSink, IndexedSrc, TagDiagnosticTranslation},
+		ext::TagForm,
+		tree::{BlockItem, InlineItem, ListItem, SectionMeta, TagInvocation, AST},
+		MarkDoll,
+	},
+	alloc::{
+		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
+	},
+};
+
+/// builds an [`AST`] from code, without any markdoll source
+///
+/// the text given to the builder is collected into a synthetic source, which tag content is parsed from and diagnostics point into, see [`source`](Self::source)
+///
+/// ```
+/// # use markdoll::{ext, tree::build::Doc, MarkDoll};
+/// let mut doll = MarkDoll::new();
+/// doll.ext_system.add_tags(ext::formatting::tags());
+///
+/// let mut doc = Doc::new(&mut doll);
+/// doc.section("release notes", |doc| {
+///     doc.paragraph(|p| {
+///         p.text("this release is ").tag("em", &[], "great");
+///     });
+/// });
+/// let ast = doc.finish().unwrap();
+/// ```
+///
+/// # note
+///
+/// like parsing, ensure that the `finish` method on the [`MarkDoll`] is called before building another document
+pub struct Doc<'a> {
+	doll: DiagnosticSink<'a>,
+	src: String,
+	/// the length of [`src`](Self::src) in characters, which positions count
+	chars: usize,
+	ast: AST,
+}
+
+impl<'a> Doc<'a> {
+	/// start building a document
+	#[must_use]
+	pub fn new(doll: &'a mut MarkDoll) -> Self {
+		if doll.diagnostic_translations.is_empty() {
+			doll.diagnostic_translations
+				.push(TagDiagnosticTranslation::new(Arc::default(), 0, 0, 0));
+		}
+
+		Self {
+			doll: doll.begin_operation(),
+			src: String::new(),
+			chars: 0,
+			ast: AST::new(),
+		}
+	}
+
+	/// the synthetic source built so far, which diagnostic positions refer to
+	#[must_use]
+	pub fn source(&self) -> &str {
+		&self.src
+	}
+
+	/// finish building
+	///
+	/// # errors
+	///
+	/// if any error diagnostics were emitted while parsing tag content, the resulting [`AST`] may be incomplete
+	pub fn finish(self) -> Result<AST, AST> {
+		let Self {
+			mut doll, src, ast, ..
+		} = self;
+
+		// the tags added have their positions indexed already, so the source is only needed once it is done
+		doll.diagnostic_translations[0] = TagDiagnosticTranslation::new(Arc::from(src), 0, 0, 0);
+		doll.source_chars = None;
+
+		if doll.end() {
+			Ok(ast)
+		} else {
+			Err(ast)
+		}
+	}
+
+	/// add a section, building its content with `build`
+	pub fn section(&mut self, name: &str, build: impl FnOnce(&mut Self)) -> &mut Self {
+		let pos = self.push_src(name);
+
+		let children = self.nested(build);
+		self.ast.push(BlockItem::Section {
+			pos,
+			name: name.to_string(),
+			meta: SectionMeta::default(),
+			children,
+		});
+
+		self
+	}
+
+	/// add a paragraph, building its content with `build`
+	///
+	/// empty paragraphs are not added
+	pub fn paragraph(&mut self, build: impl FnOnce(&mut Paragraph<'_, 'a>)) -> &mut Self {
+		let mut paragraph = Paragraph {
+			doc: self,
+			items: Vec::new(),
+		};
+		build(&mut paragraph);
+
+		let items = paragraph.items;
+		if !items.is_empty() {
+			self.ast.push(BlockItem::Inline(items));
+		}
+
+		self
+	}
+
+	/// add a paragraph of plain text
+	pub fn text(&mut self, text: &str) -> &mut Self {
+		self.paragraph(|paragraph| {
+			paragraph.text(text);
+		})
+	}
+
+	/// add a paragraph containing a single tag, see [`Paragraph::tag`]
+	pub fn tag(&mut self, tag: &str, args: &[&str], content: &str) -> &mut Self {
+		self.paragraph(|paragraph| {
+			paragraph.tag(tag, args, content);
+		})
+	}
+
+	/// add a list, building each item with `items`
+	pub fn list<F: FnOnce(&mut Self)>(
+		&mut self,
+		ordered: bool,
+		items: impl IntoIterator<Item = F>,
+	) -> &mut Self {
+		let pos = self.chars;
+
+		let items = items
+			.into_iter()
+			.map(|item| ListItem {
+				pos: self.chars,
+				value: None,
+				ast: self.nested(item),
+			})
+			.collect();
+		self.ast.push(BlockItem::List {
+			pos,
+			ordered,
+			items,
+		});
+
+		self
+	}
+
+	/// build blocks separately from the current ones
+	fn nested(&mut self, build: impl FnOnce(&mut Self)) -> AST {
+		let outer = core::mem::take(&mut self.ast);
+		build(self);
+		core::mem::replace(&mut self.ast, outer)
+	}
+
+	/// append a line to the synthetic source, returning its position
+	fn push_src(&mut self, text: &str) -> usize {
+		let pos = self.chars;
+
+		self.src.push_str(text);
+		self.src.push('\n');
+		self.chars += text.chars().count() + 1;
+		// positions are kept within the source built so far
+		self.doll.source_chars = Some(self.chars);
+
+		pos
+	}
+}
+
+/// builds the content of a paragraph, see [`Doc::paragraph`]
+pub struct Paragraph<'d, 'a> {
+	doc: &'d mut Doc<'a>,
+	items: Vec<(usize, InlineItem)>,
+}
+
+impl Paragraph<'_, '_> {
+	/// add text
+	pub fn text(&mut self, text: &str) -> &mut Self {
+		let pos = self.doc.push_src(text);
+		self.items.push((pos, InlineItem::Text(text.to_string())));
+		self
+	}
+
+	/// add a line split, which is emitted as a space
+	pub fn split(&mut self) -> &mut Self {
+		let pos = self.doc.chars;
+		self.items.push((pos, InlineItem::Split));
+		self
+	}
+
+	/// add a line break
+	pub fn line_break(&mut self) -> &mut Self {
+		let pos = self.doc.chars;
+		self.items.push((pos, InlineItem::Break));
+		self
+	}
+
+	/// add a tag, parsing its content with the tag's definition as though it were written in markdoll
+	///
+	/// if the tag is not defined, or it doesn't produce content, nothing is added and a diagnostic may be emitted
+	pub fn tag(&mut self, tag: &str, args: &[&str], content: &str) -> &mut Self {
+		let pos = self.doc.push_src(content);
+		let doll: &mut MarkDoll = &mut self.doc.doll;
+
+		// the content is a verbatim line of the source, so its positions are indexed without it
+		doll.diagnostic_translations.push(TagDiagnosticTranslation {
+			indexed: Some(IndexedSrc::verbatim(content.chars().count(), pos)),
+			..TagDiagnosticTranslation::new(content.into(), pos, pos, 0)
+		});
+
+		let parsed = if let Some(def) = doll.ext_system.get_tag(tag).cloned() {
+			if let Some(parse) = def.parse {
+				parse(doll, args.to_vec(), content, TagForm::Inline).map(|content| (def, content))
+			} else {
+				doll.diag(true, usize::MAX, "tag does not support content");
+				None
+			}
+		} else {
+			doll.diag(true, usize::MAX, "tag not defined");
+			None
+		};
+
+		let translation = doll.diagnostic_translations.pop().unwrap();
+
+		if let Some((def, content)) = parsed {
+			self.items.push((
+				pos,
+				InlineItem::Tag(TagInvocation {
+					tag: tag.to_string(),
+					def,
+					args: args.iter().map(ToString::to_string).collect(),
+					content,
+					diagnostic_translation: Some(translation),
+					generation: doll.generation,
+				}),
+			));
+		}
+
+		self
+	}
+}
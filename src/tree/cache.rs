@@ -0,0 +1,142 @@
+use {
+	crate::{
+		dependencies::DependencyKind,
+		ext::{TagCloner, TagForm, TagParser},
+		tree::TagContent,
+		MarkDoll,
+	},
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	hashbrown::HashMap,
+};
+
+/// what parsing the content of a pure tag did besides returning it, done again each time the content is reused
+#[derive(Debug, Clone, Default)]
+struct Effects {
+	/// the tags invoked within the content, counted towards [`MarkDoll::tag_usage`]
+	tags: Vec<String>,
+	/// the dependencies recorded, see [`MarkDoll::record_dependency`]
+	dependencies: Vec<(DependencyKind, String)>,
+}
+
+impl Effects {
+	fn extend(&mut self, other: &Self) {
+		self.tags.extend(other.tags.iter().cloned());
+		self.dependencies.extend(other.dependencies.iter().cloned());
+	}
+}
+
+/// the tag name, arguments, content, and form of an invocation, and whether [`MarkDoll::hard_breaks`] was set, which changes how nested content parses
+type Key = (String, Vec<String>, String, TagForm, bool);
+
+#[derive(Debug)]
+struct Entry {
+	content: Box<dyn TagContent>,
+	effects: Effects,
+}
+
+/// the content of pure tags parsed in the current source, for [`MarkDoll::cache_pure_tags`]
+#[derive(Debug, Default)]
+pub(crate) struct TagCache {
+	entries: HashMap<Key, Entry>,
+	/// the effects of each pure tag being parsed, innermost last
+	recording: Vec<Effects>,
+	/// how many tags that aren't pure have been parsed, so the pure tags containing them aren't cached
+	pub impure: usize,
+}
+
+impl TagCache {
+	/// forget everything, keeping the allocation
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.recording.clear();
+		self.impure = 0;
+	}
+
+	/// note a dependency recorded while parsing, for the pure tags containing it
+	pub fn record_dependency(&mut self, kind: DependencyKind, path: &str) {
+		if let Some(effects) = self.recording.last_mut() {
+			effects.dependencies.push((kind, path.to_string()));
+		}
+	}
+}
+
+/// count an invocation of a tag towards [`MarkDoll::tag_usage`] and [`MarkDoll::total_tag_usage`], and for the pure tags containing it
+pub(crate) fn count_usage(doll: &mut MarkDoll, tag: &str) {
+	*doll.tag_usage.entry_ref(tag).or_default() += 1;
+	*doll.total_tag_usage.entry_ref(tag).or_default() += 1;
+
+	if let Some(effects) = doll.tag_cache.recording.last_mut() {
+		effects.tags.push(tag.to_string());
+	}
+}
+
+/// parse the content of a pure tag, or copy it from an identical invocation parsed before
+///
+/// content is only cached if parsing it reported no diagnostics, suppressed or not, and invoked no tags that aren't pure, so reusing it is the same as parsing it again
+pub(crate) fn parse(
+	doll: &mut MarkDoll,
+	tag: &str,
+	(parse, clone): (TagParser, TagCloner),
+	args: &[String],
+	text: &str,
+	form: TagForm,
+) -> Option<Box<dyn TagContent>> {
+	let key = (
+		tag.to_string(),
+		args.to_vec(),
+		text.to_string(),
+		form,
+		doll.hard_breaks,
+	);
+
+	if let Some(entry) = doll.tag_cache.entries.get(&key) {
+		if let Some(content) = clone(&*entry.content) {
+			let effects = entry.effects.clone();
+
+			for tag in &effects.tags {
+				count_usage(doll, tag);
+			}
+			for (kind, path) in effects.dependencies {
+				doll.record_dependency(kind, path);
+			}
+
+			return Some(content);
+		}
+	}
+
+	let (diagnostics, suppressed, impure) = (
+		doll.diagnostics.len(),
+		doll.suppressed,
+		doll.tag_cache.impure,
+	);
+
+	doll.tag_cache.recording.push(Effects::default());
+	let content = parse(doll, args.iter().map(|arg| &**arg).collect(), text, form);
+	let effects = doll.tag_cache.recording.pop().unwrap();
+
+	// the tag containing this one did all of it too
+	if let Some(outer) = doll.tag_cache.recording.last_mut() {
+		outer.extend(&effects);
+	}
+
+	if doll.diagnostics.len() == diagnostics
+		&& doll.suppressed == suppressed
+		&& doll.tag_cache.impure == impure
+	{
+		if let Some(copy) = content.as_deref().and_then(clone) {
+			doll.tag_cache.entries.insert(
+				key,
+				Entry {
+					content: copy,
+					effects,
+				},
+			);
+		}
+	}
+
+	content
+}
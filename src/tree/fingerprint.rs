@@ -0,0 +1,156 @@
+use {
+	crate::{
+		dependencies::{Dependency, DependencyKind},
+		tree::{BlockItem, InlineItem, TagInvocation, AST},
+	},
+	core::hash::Hasher,
+};
+
+/// 64-bit FNV-1a, which unlike the hashers of `hashbrown` is the same across versions and platforms
+pub(crate) struct Fnv(u64);
+
+impl Default for Fnv {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl Hasher for Fnv {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+}
+
+impl Fnv {
+	/// a number, as the same bytes on every platform
+	fn u64(&mut self, value: u64) {
+		self.write(&value.to_le_bytes());
+	}
+
+	/// a length, as the same width on every platform
+	fn len(&mut self, len: usize) {
+		self.u64(len as u64);
+	}
+
+	/// a string, prefixed by its length so adjacent strings can't run together
+	fn str(&mut self, text: &str) {
+		self.len(text.len());
+		self.write(text.as_bytes());
+	}
+
+	/// the structure and text of blocks, leaving out their positions
+	pub(crate) fn ast(&mut self, ast: &AST) {
+		self.len(ast.len());
+
+		for block in ast {
+			match block {
+				BlockItem::Inline(items) => {
+					self.write_u8(0);
+					self.len(items.len());
+
+					for (_, item) in items {
+						match item {
+							InlineItem::Split => self.write_u8(0),
+							InlineItem::Break => self.write_u8(1),
+							InlineItem::Text(text) => {
+								self.write_u8(2);
+								self.str(text);
+							}
+							InlineItem::Tag(tag) => {
+								self.write_u8(3);
+								self.tag(tag);
+							}
+						}
+					}
+				}
+				BlockItem::Section {
+					name,
+					meta,
+					children,
+					..
+				} => {
+					// sections without props hash as they did before there were any
+					if meta.is_empty() {
+						self.write_u8(1);
+						self.str(name);
+					} else {
+						self.write_u8(3);
+						self.str(name);
+						for prop in [&meta.id, &meta.class] {
+							match prop {
+								Some(prop) => {
+									self.write_u8(1);
+									self.str(prop);
+								}
+								None => self.write_u8(0),
+							}
+						}
+						self.write_u8(u8::from(meta.unlisted));
+					}
+					self.ast(children);
+				}
+				BlockItem::List { ordered, items, .. } => {
+					self.write_u8(2);
+					self.write_u8(u8::from(*ordered));
+					self.len(items.len());
+
+					for item in items {
+						match item.value {
+							Some(value) => {
+								self.write_u8(1);
+								self.u64(value);
+							}
+							None => self.write_u8(0),
+						}
+						self.ast(&item.ast);
+					}
+				}
+			}
+		}
+	}
+
+	/// the name, arguments, and content of a tag as written, which covers any tags nested in it
+	fn tag(&mut self, tag: &TagInvocation) {
+		self.str(&tag.tag);
+
+		self.len(tag.args.len());
+		for arg in &tag.args {
+			self.str(arg);
+		}
+
+		self.str(
+			tag.diagnostic_translation
+				.as_ref()
+				.map_or("", |translation| translation.text()),
+		);
+	}
+
+	/// the kinds, paths, and modification times of dependencies, leaving out their positions
+	pub(crate) fn dependencies(&mut self, dependencies: &[Dependency]) {
+		self.len(dependencies.len());
+
+		for dependency in dependencies {
+			self.write_u8(match dependency.kind {
+				DependencyKind::SourceFile => 0,
+				DependencyKind::Executable => 1,
+				DependencyKind::Asset => 2,
+			});
+			self.str(&dependency.path);
+
+			match dependency.modified {
+				Some(modified) => {
+					self.write_u8(1);
+					self.u64(modified);
+				}
+				None => self.write_u8(0),
+			}
+		}
+	}
+}
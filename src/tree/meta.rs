@@ -0,0 +1,309 @@
+use {
+	crate::{
+		diagnostics::Data,
+		tree::{
+			text::PlainText,
+			visit::{self, Visitor},
+			BlockItem, InlineItem, PlainTextOptions, TagInvocation, AST, INVALID_INDENTATION,
+		},
+		MarkDoll,
+	},
+	alloc::{
+		string::{String, ToString},
+		vec::Vec,
+	},
+};
+
+/// how many bytes of text a title taken from the first text of a document may have, before it is cut at the last word that fits
+pub const FALLBACK_TITLE_LEN: usize = 80;
+
+/// the reading speed [`DocumentMeta::reading_minutes`] estimates with
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// a section heading within a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Heading {
+	/// how deeply the section is nested, starting at 1
+	pub level: usize,
+	/// the heading text
+	pub name: String,
+}
+
+/// metadata about a document, from [`analyze`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DocumentMeta {
+	/// the heading of the first top-level section, or the start of the first text if there is none
+	pub title: Option<String>,
+	/// the frontmatter of the document
+	pub frontmatter: Option<String>,
+	/// the section headings in order, not including sections within tags, or those marked [`unlisted`](super::SectionMeta::unlisted)
+	pub outline: Vec<Heading>,
+	/// how many whitespace separated words the [`plain_text`](super::plain_text) of the document has
+	pub words: usize,
+	/// how many `img` tags the document has, including those within other tags
+	pub images: usize,
+	/// how many `link` tags the document has, including those within other tags
+	pub links: usize,
+}
+
+impl DocumentMeta {
+	/// how many minutes the document takes to read, at [`WORDS_PER_MINUTE`], rounded up
+	#[must_use]
+	pub fn reading_minutes(&self) -> usize {
+		self.words.div_ceil(WORDS_PER_MINUTE)
+	}
+}
+
+struct Analyze {
+	text: PlainText,
+	outline: Vec<Heading>,
+	depth: usize,
+	tags: usize,
+	title: Option<String>,
+	images: usize,
+	links: usize,
+}
+
+impl Visitor for Analyze {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &BlockItem) {
+		self.text.block(block);
+
+		if let BlockItem::Section { name, meta, .. } = block {
+			if self.tags == 0 {
+				self.depth += 1;
+				if !meta.unlisted {
+					self.outline.push(Heading {
+						level: self.depth,
+						name: name.clone(),
+					});
+				}
+
+				if self.depth == 1 && self.title.is_none() {
+					self.title = Some(name.clone());
+				}
+
+				visit::walk_block(self, doll, block);
+				self.depth -= 1;
+
+				return;
+			}
+		}
+
+		visit::walk_block(self, doll, block);
+	}
+
+	fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &InlineItem) {
+		if self.text.inline(item) {
+			visit::walk_inline(self, doll, pos, item);
+		}
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+		match &*tag.tag {
+			"img" => self.images += 1,
+			"link" => self.links += 1,
+			_ => {}
+		}
+
+		if self.text.enter_tag(tag) {
+			self.tags += 1;
+			visit::walk_tag(self, doll, pos, tag);
+			self.tags -= 1;
+			self.text.exit_tag(tag);
+		}
+	}
+}
+
+/// cut text to at most `max` bytes, at the end of the last word that fits
+///
+/// a single word longer than `max` is cut at a character boundary instead
+fn truncate_words(text: &str, max: usize) -> &str {
+	if text.len() <= max {
+		return text;
+	}
+
+	let mut end = max;
+	while !text.is_char_boundary(end) {
+		end -= 1;
+	}
+
+	// including the character after, so a word ending right at `max` still fits
+	let next = text[end..].chars().next().map_or(0, char::len_utf8);
+
+	match text[..end + next].rfind(char::is_whitespace) {
+		Some(space) if !text[..space].trim_end().is_empty() => text[..space].trim_end(),
+		_ => &text[..end],
+	}
+}
+
+/// collect the title, outline, word count, and image and link counts of a document in a single walk
+///
+/// the title is the heading of the first top-level section, falling back to the first non-empty line of the document's text, cut at [`FALLBACK_TITLE_LEN`]
+#[must_use]
+pub fn analyze(doll: &MarkDoll, frontmatter: Option<&str>, ast: &AST) -> DocumentMeta {
+	let mut analyze = Analyze {
+		text: PlainText::new(PlainTextOptions::default()),
+		outline: Vec::new(),
+		depth: 0,
+		tags: 0,
+		title: None,
+		images: 0,
+		links: 0,
+	};
+
+	visit::walk(ast, doll, &mut analyze);
+
+	let text = analyze.text.finish();
+
+	DocumentMeta {
+		title: analyze.title.or_else(|| {
+			text.lines()
+				.map(str::trim)
+				.find(|line| !line.is_empty())
+				.map(|line| truncate_words(line, FALLBACK_TITLE_LEN).to_string())
+		}),
+		frontmatter: frontmatter.map(ToString::to_string),
+		outline: analyze.outline,
+		words: text.split_whitespace().count(),
+		images: analyze.images,
+		links: analyze.links,
+	}
+}
+
+/// what a document must have to be accepted, checked by [`check_thresholds`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Thresholds {
+	/// the fewest [`words`](DocumentMeta::words) it may have
+	pub min_words: usize,
+	/// whether it must have at least one section heading
+	pub require_headings: bool,
+}
+
+/// error for each threshold the document of `meta` doesn't meet, such as to reject stub pages, returning whether none of them were errors after [`severity_overrides`](MarkDoll::severity_overrides)
+///
+/// the errors are labeled at the start of the document and reported as the `thresholds` [`Phase::Pass`](crate::Phase::Pass), so call this after parsing and before [`MarkDoll::finish`]
+pub fn check_thresholds(doll: &mut MarkDoll, meta: &DocumentMeta, thresholds: Thresholds) -> bool {
+	doll.run_pass("thresholds", |doll| {
+		let count = doll.diagnostics.len();
+
+		if meta.words < thresholds.min_words {
+			doll.diag(true, 0, "document has too few words");
+		}
+
+		if thresholds.require_headings && meta.outline.is_empty() {
+			doll.diag(true, 0, "document has no headings");
+		}
+
+		doll.diagnostics[count..].iter().all(|diag| !diag.err)
+	})
+}
+
+/// a section found by [`Levels`] to skip a level
+struct Skip {
+	/// the position of its `&`
+	pos: usize,
+	/// the length of its heading, with the `&`
+	len: usize,
+	level: usize,
+	expected: usize,
+	/// the position of the outermost [`INVALID_INDENTATION`] section between it and the section it is in, if any
+	cause: Option<usize>,
+}
+
+struct Levels {
+	/// the position of each section the walk is in, and whether it is an [`INVALID_INDENTATION`] section
+	sections: Vec<(usize, bool)>,
+	tags: usize,
+	skips: Vec<Skip>,
+}
+
+impl Visitor for Levels {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &BlockItem) {
+		if let BlockItem::Section { pos, name, .. } = block {
+			if self.tags == 0 {
+				let synthetic = name == INVALID_INDENTATION;
+
+				if !synthetic {
+					let level = self.sections.len() + 1;
+					let parent = self.sections.iter().rposition(|(_, synthetic)| !synthetic);
+					let expected = parent.map_or(1, |parent| parent + 2);
+
+					if level > expected {
+						self.skips.push(Skip {
+							pos: *pos,
+							len: name.chars().count() + 1,
+							level,
+							expected,
+							cause: self.sections[parent.map_or(0, |parent| parent + 1)..]
+								.first()
+								.map(|(pos, _)| *pos),
+						});
+					}
+				}
+
+				self.sections.push((*pos, synthetic));
+				visit::walk_block(self, doll, block);
+				self.sections.pop();
+
+				return;
+			}
+		}
+
+		visit::walk_block(self, doll, block);
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+		self.tags += 1;
+		visit::walk_tag(self, doll, pos, tag);
+		self.tags -= 1;
+	}
+}
+
+/// warn of sections of the [`outline`](DocumentMeta::outline) nested more than one level deeper than the section they are in, so their headings skip a level
+///
+/// this happens when lines are indented too far, which the parser recovers from by wrapping them in an [`INVALID_INDENTATION`] section. the warning is labeled at the start of that section, and carries [`Data::HeadingLevel`] for tooling to re-indent the section
+///
+/// called by [`MarkDoll::parse_document`] as the `heading levels` [`Phase::Pass`](crate::Phase::Pass), so only needed for documents built in code
+pub fn check_heading_levels(doll: &mut MarkDoll, ast: &AST) {
+	let mut levels = Levels {
+		sections: Vec::new(),
+		tags: 0,
+		skips: Vec::new(),
+	};
+
+	visit::walk(ast, doll, &mut levels);
+
+	for skip in levels.skips {
+		let count = doll.diagnostics.len();
+		match skip.cause {
+			Some(cause) => doll.diag_with_context(
+				false,
+				skip.pos,
+				"section skips a level",
+				cause,
+				"indented too far here, nesting it in an extra section",
+			),
+			None => doll.diag(false, skip.pos, "section skips a level"),
+		}
+
+		// unless it was suppressed
+		if doll.diagnostics.len() > count {
+			let diag = doll.diagnostics.last_mut().unwrap();
+			diag.len = skip.len;
+			diag.data = Some(Data::HeadingLevel {
+				level: skip.level,
+				expected: skip.expected,
+			});
+		}
+	}
+}
+
+impl MarkDoll {
+	/// the title of a document, see [`analyze`]
+	#[must_use]
+	pub fn document_title(&self, ast: &AST) -> Option<String> {
+		analyze(self, None, ast).title
+	}
+}
@@ -1,18 +1,56 @@
+/// building syntax trees from code
+pub mod build;
+pub(crate) mod cache;
+pub(crate) mod fingerprint;
+mod meta;
 pub(crate) mod parser;
+/// printing syntax trees back to markdoll
+pub mod print;
+mod shorthand;
+mod text;
+mod typography;
+/// walking syntax trees
+pub mod visit;
 
 use {
-	crate::{emit::BuiltInEmitters, MarkDoll, TagDiagnosticTranslation},
-	alloc::{boxed::Box, string::String, vec::Vec},
+	crate::{
+		diagnostics::Data, emit::BuiltInEmitters, ext::TagDefinition, MarkDoll,
+		TagDiagnosticTranslation,
+	},
+	::alloc::format,
+	alloc::{
+		boxed::Box,
+		string::{String, ToString},
+		sync::Arc,
+		vec::Vec,
+	},
+	core::mem,
 	downcast_rs::{impl_downcast, Downcast},
 };
+pub use {
+	meta::{
+		analyze, check_heading_levels, check_thresholds, DocumentMeta, Heading, Thresholds,
+		FALLBACK_TITLE_LEN, WORDS_PER_MINUTE,
+	},
+	shorthand::Shorthand,
+	text::{plain_text, PlainTextOptions, WrapOptions},
+	typography::{
+		skip_typography, smart_punctuation, typographic_spacing, QuoteStyle, SpacingStyle,
+	},
+};
+
+/// the name of the section the parser wraps lines indented more than the block they are in, to recover from them
+pub const INVALID_INDENTATION: &str = "<invalid indentation>";
 
 /// block syntax tree
 pub type AST = Vec<BlockItem>;
 
 /// tag content, effectively just [`Any`](core::any::Any) with [`Debug`](core::fmt::Debug)
-pub trait TagContent: Downcast + core::fmt::Debug {}
+///
+/// content must be [`Send`] and [`Sync`], so parsed [`AST`]s can be moved and shared between threads
+pub trait TagContent: Downcast + core::fmt::Debug + Send + Sync {}
 
-impl<T: Downcast + core::fmt::Debug> TagContent for T {}
+impl<T: Downcast + core::fmt::Debug + Send + Sync> TagContent for T {}
 
 impl_downcast!(TagContent);
 
@@ -21,31 +59,57 @@ impl_downcast!(TagContent);
 pub struct TagInvocation {
 	/// the tag name
 	pub tag: String,
+	/// the definition the tag was parsed with
+	pub(crate) def: Arc<TagDefinition>,
 	/// the arguments to the tag
 	pub args: Vec<String>,
 	/// the content returned by the tag
 	pub content: Box<dyn TagContent>,
 	pub(crate) diagnostic_translation: Option<TagDiagnosticTranslation>,
+	/// the [`MarkDoll`] generation this was parsed in, see [`MarkDoll::reset`]
+	pub(crate) generation: usize,
 }
 
 impl TagInvocation {
+	/// the definition the tag was parsed with
+	#[must_use]
+	pub fn definition(&self) -> &TagDefinition {
+		&self.def
+	}
+
+	/// copy the invocation, if its tag is [`pure`](TagDefinition::pure) and its content can be copied
+	#[must_use]
+	pub fn try_clone(&self) -> Option<Self> {
+		Some(Self {
+			tag: self.tag.clone(),
+			def: Arc::clone(&self.def),
+			args: self.args.clone(),
+			content: (self.def.pure?)(&*self.content)?,
+			diagnostic_translation: self.diagnostic_translation.clone(),
+			generation: self.generation,
+		})
+	}
+
 	/// emit into an output
 	pub fn emit<To: 'static>(&mut self, doll: &mut MarkDoll, to: &mut To) {
+		debug_assert_eq!(
+			self.generation, doll.generation,
+			"tag emitted after the source it was parsed from was finished or reset"
+		);
+
 		doll.diagnostic_translations
 			.push(self.diagnostic_translation.take().unwrap());
 
-		let def = doll
-			.ext_system
-			.tags
-			.get(&*self.tag)
-			.expect("tag not defined, this should've been handled by the parser");
-
-		match def.emitter_for::<To>() {
+		match self.def.emitter_for::<To>() {
+			// tags built in code aren't checked when parsing
+			Some(_) if self.def.passthrough && doll.is_untrusted() => {
+				doll.diag(true, usize::MAX, "tag not allowed in untrusted documents");
+			}
 			Some(emit) => emit(doll, to, &mut self.content),
 			None => doll.diag(
 				true,
 				usize::MAX,
-				if def.has_any_emitters() {
+				if self.def.has_any_emitters() {
 					"this tag does not support emitting for this emit target"
 				} else {
 					"this tag cannot be emitted"
@@ -57,6 +121,45 @@ impl TagInvocation {
 	}
 }
 
+/// markdoll generated by a tag rather than written in the source, parsed with [`MarkDoll::parse_snippet`]
+///
+/// diagnostics within it, whether parsing or emitting, are reported where it was generated from, since it has no position in the source
+#[derive(Debug)]
+pub struct Snippet {
+	/// the parsed content
+	pub ast: AST,
+	pub(crate) diagnostic_translation: Box<TagDiagnosticTranslation>,
+}
+
+impl Snippet {
+	/// the generated source the content was parsed from
+	#[must_use]
+	pub fn source(&self) -> &str {
+		self.diagnostic_translation.text()
+	}
+
+	/// emit the content into an output, like [`BlockItem::emit`] for each block
+	pub fn emit<To: 'static>(&mut self, doll: &mut MarkDoll, to: &mut To, inline_block: bool) {
+		doll.diagnostic_translations
+			.push((*self.diagnostic_translation).clone());
+
+		for item in &mut self.ast {
+			item.emit(doll, to, inline_block);
+		}
+
+		*self.diagnostic_translation = doll.diagnostic_translations.pop().unwrap();
+	}
+
+	/// copy the snippet, if every tag in it is [`pure`](TagDefinition::pure), see [`try_clone_ast`]
+	#[must_use]
+	pub fn try_clone(&self) -> Option<Self> {
+		Some(Self {
+			ast: try_clone_ast(&self.ast)?,
+			diagnostic_translation: self.diagnostic_translation.clone(),
+		})
+	}
+}
+
 /// an inline item, containing real content
 #[derive(Debug)]
 pub enum InlineItem {
@@ -85,6 +188,8 @@ pub enum BlockItem {
 		pos: usize,
 		/// heading text
 		name: String,
+		/// the props given after the &
+		meta: SectionMeta,
 		/// content of the section
 		children: AST,
 	},
@@ -96,28 +201,370 @@ pub enum BlockItem {
 		/// whether the list is ordered
 		ordered: bool,
 		/// the items
-		items: Vec<AST>,
+		items: Vec<ListItem>,
 	},
 }
 
+/// the props of a [`BlockItem::Section`], given in parentheses right after its `&`, such as `&(id=install)(class=appendix)(unlisted)\tInstallation`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SectionMeta {
+	/// the id of the section in the output, from `id=`
+	pub id: Option<String>,
+	/// the classes of the section in the output, from `class=`
+	pub class: Option<String>,
+	/// whether the section is left out of the [`outline`](DocumentMeta::outline), from `unlisted`
+	pub unlisted: bool,
+}
+
+impl SectionMeta {
+	/// whether no props were given
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		*self == Self::default()
+	}
+}
+
+/// an item of a [`BlockItem::List`]
+#[derive(Debug)]
+pub struct ListItem {
+	/// position of the marker starting the item
+	pub pos: usize,
+	/// the number an ordered item's marker gives it, such as `7` for `=7.`, which later items count on from
+	pub value: Option<u64>,
+	/// content of the item
+	pub ast: AST,
+}
+
 impl BlockItem {
+	/// the position the block starts at, or `None` for inline items without any segments
+	#[must_use]
+	pub fn pos(&self) -> Option<usize> {
+		match self {
+			Self::Inline(segments) => segments.first().map(|(pos, _)| *pos),
+			Self::Section { pos, .. } | Self::List { pos, .. } => Some(*pos),
+		}
+	}
+
 	/// emit into an output
+	///
+	/// blocks nested deeper than [`MarkDoll::max_emit_depth`] are left out with an error, as is everything if no [`BuiltInEmitters`] are set for the output, see [`MarkDoll::has_emitters_for`]
+	///
+	/// the block is left as it was, so it can be emitted again, see [`MarkDoll::emit`]
 	pub fn emit<To: 'static>(&mut self, doll: &mut MarkDoll, to: &mut To, inline_block: bool) {
-		let builtin_emitters = doll
-			.builtin_emitters
-			.get_ref::<BuiltInEmitters<To>>()
-			.expect("no BuiltInEmitters defined for this emit target");
+		if doll.emit_depth >= doll.max_emit_depth {
+			let count = doll.diagnostics.len();
+			doll.diag(true, self.pos().unwrap_or(0), "nested too deeply to emit");
+
+			// unless it was suppressed
+			if doll.diagnostics.len() > count {
+				doll.diagnostics.last_mut().unwrap().data = Some(Data::TooDeep {
+					max_depth: doll.max_emit_depth,
+				});
+			}
+
+			return;
+		}
+
+		let Some(builtin_emitters) = doll.builtin_emitters.get_ref::<BuiltInEmitters<To>>() else {
+			doll.missing_emitters::<To>();
+			return;
+		};
+
+		doll.emit_depth += 1;
 
 		match self {
 			Self::Inline(segments) => {
 				(builtin_emitters.inline)(doll, to, segments, inline_block);
 			}
-			Self::Section { name, children, .. } => {
-				(builtin_emitters.section)(doll, to, name, children);
+			Self::Section {
+				pos,
+				name,
+				meta,
+				children,
+			} => {
+				(builtin_emitters.section)(doll, to, *pos, name, meta, children);
 			}
 			Self::List { ordered, items, .. } => {
 				(builtin_emitters.list)(doll, to, *ordered, &mut items[..]);
 			}
 		}
+
+		doll.emit_depth -= 1;
+	}
+
+	/// move the blocks nested directly within this one onto `stack`, leaving it without them
+	fn take_children(&mut self, stack: &mut Vec<BlockItem>) {
+		match self {
+			Self::Inline(_) => {}
+			Self::Section { children, .. } => stack.append(children),
+			Self::List { items, .. } => {
+				for item in items {
+					stack.append(&mut item.ast);
+				}
+			}
+		}
 	}
 }
+
+/// nested blocks are dropped one at a time rather than recursively, so arbitrarily deep [`AST`]s, such as ones built in code, don't overflow the stack
+///
+/// fields can't be moved out of a [`BlockItem`] because of this, match on it by reference and [`take`](core::mem::take) them instead
+impl Drop for BlockItem {
+	fn drop(&mut self) {
+		let mut stack = Vec::new();
+		self.take_children(&mut stack);
+
+		while let Some(mut block) = stack.pop() {
+			block.take_children(&mut stack);
+		}
+	}
+}
+
+/// copy an [`AST`], if every tag in it is [`pure`](TagDefinition::pure) and its content can be copied
+///
+/// [`AST`]s aren't [`Clone`], as the content of tags may not be, so this is how tags whose content has [`AST`]s copy it
+#[must_use]
+pub fn try_clone_ast(ast: &AST) -> Option<AST> {
+	ast.iter()
+		.map(|block| {
+			Some(match block {
+				BlockItem::Inline(items) => BlockItem::Inline(
+					items
+						.iter()
+						.map(|(pos, item)| {
+							Some((
+								*pos,
+								match item {
+									InlineItem::Split => InlineItem::Split,
+									InlineItem::Break => InlineItem::Break,
+									InlineItem::Text(text) => InlineItem::Text(text.clone()),
+									InlineItem::Tag(tag) => InlineItem::Tag(tag.try_clone()?),
+								},
+							))
+						})
+						.collect::<Option<_>>()?,
+				),
+				BlockItem::Section {
+					pos,
+					name,
+					meta,
+					children,
+				} => BlockItem::Section {
+					pos: *pos,
+					name: name.clone(),
+					meta: meta.clone(),
+					children: try_clone_ast(children)?,
+				},
+				BlockItem::List {
+					pos,
+					ordered,
+					items,
+				} => BlockItem::List {
+					pos: *pos,
+					ordered: *ordered,
+					items: items
+						.iter()
+						.map(|item| {
+							Some(ListItem {
+								pos: item.pos,
+								value: item.value,
+								ast: try_clone_ast(&item.ast)?,
+							})
+						})
+						.collect::<Option<_>>()?,
+				},
+			})
+		})
+		.collect()
+}
+
+/// the index of the top-level block of an [`AST`] containing a character index of its source, such as the position of a cursor
+///
+/// this is the last block starting at or before `at`, so whitespace after a block is part of it. emit it alone with [`MarkDoll::emit_block`]
+#[must_use]
+pub fn block_at(ast: &AST, at: usize) -> Option<usize> {
+	ast.iter()
+		.enumerate()
+		.filter_map(|(i, block)| Some((i, block.pos()?)))
+		.take_while(|(_, pos)| *pos <= at)
+		.last()
+		.map(|(i, _)| i)
+}
+
+/// tidy up an [`AST`], merging adjacent text, dropping empty text, removing splits at the start and end of inline blocks, and removing inline blocks left empty
+///
+/// this descends into sections and lists, but not tag content, which is normalized as it is parsed when [`MarkDoll::normalize`] is set
+pub fn normalize(ast: &mut AST) {
+	ast.retain_mut(|block| match block {
+		BlockItem::Inline(items) => {
+			let mut merged: Vec<(usize, InlineItem)> = Vec::with_capacity(items.len());
+
+			for (pos, item) in items.drain(..) {
+				match (merged.last_mut(), item) {
+					(_, InlineItem::Text(text)) if text.is_empty() => {}
+					(Some((_, InlineItem::Text(last))), InlineItem::Text(text)) => {
+						last.push_str(&text);
+					}
+					(None, InlineItem::Split) => {}
+					(_, item) => merged.push((pos, item)),
+				}
+			}
+
+			while let Some((_, InlineItem::Split)) = merged.last() {
+				merged.pop();
+			}
+
+			*items = merged;
+			!items.is_empty()
+		}
+		BlockItem::Section { children, .. } => {
+			normalize(children);
+			true
+		}
+		BlockItem::List { items, .. } => {
+			for item in items {
+				normalize(&mut item.ast);
+			}
+			true
+		}
+	});
+}
+
+/// options for [`number_sections`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberingOptions {
+	/// written between the numbers of each level, such as the `.` in `2.3.1`
+	pub separator: String,
+	/// how deeply sections are numbered, starting at 1, deeper sections keep their names but are still counted
+	pub max_depth: usize,
+}
+
+impl Default for NumberingOptions {
+	fn default() -> Self {
+		Self {
+			separator: ".".into(),
+			max_depth: usize::MAX,
+		}
+	}
+}
+
+/// prefix the name of each section in an [`AST`] with its hierarchical number, such as `2.3.1 name`
+///
+/// numbering restarts within every section, and sections inside lists are counted alongside their siblings outside of them. as the names themselves are changed, every target, the [`outline`](DocumentMeta::outline), and [`plain_text`] all show the same numbers
+///
+/// this descends into sections and lists, but not tag content, matching the [`outline`](DocumentMeta::outline)
+pub fn number_sections(ast: &mut AST, options: &NumberingOptions) {
+	fn number(
+		ast: &mut AST,
+		options: &NumberingOptions,
+		numbers: &mut Vec<usize>,
+		count: &mut usize,
+	) {
+		for block in ast {
+			match block {
+				BlockItem::Inline(_) => {}
+				BlockItem::Section { name, children, .. } => {
+					*count += 1;
+					numbers.push(*count);
+
+					if numbers.len() <= options.max_depth {
+						*name = format!(
+							"{} {name}",
+							numbers
+								.iter()
+								.map(ToString::to_string)
+								.collect::<Vec<_>>()
+								.join(&options.separator)
+						);
+					}
+
+					number(children, options, numbers, &mut 0);
+					numbers.pop();
+				}
+				BlockItem::List { items, .. } => {
+					for item in items {
+						number(&mut item.ast, options, numbers, count);
+					}
+				}
+			}
+		}
+	}
+
+	number(ast, options, &mut Vec::new(), &mut 0);
+}
+
+/// a top-level section of an [`AST`], split off by [`split_sections`]
+#[derive(Debug)]
+pub struct SectionDoc {
+	/// position of the & defining the section
+	pub pos: usize,
+	/// heading text
+	pub title: String,
+	/// the props given after the &
+	pub meta: SectionMeta,
+	/// content of the section
+	pub body: AST,
+	/// top-level content following the section, before the next one
+	pub trailing: AST,
+}
+
+impl SectionDoc {
+	/// put the section back into an [`AST`] of its own, followed by its trailing content
+	///
+	/// when emitted on its own, its heading starts at the first level
+	#[must_use]
+	pub fn into_ast(self) -> AST {
+		let mut ast = Vec::with_capacity(1 + self.trailing.len());
+		ast.push(BlockItem::Section {
+			pos: self.pos,
+			name: self.title,
+			meta: self.meta,
+			children: self.body,
+		});
+		ast.extend(self.trailing);
+		ast
+	}
+}
+
+/// an [`AST`] split into its top-level sections by [`split_sections`]
+#[derive(Debug)]
+pub struct SplitSections {
+	/// content before the first section
+	pub preamble: AST,
+	/// the top-level sections
+	pub sections: Vec<SectionDoc>,
+}
+
+/// split an [`AST`] into its top-level sections, so each can be emitted separately, such as one page per chapter
+///
+/// tags within each piece keep their diagnostic translations, so the pieces can be emitted before [`MarkDoll::finish`] is called for the source they were parsed from
+#[must_use]
+pub fn split_sections(ast: AST) -> SplitSections {
+	let mut split = SplitSections {
+		preamble: AST::new(),
+		sections: Vec::new(),
+	};
+
+	for mut block in ast {
+		match &mut block {
+			BlockItem::Section {
+				pos,
+				name,
+				meta,
+				children,
+			} => split.sections.push(SectionDoc {
+				pos: *pos,
+				title: mem::take(name),
+				meta: mem::take(meta),
+				body: mem::take(children),
+				trailing: AST::new(),
+			}),
+			_ => match split.sections.last_mut() {
+				Some(section) => section.trailing.push(block),
+				None => split.preamble.push(block),
+			},
+		}
+	}
+
+	split
+}
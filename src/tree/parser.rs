@@ -1,14 +1,20 @@
 use {
 	crate::{
-		tree::{BlockItem, InlineItem, TagContent, TagInvocation, AST},
-		MarkDoll, TagDiagnosticTranslation,
+		diagnostics::Data,
+		ext::{self, TagDefinition, TagFilterAction, TagFilterEvent, TagForm},
+		tree::{
+			cache, BlockItem, InlineItem, ListItem, SectionMeta, TagContent, TagInvocation, AST,
+			INVALID_INDENTATION,
+		},
+		IndexedSrc, MarkDoll, Phase, Progress, TagDiagnosticTranslation,
 	},
 	alloc::{
 		boxed::Box,
-		rc::Rc,
 		string::{String, ToString},
+		sync::Arc,
 		vec::Vec,
 	},
+	core::ops::Range,
 	log::error,
 };
 
@@ -31,17 +37,21 @@ enum StackPart {
 	List {
 		pos: usize,
 		ordered: bool,
-		items: Vec<AST>,
+		items: Vec<ListItem>,
 	},
 	Section {
 		pos: usize,
 		name: String,
+		meta: SectionMeta,
 		children: AST,
 	},
 	TagBlockContent {
 		tag: String,
 		args: Vec<String>,
-		text: String,
+		/// where each argument starts
+		arg_positions: Vec<usize>,
+		/// the char ranges of each line of content in the stream, with their indentation left out
+		lines: Vec<Range<usize>>,
 		tag_at: usize,
 		offset_in_parent: usize,
 		indent: usize,
@@ -134,6 +144,17 @@ impl Stream {
 	}
 }
 
+/// whether a character is a control character that is left out of html output: the C0 controls other than tab and newline, and the bidirectional formatting characters, which can make source read differently than it parses
+pub(crate) fn is_hidden_control(ch: char) -> bool {
+	matches!(ch, '\0'..='\x08' | '\x0b'..='\x1f' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// whether a character is whitespace other than a space, tab, or line break, such as a no-break or zero-width space, which looks like indentation but isn't
+fn is_unusual_whitespace(ch: char) -> bool {
+	(ch.is_whitespace() && !matches!(ch, ' ' | '\t' | '\n' | '\r'))
+		|| matches!(ch, '\u{200b}' | '\u{feff}')
+}
+
 /// makes a lot of noise if dropped without being explicitly handled
 struct StreamTransaction(usize);
 
@@ -150,10 +171,32 @@ pub(crate) struct Ctx<'doll> {
 	stack: Vec<StackPart>,
 	inline: Vec<(usize, InlineItem)>,
 	document: bool,
+	/// the buffer this source is a slice of and the byte offset it starts at, if it is the source of the current diagnostic translation, so tag content can share it
+	shared: Option<(Arc<str>, usize)>,
+	/// the last char index converted to a byte offset by [`byte_offset`](Self::byte_offset), and that offset
+	byte_cursor: (usize, usize),
+	/// the closers last found missing by [`find_shorthand`](tag::find_shorthand), see [`CloserMiss`](tag::CloserMiss)
+	closer_misses: Vec<tag::CloserMiss>,
+	/// where to report [`Progress`] for the root source, and the index to report at next
+	progress: Option<(fn(Progress), usize)>,
+	/// the position of the last break written explicitly with a backslash, which is kept at the end of a paragraph, unlike the breaks of [`MarkDoll::hard_breaks`]
+	explicit_break: Option<usize>,
 }
 
 impl<'doll> Ctx<'doll> {
 	pub fn new(doll: &'doll mut MarkDoll, src: &str, document: bool) -> Self {
+		let shared = doll
+			.diagnostic_translations
+			.last()
+			.filter(|translation| core::ptr::eq(translation.text(), src))
+			.map(|translation| (Arc::clone(&translation.src), translation.range.start));
+
+		// tag content is parsed with the translation of its tag pushed
+		let progress = doll
+			.progress
+			.filter(|_| doll.diagnostic_translations.len() <= 1)
+			.map(|progress| (progress, 0));
+
 		Self {
 			doll,
 			stream: Stream {
@@ -169,9 +212,47 @@ impl<'doll> Ctx<'doll> {
 			},
 			inline: Vec::new(),
 			document,
+			shared,
+			byte_cursor: (0, 0),
+			closer_misses: Vec::new(),
+			progress,
+			explicit_break: None,
+		}
+	}
+
+	/// report [`Progress`] if the next hundredth of the source has been reached, or unconditionally at the end
+	fn report_progress(&mut self, end: bool) {
+		if let Some((progress, next)) = &mut self.progress {
+			let total = self.stream.src.len();
+
+			if end || self.stream.index >= *next {
+				progress(Progress {
+					phase: Phase::Parse,
+					done: if end { total } else { self.stream.index },
+					total,
+				});
+
+				*next = self.stream.index + (total / 100).max(1);
+			}
 		}
 	}
 
+	/// the byte offset of a char index in the source, walking from the last one computed
+	fn byte_offset(&mut self, index: usize) -> usize {
+		let (from, mut offset) = if index >= self.byte_cursor.0 {
+			self.byte_cursor
+		} else {
+			(0, 0)
+		};
+
+		for ch in &self.stream.src[from..index] {
+			offset += ch.len_utf8();
+		}
+
+		self.byte_cursor = (index, offset);
+		offset
+	}
+
 	pub fn stack_terminate_top(&mut self) {
 		if self.stack.is_empty() {
 			return;
@@ -197,48 +278,76 @@ impl<'doll> Ctx<'doll> {
 			StackPart::Section {
 				pos,
 				name,
+				meta,
 				children,
 			} => {
+				// only reported when asked for, as sections are often left empty while writing
+				if children.is_empty()
+					&& self
+						.doll
+						.severity_overrides
+						.contains_key("section has no content")
+				{
+					self.doll.diag(false, pos, "section has no content");
+				}
+
 				self.stack_push_block_to_top(BlockItem::Section {
 					pos,
 					name,
+					meta,
 					children,
 				});
 			}
 			StackPart::TagBlockContent {
 				tag,
 				args,
-				mut text,
+				arg_positions,
+				lines,
 				tag_at,
 				offset_in_parent,
 				indent,
 			} => {
+				let mut text =
+					String::with_capacity(lines.iter().map(ExactSizeIterator::len).sum());
+				for line in lines {
+					text.extend(&self.stream.src[line]);
+				}
+
 				if text.ends_with('\n') {
 					text.pop().unwrap();
 				}
 
-				let text = Rc::from(text);
+				let text = Arc::from(text);
+				self.add_source(&text, tag_at);
 
 				self.doll
 					.diagnostic_translations
 					.push(TagDiagnosticTranslation {
-						src: Rc::clone(&text),
-						indexed: None,
-						offset_in_parent,
-						tag_pos_in_parent: tag_at,
-						indent,
+						args: arg_positions,
+						tag_end_in_parent: self.stream.index,
+						..TagDiagnosticTranslation::new(
+							Arc::clone(&text),
+							offset_in_parent,
+							// at the `[` before the name
+							tag_at - 1,
+							indent,
+						)
 					});
 
-				if let Some(content) = tag::transform_content(self, &args, &text, &tag) {
+				if let Some((def, content)) =
+					tag::transform_content(self, tag_at, (&args, &text), &tag, TagForm::Block)
+				{
 					self.inline.push((
 						tag_at,
 						InlineItem::Tag(TagInvocation {
 							tag,
+							def,
 							args,
 							content,
 							diagnostic_translation: Some(
 								self.doll.diagnostic_translations.pop().unwrap(),
 							),
+							generation: self.doll.generation,
 						}),
 					));
 				} else {
@@ -256,6 +365,7 @@ impl<'doll> Ctx<'doll> {
 			StackPart::List { items, .. } => items
 				.last_mut()
 				.expect("list does not have any items")
+				.ast
 				.push(item),
 			StackPart::TagBlockContent { .. } => {
 				unreachable!("attempt to push block item into tag block-content")
@@ -273,8 +383,13 @@ impl<'doll> Ctx<'doll> {
 		t!(&self.inline);
 		t!(&self.stack.last());
 
-		if let (_, InlineItem::Split | InlineItem::Break) = self.inline.last().unwrap() {
-			self.inline.pop().unwrap();
+		// the line break ending the paragraph, unless it was asked for
+		match self.inline.last().unwrap() {
+			(pos, InlineItem::Break) if self.explicit_break == Some(*pos) => {}
+			(_, InlineItem::Split | InlineItem::Break) => {
+				self.inline.pop().unwrap();
+			}
+			_ => {}
 		}
 
 		match self.stack.last_mut().unwrap() {
@@ -284,6 +399,7 @@ impl<'doll> Ctx<'doll> {
 			StackPart::List { items, .. } => items
 				.last_mut()
 				.unwrap()
+				.ast
 				.push(BlockItem::Inline(core::mem::take(&mut self.inline))),
 			StackPart::TagBlockContent { .. } => {
 				error!("attempt to push onto tagblockcontent");
@@ -291,14 +407,40 @@ impl<'doll> Ctx<'doll> {
 		}
 	}
 
+	/// count the content of the tag at `at` towards [`MarkDoll::source_stats`], unless it shares this source
+	fn add_source(&mut self, text: &Arc<str>, at: usize) {
+		if !self
+			.shared
+			.as_ref()
+			.is_some_and(|(src, _)| Arc::ptr_eq(src, text))
+		{
+			self.doll.add_source(text, at);
+		}
+	}
+
+	/// report an error at the last character read, or the start of the source if none were, such as at the end of empty tag content
 	#[track_caller]
 	pub fn err(&mut self, msg: &'static str) {
-		self.doll.diag(true, self.stream.index - 1, msg);
+		self.doll
+			.diag(true, self.stream.index.saturating_sub(1), msg);
 	}
 
+	/// report the top of the stack as unterminated where its content ended at `end`, at the opening of a tag so the mistake is found where it was made
 	#[track_caller]
-	pub fn err_next(&mut self, msg: &'static str) {
-		self.doll.diag(true, self.stream.index, msg);
+	pub fn err_unterminated(&mut self, end: usize) {
+		let top = self.stack.last().unwrap();
+		let at = match top {
+			StackPart::TagBlockContent { tag_at, .. } => *tag_at - 1,
+			_ => end,
+		};
+
+		self.doll.diag_with_context(
+			true,
+			at,
+			top.unterminated(),
+			end,
+			"expected `]` before here",
+		);
 	}
 
 	/// `:neocat_floof_explode:`
@@ -332,37 +474,139 @@ enum ParseResult<T = ()> {
 	Stop,
 }
 
-fn frontmatter(ctx: &mut Ctx) -> Option<String> {
+/// whether the parenthesized group after the `(` at the start of `src` is a section prop, `id=`, `class=`, or `unlisted`, closed on the same line
+///
+/// any other group is left as the start of the section's name, such as the `(c)` of `&(c) notice`
+fn is_section_prop(src: &[char]) -> bool {
+	let mut prop = String::new();
+	let mut chars = src.iter();
+
+	loop {
+		match chars.next() {
+			Some(')') => break,
+			Some('\\') => match chars.next() {
+				Some('\n' | '\t') | None => return false,
+				Some(&ch) => prop.push(ch),
+			},
+			Some('\n' | '\t') | None => return false,
+			Some(&ch) => prop.push(ch),
+		}
+	}
+
+	prop.starts_with("id=") || prop.starts_with("class=") || prop == "unlisted"
+}
+
+/// the props of a section from those written after its `&`, see [`is_section_prop`]
+fn section_meta(props: Vec<String>) -> SectionMeta {
+	let mut meta = SectionMeta::default();
+
+	for prop in props {
+		match prop.split_once('=') {
+			Some(("id", id)) => meta.id = Some(id.to_string()),
+			Some(("class", class)) => meta.class = Some(class.to_string()),
+			_ => meta.unlisted = true,
+		}
+	}
+
+	meta
+}
+
+/// whether a document's frontmatter enables [`MarkDoll::hard_breaks`] with a `breaks: hard` line
+fn wants_hard_breaks(frontmatter: &str) -> bool {
+	frontmatter.lines().any(|line| {
+		line.split_once(':')
+			.is_some_and(|(key, value)| key.trim() == "breaks" && value.trim() == "hard")
+	})
+}
+
+/// eat a frontmatter fence, three or more dashes alone on their line, or exactly `len` dashes to close one opened with `len`, returning how many it has and leaving the newline after it
+///
+/// spaces and tabs after the dashes are warned of, and anything else leaves the stream where it was
+fn fence(ctx: &mut Ctx, len: Option<usize>) -> Option<usize> {
 	let tr = ctx.stream.tr();
 
-	if ctx.stream.eat_all('-') != 3 {
+	let dashes = ctx.stream.eat_all('-');
+	let trailing = ctx.stream.index;
+	while matches!(ctx.stream.lookahead(1), Some(' ' | '\t')) {
+		ctx.stream.skip();
+	}
+
+	if len.map_or(dashes >= 3, |len| dashes == len)
+		&& matches!(ctx.stream.lookahead(1), Some('\n') | None)
+	{
+		ctx.stream.tr_commit(tr);
+
+		if ctx.stream.index > trailing {
+			ctx.doll.diag(
+				false,
+				trailing,
+				"trailing whitespace after frontmatter fence",
+			);
+		}
+
+		Some(dashes)
+	} else {
 		ctx.stream.tr_cancel(tr);
 
-		return None;
+		None
 	}
+}
 
-	if !ctx.stream.try_eat('\n') {
-		ctx.err("expected newline to begin frontmatter");
+/// warn if the first line of a document looks like a frontmatter fence but isn't one, which is parsed as text
+fn almost_fence(ctx: &mut Ctx) {
+	let line = ctx.stream.src[ctx.stream.index..]
+		.iter()
+		.take_while(|ch| **ch != '\n')
+		.collect::<String>();
+	let line = line.trim_end_matches([' ', '\t']);
+
+	if line == "--" {
+		ctx.doll.diag(
+			false,
+			ctx.stream.index,
+			"frontmatter fence has too few dashes",
+		);
+	} else if line.starts_with("---") {
+		ctx.doll
+			.diag(false, ctx.stream.index, "text after frontmatter fence");
 	}
+}
+
+fn frontmatter(ctx: &mut Ctx) -> Option<String> {
+	let Some(len) = fence(ctx, None) else {
+		almost_fence(ctx);
 
-	ctx.stream.tr_commit(tr);
+		return None;
+	};
 
 	let mut frontmatter = String::new();
 
+	if !ctx.stream.try_eat('\n') {
+		ctx.stream.skip();
+		ctx.err("unexpected EOI in frontmatter");
+		return None;
+	}
+
 	loop {
-		match ctx.stream.next() {
-			Some('\n') => {
-				if ctx.stream.eat_all('-') == 3 {
-					return Some(frontmatter);
-				}
+		// the closing fence, which may directly follow the opening one
+		if fence(ctx, Some(len)).is_some() {
+			frontmatter.pop();
 
-				frontmatter.push('\n');
-			}
-			Some(ch) => frontmatter.push(ch),
-			None => {
-				ctx.stream.skip();
-				ctx.err("unexpected EOI in frontmatter");
-				return None;
+			return Some(frontmatter);
+		}
+
+		loop {
+			match ctx.stream.next() {
+				Some('\n') => {
+					frontmatter.push('\n');
+					break;
+				}
+				Some(ch) => frontmatter.push(ch),
+				None => {
+					ctx.stream.skip();
+					ctx.err("unexpected EOI in frontmatter");
+					return None;
+				}
 			}
 		}
 	}
@@ -378,6 +622,12 @@ mod indent {
 				.diag(false, ctx.stream.index - n, "erroneous leading spaces");
 		}
 
+		// left in the text, but reported as they would look like indentation
+		if ctx.stream.lookahead(1).is_some_and(is_unusual_whitespace) {
+			ctx.doll
+				.diag(false, ctx.stream.index, "unusual whitespace in indentation");
+		}
+
 		// if parsing a block tag
 		if let Some(StackPart::TagBlockContent { tag_at, .. }) = ctx.stack.last() {
 			// and there's a closing bracket below its content indent
@@ -385,7 +635,7 @@ mod indent {
 				// if the indent isnt exactly one level below its content indent
 				if *indent_level + 2 < ctx.stack.len() {
 					ctx.doll
-						.diag(true, *tag_at, "misaligned closing tag for this tag");
+						.diag(true, *tag_at - 1, "misaligned closing tag for this tag");
 				}
 
 				// terminate it
@@ -417,7 +667,7 @@ mod indent {
 			} else {
 				t!("[[[terminate non-gracefully]]]");
 
-				ctx.err_next(top.unterminated());
+				ctx.err_unterminated(ctx.stream.index);
 
 				// forcibly terminate it anyways
 				ctx.stack_terminate_top();
@@ -425,8 +675,45 @@ mod indent {
 		}
 	}
 
+	/// the value an ordered list item's marker gives it and the length of it and its `.`, such as `7` for `=7.`, or `None` if the marker has none
+	fn marker_value(ctx: &mut Ctx) -> Option<(usize, Option<u64>)> {
+		let start = ctx.stream.index + 1;
+		let digits = ctx.stream.src[start..]
+			.iter()
+			.take_while(|ch| ch.is_ascii_digit())
+			.count();
+		let end = start + digits;
+
+		if digits == 0
+			|| ctx.stream.src.get(end) != Some(&'.')
+			|| !matches!(ctx.stream.src.get(end + 1), Some('\t' | '\n'))
+		{
+			return None;
+		}
+
+		let value = ctx.stream.src[start..end]
+			.iter()
+			.collect::<String>()
+			.parse()
+			.ok();
+		if value.is_none() {
+			ctx.doll.diag(true, start, "list item value too large");
+		}
+
+		Some((digits + 1, value))
+	}
+
+	/// a new list item starting with the marker at `pos`
+	fn item(pos: usize, value: Option<u64>) -> ListItem {
+		ListItem {
+			pos,
+			value,
+			ast: Vec::new(),
+		}
+	}
+
 	/// more indentation than current
-	fn more(ctx: &mut Ctx, kind: IndentKind) {
+	fn more(ctx: &mut Ctx, kind: IndentKind, marker: (usize, Option<u64>)) {
 		ctx.flush_inline();
 
 		// more indent than the stack
@@ -435,21 +722,28 @@ mod indent {
 			ctx.err("unexpected indentation");
 			ctx.stack.push(StackPart::Section {
 				pos: ctx.stream.index - 1,
-				name: "<invalid indentation>".to_string(),
+				name: INVALID_INDENTATION.to_string(),
+				meta: SectionMeta::default(),
 				children: Vec::new(),
 			});
 		} else {
 			t!("[[[new list]]]");
 			ctx.stack.push(StackPart::List {
-				pos: ctx.stream.index - 2,
+				pos: marker.0,
 				ordered: kind == IndentKind::OrderedList,
-				items: vec![Vec::new()],
+				items: vec![item(marker.0, marker.1)],
 			});
 		}
 	}
 
 	/// less indentation than current
-	fn less(ctx: &mut Ctx, kind: IndentKind, indent_level: usize, last_significant: &mut bool) {
+	fn less(
+		ctx: &mut Ctx,
+		kind: IndentKind,
+		marker: (usize, Option<u64>),
+		indent_level: usize,
+		last_significant: &mut bool,
+	) {
 		match (&mut ctx.stack[indent_level], kind) {
 			// new line in the same list element
 			(
@@ -477,15 +771,15 @@ mod indent {
 					let StackPart::List { items, .. } = &mut ctx.stack[indent_level] else {
 						unreachable!()
 					};
-					items.push(Vec::new());
+					items.push(item(marker.0, marker.1));
 				} else {
 					t!("[[[new list, flush/term]]]");
 					ctx.flush_inline();
 					squimsh_to(ctx, indent_level - 1);
 					ctx.stack.push(StackPart::List {
-						pos: ctx.stream.index - 2,
+						pos: marker.0,
 						ordered: new_ordered,
-						items: vec![Vec::new()],
+						items: vec![item(marker.0, marker.1)],
 					});
 				}
 			}
@@ -493,9 +787,9 @@ mod indent {
 				t!("[[[section end]]]");
 				squimsh_to(ctx, indent_level - 1);
 				ctx.stack.push(StackPart::List {
-					pos: ctx.stream.index - 2,
+					pos: marker.0,
 					ordered: kind == IndentKind::OrderedList,
-					items: vec![Vec::new()],
+					items: vec![item(marker.0, marker.1)],
 				});
 			}
 			(
@@ -536,9 +830,17 @@ mod indent {
 
 				// handle indentation
 				Some(ch @ ('\t' | '=' | '-')) => {
+					let marker_at = ctx.stream.index;
+					// an ordered item's marker may give its value, such as `=7.`
+					let (value_len, value) = if ch == '=' {
+						marker_value(ctx).unwrap_or((0, None))
+					} else {
+						(0, None)
+					};
+
 					// if not just plain indent, need to eat the indent after it (or dont eat anything if no indent after it)
 					if ch != '\t' {
-						if !matches!(ctx.stream.lookahead(2), Some('\t' | '\n')) {
+						if !matches!(ctx.stream.lookahead(2 + value_len), Some('\t' | '\n')) {
 							if !exit(ctx, &mut indent_level) {
 								return ParseResult::NextLine;
 							}
@@ -546,7 +848,7 @@ mod indent {
 							break 'indent;
 						}
 
-						ctx.stream.skip();
+						ctx.stream.index += 1 + value_len;
 					}
 
 					ctx.stream.skip();
@@ -573,11 +875,17 @@ mod indent {
 					} else if indent_level + 1 > ctx.stack.len() {
 						t!("[[[higher indent]]]");
 
-						more(ctx, kind);
+						more(ctx, kind, (marker_at, value));
 					} else {
 						t!("[[[lower indent]]]");
 
-						less(ctx, kind, indent_level, last_significant);
+						less(
+							ctx,
+							kind,
+							(marker_at, value),
+							indent_level,
+							last_significant,
+						);
 					}
 				}
 
@@ -606,22 +914,75 @@ mod indent {
 mod tag {
 	use super::*;
 
-	/// parse inline tag text
-	fn parse_inline_text(ctx: &mut Ctx) -> Option<Rc<str>> {
-		let mut text = String::with_capacity(16);
-		let mut stack: usize = 0;
+	/// report the line or source ending inside tag text, at each `[` left open within it if any are, as the text would otherwise have ended at its `]`
+	///
+	/// otherwise, the last `[` closed at the top level of the text is given as context, as a literal `[` takes the `]` meant to close the tag
+	fn err_unclosed(ctx: &mut Ctx, open: &[usize], last_closed: Option<usize>, code: &'static str) {
+		if open.is_empty() {
+			match last_closed {
+				Some(at) => ctx.doll.diag_with_context(
+					true,
+					ctx.stream.index - 1,
+					code,
+					at,
+					"this `[` took the `]` that may have been meant to close the tag",
+				),
+				None => ctx.err(code),
+			}
+			return;
+		}
+
+		for &at in open {
+			ctx.doll.diag_with_context(
+				true,
+				at,
+				"unclosed bracket in tag content",
+				ctx.stream.index - 1,
+				"expected `]` before here",
+			);
+		}
+	}
+
+	/// parse inline tag text, returning the buffer containing it, its byte range within, and the position of each of its characters relative to its start if it isn't verbatim
+	///
+	/// text without escapes is a slice of the source being parsed, and shares its buffer when possible
+	///
+	/// `raw` text keeps its escapes
+	fn parse_inline_text(ctx: &mut Ctx, raw: bool) -> Option<(Arc<str>, Range<usize>, Vec<usize>)> {
+		let start = ctx.stream.index;
+		let mut text = String::new();
+		// empty while verbatim, as every character is where it was written
+		let mut offsets = Vec::new();
+		let mut verbatim = true;
+		// the position of each `[` not yet closed, and of the last one closed at the top level
+		let mut open = Vec::new();
+		let mut last_closed = None;
 
 		loop {
 			match ctx.stream.next() {
 				Some('\n') => {
-					ctx.err("unexpected newline");
+					err_unclosed(ctx, &open, last_closed, "unexpected newline");
 					break;
 				}
 
 				Some('\t') => {
+					// the indentation is left out of the text
+					if verbatim {
+						verbatim = false;
+						text.extend(&ctx.stream.src[start..ctx.stream.index - 1]);
+						offsets.extend(0..ctx.stream.index - 1 - start);
+					}
+
 					ctx.err("unexpected indentation");
 				}
 
+				// raw content keeps its escapes, they only stop brackets from being counted
+				Some('\\') if verbatim && !raw => {
+					verbatim = false;
+					text.extend(&ctx.stream.src[start..ctx.stream.index - 1]);
+					offsets.extend(0..ctx.stream.index - 1 - start);
+					ctx.stream.back();
+				}
 				Some('\\') => match ctx.stream.next() {
 					Some('\n') => {
 						ctx.err("cannot escape newline in this context");
@@ -636,7 +997,14 @@ mod tag {
 						if ch == '\r' {
 							ctx.crlf_explode();
 						}
-						text.push(ch);
+						if !verbatim {
+							if raw {
+								text.push('\\');
+								offsets.push(ctx.stream.index - 2 - start);
+							}
+							text.push(ch);
+							offsets.push(ctx.stream.index - 1 - start);
+						}
 					}
 
 					None => {
@@ -646,13 +1014,21 @@ mod tag {
 				},
 
 				Some('[') => {
-					text.push('[');
-					stack += 1;
+					if !verbatim {
+						text.push('[');
+						offsets.push(ctx.stream.index - 1 - start);
+					}
+					open.push(ctx.stream.index - 1);
 				}
 				Some(']') => {
-					if stack > 0 {
-						text.push(']');
-						stack -= 1;
+					if let Some(at) = open.pop() {
+						if open.is_empty() {
+							last_closed = Some(at);
+						}
+						if !verbatim {
+							text.push(']');
+							offsets.push(ctx.stream.index - 1 - start);
+						}
 					} else {
 						break;
 					}
@@ -662,33 +1038,125 @@ mod tag {
 					if ch == '\r' {
 						ctx.crlf_explode();
 					}
-					text.push(ch);
+					if !verbatim {
+						text.push(ch);
+						offsets.push(ctx.stream.index - 1 - start);
+					}
 				}
 
 				None => {
-					ctx.err("unexpected EOI");
+					err_unclosed(ctx, &open, last_closed, "unexpected EOI");
 					return None;
 				}
 			}
 		}
 
-		Some(Rc::from(text))
+		if verbatim {
+			// the text is exactly the source up to the terminating character
+			let end = ctx.stream.index - 1;
+
+			if let Some((src, base)) = ctx.shared.clone() {
+				return Some((
+					src,
+					base + ctx.byte_offset(start)..base + ctx.byte_offset(end),
+					offsets,
+				));
+			}
+
+			text.extend(&ctx.stream.src[start..end]);
+		}
+
+		let len = text.len();
+		Some((Arc::from(text), 0..len, offsets))
 	}
 
 	/// transform tag text to actual content
+	///
+	/// the definition is resolved here once, and kept in the [`TagInvocation`] for emitting
+	///
+	/// every invocation is counted towards [`MarkDoll::tag_usage`], whether or not the tag is defined, before [`MarkDoll::tag_filter`] decides what to do with it
+	///
+	/// a tag nested deeper than [`MarkDoll::max_tag_depth`] isn't parsed, its content is kept as text at `at` instead, so the nesting stops there
 	pub fn transform_content(
 		ctx: &mut Ctx,
-		args: &[String],
-		text: &str,
-		tag: &String,
-	) -> Option<Box<dyn TagContent>> {
-		if let Some(def) = ctx.doll.ext_system.tags.get(&**tag) {
-			if let Some(parse) = def.parse {
-				(parse)(
-					ctx.doll,
-					args.iter().map(|string| &**string).collect(),
-					text,
-				)
+		at: usize,
+		(args, text): (&[String], &str),
+		tag: &str,
+		form: TagForm,
+	) -> Option<(Arc<TagDefinition>, Box<dyn TagContent>)> {
+		cache::count_usage(ctx.doll, tag);
+
+		// the root source is the first translation, and this tag's content the last
+		if ctx.doll.diagnostic_translations.len() - 1 > ctx.doll.max_tag_depth {
+			ctx.doll.diag(true, usize::MAX, "tags nested too deeply");
+			if !text.is_empty() {
+				ctx.inline.push((at, InlineItem::Text(text.to_string())));
+			}
+			return None;
+		}
+
+		let tag = match ctx.doll.tag_filter.as_ref().map(|filter| {
+			filter.decide(TagFilterEvent {
+				at,
+				tag,
+				args,
+				form,
+			})
+		}) {
+			None | Some(TagFilterAction::Allow) => tag,
+			Some(TagFilterAction::Rename(renamed)) => renamed,
+			Some(TagFilterAction::Deny { reason }) => {
+				let count = ctx.doll.diagnostics.len();
+				ctx.doll.diag(true, usize::MAX, "tag denied");
+
+				// unless it was suppressed
+				if ctx.doll.diagnostics.len() > count {
+					ctx.doll.diagnostics.last_mut().unwrap().data = Some(Data::TagDenied {
+						tag: tag.to_string(),
+						reason,
+					});
+				}
+				return None;
+			}
+		};
+
+		if let Some(def) = ctx.doll.ext_system.get_tag(tag).cloned() {
+			if def.passthrough && ctx.doll.is_untrusted() {
+				ctx.doll
+					.diag(true, usize::MAX, "tag not allowed in untrusted documents");
+				None
+			} else if let Some(parse) = def.parse {
+				// only reported when asked for, as the strict profile does
+				if def.expects_content
+					&& text.trim().is_empty()
+					&& ctx
+						.doll
+						.severity_overrides
+						.contains_key("tag has no content")
+				{
+					let count = ctx.doll.diagnostics.len();
+					ctx.doll.diag(false, usize::MAX, "tag has no content");
+
+					// unless it was suppressed
+					if ctx.doll.diagnostics.len() > count {
+						ctx.doll.diagnostics.last_mut().unwrap().data = Some(Data::EmptyContent {
+							tag: tag.to_string(),
+						});
+					}
+				}
+
+				if let Some(clone) = def.pure.filter(|_| ctx.doll.cache_pure_tags) {
+					cache::parse(ctx.doll, tag, (parse, clone), args, text, form)
+				} else {
+					ctx.doll.tag_cache.impure += 1;
+					(parse)(
+						ctx.doll,
+						args.iter().map(|string| &**string).collect(),
+						text,
+						form,
+					)
+				}
+				.map(|content| (def, content))
 			} else {
 				ctx.doll
 					.diag(true, usize::MAX, "tag does not support content");
@@ -700,8 +1168,12 @@ mod tag {
 		}
 	}
 
-	fn parse_arg(ctx: &mut Ctx) -> ParseResult<String> {
+	pub(super) fn parse_arg(ctx: &mut Ctx) -> ParseResult<String> {
 		let mut arg = String::new();
+		let start = ctx.stream.index;
+		// where each character of the argument was, to report at variables within it
+		let mut positions = Vec::new();
+		let interpolate = ctx.doll.arg_vars.is_some();
 
 		'arg: loop {
 			match ctx.stream.next() {
@@ -714,7 +1186,20 @@ mod tag {
 					ctx.err("unexpected indentation");
 				}
 
-				Some(')') => break 'arg,
+				Some(')') => {
+					// nothing between the parens, kept as an empty argument
+					if ctx.stream.index == start + 1 {
+						let count = ctx.doll.diagnostics.len();
+						ctx.doll.diag(false, start - 1, "empty argument");
+
+						// unless it was suppressed
+						if ctx.doll.diagnostics.len() > count {
+							ctx.doll.diagnostics.last_mut().unwrap().len = 2;
+						}
+					}
+
+					break 'arg;
+				}
 
 				Some('\\') => match ctx.stream.next() {
 					Some('\n') => {
@@ -729,6 +1214,9 @@ mod tag {
 							ctx.crlf_explode();
 						}
 						arg.push(ch);
+						if interpolate {
+							positions.push(ctx.stream.index - 1);
+						}
 					}
 
 					None => {
@@ -742,6 +1230,9 @@ mod tag {
 						ctx.crlf_explode();
 					}
 					arg.push(ch);
+					if interpolate {
+						positions.push(ctx.stream.index - 1);
+					}
 				}
 
 				None => {
@@ -751,13 +1242,74 @@ mod tag {
 			}
 		}
 
+		if interpolate && arg.contains("${") {
+			arg = interpolate_arg(ctx, &arg, &positions);
+		}
+
 		ParseResult::Ok(arg)
 	}
 
+	/// replace each `${key}` in an argument with the value of `key` in [`MarkDoll::arg_vars`], given where each of its characters was
+	///
+	/// keys that aren't defined are warned about and left as written, and `$${` writes `${`
+	fn interpolate_arg(ctx: &mut Ctx, arg: &str, positions: &[usize]) -> String {
+		let chars = arg.chars().collect::<Vec<_>>();
+		let mut out = String::with_capacity(arg.len());
+
+		let mut i = 0;
+		while i < chars.len() {
+			match chars[i..] {
+				['$', '$', '{', ..] => {
+					out.push_str("${");
+					i += 3;
+				}
+				['$', '{', ..] => {
+					let Some(len) = chars[i + 2..].iter().position(|ch| *ch == '}') else {
+						out.extend(&chars[i..]);
+						break;
+					};
+					let end = i + 2 + len;
+
+					let key = chars[i + 2..end].iter().collect::<String>();
+					let value = ctx
+						.doll
+						.arg_vars
+						.as_ref()
+						.and_then(|vars| vars.get(key.trim()))
+						.cloned();
+
+					if let Some(value) = value {
+						out.push_str(&value);
+					} else {
+						let count = ctx.doll.diagnostics.len();
+						ctx.doll.diag(false, positions[i], "variable not defined");
+
+						// unless it was suppressed
+						if ctx.doll.diagnostics.len() > count {
+							ctx.doll.diagnostics.last_mut().unwrap().len =
+								positions[end] - positions[i] + 1;
+						}
+
+						out.extend(&chars[i..=end]);
+					}
+
+					i = end + 1;
+				}
+				_ => {
+					out.push(chars[i]);
+					i += 1;
+				}
+			}
+		}
+
+		out
+	}
+
 	fn parse_content(
 		ctx: &mut Ctx,
 		tag: String,
 		args: Vec<String>,
+		arg_positions: Vec<usize>,
 		start: usize,
 		indent_level: usize,
 	) -> ParseResult {
@@ -776,7 +1328,8 @@ mod tag {
 				ctx.stack.push(StackPart::TagBlockContent {
 					tag,
 					args,
-					text: String::new(),
+					arg_positions,
+					lines: Vec::new(),
 					tag_at: start,
 					offset_in_parent: ctx.stream.index + 1,
 					indent: indent_level + 1,
@@ -798,6 +1351,14 @@ mod tag {
 							ctx.err("unexpected EOI");
 							return ParseResult::Stop;
 						}
+
+						// the content starts on the next line
+						if let Some(StackPart::TagBlockContent {
+							offset_in_parent, ..
+						}) = ctx.stack.last_mut()
+						{
+							*offset_in_parent = ctx.stream.index;
+						}
 					}
 
 					None => {
@@ -816,27 +1377,40 @@ mod tag {
 
 				let offset_in_parent = ctx.stream.index;
 
-				if let Some(text) = parse_inline_text(ctx) {
+				let raw = ctx.doll.ext_system.get_tag(&tag).is_some_and(|def| def.raw);
+
+				if let Some((text, range, char_offsets)) = parse_inline_text(ctx, raw) {
+					ctx.add_source(&text, start);
+
 					ctx.doll
 						.diagnostic_translations
 						.push(TagDiagnosticTranslation {
-							src: Rc::clone(&text),
-							indexed: None,
+							src: Arc::clone(&text),
+							range: range.clone(),
+							indexed: (!char_offsets.is_empty())
+								.then(|| IndexedSrc::runs(&char_offsets, offset_in_parent)),
+							char_index: None,
 							offset_in_parent,
-							tag_pos_in_parent: start,
+							tag_pos_in_parent: start - 1,
+							tag_end_in_parent: ctx.stream.index,
 							indent: 0,
+							args: arg_positions,
 						});
 
-					if let Some(content) = transform_content(ctx, &args, &text, &tag) {
+					if let Some((def, content)) =
+						transform_content(ctx, start, (&args, &text[range]), &tag, TagForm::Inline)
+					{
 						ctx.inline.push((
 							start,
 							InlineItem::Tag(TagInvocation {
 								tag,
+								def,
 								args,
 								content,
 								diagnostic_translation: Some(
 									ctx.doll.diagnostic_translations.pop().unwrap(),
 								),
+								generation: ctx.doll.generation,
 							}),
 						));
 					} else {
@@ -854,10 +1428,175 @@ mod tag {
 		ParseResult::Ok(())
 	}
 
+	/// a closer looked for to the end of its line and not found, so it isn't looked for again from a later opener on that line
+	#[derive(Debug)]
+	pub struct CloserMiss {
+		close: char,
+		from: usize,
+		/// whether each character from `from` on was outside of brackets, where looking from it would find no more than looking from `from` did
+		outside: Vec<bool>,
+	}
+
+	/// where the closer of a [`Shorthand`](crate::tree::Shorthand) opened just before `from` is on the same line, skipping escapes and brackets
+	fn find_closer(
+		src: &[char],
+		from: usize,
+		close: char,
+		misses: &mut Vec<CloserMiss>,
+	) -> Option<usize> {
+		if !src.get(from).is_some_and(|ch| !ch.is_whitespace()) {
+			return None;
+		}
+
+		if misses.iter().any(|miss| {
+			miss.close == close
+				&& from >= miss.from
+				&& miss.outside.get(from - miss.from) == Some(&true)
+		}) {
+			return None;
+		}
+
+		let mut stack: usize = 0;
+		let mut index = from;
+		let mut outside = Vec::new();
+
+		while let Some(&ch) = src.get(index) {
+			// an escaped character is left as inside, as looking from it would not see it as escaped
+			outside.resize(index - from, false);
+			outside.push(stack == 0);
+
+			match ch {
+				'\n' => break,
+				'\\' => index += 1,
+				'[' => stack += 1,
+				']' => stack = stack.saturating_sub(1),
+				_ if ch == close
+					&& stack == 0 && index > from
+					&& !src[index - 1].is_whitespace()
+					&& !src.get(index + 1).is_some_and(|ch| ch.is_alphanumeric()) =>
+				{
+					return Some(index);
+				}
+				_ => {}
+			}
+
+			index += 1;
+		}
+
+		misses.retain(|miss| miss.close != close);
+		misses.push(CloserMiss {
+			close,
+			from,
+			outside,
+		});
+
+		None
+	}
+
+	/// the closer, tag, and arguments of a [`Shorthand`](crate::tree::Shorthand) opened by the character just before the stream, if there is one
+	pub fn find_shorthand(ctx: &mut Ctx) -> Option<(usize, String, Vec<String>)> {
+		let start = ctx.stream.index - 1;
+		let open = ctx.stream.src[start];
+
+		if start > 0 && ctx.stream.src[start - 1].is_alphanumeric() {
+			return None;
+		}
+
+		ctx.doll
+			.shorthands
+			.iter()
+			.filter(|shorthand| shorthand.open == open)
+			.find_map(|shorthand| {
+				find_closer(
+					&ctx.stream.src,
+					start + 1,
+					shorthand.close,
+					&mut ctx.closer_misses,
+				)
+				.map(|end| (end, shorthand.tag.clone(), shorthand.args.clone()))
+			})
+	}
+
+	/// parse a [`Shorthand`](crate::tree::Shorthand) found by [`find_shorthand`], leaving the stream after its closer
+	pub fn shorthand(ctx: &mut Ctx, (end, tag, args): (usize, String, Vec<String>)) {
+		let start = ctx.stream.index - 1;
+
+		let raw = ctx.doll.ext_system.get_tag(&tag).is_some_and(|def| def.raw);
+
+		// the same text as between the brackets of a tag
+		let content = &ctx.stream.src[start + 1..end];
+		let (text, range, char_offsets) = if raw || !content.contains(&'\\') {
+			if let Some((src, base)) = ctx.shared.clone() {
+				(
+					src,
+					base + ctx.byte_offset(start + 1)..base + ctx.byte_offset(end),
+					Vec::new(),
+				)
+			} else {
+				let text = content.iter().collect::<String>();
+				let len = text.len();
+				(Arc::from(text), 0..len, Vec::new())
+			}
+		} else {
+			let mut text = String::with_capacity(content.len());
+			let mut offsets = Vec::with_capacity(content.len());
+			let mut chars = content.iter().enumerate();
+			while let Some((i, &ch)) = chars.next() {
+				let (i, ch) = if ch == '\\' {
+					let (i, ch) = chars.next().unwrap();
+					(i, *ch)
+				} else {
+					(i, ch)
+				};
+				text.push(ch);
+				offsets.push(i);
+			}
+			let len = text.len();
+			(Arc::from(text), 0..len, offsets)
+		};
+
+		ctx.stream.index = end + 1;
+		ctx.add_source(&text, start);
+
+		ctx.doll
+			.diagnostic_translations
+			.push(TagDiagnosticTranslation {
+				src: Arc::clone(&text),
+				range: range.clone(),
+				indexed: (!char_offsets.is_empty())
+					.then(|| IndexedSrc::runs(&char_offsets, start + 1)),
+				char_index: None,
+				offset_in_parent: start + 1,
+				tag_pos_in_parent: start,
+				tag_end_in_parent: ctx.stream.index,
+				indent: 0,
+				args: Vec::new(),
+			});
+
+		if let Some((def, content)) =
+			transform_content(ctx, start, (&args, &text[range]), &tag, TagForm::Inline)
+		{
+			ctx.inline.push((
+				start,
+				InlineItem::Tag(TagInvocation {
+					tag,
+					def,
+					args,
+					content,
+					diagnostic_translation: Some(ctx.doll.diagnostic_translations.pop().unwrap()),
+					generation: ctx.doll.generation,
+				}),
+			));
+		} else {
+			ctx.doll.diagnostic_translations.pop().unwrap();
+		}
+	}
+
 	pub fn parse(ctx: &mut Ctx, indent_level: usize) -> ParseResult {
 		let start = ctx.stream.index;
 		let mut tag = String::with_capacity(16);
 		let mut args = Vec::new();
+		let mut arg_positions = Vec::new();
 
 		'tag: loop {
 			match ctx.stream.next() {
@@ -870,39 +1609,55 @@ mod tag {
 					ctx.err("unexpected indentation");
 				}
 
-				Some('(') => match parse_arg(ctx) {
-					ParseResult::Ok(arg) => args.push(arg),
-					ParseResult::NextLine => return ParseResult::NextLine,
-					ParseResult::Stop => return ParseResult::Stop,
-				},
+				Some('(') => {
+					let at = ctx.stream.index;
 
-				Some(':') => match parse_content(ctx, tag, args, start, indent_level) {
-					ParseResult::Ok(()) => break 'tag,
-					ParseResult::NextLine => return ParseResult::NextLine,
-					ParseResult::Stop => return ParseResult::Stop,
-				},
+					match parse_arg(ctx) {
+						ParseResult::Ok(arg) => {
+							args.push(arg);
+							arg_positions.push(at);
+						}
+						ParseResult::NextLine => return ParseResult::NextLine,
+						ParseResult::Stop => return ParseResult::Stop,
+					}
+				}
+
+				Some(':') => {
+					match parse_content(ctx, tag, args, arg_positions, start, indent_level) {
+						ParseResult::Ok(()) => break 'tag,
+						ParseResult::NextLine => return ParseResult::NextLine,
+						ParseResult::Stop => return ParseResult::Stop,
+					}
+				}
 
 				Some(']') => {
 					ctx.doll
 						.diagnostic_translations
 						.push(TagDiagnosticTranslation {
-							src: Rc::default(),
-							indexed: None,
-							offset_in_parent: ctx.stream.index - 1,
-							tag_pos_in_parent: start,
-							indent: 0,
+							args: arg_positions,
+							tag_end_in_parent: ctx.stream.index,
+							..TagDiagnosticTranslation::new(
+								Arc::default(),
+								ctx.stream.index - 1,
+								start - 1,
+								0,
+							)
 						});
 
-					if let Some(content) = transform_content(ctx, &args, "", &tag) {
+					if let Some((def, content)) =
+						transform_content(ctx, start, (&args, ""), &tag, TagForm::Empty)
+					{
 						ctx.inline.push((
 							start,
 							InlineItem::Tag(TagInvocation {
 								tag,
+								def,
 								args,
 								content,
 								diagnostic_translation: Some(
 									ctx.doll.diagnostic_translations.pop().unwrap(),
 								),
+								generation: ctx.doll.generation,
 							}),
 						));
 					} else {
@@ -945,12 +1700,52 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 		None
 	};
 
+	// a document of only whitespace is empty, rather than misplaced indentation or spaces
+	if ctx.document
+		&& ctx
+			.stream
+			.src
+			.get(ctx.stream.index..)
+			.is_some_and(|rest| rest.iter().all(|ch| ch.is_whitespace()))
+	{
+		ctx.stream.index = ctx.stream.src.len();
+	}
+
+	// tag content is part of the root source, so it is only checked once
+	if ctx.doll.diagnostic_translations.len() <= 1 {
+		// carriage returns are reported on their own
+		for (i, ch) in ctx.stream.src.iter().enumerate() {
+			if *ch != '\r' && is_hidden_control(*ch) {
+				ctx.doll.diag(false, i, "control character");
+			}
+		}
+	}
+
+	// set on the instance rather than the context, so tag content parsed along the way follows it too
+	let hard_breaks = ctx.doll.hard_breaks;
+	if frontmatter.as_deref().is_some_and(wants_hard_breaks) {
+		ctx.doll.hard_breaks = true;
+	}
+	let arg_vars = ctx.doll.arg_vars.clone();
+	if let (Some(vars), Some(frontmatter)) = (&mut ctx.doll.arg_vars, &frontmatter) {
+		ext::var::frontmatter_vars(vars, frontmatter);
+	}
+
 	// significance tracks functionally-empty lines, splitting paragraphs at functionally-empty lines
 	let mut last_significant = false;
 
 	'main: loop {
 		t!("---- new line ----");
 
+		// the rest is left unparsed, and a tag containing this source stops its parent too
+		ctx.doll.check_cancelled(ctx.stream.index);
+		if ctx.doll.should_stop_parsing() {
+			ctx.stream.index = ctx.stream.src.len();
+			break 'main;
+		}
+
+		ctx.report_progress(false);
+
 		// parse indentation
 		let indent_level: usize = match indent::parse(&mut ctx, &mut last_significant) {
 			ParseResult::Ok(indent_level) => indent_level,
@@ -971,11 +1766,31 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 					t!("[[[flush section]]]");
 					ctx.flush_inline();
 
+					let mut props = Vec::new();
+					while ctx.stream.lookahead(1) == Some('(')
+						&& is_section_prop(&ctx.stream.src[ctx.stream.index + 1..])
+					{
+						ctx.stream.skip();
+						match tag::parse_arg(&mut ctx) {
+							ParseResult::Ok(prop) => props.push(prop),
+							ParseResult::NextLine | ParseResult::Stop => break,
+						}
+					}
+					let meta = section_meta(props);
+					if ctx.stream.lookahead(1) == Some('\\') && ctx.stream.lookahead(2) == Some('(')
+					{
+						// a name beginning with what would be a prop escapes its parenthesis
+						ctx.stream.skip();
+					} else if !meta.is_empty() && !ctx.stream.try_eat('\t') {
+						// a tab or space may separate the props from the name
+						ctx.stream.try_eat(' ');
+					}
+
 					let mut name = String::with_capacity(16);
 
 					'name: loop {
 						match ctx.stream.next() {
-							Some('\n') => break 'name,
+							Some('\n') | None => break 'name,
 
 							Some('\t') => {
 								ctx.err("unexpected indentation");
@@ -987,14 +1802,17 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 								}
 								name.push(ch);
 							}
-
-							None => break 'main,
 						}
 					}
 
+					if name.trim().is_empty() {
+						ctx.doll.diag(false, start, "section has no name");
+					}
+
 					ctx.stack.push(StackPart::Section {
 						pos: start,
 						name,
+						meta,
 						children: Vec::new(),
 					});
 
@@ -1004,12 +1822,22 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 				// line loop
 				loop {
 					match ctx.stream.next() {
-						Some('\n') => continue 'main,
+						Some('\n') => {
+							// a line ending with a tag, as text pushes its own break
+							if ctx.doll.hard_breaks
+								&& matches!(ctx.inline.last(), Some((_, InlineItem::Tag(_))))
+							{
+								ctx.inline.push((ctx.stream.index, InlineItem::Break));
+							}
+
+							continue 'main;
+						}
 
 						Some('\t') => ctx.err("unexpected indentation"),
 
 						// parse a tag invocation
 						Some('[') => match tag::parse(&mut ctx, indent_level) {
+							ParseResult::Ok(()) if ctx.doll.should_stop_parsing() => break 'main,
 							ParseResult::Ok(()) => {}
 							ParseResult::NextLine => continue 'main,
 							ParseResult::Stop => break 'main,
@@ -1031,7 +1859,14 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 								match ctx.stream.next() {
 									Some('\n') => {
 										ctx.inline.push((start, InlineItem::Text(text)));
-										ctx.inline.push((ctx.stream.index, InlineItem::Split));
+										ctx.inline.push((
+											ctx.stream.index,
+											if ctx.doll.hard_breaks {
+												InlineItem::Break
+											} else {
+												InlineItem::Split
+											},
+										));
 
 										continue 'main;
 									}
@@ -1043,6 +1878,7 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 										Some('\n') => {
 											ctx.inline.push((start, InlineItem::Text(text)));
 											ctx.inline.push((ctx.stream.index, InlineItem::Break));
+											ctx.explicit_break = Some(ctx.stream.index);
 
 											continue 'main;
 										}
@@ -1073,6 +1909,24 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 										break 'text;
 									}
 
+									Some(ch)
+										if ctx
+											.doll
+											.shorthands
+											.iter()
+											.any(|shorthand| shorthand.open == ch) =>
+									{
+										if let Some(shorthand) = tag::find_shorthand(&mut ctx) {
+											if !text.is_empty() {
+												ctx.inline.push((start, InlineItem::Text(text)));
+											}
+											tag::shorthand(&mut ctx, shorthand);
+											break 'text;
+										}
+
+										text.push(ch);
+									}
+
 									Some(ch) => {
 										if ch == '\r' {
 											ctx.crlf_explode();
@@ -1093,28 +1947,29 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 				}
 			}
 			// special, just insert content straight into the tag
-			StackPart::TagBlockContent { text: content, .. } => {
+			StackPart::TagBlockContent { lines, .. } => {
 				let mut warn_cr = false;
+				let start = ctx.stream.index;
 
 				'line: loop {
 					match ctx.stream.next() {
-						Some('\n') => {
-							content.push('\n');
-							break 'line;
-						}
+						Some('\n') => break 'line,
 
 						Some(ch) => {
-							content.push(ch);
-
 							if ch == '\r' {
 								warn_cr = true;
 							}
 						}
 
-						None => break 'main,
+						None => {
+							lines.push(start..ctx.stream.index);
+							break 'main;
+						}
 					}
 				}
 
+				lines.push(start..ctx.stream.index);
+
 				if warn_cr {
 					ctx.crlf_explode();
 				}
@@ -1124,12 +1979,12 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 
 	while ctx.stack.len() > 1 {
 		let top = ctx.stack.last().unwrap();
-		if top.can_gracefully_terminate() {
+		if top.can_gracefully_terminate() || ctx.doll.should_stop_parsing() {
 			t!("[[[flush/term gracefully]]]");
 			ctx.flush_inline();
 			ctx.stack_terminate_top();
 		} else {
-			ctx.err(top.unterminated());
+			ctx.err_unterminated(ctx.stream.index.saturating_sub(1));
 			t!("[[[flush/term non-gracefully]]]");
 			ctx.flush_inline();
 			ctx.stack_terminate_top();
@@ -1144,5 +1999,9 @@ pub(crate) fn parse(mut ctx: Ctx) -> Result<(Option<String>, AST), (Option<Strin
 
 	t!("---- end parse ----");
 
+	ctx.doll.hard_breaks = hard_breaks;
+	ctx.doll.arg_vars = arg_vars;
+	ctx.report_progress(true);
+
 	(if ctx.doll.ok { Ok } else { Err })((frontmatter, ast))
 }
@@ -0,0 +1,553 @@
+use {
+	crate::{
+		tree::{BlockItem, InlineItem, TagInvocation, AST},
+		MarkDoll,
+	},
+	alloc::{
+		format,
+		string::{String, ToString},
+		vec::Vec,
+	},
+	core::fmt::Write,
+};
+
+/// options for printing an [`AST`] back to markdoll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+	/// tag content longer than this many characters is printed in block form instead of inline, if the tag ends its paragraph and isn't in a list
+	///
+	/// content containing newlines or tabs is always printed in block form
+	pub max_inline_len: usize,
+}
+
+impl Default for PrintOptions {
+	fn default() -> Self {
+		Self { max_inline_len: 80 }
+	}
+}
+
+/// print an [`AST`] back to markdoll source
+///
+/// tag content is printed with the tag's [`print`](crate::ext::TagDefinition::print) hook, or with the source it was parsed from if it has none
+///
+/// # note
+///
+/// some trees can't be expressed exactly, such as multiline tag content followed by more inline items, which become a new paragraph
+#[must_use]
+pub fn to_doll(doll: &MarkDoll, ast: &AST, options: PrintOptions) -> String {
+	let mut printer = Printer {
+		doll,
+		options,
+		out: String::new(),
+	};
+
+	printer.ast(ast, "", "", false);
+
+	printer.out
+}
+
+/// print a document back to markdoll source, including frontmatter
+#[must_use]
+pub fn to_doll_document(
+	doll: &MarkDoll,
+	frontmatter: Option<&str>,
+	ast: &AST,
+	options: PrintOptions,
+) -> String {
+	let mut out = String::new();
+
+	if let Some(frontmatter) = frontmatter {
+		write!(out, "---\n{frontmatter}\n---\n").unwrap();
+	}
+
+	out.push_str(&to_doll(doll, ast, options));
+
+	out
+}
+
+struct Printer<'a> {
+	doll: &'a MarkDoll,
+	options: PrintOptions,
+	out: String,
+}
+
+impl Printer<'_> {
+	/// print blocks, the first line with the `first` prefix and the rest with the `rest` prefix
+	fn ast(&mut self, ast: &AST, first: &str, rest: &str, in_list: bool) {
+		for (i, block) in ast.iter().enumerate() {
+			if i > 0 {
+				self.out.push('\n');
+
+				// blank lines would end a list, so only use them when they're needed to separate blocks
+				let needs_blank = match (&ast[i - 1], block) {
+					(BlockItem::Inline(_), BlockItem::Inline(_)) => true,
+					(BlockItem::List { ordered: a, .. }, BlockItem::List { ordered: b, .. }) => {
+						a == b
+					}
+					_ => !in_list,
+				};
+
+				if needs_blank {
+					self.out.push('\n');
+				}
+			}
+
+			self.block(block, if i == 0 { first } else { rest }, rest, in_list);
+		}
+	}
+
+	fn block(&mut self, block: &BlockItem, first: &str, rest: &str, in_list: bool) {
+		match block {
+			BlockItem::Inline(items) => {
+				self.out.push_str(first);
+				self.inline(items, rest, in_list);
+			}
+			BlockItem::Section {
+				name,
+				meta,
+				children,
+				..
+			} => {
+				self.out.push_str(first);
+				self.out.push('&');
+
+				if !meta.is_empty() {
+					if let Some(id) = &meta.id {
+						self.arg(&format!("id={id}"));
+					}
+					if let Some(class) = &meta.class {
+						self.arg(&format!("class={class}"));
+					}
+					if meta.unlisted {
+						self.arg("unlisted");
+					}
+					self.out.push('\t');
+				} else if name.starts_with('(') {
+					// so it isn't read as a prop
+					self.out.push('\\');
+				}
+
+				self.out.push_str(name);
+
+				if !children.is_empty() {
+					self.out.push('\n');
+
+					let indent = format!("{rest}\t");
+					self.ast(children, &indent, &indent, false);
+				}
+			}
+			BlockItem::List { ordered, items, .. } => {
+				let indent = format!("{rest}\t");
+
+				for (i, item) in items.iter().enumerate() {
+					if i > 0 {
+						self.out.push('\n');
+					}
+
+					let prefix = if i == 0 { first } else { rest };
+					let marker = match item.value {
+						Some(value) if *ordered => format!("={value}.\t"),
+						_ if *ordered => "=\t".to_string(),
+						_ => "-\t".to_string(),
+					};
+
+					if item.ast.is_empty() {
+						write!(self.out, "{prefix}{marker}").unwrap();
+					} else {
+						self.ast(&item.ast, &format!("{prefix}{marker}"), &indent, true);
+					}
+				}
+			}
+		}
+	}
+
+	fn inline(&mut self, items: &[(usize, InlineItem)], rest: &str, in_list: bool) {
+		let mut line_start = true;
+		// whether the last item was a tag in block form, which already ended the line
+		let mut after_block = false;
+
+		for (i, (_, item)) in items.iter().enumerate() {
+			match item {
+				InlineItem::Split if core::mem::take(&mut after_block) => {}
+				InlineItem::Split => {
+					self.out.push('\n');
+					self.out.push_str(rest);
+					line_start = true;
+				}
+				// a break ending the paragraph escapes the newline the paragraph ends with
+				InlineItem::Break if i + 1 == items.len() => self.out.push('\\'),
+				InlineItem::Break => {
+					self.out.push_str("\\\n");
+					self.out.push_str(rest);
+					line_start = true;
+				}
+				InlineItem::Text(text) => {
+					for (i, ch) in text.chars().enumerate() {
+						let escape = match ch {
+							'\\' | '[' | ']' => true,
+							// would start a section, list, or be taken as indentation
+							'&' | '-' | '=' | ' ' => i == 0 && line_start,
+							_ => false,
+						};
+
+						if escape {
+							self.out.push('\\');
+						}
+						self.out.push(ch);
+					}
+
+					line_start &= text.is_empty();
+				}
+				InlineItem::Tag(tag) => {
+					let last = i + 1 == items.len();
+
+					// the rest of the line after a tag in block form is parsed as though it were unindented, so it must end the line
+					//
+					// the line also counts as empty, ending the list it's in, so long content in lists is kept inline
+					after_block = self.tag(tag, rest, last && !in_list) && !last;
+					if after_block {
+						self.out.push('\n');
+						self.out.push_str(rest);
+					}
+
+					line_start = after_block;
+					continue;
+				}
+			}
+
+			after_block = false;
+		}
+	}
+
+	/// print a tag, returning whether it was printed in block form
+	///
+	/// `allow_block` allows printing content in block form just for being long
+	fn tag(&mut self, tag: &TagInvocation, rest: &str, allow_block: bool) -> bool {
+		self.out.push('[');
+		self.out.push_str(&tag.tag);
+
+		for arg in &tag.args {
+			self.arg(arg);
+		}
+
+		let content = self.content(tag);
+
+		if content.is_empty() {
+			self.out.push(']');
+
+			return false;
+		}
+
+		// a tag in block form must end its line, so prefer inline form when possible
+		let inline = !content.contains(['\n', '\t'])
+			&& (!allow_block || content.chars().count() <= self.options.max_inline_len)
+			&& (!tag.def.raw || raw_inline(&content));
+
+		if inline {
+			self.out.push(':');
+			if tag.def.raw {
+				self.out.push_str(&content);
+			} else {
+				self.inline_content(&content);
+			}
+			self.out.push(']');
+
+			false
+		} else {
+			self.out.push_str("::\n");
+
+			for line in content.split('\n') {
+				if !line.is_empty() {
+					self.out.push_str(rest);
+					self.out.push('\t');
+					self.out.push_str(line);
+				}
+				self.out.push('\n');
+			}
+
+			self.out.push_str(rest);
+			self.out.push(']');
+
+			true
+		}
+	}
+
+	/// the content of a tag, as printed by its [`print`](crate::ext::TagDefinition::print) hook or as it was written
+	fn content(&self, tag: &TagInvocation) -> String {
+		match tag.def.print {
+			Some(print) => print(self.doll, &*tag.content, self.options),
+			None => tag
+				.diagnostic_translation
+				.as_ref()
+				.map(|translation| translation.text().to_string())
+				.unwrap_or_default(),
+		}
+	}
+
+	/// write an argument, or a prop of a section, in parentheses
+	fn arg(&mut self, arg: &str) {
+		self.out.push('(');
+		for ch in arg.chars() {
+			if matches!(ch, '\\' | ')') {
+				self.out.push('\\');
+			}
+			self.out.push(ch);
+		}
+		self.out.push(')');
+	}
+
+	/// write tag content in inline form, which is unescaped once before being parsed
+	fn inline_content(&mut self, content: &str) {
+		// brackets are only counted when unescaped, so balanced ones may be left as they are
+		let mut depth = 0_usize;
+		let balanced = content.chars().all(|ch| match ch {
+			'[' => {
+				depth += 1;
+				true
+			}
+			']' => depth.checked_sub(1).map(|new| depth = new).is_some(),
+			_ => true,
+		}) && depth == 0;
+
+		for (i, ch) in content.chars().enumerate() {
+			let escape = match ch {
+				'\\' => true,
+				'[' | ']' => !balanced,
+				// would start block form
+				':' => i == 0,
+				_ => false,
+			};
+
+			if escape {
+				self.out.push('\\');
+			}
+			self.out.push(ch);
+		}
+	}
+}
+
+/// whether raw content, which keeps its escapes, can be written in inline form as it is
+fn raw_inline(content: &str) -> bool {
+	if content.starts_with(':') {
+		return false;
+	}
+
+	let mut depth = 0_usize;
+	let mut chars = content.chars();
+
+	while let Some(ch) = chars.next() {
+		match ch {
+			'\\' if chars.next().is_none() => return false,
+			'[' => depth += 1,
+			']' => match depth.checked_sub(1) {
+				Some(new) => depth = new,
+				None => return false,
+			},
+			_ => {}
+		}
+	}
+
+	depth == 0
+}
+
+/// where two [`AST`]s first differ, see [`first_difference`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+	/// the path to the node that differs, such as `block 2 > item 1 > block 0 > inline 3`
+	pub path: String,
+	/// the node in each tree, described, or `None` if that tree has nothing there
+	pub nodes: [Option<String>; 2],
+	/// where the node, or the closest node containing it, is in the source of each tree
+	pub at: [usize; 2],
+}
+
+/// find the first node that differs between two [`AST`]s, such as one and the [`AST`] of it printed and parsed again, returning `None` if they are the same
+///
+/// compares as [`content_fingerprint`](MarkDoll::content_fingerprint) hashes, leaving out positions, except that tag content is compared as it is printed by [`to_doll`]. tag content is compared as a whole, so a difference within it is reported at the tag
+#[must_use]
+pub fn first_difference(
+	doll: &MarkDoll,
+	a: &AST,
+	b: &AST,
+	options: PrintOptions,
+) -> Option<Difference> {
+	let printer = Printer {
+		doll,
+		options,
+		out: String::new(),
+	};
+
+	let mut path = Vec::new();
+	printer.compare_ast([a, b], [0, 0], &mut path)
+}
+
+impl Printer<'_> {
+	/// compare blocks, `at` being where the nodes containing them are
+	fn compare_ast(
+		&self,
+		asts: [&AST; 2],
+		at: [usize; 2],
+		path: &mut Vec<String>,
+	) -> Option<Difference> {
+		for i in 0..asts[0].len().max(asts[1].len()) {
+			path.push(format!("block {i}"));
+
+			let blocks = asts.map(|ast| ast.get(i));
+			// the last block, where one tree has fewer
+			let at = [0, 1].map(|side| {
+				blocks[side]
+					.or(asts[side].last())
+					.and_then(BlockItem::pos)
+					.unwrap_or(at[side])
+			});
+
+			let difference = match blocks {
+				[Some(BlockItem::Inline(a)), Some(BlockItem::Inline(b))] => {
+					self.compare_inline([a, b], at, path)
+				}
+				[Some(BlockItem::Section {
+					name: a_name,
+					meta: a_meta,
+					children: a,
+					..
+				}), Some(BlockItem::Section {
+					name: b_name,
+					meta: b_meta,
+					children: b,
+					..
+				})] if a_name == b_name && a_meta == b_meta => self.compare_ast([a, b], at, path),
+				[Some(BlockItem::List {
+					ordered: a_ordered,
+					items: a,
+					..
+				}), Some(BlockItem::List {
+					ordered: b_ordered,
+					items: b,
+					..
+				})] if a_ordered == b_ordered => (0..a.len().max(b.len())).find_map(|i| {
+					path.push(format!("item {i}"));
+
+					let items = [a.get(i), b.get(i)];
+					let lists = [a, b];
+					let at = [0, 1].map(|side| {
+						items[side]
+							.or(lists[side].last())
+							.map_or(at[side], |item| item.pos)
+					});
+
+					let difference = match items {
+						[Some(a), Some(b)] if a.value == b.value => {
+							self.compare_ast([&a.ast, &b.ast], at, path)
+						}
+						_ => Some(difference(
+							path,
+							items.map(|item| item.map(|item| describe_item(item.value))),
+							at,
+						)),
+					};
+
+					path.pop();
+					difference
+				}),
+				_ => Some(difference(
+					path,
+					blocks.map(|block| block.map(describe_block)),
+					at,
+				)),
+			};
+
+			path.pop();
+			if difference.is_some() {
+				return difference;
+			}
+		}
+
+		None
+	}
+
+	/// compare the inline items of a paragraph
+	fn compare_inline(
+		&self,
+		paragraphs: [&[(usize, InlineItem)]; 2],
+		at: [usize; 2],
+		path: &mut Vec<String>,
+	) -> Option<Difference> {
+		for i in 0..paragraphs[0].len().max(paragraphs[1].len()) {
+			let items = paragraphs.map(|items| items.get(i));
+			let at = [0, 1].map(|side| {
+				items[side]
+					.or(paragraphs[side].last())
+					.map_or(at[side], |(pos, _)| *pos)
+			});
+
+			let same = match items {
+				[Some((_, InlineItem::Split)), Some((_, InlineItem::Split))]
+				| [Some((_, InlineItem::Break)), Some((_, InlineItem::Break))] => true,
+				[Some((_, InlineItem::Text(a))), Some((_, InlineItem::Text(b)))] => a == b,
+				[Some((_, InlineItem::Tag(a))), Some((_, InlineItem::Tag(b)))] => {
+					a.tag == b.tag && a.args == b.args && self.content(a) == self.content(b)
+				}
+				_ => false,
+			};
+
+			if !same {
+				path.push(format!("inline {i}"));
+				let difference = difference(
+					path,
+					items.map(|item| item.map(|(_, item)| self.describe_inline(item))),
+					at,
+				);
+				path.pop();
+
+				return Some(difference);
+			}
+		}
+
+		None
+	}
+
+	/// describe an inline item, with the content of tags as printed
+	fn describe_inline(&self, item: &InlineItem) -> String {
+		match item {
+			InlineItem::Split => "split".to_string(),
+			InlineItem::Break => "break".to_string(),
+			InlineItem::Text(text) => format!("text {text:?}"),
+			InlineItem::Tag(tag) => format!(
+				"tag {:?} with arguments {:?} and content {:?}",
+				tag.tag,
+				tag.args,
+				self.content(tag)
+			),
+		}
+	}
+}
+
+fn difference(path: &[String], nodes: [Option<String>; 2], at: [usize; 2]) -> Difference {
+	Difference {
+		path: path.join(" > "),
+		nodes,
+		at,
+	}
+}
+
+/// describe a block, without its content
+fn describe_block(block: &BlockItem) -> String {
+	match block {
+		BlockItem::Inline(items) => format!("paragraph of {} items", items.len()),
+		BlockItem::Section { name, meta, .. } if meta.is_empty() => format!("section {name:?}"),
+		BlockItem::Section { name, meta, .. } => format!("section {name:?} with {meta:?}"),
+		BlockItem::List { ordered, items, .. } => format!(
+			"{} list of {} items",
+			if *ordered { "ordered" } else { "unordered" },
+			items.len()
+		),
+	}
+}
+
+/// describe a list item, without its content
+fn describe_item(value: Option<u64>) -> String {
+	match value {
+		Some(value) => format!("item numbered {value}"),
+		None => "item".to_string(),
+	}
+}
@@ -0,0 +1,60 @@
+use alloc::{
+	string::{String, ToString},
+	vec::Vec,
+};
+
+#[rustfmt::skip] // see tree/parser.rs
+use alloc::vec;
+
+/// a rule turning text between a pair of delimiters into a tag, such as `*bold*` into `[em(b):bold]`, registered in [`MarkDoll::shorthands`](crate::MarkDoll::shorthands)
+///
+/// the opener must be at the start of a word and followed by something other than whitespace, and the closer must follow something other than whitespace and end a word, on the same line. the text between them is the content of the tag, as if written in its brackets
+///
+/// an opener without a closer is left as text, and an escaped delimiter, such as `\*`, never opens or closes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shorthand {
+	/// the delimiter before the content
+	pub open: char,
+	/// the delimiter after the content
+	pub close: char,
+	/// the name of the tag to invoke
+	pub tag: String,
+	/// the arguments to the tag
+	pub args: Vec<String>,
+}
+
+impl Shorthand {
+	/// a rule for text between `delimiter`s
+	#[must_use]
+	pub fn new(delimiter: char, tag: impl Into<String>) -> Self {
+		Self {
+			open: delimiter,
+			close: delimiter,
+			tag: tag.into(),
+			args: Vec::new(),
+		}
+	}
+
+	/// `*bold*`, as [`em(b)`](crate::ext::formatting::emphasis)
+	#[must_use]
+	pub fn bold() -> Self {
+		Self {
+			args: vec!["b".to_string()],
+			..Self::new('*', "em")
+		}
+	}
+
+	/// `/italic/`, as [`em`](crate::ext::formatting::emphasis)
+	///
+	/// slashes are common in paths and URLs, which only stay text if they don't happen to form a word between slashes
+	#[must_use]
+	pub fn italic() -> Self {
+		Self::new('/', "em")
+	}
+
+	/// `` `code` ``, as [`code`](crate::ext::code::code)
+	#[must_use]
+	pub fn code() -> Self {
+		Self::new('`', "code")
+	}
+}
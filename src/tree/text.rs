@@ -0,0 +1,267 @@
+use {
+	crate::{
+		emit::Strings,
+		ext::{
+			code::codeblock::Block, datetime::time::Time, emoji::Emoji,
+			formatting::spoiler::Spoiler,
+		},
+		tree::{
+			visit::{self, Visitor},
+			BlockItem, InlineItem, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	alloc::{string::String, vec::Vec},
+	core::ops::Range,
+};
+
+/// options for [`plain_text`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlainTextOptions {
+	/// whether to include the content of `code` and `codeblock` tags
+	pub include_code: bool,
+	/// whether to include the content of `spoiler` tags, rather than `[spoiler: label]`
+	pub include_spoilers: bool,
+	/// how to hard wrap lines, if at all
+	pub wrap: Option<WrapOptions>,
+}
+
+/// how [`plain_text`] hard wraps lines
+///
+/// lines break at spaces, so no-break spaces hold the words around them together, and never within the content of `code` and `codeblock` tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrapOptions {
+	/// how many characters a line can have
+	pub width: usize,
+	/// whether to break words longer than [`width`](Self::width) at it, rather than leaving them on their own line
+	pub break_long_words: bool,
+}
+
+/// extract the text of an [`AST`], such as for search indexing
+///
+/// splits become spaces, while breaks and the boundaries between blocks become newlines, section headings are included, and the content of tags that define [`children`](crate::ext::TagDefinition::children) is descended into. times are written in english, as with the default [`Strings`]
+#[must_use]
+pub fn plain_text(doll: &MarkDoll, ast: &AST, options: PlainTextOptions) -> String {
+	let mut text = PlainText::new(options);
+	visit::walk(ast, doll, &mut text);
+	text.finish()
+}
+
+/// collects plain text while walking, its hooks are shared with other visitors that need the text, such as the one behind [`analyze`](super::analyze)
+pub(super) struct PlainText {
+	options: PlainTextOptions,
+	out: String,
+	/// whether the next block starts the content of a tag, which continues the text around the tag
+	in_tag: bool,
+	/// the ranges of the text that are never wrapped
+	unbreakable: Vec<Range<usize>>,
+}
+
+impl PlainText {
+	pub fn new(options: PlainTextOptions) -> Self {
+		Self {
+			options,
+			out: String::new(),
+			in_tag: false,
+			unbreakable: Vec::new(),
+		}
+	}
+
+	/// the text collected
+	pub fn finish(mut self) -> String {
+		self.out.truncate(self.out.trim_end().len());
+
+		match self.options.wrap {
+			Some(options) => wrap(&self.out, &self.unbreakable, options),
+			None => self.out,
+		}
+	}
+
+	/// collect text that is never wrapped
+	fn push_unbreakable(&mut self, text: &str) {
+		let start = self.out.len();
+		self.out.push_str(text);
+		self.unbreakable.push(start..self.out.len());
+	}
+
+	/// separate what comes next from the last text, newlines taking precedence over spaces
+	fn separate(&mut self, separator: char) {
+		if separator == '\n' && self.out.ends_with(' ') {
+			self.out.pop();
+		}
+
+		if !self.out.is_empty() && !self.out.ends_with(['\n', ' ']) {
+			self.out.push(separator);
+		}
+	}
+
+	/// collect the text of a block before walking it
+	pub fn block(&mut self, block: &BlockItem) {
+		if !core::mem::take(&mut self.in_tag) {
+			self.separate('\n');
+		}
+
+		if let BlockItem::Section { name, .. } = block {
+			self.out.push_str(name);
+			self.separate('\n');
+		}
+	}
+
+	/// collect the text of an inline item, returning whether to walk it
+	pub fn inline(&mut self, item: &InlineItem) -> bool {
+		match item {
+			InlineItem::Split => self.separate(' '),
+			InlineItem::Break => self.separate('\n'),
+			InlineItem::Text(text) => self.out.push_str(text),
+			InlineItem::Tag(_) => return true,
+		}
+
+		false
+	}
+
+	/// collect the text of a tag before walking it, returning whether to walk it
+	pub fn enter_tag(&mut self, tag: &TagInvocation) -> bool {
+		if !self.options.include_spoilers {
+			if let Some(spoiler) = tag.content.downcast_ref::<Spoiler>() {
+				match &spoiler.label {
+					Some(label) => {
+						self.out.push_str("[spoiler: ");
+						self.out.push_str(label);
+						self.out.push(']');
+					}
+					None => self.out.push_str("[spoiler]"),
+				}
+
+				return false;
+			}
+		}
+
+		if let Some(emoji) = tag.content.downcast_ref::<Emoji>() {
+			self.out.push_str(emoji.emoji);
+		}
+
+		if let Some(time) = tag.content.downcast_ref::<Time>() {
+			if time.content.is_none() {
+				self.out.push_str(&time.display(&Strings::default()));
+			}
+		}
+
+		if self.options.include_code {
+			match &*tag.tag {
+				"code" => {
+					if let Some(code) = tag.content.downcast_ref::<String>() {
+						self.push_unbreakable(code);
+					}
+				}
+				"codeblock" => {
+					if let Some(block) = tag.content.downcast_ref::<Block>() {
+						self.separate('\n');
+						self.push_unbreakable(&block.text);
+						self.separate('\n');
+					}
+				}
+				_ => {}
+			}
+		}
+
+		// titles of works are quoted, as they would be written without `<cite>`
+		if tag.tag == "citetitle" {
+			self.out.push('“');
+		}
+
+		self.in_tag = true;
+		true
+	}
+
+	/// finish the text of a tag after walking it
+	pub fn exit_tag(&mut self, tag: &TagInvocation) {
+		if tag.tag == "citetitle" {
+			self.out.push('”');
+		}
+
+		self.in_tag = false;
+	}
+}
+
+impl Visitor for PlainText {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &BlockItem) {
+		self.block(block);
+		visit::walk_block(self, doll, block);
+	}
+
+	fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &InlineItem) {
+		if self.inline(item) {
+			visit::walk_inline(self, doll, pos, item);
+		}
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+		if self.enter_tag(tag) {
+			visit::walk_tag(self, doll, pos, tag);
+			self.exit_tag(tag);
+		}
+	}
+}
+
+/// hard wrap every line of `text`, breaking at spaces outside of the `unbreakable` ranges
+fn wrap(text: &str, unbreakable: &[Range<usize>], options: WrapOptions) -> String {
+	let width = options.width.max(1);
+	let mut out = String::with_capacity(text.len());
+
+	let mut start = 0;
+	for line in text.split('\n') {
+		if start != 0 {
+			out.push('\n');
+		}
+
+		// the words of the line, split at the spaces that can be broken at
+		let mut words = Vec::new();
+		let mut word = 0;
+		for (at, _) in line.match_indices(' ') {
+			if !unbreakable
+				.iter()
+				.any(|range| range.contains(&(start + at)))
+			{
+				words.push((start + word, &line[word..at]));
+				word = at + 1;
+			}
+		}
+		words.push((start + word, &line[word..]));
+
+		let mut len = 0;
+		for (word_start, word) in words {
+			let word_len = word.chars().count();
+
+			if len != 0 && len + 1 + word_len > width {
+				out.push('\n');
+				len = 0;
+			} else if len != 0 {
+				out.push(' ');
+				len += 1;
+			}
+
+			if options.break_long_words
+				&& word_len > width
+				&& !unbreakable
+					.iter()
+					.any(|range| range.start < word_start + word.len() && word_start < range.end)
+			{
+				let chars = word.chars().collect::<Vec<_>>();
+				for (i, chunk) in chars.chunks(width).enumerate() {
+					if i != 0 {
+						out.push('\n');
+					}
+					out.extend(chunk);
+					len = chunk.len();
+				}
+			} else {
+				out.push_str(word);
+				len += word_len;
+			}
+		}
+
+		start += line.len() + 1;
+	}
+
+	out
+}
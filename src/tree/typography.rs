@@ -0,0 +1,468 @@
+use {
+	crate::{
+		tree::{
+			visit::{self, VisitorMut},
+			BlockItem, InlineItem, TagInvocation, AST,
+		},
+		MarkDoll,
+	},
+	alloc::{string::String, vec::Vec},
+};
+
+/// which marks straight quotes become in [`smart_punctuation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+	/// “double” and ‘single’
+	#[default]
+	English,
+	/// „double“ and ‚single‘
+	German,
+	/// « double » and ‹ single ›, with narrow no-break spaces inside
+	French,
+}
+
+/// the language and region of a BCP 47 language tag such as `fr-CA`, lowercased
+fn language(locale: &str) -> (String, Option<String>) {
+	let mut subtags = locale.split(['-', '_']).map(str::to_ascii_lowercase);
+
+	(subtags.next().unwrap_or_default(), subtags.next())
+}
+
+impl QuoteStyle {
+	/// the style for a BCP 47 language tag such as [`Strings::locale`](crate::emit::Strings::locale), if its language has one
+	#[must_use]
+	pub fn for_locale(locale: &str) -> Option<Self> {
+		match &*language(locale).0 {
+			"en" => Some(Self::English),
+			"de" => Some(Self::German),
+			"fr" => Some(Self::French),
+			_ => None,
+		}
+	}
+
+	/// the opening and closing double quotes, then the opening and closing single quotes
+	#[must_use]
+	pub fn marks(self) -> [&'static str; 4] {
+		match self {
+			Self::English => ["“", "”", "‘", "’"],
+			Self::German => ["„", "“", "‚", "‘"],
+			Self::French => ["«\u{202F}", "\u{202F}»", "‹\u{202F}", "\u{202F}›"],
+		}
+	}
+}
+
+/// words that are elided at their start, so an apostrophe before them isn't an opening quote, such as `'tis`
+const ELISIONS: [&str; 8] = ["cause", "em", "n", "round", "til", "tis", "twas", "twere"];
+
+/// what a tag that wrote no text, such as `code`, reads as to the quotes around it
+const WORD: char = 'a';
+
+struct Punctuate {
+	marks: [&'static str; 4],
+	/// the last character written, `None` at the start of a block
+	prev: Option<char>,
+	/// how many double quotes are open
+	double: usize,
+	/// how many single quotes are open
+	single: usize,
+	/// how many tags are being walked
+	tags: usize,
+	/// whether any text was written since entering the innermost tag
+	wrote: bool,
+}
+
+impl Punctuate {
+	/// whether a quote after `prev` opens, rather than closes
+	fn opens(&self, prev: Option<char>) -> bool {
+		prev.map_or(true, |c| {
+			c.is_whitespace()
+				|| matches!(c, '(' | '[' | '{' | '/' | '-' | '–' | '—')
+				|| self.marks[0].ends_with(c)
+				|| self.marks[2].ends_with(c)
+		})
+	}
+
+	fn push(&mut self, out: &mut String, mark: &str) {
+		out.push_str(mark);
+		self.prev = mark.chars().next_back();
+	}
+
+	fn text(&mut self, text: &mut String) {
+		let chars = text.chars().collect::<Vec<_>>();
+		let urls = urls(&chars);
+		let mut out = String::with_capacity(text.len());
+
+		let mut i = 0;
+		while i < chars.len() {
+			if let Some(&(_, end)) = urls.iter().find(|(start, _)| *start == i) {
+				out.extend(&chars[i..end]);
+				self.prev = Some(chars[end - 1]);
+				i = end;
+				continue;
+			}
+
+			let next = chars.get(i + 1).copied();
+			match chars[i] {
+				'.' if chars[i + 1..].starts_with(&['.', '.']) => {
+					self.push(&mut out, "…");
+					i += 3;
+					continue;
+				}
+				'-' if chars[i + 1..].starts_with(&['-', '-']) => {
+					self.push(&mut out, "—");
+					i += 3;
+					continue;
+				}
+				'-' if next == Some('-') => {
+					self.push(&mut out, "–");
+					i += 2;
+					continue;
+				}
+				'"' => {
+					let spaced = self.prev.is_some_and(char::is_whitespace)
+						&& next.map_or(true, char::is_whitespace);
+					if self.opens(self.prev) && !(spaced && self.double > 0) {
+						self.double += 1;
+						self.push(&mut out, self.marks[0]);
+					} else {
+						self.double = self.double.saturating_sub(1);
+						self.push(&mut out, self.marks[1]);
+					}
+				}
+				'\'' => {
+					let elided = chars[i + 1..]
+						.iter()
+						.position(|c| !c.is_alphanumeric())
+						.map_or(&chars[i + 1..], |len| &chars[i + 1..i + 1 + len]);
+					let elided = elided.iter().collect::<String>().to_lowercase();
+
+					if self.prev.is_some_and(char::is_alphanumeric) {
+						if self.single > 0 && !next.is_some_and(char::is_alphanumeric) {
+							self.single -= 1;
+							self.push(&mut out, self.marks[3]);
+						} else {
+							self.push(&mut out, "’");
+						}
+					} else if self.opens(self.prev) {
+						if next.is_some_and(|c| c.is_ascii_digit())
+							|| ELISIONS.contains(&elided.as_str())
+						{
+							self.push(&mut out, "’");
+						} else {
+							self.single += 1;
+							self.push(&mut out, self.marks[2]);
+						}
+					} else if self.single > 0 {
+						self.single -= 1;
+						self.push(&mut out, self.marks[3]);
+					} else {
+						self.push(&mut out, "’");
+					}
+				}
+				c => {
+					out.push(c);
+					self.prev = Some(c);
+				}
+			}
+
+			i += 1;
+		}
+
+		*text = out;
+		self.wrote = true;
+	}
+}
+
+/// the ranges of URLs within text, as `(start, end)` character indices, which are copied as written
+fn urls(chars: &[char]) -> Vec<(usize, usize)> {
+	let mut urls = Vec::new();
+
+	let mut i = 0;
+	while i + 3 <= chars.len() {
+		if chars[i..].starts_with(&[':', '/', '/']) {
+			let mut start = i;
+			while start > 0
+				&& (chars[start - 1].is_ascii_alphanumeric()
+					|| matches!(chars[start - 1], '+' | '-' | '.'))
+			{
+				start -= 1;
+			}
+
+			let mut end = i + 3;
+			while end < chars.len() && !chars[end].is_whitespace() {
+				end += 1;
+			}
+			while end > i + 3
+				&& matches!(
+					chars[end - 1],
+					'"' | '\'' | '.' | ',' | ';' | ':' | '!' | '?' | ')' | ']'
+				) {
+				end -= 1;
+			}
+
+			if start < i {
+				urls.push((start, end));
+			}
+			i = end;
+		} else {
+			i += 1;
+		}
+	}
+
+	urls
+}
+
+impl VisitorMut for Punctuate {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &mut BlockItem) {
+		if self.tags == 0 {
+			self.prev = None;
+			self.double = 0;
+			self.single = 0;
+		}
+
+		visit::walk_block_mut(self, doll, block);
+	}
+
+	fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &mut InlineItem) {
+		match item {
+			InlineItem::Text(text) => self.text(text),
+			InlineItem::Split | InlineItem::Break => self.prev = Some(' '),
+			InlineItem::Tag(_) => visit::walk_inline_mut(self, doll, pos, item),
+		}
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+		self.tags += 1;
+		self.wrote = false;
+
+		visit::walk_tag_mut(self, doll, pos, tag);
+
+		if !self.wrote {
+			self.prev = Some(WORD);
+		}
+		self.wrote = true;
+		self.tags -= 1;
+	}
+}
+
+/// which spaces are kept from breaking in [`typographic_spacing`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpacingStyle {
+	/// a no-break space before `:`, and narrow no-break spaces before `;`, `!`, and `?` and inside guillemets
+	French,
+	/// a no-break space before `:` and inside guillemets, and none before `;`, `!`, and `?`
+	CanadianFrench,
+}
+
+/// a rule of a [`SpacingStyle`]
+struct Rule {
+	/// the space kept before the mark
+	space: char,
+	/// whether to add the space when there is none, rather than only replacing a regular space, which would break times such as `12:30`
+	insert: bool,
+}
+
+impl SpacingStyle {
+	/// the style for a BCP 47 language tag such as [`Strings::locale`](crate::emit::Strings::locale), if its language has one
+	#[must_use]
+	pub fn for_locale(locale: &str) -> Option<Self> {
+		match language(locale) {
+			(language, Some(region)) if language == "fr" && region == "ca" => {
+				Some(Self::CanadianFrench)
+			}
+			(language, _) if language == "fr" => Some(Self::French),
+			_ => None,
+		}
+	}
+
+	/// the rule for the space before a mark
+	fn before(self, mark: char) -> Option<Rule> {
+		let (space, insert) = match (self, mark) {
+			(Self::French, ':') => ('\u{A0}', false),
+			(Self::French, ';' | '!' | '?' | '»') => ('\u{202F}', true),
+			(Self::CanadianFrench, ':') => ('\u{A0}', false),
+			(Self::CanadianFrench, '»') => ('\u{A0}', true),
+			_ => return None,
+		};
+
+		Some(Rule { space, insert })
+	}
+
+	/// the space kept after an opening guillemet
+	fn after_guillemet(self) -> char {
+		match self {
+			Self::French => '\u{202F}',
+			Self::CanadianFrench => '\u{A0}',
+		}
+	}
+}
+
+struct Space {
+	style: SpacingStyle,
+	/// the last character written, `None` at the start of a block
+	prev: Option<char>,
+	/// how many tags are being walked
+	tags: usize,
+	/// whether any text was written since entering the innermost tag
+	wrote: bool,
+}
+
+impl Space {
+	/// space the marks in text, returning whether the regular space ending the text before it should be removed
+	///
+	/// `after_text` is whether that is text, rather than a tag whose content can't be changed from here
+	fn text(&mut self, text: &mut String, after_text: bool) -> bool {
+		let chars = text.chars().collect::<Vec<_>>();
+		let urls = urls(&chars);
+		let mut out = String::with_capacity(text.len());
+		let mut trim_before = false;
+
+		let mut i = 0;
+		while i < chars.len() {
+			if let Some(&(_, end)) = urls.iter().find(|(start, _)| *start == i) {
+				out.extend(&chars[i..end]);
+				self.prev = Some(chars[end - 1]);
+				i = end;
+				continue;
+			}
+
+			let c = chars[i];
+			if let Some(rule) = self.style.before(c) {
+				match self.prev {
+					Some(' ') if out.is_empty() && after_text => {
+						trim_before = true;
+						out.push(rule.space);
+					}
+					// the space is within a tag's content
+					Some(' ') if out.is_empty() => {}
+					Some(' ') => {
+						out.pop();
+						out.push(rule.space);
+					}
+					Some(prev)
+						if rule.insert
+							&& !prev.is_whitespace()
+							// so `?!` stays together
+							&& !matches!(prev, ';' | ':' | '!' | '?') =>
+					{
+						out.push(rule.space);
+					}
+					_ => {}
+				}
+			}
+
+			out.push(c);
+			self.prev = Some(c);
+			i += 1;
+
+			if c == '«' {
+				let space = self.style.after_guillemet();
+				match chars.get(i) {
+					Some(' ') => {
+						i += 1;
+						out.push(space);
+						self.prev = Some(space);
+					}
+					Some(next) if !next.is_whitespace() => {
+						out.push(space);
+						self.prev = Some(space);
+					}
+					_ => {}
+				}
+			}
+		}
+
+		*text = out;
+		self.wrote = true;
+		trim_before
+	}
+}
+
+impl VisitorMut for Space {
+	fn visit_block(&mut self, doll: &MarkDoll, block: &mut BlockItem) {
+		if self.tags == 0 {
+			self.prev = None;
+		}
+
+		let BlockItem::Inline(items) = block else {
+			visit::walk_block_mut(self, doll, block);
+			return;
+		};
+
+		for i in 0..items.len() {
+			let after_text = i > 0 && matches!(items[i - 1].1, InlineItem::Text(_));
+			let (pos, item) = &mut items[i];
+			match item {
+				InlineItem::Text(text) => {
+					if self.text(text, after_text) {
+						if let (_, InlineItem::Text(before)) = &mut items[i - 1] {
+							before.pop();
+						}
+					}
+				}
+				InlineItem::Split | InlineItem::Break => self.prev = Some('\n'),
+				InlineItem::Tag(_) => visit::walk_inline_mut(self, doll, *pos, item),
+			}
+		}
+	}
+
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+		self.tags += 1;
+		self.wrote = false;
+
+		visit::walk_tag_mut(self, doll, pos, tag);
+
+		if !self.wrote {
+			self.prev = Some(WORD);
+		}
+		self.wrote = true;
+		self.tags -= 1;
+	}
+}
+
+/// keep the spaces around punctuation from breaking in the given [`SpacingStyle`], throughout the text of an [`AST`]
+///
+/// a regular space before a mark is replaced, even when it ends the text before a tag, but when it is within a tag's content before the mark it is left alone. URLs in text are left as written, and code is never touched since its content isn't markdoll
+///
+/// run this after [`smart_punctuation`] so the guillemets it writes are spaced too, unless the document [opts out](skip_typography)
+pub fn typographic_spacing(doll: &MarkDoll, ast: &mut AST, style: SpacingStyle) {
+	visit::walk_mut(
+		ast,
+		doll,
+		&mut Space {
+			style,
+			prev: None,
+			tags: 0,
+			wrote: false,
+		},
+	);
+}
+
+/// whether a document's frontmatter opts out of [`smart_punctuation`] and [`typographic_spacing`] with a `typography: off` line
+#[must_use]
+pub fn skip_typography(frontmatter: &str) -> bool {
+	frontmatter.lines().any(|line| {
+		line.split_once(':')
+			.is_some_and(|(key, value)| key.trim() == "typography" && value.trim() == "off")
+	})
+}
+
+/// replace straight quotes with curly ones in the given [`QuoteStyle`], `--` and `---` with en and em dashes, and `...` with an ellipsis, throughout the text of an [`AST`]
+///
+/// quotes open or close based on the character before them, carried across the tags between text, so quotes may wrap tags or sit inside of them. apostrophes within and at the end of words are always `’`, as are those starting elided words such as `'tis` or years such as `'90s`
+///
+/// URLs in text are left as written, and code is never touched since its content isn't markdoll. this descends into sections, lists, and tags that define [`children`](crate::ext::TagDefinition::children)
+pub fn smart_punctuation(doll: &MarkDoll, ast: &mut AST, style: QuoteStyle) {
+	visit::walk_mut(
+		ast,
+		doll,
+		&mut Punctuate {
+			marks: style.marks(),
+			prev: None,
+			double: 0,
+			single: 0,
+			tags: 0,
+			wrote: false,
+		},
+	);
+}
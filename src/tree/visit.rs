@@ -0,0 +1,168 @@
+use crate::{
+	tree::{BlockItem, InlineItem, TagInvocation, AST},
+	MarkDoll,
+};
+
+/// walks an [`AST`], including the [`AST`]s within tags that define [`children`](crate::ext::TagDefinition::children)
+///
+/// every method defaults to walking into its children, so override the ones you care about and call the matching `walk_` function to keep descending
+pub trait Visitor {
+	/// visit an [`AST`]
+	fn visit_ast(&mut self, doll: &MarkDoll, ast: &AST) {
+		walk_ast(self, doll, ast);
+	}
+
+	/// visit a [`BlockItem`]
+	fn visit_block(&mut self, doll: &MarkDoll, block: &BlockItem) {
+		walk_block(self, doll, block);
+	}
+
+	/// visit an [`InlineItem`] at a position
+	fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &InlineItem) {
+		walk_inline(self, doll, pos, item);
+	}
+
+	/// visit a [`TagInvocation`] at a position
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+		walk_tag(self, doll, pos, tag);
+	}
+}
+
+/// visit every block in an [`AST`]
+pub fn walk_ast<V: Visitor + ?Sized>(visitor: &mut V, doll: &MarkDoll, ast: &AST) {
+	for block in ast {
+		visitor.visit_block(doll, block);
+	}
+}
+
+/// visit the children of a [`BlockItem`]
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, doll: &MarkDoll, block: &BlockItem) {
+	match block {
+		BlockItem::Inline(items) => {
+			for (pos, item) in items {
+				visitor.visit_inline(doll, *pos, item);
+			}
+		}
+		BlockItem::Section { children, .. } => visitor.visit_ast(doll, children),
+		BlockItem::List { items, .. } => {
+			for item in items {
+				visitor.visit_ast(doll, &item.ast);
+			}
+		}
+	}
+}
+
+/// visit the children of an [`InlineItem`]
+pub fn walk_inline<V: Visitor + ?Sized>(
+	visitor: &mut V,
+	doll: &MarkDoll,
+	pos: usize,
+	item: &InlineItem,
+) {
+	if let InlineItem::Tag(tag) = item {
+		visitor.visit_tag(doll, pos, tag);
+	}
+}
+
+/// visit the [`AST`]s within a [`TagInvocation`], if its tag defines [`children`](crate::ext::TagDefinition::children)
+pub fn walk_tag<V: Visitor + ?Sized>(
+	visitor: &mut V,
+	doll: &MarkDoll,
+	_pos: usize,
+	tag: &TagInvocation,
+) {
+	if let Some((children, _)) = tag.def.children {
+		for ast in children(&*tag.content) {
+			visitor.visit_ast(doll, ast);
+		}
+	}
+}
+
+/// walks an [`AST`] mutably, including the [`AST`]s within tags that define [`children`](crate::ext::TagDefinition::children)
+///
+/// every method defaults to walking into its children, so override the ones you care about and call the matching `walk_` function to keep descending
+pub trait VisitorMut {
+	/// visit an [`AST`]
+	fn visit_ast(&mut self, doll: &MarkDoll, ast: &mut AST) {
+		walk_ast_mut(self, doll, ast);
+	}
+
+	/// visit a [`BlockItem`]
+	fn visit_block(&mut self, doll: &MarkDoll, block: &mut BlockItem) {
+		walk_block_mut(self, doll, block);
+	}
+
+	/// visit an [`InlineItem`] at a position
+	fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &mut InlineItem) {
+		walk_inline_mut(self, doll, pos, item);
+	}
+
+	/// visit a [`TagInvocation`] at a position
+	fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &mut TagInvocation) {
+		walk_tag_mut(self, doll, pos, tag);
+	}
+}
+
+/// visit every block in an [`AST`] mutably
+pub fn walk_ast_mut<V: VisitorMut + ?Sized>(visitor: &mut V, doll: &MarkDoll, ast: &mut AST) {
+	for block in ast {
+		visitor.visit_block(doll, block);
+	}
+}
+
+/// visit the children of a [`BlockItem`] mutably
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(
+	visitor: &mut V,
+	doll: &MarkDoll,
+	block: &mut BlockItem,
+) {
+	match block {
+		BlockItem::Inline(items) => {
+			for (pos, item) in items {
+				visitor.visit_inline(doll, *pos, item);
+			}
+		}
+		BlockItem::Section { children, .. } => visitor.visit_ast(doll, children),
+		BlockItem::List { items, .. } => {
+			for item in items {
+				visitor.visit_ast(doll, &mut item.ast);
+			}
+		}
+	}
+}
+
+/// visit the children of an [`InlineItem`] mutably
+pub fn walk_inline_mut<V: VisitorMut + ?Sized>(
+	visitor: &mut V,
+	doll: &MarkDoll,
+	pos: usize,
+	item: &mut InlineItem,
+) {
+	if let InlineItem::Tag(tag) = item {
+		visitor.visit_tag(doll, pos, tag);
+	}
+}
+
+/// visit the [`AST`]s within a [`TagInvocation`] mutably, if its tag defines [`children`](crate::ext::TagDefinition::children)
+pub fn walk_tag_mut<V: VisitorMut + ?Sized>(
+	visitor: &mut V,
+	doll: &MarkDoll,
+	_pos: usize,
+	tag: &mut TagInvocation,
+) {
+	if let Some((_, children_mut)) = tag.def.children {
+		for ast in children_mut(&mut *tag.content) {
+			visitor.visit_ast(doll, ast);
+		}
+	}
+}
+
+/// walk an [`AST`] with a [`Visitor`]
+pub fn walk<V: Visitor + ?Sized>(ast: &AST, doll: &MarkDoll, visitor: &mut V) {
+	visitor.visit_ast(doll, ast);
+}
+
+/// walk an [`AST`] mutably with a [`VisitorMut`]
+pub fn walk_mut<V: VisitorMut + ?Sized>(ast: &mut AST, doll: &MarkDoll, visitor: &mut V) {
+	visitor.visit_ast(doll, ast);
+}
@@ -1,39 +1,52 @@
 use {
-	alloc::{alloc::dealloc, boxed::Box},
-	core::{alloc::Layout, any::TypeId, mem::transmute, ptr::drop_in_place},
+	alloc::boxed::Box,
+	core::any::{Any, TypeId},
 	hashbrown::HashMap,
 };
 
-#[derive(Debug, Clone)]
-struct Entry {
-	ptr: *mut (),
-	drop: unsafe fn(*mut ()),
-	layout: Layout,
+/// a value in a [`TypeMap`], which can be cloned without knowing its type
+trait Value: Any + Send + Sync {
+	fn clone_boxed(&self) -> Box<dyn Value>;
+	fn into_any(self: Box<Self>) -> Box<dyn Any>;
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<Variant: Clone + Send + Sync + 'static> Value for Variant {
+	fn clone_boxed(&self) -> Box<dyn Value> {
+		Box::new(self.clone())
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any> {
+		self
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
 }
 
 /// hashmap of typeid->value, supporting up to 1 value per type
-#[derive(Debug, Clone, Default)]
+///
+/// values must be [`Send`], [`Sync`], and [`Clone`], so the map can be too
+#[derive(Default)]
 pub struct TypeMap {
-	inner: HashMap<TypeId, Entry>,
+	inner: HashMap<TypeId, Box<dyn Value>>,
 }
 
 impl TypeMap {
 	/// put a value into the map
-	pub fn put<Variant: Sized + Clone + 'static>(&mut self, value: Variant) {
+	pub fn put<Variant: Sized + Clone + Send + Sync + 'static>(&mut self, value: Variant) {
 		self.put_boxed(Box::new(value));
 	}
 
 	/// put an already-boxed value into the map
-	pub fn put_boxed<Variant: Clone + 'static>(&mut self, value: Box<Variant>) {
-		self.inner.insert(TypeId::of::<Variant>(), unsafe {
-			Entry {
-				ptr: Box::into_raw(value).cast(),
-				drop: transmute::<unsafe fn(*mut Variant), unsafe fn(*mut ())>(
-					drop_in_place::<Variant>,
-				),
-				layout: Layout::new::<Variant>(),
-			}
-		});
+	pub fn put_boxed<Variant: Clone + Send + Sync + 'static>(&mut self, value: Box<Variant>) {
+		self.inner.insert(TypeId::of::<Variant>(), value);
 	}
 
 	/// take a value out of the map
@@ -45,7 +58,7 @@ impl TypeMap {
 	pub fn remove_boxed<Variant: Sized + Clone + 'static>(&mut self) -> Option<Box<Variant>> {
 		self.inner
 			.remove(&TypeId::of::<Variant>())
-			.map(|value| unsafe { Box::from_raw(value.ptr.cast::<Variant>()) })
+			.map(|value| value.into_any().downcast().unwrap())
 	}
 
 	/// get a reference to a value in the map
@@ -53,7 +66,7 @@ impl TypeMap {
 	pub fn get_ref<Variant: Clone + 'static>(&self) -> Option<&Variant> {
 		self.inner
 			.get(&TypeId::of::<Variant>())
-			.map(|value| unsafe { &*value.ptr.cast() })
+			.map(|value| (**value).as_any().downcast_ref().unwrap())
 	}
 
 	/// get a mutable reference to a value in the map
@@ -61,7 +74,26 @@ impl TypeMap {
 	pub fn get_mut<Variant: Clone + 'static>(&mut self) -> Option<&mut Variant> {
 		self.inner
 			.get_mut(&TypeId::of::<Variant>())
-			.map(|value| unsafe { &mut *value.ptr.cast() })
+			.map(|value| (**value).as_any_mut().downcast_mut().unwrap())
+	}
+
+	/// get a mutable reference to a value in the map, putting the result of `default` into it first if there is none
+	pub fn get_or_insert_with<Variant: Clone + Send + Sync + 'static>(
+		&mut self,
+		default: impl FnOnce() -> Variant,
+	) -> &mut Variant {
+		(**self
+			.inner
+			.entry(TypeId::of::<Variant>())
+			.or_insert_with(|| Box::new(default())))
+		.as_any_mut()
+		.downcast_mut()
+		.unwrap()
+	}
+
+	/// remove every value from the map
+	pub fn clear(&mut self) {
+		self.inner.clear();
 	}
 
 	/// how many items are in the map
@@ -77,13 +109,22 @@ impl TypeMap {
 	}
 }
 
-impl Drop for TypeMap {
-	fn drop(&mut self) {
-		for entry in self.inner.values() {
-			unsafe {
-				(entry.drop)(entry.ptr);
-				dealloc(entry.ptr.cast(), entry.layout);
-			}
+impl Clone for TypeMap {
+	fn clone(&self) -> Self {
+		Self {
+			inner: self
+				.inner
+				.iter()
+				.map(|(id, value)| (*id, (**value).clone_boxed()))
+				.collect(),
 		}
 	}
 }
+
+impl core::fmt::Debug for TypeMap {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("TypeMap")
+			.field("len", &self.inner.len())
+			.finish()
+	}
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext};
+
+fn emit(src: &str) -> (String, Vec<(bool, &'static str, usize)>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diag| (diag.err, diag.code, diag.at))
+		.collect();
+
+	(out.write, diagnostics)
+}
+
+#[test]
+pub fn explicit_title() {
+	assert_eq!(
+		emit("[abbr(Tom's <Markup> Language):TML]"),
+		(
+			"<div><abbr title='Tom&#x27;s &lt;Markup&gt; Language'>TML</abbr></div>".into(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn defined_title() {
+	assert_eq!(
+		emit("[abbrdef(HTML)(HyperText Markup Language)]\n\n[abbr:HTML] and [abbr:HTML]"),
+		(
+			"<div></div><div><abbr title='HyperText Markup Language'>HTML</abbr> and <abbr title='HyperText Markup Language'>HTML</abbr></div>".into(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn use_before_definition_warns() {
+	assert_eq!(
+		emit("[abbr:HTML]\n\n[abbrdef(HTML)(HyperText Markup Language)]"),
+		(
+			"<div><abbr>HTML</abbr></div><div></div>".into(),
+			vec![(false, "abbreviation not defined", 0)]
+		)
+	);
+}
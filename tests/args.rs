@@ -0,0 +1,344 @@
+mod common;
+
+use {
+	markdoll::{
+		args,
+		ext::{self, ArgValue, TagDefinition, TagForm},
+		tree::{build::Doc, BlockItem, InlineItem, Shorthand},
+		MarkDoll,
+	},
+	std::sync::Mutex,
+};
+
+/// the arguments parsed by the `t` tag
+#[derive(Debug, PartialEq, Eq)]
+struct Parsed {
+	a: String,
+	b: Option<String>,
+	f: bool,
+	p: Option<usize>,
+}
+
+/// an instance with only the `t` tag
+fn doll() -> MarkDoll {
+	let mut doll = common::doll_with(&[]);
+	doll.ext_system.add_tag(TagDefinition::new(
+		"t",
+		Some(|doll, mut args, _, _| {
+			args! {
+				doll, args;
+
+				args(a: String);
+				opt_args(b: String);
+				flags(f);
+				props(p: usize);
+			}
+
+			Some(Box::new(Parsed { a, b, f, p }))
+		}),
+	));
+	doll
+}
+
+/// parse a `t` tag, returning its arguments or the codes of its diagnostics
+fn parse(src: &str) -> Result<Parsed, Vec<&'static str>> {
+	let mut doll = doll();
+
+	match doll.parse(src) {
+		Ok(mut ast) => {
+			let BlockItem::Inline(inline) = &mut ast[0] else {
+				unreachable!()
+			};
+			let InlineItem::Tag(tag) = inline.remove(0).1 else {
+				unreachable!()
+			};
+
+			Ok(*tag.content.downcast::<Parsed>().unwrap())
+		}
+		Err(_) => Err(doll.finish().into_iter().map(|diag| diag.code).collect()),
+	}
+}
+
+fn parsed(
+	a: &str,
+	b: Option<&str>,
+	f: bool,
+	p: Option<usize>,
+) -> Result<Parsed, Vec<&'static str>> {
+	Ok(Parsed {
+		a: a.to_string(),
+		b: b.map(ToString::to_string),
+		f,
+		p,
+	})
+}
+
+#[test]
+pub fn positional_then_named() {
+	assert_eq!(parse("[t(x)]"), parsed("x", None, false, None));
+	assert_eq!(parse("[t(x)(y)]"), parsed("x", Some("y"), false, None));
+	assert_eq!(
+		parse("[t(x)(y)(f)(p=2)]"),
+		parsed("x", Some("y"), true, Some(2))
+	);
+	// flags and props in any order
+	assert_eq!(parse("[t(x)(p=2)(f)]"), parsed("x", None, true, Some(2)));
+	// extra positional arguments before the named ones are ignored
+	assert_eq!(parse("[t(x)(y)(z)(f)]"), parsed("x", Some("y"), true, None));
+	// as are props that aren't declared
+	assert_eq!(parse("[t(x)(f)(q=1)]"), parsed("x", None, true, None));
+}
+
+#[test]
+pub fn positional_after_named() {
+	assert_eq!(
+		parse("[t(x)(f)(y)]"),
+		Err(vec!["positional argument after named"])
+	);
+	assert_eq!(
+		parse("[t(x)(p=2)(y)]"),
+		Err(vec!["positional argument after named"])
+	);
+	assert_eq!(
+		parse("[t(x)(f)(p=2)(y)(z)]"),
+		Err(vec!["positional argument after named"])
+	);
+	// a required argument can't be taken by a named one
+	assert_eq!(
+		parse("[t(p=2)(x)]"),
+		Err(vec!["positional argument after named"])
+	);
+}
+
+#[test]
+pub fn missing_and_invalid() {
+	assert_eq!(parse("[t]"), Err(vec!["argument a required"]));
+	// named arguments alone leave the required one out
+	assert_eq!(parse("[t(f)]"), Err(vec!["argument a required"]));
+	assert_eq!(parse("[t(f)(p=2)]"), Err(vec!["argument a required"]));
+	assert_eq!(parse("[t(x)(p=two)]"), Err(vec!["prop p invalid"]));
+}
+
+/// the codes and positions of the diagnostics of parsing `src`
+fn positions(src: &str) -> Vec<(&'static str, usize)> {
+	let mut doll = doll();
+	doll.ext_system.add_tags(ext::formatting::tags());
+	let _ = doll.parse(src);
+	doll.finish()
+		.into_iter()
+		.map(|diag| (diag.code, diag.at))
+		.collect()
+}
+
+#[test]
+pub fn reported_at_argument() {
+	assert_eq!(positions("[t(x)(p=two)]"), [("prop p invalid", 6)]);
+	assert_eq!(
+		positions("[t(x)(f)(y)]"),
+		[("positional argument after named", 9)]
+	);
+	assert_eq!(
+		positions("[t(p=2)(x)]"),
+		[("positional argument after named", 8)]
+	);
+	assert_eq!(positions("[t]"), [("argument a required", 0)]);
+	assert_eq!(positions("x [t(f)]"), [("argument a required", 2)]);
+
+	// escapes in earlier arguments, and in the content of the tag around it
+	assert_eq!(positions("[t(a\\)b)(p=two)]"), [("prop p invalid", 9)]);
+	assert_eq!(
+		positions("[em:a\\]b[t(x\\(y)(p=two)]]"),
+		[("prop p invalid", 17)]
+	);
+}
+
+#[test]
+pub fn empty() {
+	// a required argument left empty is missing
+	assert_eq!(
+		parse("[t()]"),
+		Err(vec!["empty argument", "argument a required"])
+	);
+	assert_eq!(
+		positions("[t()(p=2)]"),
+		[("empty argument", 2), ("argument a required", 3)]
+	);
+
+	// an optional one is kept empty
+	assert_eq!(parse("[t(x)()]"), parsed("x", Some(""), false, None));
+	assert_eq!(
+		parse("[t(x)()(f)(p=2)]"),
+		parsed("x", Some(""), true, Some(2))
+	);
+	assert_eq!(positions("[t(x)()(f)(p=2)]"), [("empty argument", 5)]);
+}
+
+#[test]
+pub fn named_as_data() {
+	// a cell whose text can be `head`, as well as a `head` flag
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(TagDefinition::new(
+		"cell",
+		Some(|doll, mut args, _, _| {
+			args! {
+				doll, args;
+
+				args();
+				opt_args(text: String);
+				flags(head);
+				props();
+			}
+
+			Some(Box::new((text, head)))
+		}),
+	));
+	let mut cell = |src: &str| {
+		let mut ast = doll.parse(src).unwrap();
+		let BlockItem::Inline(inline) = &mut ast[0] else {
+			unreachable!()
+		};
+		let InlineItem::Tag(tag) = inline.remove(0).1 else {
+			unreachable!()
+		};
+
+		*tag.content.downcast::<(Option<String>, bool)>().unwrap()
+	};
+
+	// written as is, it is the flag wherever it is
+	assert_eq!(cell("[cell(head)]"), (None, true));
+	assert_eq!(cell("[cell(x)(head)]"), (Some("x".to_string()), true));
+	// and with a leading `=`, it is the text
+	assert_eq!(cell("[cell(=head)]"), (Some("head".to_string()), false));
+	assert_eq!(
+		cell("[cell(=head)(head)]"),
+		(Some("head".to_string()), true)
+	);
+	assert_eq!(cell("[cell(==head)]"), (Some("=head".to_string()), false));
+	assert_eq!(cell("[cell(=)]"), (Some(String::new()), false));
+
+	// which is the same for props and required arguments
+	assert_eq!(
+		parse("[t(=f)(=p=2)]"),
+		parsed("f", Some("p=2"), false, None)
+	);
+	assert_eq!(parse("[t(=x)(f)]"), parsed("x", None, true, None));
+	// and is still positional after a named argument
+	assert_eq!(
+		positions("[t(x)(f)(=y)]"),
+		[("positional argument after named", 9)]
+	);
+}
+
+#[test]
+pub fn multibyte() {
+	assert_eq!(
+		parse("[t(größe=10)(värde)]"),
+		parsed("größe=10", Some("värde"), false, None)
+	);
+	assert_eq!(
+		parse("[t(ä)(größe=10)(p=2)]"),
+		parsed("ä", Some("größe=10"), false, Some(2))
+	);
+
+	assert_eq!(positions("[t(x)(p=två)]"), [("prop p invalid", 6)]);
+	assert_eq!(
+		positions("[t(größe)(p=2)(ä)]"),
+		[("positional argument after named", 15)]
+	);
+	assert_eq!(
+		positions("[t(x)(größe=10)(p=värde)]"),
+		[("prop p invalid", 16)]
+	);
+
+	// block tags, and tags within their content
+	assert_eq!(
+		positions("ö [t(ä)(p=ü)::\n\tcontent\n]"),
+		[("prop p invalid", 8)]
+	);
+	assert_eq!(
+		positions("[em::\n\tä [t(ü)(p=ß)]\n]"),
+		[("prop p invalid", 15)]
+	);
+}
+
+#[test]
+pub fn forms() {
+	static FORMS: Mutex<Vec<TagForm>> = Mutex::new(Vec::new());
+
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(TagDefinition::new(
+		"f",
+		Some(|_, _, _, form| {
+			FORMS.lock().unwrap().push(form);
+			Some(Box::new(()))
+		}),
+	));
+	doll.shorthands.push(Shorthand::new('~', "f"));
+
+	doll.parse("[f:a] [f] ~b~\n\n[f::\n\tc\n]").unwrap();
+	assert_eq!(
+		*FORMS.lock().unwrap(),
+		[
+			TagForm::Inline,
+			TagForm::Empty,
+			TagForm::Inline,
+			TagForm::Block
+		]
+	);
+
+	// tags built in code are inline
+	FORMS.lock().unwrap().clear();
+	let mut doc = Doc::new(&mut doll);
+	doc.tag("f", &[], "d");
+	doc.finish().unwrap();
+	assert_eq!(*FORMS.lock().unwrap(), [TagForm::Inline]);
+}
+
+#[test]
+pub fn arg_values() {
+	let mut doll = common::doll_with(&[]);
+	doll.ext_system.add_tag(TagDefinition::new(
+		"v",
+		Some(|doll, mut args, _, _| {
+			args! {
+				doll, args;
+
+				args(a: ArgValue<String>);
+				opt_args();
+				flags(f);
+				props(p: ArgValue<usize>);
+			}
+
+			// values that parse but aren't usable are reported at their argument
+			if let Some(p) = p.filter(|p| **p == 0) {
+				p.diag(doll, true, "prop p invalid");
+				return None;
+			}
+
+			Some(Box::new((a, p)))
+		}),
+	));
+
+	let mut ast = doll.parse("[v(x)(f)(p=2)]").unwrap();
+	let BlockItem::Inline(inline) = &mut ast[0] else {
+		unreachable!()
+	};
+	let InlineItem::Tag(tag) = inline.remove(0).1 else {
+		unreachable!()
+	};
+	let (a, p) = *tag
+		.content
+		.downcast::<(ArgValue<String>, Option<ArgValue<usize>>)>()
+		.unwrap();
+	assert_eq!((a.as_str(), a.index), ("x", 0));
+	assert_eq!(p, Some(ArgValue { value: 2, index: 2 }));
+
+	let _ = doll.parse("[v(x)(f)(p=0)]");
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("prop p invalid", 9)]
+	);
+}
@@ -0,0 +1,254 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{sniff, AssetInfo, AssetResolver, HtmlEmit},
+		ext,
+	},
+	std::sync::Arc,
+};
+
+fn target() -> HtmlEmit {
+	HtmlEmit::default()
+}
+
+/// emit `src` into `out`, returning the codes and positions of the diagnostics, and the paths of the dependencies
+fn emit(src: &str, out: &mut HtmlEmit) -> (Vec<(&'static str, usize)>, Vec<String>) {
+	let mut doll = common::doll_with(&[&ext::links::tags(), &ext::media::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	assert!(doll.emit(&mut ast, out));
+
+	let dependencies = doll
+		.dependencies()
+		.iter()
+		.map(|dep| dep.path.clone())
+		.collect();
+	(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect(),
+		dependencies,
+	)
+}
+
+fn html(
+	src: &str,
+	asset_resolver: Option<Arc<AssetResolver>>,
+) -> (String, Vec<(&'static str, usize)>) {
+	let mut out = target();
+	out.asset_resolver = asset_resolver;
+	let (diagnostics, _) = emit(src, &mut out);
+
+	(out.write, diagnostics)
+}
+
+fn image(mime: &str, width: u32, height: u32) -> Option<AssetInfo> {
+	Some(AssetInfo {
+		exists: true,
+		width: Some(width),
+		height: Some(height),
+		mime: Some(mime.to_string()),
+	})
+}
+
+#[test]
+pub fn sniffs_headers() {
+	let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+	png.extend(88u32.to_be_bytes());
+	png.extend(31u32.to_be_bytes());
+	assert_eq!(sniff(&png), image("image/png", 88, 31));
+
+	assert_eq!(sniff(b"GIF89a\x58\x00\x1f\x00"), image("image/gif", 88, 31));
+
+	// an APP0 segment before the baseline frame
+	let jpeg = b"\xff\xd8\xff\xe0\x00\x04ab\xff\xc0\x00\x11\x08\x00\x1f\x00\x58";
+	assert_eq!(sniff(jpeg), image("image/jpeg", 88, 31));
+
+	let mut lossy = b"RIFF\0\0\0\0WEBPVP8 \0\0\0\0\0\0\0\x9d\x01\x2a".to_vec();
+	lossy.extend(88u16.to_le_bytes());
+	lossy.extend(31u16.to_le_bytes());
+	assert_eq!(sniff(&lossy), image("image/webp", 88, 31));
+
+	let mut lossless = b"RIFF\0\0\0\0WEBPVP8L\0\0\0\0\x2f".to_vec();
+	lossless.extend((87u32 | 30 << 14).to_le_bytes());
+	assert_eq!(sniff(&lossless), image("image/webp", 88, 31));
+
+	let extended = b"RIFF\0\0\0\0WEBPVP8X\0\0\0\0\0\0\0\0\x57\0\0\x1e\0\0";
+	assert_eq!(sniff(extended), image("image/webp", 88, 31));
+
+	assert_eq!(sniff(b"<svg></svg>"), None);
+	// cut off before the dimensions
+	assert_eq!(sniff(&png[..18]), None);
+	assert_eq!(sniff(b"\xff\xd8\xff\xe0\x00\x10"), None);
+}
+
+#[test]
+pub fn resolver() {
+	let assets: Arc<AssetResolver> = Arc::new(|src| match src {
+		"button.png" => image("image/png", 88, 31),
+		"missing.png" => Some(AssetInfo::default()),
+		_ => None,
+	});
+
+	assert_eq!(
+		html(
+			"[img(button.png)(decorative)]\n\n[img(missing.png)(decorative)]\n\n[img(https://a.example/b.png)(decorative)]",
+			Some(Arc::clone(&assets))
+		),
+		(
+			"<div><img src='button.png' alt='' role='presentation' width='88' height='31' /></div><div><img src='missing.png' alt='' role='presentation' /></div><div><img src='https:&#x2F;&#x2F;a.example&#x2F;b.png' alt='' role='presentation' /></div>".to_string(),
+			// at the source
+			vec![("image not found", 36)]
+		)
+	);
+
+	assert_eq!(
+		html(
+			"[img(button.png)(width=176)(decorative)] [img(button.png)(height=62)(decorative)] [img(button.png)(width=10)(height=10)(decorative)]",
+			Some(assets)
+		),
+		(
+			"<div><img src='button.png' alt='' role='presentation' width='176' height='62' /> <img src='button.png' alt='' role='presentation' width='176' height='62' /> <img src='button.png' alt='' role='presentation' width='10' height='10' /></div>".to_string(),
+			vec![]
+		)
+	);
+
+	// without a resolver, only the props
+	assert_eq!(
+		html("[img(button.png)(width=176)(decorative)]", None),
+		(
+			"<div><img src='button.png' alt='' role='presentation' width='176' /></div>"
+				.to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn rewriter() {
+	let mut out = target();
+	out.url_rewriter = Some(Arc::new(|url, kind| match url {
+		"missing.png" => None,
+		_ if url.starts_with("https:") => Some(url.to_string()),
+		_ => Some(format!("/cdn/{}/{url}", format!("{kind:?}").to_lowercase())),
+	}));
+	out.image_proxy = Some(Arc::new(|src| format!("{src}?proxied")));
+
+	let (diagnostics, dependencies) = emit(
+		"[link(report.pdf):a] [link(https://a.example):b] [img(button.png)(decorative)]\n\n[video(clip.mp4)(src=clip.webm)(poster=button.png)] [img(missing.png)(decorative)]",
+		&mut out,
+	);
+	assert_eq!(
+		out.write,
+		"<div><a href='&#x2F;cdn&#x2F;link&#x2F;report.pdf'><span>a</span></a> <a href='https:&#x2F;&#x2F;a.example'><span>b</span></a> <img src='&#x2F;cdn&#x2F;image&#x2F;button.png?proxied' alt='' role='presentation' /></div><div><video class='doll-video' controls poster='/cdn/image/button.png?proxied'><source src='/cdn/video/clip.mp4' type='video/mp4' /><source src='/cdn/video/clip.webm' type='video/webm' /></video> <img src='missing.png?proxied' alt='' role='presentation' /></div>"
+	);
+	// at the argument
+	assert_eq!(diagnostics, [("asset url could not be rewritten", 137)]);
+	// the link's file is recorded when rewritten, and the rest only when parsed
+	assert_eq!(
+		dependencies,
+		[
+			"button.png",
+			"clip.mp4",
+			"clip.webm",
+			"button.png",
+			"missing.png",
+			"report.pdf"
+		]
+	);
+}
+
+#[cfg(feature = "fs-assets")]
+#[test]
+pub fn fs_copier() {
+	let root = std::env::temp_dir().join(format!("markdoll-copier-{}", std::process::id()));
+	let out = root.join("out");
+	std::fs::create_dir_all(root.join("img")).unwrap();
+	std::fs::write(root.join("img/button.gif"), b"GIF89a\x58\x00\x1f\x00").unwrap();
+	std::fs::write(root.join("img/copy.gif"), b"GIF89a\x58\x00\x1f\x00").unwrap();
+
+	use markdoll::emit::AssetKind;
+
+	let copier = markdoll::emit::fs_copier(&root, &out);
+	let prefix = out.to_string_lossy().replace('\\', "/");
+
+	let url = copier("img/button.gif?v=2", AssetKind::Image).unwrap();
+	let name = url
+		.strip_prefix(&format!("{prefix}/"))
+		.and_then(|url| url.strip_suffix("?v=2"))
+		.unwrap();
+	assert!(name.ends_with(".gif"), "{name}");
+	assert_eq!(
+		std::fs::read(out.join(name)).unwrap(),
+		b"GIF89a\x58\x00\x1f\x00"
+	);
+	// named by their content, so the same files share a copy
+	assert_eq!(
+		copier("/img/copy.gif", AssetKind::Image).unwrap(),
+		format!("{prefix}/{name}")
+	);
+
+	assert_eq!(copier("img/missing.png", AssetKind::Image), None);
+	// files outside of the root aren't published
+	let secret = root.with_extension("secret");
+	std::fs::write(&secret, b"secret").unwrap();
+	let name = secret.file_name().unwrap().to_string_lossy();
+	assert_eq!(copier(&format!("../{name}"), AssetKind::Link), None);
+	assert_eq!(copier(&format!("img/../../{name}"), AssetKind::Link), None);
+	std::fs::remove_file(secret).unwrap();
+	assert_eq!(
+		copier("https://a.example/button.gif", AssetKind::Link).as_deref(),
+		Some("https://a.example/button.gif")
+	);
+
+	std::fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "fs-assets")]
+#[test]
+pub fn fs_resolver() {
+	let root = std::env::temp_dir().join(format!("markdoll-assets-{}", std::process::id()));
+	std::fs::create_dir_all(root.join("img")).unwrap();
+	std::fs::write(root.join("img/button.gif"), b"GIF89a\x58\x00\x1f\x00").unwrap();
+	std::fs::write(root.join("notes.txt"), b"not an image").unwrap();
+
+	let resolver = markdoll::emit::fs_resolver(&root);
+
+	assert_eq!(resolver("img/button.gif"), image("image/gif", 88, 31));
+	assert_eq!(resolver("/img/button.gif?v=2"), image("image/gif", 88, 31));
+	assert_eq!(
+		resolver("notes.txt"),
+		Some(AssetInfo {
+			exists: true,
+			..AssetInfo::default()
+		})
+	);
+	assert_eq!(resolver("img/missing.png"), Some(AssetInfo::default()));
+	assert_eq!(resolver("https://a.example/button.gif"), None);
+
+	std::fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "fs-assets")]
+#[test]
+pub fn fs_asset_path() {
+	use {markdoll::emit::fs_asset_path, std::path::Path};
+
+	let root = Path::new("src/assets");
+	assert_eq!(
+		fs_asset_path(root, "img/pic.png?v=2#top"),
+		Some(root.join("img/pic.png"))
+	);
+	assert_eq!(
+		fs_asset_path(root, "/./img/../pic.png"),
+		Some(root.join("pic.png"))
+	);
+	assert_eq!(fs_asset_path(root, "../pic.png"), None);
+	assert_eq!(fs_asset_path(root, "img/../../pic.png"), None);
+	assert_eq!(
+		fs_asset_path(Path::new(""), "pic.png"),
+		Some("pic.png".into())
+	);
+}
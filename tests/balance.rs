@@ -0,0 +1,82 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, TagDefinition},
+};
+
+fn emit(src: &str, check_balance: bool) -> (String, Vec<(&'static str, usize, usize)>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tags([
+		// forgets to close its element
+		TagDefinition::new("leaky", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| to.write_open(doll, "div", " class='leaky'")),
+		// closes the elements it opens in the wrong order
+		TagDefinition::new("crossed", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| {
+				to.write_open(doll, "b", "");
+				to.write_open(doll, "i", "");
+				to.write_close(doll, "b");
+			}),
+		TagDefinition::new("closer", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| to.write_close(doll, "span")),
+	]);
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit {
+		check_balance,
+		..Default::default()
+	};
+	doll.emit(&mut ast, &mut out);
+
+	(
+		out.write,
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at, diag.len))
+			.collect(),
+	)
+}
+
+#[test]
+pub fn balanced() {
+	assert_eq!(
+		emit("[sub:a [sup:b]] [dfn:c]", true),
+		(
+			"<div><sub><span>a <sup><span>b</span></sup></span></sub> <dfn><span>c</span></dfn></div>".to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn unbalanced() {
+	let src = "a [sub:[leaky]] b\n\n[crossed] [closer]";
+
+	assert_eq!(
+		emit(src, true).1,
+		[
+			// skipped over by the close of the `sub` containing it
+			("element left unclosed", 7, 7),
+			("element left unclosed", 19, 9),
+			("element closed without being opened", 29, 8),
+		]
+	);
+
+	// written the same, without checking
+	assert_eq!(emit(src, false).0, emit(src, true).0);
+	assert!(emit(src, false).1.is_empty());
+}
+
+#[test]
+pub fn unclosed_at_end() {
+	assert_eq!(
+		emit("a\n\n[leaky]", true),
+		(
+			"<div>a</div><div><div class='leaky'></div>".to_string(),
+			vec![("element left unclosed", 3, 7)]
+		)
+	);
+}
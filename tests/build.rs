@@ -0,0 +1,80 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext, tree::build::Doc};
+
+#[test]
+fn builds_and_emits_without_source() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let mut doc = Doc::new(&mut doll);
+	doc.section("overview", |doc| {
+		doc.paragraph(|p| {
+			p.text("built")
+				.split()
+				.text("from code, ")
+				.tag("em", &[], "nested [em:source]");
+		});
+		doc.list(
+			false,
+			[
+				|doc: &mut Doc| {
+					doc.text("first");
+				},
+				|doc: &mut Doc| {
+					doc.tag("code", &[], "second");
+				},
+			],
+		);
+	});
+	let mut ast = doc.finish().expect("builds cleanly");
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+	assert!(out.write.contains("overview"));
+	assert!(out.write.contains("built from code, "));
+	assert_eq!(
+		out.write,
+		"<section data-level='1'><h1>overview</h1><div><div>built from code, <em><span>nested <em><span>source</span></em></span></em></div><ul><li><div>first</div></li><li><div><code>second</code></div></li></ul></div></section>"
+	);
+}
+
+#[test]
+fn diagnostics_point_into_synthetic_source() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let mut doc = Doc::new(&mut doll);
+	doc.text("fine").tag("nonexistent", &[], "content");
+	let src = doc.source().to_string();
+	assert!(doc.finish().is_err());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+
+	let at = diagnostics[0].at;
+	assert_eq!(&src[at..at + "content".len()], "content");
+}
+
+#[test]
+fn diagnostics_within_tag_content() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let mut doc = Doc::new(&mut doll);
+	for _ in 0..100 {
+		doc.text("ä fine");
+	}
+	doc.tag("em", &[], "a\nb [nope]");
+	let src = doc.source().to_string();
+	assert!(doc.finish().is_err());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+
+	// positions count characters
+	let tag = src
+		.chars()
+		.skip(diagnostics[0].at)
+		.take(6)
+		.collect::<String>();
+	assert_eq!(tag, "[nope]");
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use {
+	markdoll::{
+		ext::{self, TagDefinition},
+		tree::TagContent,
+		MarkDoll,
+	},
+	std::sync::atomic::{AtomicUsize, Ordering},
+};
+
+static PURE_PARSES: AtomicUsize = AtomicUsize::new(0);
+static IMPURE_PARSES: AtomicUsize = AtomicUsize::new(0);
+
+fn doll(cache: bool) -> MarkDoll {
+	let mut doll = common::doll_with(&[
+		&ext::code::tags(),
+		&ext::formatting::tags(),
+		&ext::links::tags(),
+	]);
+	doll.cache_pure_tags = cache;
+	doll.ext_system.add_tag(
+		TagDefinition::new(
+			"pure",
+			Some(|_, _, text, _| {
+				PURE_PARSES.fetch_add(1, Ordering::Relaxed);
+				Some(Box::new(text.to_string()))
+			}),
+		)
+		.with_pure(|content| Some(Box::new(content.downcast_ref::<String>().unwrap().clone()))),
+	);
+	doll.ext_system.add_tag(TagDefinition::new(
+		"impure",
+		Some(|_, _, text, _| {
+			IMPURE_PARSES.fetch_add(1, Ordering::Relaxed);
+			Some(Box::new(text.to_string()) as Box<dyn TagContent>)
+		}),
+	));
+	doll
+}
+
+fn html(doll: &mut MarkDoll, src: &str) -> String {
+	common::html_warnings(doll, src).0
+}
+
+#[test]
+pub fn parses_pure_tags_once() {
+	let mut doll = doll(true);
+
+	doll.parse_document("[pure:a] [pure:a] [pure(x):a] [pure:b]\n\n[pure:a]")
+		.unwrap();
+	assert_eq!(PURE_PARSES.swap(0, Ordering::Relaxed), 3);
+	assert_eq!(doll.tag_usage()["pure"], 5);
+
+	// and again in the next source
+	doll.finish();
+	doll.parse_document("[pure:a]").unwrap();
+	assert_eq!(PURE_PARSES.swap(0, Ordering::Relaxed), 1);
+}
+
+#[test]
+pub fn never_caches_impure_tags() {
+	let mut doll = doll(true);
+
+	doll.parse_document("[impure:a] [impure:a] [em:[impure:a]] [em:[impure:a]]")
+		.unwrap();
+	assert_eq!(IMPURE_PARSES.load(Ordering::Relaxed), 4);
+	assert_eq!(doll.tag_usage()["impure"], 4);
+}
+
+#[test]
+pub fn same_as_parsing() {
+	let src = "[em(b):see [link(https://example.com):[code:the site]]] [img(logo.svg):logo]\n\n[em(b):see [link(https://example.com):[code:the site]]] [img(logo.svg):logo]\n\n[img(logo.svg)]\n\n[img(logo.svg)]";
+
+	let mut cached = doll(true);
+	let mut parsed = doll(false);
+	assert_eq!(html(&mut cached, src), html(&mut parsed, src));
+
+	assert_eq!(cached.tag_usage(), parsed.tag_usage());
+	assert_eq!(cached.dependencies(), parsed.dependencies());
+
+	// diagnostics are reported at each invocation
+	let diagnostics = |doll: &mut MarkDoll| {
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(diagnostics(&mut cached), diagnostics(&mut parsed));
+}
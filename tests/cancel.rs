@@ -0,0 +1,137 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self, TagDefinition},
+	},
+	std::{
+		sync::{
+			atomic::{AtomicBool, AtomicU64, Ordering},
+			Arc, Mutex,
+		},
+		thread,
+		time::{Duration, Instant},
+	},
+};
+
+/// `count` paragraphs, each with a tag
+fn document(count: usize) -> String {
+	"a [em:b] c\n\n".repeat(count)
+}
+
+#[test]
+pub fn within_a_tag() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.cancel = Some(Arc::new(AtomicBool::new(false)));
+	// cancels as if the editor had a newer source
+	doll.ext_system.add_tag(TagDefinition::new(
+		"stop",
+		Some(|doll, _, _, _| {
+			doll.cancel.as_ref().unwrap().store(true, Ordering::Relaxed);
+			None
+		}),
+	));
+
+	let src = format!(
+		"{}[em::\n\tbefore [stop]\n\n\tafter\n]\n\n{}",
+		document(10),
+		document(1000)
+	);
+	let mut ast = doll.parse_document(src).unwrap_err().1;
+	assert!(doll.stopped_early());
+	// the paragraphs before it, and the tag it was in
+	assert_eq!(ast.len(), 11);
+
+	// which emits as any other
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	let mut to = HtmlEmit::untrusted_profile();
+	assert!(doll.emit(&mut ast, &mut to));
+	assert!(to.write.contains("before"));
+	assert!(!to.write.contains("after"));
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "parsing cancelled");
+	assert!(diagnostics[0].err);
+
+	// parsing again with it set stops at once
+	assert!(doll.parse_document("a").unwrap_err().1.is_empty());
+	doll.finish();
+
+	doll.cancel
+		.as_ref()
+		.unwrap()
+		.store(false, Ordering::Relaxed);
+	assert!(doll.parse_document("a").is_ok());
+}
+
+#[test]
+pub fn from_another_thread() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	let cancel = Arc::new(AtomicBool::new(false));
+	doll.cancel = Some(Arc::clone(&cancel));
+
+	// once it is partway through the document
+	static STARTED: AtomicBool = AtomicBool::new(false);
+	doll.progress = Some(|progress| {
+		if progress.done > progress.total / 10 {
+			STARTED.store(true, Ordering::Relaxed);
+		}
+	});
+
+	let cancelled_at = Arc::new(Mutex::new(None));
+	let canceller = thread::spawn({
+		let cancelled_at = Arc::clone(&cancelled_at);
+		move || {
+			while !STARTED.load(Ordering::Relaxed) {
+				thread::yield_now();
+			}
+			*cancelled_at.lock().unwrap() = Some(Instant::now());
+			cancel.store(true, Ordering::Relaxed);
+		}
+	});
+
+	let count = 200_000;
+	let ast = doll.parse_document(document(count)).unwrap_err().1;
+	let returned_at = Instant::now();
+	canceller.join().unwrap();
+
+	assert!(!ast.is_empty() && ast.len() < count, "{}", ast.len());
+	let cancelled_at = cancelled_at.lock().unwrap().unwrap();
+	assert!(
+		returned_at.duration_since(cancelled_at) < Duration::from_millis(500),
+		"{:?}",
+		returned_at.duration_since(cancelled_at)
+	);
+	assert_eq!(doll.finish()[0].code, "parsing cancelled");
+}
+
+static NOW: AtomicU64 = AtomicU64::new(0);
+
+/// a clock that moves a millisecond every time it is read
+fn clock() -> Duration {
+	Duration::from_millis(NOW.fetch_add(1, Ordering::Relaxed))
+}
+
+#[test]
+pub fn max_duration() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.max_duration = Some(Duration::from_millis(50));
+
+	// without a clock, there is no limit
+	assert!(doll.parse_document(document(100)).is_ok());
+	doll.finish();
+
+	doll.clock = Some(clock);
+	let ast = doll.parse_document(document(100)).unwrap_err().1;
+	assert!(doll.stopped_early());
+	assert!(!ast.is_empty() && ast.len() < 100, "{}", ast.len());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "parsing took too long");
+
+	// each source has its own
+	assert!(doll.parse_document("a").is_ok());
+}
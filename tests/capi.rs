@@ -0,0 +1,83 @@
+use {
+	markdoll::ffi::{
+		capi::{markdoll_convert_html, markdoll_free},
+		ALLOW_DANGER,
+	},
+	std::ffi::CStr,
+};
+
+/// convert through the C interface, returning whether it was ok, the html, and the diagnostics json
+fn convert(src: &[u8], flags: u32) -> (bool, String, String) {
+	// SAFETY: `src` is valid for its length
+	let mut output = unsafe { markdoll_convert_html(src.as_ptr(), src.len(), flags) };
+
+	// SAFETY: both are nul-terminated strings created by `markdoll_convert_html`
+	let result = unsafe {
+		(
+			output.ok,
+			CStr::from_ptr(output.html).to_str().unwrap().to_string(),
+			CStr::from_ptr(output.diagnostics_json)
+				.to_str()
+				.unwrap()
+				.to_string(),
+		)
+	};
+
+	// SAFETY: returned by `markdoll_convert_html` and not yet freed
+	unsafe { markdoll_free(&mut output) };
+	assert!(output.html.is_null() && output.diagnostics_json.is_null());
+
+	result
+}
+
+#[test]
+pub fn converts() {
+	assert_eq!(
+		convert(b"hello [em:world]", 0),
+		(
+			true,
+			"<div>hello <em><span>world</span></em></div>".to_string(),
+			"[]".to_string()
+		)
+	);
+}
+
+#[test]
+pub fn fixture() {
+	let (ok, html, diagnostics) = convert(include_bytes!("fixtures/errors.doll"), 0);
+
+	assert!(!ok);
+	// what was recovered from the errors
+	assert_eq!(
+		html,
+		include_str!("fixtures/snapshots/errors.doll.html").trim_end()
+	);
+	assert!(diagnostics.starts_with("[{\"code\":"));
+	assert!(diagnostics.contains("\"line\":2"));
+}
+
+#[test]
+pub fn danger_is_opt_in() {
+	let (ok, _, diagnostics) = convert(b"[html:<b>b</b>]", 0);
+	assert!(!ok);
+	assert!(diagnostics.contains("\"code\":\"tag not defined\""));
+
+	let (ok, html, _) = convert(b"[html:<b>b</b>]", ALLOW_DANGER);
+	assert_eq!(ok, cfg!(feature = "danger"));
+	if ok {
+		assert_eq!(html, "<div><b>b</b></div>");
+	}
+}
+
+#[test]
+pub fn empty_and_invalid() {
+	// SAFETY: null is allowed for an empty source
+	let mut output = unsafe { markdoll_convert_html(std::ptr::null(), 0, 0) };
+	assert!(output.ok);
+	// SAFETY: returned by `markdoll_convert_html` and not yet freed
+	unsafe { markdoll_free(&mut output) };
+	// SAFETY: freeing null does nothing
+	unsafe { markdoll_free(std::ptr::null_mut()) };
+
+	assert_eq!(convert(b"a\xFFb", 0).1, "<div>a\u{FFFD}b</div>");
+}
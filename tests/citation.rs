@@ -0,0 +1,121 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::HtmlEmit,
+		ext::{
+			self,
+			citation::{Author, Bibliography, Reference, Style},
+		},
+	},
+	std::sync::Arc,
+};
+
+fn bibliography(style: Style) -> Bibliography {
+	let mut bibliography = [
+		Reference {
+			id: "smith2020".to_string(),
+			authors: vec![Author {
+				family: "Smith".to_string(),
+				given: Some("Jane".to_string()),
+			}],
+			year: Some(2020),
+			title: Some("On Dolls".to_string()),
+			container: Some("Journal of Markup".to_string()),
+			url: None,
+		},
+		Reference {
+			id: "doe2019".to_string(),
+			authors: vec![
+				Author {
+					family: "Doe".to_string(),
+					given: None,
+				},
+				Author {
+					family: "Roe".to_string(),
+					given: None,
+				},
+			],
+			year: None,
+			title: Some("Markup & You".to_string()),
+			container: None,
+			url: Some("https://example.com".to_string()),
+		},
+	]
+	.into_iter()
+	.collect::<Bibliography>();
+	bibliography.style = style;
+
+	bibliography
+}
+
+fn html(src: &str, bibliography: Option<Bibliography>) -> (String, Vec<&'static str>) {
+	let mut doll = common::doll_with(&[&ext::citation::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	ext::citation::collect(&doll, &mut ast);
+
+	let mut out = HtmlEmit {
+		bibliography: bibliography.map(Arc::new),
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let warnings = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| {
+			assert!(!diagnostic.err);
+			diagnostic.code
+		})
+		.collect();
+
+	(out.write, warnings)
+}
+
+#[test]
+pub fn author_year() {
+	let (out, warnings) = html(
+		"see [cite(doe2019)] and [cite(smith2020)]\n\n[references]",
+		Some(bibliography(Style::AuthorYear)),
+	);
+
+	assert!(warnings.is_empty());
+	assert_eq!(
+		out,
+		"<div>see <a class='doll-cite' href='#ref-doe2019'>(Doe and Roe n.d.)</a> and <a class='doll-cite' href='#ref-smith2020'>(Smith 2020)</a></div>\
+		<div><ul class='doll-references'>\
+			<li id='ref-doe2019'>Doe; Roe (n.d.). Markup &amp; You. <a href='https://example.com'>https://example.com</a></li>\
+			<li id='ref-smith2020'>Smith, Jane (2020). On Dolls. <em>Journal of Markup</em>.</li>\
+		</ul></div>"
+	);
+}
+
+#[test]
+pub fn numeric() {
+	let (out, warnings) = html(
+		"[references]\n\n[cite(smith2020)] [cite(doe2019)] [cite(smith2020)]",
+		Some(bibliography(Style::Numeric)),
+	);
+
+	assert!(warnings.is_empty());
+	assert!(out.starts_with(
+		"<div><ol class='doll-references'><li id='ref-smith2020'>Smith, Jane (2020)."
+	));
+	assert!(out.ends_with(
+		"<div><a class='doll-cite' href='#ref-smith2020'>[1]</a> <a class='doll-cite' href='#ref-doe2019'>[2]</a> <a class='doll-cite' href='#ref-smith2020'>[1]</a></div>"
+	));
+}
+
+#[test]
+pub fn unknown_key() {
+	let bibliography = bibliography(Style::AuthorYear);
+	assert_eq!(bibliography.near_matches("smith202"), ["smith2020"]);
+
+	let (out, warnings) = html("[cite(smith202)]", Some(bibliography));
+	assert_eq!(warnings, ["citation key not found"]);
+	assert_eq!(out, "<div>[smith202]</div>");
+
+	let (_, warnings) = html("[cite(smith2020)]", None);
+	assert_eq!(warnings, ["no bibliography for citation"]);
+}
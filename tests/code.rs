@@ -0,0 +1,162 @@
+mod common;
+
+use {
+	markdoll::{
+		diagnostics::Data,
+		emit::HtmlEmit,
+		ext::{self, code::dedent},
+	},
+	std::sync::Arc,
+};
+
+#[test]
+pub fn dedent_strips_common_indentation() {
+	assert_eq!(dedent("\t\ta\n\t\t\tb\n\t\tc"), "a\n\tb\nc");
+	assert_eq!(dedent("    a\n  b"), "  a\nb");
+}
+
+#[test]
+pub fn dedent_ignores_blank_lines() {
+	assert_eq!(dedent("\ta\n\n\tb"), "a\n\nb");
+	assert_eq!(dedent("\t\ta\n\t\n\t\tb"), "a\n\nb");
+	assert_eq!(dedent("\ta\n\t\t\t\n\tb"), "a\n\t\t\nb");
+}
+
+#[test]
+pub fn dedent_does_not_mix_tabs_and_spaces() {
+	assert_eq!(dedent("\ta\n    b"), "\ta\n    b");
+	assert_eq!(dedent("\t  a\n\t b"), " a\nb");
+}
+
+#[test]
+pub fn dedent_unindented_first_line() {
+	assert_eq!(dedent("a\n\tb\n\tc"), "a\n\tb\n\tc");
+}
+
+fn emit(src: &str) -> String {
+	common::html_warnings(&mut common::doll_with(&[&ext::code::tags()]), src).0
+}
+
+#[test]
+pub fn diff_lines() {
+	assert_eq!(
+		emit("[codeblock(diff)(hl=2)::\n\t@@ -1 +1 @@\n\t-old <\n\t+new\n\t same\n]"),
+		"<div><figure class='doll-code-block'><pre><span class='doll-diff-hunk'>@@ -1 +1 @@</span>\n<span class='doll-hl-line'><span class='doll-diff-del'>-old &lt;</span></span>\n<span class='doll-diff-add'>+new</span>\n same</pre></figure></div>"
+	);
+}
+
+#[test]
+pub fn non_diff_lines() {
+	assert_eq!(
+		emit("[codeblock::\n\t-old\n\t+new\n]"),
+		"<div><figure class='doll-code-block'><pre>-old\n+new</pre></figure></div>"
+	);
+}
+
+#[test]
+pub fn highlighted_lines_with_a_language() {
+	// a formatter with an element across lines, as multi-line comments are
+	let to = HtmlEmit {
+		code_block_format: Arc::new(|_, to, _, src| {
+			to.write.push_str("<pre class='x'><i>");
+			to.write.push_str(&src.replace("b\n", "b<b class='c'>\n"));
+			to.write.push_str("</b></i></pre>");
+		}),
+		..Default::default()
+	};
+
+	let (out, warnings) = common::html_to(
+		&mut common::doll_with(&[&ext::code::tags()]),
+		"[codeblock(txt)(hl=2-3)::\n\ta\n\tb\n\tc\n\td\n]",
+		to,
+	);
+	assert!(warnings.is_empty());
+	assert_eq!(
+		out,
+		"<div><figure class='doll-code-block' data-lang='txt'><pre class='x'><i>a\n</i><span class='doll-hl-line'><i>b<b class='c'></b></i></span><i><b class='c'>\n</b></i><span class='doll-hl-line'><i><b class='c'>c</b></i></span><i><b class='c'>\nd</b></i></pre></figure></div>"
+	);
+}
+
+#[test]
+pub fn highlighted_lines_outside() {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+
+	assert!(doll
+		.parse_document("[codeblock(linenos=3)(hl=1-4,6,9-10)::\n\ta\n\tb\n]")
+		.is_ok());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(
+		diagnostics[0].data,
+		Some(Data::LinesOutside {
+			lines: "1-2,6,9-10".to_string(),
+			first: 3,
+			last: 4,
+		})
+	);
+}
+
+#[test]
+pub fn highlighted_lines_overflowing() {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+
+	for hl in ["18446744073709551615", "1-18446744073709551615"] {
+		assert!(doll
+			.parse_document(format!("[codeblock(hl={hl})::\n\ta\n]"))
+			.is_err());
+
+		let diagnostics = doll.finish();
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(
+			diagnostics[0].code,
+			"prop hl invalid, expected lines or ranges such as `1,4-6,12`"
+		);
+	}
+}
+
+#[test]
+pub fn line_numbers_overflowing() {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+
+	for src in [
+		"[codeblock(linenos=18446744073709551615)::\n\ta\n]",
+		"[codeblock(linenos=18446744073709551614)::\n\ta\n\tb\n]",
+	] {
+		assert!(doll.parse_document(src).is_err());
+
+		let diagnostics = doll.finish();
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].code, "prop linenos invalid");
+	}
+
+	// the last line can still be numbered as high as it goes
+	common::html(
+		&mut doll,
+		"[codeblock(linenos=18446744073709551613)::\n\ta\n\tb\n]",
+	);
+}
+
+#[cfg(feature = "highlight-syntect")]
+#[test]
+pub fn syntect_highlights_known_languages() {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+
+	let mut ast = doll
+		.parse_document("[codeblock(rust)::\n\tfn main() {}\n]\n\n[codeblock(nope)::\n\t<x>\n]")
+		.unwrap()
+		.1;
+
+	let mut out = HtmlEmit {
+		code_block_format: Arc::new(ext::code::syntect_highlighter("InspiredGitHub")),
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+
+	assert_eq!(out.write.matches("<style>").count(), 1);
+	assert!(out.write.contains(
+		"<span class=\"doll-syn-storage doll-syn-type doll-syn-function doll-syn-rust\">fn</span>"
+	));
+	assert!(out.write.contains("<pre>&lt;x&gt;</pre>"));
+	assert_eq!(doll.finish().len(), 1);
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use {
+	markdoll::{
+		diagnostics::{codes, explain, Profile},
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext,
+	},
+	std::{fs, path::Path},
+};
+
+/// the string literals passed where diagnostic codes are, skipping the pieces the `args!` macro concatenates
+fn literal_codes(src: &str) -> Vec<String> {
+	const CALLS: [&str; 8] = [
+		".diag(",
+		".diag_arg(",
+		"warn(",
+		".err(",
+		".err_next(",
+		"Err(\"",
+		".ok_or(\"",
+		"{ .. } => \"",
+	];
+
+	let mut found = Vec::new();
+
+	for call in CALLS {
+		for (start, _) in src.match_indices(call) {
+			let mut chars = src[start + call.len()..].chars();
+			let mut depth = 1;
+			let mut literal = None::<String>;
+
+			if call.ends_with('"') {
+				literal = Some(String::new());
+			}
+
+			while let Some(ch) = chars.next() {
+				match (&mut literal, ch) {
+					(Some(text), '"') => {
+						if !text.starts_with(' ') && !text.ends_with(' ') {
+							found.push(text.clone());
+						}
+						literal = None;
+
+						if call.ends_with('"') {
+							break;
+						}
+					}
+					(Some(text), '\\') => {
+						text.push(ch);
+						text.extend(chars.next());
+					}
+					(Some(text), _) => text.push(ch),
+					(None, '"') => literal = Some(String::new()),
+					(None, '(') => depth += 1,
+					(None, ')') => {
+						depth -= 1;
+						if depth == 0 {
+							break;
+						}
+					}
+					(None, _) => {}
+				}
+			}
+		}
+	}
+
+	found
+}
+
+fn visit(dir: &Path, codes: &mut Vec<(String, String)>) {
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+
+		if path.is_dir() {
+			visit(&path, codes);
+		} else if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with("main.rs") {
+			codes.extend(
+				literal_codes(&fs::read_to_string(&path).unwrap())
+					.into_iter()
+					.map(|code| (path.display().to_string(), code)),
+			);
+		}
+	}
+}
+
+#[test]
+pub fn every_emitted_code_is_registered() {
+	let mut found = Vec::new();
+	visit(
+		&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+		&mut found,
+	);
+
+	assert!(found.len() > 50, "only found {} codes", found.len());
+
+	let missing = found
+		.iter()
+		.filter(|(_, code)| explain(code).is_none())
+		.collect::<Vec<_>>();
+	assert!(missing.is_empty(), "unregistered codes: {missing:#?}");
+
+	// the `args!` macro fills in the name of the argument
+	assert_eq!(
+		explain("arg count invalid").map(|entry| entry.code),
+		Some("arg <name> invalid")
+	);
+	assert_eq!(
+		explain("argument src required").map(|entry| entry.code),
+		Some("argument <name> required")
+	);
+	assert_eq!(
+		explain("prop linenos invalid").map(|entry| entry.code),
+		Some("prop <name> invalid")
+	);
+	assert!(explain("arg count of columns invalid").is_none());
+	assert!(explain("arg  invalid").is_none());
+}
+
+#[test]
+pub fn codes_are_unique() {
+	for (i, entry) in codes().iter().enumerate() {
+		assert_eq!(
+			explain(entry.code).map(|entry| entry.code),
+			Some(entry.code),
+			"{i}"
+		);
+	}
+}
+
+#[test]
+pub fn profiles_override_registered_codes() {
+	for profile in [Profile::Default, Profile::Strict, Profile::Relaxed] {
+		for (code, _) in profile.overrides() {
+			assert!(explain(code).is_some(), "{profile:?} overrides {code:?}");
+		}
+	}
+}
+
+#[test]
+pub fn examples_emit_their_code() {
+	let mut failed = Vec::new();
+
+	for entry in codes().iter().filter(|entry| !entry.example.is_empty()) {
+		if cfg!(not(feature = "qr")) && entry.example.starts_with("[qr") {
+			continue;
+		}
+
+		let mut doll = common::doll_with(&[
+			&ext::citation::tags(),
+			&ext::common::tags(),
+			&ext::conditional::tags(),
+			&ext::formatting::tags(),
+			&ext::code::tags(),
+			&ext::datetime::tags(),
+			&ext::diagram::tags(),
+			&ext::emoji::tags(),
+			&ext::glossary::tags(),
+			&ext::index::tags(),
+			&ext::layout::tags(),
+			&ext::links::tags(),
+			&ext::math::tags(),
+			&ext::media::tags(),
+			&ext::page::tags(),
+		]);
+		#[cfg(feature = "qr")]
+		doll.ext_system.add_tags(ext::qr::tags());
+		doll.ext_system.add_tags(ext::table::tags());
+		doll.ext_system.add_tags(ext::tabs::tags());
+		doll.ext_system.add_tags(ext::todo::tags());
+		doll.ext_system.add_tags(ext::var::tags());
+		doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+		if let Ok((_, mut ast)) = doll.parse_document(entry.example) {
+			let mut out = HtmlEmit {
+				draft_mode: ext::todo::DraftPolicy::Error,
+				..Default::default()
+			};
+
+			doll.emit(&mut ast, &mut out);
+		}
+
+		let emitted = doll
+			.finish()
+			.iter()
+			.map(|diag| diag.code)
+			.collect::<Vec<_>>();
+
+		if !emitted.iter().any(|code| entry.matches(code)) {
+			failed.push((entry.code, emitted));
+		}
+	}
+
+	assert!(
+		failed.is_empty(),
+		"examples emitting other codes: {failed:#?}"
+	);
+}
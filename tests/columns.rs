@@ -0,0 +1,48 @@
+mod common;
+
+use markdoll::ext;
+
+fn html(src: &str) -> (String, Vec<&'static str>) {
+	common::html_warnings(&mut common::doll_with(&[&ext::layout::tags()]), src)
+}
+
+#[test]
+pub fn flow() {
+	assert_eq!(
+		html("[columns::\n\tfirst\n\n\tsecond\n]").0,
+		"<div><div class='doll-columns' style='column-count: 2'><div>first</div><div>second</div></div></div>"
+	);
+	assert_eq!(
+		html("[columns(3):text]").0,
+		"<div><div class='doll-columns' style='column-count: 3'><span>text</span></div></div>"
+	);
+}
+
+#[test]
+pub fn explicit() {
+	assert_eq!(
+		html("[columns::\n\t-\tleft\n\t-\tmiddle\n\t-\tright\n]").0,
+		"<div><div class='doll-columns' style='column-count: 3'><div class='doll-column'><span>left</span></div><div class='doll-column'><span>middle</span></div><div class='doll-column'><span>right</span></div></div></div>"
+	);
+	assert_eq!(
+		html("[columns(2)::\n\t-\tleft\n\t-\tmiddle\n\t-\tright\n]").0,
+		"<div><div class='doll-columns' style='column-count: 2'><div class='doll-column'><span>left</span></div><div class='doll-column'><span>middle</span></div><div class='doll-column'><span>right</span></div></div></div>"
+	);
+}
+
+#[test]
+pub fn nested() {
+	assert_eq!(
+		html("[columns::\n\t[columns:inner]\n]").1,
+		["columns nested within columns"]
+	);
+}
+
+#[test]
+pub fn invalid_count() {
+	for src in ["[columns(1):text]", "[columns(many):text]"] {
+		assert!(common::doll_with(&[&ext::layout::tags()])
+			.parse_document(src)
+			.is_err());
+	}
+}
@@ -0,0 +1,80 @@
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, todo::DraftPolicy},
+	tree::print::{self, PrintOptions},
+	MarkDoll,
+};
+
+const SRC: &str = "a [//:check <this>] b
+
+[//::
+	many
+
+	lines
+]";
+
+fn html(keep_comments: bool, draft_mode: DraftPolicy) -> (bool, String, Vec<&'static str>) {
+	let mut doll = MarkDoll::new();
+	doll.keep_comments = keep_comments;
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document(SRC).unwrap().1;
+
+	let mut out = HtmlEmit {
+		draft_mode,
+		..Default::default()
+	};
+	let ok = doll.emit(&mut ast, &mut out);
+
+	(
+		ok,
+		out.write,
+		doll.finish().iter().map(|diag| diag.code).collect(),
+	)
+}
+
+#[test]
+pub fn left_out_unless_kept() {
+	for draft_mode in [DraftPolicy::Show, DraftPolicy::Hide, DraftPolicy::Error] {
+		assert_eq!(
+			html(false, draft_mode),
+			(true, "<div>a  b</div>".to_string(), vec![])
+		);
+	}
+}
+
+#[test]
+pub fn shown_in_drafts() {
+	assert_eq!(
+		html(true, DraftPolicy::Show),
+		(
+			true,
+			"<div>a <aside class='doll-comment'>check &lt;this&gt;</aside> b</div><div><aside class='doll-comment'>many\n\nlines</aside></div>"
+				.to_string(),
+			vec![]
+		)
+	);
+
+	assert_eq!(
+		html(true, DraftPolicy::Hide),
+		(true, "<div>a  b</div><div></div>".to_string(), vec![])
+	);
+
+	let (ok, _, codes) = html(true, DraftPolicy::Error);
+	assert!(!ok);
+	assert_eq!(codes, ["comment in release", "comment in release"]);
+}
+
+#[test]
+pub fn round_trips() {
+	let mut doll = MarkDoll::new();
+	doll.keep_comments = true;
+	doll.ext_system.add_tags(ext::common::tags());
+
+	let ast = doll.parse_document(SRC).unwrap().1;
+	assert_eq!(
+		print::to_doll(&doll, &ast, PrintOptions::default()).trim_end(),
+		SRC
+	);
+}
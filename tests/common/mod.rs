@@ -0,0 +1,84 @@
+//! fixtures shared by the tests
+
+#![allow(dead_code, reason = "each test uses some of these")]
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, TagDefinition},
+	MarkDoll,
+};
+
+/// an instance with each of `tags`, emitting to html
+pub fn doll_with(tags: &[&[TagDefinition]]) -> MarkDoll {
+	let mut doll = MarkDoll::new();
+	for tag in tags.iter().copied().flatten() {
+		doll.ext_system.add_tag(tag.clone());
+	}
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	doll
+}
+
+/// an instance with every standard tag, emitting to html
+pub fn doll() -> MarkDoll {
+	doll_with(&[
+		&ext::citation::tags(),
+		&ext::common::tags(),
+		&ext::conditional::tags(),
+		&ext::formatting::tags(),
+		&ext::code::tags(),
+		&ext::datetime::tags(),
+		&ext::diagram::tags(),
+		&ext::emoji::tags(),
+		&ext::glossary::tags(),
+		&ext::index::tags(),
+		&ext::layout::tags(),
+		&ext::links::tags(),
+		&ext::math::tags(),
+		&ext::media::tags(),
+		&ext::page::tags(),
+		&ext::table::tags(),
+		&ext::tabs::tags(),
+		&ext::todo::tags(),
+		&ext::var::tags(),
+	])
+}
+
+/// a target writing code blocks with a language as `<pre lang='..'>`, so they show in the output
+pub fn lang_target() -> HtmlEmit {
+	HtmlEmit {
+		code_block_format: std::sync::Arc::new(|_, to, lang, src| {
+			to.write
+				.push_str(&format!("<pre lang='{lang}'>{src}</pre>"));
+		}),
+		..Default::default()
+	}
+}
+
+/// parse and emit `src` into `to`, returning the output and the codes of the diagnostics, which must all be warnings
+pub fn html_to(doll: &mut MarkDoll, src: &str, mut to: HtmlEmit) -> (String, Vec<&'static str>) {
+	let mut ast = doll.parse_document(src).unwrap().1;
+	assert!(doll.emit(&mut ast, &mut to));
+
+	let warnings = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| {
+			assert!(!diagnostic.err, "{}", diagnostic.code);
+			diagnostic.code
+		})
+		.collect();
+
+	(to.write, warnings)
+}
+
+/// parse and emit `src`, returning the output and the codes of the diagnostics, which must all be warnings
+pub fn html_warnings(doll: &mut MarkDoll, src: &str) -> (String, Vec<&'static str>) {
+	html_to(doll, src, HtmlEmit::default())
+}
+
+/// parse and emit `src`, which must have no diagnostics, returning the output
+pub fn html(doll: &mut MarkDoll, src: &str) -> String {
+	let (out, warnings) = html_warnings(doll, src);
+	assert!(warnings.is_empty(), "{warnings:?}");
+	out
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext, MarkDoll};
+
+fn html(doll: &mut MarkDoll, ast: &mut markdoll::tree::AST, flags: &[&str]) -> String {
+	let mut out = HtmlEmit {
+		flags: flags.iter().map(ToString::to_string).collect(),
+		..Default::default()
+	};
+	assert!(doll.emit(ast, &mut out));
+
+	out.write
+}
+
+fn parse(src: &str) -> (MarkDoll, markdoll::tree::AST) {
+	let mut doll = common::doll_with(&[&ext::conditional::tags()]);
+
+	let ast = doll.parse_document(src).unwrap().1;
+
+	(doll, ast)
+}
+
+#[test]
+pub fn same_document_per_flags() {
+	let (mut doll, mut ast) = parse("a [if(draft):draft][if(not:draft):final]");
+
+	assert_eq!(
+		html(&mut doll, &mut ast, &["draft"]),
+		"<div>a <span>draft</span></div>"
+	);
+	assert_eq!(
+		html(&mut doll, &mut ast, &[]),
+		"<div>a <span>final</span></div>"
+	);
+	assert!(doll.finish().iter().all(|diagnostic| !diagnostic.err));
+}
+
+#[test]
+pub fn targets_and_or() {
+	let (mut doll, mut ast) = parse("[if(target:print):print][if(target:print,target:html):both]");
+
+	assert_eq!(
+		html(&mut doll, &mut ast, &[]),
+		"<div><span>both</span></div>"
+	);
+	assert!(doll.finish().is_empty());
+}
+
+#[test]
+pub fn undefined_flag() {
+	let (mut doll, mut ast) = parse("[if(draft):draft]");
+
+	assert_eq!(html(&mut doll, &mut ast, &["print"]), "<div></div>");
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert!(!diagnostics[0].err);
+	assert_eq!(diagnostics[0].code, "flag not defined");
+}
+
+#[test]
+pub fn invalid_condition() {
+	let mut doll = common::doll_with(&[&ext::conditional::tags()]);
+
+	assert!(doll.parse_document("[if(a,,b):text]").is_err());
+	assert_eq!(doll.finish()[0].code, "condition invalid");
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use {
+	markdoll::{emit::HtmlEmit, ext::TagDefinition, MarkDoll},
+	std::collections::HashMap,
+};
+
+/// the messages a `msg` tag may name, given by the embedder
+#[derive(Debug, Clone)]
+struct Messages(HashMap<String, String>);
+
+fn doll() -> MarkDoll {
+	let mut doll = common::doll_with(&[]);
+	doll.ext_system.add_tag(
+		TagDefinition::new(
+			"msg",
+			Some(|doll, args, _, _| {
+				let key = args.first()?;
+
+				// validated while parsing, rather than waiting to emit
+				let Some(message) = doll
+					.context
+					.get_ref::<Messages>()
+					.and_then(|messages| messages.0.get(*key))
+				else {
+					doll.diag(true, usize::MAX, "message not defined");
+					return None;
+				};
+
+				Some(Box::new(message.clone()))
+			}),
+		)
+		.with_emitter::<HtmlEmit>(|_, to, content| {
+			to.write.push_str(content.downcast_ref::<String>().unwrap());
+		}),
+	);
+	doll
+}
+
+#[test]
+pub fn tags_read_context_while_parsing() {
+	let mut doll = doll();
+	doll.context.put(Messages(HashMap::from([(
+		"greeting".to_string(),
+		"hello".to_string(),
+	)])));
+
+	let mut ast = doll.parse_document("[msg(greeting)], world").unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert_eq!(out.write, "<div>hello, world</div>");
+
+	// kept across documents
+	assert!(doll.finish().is_empty());
+	assert!(doll.parse_document("[msg(greeting)]").is_ok());
+	assert!(doll.parse_document("[msg(farewell)]").is_err());
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| diag.code)
+			.collect::<Vec<_>>(),
+		["message not defined"]
+	);
+}
+
+#[test]
+pub fn missing_context() {
+	let mut doll = doll();
+	assert!(doll.parse_document("[msg(greeting)]").is_err());
+}
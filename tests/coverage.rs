@@ -0,0 +1,45 @@
+use markdoll::{
+	emit::HtmlEmit,
+	ext,
+	testing::{assert_tag_coverage, Target},
+};
+
+#[test]
+pub fn standard_tags() {
+	let mut tags = Vec::new();
+	tags.extend(ext::citation::tags());
+	tags.extend(ext::code::tags());
+	tags.extend(ext::common::tags());
+	tags.extend(ext::conditional::tags());
+	#[cfg(feature = "danger")]
+	tags.extend(ext::danger::tags());
+	tags.extend(ext::datetime::tags());
+	tags.extend(ext::diagram::tags());
+	tags.extend(ext::emoji::tags());
+	tags.extend(ext::formatting::tags());
+	tags.extend(ext::glossary::tags());
+	tags.extend(ext::index::tags());
+	tags.extend(ext::layout::tags());
+	tags.extend(ext::links::tags());
+	tags.extend(ext::math::tags());
+	tags.extend(ext::media::tags());
+	tags.extend(ext::page::tags());
+	#[cfg(feature = "qr")]
+	tags.extend(ext::qr::tags());
+	tags.extend(ext::table::tags());
+	tags.extend(ext::tabs::tags());
+	tags.extend(ext::todo::tags());
+	tags.extend(ext::var::tags());
+
+	let html = Target::of::<HtmlEmit>();
+	assert_tag_coverage(
+		&tags,
+		&[html],
+		&[
+			// only parsed within the tag containing them, which emits them itself
+			("tr", html),
+			("tc", html),
+			("tab", html),
+		],
+	);
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext};
+
+#[test]
+pub fn raw_html_is_not_escaped() {
+	let mut doll = common::doll_with(&[&ext::danger::tags()]);
+
+	let mut ast = doll
+		.parse_document(
+			"a [html:<b>b</b>]\n\n[html::\n\t<details>\n\t\t<summary>c</summary>\n\t</details>\n]",
+		)
+		.unwrap()
+		.1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	assert_eq!(
+		out.write,
+		"<div>a <b>b</b></div><div><details>\n\t<summary>c</summary>\n</details></div>"
+	);
+}
@@ -0,0 +1,56 @@
+use {markdoll::emit::DEFAULT_CSS, std::path::Path};
+
+/// the `doll-` classes written in the sources under `dir`, leaving out prefixes completed at runtime, such as `doll-tabs-{}`
+fn classes(dir: &Path, classes: &mut Vec<String>) {
+	for entry in std::fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+
+		if path.is_dir() {
+			self::classes(&path, classes);
+		} else if path.extension().is_some_and(|ext| ext == "rs") {
+			let src = std::fs::read_to_string(&path).unwrap();
+
+			for (i, _) in src.match_indices("doll-") {
+				// custom properties, such as `--doll-accent`
+				if src[..i].ends_with('-') {
+					continue;
+				}
+
+				let class = src[i..]
+					.split(|ch: char| !(ch.is_ascii_lowercase() || ch == '-'))
+					.next()
+					.unwrap();
+
+				if !class.ends_with('-') {
+					classes.push(class.to_string());
+				}
+			}
+		}
+	}
+}
+
+#[test]
+pub fn styles_every_class() {
+	let mut emitted = Vec::new();
+	classes(Path::new("src/ext"), &mut emitted);
+	classes(Path::new("src/emit"), &mut emitted);
+	// `doll-{element}`
+	emitted.extend(["doll-video".to_string(), "doll-audio".to_string()]);
+
+	emitted.sort();
+	emitted.dedup();
+	assert!(emitted.len() > 30);
+
+	let unstyled = emitted
+		.iter()
+		.filter(|class| {
+			!DEFAULT_CSS
+				.match_indices(&format!(".{class}"))
+				.any(|(i, selector)| {
+					!DEFAULT_CSS[i + selector.len()..]
+						.starts_with(|ch: char| ch.is_ascii_lowercase() || ch == '-')
+				})
+		})
+		.collect::<Vec<_>>();
+	assert!(unstyled.is_empty(), "unstyled classes: {unstyled:?}");
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, DeferredEmit, HtmlEmit, SourceMap},
+	ext::{self, TagDefinition},
+	MarkDoll,
+};
+
+/// how many `mark` tags have been emitted
+#[derive(Debug, Clone, Default)]
+struct Marks(usize);
+
+fn doll() -> MarkDoll {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tags([
+		TagDefinition::new("mark", Some(|_, _, _, _| Some(Box::new(())))).with_emitter::<HtmlEmit>(
+			|doll, to, _| {
+				doll.scratch.get_or_insert_with(Marks::default).0 += 1;
+				to.write.push('*');
+			},
+		),
+		TagDefinition::new("total", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| {
+				to.defer(doll, |doll, to| {
+					let marks = doll.scratch.get_ref::<Marks>().map_or(0, |marks| marks.0);
+					to.write.push_str(&format!("<b>{marks}</b>"));
+				});
+			}),
+		TagDefinition::new("late", Some(|_, _, _, _| Some(Box::new(())))).with_emitter::<HtmlEmit>(
+			|doll, to, _| {
+				to.defer(doll, |doll, _| {
+					doll.diag(false, usize::MAX, "flag not defined")
+				});
+			},
+		),
+		TagDefinition::new("nested", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| {
+				to.defer(doll, |doll, to| to.defer(doll, |_, _| unreachable!()));
+			}),
+	]);
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	doll
+}
+
+fn emit(doll: &mut MarkDoll, src: &str, source_map: bool) -> (bool, HtmlEmit) {
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit {
+		source_map: source_map.then(SourceMap::default),
+		..Default::default()
+	};
+	let ok = doll.emit(&mut ast, &mut out);
+
+	(ok, out)
+}
+
+#[test]
+pub fn emitted_after_document() {
+	let mut doll = doll();
+
+	let (ok, out) = emit(
+		&mut doll,
+		"[total] [total]\n\n[mark] [mark]\n\n[mark]",
+		false,
+	);
+	assert!(ok);
+	assert_eq!(
+		out.write,
+		"<div><b>3</b> <b>3</b></div><div>* *</div><div>*</div>"
+	);
+	assert!(doll.finish().is_empty());
+
+	// each emit starts over
+	let (_, out) = emit(&mut doll, "[total]", false);
+	assert_eq!(out.write, "<div><b>0</b></div>");
+	doll.finish();
+}
+
+#[test]
+pub fn source_map() {
+	let mut doll = doll();
+
+	let (_, out) = emit(&mut doll, "a [total] b\n\n[mark]", true);
+	assert_eq!(out.write, "<div>a <b>1</b> b</div><div>*</div>");
+
+	let map = out.source_map.unwrap();
+	// the spliced output is the tag's, and the output after it has moved
+	assert_eq!(map.source_at(out.write.find("<b>").unwrap()), Some(2));
+	assert_eq!(map.source_at(out.write.find("1").unwrap()), Some(2));
+	assert_eq!(map.source_at(out.write.find(" b").unwrap()), Some(9));
+	assert_eq!(map.source_at(out.write.find("*").unwrap()), Some(13));
+
+	doll.finish();
+}
+
+#[test]
+pub fn diagnostics() {
+	let mut doll = doll();
+
+	let (ok, _) = emit(&mut doll, "ab [late]\n\n[em::\n\t[nested]\n]", false);
+	assert!(!ok);
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[
+			("flag not defined", 3),
+			("deferred emit within deferred emit", 18),
+		]
+	);
+}
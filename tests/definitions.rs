@@ -0,0 +1,54 @@
+mod common;
+
+use markdoll::{
+	emit::HtmlEmit,
+	ext::{self, ExtensionSystem},
+	tree::{BlockItem, InlineItem},
+};
+
+#[test]
+pub fn tags_keep_their_definition() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let mut ast = doll.parse_document("[em:a]").unwrap().1;
+
+	let BlockItem::Inline(items) = &ast[0] else {
+		panic!("expected inline")
+	};
+	let InlineItem::Tag(tag) = &items[0].1 else {
+		panic!("expected tag")
+	};
+	assert_eq!(tag.definition().key, "em");
+
+	// emitting doesn't look the tag up again
+	doll.ext_system = ExtensionSystem::default();
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert_eq!(out.write, "<div><em><span>a</span></em></div>");
+}
+
+#[test]
+pub fn registered_tags() {
+	let mut system = ExtensionSystem::default();
+	system.add_tags(ext::table::tags());
+	system.add_tags(ext::formatting::tags());
+
+	let keys = system.tags().map(|tag| tag.key).collect::<Vec<_>>();
+	assert_eq!(keys.len(), system.tags().len());
+	assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+	assert!(keys.contains(&"em") && keys.contains(&"table"));
+
+	assert_eq!(system.get_tag("em").unwrap().key, "em");
+	assert!(system.get_tag("missing").is_none());
+
+	let old = system
+		.replace_tag(ext::TagDefinition::new("em", None))
+		.unwrap();
+	assert!(old.parse.is_some());
+	assert!(system.get_tag("em").unwrap().parse.is_none());
+
+	assert!(system.remove_tag("em").is_some());
+	assert!(system.remove_tag("em").is_none());
+	assert_eq!(system.tags().len(), keys.len() - 1);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use markdoll::{
+	dependencies::{self, Dependency, DependencyKind},
+	ext, MarkDoll,
+};
+
+fn paths(doll: &MarkDoll) -> Vec<&str> {
+	doll.dependencies()
+		.iter()
+		.map(|dependency| dependency.path.as_str())
+		.collect()
+}
+
+#[test]
+pub fn records_local_assets() {
+	let mut doll = common::doll_with(&[&ext::links::tags(), &ext::media::tags()]);
+
+	doll.parse_document(
+		"[img(a.png):a] [img(https://b.example/b.png):b]
+[video(c.webm)(src=//d.example/c.mp4)(poster=c.png)]
+[audio(data:audio/ogg;base64,AA==)]",
+	)
+	.unwrap();
+
+	assert_eq!(paths(&doll), ["a.png", "c.webm", "c.png"]);
+	assert!(doll
+		.dependencies()
+		.iter()
+		.all(|dependency| dependency.kind == DependencyKind::Asset));
+	assert_eq!(doll.dependencies()[0].at, 0);
+
+	doll.finish();
+	assert!(doll.dependencies().is_empty());
+}
+
+#[test]
+pub fn session_keeps_dependencies() {
+	let mut doll = common::doll_with(&[&ext::links::tags(), &ext::media::tags()]);
+
+	doll.parse_document("[img(outer.png):a]").unwrap();
+	doll.session(|doll| doll.parse_document("[img(inner.png):b]").unwrap());
+
+	assert_eq!(paths(&doll), ["outer.png", "inner.png"]);
+}
+
+#[test]
+pub fn local_urls() {
+	for url in ["a.png", "../a b.png", "/a.png", "a.png?v=1", "C:a.png"] {
+		assert!(dependencies::is_local(url), "{url}");
+	}
+
+	for url in [
+		"",
+		"#top",
+		"//a.example/a.png",
+		"https://a.example",
+		"data:,a",
+	] {
+		assert!(!dependencies::is_local(url), "{url}");
+	}
+}
+
+#[test]
+pub fn depfile() {
+	let dependency = |path: &str| Dependency {
+		kind: DependencyKind::Asset,
+		path: path.to_string(),
+		at: 0,
+		modified: None,
+	};
+
+	assert_eq!(
+		dependencies::depfile(
+			"out/#1.html",
+			&[
+				dependency("a b.png"),
+				dependency("c.png?v=2"),
+				dependency("a b.png#x"),
+				dependency("$a.png"),
+			]
+		),
+		"out/\\#1.html: \\
+  a\\ b.png \\
+  c.png \\
+  $$a.png
+
+a\\ b.png:
+
+c.png:
+
+$$a.png:
+"
+	);
+
+	assert_eq!(dependencies::depfile("out.html", &[]), "out.html:\n");
+}
+
+#[test]
+pub fn content_fingerprint() {
+	let fingerprint = |src: &str| {
+		let mut doll = common::doll_with(&[&ext::links::tags(), &ext::media::tags()]);
+		doll.ext_system.add_tags(ext::formatting::tags());
+
+		let ast = doll.parse_document(src).unwrap().1;
+		let fingerprint = doll.content_fingerprint(&ast);
+		doll.finish();
+		fingerprint
+	};
+
+	let src = "intro [em:a [em:b]]
+
+&section
+	-	[img(a.png):c]
+	-	d";
+
+	assert_eq!(fingerprint(src), fingerprint(src));
+
+	// only the positions differ
+	assert_eq!(fingerprint(src), fingerprint(&format!("\n\n{src}")));
+
+	assert_ne!(fingerprint(src), fingerprint(&src.replace("b]", "c]")));
+	assert_ne!(
+		fingerprint(src),
+		fingerprint(&src.replace("intro", "intra"))
+	);
+	assert_ne!(
+		fingerprint(src),
+		fingerprint(&src.replace("a.png", "b.png"))
+	);
+	assert_ne!(fingerprint(src), fingerprint(&src.replace('-', "=")));
+	assert_ne!(fingerprint("[em:ab]"), fingerprint("[em:a][em:b]"));
+}
+
+#[test]
+pub fn content_fingerprint_modified() {
+	let mut doll = common::doll_with(&[&ext::links::tags(), &ext::media::tags()]);
+
+	let ast = doll.parse_document("[img(a.png):a]").unwrap().1;
+	let unknown = doll.content_fingerprint(&ast);
+
+	doll.dependencies_mut()[0].modified = Some(1_700_000_000);
+	let modified = doll.content_fingerprint(&ast);
+	assert_ne!(unknown, modified);
+
+	doll.dependencies_mut()[0].modified = Some(1_700_000_001);
+	assert_ne!(modified, doll.content_fingerprint(&ast));
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use markdoll::{
+	diagnostics::Data,
+	emit::HtmlEmit,
+	ext,
+	tree::{BlockItem, InlineItem, ListItem},
+};
+
+#[test]
+pub fn deep_ast_is_truncated() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.parse_document("").unwrap();
+
+	let mut ast = vec![BlockItem::Inline(vec![(
+		0,
+		InlineItem::Text("deepest".into()),
+	)])];
+	for depth in 0..100_000 {
+		ast = vec![if depth % 2 == 0 {
+			BlockItem::Section {
+				pos: 0,
+				name: "section".into(),
+				meta: Default::default(),
+				children: ast,
+			}
+		} else {
+			BlockItem::List {
+				pos: 0,
+				ordered: false,
+				items: vec![ListItem {
+					pos: 0,
+					value: None,
+					ast,
+				}],
+			}
+		}];
+	}
+
+	let mut out = HtmlEmit::default();
+	assert!(!doll.emit(&mut ast, &mut out));
+	assert!(!out.write.contains("deepest"));
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "nested too deeply to emit");
+	assert_eq!(diagnostics[0].data, Some(Data::TooDeep { max_depth: 256 }));
+}
+
+#[test]
+pub fn deep_tags_are_truncated() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.max_emit_depth = 8;
+
+	let src = format!("{}deepest{}", "[em:".repeat(16), "]".repeat(16));
+	let mut ast = doll.parse_document(&*src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(!doll.emit(&mut ast, &mut out));
+	assert_eq!(out.write.matches("<em>").count(), 8);
+	assert!(!out.write.contains("deepest"));
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "nested too deeply to emit");
+	assert_eq!(diagnostics[0].data, Some(Data::TooDeep { max_depth: 8 }));
+}
+
+#[test]
+pub fn tag_content_counts_towards_depth() {
+	// the standard tags emit the blocks of their content through `BlockItem::emit`, which counts them
+	for src in [
+		"[quote:deepest]",
+		"[em:deepest]",
+		"[link(https://example.com):deepest]",
+		"[table::\n\t[tr::\n\t\t[tc:deepest]\n\t]\n]",
+	] {
+		let mut doll = common::doll();
+		doll.max_emit_depth = 1;
+
+		let mut ast = doll.parse_document(src).unwrap().1;
+
+		let mut out = HtmlEmit::default();
+		assert!(!doll.emit(&mut ast, &mut out), "{src}");
+		assert!(!out.write.contains("deepest"), "{src}");
+
+		assert_eq!(
+			doll.finish()
+				.into_iter()
+				.map(|diag| (diag.code, diag.data))
+				.collect::<Vec<_>>(),
+			[(
+				"nested too deeply to emit",
+				Some(Data::TooDeep { max_depth: 1 })
+			)],
+			"{src}"
+		);
+	}
+}
+
+#[test]
+pub fn deep_tags_are_kept_as_text() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let depth = 10_000;
+	let src = format!("{}x{}", "[em:".repeat(depth), "]".repeat(depth));
+
+	let start = std::time::Instant::now();
+	let (_, mut ast) = doll.parse_document(src.as_str()).unwrap_err();
+	assert!(start.elapsed().as_secs() < 10);
+
+	// the deepest tag's content is left as written
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert_eq!(out.write.matches("<em>").count(), 128);
+	assert!(out.write.contains(&format!(
+		"{}x{}",
+		"[em:".repeat(depth - 129),
+		"]".repeat(depth - 129)
+	)));
+
+	assert_eq!(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("tags nested too deeply", 128 * 4)]
+	);
+}
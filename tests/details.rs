@@ -0,0 +1,51 @@
+mod common;
+
+use markdoll::{
+	ext,
+	tree::{plain_text, PlainTextOptions},
+};
+
+#[test]
+pub fn emits_details() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	assert_eq!(
+		common::html(&mut doll, "[details([em:more] [code:x])::\n\tcontent\n]"),
+		"<div><details class='doll-details'><summary><span><em><span>more</span></em> <code>x</code></span></summary><span>content</span></details></div>"
+	);
+	assert_eq!(
+		common::html(
+			&mut doll,
+			"[details(open)::\n\t[details(inner)::\n\t\tnested\n\t]\n]"
+		),
+		"<div><details class='doll-details' open><span><details class='doll-details'><summary><span>inner</span></summary><span>nested</span></details></span></details></div>"
+	);
+}
+
+#[test]
+pub fn summary_is_text() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let ast = doll
+		.parse_document("[details([em:summary])::\n\tbody\n]")
+		.unwrap()
+		.1;
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"summary\nbody"
+	);
+}
+
+#[test]
+pub fn summary_diagnostics_point_to_tag() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	assert!(doll
+		.parse_document("&section\n\t[details([nope:x])::\n\t\tbody\n\t]")
+		.is_err());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "tag not defined");
+	assert_eq!(diagnostics[0].at, 10);
+}
@@ -0,0 +1,477 @@
+mod common;
+
+use markdoll::{
+	diagnostics::{self, LineCol, LineIndex, Profile, Severity},
+	emit::HtmlEmit,
+	ext,
+	tree::Shorthand,
+	MarkDoll, Phase,
+};
+
+#[test]
+pub fn severity_overrides() {
+	let mut doll = MarkDoll::new();
+	assert!(doll.parse_document("  a").is_ok());
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert!(!diagnostics[0].err);
+
+	doll.severity_overrides
+		.insert("erroneous leading spaces".into(), Some(true));
+	assert!(doll.parse_document("  a").is_err());
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert!(diagnostics[0].err);
+
+	doll.severity_overrides
+		.insert("erroneous leading spaces".into(), None);
+	assert!(doll.parse_document("  a\n\n  b").is_ok());
+	assert_eq!(doll.suppressed, 2);
+	assert!(doll.finish().is_empty());
+	assert_eq!(doll.suppressed, 0);
+}
+
+#[test]
+pub fn profiles() {
+	let severities = |doll: &mut MarkDoll, src: &str| {
+		let ok = doll.parse_document(src).is_ok();
+		let diagnostics = doll
+			.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.err))
+			.collect::<Vec<_>>();
+
+		(ok, diagnostics)
+	};
+
+	let mut doll = MarkDoll::new();
+	doll.set_profile(Profile::Strict);
+	assert_eq!(
+		severities(&mut doll, "  a"),
+		(false, vec![("erroneous leading spaces", true)])
+	);
+
+	// individual overrides set afterwards take precedence
+	doll.severity_overrides
+		.insert("erroneous leading spaces".into(), Some(false));
+	assert_eq!(
+		severities(&mut doll, "  a"),
+		(true, vec![("erroneous leading spaces", false)])
+	);
+
+	let mut doll = MarkDoll::new();
+	doll.set_profile(Profile::Relaxed);
+	assert_eq!(
+		severities(&mut doll, "  a [nope]"),
+		(true, vec![("tag not defined", false)])
+	);
+
+	let mut doll = MarkDoll::new();
+	doll.set_profile(Profile::Default);
+	assert!(doll.severity_overrides.is_empty());
+}
+
+#[test]
+pub fn collapses_similar_diagnostics() {
+	let mut doll = MarkDoll::new();
+	doll.similar_diagnostics_limit = 2;
+
+	let src = "  a\n\n".repeat(5);
+
+	assert!(doll.parse_document(&*src).is_ok());
+	let diagnostics = doll.finish_collapsed();
+	assert_eq!(diagnostics.len(), 2);
+	assert!(diagnostics[0].similar.is_empty());
+	assert_eq!(diagnostics[1].similar, [10, 15, 20]);
+
+	assert!(doll.parse_document(&*src).is_ok());
+	assert_eq!(doll.finish().len(), 5);
+}
+
+#[test]
+pub fn summarizes_by_code() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+	doll.similar_diagnostics_limit = 2;
+	doll.severity_overrides
+		.insert("image has no alt text".to_string(), None);
+
+	let src = format!("{}[nope]\n\n[img(a.png)] [nope] [nah]", "  a\n\n".repeat(5));
+	assert!(doll.parse_document(&*src).is_err());
+	let suppressed = doll.suppressed;
+	let diagnostics = doll.finish_collapsed();
+
+	assert_eq!(
+		diagnostics::summarize(&diagnostics)
+			.iter()
+			.map(|entry| (entry.code, entry.severity, entry.count, entry.first))
+			.collect::<Vec<_>>(),
+		[
+			("tag not defined", Severity::Error, 3, 25),
+			("erroneous leading spaces", Severity::Warning, 5, 0),
+		]
+	);
+	// collapsed, but counted
+	assert!(diagnostics.len() < 8);
+	assert_eq!(suppressed, 1);
+}
+
+#[test]
+pub fn structured_locations() {
+	let mut doll = MarkDoll::new();
+	doll.similar_diagnostics_limit = 1;
+
+	let src = "ä\n\n  a\n\n  b";
+	assert!(doll.parse_document(src).is_ok());
+	let diagnostics = doll.finish_collapsed();
+	assert_eq!(diagnostics.len(), 1);
+
+	let structured = diagnostics[0].to_structured(&LineIndex::new(src));
+	assert_eq!(structured.severity, Severity::Warning);
+	assert_eq!(
+		structured.help.as_deref(),
+		Some("...and 1 more similar warning")
+	);
+	assert_eq!(
+		structured
+			.labels
+			.iter()
+			.map(|label| (label.at, label.line, label.col))
+			.collect::<Vec<_>>(),
+		[(3, 3, 1), (8, 5, 1)]
+	);
+}
+
+#[cfg(feature = "ariadne")]
+#[test]
+pub fn render_all_without_color() {
+	use markdoll::diagnostics::{render_all, RenderOptions};
+
+	let mut doll = MarkDoll::new();
+
+	let src = "  a";
+	assert!(doll.parse_document(src).is_ok());
+	let rendered = render_all(
+		&doll.finish(),
+		src,
+		RenderOptions {
+			color: false,
+			..Default::default()
+		},
+	);
+
+	assert_eq!(rendered.len(), 1);
+	assert!(rendered[0].starts_with("Warning: erroneous leading spaces"));
+	assert!(!rendered[0].contains('\x1b'));
+}
+
+#[test]
+pub fn line_col_three_levels_deep() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let src = "a\n[em::\n\t[em::\n\t\t[em::\n\t\t\t  b\n\t\t]\n\t]\n]";
+	assert!(doll.parse_document(src).is_ok());
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+
+	assert_eq!(
+		LineCol::of(src, diagnostics[0].at),
+		LineCol { line: 5, col: 4 }
+	);
+	assert_eq!(
+		LineIndex::new(src).line_col(diagnostics[0].at),
+		LineCol { line: 5, col: 4 }
+	);
+}
+
+#[test]
+pub fn line_col_after_multibyte_lines() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let src = "a [quote::\n\tééé\n\t\t[nope:x]\n]";
+	let _ = doll.parse_document(src);
+	let diagnostics = doll.finish();
+	let undefined = diagnostics
+		.iter()
+		.find(|diag| diag.code == "tag not defined")
+		.unwrap();
+	assert_eq!(LineCol::of(src, undefined.at), LineCol { line: 3, col: 3 });
+	assert_eq!(undefined.len, "[nope:x]".chars().count());
+
+	let src = "a\n[em::\n\té\n\t[em::\n\t\tü\n\t\t[em::\n\t\t\tö\n\t\t\t  b\n\t\t]\n\t]\n]";
+	assert!(doll.parse_document(src).is_ok());
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(
+		LineIndex::new(src).line_col(diagnostics[0].at),
+		LineCol { line: 8, col: 4 }
+	);
+}
+
+#[test]
+pub fn errors_fail_containing_operations() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	// the error is within the content of a tag, which is parsed in its own operation
+	assert!(doll.parse_document("[em:[nope:a]]").is_err());
+	assert!(!doll.is_ok());
+	doll.finish();
+	assert!(doll.is_ok());
+
+	assert!(doll.parse_document("a").is_ok());
+
+	let mut operation = doll.begin_operation();
+	operation.diag(false, 0, "just a warning");
+	assert!(operation.end());
+	assert!(doll.is_ok());
+
+	let mut operation = doll.begin_operation();
+	operation.diag(true, 0, "an error");
+	assert!(!operation.end());
+	assert!(!doll.is_ok());
+}
+
+#[test]
+pub fn positions_after_escapes() {
+	let positions = |src: &str| {
+		let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+		doll.shorthands.push(Shorthand::bold());
+		let _ = doll.parse(src);
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>()
+	};
+
+	// escapes in inline content are left out of it, but not out of positions within it
+	assert_eq!(positions("[em:ab[nope]]"), [("tag not defined", 6)]);
+	assert_eq!(positions("[em:a\\]b[nope]]"), [("tag not defined", 8)]);
+	assert_eq!(positions("[em:\\\\\\][nope\\]]]"), [("tag not defined", 8)]);
+	assert_eq!(
+		positions("[em:a\\]b[em:c\\]d[nope]]]"),
+		[("tag not defined", 16)]
+	);
+
+	// and the same for shorthands
+	assert_eq!(positions("*a\\*b[nope]*"), [("tag not defined", 5)]);
+}
+
+#[test]
+pub fn positions_at_end_of_content() {
+	// reports at the end of its content, and at its last character
+	let end = || {
+		ext::TagDefinition::new(
+			"end",
+			Some(|doll, _, text, _| {
+				let len = text.chars().count();
+				doll.diag(true, len, "at end");
+				doll.diag(true, len.saturating_sub(1), "at last");
+				Some(Box::new(()))
+			}),
+		)
+	};
+
+	for src in [
+		"[end:]",
+		"[end:a]",
+		"[end::\n]",
+		"[end::\n\ta\n]",
+		// content that runs to the end of the source, empty or not
+		"[end:",
+		"[end::\n",
+		"[end::\n\t",
+		"[end::\n\ta",
+		"[em::\n\t[end::\n\t\t",
+		"- [end::\n\t\ta",
+		"a\n[em::\n\t[end::\n\t\tä",
+		// frontmatter left unclosed
+		"---\ntitle: x\n--\nbody",
+		"---\n",
+	] {
+		let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+		doll.ext_system.add_tag(end());
+		let _ = doll.parse_document(src);
+
+		let len = src.chars().count();
+		for diag in doll.finish() {
+			assert!(diag.at + diag.len <= len, "{src:?}: {diag:?}");
+			if let Some((at, _)) = diag.context {
+				assert!(at < len, "{src:?}: {diag:?}");
+			}
+		}
+	}
+
+	// kept on the last character when past it
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(end());
+	let _ = doll.parse_document("[end::\n\ta");
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("unterminated tag block", 0), ("at end", 8), ("at last", 8)]
+	);
+}
+
+#[test]
+pub fn tag_spans() {
+	let spans = |src: &str| {
+		let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::links::tags()]);
+		let _ = doll.parse(src);
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at, diag.len))
+			.collect::<Vec<_>>()
+	};
+
+	// diagnostics at a tag cover it through its closing `]`, whether inline or block
+	assert_eq!(spans("a [img:b] c"), [("argument src required", 2, 7)]);
+	assert_eq!(
+		spans("a\n[img::\n\tb\n\tc\n]\nd"),
+		[("argument src required", 2, 14)]
+	);
+	assert_eq!(
+		spans("[quote::\n\t[img::\n\t\tb\n\t]\n]"),
+		[("argument src required", 10, 13)]
+	);
+
+	// an undefined tag is reported at itself
+	assert_eq!(spans("[quote::\n\t[nope]\n]"), [("tag not defined", 10, 6)]);
+
+	// and other diagnostics within a tag only at their position
+	assert_eq!(
+		spans("[quote::\n\t  a\n]"),
+		[("erroneous leading spaces", 10, 1)]
+	);
+
+	let src = "[quote::\n\t[img::\n\t\tb\n\t]\n]";
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::links::tags()]);
+	let _ = doll.parse(src);
+	let structured = doll.finish()[0].to_structured(&LineIndex::new(src));
+	assert_eq!(
+		structured
+			.labels
+			.iter()
+			.map(|label| (label.line, label.col, label.length))
+			.collect::<Vec<_>>(),
+		[(2, 2, 13)]
+	);
+}
+
+#[test]
+pub fn unclosed_brackets() {
+	let unclosed = |src: &str| {
+		let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+		let _ = doll.parse(src);
+		doll.finish_collapsed()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at, diag.context, diag.similar))
+			.collect::<Vec<_>>()
+	};
+
+	// reported at the `[` rather than where the line ended, besides the content being unterminated when it is parsed
+	assert_eq!(
+		unclosed("[em:a [b\nc"),
+		[
+			(
+				"unclosed bracket in tag content",
+				6,
+				Some((8, "expected `]` before here")),
+				vec![]
+			),
+			("unexpected EOI", 7, None, vec![])
+		]
+	);
+
+	// each one left open, wherever it is
+	assert_eq!(
+		unclosed("text [em:[em:a] [b [em:c] d [e\n")[0],
+		(
+			"unclosed bracket in tag content",
+			16,
+			Some((30, "expected `]` before here")),
+			vec![28]
+		)
+	);
+	assert_eq!(
+		unclosed("[em:[[a"),
+		[(
+			"unclosed bracket in tag content",
+			4,
+			Some((6, "expected `]` before here")),
+			vec![5]
+		)]
+	);
+
+	// a literal `[` that took the tag's `]` is pointed to
+	assert_eq!(
+		unclosed("[em:see [em:a] or [ here]\nmore")[0],
+		(
+			"unexpected newline",
+			25,
+			Some((
+				18,
+				"this `[` took the `]` that may have been meant to close the tag"
+			)),
+			vec![]
+		)
+	);
+	assert_eq!(unclosed("[em:a"), [("unexpected EOI", 4, None, vec![])]);
+}
+
+#[test]
+pub fn phases() {
+	let mut doll = common::doll_with(&[&ext::todo::tags()]);
+
+	let mut ast = doll
+		.parse_document("  [todo:write this]\n\n&a\n\t\t&c\n\t\t\td")
+		.unwrap_err()
+		.1;
+	assert_eq!(doll.phase(), Phase::Parse);
+
+	// a pass's own diagnostics, between those of parsing and emitting
+	doll.run_pass("lint", |doll| {
+		doll.diag(false, 0, "erroneous leading spaces");
+		doll.run_pass("nested", |doll| doll.diag(false, 2, "tag has no content"));
+		assert_eq!(doll.phase(), Phase::Pass("lint"));
+	});
+	assert_eq!(doll.phase(), Phase::Parse);
+
+	let mut out = HtmlEmit {
+		draft_mode: ext::todo::DraftPolicy::Error,
+		..Default::default()
+	};
+	doll.emit(&mut ast, &mut out);
+	assert_eq!(doll.phase(), Phase::Parse);
+
+	// in the order they were reported, not collapsed across phases
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics
+			.iter()
+			.map(|diag| (diag.code, diag.phase))
+			.collect::<Vec<_>>(),
+		[
+			("erroneous leading spaces", Phase::Parse),
+			("unexpected indentation", Phase::Parse),
+			("section skips a level", Phase::Pass("heading levels")),
+			("erroneous leading spaces", Phase::Pass("lint")),
+			("tag has no content", Phase::Pass("nested")),
+			("todo in release", Phase::Emit),
+		]
+	);
+	assert_eq!(
+		diagnostics[3].to_structured(&LineIndex::new("")).phase,
+		Phase::Pass("lint")
+	);
+
+	let summary = diagnostics::summarize(&diagnostics);
+	assert_eq!(
+		summary
+			.iter()
+			.filter(|entry| entry.code == "erroneous leading spaces")
+			.map(|entry| entry.phase)
+			.collect::<Vec<_>>(),
+		[Phase::Parse, Phase::Pass("lint")]
+	);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{DiagramRenderer, HtmlEmit},
+		ext,
+	},
+	std::sync::Arc,
+};
+
+fn html(src: &str) -> (String, Vec<(bool, &'static str)>) {
+	let mut doll = common::doll_with(&[&ext::diagram::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let dot: Arc<DiagramRenderer> = Arc::new(|src| {
+		if src.starts_with("digraph") {
+			Ok(format!("<svg>{}</svg>", src.len()))
+		} else {
+			Err("dot syntax invalid")
+		}
+	});
+
+	let mut out = HtmlEmit {
+		diagram_renderers: [("dot".to_string(), dot)].into_iter().collect(),
+		..Default::default()
+	};
+	doll.emit(&mut ast, &mut out);
+
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| (diagnostic.err, diagnostic.code))
+		.collect();
+
+	(out.write, diagnostics)
+}
+
+#[test]
+pub fn renders() {
+	let (out, diagnostics) = html("[diagram(dot)::\n\tdigraph { a -> b }\n]");
+
+	assert!(diagnostics.is_empty());
+	assert_eq!(out, "<div><svg>18</svg></div>");
+}
+
+#[test]
+pub fn preformatted() {
+	let (out, diagnostics) = html("[diagram(preformatted):a -> b \\[c\\]]");
+
+	assert!(diagnostics.is_empty());
+	assert_eq!(
+		out,
+		"<div><pre class='doll-diagram-src'>a -&gt; b \\[c\\]</pre></div>"
+	);
+}
+
+#[test]
+pub fn failures_show_source() {
+	let (out, diagnostics) = html("[diagram(dot):graph][diagram(mermaid):a --> b]");
+
+	assert_eq!(
+		diagnostics,
+		[
+			(true, "dot syntax invalid"),
+			(true, "diagram kind not registered")
+		]
+	);
+	assert_eq!(
+		out,
+		"<div><pre class='doll-diagram-src'>graph</pre><pre class='doll-diagram-src'>a --&gt; b</pre></div>"
+	);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, TagDefinition},
+	tree::InlineItem,
+	MarkDoll,
+};
+
+/// a target with only a tag emitter
+struct Plain(String);
+
+fn doll() -> MarkDoll {
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tags([TagDefinition::new(
+		"t",
+		Some(|_, _, text, _| Some(Box::new(text.to_string()))),
+	)
+	.with_emitter::<Plain>(|_, to, content| {
+		to.0.push_str(content.downcast_ref::<String>().unwrap());
+	})]);
+	doll
+}
+
+#[test]
+pub fn missing_builtin_emitters() {
+	let mut doll = doll();
+	assert!(!doll.has_emitters_for::<Plain>());
+	assert!(!doll.has_emitters_for::<HtmlEmit>());
+
+	let mut ast = doll.parse_document("[t:a]\n\n[t:b]\n\n- [t:c]").unwrap().1;
+
+	let mut out = Plain(String::new());
+	assert!(!doll.emit(&mut ast, &mut out));
+	assert!(!doll.emit_block(&mut ast[0], &mut out));
+	assert!(out.0.is_empty());
+
+	// once per emit, not per block
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics.iter().map(|diag| diag.code).collect::<Vec<_>>(),
+		[
+			"no built-in emitters for this emit target",
+			"no built-in emitters for this emit target"
+		]
+	);
+}
+
+#[test]
+pub fn missing_builtin_emitters_without_a_source() {
+	// from a fresh instance
+	let mut doll = MarkDoll::new();
+	assert!(!doll.emit(&mut Vec::new(), &mut HtmlEmit::default()));
+	assert_eq!(
+		doll.finish()[0].code,
+		"no built-in emitters for this emit target"
+	);
+
+	// after finishing the parse
+	let mut doll = self::doll();
+	let mut ast = doll.parse_document("[t:a]").unwrap().1;
+	assert!(doll.finish().is_empty());
+	assert!(!doll.emit(&mut ast, &mut HtmlEmit::default()));
+	assert_eq!(
+		doll.finish()[0].code,
+		"no built-in emitters for this emit target"
+	);
+}
+
+#[test]
+pub fn has_emitters_for() {
+	let mut doll = doll();
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	assert!(doll.has_emitters_for::<HtmlEmit>());
+	assert!(!doll.has_emitters_for::<Plain>());
+}
+
+#[test]
+pub fn emitting_is_repeatable() {
+	let mut doll = common::doll_with(&[
+		&ext::citation::tags(),
+		&ext::common::tags(),
+		&ext::conditional::tags(),
+		&ext::formatting::tags(),
+		&ext::code::tags(),
+		&ext::datetime::tags(),
+		&ext::diagram::tags(),
+		&ext::emoji::tags(),
+		&ext::glossary::tags(),
+		&ext::index::tags(),
+		&ext::layout::tags(),
+		&ext::links::tags(),
+		&ext::math::tags(),
+		&ext::media::tags(),
+		&ext::page::tags(),
+		&ext::table::tags(),
+		&ext::tabs::tags(),
+		&ext::todo::tags(),
+		&ext::var::tags(),
+	]);
+	// only the text outside of tags, which have no emitters for it
+	doll.set_emitters(BuiltInEmitters::<Plain> {
+		inline: |doll, to, segments, _| {
+			for (_, segment) in segments {
+				match segment {
+					InlineItem::Split | InlineItem::Break => to.0.push(' '),
+					InlineItem::Text(text) => to.0.push_str(text),
+					InlineItem::Tag(tag) => tag.emit(doll, to),
+				}
+			}
+		},
+		section: |doll, to, _, name, _, children| {
+			to.0.push_str(name);
+			for block in children {
+				block.emit(doll, to, true);
+			}
+		},
+		list: |doll, to, _, items| {
+			for item in items {
+				for block in &mut item.ast {
+					block.emit(doll, to, true);
+				}
+			}
+		},
+	});
+
+	let mut ast = doll.parse_document(include_str!("../spec.doll")).unwrap().1;
+	ext::index::collect(&doll, &mut ast);
+	ext::citation::collect(&doll, &mut ast);
+	let fingerprint = doll.content_fingerprint(&ast);
+
+	let html = |doll: &mut MarkDoll, ast: &mut _| {
+		let mut out = HtmlEmit::untrusted_profile();
+		out.max_output_bytes = usize::MAX;
+		assert!(doll.emit(ast, &mut out));
+		out.write
+	};
+	let plain = |doll: &mut MarkDoll, ast: &mut _| {
+		let mut out = Plain(String::new());
+		doll.emit(ast, &mut out);
+		out.0
+	};
+
+	let first = html(&mut doll, &mut ast);
+	let first_plain = plain(&mut doll, &mut ast);
+	assert!(first_plain.contains("markdoll"));
+	assert_eq!(html(&mut doll, &mut ast), first);
+	assert_eq!(plain(&mut doll, &mut ast), first_plain);
+	assert_eq!(html(&mut doll, &mut ast), first);
+
+	assert_eq!(doll.content_fingerprint(&ast), fingerprint);
+	doll.finish();
+}
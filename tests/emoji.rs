@@ -0,0 +1,70 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, emoji},
+};
+
+fn html(src: &str, replace: bool, labelled: bool) -> String {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+	if labelled {
+		doll.ext_system
+			.add_tag(emoji::emoji::tag().with_emitter::<HtmlEmit>(emoji::emoji::html_labelled));
+	} else {
+		doll.ext_system.add_tags(emoji::tags());
+	}
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	if replace {
+		emoji::replace_shortcodes(&doll, &mut ast);
+	}
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+
+	out.write
+}
+
+#[test]
+pub fn table_is_sorted() {
+	assert!(emoji::SHORTCODES
+		.windows(2)
+		.all(|pair| pair[0].0 < pair[1].0));
+}
+
+#[test]
+pub fn tag() {
+	assert_eq!(
+		html("shiny [emoji(sparkles)]", false, false),
+		"<div>shiny ✨</div>"
+	);
+	assert_eq!(
+		html("shiny [emoji(sparkles)]", false, true),
+		"<div>shiny <span class='doll-emoji' role='img' aria-label='sparkles'>✨</span></div>"
+	);
+}
+
+#[test]
+pub fn unknown() {
+	let mut doll = common::doll_with(&[&ext::emoji::tags()]);
+
+	assert!(doll.parse_document("[emoji(sparkle)]").is_err());
+	assert_eq!(doll.finish()[0].code, "emoji not found");
+
+	assert_eq!(emoji::near_matches("sparkle")[0], "sparkles");
+	assert!(emoji::near_matches("nothing like it").is_empty());
+}
+
+#[test]
+pub fn replaces_shortcodes() {
+	assert_eq!(
+		html(
+			":tada: at 12:30:45, a:fire: :nope: :+1::rocket: [code:x :fire:]",
+			true,
+			false
+		),
+		"<div>🎉 at 12:30:45, a:fire: :nope: 👍🚀 <code>x :fire:</code></div>"
+	);
+}
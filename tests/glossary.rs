@@ -0,0 +1,93 @@
+mod common;
+
+use markdoll::{
+	emit::HtmlEmit,
+	ext::{
+		self,
+		glossary::{link_terms, LinkTermsOptions},
+	},
+	tree::print::{self, PrintOptions},
+};
+
+fn linked(src: &str, options: LinkTermsOptions) -> String {
+	let mut doll = common::doll_with(&[&ext::code::tags(), &ext::glossary::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	link_terms(&doll, &mut ast, options);
+
+	print::to_doll(&doll, &ast, PrintOptions::default())
+		.trim_end()
+		.to_string()
+}
+
+#[test]
+pub fn links_first_occurrence_per_section() {
+	assert_eq!(
+		linked(
+			"[term(API):an application programming interface]\n\nthe api and the API\n\n&usage\n\tcall the API, not [code:API] or APIs",
+			LinkTermsOptions::default()
+		),
+		"[term(API):an application programming interface]\n\nthe [termref(API):api] and the API\n\n&usage\n\tcall the [termref(API):API], not [code:API] or APIs"
+	);
+}
+
+#[test]
+pub fn options() {
+	let src = "[term(API):interface]\n\nthe api and the API";
+
+	assert_eq!(
+		linked(
+			src,
+			LinkTermsOptions {
+				case_sensitive: true,
+				..LinkTermsOptions::default()
+			}
+		),
+		"[term(API):interface]\n\nthe api and the [termref(API):API]"
+	);
+	assert_eq!(
+		linked(
+			src,
+			LinkTermsOptions {
+				every_occurrence: true,
+				..LinkTermsOptions::default()
+			}
+		),
+		"[term(API):interface]\n\nthe [termref(API):api] and the [termref(API):API]"
+	);
+}
+
+#[test]
+pub fn prefers_longest_term() {
+	// the order terms are defined in doesn't change the result
+	for terms in [
+		"[term(API):interface]\n\n[term(API key):secret]",
+		"[term(API key):secret]\n\n[term(API):interface]",
+	] {
+		let src = format!("{terms}\n\nan API key for the API");
+		let expected =
+			format!("{terms}\n\nan [termref(API key):API key] for the [termref(API):API]");
+
+		assert_eq!(linked(&src, LinkTermsOptions::default()), expected);
+	}
+}
+
+#[test]
+pub fn emits_links() {
+	let mut doll = common::doll_with(&[&ext::code::tags(), &ext::glossary::tags()]);
+
+	let mut ast = doll
+		.parse_document("[term(build system):compiles things]\n\nthe build system")
+		.unwrap()
+		.1;
+	link_terms(&doll, &mut ast, LinkTermsOptions::default());
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+
+	assert_eq!(
+		out.write,
+		"<div><div class='doll-term' id='term-build-system'><dfn>build system</dfn>: <span>compiles things</span></div></div><div>the <a class='doll-term-ref' href='#term-build-system'><span>build system</span></a></div>"
+	);
+}
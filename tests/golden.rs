@@ -0,0 +1,25 @@
+mod common;
+
+use markdoll::testing::{assert_doll_diagnostics, assert_doll_html};
+
+#[test]
+pub fn spec() {
+	assert_doll_html("spec.doll", &mut common::doll());
+}
+
+#[test]
+pub fn fixtures() {
+	let mut fixtures = std::fs::read_dir("tests/fixtures")
+		.unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "doll"))
+		.collect::<Vec<_>>();
+	fixtures.sort();
+	assert!(!fixtures.is_empty());
+
+	let mut doll = common::doll();
+	for fixture in fixtures {
+		assert_doll_html(&fixture, &mut doll);
+		assert_doll_diagnostics(&fixture, &mut doll);
+	}
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, TagDefinition},
+};
+
+/// emit the `probe` tags of a document, which write the heading level they see
+fn levels(src: &str) -> String {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tag(
+		TagDefinition::new("probe", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|_, to, _| {
+				let level = to.current_heading_level();
+				to.write.push_str(&format!("<{level}>"));
+			}),
+	);
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+
+	out.write
+		.split(['<', '>'])
+		.filter(|part| part.parse::<usize>().is_ok())
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+#[test]
+pub fn outside_sections() {
+	assert_eq!(levels("[probe]\n\n-\t[probe]"), "0 0");
+}
+
+#[test]
+pub fn section_content() {
+	assert_eq!(
+		levels("&a\n\t[probe]\n\t&b\n\t\t[em:[probe]]\n\t[probe]\n[probe]"),
+		"1 2 1 0"
+	);
+}
+
+#[test]
+pub fn nested_lists() {
+	assert_eq!(
+		levels("&a\n\t&b\n\t\t-\t-\t[probe]\n\t\t\t-\t[em::\n\t\t\t\t\t[probe]\n\t\t\t\t]"),
+		"2 2"
+	);
+}
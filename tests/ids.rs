@@ -0,0 +1,232 @@
+mod common;
+
+use markdoll::{
+	emit::{html_id, slugify, HtmlEmit, IdAllocator},
+	ext,
+};
+
+#[test]
+pub fn slugs() {
+	for (text, slug) in [
+		("Getting Started", "getting-started"),
+		// punctuation
+		("  C++ / Rust FAQ! ", "c-rust-faq"),
+		("what's new?", "what-s-new"),
+		("a--b__c..d", "a-b-c-d"),
+		("¿¡—…!?", ""),
+		("", ""),
+		// letters and digits of any script
+		("Über Café", "über-café"),
+		// lowercased in context, with a final sigma
+		("ΣΊΣΥΦΟΣ", "σίσυφος"),
+		("日本語 の 見出し", "日本語-の-見出し"),
+		("٣ أرقام", "٣-أرقام"),
+		// not letters or digits
+		("emoji 🎉 party", "emoji-party"),
+		("tab\tand\nnewline", "tab-and-newline"),
+		("2024 plans", "2024-plans"),
+	] {
+		assert_eq!(slugify(text), slug, "{text:?}");
+	}
+}
+
+#[test]
+pub fn generated() {
+	let mut ids = IdAllocator::default();
+
+	assert_eq!(ids.generate("Introduction"), "introduction");
+	assert_eq!(ids.generate("introduction!"), "introduction-2");
+	assert_eq!(ids.generate("Introduction 2"), "introduction-2-2");
+	assert_eq!(ids.generate("INTRODUCTION"), "introduction-3");
+
+	// leading digits and nothing at all
+	assert_eq!(ids.generate("2024 plans"), "id-2024-plans");
+	assert_eq!(ids.generate("٣ أرقام"), "id-٣-أرقام");
+	assert_eq!(ids.generate("!!!"), "id");
+	assert_eq!(ids.generate(""), "id-2");
+	assert_eq!(ids.generate("Über"), "über");
+
+	assert!(ids.is_taken("introduction-3"));
+	assert!(!ids.is_taken("introduction-4"));
+}
+
+#[test]
+pub fn reserved() {
+	let mut ids = IdAllocator::default();
+
+	assert!(ids.reserve("setup"));
+	assert!(ids.reserve("setup-2"));
+	assert!(!ids.reserve("setup"));
+
+	// generated and claimed ids step around reserved ones
+	assert_eq!(ids.generate("Setup"), "setup-3");
+	assert_eq!(ids.claim("setup"), ("setup-4".to_string(), false));
+	assert_eq!(ids.claim("other"), ("other".to_string(), true));
+
+	// but can't take back an id that was already taken
+	assert!(!ids.reserve("other"));
+}
+
+#[test]
+pub fn reserved_before_emit() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+
+	let mut ast = doll
+		.parse_document("[def(setup):a]\n\n[def(setup):b]")
+		.unwrap()
+		.1;
+
+	let mut out = HtmlEmit::default();
+	// written by the author elsewhere in the page
+	out.ids.reserve("ref-setup");
+	doll.emit(&mut ast, &mut out);
+
+	let ids = out
+		.write
+		.split("id='")
+		.skip(1)
+		.map(|rest| &rest[..rest.find('\'').unwrap()])
+		.collect::<Vec<_>>();
+	assert_eq!(ids, ["ref-setup-2", "ref-setup-3"]);
+
+	assert_eq!(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at, diag.context))
+			.collect::<Vec<_>>(),
+		[
+			// reserved rather than claimed by a tag, so without context
+			("id already used in this document", 0, None),
+			("id already used in this document", 16, None),
+		]
+	);
+}
+
+#[test]
+pub fn section_ids() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+
+	let src = "&(id=setup)(class=appendix 'x')\tSetup\n\t[def(setup):a]\n&(id=setup)\tAgain";
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	doll.emit(&mut ast, &mut out);
+
+	assert_eq!(
+		out.write,
+		"<section data-level='1' id='setup' class='appendix &#x27;x&#x27;'><h1>Setup</h1><div><div><div class='doll-ref' id='ref-setup'>[setup]: <span>a</span></div></div></div></section><section data-level='1' id='setup-2'><h1>Again</h1><div></div></section>"
+	);
+	assert_eq!(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at, diag.context))
+			.collect::<Vec<_>>(),
+		[(
+			"id already used in this document",
+			src.rfind('&').unwrap(),
+			Some((0, "first used here"))
+		)]
+	);
+}
+
+#[test]
+pub fn html_ids() {
+	for (text, id) in [
+		("setup", "setup"),
+		("my setup", "my-setup"),
+		("a'b\"c<d>&e", "a-b-c-d--e"),
+		("_private", "_private"),
+		("Über café", "Über-café"),
+		// not a letter first, as `#2024` can't select it
+		("2024", "id-2024"),
+		("-1", "id--1"),
+		("٣", "id-٣"),
+		("🎉", "id--"),
+		("", "id"),
+	] {
+		assert_eq!(html_id(text), id, "{text:?}");
+	}
+}
+
+/// whether `id` is a valid HTML id that can be selected as `#id` without escaping
+fn valid_id(id: &str) -> bool {
+	let ident = |ch: char| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || !ch.is_ascii();
+
+	match id.chars().next() {
+		Some(first) => {
+			(first.is_ascii_alphabetic() || first == '_' || !first.is_ascii())
+				&& id.chars().all(ident)
+				&& !id.contains(|ch: char| ch.is_ascii_whitespace())
+		}
+		None => false,
+	}
+}
+
+#[test]
+pub fn author_ids_are_valid() {
+	const CHARS: &[char] = &[
+		'a', 'Z', '0', '9', ' ', '-', '_', '.', ':', '\'', '"', '&', '<', '>', '#', '=', '/', '(',
+		')', '[', ']', '\\', 'é', '٣', '日', '🎉', '\u{a0}', '\u{200b}', '\u{3000}', 'ª', '²',
+	];
+
+	let mut doll = common::doll_with(&[&ext::links::tags(), &ext::glossary::tags()]);
+
+	// xorshift, so every run checks the same texts
+	let mut state = 0x2545_f491_4f6c_dd1d_u64;
+	let mut next = |bound: usize| {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		usize::try_from(state % bound as u64).unwrap()
+	};
+
+	for _ in 0..200 {
+		let text = (0..next(6))
+			.map(|_| CHARS[next(CHARS.len())])
+			.collect::<String>();
+		// empty arguments are as good as missing
+		if text.is_empty() {
+			continue;
+		}
+		// escaped so they stay within the arguments
+		let arg = text
+			.chars()
+			.flat_map(|ch| {
+				if "\\()[]".contains(ch) {
+					vec!['\\', ch]
+				} else {
+					vec![ch]
+				}
+			})
+			.collect::<String>();
+
+		let src = format!(
+			"&(id={arg})\tsection\n\t[def({arg}):a] [ref({arg})]\n\t[term({arg}):b] [termref({arg}):c]"
+		);
+		let mut ast = match doll.parse_document(&*src) {
+			Ok((_, ast)) | Err((_, ast)) => ast,
+		};
+		let mut out = HtmlEmit::untrusted_profile();
+		doll.emit(&mut ast, &mut out);
+		doll.finish();
+
+		let values = |attr: &str| {
+			out.write
+				.split(attr)
+				.skip(1)
+				.map(|rest| rest[..rest.find('\'').unwrap()].to_string())
+				.collect::<Vec<_>>()
+		};
+
+		let ids = values(" id='");
+		assert_eq!(ids.len(), 3, "{text:?} wrote {}", out.write);
+		for id in &ids {
+			assert!(valid_id(id), "{text:?} wrote {}", out.write);
+		}
+
+		// and links to them find them
+		for href in values(" href='#") {
+			assert!(ids.contains(&href), "{text:?} wrote {}", out.write);
+		}
+	}
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext};
+
+fn html(src: &str) -> (String, Vec<&'static str>) {
+	let mut doll = common::doll_with(&[&ext::index::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	ext::index::collect(&doll, &mut ast);
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let warnings = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| {
+			assert!(!diagnostic.err);
+			diagnostic.code
+		})
+		.collect();
+
+	(out.write, warnings)
+}
+
+#[test]
+pub fn lists_entries() {
+	let (out, warnings) = html(
+		"[index]\n\nmerge[ix(sorting)(stable)] sort[ix(Sorting)]\n\n[ix(arrays)]arrays and [ix(sorting)(stable)]again",
+	);
+
+	assert!(warnings.is_empty());
+	assert_eq!(
+		out,
+		"<div><nav class='doll-index'>\
+			<div class='doll-index-group'><div class='doll-index-letter'>A</div><ul>\
+				<li>arrays <a href='#ix-3'>1</a></li>\
+			</ul></div>\
+			<div class='doll-index-group'><div class='doll-index-letter'>S</div><ul>\
+				<li>Sorting <a href='#ix-2'>1</a></li>\
+				<li>sorting<ul><li>stable <a href='#ix-1'>1</a> <a href='#ix-4'>2</a></li></ul></li>\
+			</ul></div>\
+		</nav></div>\
+		<div>merge<span class='doll-ix' id='ix-1'></span> sort<span class='doll-ix' id='ix-2'></span></div>\
+		<div><span class='doll-ix' id='ix-3'></span>arrays and <span class='doll-ix' id='ix-4'></span>again</div>"
+	);
+}
+
+#[test]
+pub fn warns_without_entries() {
+	assert_eq!(
+		html("[index]"),
+		("<div></div>".to_string(), vec!["index has no entries"])
+	);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use {
+	hashbrown::HashMap,
+	markdoll::{
+		ext::{self, TagDefinition},
+		tree::{BlockItem, InlineItem, AST},
+		MarkDoll,
+	},
+};
+
+/// an instance with a `t` tag keeping its arguments, and `vars` to interpolate if given
+fn doll(vars: Option<&[(&str, &str)]>) -> MarkDoll {
+	let mut doll = common::doll_with(&[&ext::code::tags()]);
+	doll.ext_system.add_tag(TagDefinition::new(
+		"t",
+		Some(|_, args, _, _| {
+			Some(Box::new(
+				args.into_iter()
+					.map(ToString::to_string)
+					.collect::<Vec<_>>(),
+			))
+		}),
+	));
+	doll.arg_vars = vars.map(|vars| {
+		vars.iter()
+			.map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+			.collect::<HashMap<_, _>>()
+	});
+	doll
+}
+
+/// the arguments of the first tag in `ast`
+fn args(ast: &AST) -> &Vec<String> {
+	let BlockItem::Inline(inline) = &ast[0] else {
+		unreachable!()
+	};
+	let InlineItem::Tag(tag) = &inline[0].1 else {
+		unreachable!()
+	};
+	tag.content.downcast_ref::<Vec<String>>().unwrap()
+}
+
+/// parse a document, returning the arguments of its first tag and the codes and positions of its diagnostics
+fn parse(doll: &mut MarkDoll, src: &str) -> (Vec<String>, Vec<(&'static str, usize, usize)>) {
+	let ast = match doll.parse_document(src) {
+		Ok((_, ast)) | Err((_, ast)) => ast,
+	};
+	let args = args(&ast).clone();
+
+	(
+		args,
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at, diag.len))
+			.collect(),
+	)
+}
+
+#[test]
+pub fn off_by_default() {
+	let mut doll = doll(None);
+	let (args, diags) = parse(&mut doll, "---\nbase: x\n---\n[t(${base}/a)]");
+	assert_eq!(args, ["${base}/a"]);
+	assert!(diags.is_empty());
+}
+
+#[test]
+pub fn from_frontmatter_and_given() {
+	let mut doll = doll(Some(&[("base", "https://example.com"), ("v", "2")]));
+
+	let (args, diags) = parse(&mut doll, "[t(${base}/a)(v${ v }.${v})]");
+	assert_eq!(args, ["https://example.com/a", "v2.2"]);
+	assert!(diags.is_empty());
+
+	// the frontmatter adds to those given, without replacing them
+	let (args, _) = parse(
+		&mut doll,
+		"---\nbase: https://other.example\nlang: en\n---\n[t(${base}/${lang})]",
+	);
+	assert_eq!(args, ["https://example.com/en"]);
+
+	// and only for the document it's in
+	let (args, diags) = parse(&mut doll, "[t(${lang})]");
+	assert_eq!(args, ["${lang}"]);
+	assert_eq!(diags, [("variable not defined", 3, 7)]);
+}
+
+#[test]
+pub fn unknown_and_escaped() {
+	let mut doll = doll(Some(&[("a", "x")]));
+
+	let (args, diags) = parse(&mut doll, "[t(${a}${nope})(b)]");
+	assert_eq!(args, ["x${nope}", "b"]);
+	assert_eq!(diags, [("variable not defined", 7, 7)]);
+
+	// positions are within the source, around escapes
+	let (args, diags) = parse(&mut doll, "[t(\\)${ nope\\) })]");
+	assert_eq!(args, [")${ nope) }"]);
+	assert_eq!(diags, [("variable not defined", 5, 11)]);
+
+	let (args, diags) = parse(&mut doll, "[t($${a}$$)(${a)($)]");
+	assert_eq!(args, ["${a}$$", "${a", "$"]);
+	assert!(diags.is_empty());
+}
+
+#[test]
+pub fn content_is_kept() {
+	let mut doll = doll(Some(&[("a", "x")]));
+
+	let ast = doll.parse("[t(${a}):${a}] [code:${a}]").unwrap();
+	let BlockItem::Inline(inline) = &ast[0] else {
+		unreachable!()
+	};
+	let InlineItem::Tag(tag) = &inline[2].1 else {
+		unreachable!()
+	};
+	assert_eq!(tag.content.downcast_ref::<String>().unwrap(), "${a}");
+	assert_eq!(args(&ast), &["x"]);
+}
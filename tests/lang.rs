@@ -0,0 +1,67 @@
+mod common;
+
+use markdoll::{
+	emit::HtmlEmit,
+	ext::{
+		self,
+		formatting::lang::{frontmatter_lang, is_valid_code},
+	},
+};
+
+fn html(src: &str) -> (String, Vec<(&'static str, bool)>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diag| (diag.code, diag.err))
+		.collect();
+
+	(out.write, diagnostics)
+}
+
+#[test]
+pub fn inline_and_block() {
+	assert_eq!(
+		html("she said [lang(fr):je ne sais [em:quoi]]"),
+		(
+			"<div>she said <span lang='fr'><span>je ne sais <em><span>quoi</span></em></span></span></div>"
+				.to_string(),
+			vec![]
+		)
+	);
+	assert_eq!(
+		html("[lang(de-AT)::\n\terste\n\n\tzweite\n]"),
+		(
+			"<div><div lang='de-AT'><div>erste</div><div>zweite</div></div></div>".to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn invalid_code() {
+	assert_eq!(
+		html("[lang(en_US'):hi]"),
+		(
+			"<div><span lang='en_US&#x27;'><span>hi</span></span></div>".to_string(),
+			vec![("lang code invalid", false)]
+		)
+	);
+
+	assert!(is_valid_code("zh-Hant-TW"));
+	assert!(!is_valid_code(""));
+	assert!(!is_valid_code("en-"));
+	assert!(!is_valid_code("en US"));
+}
+
+#[test]
+pub fn frontmatter() {
+	assert_eq!(frontmatter_lang("title: post\nlang: de\n"), Some("de"));
+	assert_eq!(frontmatter_lang("language: de\nlang:"), None);
+}
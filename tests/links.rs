@@ -0,0 +1,168 @@
+mod common;
+
+use markdoll::{
+	diagnostics::Profile,
+	emit::HtmlEmit,
+	ext::{
+		self,
+		links::{extract, ExtractedLink, LinkKind},
+	},
+};
+
+fn img(src: &str, profile: Profile) -> (String, Vec<(&'static str, bool)>) {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+	doll.set_profile(profile);
+
+	let mut ast = match doll.parse_document(src) {
+		Ok((_, ast)) | Err((_, ast)) => ast,
+	};
+
+	let mut out = HtmlEmit::default();
+	doll.emit(&mut ast, &mut out);
+
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| (diagnostic.code, diagnostic.err))
+		.collect();
+
+	(out.write, diagnostics)
+}
+
+#[test]
+pub fn extracts_links() {
+	let src = "[link(https://a.example):a] [img(b.png):b]
+
+&section
+	[em::
+		[quote:[ref(c)]]
+		[link(#d):d]
+	]";
+
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::links::tags()]);
+
+	let ast = doll.parse_document(src).unwrap().1;
+	let links = extract(&doll, src, &ast);
+
+	let link = |url: &str, kind| ExtractedLink {
+		url: url.to_string(),
+		kind,
+		at: src
+			.chars()
+			.collect::<String>()
+			.find(&format!("({url})"))
+			.unwrap() + 1,
+	};
+
+	assert_eq!(
+		links,
+		[
+			link("https://a.example", LinkKind::Hyperlink),
+			link("b.png", LinkKind::Image),
+			link("c", LinkKind::Reference),
+			link("#d", LinkKind::Hyperlink),
+		]
+	);
+}
+
+#[test]
+pub fn alt_text() {
+	assert_eq!(
+		img("[img(a.png):a cat]", Profile::Default),
+		(
+			"<div><img src='a.png' alt='a cat' /></div>".to_string(),
+			vec![]
+		)
+	);
+
+	assert_eq!(
+		img("[img(a.png)]", Profile::Default),
+		(
+			"<div><img src='a.png' alt='' /></div>".to_string(),
+			vec![("image has no alt text", false)]
+		)
+	);
+
+	assert_eq!(
+		img("[img(a.png)(decorative)]", Profile::Default),
+		(
+			"<div><img src='a.png' alt='' role='presentation' /></div>".to_string(),
+			vec![]
+		)
+	);
+
+	assert_eq!(
+		img("[img(a.png)(decorative):a cat]", Profile::Default),
+		(
+			"<div><img src='a.png' alt='' role='presentation' /></div>".to_string(),
+			vec![("decorative image has alt text", false)]
+		)
+	);
+
+	assert_eq!(
+		img("[img(a.png)]", Profile::Strict).1,
+		[("image has no alt text", true)]
+	);
+
+	// the flag is no source
+	assert_eq!(
+		img("[img(decorative)]", Profile::Default).1,
+		[("argument src required", true)]
+	);
+}
+
+#[test]
+pub fn duplicate_ids() {
+	let src = "[def(setup):a]\n\n[def(setup):b]\n\n[def(setup-2):c]\n\n[term(setup):d]";
+
+	let mut doll = common::doll_with(&[&ext::glossary::tags(), &ext::links::tags()]);
+
+	let mut emit = || {
+		let mut ast = doll.parse_document(src).unwrap().1;
+
+		let mut out = HtmlEmit::default();
+		doll.emit(&mut ast, &mut out);
+
+		let diagnostics = doll
+			.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.err, diag.at, diag.context))
+			.collect::<Vec<_>>();
+
+		(out.write, diagnostics)
+	};
+
+	let (html, diagnostics) = emit();
+
+	// an id given a suffix is used too, so the third collides with the second
+	let ids = html
+		.split("id='")
+		.skip(1)
+		.map(|rest| &rest[..rest.find('\'').unwrap()])
+		.collect::<Vec<_>>();
+	assert_eq!(
+		ids,
+		["ref-setup", "ref-setup-2", "ref-setup-2-2", "term-setup"]
+	);
+
+	assert_eq!(
+		diagnostics,
+		[
+			(
+				"id already used in this document",
+				false,
+				16,
+				Some((0, "first used here"))
+			),
+			(
+				"id already used in this document",
+				false,
+				32,
+				Some((16, "first used here"))
+			),
+		]
+	);
+
+	// the same every time
+	assert_eq!(emit(), (html, diagnostics));
+}
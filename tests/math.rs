@@ -0,0 +1,87 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{HtmlEmit, MathRenderer},
+		ext,
+		tree::print::{self, PrintOptions},
+	},
+	std::sync::Arc,
+};
+
+fn html(src: &str, math_render: Option<Arc<MathRenderer>>) -> String {
+	let (out, warnings) = common::html_to(
+		&mut common::doll_with(&[&ext::math::tags()]),
+		src,
+		HtmlEmit {
+			math_render,
+			..Default::default()
+		},
+	);
+	assert!(warnings.is_empty());
+
+	out
+}
+
+#[test]
+pub fn keeps_escapes() {
+	assert_eq!(
+		html(r"[math:\frac{1}{2} \[x\]]", None),
+		r"<div><span class='doll-math'>\(\frac{1}{2} \[x\]\)</span></div>"
+	);
+	assert_eq!(
+		html(r"[math:[a, b] \]]", None),
+		r"<div><span class='doll-math'>\([a, b] \]\)</span></div>"
+	);
+	assert_eq!(
+		html("[math:a < b]", None),
+		r"<div><span class='doll-math'>\(a &lt; b\)</span></div>"
+	);
+}
+
+#[test]
+pub fn display() {
+	assert_eq!(
+		html("[math(display)::\n\t\\sum_{k=1}^{n} k\n]", None),
+		r"<div><div class='doll-math-display'>\[\sum_{k=1}^{n} k\]</div></div>"
+	);
+}
+
+#[test]
+pub fn renderer() {
+	assert_eq!(
+		html(
+			r"[math:\pi] [math(display):\tau]",
+			Some(Arc::new(|_, to, tex, display| {
+				to.write
+					.push_str(&format!("<rendered display='{display}'>{tex}</rendered>"));
+			}))
+		),
+		r"<div><rendered display='false'>\pi</rendered> <rendered display='true'>\tau</rendered></div>"
+	);
+}
+
+#[test]
+pub fn rejects_escaped_newline() {
+	let mut doll = common::doll_with(&[&ext::math::tags()]);
+	assert!(doll.parse_document("[math:a \\\nb]").is_err());
+}
+
+#[test]
+pub fn prints_raw() {
+	for (src, expected) in [
+		(r"[math:\frac{1}{2} \[x\]]", r"[math:\frac{1}{2} \[x\]]"),
+		(r"[math:\:a]", r"[math:\:a]"),
+		// unbalanced and leading `:` content can only be written in block form
+		("[math::\n\t[a, b)\n]", "[math::\n\t[a, b)\n]"),
+		("[math::\n\t:a\n]", "[math::\n\t:a\n]"),
+	] {
+		let mut doll = common::doll_with(&[&ext::math::tags()]);
+		let ast = doll.parse_document(src).unwrap().1;
+
+		assert_eq!(
+			print::to_doll(&doll, &ast, PrintOptions::default()).trim_end(),
+			expected
+		);
+	}
+}
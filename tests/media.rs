@@ -0,0 +1,54 @@
+mod common;
+
+use markdoll::ext;
+
+fn html(src: &str) -> (String, Vec<&'static str>) {
+	common::html_warnings(
+		&mut common::doll_with(&[&ext::formatting::tags(), &ext::media::tags()]),
+		src,
+	)
+}
+
+#[test]
+pub fn video() {
+	assert_eq!(
+		html("[video(clip.webm)(src=clip.mp4)(src=clip.mkv)(poster=a'b.png):no [em:video]]"),
+		(
+			"<div><video class='doll-video' controls poster='a&#x27;b.png'><source src='clip.webm' type='video/webm' /><source src='clip.mp4' type='video/mp4' /><source src='clip.mkv' /><span>no <em><span>video</span></em></span></video></div>".to_string(),
+			vec![]
+		)
+	);
+	assert_eq!(
+		html("[video(loop.MP4?v=2)(nocontrols)(loop)(muted)(autoplay)]"),
+		(
+			"<div><video class='doll-video' loop muted autoplay><source src='loop.MP4?v=2' type='video/mp4' /></video></div>".to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn audio() {
+	assert_eq!(
+		html("[audio(song.opus)(src=<song>.mp3):no audio]"),
+		(
+			"<div><audio class='doll-audio' controls><source src='song.opus' type='audio/ogg' /><source src='&lt;song&gt;.mp3' type='audio/mpeg' /><span>no audio</span></audio></div>".to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn warnings() {
+	assert_eq!(
+		html("[video(a.mp4)(autoplay)]").1,
+		["autoplay is blocked unless muted"]
+	);
+	assert_eq!(
+		html("[audio(a.mp3)(poster=a.png)]"),
+		(
+			"<div><audio class='doll-audio' controls><source src='a.mp3' type='audio/mpeg' /></audio></div>".to_string(),
+			vec!["poster is only used by video"]
+		)
+	);
+}
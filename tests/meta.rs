@@ -0,0 +1,192 @@
+mod common;
+
+use markdoll::{
+	diagnostics::Data,
+	ext,
+	tree::{analyze, check_thresholds, DocumentMeta, Heading, Thresholds, FALLBACK_TITLE_LEN},
+};
+
+#[test]
+pub fn analyzes_document() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let (frontmatter, ast) = doll
+		.parse_document(
+			"---
+author
+---
+&first
+	some [em:emphasized] text
+
+	&nested
+		more text
+
+	[quote::
+		&quoted
+			hidden from the outline
+	]
+&second
+	end",
+		)
+		.unwrap();
+
+	assert_eq!(
+		analyze(&doll, frontmatter.as_deref(), &ast),
+		DocumentMeta {
+			title: Some("first".into()),
+			frontmatter: Some("author".into()),
+			outline: vec![
+				Heading {
+					level: 1,
+					name: "first".into(),
+				},
+				Heading {
+					level: 2,
+					name: "nested".into(),
+				},
+				Heading {
+					level: 1,
+					name: "second".into(),
+				},
+			],
+			words: 14,
+			images: 0,
+			links: 0,
+		}
+	);
+}
+
+#[test]
+pub fn falls_back_to_first_text() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let ast = doll
+		.parse_document(format!("\n[em:{}]\n\nsecond paragraph", "word ".repeat(20)))
+		.unwrap()
+		.1;
+	let title = doll.document_title(&ast).unwrap();
+	assert!(title.len() <= 80, "{title:?}");
+	assert_eq!(title, "word ".repeat(16).trim_end());
+
+	let ast = doll.parse_document("").unwrap().1;
+	assert_eq!(doll.document_title(&ast), None);
+}
+
+#[test]
+pub fn multibyte_at_cut() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	// a character starting right at the cut
+	let line = format!("{} {}", "a".repeat(40), "b".repeat(FALLBACK_TITLE_LEN - 41));
+	let ast = doll.parse_document(format!("{line}é more")).unwrap().1;
+	assert_eq!(doll.document_title(&ast).unwrap(), "a".repeat(40));
+
+	// and one straddling it
+	let line = format!("{} {}", "a".repeat(40), "b".repeat(FALLBACK_TITLE_LEN - 42));
+	let ast = doll.parse_document(format!("{line}é more")).unwrap().1;
+	assert_eq!(doll.document_title(&ast).unwrap(), "a".repeat(40));
+
+	// and one word running past it
+	let ast = doll
+		.parse_document("é".repeat(FALLBACK_TITLE_LEN))
+		.unwrap()
+		.1;
+	assert_eq!(
+		doll.document_title(&ast).unwrap(),
+		"é".repeat(FALLBACK_TITLE_LEN / 2)
+	);
+}
+
+#[test]
+pub fn skipped_levels() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let src = "&a\n\tb\n\n\t\t&c\n\t\t\td\n\n\t&e\n\t\tf";
+	assert!(doll.parse_document(src).is_err());
+
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics.iter().map(|diag| diag.code).collect::<Vec<_>>(),
+		["unexpected indentation", "section skips a level"]
+	);
+
+	let skip = &diagnostics[1];
+	assert!(!skip.err);
+	assert_eq!((skip.at, skip.len), (src.find("&c").unwrap(), 2));
+	assert_eq!(
+		skip.data,
+		Some(Data::HeadingLevel {
+			level: 3,
+			expected: 2
+		})
+	);
+	// labeled where the extra section starts
+	assert_eq!(
+		skip.context.map(|(_, message)| message),
+		Some("indented too far here, nesting it in an extra section")
+	);
+
+	// sections within tags are outside the outline
+	assert!(doll
+		.parse_document("&a\n\t[quote::\n\t\t&b\n\t\t\tc\n\t]")
+		.is_ok());
+	assert!(doll.finish().is_empty());
+}
+
+#[test]
+pub fn thresholds() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+	doll.ext_system.add_tags(ext::links::tags());
+
+	let src = "a [link(b):[img(c.png):c] link] and [em:[link(d):d]]\n\n[img(e.png):e]";
+	let (frontmatter, ast) = doll.parse_document(src).unwrap();
+	let meta = analyze(&doll, frontmatter.as_deref(), &ast);
+	assert_eq!((meta.images, meta.links, meta.words), (2, 2, 4));
+	assert_eq!(meta.reading_minutes(), 1);
+
+	// met
+	assert!(check_thresholds(
+		&mut doll,
+		&meta,
+		Thresholds {
+			min_words: 4,
+			require_headings: false,
+		}
+	));
+	assert!(doll.finish().is_empty());
+
+	// and not
+	doll.parse_document(src).unwrap();
+	assert!(!check_thresholds(
+		&mut doll,
+		&meta,
+		Thresholds {
+			min_words: 5,
+			require_headings: true,
+		}
+	));
+	assert_eq!(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.err, diag.at))
+			.collect::<Vec<_>>(),
+		[
+			("document has too few words", true, 0),
+			("document has no headings", true, 0)
+		]
+	);
+
+	// unless they are made warnings
+	doll.severity_overrides
+		.insert("document has no headings".to_string(), Some(false));
+	doll.parse_document(src).unwrap();
+	assert!(check_thresholds(
+		&mut doll,
+		&meta,
+		Thresholds {
+			min_words: 0,
+			require_headings: true,
+		}
+	));
+	doll.finish();
+}
@@ -0,0 +1,84 @@
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext,
+	tree::{BlockItem, InlineItem, AST},
+	MarkDoll,
+};
+
+const SRC: &str = "a[//:x]b
+\\
+c
+
+[//:y]
+
+-	\\\\
+	d[em:[//:w]e[//:v]f]";
+
+/// a compact snapshot of an [`AST`]'s shape
+fn snapshot(ast: &AST) -> String {
+	ast.iter()
+		.map(|block| match block {
+			BlockItem::Inline(items) => format!(
+				"inline({})",
+				items
+					.iter()
+					.map(|(_, item)| match item {
+						InlineItem::Split => "split".to_string(),
+						InlineItem::Break => "break".to_string(),
+						InlineItem::Text(text) => format!("{text:?}"),
+						InlineItem::Tag(tag) => tag.tag.clone(),
+					})
+					.collect::<Vec<_>>()
+					.join(" ")
+			),
+			BlockItem::Section { name, children, .. } => {
+				format!("section({name}: {})", snapshot(children))
+			}
+			BlockItem::List { items, .. } => format!(
+				"list({})",
+				items
+					.iter()
+					.map(|item| snapshot(&item.ast))
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+		})
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+fn parse(normalize: bool) -> (String, String) {
+	let mut doll = MarkDoll::new();
+	doll.normalize = normalize;
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document(SRC).unwrap().1;
+	let snapshot = snapshot(&ast);
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	(snapshot, out.write)
+}
+
+#[test]
+pub fn normalizes() {
+	let (before, before_html) = parse(false);
+	let (after, after_html) = parse(true);
+
+	assert_eq!(
+		before,
+		r#"inline("a" "b" split "" break "c") list(inline("\\" split "d" em))"#
+	);
+	assert_eq!(
+		after,
+		r#"inline("ab" split break "c") list(inline("\\" split "d" em))"#
+	);
+	assert_eq!(before_html, after_html);
+	assert_eq!(
+		after_html,
+		r"<div>ab <br />c</div><ul><li><div>\ d<em><span>ef</span></em></div></li></ul>"
+	);
+}
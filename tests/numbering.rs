@@ -0,0 +1,84 @@
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	tree::{analyze, number_sections, plain_text, NumberingOptions, PlainTextOptions},
+	MarkDoll,
+};
+
+const SRC: &str = "&intro
+	text
+&design
+	&goals
+		&speed
+			fast
+	-	&listed
+			in a list
+	&security
+		text
+&outro
+	end";
+
+fn headings(options: &NumberingOptions) -> Vec<String> {
+	let mut doll = MarkDoll::new();
+	let mut ast = doll.parse_document(SRC).unwrap().1;
+	number_sections(&mut ast, options);
+
+	analyze(&doll, None, &ast)
+		.outline
+		.into_iter()
+		.map(|heading| heading.name)
+		.collect()
+}
+
+#[test]
+pub fn numbers_outline() {
+	assert_eq!(
+		headings(&NumberingOptions::default()),
+		[
+			"1 intro",
+			"2 design",
+			"2.1 goals",
+			"2.1.1 speed",
+			"2.2 listed",
+			"2.3 security",
+			"3 outro"
+		]
+	);
+}
+
+#[test]
+pub fn separator_and_depth() {
+	assert_eq!(
+		headings(&NumberingOptions {
+			separator: "-".into(),
+			max_depth: 2,
+		}),
+		[
+			"1 intro",
+			"2 design",
+			"2-1 goals",
+			"speed",
+			"2-2 listed",
+			"2-3 security",
+			"3 outro"
+		]
+	);
+}
+
+#[test]
+pub fn targets_match() {
+	let mut doll = MarkDoll::new();
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let mut ast = doll.parse_document("&a\n\t&b\n\t\ttext").unwrap().1;
+	number_sections(&mut ast, &NumberingOptions::default());
+
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"1 a\n1.1 b\ntext"
+	);
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(out.write.contains(">1 a</h1>"));
+	assert!(out.write.contains(">1.1 b</h2>"));
+}
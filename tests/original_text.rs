@@ -0,0 +1,87 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self, TagDefinition},
+		MarkDoll,
+	},
+	std::sync::Mutex,
+};
+
+/// the source of each `snippet` tag parsed, and of its content
+static SNIPPETS: Mutex<Vec<(Option<String>, Option<String>)>> = Mutex::new(Vec::new());
+
+fn snippet() -> TagDefinition {
+	TagDefinition::new(
+		"snippet",
+		Some(|doll, _, text, _| {
+			let tag = doll.original_tag_text().map(ToString::to_string);
+			let content = doll
+				.original_text(0..text.chars().count())
+				.map(ToString::to_string);
+			SNIPPETS.lock().unwrap().push((tag, content));
+
+			Some(Box::new(()))
+		}),
+	)
+}
+
+fn snippets(src: &str) -> Vec<(Option<String>, Option<String>)> {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tag(snippet());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	SNIPPETS.lock().unwrap().clear();
+	assert!(doll.parse_document(src).is_ok(), "{:#?}", doll.finish());
+	doll.finish();
+
+	core::mem::take(&mut *SNIPPETS.lock().unwrap())
+}
+
+#[test]
+pub fn block_tag_in_list() {
+	const SRC: &str = "-\tfirst\n-\tsecond\n\t[snippet(a)(b=c)::\n\t\tline [em:one]\n\t\t\\[escaped\\]\n\t\t\tindented \u{e9}\n\t]\n-\tthird\n";
+
+	let start = SRC.find("[snippet").unwrap();
+	let end = SRC.rfind("\t]").unwrap() + 2;
+	let content_start = SRC.find("\t\tline").unwrap() + 2;
+	let content_end = SRC.find("\u{e9}").unwrap() + 2;
+
+	assert_eq!(
+		snippets(SRC),
+		[(
+			Some(SRC[start..end].to_string()),
+			Some(SRC[content_start..content_end].to_string())
+		)]
+	);
+}
+
+#[test]
+pub fn nested_and_inline() {
+	const SRC: &str = "a [em:b [snippet:c \u{e9}] d] [snippet] e\n";
+
+	assert_eq!(
+		snippets(SRC),
+		[
+			(
+				Some("[snippet:c \u{e9}]".to_string()),
+				Some("c \u{e9}".to_string())
+			),
+			(Some("[snippet]".to_string()), Some(String::new())),
+		]
+	);
+}
+
+#[test]
+pub fn not_written_in_source() {
+	// arguments parsed as markdoll
+	assert_eq!(
+		snippets("[details([snippet:a])::\n\tb\n]\n"),
+		[(None, None)]
+	);
+
+	// outside of a document
+	assert_eq!(MarkDoll::new().original_tag_text(), None);
+	assert_eq!(MarkDoll::new().original_text(0..0), None);
+}
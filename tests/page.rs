@@ -0,0 +1,75 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{HtmlEmit, PageResolver},
+		ext::{
+			self,
+			page::{slug, ResolvedPage},
+		},
+	},
+	std::sync::Arc,
+};
+
+fn html(
+	src: &str,
+	page_resolver: Option<Arc<PageResolver>>,
+) -> (String, Vec<(&'static str, usize)>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::page::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit {
+		page_resolver,
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+
+	(
+		out.write,
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.similar.len()))
+			.collect(),
+	)
+}
+
+#[test]
+pub fn slugs() {
+	assert_eq!(slug("Getting Started!"), "getting-started");
+	assert_eq!(slug("  C++ / Rust FAQ "), "c-rust-faq");
+}
+
+#[test]
+pub fn default_resolver() {
+	assert_eq!(
+		html("see [page(Getting Started)] or [page(FAQ):[em:the faq]]", None),
+		(
+			"<div>see <a href='./getting-started.html'>Getting Started</a> or <a href='./faq.html'><span><em><span>the faq</span></em></span></a></div>".to_string(),
+			vec![]
+		)
+	);
+}
+
+#[test]
+pub fn resolver() {
+	let wiki: Arc<PageResolver> = Arc::new(|name| match name {
+		"Home" => Some(ResolvedPage {
+			url: "/wiki/Home".to_string(),
+			exists: true,
+		}),
+		"Draft" => Some(ResolvedPage {
+			url: "/wiki/Draft?action=edit".to_string(),
+			exists: false,
+		}),
+		_ => None,
+	});
+
+	assert_eq!(
+		html("[page(Home)] [page(Draft)] [page(Special:Nothing)]", Some(wiki)),
+		(
+			"<div><a href='/wiki/Home'>Home</a> <a class='doll-page-missing' href='/wiki/Draft?action=edit'>Draft</a> <span class='doll-page-missing'>Special:Nothing</span></div>".to_string(),
+			vec![("page not found", 0), ("page not found", 0)]
+		)
+	);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use markdoll::{
+	emit::{DeferredEmit, HtmlEmit},
+	ext::TagDefinition,
+	tree::AST,
+	MarkDoll,
+};
+
+/// how many `mark` tags have been emitted
+#[derive(Debug, Clone, Default)]
+struct Marks(usize);
+
+/// every standard tag, and `mark` and `total` tags keeping state for the rest of the document
+fn doll() -> MarkDoll {
+	let mut doll = common::doll();
+	doll.ext_system.add_tags([
+		TagDefinition::new("mark", Some(|_, _, _, _| Some(Box::new(())))).with_emitter::<HtmlEmit>(
+			|doll, to, _| {
+				doll.scratch.get_or_insert_with(Marks::default).0 += 1;
+				to.write.push('*');
+			},
+		),
+		TagDefinition::new("total", Some(|_, _, _, _| Some(Box::new(()))))
+			.with_emitter::<HtmlEmit>(|doll, to, _| {
+				to.defer(doll, |doll, to| {
+					let marks = doll.scratch.get_ref::<Marks>().map_or(0, |marks| marks.0);
+					to.write.push_str(&format!("<b>{marks}</b>"));
+				});
+			}),
+	]);
+	doll
+}
+
+/// the code, position, and context of a diagnostic
+type Reported = (&'static str, usize, Option<(usize, &'static str)>);
+
+/// emit `src` to `to` with `emit`, returning whether it succeeded, the output, and the diagnostics
+fn emit_with(
+	doll: &mut MarkDoll,
+	src: &str,
+	mut to: HtmlEmit,
+	emit: fn(&mut MarkDoll, &mut AST, &mut HtmlEmit) -> bool,
+) -> (bool, String, Vec<Reported>) {
+	let (Ok((_, mut ast)) | Err((_, mut ast))) = doll.parse_document(src);
+	let ok = emit(doll, &mut ast, &mut to);
+
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diag| (diag.code, diag.at, diag.context))
+		.collect();
+
+	(ok, to.write, diagnostics)
+}
+
+/// assert that emitting `src` to a `target` in parallel writes and reports the same as emitting it serially
+fn assert_matches_serial(src: &str, target: fn() -> HtmlEmit) {
+	let mut doll = doll();
+
+	let serial = emit_with(&mut doll, src, target(), MarkDoll::emit);
+	let parallel = emit_with(&mut doll, src, target(), MarkDoll::emit_parallel);
+
+	assert_eq!(serial, parallel, "{src:?}");
+}
+
+#[test]
+pub fn matches_serial_emit() {
+	assert_matches_serial(include_str!("../spec.doll"), common::lang_target);
+
+	for src in [
+		// deferred output sees the whole document
+		"[total]\n\n[mark] [mark]\n\n[mark]",
+		"[mark]\n\n[total]\n\n[mark]",
+		// as do tags after one leaving state for them
+		"[abbr:HTML]\n\n[abbrdef(HTML)(HyperText Markup Language)]\n\n[abbr:HTML]",
+	] {
+		assert_matches_serial(src, HtmlEmit::default);
+	}
+
+	// with the elements of each block checked to balance
+	assert_matches_serial("a\n\n[mark]\n\nb", || HtmlEmit {
+		check_balance: true,
+		..Default::default()
+	});
+}
+
+#[test]
+pub fn duplicate_ids() {
+	let src = "&(id=a)A\n\tx\n&(id=a)A\n\ty\n\n[def(a):b]\n\n[def(a):c]";
+	assert_matches_serial(src, HtmlEmit::default);
+
+	let (ok, out, diagnostics) = emit_with(
+		&mut doll(),
+		src,
+		HtmlEmit::default(),
+		MarkDoll::emit_parallel,
+	);
+	assert!(ok);
+	assert!(out.contains(" id='a'") && out.contains(" id='a-2'"));
+	assert!(out.contains(" id='ref-a'") && out.contains(" id='ref-a-2'"));
+	assert_eq!(
+		diagnostics,
+		[
+			(
+				"id already used in this document",
+				src.rfind("&(id=a)").unwrap(),
+				Some((0, "first used here"))
+			),
+			(
+				"id already used in this document",
+				src.rfind("[def").unwrap(),
+				Some((src.find("[def").unwrap(), "first used here"))
+			),
+		]
+	);
+}
+
+#[test]
+pub fn tab_groups() {
+	let src = "[tabs::\n\t[tab(a):x]\n\t[tab(b):y]\n]\n\n[tabs:[tab(c):z]]";
+	assert_matches_serial(src, HtmlEmit::default);
+
+	let (_, out, _) = emit_with(
+		&mut doll(),
+		src,
+		HtmlEmit::default(),
+		MarkDoll::emit_parallel,
+	);
+	assert!(out.contains("name='doll-tabs-1' id='doll-tabs-1-2'"));
+	assert!(out.contains("name='doll-tabs-2' id='doll-tabs-2-1'"));
+}
+
+#[test]
+pub fn output_limit() {
+	/// an untrusted target, limited to 16 KiB
+	fn target() -> HtmlEmit {
+		HtmlEmit {
+			max_output_bytes: 16 << 10,
+			..HtmlEmit::untrusted_profile()
+		}
+	}
+
+	// each block fits, but together they pass it many times over
+	let src = format!("[em:{}]\n\n", "a".repeat(1000)).repeat(500);
+	assert_matches_serial(&src, target);
+
+	let mut doll = MarkDoll::untrusted();
+	let (ok, out, diagnostics) = emit_with(&mut doll, &src, target(), MarkDoll::emit_parallel);
+	assert!(!ok);
+	assert_eq!(
+		diagnostics
+			.iter()
+			.map(|(code, ..)| *code)
+			.collect::<Vec<_>>(),
+		["output too large"]
+	);
+	// past the limit, each block after it only writes its empty element
+	assert!(out.len() < 2 * (16 << 10), "{}", out.len());
+	assert!(out.ends_with("</div>"));
+}
+
+#[test]
+pub fn collects_diagnostics_in_order() {
+	let src = "[em:a]\n\n[code(x):b]\n\n[em:c]\n\n[code(y):d]";
+
+	let mut doll = common::doll();
+	doll.ext_system.remove_tag("code");
+	doll.ext_system.add_tag(TagDefinition::new(
+		"code",
+		Some(|_, _, text, _| Some(Box::new(text.to_string()))),
+	));
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	assert!(!doll.emit_parallel(&mut ast, &mut common::lang_target()));
+
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics.iter().map(|diag| diag.at).collect::<Vec<_>>(),
+		[src.find("[code(x)").unwrap(), src.find("[code(y)").unwrap()]
+	);
+}
@@ -0,0 +1,542 @@
+mod common;
+
+use markdoll::{
+	diagnostics::{Data, Profile},
+	emit::HtmlEmit,
+	ext,
+	tree::{analyze, plain_text, print, BlockItem, PlainTextOptions},
+	MarkDoll,
+};
+
+/// parse a document, returning whether it parsed successfully and the code and position of each diagnostic
+fn check(src: &str) -> (bool, Vec<(&'static str, usize)>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let ok = doll.parse_document(src).is_ok();
+	let diagnostics = doll
+		.finish()
+		.into_iter()
+		.map(|diag| (diag.code, diag.at))
+		.collect();
+
+	(ok, diagnostics)
+}
+
+#[test]
+pub fn indentation() {
+	assert_eq!(check("&a\n\t&b\n\t\tc\nd"), (true, vec![]));
+	assert_eq!(
+		check("\t\ta"),
+		(
+			false,
+			vec![("unexpected indentation", 0), ("unexpected indentation", 1)]
+		)
+	);
+	assert_eq!(check("  a"), (true, vec![("erroneous leading spaces", 0)]));
+
+	// kept as text, but it looks like indentation
+	assert_eq!(
+		check("\u{a0}a"),
+		(true, vec![("unusual whitespace in indentation", 0)])
+	);
+	assert_eq!(
+		check("&a\n\t\u{200b}b\n\t\u{3000}c"),
+		(
+			true,
+			vec![
+				("unusual whitespace in indentation", 4),
+				("unusual whitespace in indentation", 8)
+			]
+		)
+	);
+	assert_eq!(check("a\u{a0}b"), (true, vec![]));
+}
+
+#[test]
+pub fn block_tags() {
+	assert_eq!(check("[em::\n\ttext\n]"), (true, vec![]));
+
+	// closing brackets must be at the tag's indentation
+	assert_eq!(
+		check("&s\n\t&t\n\t\t[em::\n\t\t\tx\n\t]"),
+		(false, vec![("misaligned closing tag for this tag", 9)])
+	);
+	assert_eq!(
+		check("[em::\n\ttext\n\t]\n"),
+		(false, vec![("unterminated tag block", 0)])
+	);
+	assert_eq!(
+		check("[em::\n\t\ttext\n]\n"),
+		(false, vec![("unexpected indentation", 7)])
+	);
+
+	assert_eq!(
+		check("[em::x\n\ty\n]"),
+		(false, vec![("expected newline", 5)])
+	);
+	// content after an error on the tag's line starts on the next line
+	assert_eq!(
+		check("[em::x\n\t\ty\n]"),
+		(
+			false,
+			vec![("expected newline", 5), ("unexpected indentation", 8)]
+		)
+	);
+	assert_eq!(
+		check("\t[em::::\t\tx\n\t\t\ty"),
+		(
+			false,
+			vec![
+				("unexpected indentation", 0),
+				("expected newline", 6),
+				("unterminated tag block", 1),
+				("unexpected indentation", 14)
+			]
+		)
+	);
+}
+
+#[test]
+pub fn inline_tags() {
+	assert_eq!(check("[em(a):b]"), (true, vec![]));
+	assert_eq!(check("[em:\n"), (false, vec![("unexpected newline", 4)]));
+	assert_eq!(
+		check("[em:a\\\nb]"),
+		(false, vec![("cannot escape newline in this context", 6)])
+	);
+	assert_eq!(
+		check("[em(a\\\tb):x]"),
+		(
+			false,
+			vec![("cannot escape indentation in this context", 6)]
+		)
+	);
+}
+
+#[test]
+pub fn end_of_input() {
+	assert_eq!(check("[em:text"), (false, vec![("unexpected EOI", 7)]));
+	assert_eq!(check("[em(a"), (false, vec![("unexpected EOI", 4)]));
+	assert_eq!(check("\\"), (false, vec![("unexpected EOI", 0)]));
+	assert_eq!(
+		check("[em::\n\ttext\n"),
+		(false, vec![("unterminated tag block", 0)])
+	);
+}
+
+#[test]
+pub fn unterminated_tag_blocks() {
+	// reported at the tag, with where its content ended as context
+	let unterminated = |src: &str| {
+		let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+		let (ok, ast) = match doll.parse_document(src) {
+			Ok((_, ast)) => (true, ast),
+			Err((_, ast)) => (false, ast),
+		};
+		assert!(!ok);
+
+		let diagnostics = doll
+			.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at, diag.context))
+			.collect::<Vec<_>>();
+
+		(ast.len(), diagnostics)
+	};
+
+	assert_eq!(
+		unterminated("[quote::\n\ttext"),
+		(
+			1,
+			vec![(
+				"unterminated tag block",
+				0,
+				Some((13, "expected `]` before here"))
+			)]
+		)
+	);
+
+	// the content ends at the first line below its indentation, so the rest of the document is kept
+	assert_eq!(
+		unterminated(
+			"&intro\n\t[quote::\n\t\tto be, or not to be\n\n\t\tthat is the question\n\tas he said\n&next\n\tmore\n"
+		),
+		(
+			2,
+			vec![(
+				"unterminated tag block",
+				8,
+				Some((64, "expected `]` before here"))
+			)]
+		)
+	);
+}
+
+#[test]
+pub fn carriage_returns() {
+	assert_eq!(
+		check("a\r\nb\n"),
+		(
+			true,
+			vec![("markdoll does not support CRLF, fatal parsing error", 2)]
+		)
+	);
+
+	let (ok, diagnostics) = check("[em::\r\n\tx\r\n]");
+	assert!(!ok);
+	assert_eq!(
+		diagnostics[0],
+		("markdoll does not support CRLF, fatal parsing error", 6)
+	);
+}
+
+#[test]
+pub fn control_characters() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	// reads as a comment, but the comment ends before `[em:true]`
+	let src = "admin = false \u{202e}\u{2066}// check [em:true]\u{2069}\u{2066}\n\nbell\u{7}";
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert_eq!(
+		out.write,
+		"<div>admin = false // check <em><span>true</span></em></div><div>bell</div>"
+	);
+
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[
+			("control character", 14),
+			("control character", 15),
+			("control character", 34),
+			("control character", 35),
+			("control character", 42),
+		]
+	);
+}
+
+#[test]
+pub fn frontmatter() {
+	let mut doll = MarkDoll::new();
+	assert_eq!(
+		doll.parse_document("---\nfront\nmatter\n---\nbody")
+			.unwrap()
+			.0
+			.as_deref(),
+		Some("front\nmatter")
+	);
+	assert!(doll.finish().is_empty());
+
+	assert_eq!(
+		doll.parse_document("---\n---\n---\nbody")
+			.unwrap()
+			.0
+			.as_deref(),
+		Some("")
+	);
+	assert_eq!(
+		doll.parse_document("---\n----\n\n---")
+			.unwrap()
+			.0
+			.as_deref(),
+		Some("----\n")
+	);
+	assert!(doll.finish().is_empty());
+
+	// kept on the last character rather than past it
+	assert_eq!(
+		check("---\ntitle: x\n--\nbody"),
+		(false, vec![("unexpected EOI in frontmatter", 19)])
+	);
+	assert_eq!(
+		check("---\nfront\n"),
+		(false, vec![("unexpected EOI in frontmatter", 9)])
+	);
+	assert_eq!(
+		check("---\n"),
+		(false, vec![("unexpected EOI in frontmatter", 3)])
+	);
+	assert_eq!(
+		check("---"),
+		(false, vec![("unexpected EOI in frontmatter", 2)])
+	);
+}
+
+#[test]
+pub fn frontmatter_fences() {
+	for (src, frontmatter, diagnostics) in [
+		("----\na: b\n---\n----\ntext", Some("a: b\n---"), vec![]),
+		("-----\n-----", Some(""), vec![]),
+		(
+			"--- \na: b\n---\t\ntext",
+			Some("a: b"),
+			vec![
+				("trailing whitespace after frontmatter fence", 3),
+				("trailing whitespace after frontmatter fence", 13),
+			],
+		),
+		// a closing fence followed by text is part of the frontmatter
+		("---\na: b\n--- c\n---\ntext", Some("a: b\n--- c"), vec![]),
+	] {
+		let mut doll = MarkDoll::new();
+		let parsed = doll.parse_document(src).unwrap().0;
+		assert_eq!(parsed.as_deref(), frontmatter, "{src:?}");
+		assert_eq!(
+			doll.finish()
+				.iter()
+				.map(|diag| (diag.code, diag.at))
+				.collect::<Vec<_>>(),
+			diagnostics,
+			"{src:?}"
+		);
+	}
+}
+
+#[test]
+pub fn almost_frontmatter() {
+	for (src, code) in [
+		(
+			"--\na: b\n--\n\ntext",
+			"frontmatter fence has too few dashes",
+		),
+		("--  \ntext", "frontmatter fence has too few dashes"),
+		("--- title\ntext", "text after frontmatter fence"),
+		("---x", "text after frontmatter fence"),
+		("-----title", "text after frontmatter fence"),
+	] {
+		let mut doll = MarkDoll::new();
+		assert_eq!(doll.parse_document(src).unwrap().0, None, "{src:?}");
+		assert_eq!(
+			doll.finish()
+				.iter()
+				.map(|diag| (diag.code, diag.at))
+				.collect::<Vec<_>>(),
+			vec![(code, 0)],
+			"{src:?}"
+		);
+	}
+
+	// not a fence at all
+	for src in ["-- text", "- item", "text\n---"] {
+		let mut doll = MarkDoll::new();
+		let _ = doll.parse_document(src);
+		assert!(doll.finish().is_empty(), "{src:?}");
+	}
+}
+
+#[test]
+pub fn empty_documents() {
+	for (src, frontmatter) in [
+		("", None),
+		("\n", None),
+		("\n\n\n", None),
+		("\t", None),
+		("\t\t\n\t\n\t\t\t", None),
+		("  \n \t", None),
+		("---\n---\n", Some("")),
+		("---\n---", Some("")),
+		("---\na: b\n---\n\t\n", Some("a: b")),
+	] {
+		let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+		let (parsed, mut ast) = doll.parse_document(src).unwrap();
+		assert_eq!(parsed.as_deref(), frontmatter, "{src:?}");
+		assert!(ast.is_empty(), "{src:?}");
+
+		let mut out = HtmlEmit::default();
+		assert!(doll.emit(&mut ast, &mut out));
+		assert_eq!(out.write, "");
+		assert_eq!(print::to_doll(&doll, &ast, Default::default()), "");
+		assert_eq!(plain_text(&doll, &ast, PlainTextOptions::default()), "");
+
+		assert!(doll.finish().is_empty(), "{src:?}");
+	}
+}
+
+#[test]
+pub fn list_item_positions() {
+	let src = "-\ta\n-\tb\n\t=\tc\n\t=\td\n\n-\te";
+
+	let mut doll = MarkDoll::new();
+	let ast = doll.parse_document(src).unwrap().1;
+
+	let markers = |block: &BlockItem| match block {
+		BlockItem::List { items, .. } => items.iter().map(|item| item.pos).collect::<Vec<_>>(),
+		_ => panic!("expected a list"),
+	};
+
+	assert_eq!(ast.len(), 2);
+	assert_eq!(markers(&ast[0]), [0, 4]);
+	assert_eq!(markers(&ast[1]), [src.rfind('-').unwrap()]);
+
+	let BlockItem::List { items, .. } = &ast[0] else {
+		unreachable!()
+	};
+	assert_eq!(markers(&items[1].ast[1]), [9, 14]);
+}
+
+#[test]
+pub fn list_item_values() {
+	let src = "=7.\ta\n=\tb\n=10.\tc\n\n-\t1.\n-\td\n\n=99999999999999999999.\te\n=1.x";
+
+	let mut doll = MarkDoll::new();
+	let (ok, ast) = match doll.parse_document(src) {
+		Ok((_, ast)) => (true, ast),
+		Err((_, ast)) => (false, ast),
+	};
+	assert!(!ok);
+
+	let values = |block: &BlockItem| match block {
+		BlockItem::List { items, .. } => items.iter().map(|item| item.value).collect::<Vec<_>>(),
+		_ => panic!("expected a list"),
+	};
+
+	// the marker still starts the item
+	let BlockItem::List { items, .. } = &ast[0] else {
+		panic!("expected a list")
+	};
+	assert_eq!(items[2].pos, src.find("=10").unwrap());
+
+	assert_eq!(values(&ast[0]), [Some(7), None, Some(10)]);
+	// only ordered items have values, so this is text
+	assert_eq!(values(&ast[1]), [None, None]);
+	assert_eq!(values(&ast[2]), [None]);
+
+	assert_eq!(
+		doll.finish()
+			.iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("list item value too large", src.find("99").unwrap())]
+	);
+}
+
+#[test]
+pub fn empty_sections() {
+	assert_eq!(check("&\n\ntext"), (true, vec![("section has no name", 0)]));
+	assert_eq!(
+		check("text\n\n& "),
+		(true, vec![("section has no name", 6)])
+	);
+	assert_eq!(check("&a\n\ttext"), (true, vec![]));
+
+	// a section at the end of the source is kept
+	let mut doll = MarkDoll::new();
+	assert!(matches!(
+		&doll.parse_document("text\n\n&").unwrap().1[..],
+		[BlockItem::Inline(_), BlockItem::Section { pos: 6, .. }]
+	));
+	assert_eq!(doll.finish()[0].code, "section has no name");
+
+	// sections without content are only reported when asked for
+	assert_eq!(check("&a\n&b\n\ttext"), (true, vec![]));
+
+	doll.set_profile(Profile::Strict);
+	assert!(doll.parse_document("&a\n&b\n\ttext\n&").is_err());
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.err, diag.at))
+			.collect::<Vec<_>>(),
+		[
+			("section has no content", false, 0),
+			("section has no name", true, 12),
+			("section has no content", false, 12),
+		]
+	);
+}
+
+#[test]
+pub fn section_props() {
+	let src = "&(id=install)(class=appendix)(unlisted)\tInstallation\n\ttext\n&(id=a\\)b) Usage\n&(c) 2024 notice\n&(colour=red)(unlisted) c\n&\\(unlisted) notice\n&(unlisted)\\(d) e\n& (not props)\n&plain";
+
+	let mut doll = MarkDoll::new();
+	let ast = doll.parse_document(src).unwrap().1;
+	assert_eq!(
+		ast.iter()
+			.map(|block| match block {
+				BlockItem::Section { name, meta, .. } => {
+					(
+						name.as_str(),
+						meta.id.as_deref(),
+						meta.class.as_deref(),
+						meta.unlisted,
+					)
+				}
+				_ => panic!("expected a section"),
+			})
+			.collect::<Vec<_>>(),
+		[
+			("Installation", Some("install"), Some("appendix"), true),
+			("Usage", Some("a)b"), None, false),
+			("(c) 2024 notice", None, None, false),
+			("(colour=red)(unlisted) c", None, None, false),
+			("(unlisted) notice", None, None, false),
+			("(d) e", None, None, true),
+			(" (not props)", None, None, false),
+			("plain", None, None, false),
+		]
+	);
+
+	// unlisted sections are left out of the outline
+	assert_eq!(
+		analyze(&doll, None, &ast)
+			.outline
+			.iter()
+			.map(|heading| heading.name.as_str())
+			.collect::<Vec<_>>(),
+		[
+			"Usage",
+			"(c) 2024 notice",
+			"(colour=red)(unlisted) c",
+			"(unlisted) notice",
+			" (not props)",
+			"plain"
+		]
+	);
+
+	assert!(doll.finish().is_empty());
+}
+
+#[test]
+pub fn empty_tags() {
+	let mut doll = common::doll_with(&[
+		&ext::common::tags(),
+		&ext::formatting::tags(),
+		&ext::links::tags(),
+	]);
+
+	let src = "[em:] [link(a):]\n\n[quote::\n\n]\n\n[em] [em:a] [img(a.png):a] [//:]";
+
+	// only reported when asked for
+	doll.parse_document(src).unwrap();
+	assert!(doll.finish().is_empty());
+
+	doll.set_profile(Profile::Strict);
+	doll.parse_document(src).unwrap();
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics
+			.iter()
+			.map(|diag| (diag.code, diag.err, diag.at))
+			.collect::<Vec<_>>(),
+		[
+			("tag has no content", false, 0),
+			("tag has no content", false, 6),
+			("tag has no content", false, 18),
+			("tag has no content", false, 31),
+		]
+	);
+	assert_eq!(
+		diagnostics[1].data,
+		Some(Data::EmptyContent {
+			tag: "link".to_string()
+		})
+	);
+}
@@ -1,9 +1,9 @@
+mod common;
+
 use {
-	::hashbrown::HashMap,
-	::markdoll::emit::{BuiltInEmitters, HtmlEmit},
-	::std::rc::Rc,
+	::markdoll::emit::HtmlEmit,
 	ariadne::Source,
-	markdoll::{diagnostics::render, ext, MarkDoll},
+	markdoll::diagnostics::render,
 };
 
 #[test]
@@ -16,21 +16,11 @@ pub fn parses_own_spec() {
 		.default_format()
 		.init();
 
-	const SRC: &'static str = include_str!("../spec.doll");
+	const SRC: &str = include_str!("../spec.doll");
 
-	let mut out = HtmlEmit {
-		write: String::new(),
-		section_level: 0,
-		code_block_format: Rc::new(|_, _, _, _| {}),
-	};
+	let mut out = HtmlEmit::default();
 
-	let mut doll = MarkDoll::new();
-	doll.ext_system.add_tags(ext::common::tags());
-	doll.ext_system.add_tags(ext::formatting::tags());
-	doll.ext_system.add_tags(ext::code::tags());
-	doll.ext_system.add_tags(ext::links::tags());
-	doll.ext_system.add_tags(ext::table::tags());
-	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	let mut doll = common::doll();
 
 	println!("parse");
 
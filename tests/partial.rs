@@ -0,0 +1,83 @@
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext, MarkDoll,
+};
+
+/// parse and emit a broken document as `convert --idc` would, returning whether it parsed, whether it emitted, and the output
+fn partial(doll: &mut MarkDoll, src: &str) -> (bool, bool, String) {
+	doll.ext_system.add_tags(ext::common::tags());
+	doll.ext_system.add_tags(ext::formatting::tags());
+	doll.ext_system.add_tags(ext::table::tags());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let (parsed, mut ast) = match doll.parse_document(src) {
+		Ok((_, ast)) => (true, ast),
+		Err((_, ast)) => (false, ast),
+	};
+
+	let mut out = HtmlEmit::default();
+	let emitted = doll.emit(&mut ast, &mut out);
+	doll.finish();
+
+	(parsed, emitted, out.write)
+}
+
+/// check every element opened in `html` is closed, in order
+fn well_formed(html: &str) {
+	const VOID: &[&str] = &["br", "hr", "img", "input", "wbr", "source"];
+
+	let mut open = Vec::new();
+	let mut rest = html;
+
+	while let Some(start) = rest.find('<') {
+		let end = rest[start..].find('>').expect("unterminated element") + start;
+		let tag = &rest[start + 1..end];
+		rest = &rest[end + 1..];
+
+		if let Some(name) = tag.strip_prefix('/') {
+			assert_eq!(open.pop(), Some(name), "mismatched close in {html}");
+		} else if !tag.ends_with('/') {
+			let name = tag.split([' ', '\t', '\n']).next().unwrap();
+			if !VOID.contains(&name) {
+				open.push(name);
+			}
+		}
+	}
+
+	assert!(open.is_empty(), "unclosed {open:?} in {html}");
+}
+
+#[test]
+pub fn failed_parse_keeps_blocks() {
+	for src in [
+		"before\n\n[em:unterminated",
+		"before\n\n[nonexistent:tag]\n\nafter",
+		"before\n\n[table::\n\t[tr::\n\t\tnot a cell\n\t]\n]\n\nafter",
+		"before\n\n[em::\n\tblock content\n",
+		"&section\n\tbefore\n\n\t[em:unterminated",
+	] {
+		let (parsed, emitted, html) = partial(&mut MarkDoll::new(), src);
+		assert!(!parsed, "{src:?} parsed");
+		assert!(emitted, "{src:?} failed to emit");
+		assert!(
+			html.contains("before"),
+			"lost earlier blocks of {src:?}: {html}"
+		);
+		well_formed(&html);
+	}
+}
+
+#[test]
+pub fn failed_emit_is_well_formed() {
+	let mut doll = MarkDoll::new();
+	doll.max_emit_depth = 4;
+
+	let (parsed, emitted, html) = partial(
+		&mut doll,
+		"before\n\n[em:[em:[em:[em:[em:[em:deep]]]]]]\n\n[quote::\n\t[em:[em:[em:[em:too deep]]]]\n]\n\nafter",
+	);
+	assert!(parsed);
+	assert!(!emitted);
+	assert!(html.contains("before") && html.contains("after"), "{html}");
+	well_formed(&html);
+}
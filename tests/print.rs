@@ -0,0 +1,130 @@
+mod common;
+
+use markdoll::{
+	tree::{
+		print::{first_difference, to_doll, to_doll_document, Difference, PrintOptions},
+		AST,
+	},
+	MarkDoll,
+};
+
+fn emit(doll: &mut MarkDoll, ast: &mut AST) -> String {
+	let mut out = common::lang_target();
+	assert!(doll.emit(ast, &mut out));
+	out.write
+}
+
+fn round_trip(src: &str, options: PrintOptions) {
+	let mut doll = common::doll();
+
+	let (frontmatter, mut ast) = doll.parse_document(src).unwrap();
+	let printed = to_doll_document(&doll, frontmatter.as_deref(), &ast, options);
+	let html = emit(&mut doll, &mut ast);
+	doll.finish();
+
+	let (reparsed_frontmatter, mut reparsed) =
+		doll.parse_document(&*printed).unwrap_or_else(|_| {
+			std::fs::write("target/printed.doll", &printed).unwrap();
+			panic!("printed source does not parse, see target/printed.doll")
+		});
+	assert_eq!(frontmatter, reparsed_frontmatter);
+	assert_eq!(first_difference(&doll, &ast, &reparsed, options), None);
+	assert_eq!(
+		to_doll(&doll, &reparsed, options),
+		to_doll(&doll, &ast, options)
+	);
+	assert_eq!(emit(&mut doll, &mut reparsed), html);
+	doll.finish();
+}
+
+#[test]
+pub fn round_trips_spec() {
+	let src = include_str!("../spec.doll");
+
+	round_trip(src, PrintOptions::default());
+	round_trip(src, PrintOptions { max_inline_len: 0 });
+}
+
+#[test]
+pub fn round_trips_escapes() {
+	round_trip(
+		"\\&not a section\n\\- not a list\n[em:a \\\\\\[b]\n[code:\\:colon] [code:[balanced]] [code:\\[unbalanced]",
+		PrintOptions::default(),
+	);
+}
+
+#[test]
+pub fn round_trips_section_props() {
+	round_trip(
+		"&(id=a\\)b)(class=c d)(unlisted)\tname\n\ttext\n&(unlisted)\t(parenthesized)\n& (spaced)\n&(c) notice\n&\\(unlisted) notice",
+		PrintOptions::default(),
+	);
+}
+
+#[test]
+pub fn round_trips_trailing_breaks() {
+	round_trip(
+		"line one\\\n\nline two\\\n\n-\tstanza\\\n-\tend",
+		PrintOptions::default(),
+	);
+}
+
+#[test]
+pub fn prints_structure() {
+	let mut doll = common::doll();
+
+	let ast = doll
+		.parse_document("&a\n\tb\n\t-\tc\n\t\td\n\t-\te\n\n[em(b)::\n\tf\n\tg\n]")
+		.unwrap()
+		.1;
+
+	assert_eq!(
+		to_doll(&doll, &ast, PrintOptions::default()),
+		"&a\n\tb\n\n\t-\tc\n\t\td\n\t-\te\n\n[em(b)::\n\tf\n\tg\n]"
+	);
+}
+
+#[test]
+pub fn finds_differences() {
+	let mut doll = common::doll();
+
+	let mut parse = |src: &str| doll.parse_document(src).unwrap().1;
+	let a = parse("&a\n\t-\tb [em:c]\n\t-\td\n\n[em:e]");
+	let same = parse("\n&a\n\n\t-\tb [em:c]\n\t-\td\n\n\n[em::\n\te\n]");
+	let text = parse("&a\n\t-\tb [em:c]\n\t-\tf\n\n[em:e]");
+	let content = parse("&a\n\t-\tb [em:x]\n\t-\td\n\n[em:e]");
+	let fewer = parse("&a\n\t-\tb [em:c]\n\n[em:e]");
+
+	let options = PrintOptions::default();
+	let difference = |b| first_difference(&doll, &a, b, options);
+
+	// positions and the form of tags aren't compared
+	assert_eq!(difference(&same), None);
+	assert_eq!(
+		difference(&text),
+		Some(Difference {
+			path: "block 0 > block 0 > item 1 > block 0 > inline 0".to_string(),
+			nodes: [
+				Some("text \"d\"".to_string()),
+				Some("text \"f\"".to_string())
+			],
+			at: [18, 18],
+		})
+	);
+	assert_eq!(
+		difference(&content).unwrap().nodes,
+		[
+			Some("tag \"em\" with arguments [] and content \"c\"".to_string()),
+			Some("tag \"em\" with arguments [] and content \"x\"".to_string())
+		]
+	);
+	// where one has nothing, it is at the last node before
+	assert_eq!(
+		difference(&fewer),
+		Some(Difference {
+			path: "block 0 > block 0 > item 1".to_string(),
+			nodes: [Some("item".to_string()), None],
+			at: [16, 4],
+		})
+	);
+}
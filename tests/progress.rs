@@ -0,0 +1,48 @@
+mod common;
+
+use {
+	markdoll::{emit::HtmlEmit, ext, Phase, Progress},
+	std::sync::Mutex,
+};
+
+static REPORTED: Mutex<Vec<Progress>> = Mutex::new(Vec::new());
+
+#[test]
+pub fn progress() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.progress = Some(|progress| REPORTED.lock().unwrap().push(progress));
+
+	let src = (0..300)
+		.map(|i| format!("paragraph {i} with [em::\n\tsome\n\n\tcontent\n]\n\n"))
+		.collect::<String>();
+	let total = src.chars().count();
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let parsed = core::mem::take(&mut *REPORTED.lock().unwrap());
+
+	// the content of tags isn't reported
+	assert!(parsed
+		.iter()
+		.all(|progress| progress.phase == Phase::Parse && progress.total == total));
+	assert_eq!(parsed.first().unwrap().done, 0);
+	assert_eq!(parsed.last().unwrap().done, total);
+	assert!(parsed.windows(2).all(|pair| pair[0].done <= pair[1].done));
+	// about a hundred reports, not one per line
+	assert!((100..=110).contains(&parsed.len()), "{}", parsed.len());
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let emitted = core::mem::take(&mut *REPORTED.lock().unwrap());
+	assert_eq!(
+		emitted,
+		(0..=300)
+			.map(|done| Progress {
+				phase: Phase::Emit,
+				done,
+				total: 300,
+			})
+			.collect::<Vec<_>>()
+	);
+}
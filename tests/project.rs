@@ -0,0 +1,70 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext, project::Project};
+
+#[test]
+pub fn links_between_documents() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+	let mut project = Project::new();
+
+	let (index, ok) = project.add(
+		&mut doll,
+		"index",
+		"see [ref(install)] and [ref(local)]\n\n[def(local):here]",
+	);
+	assert!(ok);
+	let (install, ok) = project.add(&mut doll, "install", "[def(install):run the installer]");
+	assert!(ok);
+
+	assert_eq!(project.definitions()["install"].document, install);
+	assert!(project.link(&mut doll));
+
+	let mut out = HtmlEmit::default();
+	assert!(project.emit(&mut doll, index, &mut out));
+	assert!(out
+		.write
+		.contains("<a href='install.html#ref-install'>[install]</a>"));
+	assert!(out.write.contains("<a href='#ref-local'>[local]</a>"));
+
+	let mut out = HtmlEmit::default();
+	assert!(project.emit(&mut doll, install, &mut out));
+	assert!(out.write.contains("id='ref-install'"));
+
+	assert!(project.finish(&mut doll).iter().all(Vec::is_empty));
+}
+
+#[test]
+pub fn reports_duplicate_and_missing_ids() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+	let mut project = Project::new();
+
+	let a = "[def(dup):a]";
+	let b = "[def(dup):b] [ref(missing)]";
+	assert!(project.add(&mut doll, "a", a).1);
+	assert!(!project.add(&mut doll, "b", b).1);
+	assert!(!project.link(&mut doll));
+
+	let diagnostics = project.finish(&mut doll);
+
+	assert_eq!(diagnostics[0].len(), 1);
+	assert_eq!(
+		diagnostics[0][0].code,
+		"id defined again in another document"
+	);
+	assert_eq!(diagnostics[0][0].at, a.find("dup").unwrap());
+
+	let codes = diagnostics[1]
+		.iter()
+		.map(|diag| (diag.code, diag.at))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		codes,
+		[
+			(
+				"id already defined in another document",
+				b.find("dup").unwrap()
+			),
+			("id not defined in any document", b.find("missing").unwrap()),
+		]
+	);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use markdoll::ext::{
+	self,
+	qr::{Ecc, Qr},
+};
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::qr::tags()]), src)
+}
+
+#[test]
+pub fn encodes() {
+	let qr = Qr::encode("https://example.com", Ecc::Medium, None).unwrap();
+
+	// version 2 is the smallest that fits 19 bytes at medium
+	assert_eq!(qr.width, 25);
+	assert_eq!(qr.modules.len(), 25 * 25);
+	// finder pattern corners
+	assert!(qr.modules[0] && qr.modules[24] && qr.modules[24 * 25]);
+	assert!(qr.path().starts_with("M4,4h1v1h-1z"));
+}
+
+#[test]
+pub fn svg() {
+	let out = html("[qr(https://example.com)(size=128)(ecc=h)]");
+
+	assert!(out.starts_with(
+		"<div><svg class='doll-qr' xmlns='http://www.w3.org/2000/svg' viewBox='0 0 37 37' width='128' height='128' shape-rendering='crispEdges' role='img' aria-label='https://example.com'><rect width='37' height='37' fill='#fff'/><path d='M4,4h1v1h-1z"
+	));
+	assert!(out.ends_with("'/></svg></div>"));
+}
+
+#[test]
+pub fn too_long() {
+	let data = "a".repeat(Ecc::High.max_bytes() + 1);
+	assert!(Qr::encode(&data, Ecc::High, None).is_none());
+	assert!(Qr::encode(&data, Ecc::Low, None).is_some());
+
+	let mut doll = common::doll_with(&[&ext::qr::tags()]);
+
+	assert!(doll.parse_document(format!("[qr({data})(ecc=h)]")).is_err());
+	assert_eq!(doll.finish()[0].code, "qr data too long for ecc");
+
+	let data = "a".repeat(Ecc::High.max_bytes());
+	assert!(Qr::encode(&data, Ecc::High, None).is_some());
+}
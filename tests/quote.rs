@@ -0,0 +1,56 @@
+mod common;
+
+use markdoll::ext;
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::formatting::tags()]), src)
+}
+
+#[test]
+pub fn cite() {
+	assert_eq!(
+		html("[quote(Ada <Lovelace>)::\n\ttext\n]"),
+		"<div><figure class='doll-quote'><figcaption>Ada &lt;Lovelace&gt;</figcaption><blockquote><span>text</span></blockquote></figure></div>"
+	);
+	assert_eq!(
+		html("[quote(Ada)(dash)::\n\ttext\n]"),
+		"<div><figure class='doll-quote'><figcaption>\u{2014} Ada</figcaption><blockquote><span>text</span></blockquote></figure></div>"
+	);
+}
+
+#[test]
+pub fn url() {
+	assert_eq!(
+		html("[quote(Ada)(url=https://a.example/?q='x')(dash)::\n\ttext\n]"),
+		"<div><figure class='doll-quote'><figcaption>\u{2014} <a href='https:&#x2F;&#x2F;a.example&#x2F;?q=&#x27;x&#x27;'>Ada</a></figcaption><blockquote cite='https:&#x2F;&#x2F;a.example&#x2F;?q=&#x27;x&#x27;'><span>text</span></blockquote></figure></div>"
+	);
+
+	// without a citation, only the attribute
+	assert_eq!(
+		html("[quote(url=https://a.example/)::\n\ttext\n]"),
+		"<div><figure class='doll-quote'><blockquote cite='https:&#x2F;&#x2F;a.example&#x2F;'><span>text</span></blockquote></figure></div>"
+	);
+}
+
+#[test]
+pub fn forms() {
+	// inline, a quotation within the text
+	assert_eq!(
+		html("she said [quote(Ada)(url=https://a.example/)(dash):text] once"),
+		"<div>she said <span class='doll-quote'><q cite='https:&#x2F;&#x2F;a.example&#x2F;'><span>text</span></q> <cite>\u{2014} <a href='https:&#x2F;&#x2F;a.example&#x2F;'>Ada</a></cite></span> once</div>"
+	);
+	assert_eq!(
+		html("[quote:text]"),
+		"<div><span class='doll-quote'><q><span>text</span></q></span></div>"
+	);
+
+	// a block, as is an empty quote
+	assert_eq!(
+		html("[quote::\n\ttext\n]"),
+		"<div><figure class='doll-quote'><blockquote><span>text</span></blockquote></figure></div>"
+	);
+	assert_eq!(
+		html("[quote]"),
+		"<div><figure class='doll-quote'><blockquote></blockquote></figure></div>"
+	);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{BuiltInEmitters, HtmlEmit},
+		ext::{self, TagDefinition},
+		typemap::TypeMap,
+		MarkDoll,
+	},
+	std::sync::Arc,
+};
+
+/// the number of the last `note` emitted
+#[derive(Debug, Clone, Default)]
+struct NoteCounter(usize);
+
+fn doll() -> MarkDoll {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tag(
+		TagDefinition::new("note", Some(|_, _, _, _| Some(Box::new(())))).with_emitter::<HtmlEmit>(
+			|doll, to, _| {
+				let counter = doll.scratch.get_or_insert_with(NoteCounter::default);
+				counter.0 += 1;
+				to.write.push_str(&format!("<sup>{}</sup>", counter.0));
+			},
+		),
+	);
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+	doll
+}
+
+#[test]
+pub fn tags_share_scratch_within_an_emit() {
+	let mut doll = doll();
+	let mut ast = doll
+		.parse_document("a[note:]\n\n[em:b[note:]]\n\n&section\n\tc[note:]")
+		.unwrap()
+		.1;
+
+	for _ in 0..2 {
+		let mut out = HtmlEmit::default();
+		assert!(doll.emit(&mut ast, &mut out));
+
+		// the counter restarts with each emit
+		assert_eq!(out.write.matches("<sup>").count(), 3);
+		assert!(out.write.contains("<sup>1</sup>"));
+		assert!(out.write.contains("<sup>3</sup>"));
+		assert_eq!(doll.scratch.get_ref::<NoteCounter>().unwrap().0, 3);
+	}
+}
+
+#[test]
+pub fn drops_values() {
+	let value = Arc::new(());
+
+	let mut map = TypeMap::default();
+	map.put(Arc::clone(&value));
+	assert_eq!(Arc::strong_count(&value), 2);
+
+	let clone = map.clone();
+	assert_eq!(Arc::strong_count(&value), 3);
+	drop(clone);
+	assert_eq!(Arc::strong_count(&value), 2);
+
+	// replaced values are dropped
+	map.put(Arc::clone(&value));
+	assert_eq!(Arc::strong_count(&value), 2);
+
+	assert!(map.remove::<Arc<()>>().is_some());
+	assert_eq!(Arc::strong_count(&value), 1);
+
+	map.put(Arc::clone(&value));
+	map.put(());
+	map.clear();
+	assert!(map.is_empty());
+	assert_eq!(Arc::strong_count(&value), 1);
+
+	map.put(Arc::clone(&value));
+	drop(map);
+	assert_eq!(Arc::strong_count(&value), 1);
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use markdoll::{
+	ext,
+	tree::{plain_text, PlainTextOptions},
+};
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::formatting::tags()]), src)
+}
+
+#[test]
+pub fn elements() {
+	assert_eq!(
+		html("[citetitle:Dune], [dfn:doll], [varname:n], [samp:ok]"),
+		"<div><cite><span>Dune</span></cite>, <dfn><span>doll</span></dfn>, <var><span>n</span></var>, <samp><span>ok</span></samp></div>"
+	);
+}
+
+#[test]
+pub fn edits() {
+	assert_eq!(
+		html("[del:old][ins(datetime=2024-05-01)(cite=https://example.com/?a=1&b='2'):[em:new]]"),
+		"<div><del><span>old</span></del><ins datetime='2024-05-01' cite='https://example.com/?a=1&amp;b=&#x27;2&#x27;'><span><em><span>new</span></em></span></ins></div>"
+	);
+}
+
+#[test]
+pub fn plain_text_quotes_titles() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let ast = doll
+		.parse_document("read [citetitle:Dune] by [dfn:Herbert]")
+		.unwrap()
+		.1;
+
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"read “Dune” by Herbert"
+	);
+}
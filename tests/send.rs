@@ -0,0 +1,33 @@
+mod common;
+
+use markdoll::{
+	diagnostics::Diagnostic,
+	ext,
+	tree::{BlockItem, TagInvocation, AST},
+	MarkDoll,
+};
+
+const fn assert_send_sync<T: Send + Sync>() {}
+
+const _: () = {
+	assert_send_sync::<AST>();
+	assert_send_sync::<BlockItem>();
+	assert_send_sync::<TagInvocation>();
+	assert_send_sync::<Diagnostic>();
+	assert_send_sync::<MarkDoll>();
+};
+
+#[test]
+pub fn ast_crosses_threads() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::links::tags()]);
+
+	let ast = std::thread::spawn(move || {
+		doll.parse_document("[em:[link(https://example.com):hello]]")
+			.unwrap()
+			.1
+	})
+	.join()
+	.unwrap();
+
+	assert_eq!(ast.len(), 1);
+}
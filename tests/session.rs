@@ -0,0 +1,133 @@
+mod common;
+
+use {
+	markdoll::{emit::HtmlEmit, ext, tree::block_at},
+	std::sync::Arc,
+};
+
+#[test]
+pub fn reset_discards_state() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	assert!(doll.parse_document("[nope:a]").is_err());
+	doll.reset();
+
+	assert!(doll.is_ok());
+	let mut ast = doll.parse_document("[em:b]").unwrap().1;
+	assert!(doll.emit(&mut ast, &mut HtmlEmit::default()));
+	assert!(doll.finish().is_empty());
+}
+
+#[test]
+pub fn session_sets_aside_current_source() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	assert!(doll.parse_document("  outer").is_ok());
+
+	let (out, diagnostics) = doll.session(|doll| {
+		let mut ast = doll.parse_document("[em:inner]\n[nope:x]").unwrap_err().1;
+		let mut out = HtmlEmit::default();
+		doll.emit(&mut ast, &mut out);
+		out.write
+	});
+	assert_eq!(out, "<div><em><span>inner</span></em></div>");
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "tag not defined");
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "erroneous leading spaces");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "tag emitted after the source it was parsed from was finished or reset"]
+pub fn emitting_after_reset_panics() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let mut ast = doll.parse_document("[em:a]").unwrap().1;
+	doll.reset();
+	doll.emit(&mut ast, &mut HtmlEmit::default());
+}
+
+#[test]
+pub fn shares_document_source() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let sources = (0..8)
+		.map(|i| Arc::<str>::from(format!("document [em:{i}]")))
+		.collect::<Vec<_>>();
+
+	for src in &sources {
+		let mut ast = doll.parse_document(Arc::clone(src)).unwrap().1;
+		// held by the doll to resolve diagnostics, and by the `em` whose content is a slice of it, rather than copied
+		assert_eq!(Arc::strong_count(src), 3);
+
+		assert!(doll.emit(&mut ast, &mut HtmlEmit::default()));
+		assert!(doll.finish().is_empty());
+		// the doll lets go of it, the `em` keeps it for as long as the AST is kept
+		assert_eq!(Arc::strong_count(src), 2);
+
+		drop(ast);
+		assert_eq!(Arc::strong_count(src), 1);
+	}
+}
+
+#[test]
+pub fn verbatim_tag_content_shares_source() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let src = Arc::<str>::from("[em:a [em:b]] [em:c\\]]");
+	let mut ast = doll.parse_document(Arc::clone(&src)).unwrap().1;
+
+	// this, the doll, the outer `em` containing `a [em:b]`, and the `em` within it share the source, while the escaped `c]` has a buffer of its own
+	assert_eq!(Arc::strong_count(&src), 4);
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert_eq!(
+		out.write,
+		"<div><em><span>a <em><span>b</span></em></span></em> <em><span>c]</span></em></div>"
+	);
+
+	assert!(doll.finish().is_empty());
+	drop(ast);
+	assert_eq!(Arc::strong_count(&src), 1);
+}
+
+#[test]
+pub fn emits_block_at_position() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	let src = "intro
+
+&outer
+	[em:a]
+
+	&inner
+		b
+
+- c";
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	assert_eq!(block_at(&ast, 0), Some(0));
+	assert_eq!(block_at(&ast, src.find("[em").unwrap()), Some(1));
+	assert_eq!(block_at(&ast, src.len()), Some(2));
+
+	let at = block_at(&ast, src.find("inner").unwrap()).unwrap();
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit_block(&mut ast[at], &mut out));
+	assert_eq!(out.write, "<section data-level='1'><h1>outer</h1><div><div><em><span>a</span></em></div><section data-level='2'><h2>inner</h2><div><div>b</div></div></section></div></section>");
+
+	let mut out = HtmlEmit {
+		section_level: 2,
+		..Default::default()
+	};
+	assert!(doll.emit_block(&mut ast[1], &mut out));
+	assert!(out
+		.write
+		.starts_with("<section data-level='3'><h3>outer</h3>"));
+	assert!(doll.finish().is_empty());
+
+	assert_eq!(block_at(&Vec::new(), 0), None);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use markdoll::{ext, tree::Shorthand};
+
+fn html(src: &str, shorthands: bool) -> String {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+	if shorthands {
+		doll.shorthands = vec![Shorthand::bold(), Shorthand::italic(), Shorthand::code()];
+	}
+
+	common::html(&mut doll, src)
+}
+
+#[test]
+pub fn off_by_default() {
+	assert_eq!(html("*a* /b/ `c`", false), "<div>*a* /b/ `c`</div>");
+}
+
+#[test]
+pub fn presets() {
+	assert_eq!(
+		html("*a b* /c/ `d`", true),
+		"<div><strong><span>a b</span></strong> <em><span>c</span></em> <code>d</code></div>"
+	);
+	assert_eq!(
+		html("x *a [em:b*]* y", true),
+		"<div>x <strong><span>a <em><span>b*</span></em></span></strong> y</div>"
+	);
+	assert_eq!(html("`[a]`", true), "<div><code>[a]</code></div>");
+}
+
+#[test]
+pub fn stays_text() {
+	for src in [
+		"\\*a*",
+		"*a\\*",
+		"a*b*",
+		"*a*b",
+		"* a*",
+		"*a *",
+		"**",
+		"*a\nb*",
+		"https://a.example/b",
+		"a / b / c",
+	] {
+		let text = src.replace('\\', "").replace('\n', " ");
+		assert_eq!(html(src, true), format!("<div>{text}</div>"), "{src}");
+	}
+}
+
+#[test]
+pub fn diagnostic_positions() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.shorthands.push(Shorthand::bold());
+
+	assert!(doll.parse_document("ab *c [nope] d*").is_err());
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("tag not defined", 6)]
+	);
+
+	// a tag the shorthand names is reported at its opener
+	doll.shorthands = vec![Shorthand::new('~', "strike")];
+	assert!(doll.parse_document("ab ~c~").is_err());
+	assert_eq!(
+		doll.finish()
+			.into_iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("tag not defined", 3)]
+	);
+}
+
+#[test]
+pub fn many_unclosed_openers() {
+	// each opener looks for a closer to the end of the line
+	let src = format!("x{}", " *a".repeat(40_000));
+
+	let start = std::time::Instant::now();
+	assert_eq!(html(&src, true), format!("<div>{src}</div>"));
+	assert!(
+		start.elapsed() < std::time::Duration::from_secs(5),
+		"{:?}",
+		start.elapsed()
+	);
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use markdoll::{
+	emit::{BuiltInEmitters, HtmlEmit},
+	ext::{self, TagDefinition},
+	tree::{Snippet, TagContent},
+};
+
+/// a tag that generates markdoll from its argument
+fn badge() -> TagDefinition {
+	TagDefinition::new(
+		"badge",
+		Some(|doll, args, _, _| {
+			let src = format!("[em:{}] [{}:x]", args[0], args[1]);
+			Some(Box::new(match doll.parse_snippet(src, usize::MAX) {
+				Ok(snippet) | Err(snippet) => snippet,
+			}))
+		}),
+	)
+	.with_emitter::<HtmlEmit>(|doll, to, content: &mut Box<dyn TagContent>| {
+		content
+			.downcast_mut::<Snippet>()
+			.unwrap()
+			.emit(doll, to, false);
+	})
+}
+
+#[test]
+pub fn generated_content() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.ext_system.add_tag(badge());
+	doll.set_emitters(BuiltInEmitters::<HtmlEmit>::default());
+
+	let (_, mut ast) = doll
+		.parse_document("a\n\nb [badge(new)(em):c] [badge(old)(nope):d]")
+		.unwrap_err();
+
+	let mut out = HtmlEmit::untrusted_profile();
+	doll.emit(&mut ast, &mut out);
+	assert_eq!(
+		out.write,
+		"<div>a</div><div>b <span><em><span>new</span></em> <em><span>x</span></em></span> <span><em><span>old</span></em> </span></div>"
+	);
+
+	// diagnostics in the generated source are reported at the tag
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics
+			.iter()
+			.map(|diag| (diag.code, diag.at))
+			.collect::<Vec<_>>(),
+		[("tag not defined", 24)]
+	);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use markdoll::{ext, SourceStats};
+
+#[test]
+pub fn stats() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	// only content with escapes or indentation removed needs its own buffer
+	let src = "a [em:b [em:c]] [em:d\\]e]\n\n[em::\n\tf\n]";
+	doll.parse_document(src).unwrap();
+	assert_eq!(
+		doll.source_stats(),
+		SourceStats {
+			buffers: 3,
+			bytes: src.len() + "d]e".len() + "f".len(),
+		}
+	);
+
+	// a session's buffers are kept by the source containing it
+	doll.session(|doll| doll.parse_document("[em:g\\]]").unwrap());
+	assert_eq!(doll.source_stats().buffers, 5);
+
+	doll.finish();
+	assert_eq!(doll.source_stats(), SourceStats::default());
+}
+
+#[test]
+pub fn max_buffers() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.max_buffers = 3;
+
+	let src = "[em:\\a] [em:\\b]\n[em:\\c] [em:\\d]\n\n[em:\\e]";
+	let ast = doll.parse_document(src).unwrap_err().1;
+	assert_eq!(doll.tag_usage()["em"], 3);
+	assert_eq!(ast.len(), 1);
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "source limit exceeded");
+	assert_eq!(diagnostics[0].at, 17);
+}
+
+#[test]
+pub fn max_source_bytes() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.max_source_bytes = 8;
+
+	assert!(doll.parse_document("[em:\\a]").is_ok());
+	doll.finish();
+
+	let ast = doll.parse_document("too long to parse").unwrap_err().1;
+	assert!(ast.is_empty());
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "source limit exceeded");
+	assert_eq!(diagnostics[0].at, 0);
+}
+
+#[test]
+pub fn fail_fast() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.fail_fast = true;
+
+	// warnings don't stop it
+	let ast = doll
+		.parse_document("  a\n\nb [nope] [nah]\n\nc")
+		.unwrap_err()
+		.1;
+	assert!(doll.stopped_early());
+	assert_eq!(ast.len(), 2);
+
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics
+			.iter()
+			.map(|diag| (diag.code, diag.err))
+			.collect::<Vec<_>>(),
+		[
+			("erroneous leading spaces", false),
+			("tag not defined", true)
+		]
+	);
+	assert!(!doll.stopped_early());
+
+	// nor does the rest of a tag containing the error
+	doll.parse_document("[em:a [nope] [nah]] [no]\n\nb")
+		.unwrap_err();
+	assert!(doll.stopped_early());
+	assert_eq!(doll.tag_usage()["em"], 1);
+	assert_eq!(doll.finish().len(), 1);
+
+	doll.fail_fast = false;
+	doll.parse_document("[nope] [nah]").unwrap_err();
+	assert!(!doll.stopped_early());
+	assert_eq!(doll.finish().len(), 2);
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use markdoll::{
+	emit::{HtmlEmit, SourceMap, SourceMapping},
+	ext, MarkDoll,
+};
+
+fn emit(doll: &mut MarkDoll, src: &str) -> (String, SourceMap) {
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	let mut out = HtmlEmit {
+		source_map: Some(SourceMap::default()),
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+	doll.finish();
+
+	(out.write, out.source_map.unwrap())
+}
+
+#[test]
+pub fn records_items() {
+	let (html, map) = emit(
+		&mut common::doll_with(&[
+			&ext::code::tags(),
+			&ext::common::tags(),
+			&ext::formatting::tags(),
+			&ext::links::tags(),
+			&ext::table::tags(),
+		]),
+		"ab [em:cd]\n\n-\tef",
+	);
+	assert_eq!(
+		html,
+		"<div>ab <em><span>cd</span></em></div><ul><li><div>ef</div></li></ul>"
+	);
+
+	let mapping = |output: std::ops::Range<usize>, source| SourceMapping { output, source };
+	assert_eq!(
+		map.mappings,
+		[
+			mapping(5..8, 0),
+			mapping(18..20, 7),
+			mapping(8..32, 3),
+			mapping(51..53, 14),
+			mapping(42..64, 12),
+		]
+	);
+
+	assert_eq!(map.source_at(5), Some(0));
+	assert_eq!(map.source_at(19), Some(7));
+	assert_eq!(map.source_at(12), Some(3));
+	assert_eq!(map.source_at(0), None);
+}
+
+#[test]
+pub fn spec_table() {
+	let src = std::fs::read_to_string("spec.doll").unwrap();
+	let chars = src.chars().collect::<Vec<_>>();
+
+	let mut doll = common::doll_with(&[
+		&ext::code::tags(),
+		&ext::common::tags(),
+		&ext::formatting::tags(),
+		&ext::links::tags(),
+		&ext::table::tags(),
+	]);
+	doll.ext_system.add_tags(ext::citation::tags());
+	doll.ext_system.add_tags(ext::conditional::tags());
+	doll.ext_system.add_tags(ext::datetime::tags());
+	doll.ext_system.add_tags(ext::diagram::tags());
+	doll.ext_system.add_tags(ext::emoji::tags());
+	doll.ext_system.add_tags(ext::glossary::tags());
+	doll.ext_system.add_tags(ext::index::tags());
+	doll.ext_system.add_tags(ext::layout::tags());
+	doll.ext_system.add_tags(ext::math::tags());
+	doll.ext_system.add_tags(ext::media::tags());
+	doll.ext_system.add_tags(ext::page::tags());
+	doll.ext_system.add_tags(ext::tabs::tags());
+	doll.ext_system.add_tags(ext::todo::tags());
+	doll.ext_system.add_tags(ext::var::tags());
+	let (html, map) = emit(&mut doll, &src);
+
+	// the block from the `[` of the first table to the `]` closing it, in characters
+	let start = src[..src.find("[table::").unwrap()].chars().count();
+	let indent = chars[..start]
+		.iter()
+		.rev()
+		.take_while(|ch| **ch == '\t')
+		.count();
+	let close = format!("\n{}]", "\t".repeat(indent));
+	let end = chars[start..]
+		.windows(close.chars().count())
+		.position(|window| window.iter().copied().eq(close.chars()))
+		.unwrap()
+		+ start
+		+ close.chars().count();
+
+	let output_start = html.find("<table").unwrap();
+	let output_end = output_start + html[output_start..].find("</table>").unwrap();
+
+	for output in output_start..output_end {
+		let source = map.source_at(output).unwrap();
+		assert!((start..end).contains(&source), "{output} maps to {source}");
+	}
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use markdoll::{
+	emit::HtmlEmit,
+	ext::{
+		self,
+		links::{extract, LinkKind},
+	},
+	tree::split_sections,
+};
+
+const SRC: &str = "preamble
+
+&one
+	see [ref(note)]
+
+	&nested
+		deep
+
+after one
+
+&two
+	[def(note):a note]";
+
+#[test]
+pub fn splits_sections() {
+	let mut doll = common::doll_with(&[&ext::links::tags()]);
+
+	let ast = doll.parse_document(SRC).unwrap().1;
+	let split = split_sections(ast);
+
+	assert_eq!(split.preamble.len(), 1);
+	assert_eq!(
+		split
+			.sections
+			.iter()
+			.map(|section| &*section.title)
+			.collect::<Vec<_>>(),
+		["one", "two"]
+	);
+
+	// find the ref on one page which is defined on another
+	let pages = split
+		.sections
+		.iter()
+		.map(|section| {
+			extract(&doll, SRC, &section.body)
+				.into_iter()
+				.map(|link| (link.url, link.kind))
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(pages[0], [("note".to_string(), LinkKind::Reference)]);
+	assert_eq!(pages[1], [("note".to_string(), LinkKind::Definition)]);
+
+	let html = split
+		.sections
+		.into_iter()
+		.map(|section| {
+			let mut out = HtmlEmit::default();
+			assert!(doll.emit(&mut section.into_ast(), &mut out));
+			out.write
+		})
+		.collect::<Vec<_>>();
+
+	assert!(html[0].starts_with("<section data-level='1'><h1>one</h1>"));
+	assert!(html[0].contains("<section data-level='2'><h2>nested</h2>"));
+	assert!(html[0].ends_with("<div>after one</div>"));
+	assert!(html[1].starts_with("<section data-level='1'><h1>two</h1>"));
+	assert!(html[1].contains("id='ref-note'"));
+
+	assert!(doll.finish().is_empty());
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use markdoll::{
+	ext,
+	tree::{plain_text, PlainTextOptions},
+};
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::formatting::tags()]), src)
+}
+
+#[test]
+pub fn inline() {
+	assert_eq!(
+		html("it was [spoiler(ending):the <butler>]"),
+		"<div>it was <span class='doll-spoiler' tabindex='0'><span class='doll-spoiler-label'>ending</span><span class='doll-spoiler-content'><span>the &lt;butler&gt;</span></span></span></div>"
+	);
+	assert_eq!(
+		html("[spoiler:[em:hidden]]"),
+		"<div><span class='doll-spoiler' tabindex='0'><span class='doll-spoiler-content'><span><em><span>hidden</span></em></span></span></span></div>"
+	);
+}
+
+#[test]
+pub fn block() {
+	assert_eq!(
+		html("[spoiler::\n\tfirst\n\n\tsecond\n]"),
+		"<div><div class='doll-spoiler' tabindex='0'><div class='doll-spoiler-content'><div>first</div><div>second</div></div></div></div>"
+	);
+}
+
+#[test]
+pub fn plain_text_hides_content() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	let ast = doll
+		.parse_document("it was [spoiler(ending):the butler] and [spoiler:twins]")
+		.unwrap()
+		.1;
+
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"it was [spoiler: ending] and [spoiler]"
+	);
+	assert_eq!(
+		plain_text(
+			&doll,
+			&ast,
+			PlainTextOptions {
+				include_spoilers: true,
+				..PlainTextOptions::default()
+			}
+		),
+		"it was the butler and twins"
+	);
+}
@@ -0,0 +1,61 @@
+use {
+	markdoll::{diagnostics::Profile, emit::HtmlEmit},
+	std::sync::Arc,
+};
+
+#[test]
+pub fn options_fingerprint() {
+	let target = || {
+		let mut to = HtmlEmit::untrusted_profile();
+		to.vars.insert("a".to_string(), "1".to_string());
+		to.vars.insert("b".to_string(), "2".to_string());
+		to
+	};
+	let fingerprint = target().options_fingerprint();
+
+	// the same whatever order maps were filled in, and however much was emitted
+	let mut to = HtmlEmit::untrusted_profile();
+	to.vars.insert("b".to_string(), "2".to_string());
+	to.vars.insert("a".to_string(), "1".to_string());
+	to.write.push_str("<div>a</div>");
+	to.ids.number("doll-tabs");
+	assert_eq!(to.options_fingerprint(), fingerprint);
+
+	let mut to = target();
+	to.flags.insert("draft".to_string());
+	assert_ne!(to.options_fingerprint(), fingerprint);
+
+	let mut to = target();
+	to.vars.insert("a".to_string(), "1\nb=2".to_string());
+	assert_ne!(to.options_fingerprint(), fingerprint);
+
+	let mut to = target();
+	to.math_render = Some(Arc::new(|_, _, _, _| {}));
+	assert_ne!(to.options_fingerprint(), fingerprint);
+
+	let mut to = target();
+	to.ids.reserve("intro");
+	assert_ne!(to.options_fingerprint(), fingerprint);
+
+	let mut to = target();
+	to.code_block_format_name = Some("syntect:base16-ocean.dark".to_string());
+	assert_ne!(to.options_fingerprint(), fingerprint);
+}
+
+#[test]
+pub fn stamp() {
+	let to = HtmlEmit::untrusted_profile();
+
+	assert_eq!(
+		to.stamp(Profile::Strict),
+		format!(
+			"<!-- markdoll {} profile=strict options-hash={:016x} -->",
+			env!("CARGO_PKG_VERSION"),
+			to.options_fingerprint()
+		)
+	);
+	assert!(to
+		.canonical_options()
+		.lines()
+		.any(|line| line == "safe_urls=true"));
+}
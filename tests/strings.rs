@@ -0,0 +1,164 @@
+mod common;
+
+use {
+	markdoll::{
+		emit::{HtmlEmit, StringKey, Strings},
+		ext::{
+			self,
+			citation::{Author, Reference},
+			datetime::Unit,
+		},
+		tree::{QuoteStyle, SpacingStyle},
+	},
+	std::{borrow::Cow, sync::Arc},
+};
+
+/// every key without a wildcard, so adding one fails to compile here
+fn german(key: &StringKey) -> String {
+	const MONTHS: [&str; 12] = [
+		"Januar",
+		"Februar",
+		"März",
+		"April",
+		"Mai",
+		"Juni",
+		"Juli",
+		"August",
+		"September",
+		"Oktober",
+		"November",
+		"Dezember",
+	];
+
+	const WEEKDAYS: [&str; 7] = [
+		"Sonntag",
+		"Montag",
+		"Dienstag",
+		"Mittwoch",
+		"Donnerstag",
+		"Freitag",
+		"Samstag",
+	];
+
+	// in the dative, after both `vor` and `in`
+	let unit = |count: u64, unit: Unit| {
+		let (one, many) = match unit {
+			Unit::Minute => ("Minute", "Minuten"),
+			Unit::Hour => ("Stunde", "Stunden"),
+			Unit::Day => ("Tag", "Tagen"),
+			Unit::Month => ("Monat", "Monaten"),
+			Unit::Year => ("Jahr", "Jahren"),
+		};
+
+		format!("{count} {}", if count == 1 { one } else { many })
+	};
+
+	match *key {
+		StringKey::Date { year, month, day } => {
+			format!("{day}. {} {year}", MONTHS[usize::from(month) - 1])
+		}
+		StringKey::Weekday { weekday, date } => {
+			format!("{}, {date}", WEEKDAYS[usize::from(weekday)])
+		}
+		StringKey::JustNow => "gerade eben".to_string(),
+		StringKey::Ago(count, of) => format!("vor {}", unit(count, of)),
+		StringKey::In(count, of) => format!("in {}", unit(count, of)),
+		StringKey::TwoAuthors(first, second) => format!("{first} und {second}"),
+		StringKey::EtAl(first) => format!("{first} u. a."),
+		StringKey::NoDate => "o. J.".to_string(),
+	}
+}
+
+fn html(src: &str, strings: Strings) -> String {
+	let mut doll = common::doll_with(&[&ext::citation::tags(), &ext::datetime::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	ext::citation::collect(&doll, &mut ast);
+
+	let author = |family: &str| Author {
+		family: family.to_string(),
+		given: None,
+	};
+
+	let mut out = HtmlEmit {
+		time_reference: Some(1_710_000_000),
+		bibliography: Some(Arc::new(
+			[
+				Reference {
+					id: "two".to_string(),
+					authors: vec![author("Doe"), author("Roe")],
+					year: None,
+					title: None,
+					container: None,
+					url: None,
+				},
+				Reference {
+					id: "three".to_string(),
+					authors: vec![author("Doe"), author("Roe"), author("Poe")],
+					year: Some(2020),
+					title: None,
+					container: None,
+					url: None,
+				},
+			]
+			.into_iter()
+			.collect(),
+		)),
+		strings,
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+
+	out.write
+}
+
+const SRC: &str = "[time(2024-03-09)(format=weekday)] [time(2024-03-09T14:30)(format=datetime)]
+[time(2024-03-09T15:59:50Z)(relative)] [time(2024-03-06T16:00Z)(relative)] [time(2024-03-09T18:00Z)(relative)]
+[cite(two)] [cite(three)]";
+
+#[test]
+pub fn english_by_default() {
+	let strings = Strings::default();
+	assert_eq!(strings.locale, "en");
+
+	assert_eq!(
+		html(SRC, strings),
+		"<div><time datetime='2024-03-09'>Saturday, March 9, 2024</time> <time datetime='2024-03-09T14:30'>March 9, 2024 14:30</time><time datetime='2024-03-09T15:59:50Z'>just now</time> <time datetime='2024-03-06T16:00Z'>3 days ago</time> <time datetime='2024-03-09T18:00Z'>in 2 hours</time><a class='doll-cite' href='#ref-two'>(Doe and Roe n.d.)</a> <a class='doll-cite' href='#ref-three'>(Doe et al. 2020)</a></div>"
+	);
+}
+
+#[test]
+pub fn german_table() {
+	let strings = Strings {
+		locale: Cow::Borrowed("de"),
+		text: Arc::new(german),
+	};
+	assert_eq!(
+		QuoteStyle::for_locale(&strings.locale),
+		Some(QuoteStyle::German)
+	);
+	assert_eq!(SpacingStyle::for_locale(&strings.locale), None);
+
+	assert_eq!(
+		html(SRC, strings),
+		"<div><time datetime='2024-03-09'>Samstag, 9. März 2024</time> <time datetime='2024-03-09T14:30'>9. März 2024 14:30</time><time datetime='2024-03-09T15:59:50Z'>gerade eben</time> <time datetime='2024-03-06T16:00Z'>vor 3 Tagen</time> <time datetime='2024-03-09T18:00Z'>in 2 Stunden</time><a class='doll-cite' href='#ref-two'>(Doe und Roe o. J.)</a> <a class='doll-cite' href='#ref-three'>(Doe u. a. 2020)</a></div>"
+	);
+}
+
+#[test]
+pub fn locales() {
+	assert_eq!(QuoteStyle::for_locale("en-GB"), Some(QuoteStyle::English));
+	assert_eq!(QuoteStyle::for_locale("FR"), Some(QuoteStyle::French));
+	assert_eq!(QuoteStyle::for_locale("ja"), None);
+
+	assert_eq!(
+		SpacingStyle::for_locale("fr_CA"),
+		Some(SpacingStyle::CanadianFrench)
+	);
+	assert_eq!(
+		SpacingStyle::for_locale("fr-BE"),
+		Some(SpacingStyle::French)
+	);
+	assert_eq!(SpacingStyle::for_locale("en"), None);
+}
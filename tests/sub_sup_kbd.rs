@@ -0,0 +1,49 @@
+mod common;
+
+use markdoll::ext;
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::formatting::tags()]), src)
+}
+
+#[test]
+pub fn scripts() {
+	assert_eq!(
+		html("H[sub:2]O and x[sup:[em:n]]"),
+		"<div>H<sub><span>2</span></sub>O and x<sup><span><em><span>n</span></em></span></sup></div>"
+	);
+}
+
+#[test]
+pub fn emphasis_kinds() {
+	// nested in a fixed order, closed in reverse, however the flags are written
+	assert_eq!(
+		html("[em(sub)(sc)(b):a]"),
+		"<div><strong><span class='doll-sc'><sub><span>a</span></sub></span></strong></div>"
+	);
+	assert_eq!(
+		html("[em(sup):a] [em:b] [em(i)(sup):c]"),
+		"<div><sup><span>a</span></sup> <em><span>b</span></em> <em><sup><span>c</span></sup></em></div>"
+	);
+}
+
+#[test]
+pub fn keys() {
+	assert_eq!(
+		html("[kbd:Ctrl+Shift+P]"),
+		"<div><kbd class='doll-kbd'><kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>P</kbd></kbd></div>"
+	);
+	assert_eq!(
+		html("[kbd:Ctrl + +]"),
+		"<div><kbd class='doll-kbd'><kbd>Ctrl</kbd>+<kbd>+</kbd></kbd></div>"
+	);
+	assert_eq!(
+		html("[kbd(raw):Ctrl+P]"),
+		"<div><kbd><span>Ctrl+P</span></kbd></div>"
+	);
+	// content with tags isn't split
+	assert_eq!(
+		html("[kbd:[em:Ctrl]+P]"),
+		"<div><kbd><span><em><span>Ctrl</span></em>+P</span></kbd></div>"
+	);
+}
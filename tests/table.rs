@@ -0,0 +1,59 @@
+mod common;
+
+#[test]
+pub fn huge_spans() {
+	for span in ["4000000000", "18446744073709551615"] {
+		let mut doll = common::doll();
+
+		let (html, warnings) = common::html_warnings(
+			&mut doll,
+			&format!("[table::\n\t[tr::\n\t\t[tc(cols={span}):a][tc:b]\n\t]\n\t[tr::\n\t\t[tc(rows={span}):c]\n\t]\n]"),
+		);
+		assert!(html.contains(&format!("colspan='{span}'")), "{html}");
+		assert_eq!(
+			warnings,
+			[
+				"row has an inconsistent amount of columns",
+				"rowspan extends past the last row"
+			]
+		);
+	}
+}
+
+#[test]
+pub fn spans_fill_later_rows() {
+	let mut doll = common::doll();
+
+	common::html(
+		&mut doll,
+		"[table::\n\t[tr::\n\t\t[tc(rows=2):a][tc(cols=2):b]\n\t]\n\t[tr::\n\t\t[tc:c][tc:d]\n\t]\n]",
+	);
+}
+
+#[test]
+pub fn many_spanning_rows() {
+	// each cell is pushed past those spanning down from the rows before it
+	let rows = 20_000;
+	let src = format!(
+		"[table::\n{}]",
+		"\t[tr::\n\t\t[tc(rows=60000):x]\n\t]\n".repeat(rows)
+	);
+
+	let mut doll = common::doll();
+	let start = std::time::Instant::now();
+	let _ = doll.parse_document(src);
+	assert!(
+		start.elapsed() < std::time::Duration::from_secs(5),
+		"{:?}",
+		start.elapsed()
+	);
+
+	let diagnostics = doll.finish();
+	assert_eq!(
+		diagnostics
+			.iter()
+			.filter(|diag| diag.code == "rowspan extends past the last row")
+			.count(),
+		rows
+	);
+}
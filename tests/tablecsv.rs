@@ -0,0 +1,42 @@
+mod common;
+
+use markdoll::{diagnostics::LineCol, emit::HtmlEmit, tree::build::Doc};
+
+#[test]
+pub fn crlf() {
+	// documents can't contain CRLF, but content passed to tags from code can
+	let html = |csv: &str| {
+		let mut doll = common::doll();
+		let mut doc = Doc::new(&mut doll);
+		doc.tag("tablecsv", &[], csv);
+		let mut ast = doc.finish().unwrap();
+
+		let mut out = HtmlEmit::default();
+		assert!(doll.emit(&mut ast, &mut out));
+		assert!(doll.finish().is_empty());
+		out.write
+	};
+
+	assert_eq!(html("a,b\r\nc,\"d\"\r\n"), html("a,b\nc,\"d\"\n"));
+	assert_eq!(html("a,\r\nb,\r\n"), html("a,\nb,\n"));
+}
+
+#[test]
+pub fn unterminated_quote_at_the_quote() {
+	for (src, at) in [
+		(
+			"[tablecsv::\n\ta,b\n\tc,\"d\n\te\n]",
+			LineCol { line: 3, col: 4 },
+		),
+		// positions count characters
+		("[tablecsv::\n\tééé,\"b\n]", LineCol { line: 2, col: 6 }),
+	] {
+		let mut doll = common::doll();
+		assert!(doll.parse_document(src).is_err());
+
+		let diagnostics = doll.finish();
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].code, "unterminated quote in csv record");
+		assert_eq!(LineCol::of(src, diagnostics[0].at), at, "{src:?}");
+	}
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use markdoll::ext;
+
+fn html(src: &str) -> String {
+	common::html(&mut common::doll_with(&[&ext::tabs::tags()]), src)
+}
+
+#[test]
+pub fn groups() {
+	assert_eq!(
+		html("[tabs::\n\t[tab(rust):fn]\n\t[tab(<py>):def]\n]\n\n[tabs:[tab(only):one]]"),
+		"<div><div class='doll-tabs' role='tablist'>\
+			<input type='radio' class='doll-tab-input' name='doll-tabs-1' id='doll-tabs-1-1' checked />\
+			<label class='doll-tab' role='tab' id='doll-tabs-1-1-tab' for='doll-tabs-1-1'>rust</label>\
+			<div class='doll-tab-panel' role='tabpanel' aria-labelledby='doll-tabs-1-1-tab'><span>fn</span></div>\
+			<input type='radio' class='doll-tab-input' name='doll-tabs-1' id='doll-tabs-1-2' />\
+			<label class='doll-tab' role='tab' id='doll-tabs-1-2-tab' for='doll-tabs-1-2'>&lt;py&gt;</label>\
+			<div class='doll-tab-panel' role='tabpanel' aria-labelledby='doll-tabs-1-2-tab'><span>def</span></div>\
+		</div></div>\
+		<div><div class='doll-tabs' role='tablist'>\
+			<input type='radio' class='doll-tab-input' name='doll-tabs-2' id='doll-tabs-2-1' checked />\
+			<label class='doll-tab' role='tab' id='doll-tabs-2-1-tab' for='doll-tabs-2-1'>only</label>\
+			<div class='doll-tab-panel' role='tabpanel' aria-labelledby='doll-tabs-2-1-tab'><span>one</span></div>\
+		</div></div>"
+	);
+}
+
+#[test]
+pub fn non_tab() {
+	let mut doll = common::doll_with(&[&ext::tabs::tags()]);
+
+	assert!(doll
+		.parse_document("[tabs::\n\t[tab(a):a]\n\ttext\n]")
+		.is_err());
+	assert_eq!(
+		doll.finish()[0].code,
+		"`tabs` tags may only contain `tab` tags"
+	);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use {
+	markdoll::{
+		diagnostics::{Data, LineIndex},
+		ext::{self, TagFilter, TagFilterAction, TagForm},
+		tree::{BlockItem, InlineItem},
+		MarkDoll,
+	},
+	std::sync::{Arc, Mutex},
+};
+
+fn doll(filter: TagFilter) -> MarkDoll {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::links::tags()]);
+	doll.tag_filter = Some(filter);
+	doll
+}
+
+#[test]
+pub fn rename() {
+	let mut doll = doll(TagFilter::new(|event| match event.tag {
+		"bold" => TagFilterAction::Rename("em"),
+		"gone" => TagFilterAction::Rename("nope"),
+		_ => TagFilterAction::Allow,
+	}));
+
+	let ast = doll.parse_document("[bold(b):a] [em:b]").unwrap().1;
+	let BlockItem::Inline(items) = &ast[0] else {
+		panic!("{ast:?}");
+	};
+	let InlineItem::Tag(tag) = &items[0].1 else {
+		panic!("{items:?}");
+	};
+	// found by the new name, but kept as written
+	assert_eq!(tag.tag, "bold");
+	assert_eq!(tag.args, ["b"]);
+	assert_eq!(tag.definition().key, "em");
+	assert_eq!(doll.tag_usage()["bold"], 1);
+	assert_eq!(doll.tag_usage()["em"], 1);
+	doll.finish();
+
+	// renaming to a tag that isn't defined is the same as writing it
+	doll.parse_document("[gone:a]").unwrap_err();
+	let codes = doll
+		.finish()
+		.iter()
+		.map(|diag| diag.code)
+		.collect::<Vec<_>>();
+	assert_eq!(codes, ["tag not defined"]);
+}
+
+#[test]
+pub fn deny() {
+	let seen = Arc::new(Mutex::new(Vec::new()));
+	let mut doll = doll(TagFilter::new({
+		let seen = Arc::clone(&seen);
+		move |event| {
+			seen.lock().unwrap().push((
+				event.at,
+				event.tag.to_string(),
+				event.args.to_vec(),
+				event.form,
+			));
+
+			if event.tag == "img" {
+				TagFilterAction::Deny {
+					reason: "images can't be shown here".to_string(),
+				}
+			} else {
+				TagFilterAction::Allow
+			}
+		}
+	}));
+
+	let src = "a [img(a.png):[em:alt]] [em]";
+	let ast = doll.parse_document(src).unwrap_err().1;
+	let BlockItem::Inline(items) = &ast[0] else {
+		panic!("{ast:?}");
+	};
+	assert!(!items
+		.iter()
+		.any(|(_, item)| matches!(item, InlineItem::Tag(tag) if tag.tag == "img")));
+
+	// its content isn't parsed
+	assert_eq!(
+		*seen.lock().unwrap(),
+		[
+			(
+				3,
+				"img".to_string(),
+				vec!["a.png".to_string()],
+				TagForm::Inline
+			),
+			(25, "em".to_string(), vec![], TagForm::Empty),
+		]
+	);
+
+	let diagnostics = doll.finish();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].code, "tag denied");
+	assert_eq!((diagnostics[0].at, diagnostics[0].len), (2, 21));
+	assert_eq!(
+		diagnostics[0].data,
+		Some(Data::TagDenied {
+			tag: "img".to_string(),
+			reason: "images can't be shown here".to_string(),
+		})
+	);
+	assert_eq!(
+		diagnostics[0]
+			.to_structured(&LineIndex::new(src))
+			.help
+			.as_deref(),
+		Some("images can't be shown here")
+	);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use markdoll::{
+	ext,
+	tree::{plain_text, PlainTextOptions, WrapOptions},
+};
+
+const SRC: &str = "&title
+	some [em:emphasized] text\\
+	broken
+
+	[link(https://example.com):a link] and [code:code]
+
+	[table::
+		-	-	cell
+			-	[quote:quoted]
+	]
+
+	[codeblock::
+		block
+	]";
+
+#[test]
+pub fn extracts_plain_text() {
+	let mut doll = common::doll_with(&[
+		&ext::formatting::tags(),
+		&ext::code::tags(),
+		&ext::links::tags(),
+		&ext::table::tags(),
+	]);
+
+	let ast = doll.parse_document(SRC).unwrap().1;
+
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"title\nsome emphasized text\nbroken\na link and\ncell\nquoted"
+	);
+	assert_eq!(
+		plain_text(
+			&doll,
+			&ast,
+			PlainTextOptions {
+				include_code: true,
+				..PlainTextOptions::default()
+			}
+		),
+		"title\nsome emphasized text\nbroken\na link and code\ncell\nquoted\nblock"
+	);
+}
+
+/// the plain text of `src`, wrapped at `width`
+fn wrapped(src: &str, width: usize, break_long_words: bool) -> String {
+	let mut doll = common::doll_with(&[
+		&ext::formatting::tags(),
+		&ext::code::tags(),
+		&ext::links::tags(),
+	]);
+
+	let ast = doll.parse(src).unwrap();
+
+	plain_text(
+		&doll,
+		&ast,
+		PlainTextOptions {
+			include_code: true,
+			wrap: Some(WrapOptions {
+				width,
+				break_long_words,
+			}),
+			..PlainTextOptions::default()
+		},
+	)
+}
+
+#[test]
+pub fn wraps() {
+	let src = "[quote::
+	the first item of a list, inside of a quote, is long enough to wrap
+
+	-	and so is this item, nested within the list inside of the quote
+		-	and this one
+]";
+	let text = wrapped(src, 24, false);
+	assert!(
+		text.lines().all(|line| line.chars().count() <= 24),
+		"{text}"
+	);
+	assert_eq!(
+		text,
+		"the first item of a\nlist, inside of a quote,\nis long enough to wrap\nand so is this item,\nnested within the list\ninside of the quote\nand this one"
+	);
+
+	// no-break spaces hold words together, and code is never broken
+	assert_eq!(
+		wrapped("a b c\u{a0}d e [code:f g h i] j", 6, false),
+		"a b\nc\u{a0}d e\nf g h i\nj"
+	);
+}
+
+#[test]
+pub fn wraps_long_words() {
+	let url = format!("https://example.com/{}", "a".repeat(280));
+	let src = format!("see [link({url}):{url}] for more");
+
+	assert_eq!(wrapped(&src, 72, false), format!("see\n{url}\nfor more"));
+
+	let text = wrapped(&src, 72, true);
+	assert_eq!(text.replace('\n', ""), format!("see{url} for more"));
+	assert_eq!(
+		text.lines()
+			.map(|line| line.chars().count())
+			.collect::<Vec<_>>(),
+		[3, 72, 72, 72, 72, 21]
+	);
+
+	// code is kept whole even so
+	assert_eq!(
+		wrapped(&format!("see [code:{url}]"), 72, true),
+		format!("see\n{url}")
+	);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use markdoll::{
+	emit::HtmlEmit,
+	ext::{self, datetime::DateTime},
+	tree::{plain_text, PlainTextOptions},
+};
+
+fn html(src: &str, time_reference: Option<i64>) -> (String, Vec<&'static str>) {
+	common::html_to(
+		&mut common::doll_with(&[&ext::formatting::tags(), &ext::datetime::tags()]),
+		src,
+		HtmlEmit {
+			time_reference,
+			..Default::default()
+		},
+	)
+}
+
+#[test]
+pub fn parses_iso_8601() {
+	assert_eq!(
+		DateTime::parse("2024-02-29"),
+		Ok(DateTime {
+			year: 2024,
+			month: 2,
+			day: 29,
+			time: None,
+			offset: None,
+		})
+	);
+	assert_eq!(
+		DateTime::parse("2024-03-09T14:30:05.250-05:30"),
+		Ok(DateTime {
+			year: 2024,
+			month: 3,
+			day: 9,
+			time: Some((14, 30, 5)),
+			offset: Some(-330),
+		})
+	);
+	assert_eq!(DateTime::parse("1970-01-01T00:00Z").unwrap().timestamp(), 0);
+	assert_eq!(
+		DateTime::parse("2024-03-09T01:00+01:00")
+			.unwrap()
+			.timestamp(),
+		1_709_942_400
+	);
+
+	for (src, code) in [
+		("24-03-09", "time year invalid"),
+		("2024-13-09", "time month invalid"),
+		("2023-02-29", "time day invalid"),
+		("2024-03-09 14:30", "time hour invalid"),
+		("2024-03-09T14", "time minute invalid"),
+		("2024-03-09T14:30:61", "time second invalid"),
+		("2024-03-09T14:30+5", "time offset invalid"),
+		("2024-03-09T14:30Zulu", "time has trailing characters"),
+	] {
+		assert_eq!(DateTime::parse(src), Err(code), "{src}");
+	}
+}
+
+#[test]
+pub fn formats() {
+	assert_eq!(
+		html(
+			"[time(2024-03-09)] [time(2024-03-09)(format=date)] [time(2024-03-09T14:30)(format=datetime)] [time(2024-03-09)(format=weekday)]",
+			None
+		)
+		.0,
+		"<div><time datetime='2024-03-09'>2024-03-09</time> <time datetime='2024-03-09'>March 9, 2024</time> <time datetime='2024-03-09T14:30'>March 9, 2024 14:30</time> <time datetime='2024-03-09'>Saturday, March 9, 2024</time></div>"
+	);
+	assert_eq!(
+		html("[time(2024-03-09):the [em:release]]", None).0,
+		"<div><time datetime='2024-03-09'><span>the <em><span>release</span></em></span></time></div>"
+	);
+}
+
+#[test]
+pub fn relative() {
+	let reference = DateTime::parse("2024-03-12T12:00Z").unwrap().timestamp();
+
+	assert_eq!(
+		html(
+			"[time(2024-03-09)(relative)] [time(2024-03-12T11:58Z)(relative)] [time(2025-03-12)(relative)]",
+			Some(reference)
+		)
+		.0,
+		"<div><time datetime='2024-03-09'>3 days ago</time> <time datetime='2024-03-12T11:58Z'>2 minutes ago</time> <time datetime='2025-03-12'>in 1 year</time></div>"
+	);
+	assert_eq!(
+		html("[time(2024-03-09)(relative)]", None),
+		(
+			"<div><time datetime='2024-03-09'>2024-03-09</time></div>".to_string(),
+			vec!["no time reference for relative time"]
+		)
+	);
+}
+
+#[test]
+pub fn invalid() {
+	for src in ["[time(2024-02-30)]", "[time(2024-03-09)(format=fancy)]"] {
+		assert!(
+			common::doll_with(&[&ext::formatting::tags(), &ext::datetime::tags()])
+				.parse_document(src)
+				.is_err(),
+			"{src}"
+		);
+	}
+}
+
+#[test]
+pub fn plain_text_shows_display() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::datetime::tags()]);
+	let ast = doll
+		.parse_document("released [time(2024-03-09)(format=date)] or [time(2024-03-09):then]")
+		.unwrap()
+		.1;
+
+	assert_eq!(
+		plain_text(&doll, &ast, PlainTextOptions::default()),
+		"released March 9, 2024 or then"
+	);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use markdoll::{
+	diagnostics::LineIndex,
+	emit::HtmlEmit,
+	ext::{
+		self,
+		todo::{self, DraftPolicy},
+	},
+};
+
+const SRC: &str = "intro [todo:cite this]
+
+&section
+	see [em:nested [todo(alice):rewrite]]
+
+	[todo::
+		many
+
+		lines
+	]";
+
+fn html(draft_mode: DraftPolicy) -> (bool, String, Vec<&'static str>) {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::todo::tags()]);
+
+	let mut ast = doll.parse_document(SRC).unwrap().1;
+
+	let mut out = HtmlEmit {
+		draft_mode,
+		..Default::default()
+	};
+	let ok = doll.emit(&mut ast, &mut out);
+
+	(
+		ok,
+		out.write,
+		doll.finish().iter().map(|diag| diag.code).collect(),
+	)
+}
+
+#[test]
+pub fn show() {
+	let (ok, out, diagnostics) = html(DraftPolicy::Show);
+
+	assert!(ok);
+	assert!(diagnostics.is_empty());
+	assert!(out.starts_with(
+		"<div>intro <span class='doll-todo' role='note'><span>cite this</span></span></div>"
+	));
+	assert!(out.contains(
+		"<em><span>nested <span class='doll-todo' role='note'><span class='doll-todo-assignee'>alice</span><span>rewrite</span></span></span></em>"
+	));
+	assert!(
+		out.contains("<div class='doll-todo' role='note'><div>many</div><div>lines</div></div>")
+	);
+}
+
+#[test]
+pub fn hide() {
+	let (ok, out, diagnostics) = html(DraftPolicy::Hide);
+
+	assert!(ok);
+	assert!(diagnostics.is_empty());
+	assert!(!out.contains("doll-todo"));
+	assert!(!out.contains("rewrite"));
+	assert!(!out.contains("many"));
+}
+
+#[test]
+pub fn error() {
+	let (ok, _, diagnostics) = html(DraftPolicy::Error);
+
+	assert!(!ok);
+	assert_eq!(
+		diagnostics,
+		["todo in release", "todo in release", "todo in release"]
+	);
+}
+
+#[test]
+pub fn lists() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::todo::tags()]);
+
+	let ast = doll.parse_document(SRC).unwrap().1;
+	let lines = LineIndex::new(SRC);
+
+	let todos = todo::list(&doll, &ast)
+		.into_iter()
+		.map(|todo| {
+			let pos = lines.line_col(todo.at);
+			(pos.line, pos.col, todo.assignee, todo.text)
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		todos,
+		[
+			(1, 7, None, "cite this".to_string()),
+			(4, 17, Some("alice".to_string()), "rewrite".to_string()),
+			(6, 2, None, "many\nlines".to_string())
+		]
+	);
+}
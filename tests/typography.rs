@@ -0,0 +1,135 @@
+mod common;
+
+use markdoll::{
+	ext,
+	tree::{
+		plain_text, skip_typography, smart_punctuation, typographic_spacing, PlainTextOptions,
+		QuoteStyle, SpacingStyle, AST,
+	},
+	MarkDoll,
+};
+
+fn text(src: &str, pass: impl FnOnce(&MarkDoll, &mut AST)) -> String {
+	let mut doll = common::doll_with(&[&ext::formatting::tags(), &ext::code::tags()]);
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+	pass(&doll, &mut ast);
+
+	plain_text(
+		&doll,
+		&ast,
+		PlainTextOptions {
+			include_code: true,
+			..Default::default()
+		},
+	)
+}
+
+fn punctuate(src: &str, style: QuoteStyle) -> String {
+	text(src, |doll, ast| smart_punctuation(doll, ast, style))
+}
+
+fn space(src: &str, style: SpacingStyle) -> String {
+	text(src, |doll, ast| typographic_spacing(doll, ast, style))
+}
+
+#[test]
+pub fn quotes() {
+	assert_eq!(
+		punctuate(
+			"\"she said 'hi' to me,\" he said. it's the dogs' bones",
+			QuoteStyle::English
+		),
+		"“she said ‘hi’ to me,” he said. it’s the dogs’ bones"
+	);
+}
+
+#[test]
+pub fn apostrophes_at_word_start() {
+	assert_eq!(
+		punctuate("'tis the '90s, 'quoted'", QuoteStyle::English),
+		"’tis the ’90s, ‘quoted’"
+	);
+}
+
+#[test]
+pub fn dashes_and_ellipsis() {
+	assert_eq!(
+		punctuate("pages 1--2 --- wait...", QuoteStyle::English),
+		"pages 1–2 — wait…"
+	);
+}
+
+#[test]
+pub fn across_tags() {
+	assert_eq!(
+		punctuate(
+			"(\"a\") \"[em:quoted]\" [em:\"inside\"] \"[code:x]\" and [code:\"code\"]",
+			QuoteStyle::English
+		),
+		"(“a”) “quoted” “inside” “x” and \"code\""
+	);
+}
+
+#[test]
+pub fn urls() {
+	assert_eq!(
+		punctuate(
+			"see \"https://example.com/a--b...c\" -- done",
+			QuoteStyle::English
+		),
+		"see “https://example.com/a--b...c” – done"
+	);
+}
+
+#[test]
+pub fn styles() {
+	assert_eq!(punctuate("\"a 'b' c\"", QuoteStyle::German), "„a ‚b‘ c“");
+	assert_eq!(
+		punctuate("\"a\" and \" b \"", QuoteStyle::French),
+		"«\u{202F}a\u{202F}» and «\u{202F} b \u{202F}»"
+	);
+}
+
+#[test]
+pub fn spacing() {
+	assert_eq!(
+		space(
+			"Quoi ? Vraiment! Il est 12:30 : « bonjour »; «salut»",
+			SpacingStyle::French
+		),
+		"Quoi\u{202F}? Vraiment\u{202F}! Il est 12:30\u{A0}: «\u{202F}bonjour\u{202F}»\u{202F}; «\u{202F}salut\u{202F}»"
+	);
+	assert_eq!(
+		space("Quoi ? Il est : « oui »", SpacingStyle::CanadianFrench),
+		"Quoi ? Il est\u{A0}: «\u{A0}oui\u{A0}»"
+	);
+}
+
+#[test]
+pub fn spacing_across_tags() {
+	assert_eq!(
+		space(
+			"[em:toi]? [code:x]! [em:moi ]? voir https://example.com?a=b:c ?",
+			SpacingStyle::French
+		),
+		"toi\u{202F}? x\u{202F}! moi ? voir https://example.com?a=b:c\u{202F}?"
+	);
+}
+
+#[test]
+pub fn spacing_after_smart_punctuation() {
+	assert_eq!(
+		text("\"oui\" ?", |doll, ast| {
+			smart_punctuation(doll, ast, QuoteStyle::French);
+			typographic_spacing(doll, ast, SpacingStyle::French);
+		}),
+		"«\u{202F}oui\u{202F}»\u{202F}?"
+	);
+}
+
+#[test]
+pub fn skips() {
+	assert!(skip_typography("title: x\ntypography: off"));
+	assert!(!skip_typography("typography: on"));
+}
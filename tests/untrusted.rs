@@ -0,0 +1,284 @@
+use {
+	markdoll::{emit::HtmlEmit, ext::TagDefinition, tree::TagContent, MarkDoll},
+	std::sync::Arc,
+};
+
+/// parse and emit a document as a forum would, returning whether it emitted, the output, and the codes of the diagnostics
+fn render(doll: &mut MarkDoll, to: &mut HtmlEmit, src: &str) -> (bool, String, Vec<&'static str>) {
+	let ast = match doll.parse_document(src) {
+		Ok((_, ast)) | Err((_, ast)) => ast,
+	};
+	let mut ast = ast;
+
+	let emitted = doll.emit(&mut ast, to);
+
+	(
+		emitted,
+		core::mem::take(&mut to.write),
+		doll.finish().iter().map(|diag| diag.code).collect(),
+	)
+}
+
+/// the values of every url attribute in `html`, unescaped enough to see their scheme
+fn urls(html: &str) -> Vec<String> {
+	let mut urls = Vec::new();
+
+	for attr in [" href='", " src='", " cite='", " poster='"] {
+		for (start, _) in html.match_indices(attr) {
+			let value = &html[start + attr.len()..];
+			let value = &value[..value.find('\'').unwrap()];
+			urls.push(value.replace("&#x3A;", ":").replace("&colon;", ":"));
+		}
+	}
+
+	urls
+}
+
+/// the names of the attributes of every element in `html`
+fn attributes(html: &str) -> Vec<&str> {
+	let mut names = Vec::new();
+
+	for element in html.split('<').skip(1) {
+		let mut rest = &element[..element.find('>').unwrap()];
+		rest = rest.trim_start_matches(|ch: char| !ch.is_ascii_whitespace());
+
+		loop {
+			rest = rest.trim_start();
+			let end = rest.find(['=', ' ', '/']).unwrap_or(rest.len());
+			if end == 0 {
+				break;
+			}
+			names.push(&rest[..end]);
+			rest = &rest[end..];
+
+			if let Some(value) = rest.strip_prefix("='") {
+				rest = &value[value.find('\'').unwrap() + 1..];
+			}
+		}
+	}
+
+	names
+}
+
+#[test]
+pub fn injection_through_every_argument() {
+	const PAYLOADS: &[&str] = &[
+		"javascript:alert`1`",
+		" JaVaScRiPt:alert`1`",
+		"java\u{0}script:alert`1`",
+		"data:text/html,<script>alert`1`</script>",
+		"vbscript:msgbox",
+		"'onmouseover='alert`1`",
+		"\"><script>alert`1`</script>",
+		"</a><img src=x onerror=alert`1`>",
+	];
+
+	let mut doll = MarkDoll::untrusted();
+	let tags = doll
+		.ext_system
+		.tags()
+		.map(|tag| tag.key)
+		.collect::<Vec<_>>();
+
+	for tag in tags {
+		for payload in PAYLOADS {
+			for src in [
+				format!("[{tag}({payload})]"),
+				format!("[{tag}({payload}):{payload}]"),
+				format!("[{tag}({payload})({payload}):{payload}]"),
+				format!("[{tag}(src={payload})(url={payload})(title={payload}):{payload}]"),
+				format!("[{tag}({payload})::\n\t{payload}\n]"),
+			] {
+				let (_, html, _) = render(&mut doll, &mut HtmlEmit::untrusted_profile(), &src);
+
+				assert!(!html.contains("<script"), "{src:?} wrote {html}");
+				assert!(!html.contains("<img src=x"), "{src:?} wrote {html}");
+				assert!(
+					!attributes(&html).iter().any(|name| name.starts_with("on")),
+					"{src:?} wrote {html}"
+				);
+
+				for url in urls(&html) {
+					let scheme = url
+						.chars()
+						.filter(|ch| !ch.is_ascii_whitespace() && !ch.is_control())
+						.collect::<String>()
+						.to_ascii_lowercase();
+					assert!(
+						!["javascript:", "data:", "vbscript:"]
+							.iter()
+							.any(|bad| scheme.starts_with(bad)),
+						"{src:?} wrote {html}"
+					);
+				}
+			}
+		}
+	}
+}
+
+#[test]
+pub fn url_schemes() {
+	let mut doll = MarkDoll::untrusted();
+
+	let (emitted, html, codes) = render(
+		&mut doll,
+		&mut HtmlEmit::untrusted_profile(),
+		"[link(javascript:alert`1`):a] [link(https://example.com):b] [link(#top):c] [link(../d):d] [link(mailto:e@example.com):e]",
+	);
+	assert!(!emitted);
+	assert_eq!(codes, ["url scheme not allowed"]);
+	assert_eq!(
+		urls(&html),
+		[
+			"#",
+			"https:&#x2F;&#x2F;example.com",
+			"#top",
+			"..&#x2F;d",
+			"mailto:e@example.com"
+		]
+	);
+
+	// images through a proxy, but not those that aren't allowed
+	let mut to = HtmlEmit::untrusted_profile();
+	to.image_proxy = Some(Arc::new(|src| format!("https://proxy.example/?u={src}")));
+	let (_, html, _) = render(
+		&mut doll,
+		&mut to,
+		"[img(logo.svg):logo] [img(javascript:alert`1`):x]",
+	);
+	assert_eq!(
+		urls(&html),
+		["https:&#x2F;&#x2F;proxy.example&#x2F;?u=logo.svg", "#"]
+	);
+
+	// and written as is otherwise
+	let mut to = HtmlEmit::untrusted_profile();
+	to.safe_urls = false;
+	let (_, html, codes) = render(&mut doll, &mut to, "[link(javascript:x):a]");
+	assert_eq!(urls(&html), ["javascript:x"]);
+	assert!(codes.is_empty());
+}
+
+fn raw() -> TagDefinition {
+	TagDefinition::new(
+		"raw",
+		Some(|_, _, text, _| Some(Box::new(text.to_string()))),
+	)
+	.with_passthrough()
+	.with_emitter::<HtmlEmit>(|_, to, content: &mut Box<dyn TagContent>| {
+		to.write.push_str(content.downcast_ref::<String>().unwrap());
+	})
+}
+
+#[test]
+pub fn refuses_passthrough_tags() {
+	let mut doll = MarkDoll::untrusted();
+	doll.ext_system.add_tag(raw());
+
+	let (emitted, html, codes) = render(
+		&mut doll,
+		&mut HtmlEmit::untrusted_profile(),
+		"a [raw:<script>alert`1`</script>] b",
+	);
+	assert!(emitted);
+	assert!(!html.contains("<script"), "{html}");
+	assert_eq!(codes, ["tag not allowed in untrusted documents"]);
+
+	// which trusted documents can use
+	let mut doll = MarkDoll::new();
+	doll.ext_system.add_tag(raw());
+	doll.set_emitters(markdoll::emit::BuiltInEmitters::<HtmlEmit>::default());
+	let (_, html, _) = render(
+		&mut doll,
+		&mut HtmlEmit::untrusted_profile(),
+		"[raw:<b>b</b>]",
+	);
+	assert!(html.contains("<b>b</b>"));
+}
+
+#[test]
+pub fn gigantic_nesting() {
+	let mut doll = MarkDoll::untrusted();
+
+	let depth = 100_000;
+	let src = format!("{}x{}", "[em:".repeat(depth), "]".repeat(depth));
+	let (_, html, codes) = render(&mut doll, &mut HtmlEmit::untrusted_profile(), &src);
+	assert!(codes.contains(&"tags nested too deeply"), "{codes:?}");
+	assert!(html.matches("<em>").count() <= 32);
+
+	let src = format!("{}x{}", "[quote::\n".repeat(200), "]\n".repeat(200));
+	let (_, _, codes) = render(&mut doll, &mut HtmlEmit::untrusted_profile(), &src);
+	assert!(!codes.is_empty());
+}
+
+#[test]
+pub fn output_is_capped() {
+	let mut doll = MarkDoll::untrusted();
+
+	let mut to = HtmlEmit::untrusted_profile();
+	to.max_output_bytes = 64 << 10;
+
+	let src = format!("[em(b):{}] ", "a".repeat(1000)).repeat(900);
+	let (emitted, html, codes) = render(&mut doll, &mut to, &src);
+	assert!(!emitted);
+	assert_eq!(codes, ["output too large"]);
+	assert!(html.len() < (64 << 10) + 2048, "{}", html.len());
+	assert!(html.ends_with("</div>"));
+}
+
+#[test]
+pub fn limits_are_locked() {
+	let mut doll = MarkDoll::untrusted();
+	doll.max_tag_depth = usize::MAX;
+	doll.max_source_bytes = 1024;
+	doll.severity_overrides
+		.insert("tag not defined".to_string(), None);
+	doll.severity_overrides
+		.insert("url scheme not allowed".to_string(), Some(false));
+	doll.severity_overrides
+		.insert("image has no alt text".to_string(), None);
+
+	assert!(doll.parse_document("[nope] [img(a.png)]").is_err());
+	assert_eq!(doll.max_tag_depth, 32);
+	// lower limits are kept
+	assert_eq!(doll.max_source_bytes, 1024);
+
+	let codes = doll
+		.finish()
+		.iter()
+		.map(|diag| (diag.code, diag.err))
+		.collect::<Vec<_>>();
+	// warnings can still be suppressed
+	assert_eq!(codes, [("tag not defined", true)]);
+	assert_eq!(doll.suppressed, 0);
+
+	let (emitted, _, codes) = render(
+		&mut doll,
+		&mut HtmlEmit::untrusted_profile(),
+		"[link(javascript:x):a]",
+	);
+	assert!(!emitted);
+	assert_eq!(codes, ["url scheme not allowed"]);
+}
+
+#[test]
+pub fn huge_table_spans() {
+	let mut doll = MarkDoll::untrusted();
+
+	for span in ["4000000000", "18446744073709551615"] {
+		let src = format!(
+			"[table::\n\t[tr::\n\t\t[tc(cols={span})(rows={span}):a]\n\t]\n\t[tr::\n\t\t[tc:b]\n\t]\n]"
+		);
+		let (emitted, html, codes) = render(&mut doll, &mut HtmlEmit::untrusted_profile(), &src);
+		assert!(emitted);
+		assert!(html.contains("colspan='1000'"), "{html}");
+		assert!(html.contains("rowspan='65534'"), "{html}");
+		assert_eq!(
+			codes,
+			[
+				"row has an inconsistent amount of columns",
+				"rowspan extends past the last row"
+			]
+		);
+	}
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use markdoll::ext;
+
+#[test]
+pub fn counts_per_document_and_total() {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+
+	doll.parse_document("[em:a] [em:[sub:b]] [tabel:c]")
+		.unwrap_err();
+	doll.finish();
+
+	assert_eq!(doll.tag_usage().get("em"), Some(&2));
+	assert_eq!(doll.tag_usage().get("sub"), Some(&1));
+	assert_eq!(doll.tag_usage().get("tabel"), Some(&1));
+
+	doll.parse_document("[sup:a] [em:b]").unwrap();
+	doll.finish();
+
+	assert_eq!(doll.tag_usage().len(), 2);
+	assert_eq!(doll.tag_usage().get("em"), Some(&1));
+	assert_eq!(doll.total_tag_usage().get("em"), Some(&3));
+	assert_eq!(doll.total_tag_usage().get("tabel"), Some(&1));
+
+	// every formatting tag but those used
+	let mut unused = ext::formatting::tags()
+		.into_iter()
+		.map(|tag| tag.key)
+		.filter(|key| !["em", "sub", "sup"].contains(key))
+		.collect::<Vec<_>>();
+	unused.sort_unstable();
+	assert!(unused.contains(&"quote"));
+	assert_eq!(doll.unused_tags(), unused);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use {
+	hashbrown::HashMap,
+	markdoll::{emit::HtmlEmit, ext},
+};
+
+fn html(src: &str, vars: &[(&str, &str)]) -> (String, Vec<&'static str>) {
+	let mut doll = common::doll_with(&[&ext::var::tags()]);
+
+	let (frontmatter, mut ast) = doll.parse_document(src).unwrap();
+
+	let mut vars = vars
+		.iter()
+		.map(|(key, value)| (key.to_string(), value.to_string()))
+		.collect();
+	if let Some(frontmatter) = &frontmatter {
+		ext::var::frontmatter_vars(&mut vars, frontmatter);
+	}
+
+	let mut out = HtmlEmit {
+		vars,
+		..Default::default()
+	};
+	assert!(doll.emit(&mut ast, &mut out));
+
+	let warnings = doll
+		.finish()
+		.into_iter()
+		.map(|diagnostic| {
+			assert!(!diagnostic.err);
+			diagnostic.code
+		})
+		.collect();
+
+	(out.write, warnings)
+}
+
+#[test]
+pub fn substitutes_escaped() {
+	let (out, warnings) = html("version [var(version)]", &[("version", "<1.2>")]);
+
+	assert!(warnings.is_empty());
+	assert_eq!(out, "<div>version &lt;1.2&gt;</div>");
+}
+
+#[test]
+pub fn unknown_is_visible() {
+	let (out, warnings) = html("version [var(version)]", &[]);
+
+	assert_eq!(warnings, ["variable not defined"]);
+	assert_eq!(out, "<div>version [version]</div>");
+}
+
+#[test]
+pub fn frontmatter_is_overridden() {
+	let (out, warnings) = html(
+		"---\nname: markdoll\nversion: 1.0\n# comment: ignored\n---\n[var(name)] [var(version)]",
+		&[("version", "2.0")],
+	);
+
+	assert!(warnings.is_empty());
+	assert_eq!(out, "<div>markdoll 2.0</div>");
+}
+
+#[test]
+pub fn frontmatter_lines() {
+	let mut vars = HashMap::new();
+	ext::var::frontmatter_vars(
+		&mut vars,
+		"a: 1\n  b :  two: three \nnot a variable\n: empty\n#c: 4",
+	);
+
+	assert_eq!(vars.len(), 2);
+	assert_eq!(vars["a"], "1");
+	assert_eq!(vars["b"], "two: three");
+}
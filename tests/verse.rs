@@ -0,0 +1,101 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext};
+
+fn html(src: &str, hard_breaks: bool) -> String {
+	let mut doll = common::doll_with(&[&ext::formatting::tags()]);
+	doll.hard_breaks = hard_breaks;
+
+	let mut ast = doll.parse_document(src).unwrap().1;
+
+	// the frontmatter and `verse` tags only enable it while parsing
+	assert_eq!(doll.hard_breaks, hard_breaks);
+
+	let mut out = HtmlEmit::default();
+	assert!(doll.emit(&mut ast, &mut out));
+	assert!(doll.finish().is_empty());
+
+	out.write
+}
+
+#[test]
+pub fn hard_breaks() {
+	assert_eq!(html("one\ntwo", false), "<div>one two</div>");
+	assert_eq!(html("one\ntwo", true), "<div>one<br />two</div>");
+	assert_eq!(
+		html("one\ntwo\n\nthree\nfour", true),
+		"<div>one<br />two</div><div>three<br />four</div>"
+	);
+	assert_eq!(
+		html("one [em:two]\nthree", true),
+		"<div>one <em><span>two</span></em><br />three</div>"
+	);
+	assert_eq!(
+		html("&section\n\tone\n\ttwo", true),
+		"<section data-level='1'><h1>section</h1><div><div>one<br />two</div></div></section>"
+	);
+}
+
+#[test]
+pub fn trailing_breaks() {
+	// the break ending a paragraph is dropped like the space would be
+	assert_eq!(html("one\ntwo\n", true), "<div>one<br />two</div>");
+	assert_eq!(html("one\ntwo\n\n\n", true), "<div>one<br />two</div>");
+	assert_eq!(
+		html("one [em:two]\n", true),
+		"<div>one <em><span>two</span></em></div>"
+	);
+	assert_eq!(
+		html("one\n-\ttwo\n\tthree\n", true),
+		"<div>one</div><ul><li><div>two<br />three</div></li></ul>"
+	);
+	// escaped newlines are breaks either way
+	assert_eq!(html("one\\\ntwo\n", true), "<div>one<br />two</div>");
+
+	// and are kept at the end of a paragraph, where they were asked for
+	assert_eq!(
+		html("line one\\\n\nline two", false),
+		"<div>line one<br /></div><div>line two</div>"
+	);
+	assert_eq!(
+		html("line one\n\nline two", false),
+		"<div>line one</div><div>line two</div>"
+	);
+	assert_eq!(html("one\\\n", true), "<div>one<br /></div>");
+	assert_eq!(
+		html("-\tone\\\n-\ttwo", false),
+		"<ul><li><div>one<br /></div></li><li><div>two</div></li></ul>"
+	);
+}
+
+#[test]
+pub fn frontmatter() {
+	assert_eq!(
+		html("---\nbreaks: hard\n---\none\ntwo", false),
+		"<div>one<br />two</div>"
+	);
+	assert_eq!(
+		html("---\nbreaks: soft\n---\none\ntwo", false),
+		"<div>one two</div>"
+	);
+	// tag content follows the document
+	assert_eq!(
+		html("---\nbreaks: hard\n---\n[em::\n\tone\n\ttwo\n]", false),
+		"<div><em><span>one<br />two</span></em></div>"
+	);
+}
+
+#[test]
+pub fn verse() {
+	assert_eq!(
+		html(
+			"before\nit\n[verse::\n\tone\n\ttwo [em:three]\n\tfour\n\n\tfive\n]\n\nafter\nit",
+			false
+		),
+		"<div>before it <div class='doll-verse'><div>one<br />two <em><span>three</span></em><br />four</div><div>five</div></div></div><div>after it</div>"
+	);
+	assert_eq!(
+		html("[verse:one]", false),
+		"<div><div class='doll-verse'><div>one</div></div></div>"
+	);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use markdoll::{
+	ext::{self, links::Link},
+	tree::{
+		visit::{self, Visitor, VisitorMut},
+		InlineItem, TagInvocation,
+	},
+	MarkDoll,
+};
+
+const SRC: &str = "[link(a):a]
+
+[table::
+	-	-	[em:[link(b):b]]
+		-	[quote:[link(c):c]]
+]
+
+&section
+	-	[link(d):d]";
+
+#[test]
+pub fn visits_tag_children() {
+	struct Hrefs(Vec<String>);
+
+	impl Visitor for Hrefs {
+		fn visit_tag(&mut self, doll: &MarkDoll, pos: usize, tag: &TagInvocation) {
+			if let Some(link) = tag.content.downcast_ref::<Link>() {
+				self.0.push(link.href.clone());
+			}
+
+			visit::walk_tag(self, doll, pos, tag);
+		}
+	}
+
+	let mut doll = common::doll_with(&[
+		&ext::formatting::tags(),
+		&ext::links::tags(),
+		&ext::table::tags(),
+	]);
+	let ast = doll.parse_document(SRC).unwrap().1;
+
+	let mut hrefs = Hrefs(Vec::new());
+	visit::walk(&ast, &doll, &mut hrefs);
+
+	assert_eq!(hrefs.0, ["a", "b", "c", "d"]);
+}
+
+#[test]
+pub fn visits_mutably() {
+	struct Shout;
+
+	impl VisitorMut for Shout {
+		fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &mut InlineItem) {
+			if let InlineItem::Text(text) = item {
+				*text = text.to_uppercase();
+			}
+
+			visit::walk_inline_mut(self, doll, pos, item);
+		}
+	}
+
+	struct Text(String);
+
+	impl Visitor for Text {
+		fn visit_inline(&mut self, doll: &MarkDoll, pos: usize, item: &InlineItem) {
+			if let InlineItem::Text(text) = item {
+				self.0.push_str(text);
+			}
+
+			visit::walk_inline(self, doll, pos, item);
+		}
+	}
+
+	let mut doll = common::doll_with(&[
+		&ext::formatting::tags(),
+		&ext::links::tags(),
+		&ext::table::tags(),
+	]);
+	let mut ast = doll.parse_document(SRC).unwrap().1;
+
+	visit::walk_mut(&mut ast, &doll, &mut Shout);
+
+	let mut text = Text(String::new());
+	visit::walk(&ast, &doll, &mut text);
+
+	assert_eq!(text.0, "ABCD");
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use markdoll::{emit::HtmlEmit, ext};
+
+const SRC: &str = "---
+breaks: hard
+---
+a
+b [img(a.png):a]
+
+[tabs:[tab(x):y]]
+
+[audio(a.mp3):no audio]";
+
+fn html(xml_style: bool) -> String {
+	let (out, warnings) = common::html_to(
+		&mut common::doll_with(&[&ext::links::tags(), &ext::media::tags(), &ext::tabs::tags()]),
+		SRC,
+		HtmlEmit {
+			xml_style,
+			..Default::default()
+		},
+	);
+	assert!(warnings.is_empty());
+
+	out
+}
+
+/// the void elements of the output, as written
+fn voids(html: &str) -> Vec<&str> {
+	html.split('<')
+		.filter(|tag| {
+			["br", "img", "input", "source"]
+				.iter()
+				.any(|name| tag.starts_with(name))
+		})
+		.map(|tag| &tag[..=tag.find('>').unwrap()])
+		.collect()
+}
+
+#[test]
+pub fn styles() {
+	assert_eq!(
+		voids(&html(true)),
+		[
+			"br />",
+			"img src='a.png' alt='a' />",
+			"input type='radio' class='doll-tab-input' name='doll-tabs-1' id='doll-tabs-1-1' checked />",
+			"source src='a.mp3' type='audio/mpeg' />",
+		]
+	);
+	assert_eq!(
+		voids(&html(false)),
+		[
+			"br>",
+			"img src='a.png' alt='a'>",
+			"input type='radio' class='doll-tab-input' name='doll-tabs-1' id='doll-tabs-1-1' checked>",
+			"source src='a.mp3' type='audio/mpeg'>",
+		]
+	);
+
+	// and nothing else differs
+	assert_eq!(html(true).replace(" />", ">"), html(false));
+}
@@ -0,0 +1,27 @@
+//! run with `wasm-pack test --node --features wasm`
+#![cfg(target_arch = "wasm32")]
+
+use {markdoll::ffi::convert_html, wasm_bindgen_test::wasm_bindgen_test};
+
+#[wasm_bindgen_test]
+fn converts() {
+	let converted = convert_html("hello [em:world]", 0);
+
+	assert!(converted.ok);
+	assert_eq!(
+		converted.html,
+		"<div>hello <em><span>world</span></em></div>"
+	);
+	assert_eq!(converted.diagnostics, "[]");
+}
+
+#[wasm_bindgen_test]
+fn fixture() {
+	let converted = convert_html(include_str!("fixtures/table.doll"), 0);
+
+	assert!(converted.ok);
+	assert_eq!(
+		converted.html,
+		include_str!("fixtures/snapshots/table.doll.html").trim_end()
+	);
+}
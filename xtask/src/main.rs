@@ -0,0 +1,232 @@
+//! `cargo xtask bench-compare`, running the benchmarks and comparing them against a baseline
+//!
+//! ```text
+//! cargo xtask bench-compare [--skip-run] [--threshold PERCENT] [--save PATH] [BASELINE]
+//! ```
+//!
+//! - `--skip-run` reads the results of the last `cargo bench` instead of running it
+//! - `--threshold PERCENT` is how much slower a benchmark may get before failing, 10 by default
+//! - `--save PATH` writes the results as a baseline
+//! - `BASELINE` is a baseline to compare against, failing if any benchmark regressed past the threshold
+
+use {
+	serde_json::{Map, Number, Value},
+	std::{
+		collections::BTreeMap,
+		env, fs,
+		path::{Path, PathBuf},
+		process::{exit, Command},
+		time::SystemTime,
+	},
+};
+
+const USAGE: &str =
+	"usage: cargo xtask bench-compare [--skip-run] [--threshold PERCENT] [--save PATH] [BASELINE]";
+
+/// mean time of each benchmark in nanoseconds, by name
+type Results = BTreeMap<String, f64>;
+
+fn fail(msg: impl std::fmt::Display) -> ! {
+	eprintln!("{msg}");
+	exit(2);
+}
+
+fn read_json(path: &Path) -> Value {
+	let src =
+		fs::read_to_string(path).unwrap_or_else(|err| fail(format!("{}: {err}", path.display())));
+	serde_json::from_str(&src).unwrap_or_else(|err| fail(format!("{}: {err}", path.display())))
+}
+
+/// the results criterion wrote into `dir`, skipping those from before `since`
+fn collect(dir: &Path, since: Option<SystemTime>) -> Results {
+	let mut results = Results::new();
+
+	let entries = fs::read_dir(dir).unwrap_or_else(|err| {
+		fail(format!(
+			"{}: {err}, run the benchmarks first",
+			dir.display()
+		))
+	});
+
+	for entry in entries.flatten() {
+		let new = entry.path().join("new");
+		let estimates = new.join("estimates.json");
+
+		let Ok(meta) = fs::metadata(&estimates) else {
+			// criterion's report directory, or a benchmark that never finished
+			continue;
+		};
+		// left over from a benchmark that no longer exists
+		if since.is_some_and(|since| meta.modified().is_ok_and(|modified| modified < since)) {
+			continue;
+		}
+
+		let title = read_json(&new.join("benchmark.json"))["title"]
+			.as_str()
+			.map_or_else(
+				|| entry.file_name().to_string_lossy().into_owned(),
+				ToString::to_string,
+			);
+		let Some(mean) = read_json(&estimates)["mean"]["point_estimate"].as_f64() else {
+			fail(format!("{}: no mean", estimates.display()));
+		};
+
+		results.insert(title, mean);
+	}
+
+	results
+}
+
+fn format_time(ns: f64) -> String {
+	if ns >= 1e9 {
+		format!("{:.2} s", ns / 1e9)
+	} else if ns >= 1e6 {
+		format!("{:.2} ms", ns / 1e6)
+	} else if ns >= 1e3 {
+		format!("{:.2} µs", ns / 1e3)
+	} else {
+		format!("{ns:.0} ns")
+	}
+}
+
+/// print how each benchmark changed, returning how many regressed past `threshold` percent
+fn compare(baseline: &Results, results: &Results, threshold: f64) -> usize {
+	let width = baseline
+		.keys()
+		.chain(results.keys())
+		.map(|name| name.chars().count())
+		.max()
+		.unwrap_or(0);
+
+	let mut regressed = 0;
+
+	for (name, &now) in results {
+		let Some(&before) = baseline.get(name) else {
+			println!("{name:width$}  {:>10}  new", format_time(now));
+			continue;
+		};
+
+		let change = (now - before) / before * 100.0;
+		let verdict = if change > threshold {
+			regressed += 1;
+			"REGRESSED"
+		} else if change < -threshold {
+			"improved"
+		} else {
+			""
+		};
+
+		let line = format!(
+			"{name:width$}  {:>10} -> {:>10}  {change:+6.1}%  {verdict}",
+			format_time(before),
+			format_time(now),
+		);
+		println!("{}", line.trim_end());
+	}
+
+	for name in baseline.keys().filter(|name| !results.contains_key(*name)) {
+		println!("{name:width$}  missing, not in these results");
+	}
+
+	regressed
+}
+
+fn bench_compare(mut args: impl Iterator<Item = String>) {
+	let mut skip_run = false;
+	let mut threshold = 10.0;
+	let mut save = None;
+	let mut baseline = None;
+
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--skip-run" => skip_run = true,
+			"--threshold" => {
+				threshold = args
+					.next()
+					.and_then(|value| value.parse::<f64>().ok())
+					.unwrap_or_else(|| fail("--threshold takes a percentage"));
+			}
+			"--save" => {
+				save = Some(PathBuf::from(
+					args.next().unwrap_or_else(|| fail("--save takes a path")),
+				));
+			}
+			_ if arg.starts_with('-') || baseline.is_some() => fail(USAGE),
+			_ => baseline = Some(PathBuf::from(arg)),
+		}
+	}
+
+	if save.is_none() && baseline.is_none() {
+		fail(USAGE);
+	}
+
+	// the repository, which the `cargo xtask` alias runs this from
+	let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+
+	// read first, so a mistyped path fails before the benchmarks take minutes
+	let baseline = baseline.map(|path| {
+		let Value::Object(map) = read_json(&path) else {
+			fail(format!("{}: not a baseline", path.display()));
+		};
+
+		map.into_iter()
+			.map(|(name, mean)| {
+				let mean = mean.as_f64().unwrap_or_else(|| {
+					fail(format!("{}: `{name}` isn't a number", path.display()))
+				});
+				(name, mean)
+			})
+			.collect::<Results>()
+	});
+
+	let since = if skip_run {
+		None
+	} else {
+		let started = SystemTime::now();
+		let status = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+			.arg("bench")
+			.current_dir(root)
+			.status()
+			.unwrap_or_else(|err| fail(format!("couldn't run `cargo bench`: {err}")));
+
+		if !status.success() {
+			fail("`cargo bench` failed");
+		}
+
+		Some(started)
+	};
+
+	let results = collect(&root.join("target/criterion"), since);
+	if results.is_empty() {
+		fail("no benchmark results");
+	}
+
+	if let Some(path) = save {
+		let map = results
+			.iter()
+			.map(|(name, &mean)| (name.clone(), Value::Number(Number::from_f64(mean).unwrap())))
+			.collect::<Map<_, _>>();
+
+		fs::write(&path, serde_json::to_string_pretty(&map).unwrap() + "\n")
+			.unwrap_or_else(|err| fail(format!("{}: {err}", path.display())));
+		println!("saved {} results to {}", results.len(), path.display());
+	}
+
+	if let Some(baseline) = baseline {
+		let regressed = compare(&baseline, &results, threshold);
+
+		if regressed > 0 {
+			eprintln!("{regressed} benchmarks regressed by more than {threshold}%");
+			exit(1);
+		}
+	}
+}
+
+fn main() {
+	let mut args = env::args().skip(1);
+
+	match args.next().as_deref() {
+		Some("bench-compare") => bench_compare(args),
+		_ => fail(USAGE),
+	}
+}